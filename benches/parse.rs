@@ -0,0 +1,29 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use openapiv3::OpenAPI;
+
+fn fixture(name: &str) -> String {
+    let input = std::fs::read_to_string(format!("{}/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap();
+    if name.ends_with(".yaml") {
+        // Benchmark JSON parsing only, which isn't dominated by the YAML scanner.
+        let value: serde_json::Value = serde_yaml::from_str(&input).unwrap();
+        serde_json::to_string(&value).unwrap()
+    } else {
+        input
+    }
+}
+
+/// Parsing into the model, against parsing the same input into a `serde_json::Value`
+/// as a lower bound.
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for name in ["stripe.yaml", "slack.json", "twilio.json", "quayio.json"] {
+        let input = fixture(name);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| b.iter(|| serde_json::from_str::<OpenAPI>(&input).unwrap()));
+        group.bench_function(format!("{} (Value)", name), |b| b.iter(|| serde_json::from_str::<serde_json::Value>(&input).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use crate::{LoadError, OpenAPI};
+
+/// Compact binary encodings, e.g. for caching parsed specs between runs.
+///
+/// The model relies on `#[serde(flatten)]` and untagged enums, which need a
+/// self-describing format: CBOR and MessagePack (written with field names) work,
+/// while formats like bincode or postcard cannot represent it.
+impl OpenAPI {
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn to_cbor(&self) -> Result<Vec<u8>, LoadError> {
+        let mut out = Vec::new();
+        ciborium::into_writer(self, &mut out).map_err(LoadError::CborEncode)?;
+        Ok(out)
+    }
+
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, LoadError> {
+        ciborium::from_reader(bytes).map_err(LoadError::CborDecode)
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, LoadError> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, LoadError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> Vec<(&'static str, OpenAPI)> {
+        [
+            ("quayio.json", include_str!("../fixtures/quayio.json")),
+            ("slack.json", include_str!("../fixtures/slack.json")),
+            ("twilio.json", include_str!("../fixtures/twilio.json")),
+            ("xkcd.json", include_str!("../fixtures/xkcd.json")),
+        ].iter().map(|(name, s)| (*name, serde_json::from_str(s).unwrap())).collect()
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_round_trip() {
+        for (name, spec) in fixtures() {
+            assert_eq!(OpenAPI::from_cbor(&spec.to_cbor().unwrap()).unwrap(), spec, "{}", name);
+        }
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_round_trip() {
+        for (name, spec) in fixtures() {
+            assert_eq!(OpenAPI::from_msgpack(&spec.to_msgpack().unwrap()).unwrap(), spec, "{}", name);
+        }
+    }
+}
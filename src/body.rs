@@ -0,0 +1,213 @@
+use serde_json::{Map, Value};
+
+use crate::example::declared_example;
+use crate::parameter_style::{is_json, property_schema, text, Style};
+use crate::xml::to_xml;
+use crate::*;
+
+/// What [MediaType::generate_body] sends for binary values: files in multipart
+/// bodies, or whole bodies of a non-text media type.
+pub const BINARY_PLACEHOLDER: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+const BOUNDARY: &str = "openapiv3-boundary";
+
+impl MediaType {
+    /// Encodes `value` as the body of this media type, `content_type`, which is
+    /// its key in the `content` map, returning the `Content-Type` to send it with
+    /// and the bytes. `application/x-www-form-urlencoded` bodies have their
+    /// properties serialized as the `style`, `explode` and `allowReserved` of their
+    /// [Encoding]s say, like query parameters. `multipart/form-data` ones get a part
+    /// per property, or per item of arrays, with the `contentType` of its encoding,
+    /// else the default for its schema, and the headers of the encoding that
+    /// declare an example; binary properties are sent as files. XML media types are
+    /// written as laid out by the schema's `xml` objects, JSON ones as JSON, and
+    /// other values as their text.
+    pub fn encode_body(&self, spec: &OpenAPI, content_type: &str, value: &Value) -> (String, Vec<u8>) {
+        self.encode(spec, content_type, value, false)
+    }
+
+    /// `placeholders` replaces the values of binary parts by [BINARY_PLACEHOLDER].
+    fn encode(&self, spec: &OpenAPI, content_type: &str, value: &Value, placeholders: bool) -> (String, Vec<u8>) {
+        let view = spec.resolved();
+        let schema = self.schema.as_ref().and_then(|schema| view.schema(schema).ok());
+        let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match (essence.as_str(), value) {
+            ("application/x-www-form-urlencoded", Value::Object(fields)) => {
+                (content_type.to_string(), self.urlencoded(spec, fields).into_bytes())
+            }
+            ("multipart/form-data", Value::Object(fields)) => {
+                let body = self.multipart(spec, schema, fields, placeholders);
+                (format!("{}; boundary={}", content_type, BOUNDARY), body)
+            }
+            (essence, value) if essence.ends_with("/xml") || essence.ends_with("+xml") => {
+                let name = match &self.schema {
+                    Some(RefOr::Reference { reference }) => reference.rsplit('/').next().unwrap_or("root"),
+                    _ => "root",
+                };
+                (content_type.to_string(), to_xml(spec, schema, name, value).into_bytes())
+            }
+            (_, Value::String(s)) if !is_json(content_type) => (content_type.to_string(), s.clone().into_bytes()),
+            _ => (content_type.to_string(), serde_json::to_vec(value).expect("values serialize")),
+        }
+    }
+
+    /// An example body of this media type, `content_type`, encoded as by
+    /// [MediaType::encode_body]: the declared example if `options` use those,
+    /// else one generated from the schema, with [BINARY_PLACEHOLDER] for binary
+    /// values. `None` without an example or a schema to generate one from.
+    pub fn generate_body(&self, spec: &OpenAPI, content_type: &str, options: &ExampleOptions) -> Option<(String, Vec<u8>)> {
+        let view = spec.resolved();
+        let schema = self.schema.as_ref().and_then(|schema| view.schema(schema).ok());
+        let declared = options.use_declared
+            .then(|| declared_example(spec, self.example.as_ref(), &self.examples, self.schema.as_ref()))
+            .flatten();
+        let value = match declared.clone() {
+            Some(value) => value,
+            None if schema.and_then(binary).is_some() => return Some((content_type.to_string(), BINARY_PLACEHOLDER.to_vec())),
+            None => schema?.generate_example(spec, options),
+        };
+        Some(self.encode(spec, content_type, &value, declared.is_none()))
+    }
+
+    fn urlencoded(&self, spec: &OpenAPI, fields: &Map<String, Value>) -> String {
+        let pairs = fields.iter().map(|(name, value)| {
+            let part = self.part_encoding(spec, name);
+            let style = match part.style {
+                QueryStyle::Form => Style::Form,
+                QueryStyle::SpaceDelimited => Style::SpaceDelimited,
+                QueryStyle::PipeDelimited => Style::PipeDelimited,
+                QueryStyle::DeepObject => Style::DeepObject,
+            };
+            style.serialize(name, value, part.explode, &|s| percent_encode(s, part.allow_reserved))
+        });
+        pairs.collect::<Vec<_>>().join("&")
+    }
+
+    fn multipart(&self, spec: &OpenAPI, schema: Option<&Schema>, fields: &Map<String, Value>, placeholders: bool) -> Vec<u8> {
+        let view = spec.resolved();
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            let property = schema.and_then(|schema| property_schema(view, schema, name));
+            let (items, item_schema) = match (value, property) {
+                (Value::Array(items), Some(property)) => (items.iter().collect(), array_items(view, property)),
+                (Value::Array(items), None) => (items.iter().collect(), None),
+                (value, property) => (vec![value], property),
+            };
+            let encoding = self.encoding.get(name);
+            let part = self.part_encoding(spec, name);
+            for item in items {
+                let binary = item_schema.and_then(binary);
+                // Without a schema, the default follows the value.
+                let content_type = match (encoding.and_then(Encoding::declared_content_type), item_schema) {
+                    (Some(content_type), _) => content_type.to_string(),
+                    (None, Some(schema)) => default_part_content_type(spec, Some(schema)),
+                    (None, None) if item.is_object() || item.is_array() => "application/json".to_string(),
+                    (None, None) => "text/plain".to_string(),
+                };
+                body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"", BOUNDARY, name).as_bytes());
+                if binary.is_some() {
+                    body.extend_from_slice(format!("; filename=\"{}\"", name).as_bytes());
+                }
+                body.extend_from_slice(b"\r\n");
+                if content_type != "text/plain" {
+                    body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+                }
+                for (header, declared) in &part.headers {
+                    let schema = match &declared.format {
+                        ParameterSchemaOrContent::Schema(schema) => Some(schema),
+                        ParameterSchemaOrContent::Content(_) => None,
+                    };
+                    if let Some(example) = declared_example(spec, declared.example.as_ref(), &declared.examples, schema) {
+                        body.extend_from_slice(format!("{}: {}\r\n", header, text(&example)).as_bytes());
+                    }
+                }
+                body.extend_from_slice(b"\r\n");
+                match item {
+                    _ if binary.is_some() && placeholders => body.extend_from_slice(BINARY_PLACEHOLDER),
+                    Value::String(s) => body.extend_from_slice(s.as_bytes()),
+                    Value::Object(_) | Value::Array(_) => body.extend_from_slice(&serde_json::to_vec(item).expect("values serialize")),
+                    item => body.extend_from_slice(text(item).as_bytes()),
+                }
+                body.extend_from_slice(b"\r\n");
+            }
+        }
+        body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+        body
+    }
+}
+
+/// The media type of binary values of `schema`: `application/octet-stream` for
+/// `format: binary`, else its `contentMediaType`.
+pub(crate) fn binary(schema: &Schema) -> Option<&str> {
+    let (format, encoding, media_type) = match &schema.kind {
+        SchemaKind::Type(Type::String(s)) => (s.format.as_str(), s.content_encoding.as_deref(), s.content_media_type.as_deref()),
+        SchemaKind::Any(any) if any.typ.as_deref() == Some("string") => {
+            (any.format.as_deref().unwrap_or(""), any.content_encoding.as_deref(), any.content_media_type.as_deref())
+        }
+        _ => return None,
+    };
+    match (format, encoding, media_type) {
+        ("binary", _, media_type) => Some(media_type.unwrap_or("application/octet-stream")),
+        (_, None, Some(media_type)) => Some(media_type),
+        _ => None,
+    }
+}
+
+pub(crate) fn array_items<'a>(view: ResolvedSpec<'a>, schema: &'a Schema) -> Option<&'a Schema> {
+    match &schema.kind {
+        SchemaKind::Type(Type::Array(array)) => view.schema(array.items.as_deref()?).ok(),
+        SchemaKind::Any(any) => view.schema(any.items.as_deref()?).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_generate_body() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {"Upload": {
+                "type": "object",
+                "properties": {
+                    "title": {"type": "string", "example": "a b&c"},
+                    "meta": {"type": "object", "properties": {"size": {"type": "integer"}}},
+                    "files": {"type": "array", "items": {"type": "string", "format": "binary"}},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                }
+            }}}
+        })).unwrap();
+        let options = ExampleOptions::default();
+        let media = |encoding: Value| -> MediaType {
+            serde_json::from_value(json!({"schema": {"$ref": "#/components/schemas/Upload"}, "encoding": encoding})).unwrap()
+        };
+
+        let form = media(json!({"tags": {"style": "pipeDelimited"}}));
+        let (content_type, body) = form.generate_body(&spec, "application/x-www-form-urlencoded", &options).unwrap();
+        assert_eq!(content_type, "application/x-www-form-urlencoded");
+        assert_eq!(String::from_utf8(body).unwrap(), "title=a%20b%26c&size=0&files=string&tags=string");
+
+        let multipart = media(json!({
+            "files": {"contentType": "image/png, image/*", "headers": {"X-Rate": {"schema": {"type": "integer"}, "example": 3}}}
+        }));
+        let (content_type, body) = multipart.generate_body(&spec, "multipart/form-data", &options).unwrap();
+        assert_eq!(content_type, "multipart/form-data; boundary=openapiv3-boundary");
+        let mut expected = b"--openapiv3-boundary\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\na b&c\r\n\
+            --openapiv3-boundary\r\nContent-Disposition: form-data; name=\"meta\"\r\nContent-Type: application/json\r\n\r\n{\"size\":0}\r\n\
+            --openapiv3-boundary\r\nContent-Disposition: form-data; name=\"files\"; filename=\"files\"\r\nContent-Type: image/png\r\nX-Rate: 3\r\n\r\n".to_vec();
+        expected.extend_from_slice(BINARY_PLACEHOLDER);
+        expected.extend_from_slice(b"\r\n--openapiv3-boundary\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nstring\r\n--openapiv3-boundary--\r\n");
+        assert_eq!(String::from_utf8_lossy(&body), String::from_utf8_lossy(&expected));
+
+        let (_, xml) = form.generate_body(&spec, "application/xml", &options).unwrap();
+        assert!(String::from_utf8(xml).unwrap().contains("<Upload>\n  <title>a b&amp;c</title>"));
+        let file: MediaType = serde_json::from_value(json!({"schema": {"type": "string", "format": "binary"}})).unwrap();
+        assert_eq!(file.generate_body(&spec, "image/png", &options), Some(("image/png".to_string(), BINARY_PLACEHOLDER.to_vec())));
+    }
+}
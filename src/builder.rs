@@ -0,0 +1,393 @@
+use http::Method;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::*;
+
+/// Builds an [OpenAPI] document, e.g.
+///
+/// ```
+/// # use openapiv3::*;
+/// let spec = OpenAPIBuilder::new("Pets", "1.0")
+///     .server("https://api.example.com")
+///     .schema("Pet", SchemaBuilder::object().required_property("name", Schema::new_string()))
+///     .operation("/pets", http::Method::GET, OperationBuilder::new("listPets")
+///         .tag("pets")
+///         .query_param("limit", Schema::new_integer())
+///         .json_response(200, Schema::new_array(RefOr::schema_ref("Pet"))))
+///     .build();
+/// assert_eq!(spec.tags[0].name, "pets");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OpenAPIBuilder {
+    spec: OpenAPI,
+}
+
+impl OpenAPIBuilder {
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        let mut spec = OpenAPI::default();
+        spec.info.title = title.into();
+        spec.info.version = version.into();
+        Self { spec }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.spec.info.description = Some(description.into());
+        self
+    }
+
+    pub fn server(mut self, url: impl Into<String>) -> Self {
+        self.spec.servers.push(Server { url: url.into(), ..Server::default() });
+        self
+    }
+
+    /// Declares a tag with a description. Tags operations use are declared by
+    /// [OpenAPIBuilder::build] anyway.
+    pub fn tag(mut self, name: impl Into<String>, description: impl Into<String>) -> Self {
+        self.spec.tags.push(Tag { name: name.into(), description: Some(description.into()), ..Tag::default() });
+        self
+    }
+
+    /// Adds a component schema, for reference with [RefOr::schema_ref].
+    pub fn schema(mut self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.spec.schemas.insert(name.into(), schema.into());
+        self
+    }
+
+    pub fn security_scheme(mut self, name: impl Into<String>, scheme: SecurityScheme) -> Self {
+        self.spec.security_schemes.insert(name.into(), RefOr::Item(scheme));
+        self
+    }
+
+    /// Requires `scheme` with `scopes` for every operation that doesn't declare its own.
+    pub fn security<S: Into<String>>(mut self, scheme: impl Into<String>, scopes: impl IntoIterator<Item=S>) -> Self {
+        self.spec.security.push(IndexMap::from([(scheme.into(), scopes.into_iter().map(Into::into).collect())]));
+        self
+    }
+
+    /// Adds the operation for `method` on `path`, replacing any there already.
+    pub fn operation(mut self, path: impl Into<String>, method: Method, operation: impl Into<Operation>) -> Self {
+        // The builder only adds path items inline, so this can't fail.
+        let _ = self.spec.paths.insert_operation(path.into(), method, operation.into());
+        self
+    }
+
+    /// The document, with the tags its operations use declared after those
+    /// declared with [OpenAPIBuilder::tag].
+    pub fn build(self) -> OpenAPI {
+        let mut spec = self.spec;
+        let used: Vec<String> = spec.operations().flat_map(|(_, _, operation, _)| operation.tags.iter().cloned()).collect();
+        for name in used {
+            if !spec.tags.iter().any(|tag| tag.name == name) {
+                spec.tags.push(Tag { name, ..Tag::default() });
+            }
+        }
+        spec
+    }
+}
+
+impl From<OpenAPIBuilder> for OpenAPI {
+    fn from(builder: OpenAPIBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builds an [Operation], for [OpenAPIBuilder::operation].
+#[derive(Debug, Clone, Default)]
+pub struct OperationBuilder {
+    operation: Operation,
+}
+
+impl OperationBuilder {
+    pub fn new(operation_id: impl Into<String>) -> Self {
+        Self { operation: Operation { operation_id: Some(operation_id.into()), ..Operation::default() } }
+    }
+
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.operation.summary = Some(summary.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.operation.description = Some(description.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.operation.tags.push(tag.into());
+        self
+    }
+
+    pub fn deprecated(mut self) -> Self {
+        self.operation.deprecated = true;
+        self
+    }
+
+    pub fn parameter(mut self, parameter: impl Into<RefOr<Parameter>>) -> Self {
+        self.operation.parameters.push(parameter.into());
+        self
+    }
+
+    /// Adds an optional query parameter.
+    pub fn query_param(self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.parameter(Parameter::query(name, schema))
+    }
+
+    /// Adds a path parameter, which is always required.
+    pub fn path_param(self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.parameter(Parameter::path(name, schema))
+    }
+
+    /// Adds an optional header parameter.
+    pub fn header_param(self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.parameter(Parameter::header(name, schema))
+    }
+
+    /// Sets a required `application/json` request body.
+    pub fn json_body(mut self, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.operation.add_request_body_json(Some(schema.into()));
+        self
+    }
+
+    pub fn request_body(mut self, body: impl Into<RefOr<RequestBody>>) -> Self {
+        self.operation.request_body = Some(body.into());
+        self
+    }
+
+    /// Adds the response for `status`, replacing any there already.
+    pub fn response(mut self, status: u16, response: impl Into<Response>) -> Self {
+        self.operation.responses.responses.insert(StatusCode::Code(status), RefOr::Item(response.into()));
+        self
+    }
+
+    /// Adds an `application/json` response described by the status's reason
+    /// phrase, e.g. `OK`.
+    pub fn json_response(self, status: u16, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.response(status, ResponseBuilder::for_status(status).json(schema))
+    }
+
+    pub fn default_response(mut self, response: impl Into<Response>) -> Self {
+        self.operation.responses.default = Some(RefOr::Item(response.into()));
+        self
+    }
+
+    /// See [Operation::add_security].
+    pub fn security<S: Into<String>>(mut self, scheme: impl Into<String>, scopes: impl IntoIterator<Item=S>) -> Self {
+        self.operation.add_security(scheme, scopes);
+        self
+    }
+
+    pub fn build(self) -> Operation {
+        self.operation
+    }
+}
+
+impl From<OperationBuilder> for Operation {
+    fn from(builder: OperationBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builds a [Response], for [OperationBuilder::response].
+#[derive(Debug, Clone, Default)]
+pub struct ResponseBuilder {
+    response: Response,
+}
+
+impl ResponseBuilder {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self { response: Response { description: description.into(), ..Response::default() } }
+    }
+
+    /// A response described by the reason phrase of `status`, e.g. `Not Found`.
+    pub fn for_status(status: u16) -> Self {
+        let reason = http::StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason());
+        Self::new(reason.unwrap_or_default())
+    }
+
+    pub fn content(mut self, media_type: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.response.content.insert(media_type.into(), MediaType { schema: Some(schema.into()), ..MediaType::default() });
+        self
+    }
+
+    pub fn json(self, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.content("application/json", schema)
+    }
+
+    pub fn header(mut self, name: impl Into<String>, header: impl Into<RefOr<Header>>) -> Self {
+        self.response.headers.insert(name.into(), header.into());
+        self
+    }
+
+    pub fn build(self) -> Response {
+        self.response
+    }
+}
+
+impl From<ResponseBuilder> for Response {
+    fn from(builder: ResponseBuilder) -> Self {
+        builder.build()
+    }
+}
+
+// The keywords any schema has, set the same way on both schema builders.
+macro_rules! schema_data_setters {
+    ($($data:ident).+) => {
+        pub fn title(mut self, title: impl Into<String>) -> Self {
+            self.$($data).+.title = Some(title.into());
+            self
+        }
+
+        pub fn description(mut self, description: impl Into<String>) -> Self {
+            self.$($data).+.description = Some(description.into());
+            self
+        }
+
+        pub fn nullable(mut self) -> Self {
+            self.$($data).+.nullable = true;
+            self
+        }
+
+        pub fn example(mut self, example: Value) -> Self {
+            self.$($data).+.example = Some(example);
+            self
+        }
+
+        pub fn default_value(mut self, default: Value) -> Self {
+            self.$($data).+.default = Some(default);
+            self
+        }
+    };
+}
+
+/// Builds a [Schema] from one of its constructors, such as [Schema::new_string].
+/// Object schemas have their own builder, [ObjectSchemaBuilder], from
+/// [SchemaBuilder::object].
+#[derive(Debug, Clone)]
+pub struct SchemaBuilder {
+    schema: Schema,
+}
+
+impl SchemaBuilder {
+    pub fn new(schema: Schema) -> Self {
+        Self { schema }
+    }
+
+    pub fn object() -> ObjectSchemaBuilder {
+        ObjectSchemaBuilder::default()
+    }
+
+    schema_data_setters!(schema.data);
+
+    pub fn build(self) -> Schema {
+        self.schema
+    }
+}
+
+impl From<SchemaBuilder> for Schema {
+    fn from(builder: SchemaBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<SchemaBuilder> for RefOr<Schema> {
+    fn from(builder: SchemaBuilder) -> Self {
+        RefOr::Item(builder.build())
+    }
+}
+
+/// Builds an object [Schema] property by property, from [SchemaBuilder::object].
+#[derive(Debug, Clone, Default)]
+pub struct ObjectSchemaBuilder {
+    data: SchemaData,
+    object: ObjectType,
+}
+
+impl ObjectSchemaBuilder {
+    schema_data_setters!(data);
+
+    /// Adds an optional property.
+    pub fn property(mut self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        self.object.properties.insert(name.into(), schema.into());
+        self
+    }
+
+    /// Adds a property and lists it in `required`.
+    pub fn required_property(mut self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        let name = name.into();
+        self.object.required.push(name.clone());
+        self.property(name, schema)
+    }
+
+    pub fn build(self) -> Schema {
+        Schema { data: self.data, kind: SchemaKind::Type(Type::Object(self.object)) }
+    }
+}
+
+impl From<ObjectSchemaBuilder> for Schema {
+    fn from(builder: ObjectSchemaBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<ObjectSchemaBuilder> for RefOr<Schema> {
+    fn from(builder: ObjectSchemaBuilder) -> Self {
+        RefOr::Item(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_builders() {
+        let spec = OpenAPIBuilder::new("Pets", "1.0")
+            .tag("admin", "Operations for staff")
+            .schema("Pet", SchemaBuilder::object()
+                .description("A pet")
+                .required_property("name", Schema::new_string())
+                .property("tag", SchemaBuilder::new(Schema::new_string()).nullable()))
+            .operation("/pets/{id}", Method::GET, OperationBuilder::new("getPet")
+                .tag("pets")
+                .path_param("id", Schema::new_integer())
+                .json_response(200, RefOr::schema_ref("Pet"))
+                .response(404, ResponseBuilder::new("No such pet")))
+            .operation("/pets", Method::POST, OperationBuilder::new("addPet")
+                .tag("pets")
+                .tag("admin")
+                .json_body(RefOr::schema_ref("Pet")))
+            .build();
+        assert_eq!(serde_json::to_value(&spec).unwrap(), json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets", "version": "1.0"},
+            "paths": {
+                "/pets/{id}": {"get": {
+                    "tags": ["pets"],
+                    "operationId": "getPet",
+                    "parameters": [{"name": "id", "in": "path", "style": "simple", "required": true, "schema": {"type": "integer"}}],
+                    "responses": {
+                        "200": {"description": "OK", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}},
+                        "404": {"description": "No such pet"}
+                    }
+                }},
+                "/pets": {"post": {
+                    "tags": ["pets", "admin"],
+                    "operationId": "addPet",
+                    "requestBody": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}, "required": true},
+                    "responses": {}
+                }}
+            },
+            "components": {"schemas": {"Pet": {
+                "description": "A pet",
+                "type": "object",
+                "properties": {"name": {"type": "string"}, "tag": {"type": "string", "nullable": true}},
+                "required": ["name"]
+            }}},
+            "tags": [{"name": "admin", "description": "Operations for staff"}, {"name": "pets"}]
+        }));
+        assert!(spec.validate().is_ok());
+    }
+}
@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::io::{from_value, read_value};
+use crate::{LoadError, OpenAPI};
+use crate::example_validation::{child, unescape};
+use crate::util::unique_name;
+
+/// Component sections, as they appear in `#/components/<section>/<name>`.
+pub(crate) const SECTIONS: &[&str] = &[
+    "schemas", "responses", "parameters", "examples", "requestBodies", "headers",
+    "securitySchemes", "links", "callbacks",
+];
+
+/// File names tried, in order, by [OpenAPI::load_dir].
+const ROOT_FILES: &[&str] = &["openapi.yaml", "openapi.yml", "openapi.json"];
+
+/// Where a [Bundler] reads the documents `$ref`s point to. Locations are file
+/// paths or `http(s)` URLs, resolved against the document holding the `$ref`,
+/// without `.` and `..` segments. Closures taking a location implement it too.
+pub trait RefSource {
+    fn load(&mut self, location: &str) -> Result<Value, LoadError>;
+}
+
+impl<F: FnMut(&str) -> Result<Value, LoadError>> RefSource for F {
+    fn load(&mut self, location: &str) -> Result<Value, LoadError> {
+        self(location)
+    }
+}
+
+/// Reads documents from the filesystem, as JSON or YAML.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileSource;
+
+impl RefSource for FileSource {
+    fn load(&mut self, location: &str) -> Result<Value, LoadError> {
+        read_value(Path::new(location))
+    }
+}
+
+/// Documents held in memory by location, e.g. for tests or files unpacked from
+/// an archive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemorySource(pub HashMap<String, Value>);
+
+impl RefSource for MemorySource {
+    fn load(&mut self, location: &str) -> Result<Value, LoadError> {
+        self.0.get(location).cloned().ok_or_else(|| LoadError::Io(std::io::ErrorKind::NotFound.into()))
+    }
+}
+
+/// Fetches `http` and `https` URLs, and reads other locations from the
+/// filesystem.
+#[cfg(feature = "remote-refs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "remote-refs")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpSource;
+
+#[cfg(feature = "remote-refs")]
+impl RefSource for HttpSource {
+    fn load(&mut self, location: &str) -> Result<Value, LoadError> {
+        if !is_url(location) {
+            return FileSource.load(location);
+        }
+        let fetch = || -> Result<Vec<u8>, ureq::Error> { ureq::get(location).call()?.body_mut().read_to_vec() };
+        let bytes = fetch().map_err(|e| LoadError::Reference(format!("cannot fetch {}: {}", location, e)))?;
+        let path = location.split(['?', '#']).next().unwrap_or(location);
+        crate::io::parse_value(&bytes, Some(Path::new(path)))
+    }
+}
+
+impl OpenAPI {
+    /// Loads a spec split across files, as in the common layout of an `openapi.yaml`
+    /// next to `paths/*.yaml` and `components/schemas/*.yaml`, joined by relative `$ref`s.
+    ///
+    /// Files in a directory named after a component section (e.g. `components/schemas/Pet.yaml`
+    /// or `schemas/Pet.yaml`) become components named after the file, or after the last
+    /// segment of the `$ref` fragment, and are referenced by local `#/components/...` refs.
+    /// Everything else, such as path items, is inlined where it is referenced.
+    pub fn load_dir(root: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let root = root.as_ref();
+        let entry = ROOT_FILES.iter()
+            .map(|name| root.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| LoadError::Reference(format!("no {} found in {}", ROOT_FILES.join(" or "), root.display())))?;
+        Bundler::new(FileSource).bundle(&entry.to_string_lossy())
+    }
+}
+
+enum Resolved {
+    /// Keep a `$ref`, pointing at this (local) reference.
+    Ref(String),
+    /// Replace the `$ref` object with this content.
+    Inline(Value),
+}
+
+/// Assembles a single document out of documents connected by `$ref`s, such as
+/// `./schemas/user.yaml#/User`, read from a [RefSource].
+///
+/// Targets in a directory named after a component section (e.g. `schemas/User.yaml`),
+/// or at `#/components/<section>/<name>` of another document, become components of
+/// the bundle, named after the last segment of the `$ref` fragment or after the
+/// file, and are referenced by local `#/components/...` refs. Everything else,
+/// such as path items, is inlined where it is referenced.
+pub struct Bundler<S> {
+    source: S,
+    root: String,
+    documents: HashMap<String, Value>,
+    /// Local refs already assigned to external targets, keyed by `(location, pointer)`.
+    hoisted: HashMap<(String, String), String>,
+    /// Components collected from external documents, by section.
+    components: Map<String, Value>,
+    /// External targets currently being inlined, to detect cycles.
+    inlining: Vec<(String, String)>,
+}
+
+impl<S: RefSource> Bundler<S> {
+    pub fn new(source: S) -> Self {
+        Bundler {
+            source,
+            root: String::new(),
+            documents: HashMap::new(),
+            hoisted: HashMap::new(),
+            components: Map::new(),
+            inlining: Vec::new(),
+        }
+    }
+
+    /// Loads the document at `root` and everything it references into a single spec.
+    pub fn bundle(self, root: &str) -> Result<OpenAPI, LoadError> {
+        Ok(from_value(self.bundle_value(root)?)?)
+    }
+
+    /// [Bundler::bundle], without parsing the result, e.g. for documents that are
+    /// fragments of a spec.
+    pub fn bundle_value(mut self, root: &str) -> Result<Value, LoadError> {
+        self.root = normalize(root);
+        let root = self.root.clone();
+        let mut document = self.document(&root)?.clone();
+        if let Some(Value::Object(components)) = document.get_mut("components") {
+            self.inline_components(components, &root)?;
+            // Existing components take their names first.
+            self.components = components.clone();
+        }
+        self.rewrite(&mut document, &root)?;
+        if let Value::Object(map) = &mut document {
+            if let Value::Object(components) = map.entry("components").or_insert_with(|| Value::Object(Map::new())) {
+                for (section, entries) in std::mem::take(&mut self.components) {
+                    let existing = components.entry(section).or_insert_with(|| Value::Object(Map::new()));
+                    if let (Value::Object(existing), Value::Object(entries)) = (existing, entries) {
+                        for (name, value) in entries {
+                            existing.entry(name).or_insert(value);
+                        }
+                    }
+                }
+            }
+            if map["components"].as_object().is_some_and(|c| c.is_empty()) {
+                map.remove("components");
+            }
+        }
+        Ok(document)
+    }
+
+    /// Replaces root components that are just a `$ref` to another file, as in
+    /// `schemas: {Pet: {$ref: schemas/Pet.yaml}}`, with the file's content, keeping
+    /// their names. Other refs to the same file then point at that component.
+    fn inline_components(&mut self, components: &mut Map<String, Value>, root: &str) -> Result<(), LoadError> {
+        let mut external = Vec::new();
+        for (section, entries) in components.iter() {
+            let Some(section) = SECTIONS.iter().find(|s| **s == section) else { continue };
+            let Value::Object(entries) = entries else { continue };
+            for (name, entry) in entries {
+                let Some(Value::String(reference)) = entry.get("$ref") else { continue };
+                let (location, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+                let target = join(root, location);
+                if location.is_empty() || target == self.root {
+                    continue;
+                }
+                let local = child(&child("#/components", section), name);
+                self.hoisted.insert((target.clone(), pointer.to_string()), local);
+                external.push((*section, name.clone(), target, pointer.to_string()));
+            }
+        }
+        for (section, name, target, pointer) in external {
+            let mut content = self.fragment(&target, &pointer)?;
+            self.rewrite(&mut content, &target)?;
+            let entry = &mut components[section][&name];
+            if let (Value::Object(content), Value::Object(siblings)) = (&mut content, &mut *entry) {
+                siblings.remove("$ref");
+                content.extend(std::mem::take(siblings));
+            }
+            *entry = content;
+        }
+        Ok(())
+    }
+
+    fn document(&mut self, location: &str) -> Result<&Value, LoadError> {
+        if !self.documents.contains_key(location) {
+            let value = self.source.load(location).map_err(|e| match e {
+                LoadError::Reference(_) => e,
+                e => LoadError::Reference(format!("cannot load {}: {}", location, e)),
+            })?;
+            self.documents.insert(location.to_string(), value);
+        }
+        Ok(&self.documents[location])
+    }
+
+    fn fragment(&mut self, location: &str, pointer: &str) -> Result<Value, LoadError> {
+        self.document(location)?
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| LoadError::Reference(format!("{} has nothing at #{}", location, pointer)))
+    }
+
+    /// Rewrites every `$ref` in `value`, which was read from `file`.
+    fn rewrite(&mut self, value: &mut Value, file: &str) -> Result<(), LoadError> {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    match self.resolve(&reference.clone(), file)? {
+                        Resolved::Ref(local) => {
+                            map.insert("$ref".to_string(), Value::String(local));
+                        }
+                        Resolved::Inline(Value::Object(mut content)) => {
+                            // Sibling keys (e.g. a `description` next to the `$ref`) win.
+                            map.remove("$ref");
+                            content.extend(std::mem::take(map));
+                            *map = content;
+                        }
+                        Resolved::Inline(content) => *value = content,
+                    }
+                    return Ok(());
+                }
+                for child in map.values_mut() {
+                    self.rewrite(child, file)?;
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite(item, file)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn resolve(&mut self, reference: &str, file: &str) -> Result<Resolved, LoadError> {
+        let (location, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+        let target = join(file, location);
+        if target == self.root {
+            return Ok(Resolved::Ref(format!("#{}", pointer)));
+        }
+        let key = (target.clone(), pointer.to_string());
+        if let Some(local) = self.hoisted.get(&key) {
+            return Ok(Resolved::Ref(local.clone()));
+        }
+        // A ref into a file that was bundled as a whole, like `Pet.yaml#/properties/id`.
+        if let Some(local) = self.hoisted.get(&(target.clone(), String::new())) {
+            return Ok(Resolved::Ref(format!("{}{}", local, pointer)));
+        }
+        if let Some((section, name)) = component_slot(&target, pointer) {
+            let name = self.unique_name(section, name);
+            let local = child(&child("#/components", section), &name);
+            self.hoisted.insert(key, local.clone());
+            // Reserve the name before recursing, so cyclic refs find it.
+            self.section(section).insert(name.clone(), Value::Null);
+            let mut content = self.fragment(&target, pointer)?;
+            self.rewrite(&mut content, &target)?;
+            self.section(section).insert(name, content);
+            return Ok(Resolved::Ref(local));
+        }
+        if self.inlining.contains(&key) {
+            return Err(LoadError::Reference(format!(
+                "{} refers to itself; move it under a components directory so it can be referenced by name",
+                reference,
+            )));
+        }
+        self.inlining.push(key);
+        let mut content = self.fragment(&target, pointer)?;
+        self.rewrite(&mut content, &target)?;
+        self.inlining.pop();
+        Ok(Resolved::Inline(content))
+    }
+
+    fn section(&mut self, section: &str) -> &mut Map<String, Value> {
+        let entry = self.components.entry(section.to_string()).or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        entry.as_object_mut().unwrap()
+    }
+
+    fn unique_name(&mut self, section: &str, name: String) -> String {
+        let taken = self.section(section);
+        unique_name(&name, |n| taken.contains_key(n))
+    }
+}
+
+/// The component section and name an external target should be bundled as, if any.
+fn component_slot(target: &str, pointer: &str) -> Option<(&'static str, String)> {
+    let segments: Vec<&str> = pointer.split('/').skip(1).collect();
+    // A ref into another full document, like `common.yaml#/components/schemas/Error`.
+    if let ["components", section, name] = segments.as_slice() {
+        if let Some(section) = SECTIONS.iter().find(|s| *s == section) {
+            return Some((section, unescape(name)));
+        }
+    }
+    let path = split_url(target).map_or(target, |(_, path)| path);
+    let mut dirs: Vec<&str> = path.split(['/', '\\']).collect();
+    let file = dirs.pop()?;
+    let section = dirs.iter().rev().find_map(|dir| SECTIONS.iter().find(|s| *s == dir))?;
+    let name = match segments.last() {
+        Some(name) if !name.is_empty() => unescape(name),
+        _ => file.rsplit_once('.').map_or(file, |(stem, _)| stem).to_string(),
+    };
+    Some((section, name))
+}
+
+fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// A URL's origin, like `https://example.com`, and its path.
+fn split_url(location: &str) -> Option<(&str, &str)> {
+    if !is_url(location) {
+        return None;
+    }
+    let authority = location.find("://")? + 3;
+    let path = location[authority..].find('/').map_or(location.len(), |i| authority + i);
+    Some(location.split_at(path))
+}
+
+/// `location` as referenced from the document at `base`.
+fn join(base: &str, location: &str) -> String {
+    if location.is_empty() {
+        return base.to_string();
+    }
+    if is_url(location) {
+        return normalize(location);
+    }
+    if let Some((origin, path)) = split_url(base) {
+        let dir = &path[..path.rfind('/').map_or(0, |i| i + 1)];
+        let path = if location.starts_with('/') { location.to_string() } else { format!("{}{}", dir, location) };
+        return format!("{}{}", origin, normalize_segments(&path));
+    }
+    normalize(&Path::new(base).parent().unwrap_or_else(|| Path::new("")).join(location).to_string_lossy())
+}
+
+/// Resolves `.` and `..` without touching the filesystem or network.
+fn normalize(location: &str) -> String {
+    if let Some((origin, path)) = split_url(location) {
+        return format!("{}{}", origin, normalize_segments(path));
+    }
+    let mut out = PathBuf::new();
+    for component in Path::new(location).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(out.components().next_back(), Some(Component::Normal(_))) => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out.to_string_lossy().into_owned()
+}
+
+fn normalize_segments(path: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." if out.len() > 1 => {
+                out.pop();
+            }
+            ".." => {}
+            segment => out.push(segment),
+        }
+    }
+    out.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn bundle(files: &[(&str, Value)]) -> Result<Value, LoadError> {
+        let files = files.iter().map(|(p, v)| (p.to_string(), v.clone())).collect();
+        Bundler::new(MemorySource(files)).bundle_value("api/openapi.yaml")
+    }
+
+    #[test]
+    fn test_bundle() {
+        let value = bundle(&[
+            ("api/openapi.yaml", json!({
+                "paths": {"/pets": {"$ref": "paths/pets.yaml"}},
+                "components": {"schemas": {"Pet": {"type": "string"}}}
+            })),
+            ("api/paths/pets.yaml", json!({
+                "get": {"responses": {"200": {"content": {"application/json": {
+                    "schema": {"$ref": "../components/schemas/Pet.yaml"}
+                }}}, "default": {"$ref": "../openapi.yaml#/components/responses/Error"}}}
+            })),
+            ("api/components/schemas/Pet.yaml", json!({
+                "type": "object",
+                "properties": {
+                    "owner": {"$ref": "./Person.yaml"},
+                    "friends": {"type": "array", "items": {"$ref": "Pet.yaml"}}
+                }
+            })),
+            ("api/components/schemas/Person.yaml", json!({"type": "object"})),
+        ]).unwrap();
+        assert_eq!(value, json!({
+            "paths": {"/pets": {
+                "get": {"responses": {"200": {"content": {"application/json": {
+                    "schema": {"$ref": "#/components/schemas/Pet2"}
+                }}}, "default": {"$ref": "#/components/responses/Error"}}}
+            }},
+            "components": {"schemas": {
+                "Pet": {"type": "string"},
+                "Pet2": {"type": "object", "properties": {
+                    "owner": {"$ref": "#/components/schemas/Person"},
+                    "friends": {"type": "array", "items": {"$ref": "#/components/schemas/Pet2"}}
+                }},
+                "Person": {"type": "object"}
+            }}
+        }));
+    }
+
+    #[test]
+    fn test_bundle_errors() {
+        let missing = bundle(&[("api/openapi.yaml", json!({"paths": {"/a": {"$ref": "paths/a.yaml"}}}))]);
+        assert!(matches!(missing, Err(LoadError::Reference(msg)) if msg.contains("api/paths/a.yaml")));
+
+        let cycle = bundle(&[
+            ("api/openapi.yaml", json!({"paths": {"/a": {"$ref": "paths/a.yaml"}}})),
+            ("api/paths/a.yaml", json!({"get": {"$ref": "a.yaml"}})),
+        ]);
+        assert!(matches!(cycle, Err(LoadError::Reference(msg)) if msg.contains("refers to itself")));
+    }
+
+    #[test]
+    fn test_bundle_urls() {
+        let mut requested = Vec::new();
+        let mut files = MemorySource(HashMap::from([
+            ("https://example.com/api/openapi.yaml".to_string(), json!({
+                "openapi": "3.0.3",
+                "info": {"title": "t", "version": "1"},
+                "paths": {"/users": {"get": {"responses": {
+                    "200": {"description": "", "content": {"application/json": {"schema": {"$ref": "./schemas/user.yaml#/User"}}}},
+                    "default": {"$ref": "../common.yaml#/components/responses/Error"}
+                }}}}
+            })),
+            ("https://example.com/api/schemas/user.yaml".to_string(), json!({"User": {"type": "object"}})),
+            ("https://example.com/common.yaml".to_string(), json!({"components": {"responses": {"Error": {"description": "failed"}}}})),
+        ]));
+        let spec = Bundler::new(|location: &str| {
+            requested.push(location.to_string());
+            files.load(location)
+        }).bundle("https://example.com/api/./openapi.yaml").unwrap();
+        assert_eq!(requested, [
+            "https://example.com/api/openapi.yaml",
+            "https://example.com/api/schemas/user.yaml",
+            "https://example.com/common.yaml",
+        ]);
+        let responses = &spec.paths.paths["/users"].as_item().unwrap().get.as_ref().unwrap().responses;
+        assert_eq!(responses.default.as_ref().unwrap().as_ref_str(), Some("#/components/responses/Error"));
+        assert!(spec.schemas.get2("User").is_some());
+        assert!(spec.components.responses.contains_key("Error"));
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let spec = OpenAPI::load_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/split")).unwrap();
+        let operation = spec.paths.paths["/pets"].as_item().unwrap().get.as_ref().unwrap();
+        assert_eq!(operation.operation_id.as_deref(), Some("listPets"));
+        assert!(spec.schemas.get2("Pet").is_some());
+        assert!(spec.schemas.get2("Error").is_some());
+    }
+}
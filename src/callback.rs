@@ -7,3 +7,56 @@ use indexmap::IndexMap;
 /// The key value used to identify the callback object is an expression,
 /// evaluated at runtime, that identifies a URL to use for the callback operation.
 pub type Callback = IndexMap<String, PathItem>;
+
+/// Evaluation of a [Callback], which can't have inherent methods as a type alias.
+pub trait CallbackExt {
+    /// The URLs the callbacks are sent to after the request of `context`, with
+    /// their path items, in order: each key with its runtime expressions replaced
+    /// as by [expand_expressions].
+    fn expand(&self, context: &RuntimeContext) -> Result<Vec<(String, &PathItem)>, RuntimeExpressionError>;
+}
+
+impl CallbackExt for Callback {
+    fn expand(&self, context: &RuntimeContext) -> Result<Vec<(String, &PathItem)>, RuntimeExpressionError> {
+        self.iter()
+            .map(|(key, item)| Ok((expand_expressions(key, context)?, item)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let callback: Callback = serde_json::from_value(json!({
+            "{$request.body#/callbackUrl}/events": {"post": {"responses": {"204": {"description": ""}}}},
+            "https://audit.example.com/?order={$request.path.id}&email={$request.body#/email}": {},
+            "$request.header.X-Hook": {}
+        })).unwrap();
+        let mut context = RuntimeContext {
+            url: "https://api.example.com/orders/42".to_string(),
+            method: "POST".to_string(),
+            ..RuntimeContext::default()
+        };
+        context.request.path.insert("id".to_string(), "42".to_string());
+        context.request.headers.insert("x-hook".to_string(), "https://hooks.example.com".to_string());
+        context.request.body = Some(json!({"callbackUrl": "https://client.example.com", "email": "a@example.com"}));
+
+        let expanded = callback.expand(&context).unwrap();
+        let urls: Vec<&str> = expanded.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(urls, [
+            "https://client.example.com/events",
+            "https://audit.example.com/?order=42&email=a@example.com",
+            "https://hooks.example.com",
+        ]);
+        assert!(expanded[0].1.post.is_some());
+
+        context.request.body = None;
+        let error = callback.expand(&context).unwrap_err();
+        assert_eq!(error.expression, "$request.body#/callbackUrl");
+    }
+}
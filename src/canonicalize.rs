@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::example_validation::child;
+use crate::*;
+
+impl OpenAPI {
+    /// Rewrites the document into a canonical form, so that documents that only
+    /// differ in ordering and other insignificant details serialize identically,
+    /// e.g. to keep generated documents from churning in version control:
+    ///
+    /// - paths, webhooks, the sections of `components` and the responses of
+    ///   each operation are sorted by key,
+    /// - `required` lists of schemas are sorted,
+    /// - empty server `variables` are removed, and
+    /// - the names of header parameters and response headers are lowercased,
+    ///   header names being case-insensitive.
+    ///
+    /// The order of schema properties, `enum` values, tags and servers is kept,
+    /// since tools show them as they are. Response headers whose names only
+    /// differ in case keep their names, as lowercasing them would have one
+    /// replace the other; their JSON pointers are returned, e.g.
+    /// `/paths/~1pets/get/responses/200/headers/X-Rate`.
+    pub fn canonicalize(&mut self) -> Vec<String> {
+        let mut canonicalize = Canonicalize { collisions: Vec::new() };
+        self.transform(&mut canonicalize);
+
+        let components = &mut self.components;
+        components.schemas.sort_keys();
+        components.responses.sort_keys();
+        components.parameters.sort_keys();
+        components.examples.sort_keys();
+        components.request_bodies.sort_keys();
+        components.headers.sort_keys();
+        components.security_schemes.sort_keys();
+        components.links.sort_keys();
+        components.callbacks.sort_keys();
+        components.path_items.sort_keys();
+        self.paths.paths.sort_keys();
+        self.webhooks.sort_keys();
+
+        canonical_servers(&mut self.servers);
+        for item in self.paths.paths.values_mut().chain(self.webhooks.values_mut()).filter_map(RefOr::as_mut) {
+            canonical_servers(&mut item.servers);
+        }
+        canonicalize.collisions
+    }
+}
+
+struct Canonicalize {
+    /// The response headers left as they are, their names colliding once lowercased.
+    collisions: Vec<String>,
+}
+
+impl Transform for Canonicalize {
+    fn operation(&mut self, _pointer: &str, operation: &mut Operation) -> bool {
+        operation.responses.responses.sort_keys();
+        canonical_servers(&mut operation.servers);
+        true
+    }
+
+    fn parameter(&mut self, _pointer: &str, parameter: &mut Parameter) {
+        if matches!(parameter.kind, ParameterKind::Header { .. }) {
+            parameter.name.make_ascii_lowercase();
+        }
+    }
+
+    fn response(&mut self, pointer: &str, response: &mut Response) {
+        let mut counts = HashMap::new();
+        for name in response.headers.keys() {
+            *counts.entry(name.to_ascii_lowercase()).or_insert(0) += 1;
+        }
+        let headers = std::mem::take(&mut response.headers);
+        response.headers = headers.into_iter().map(|(name, header)| {
+            let lowercase = name.to_ascii_lowercase();
+            if counts[&lowercase] > 1 {
+                self.collisions.push(child(&child(pointer, "headers"), &name));
+                (name, header)
+            } else {
+                (lowercase, header)
+            }
+        }).collect();
+    }
+
+    fn schema(&mut self, _pointer: &str, schema: &mut Schema) {
+        match &mut schema.kind {
+            SchemaKind::Type(Type::Object(object)) => object.required.sort(),
+            SchemaKind::Any(any) => any.required.sort(),
+            _ => {}
+        }
+    }
+}
+
+fn canonical_servers(servers: &mut [Server]) {
+    for server in servers {
+        if server.variables.as_ref().is_some_and(|variables| variables.is_empty()) {
+            server.variables = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_canonicalize() {
+        let spec = |paths: serde_json::Value, schemas: serde_json::Value| -> OpenAPI {
+            serde_json::from_value(json!({
+                "openapi": "3.0.3",
+                "info": {"title": "t", "version": "1"},
+                "servers": [{"url": "/", "variables": {}}],
+                "paths": paths,
+                "components": {"schemas": schemas}
+            })).unwrap()
+        };
+        let mut a = spec(json!({
+            "/pets": {"get": {
+                "parameters": [{"name": "X-Trace", "in": "header", "schema": {"type": "string"}}],
+                "responses": {
+                    "404": {"description": ""},
+                    "200": {"description": "", "headers": {"X-Rate-Limit": {"schema": {"type": "integer"}}}}
+                }
+            }},
+            "/owners": {}
+        }), json!({
+            "Pet": {"type": "object", "required": ["name", "id"], "properties": {"name": {"type": "string"}, "id": {"type": "integer"}}},
+            "Kind": {"type": "string", "enum": ["dog", "cat"]}
+        }));
+        let mut b = spec(json!({
+            "/owners": {},
+            "/pets": {"get": {
+                "parameters": [{"name": "x-trace", "in": "header", "schema": {"type": "string"}}],
+                "responses": {
+                    "200": {"description": "", "headers": {"x-rate-limit": {"schema": {"type": "integer"}}}},
+                    "404": {"description": ""}
+                }
+            }}
+        }), json!({
+            "Kind": {"type": "string", "enum": ["dog", "cat"]},
+            "Pet": {"type": "object", "required": ["id", "name"], "properties": {"name": {"type": "string"}, "id": {"type": "integer"}}}
+        }));
+        assert_ne!(serde_json::to_string(&a).unwrap(), serde_json::to_string(&b).unwrap());
+        assert!(a.canonicalize().is_empty());
+        assert!(b.canonicalize().is_empty());
+        assert_eq!(serde_json::to_string(&a).unwrap(), serde_json::to_string(&b).unwrap());
+
+        let paths: Vec<&str> = a.paths.keys().map(String::as_str).collect();
+        assert_eq!(paths, ["/owners", "/pets"]);
+        assert_eq!(a.servers[0].variables, None);
+        let properties: Vec<&str> = a.components.schemas["Pet"].as_item().unwrap().get_properties().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(properties, ["name", "id"]);
+        let kind = a.components.schemas["Kind"].as_item().unwrap();
+        assert_eq!(serde_json::to_value(kind).unwrap()["enum"], json!(["dog", "cat"]));
+
+        let mut c = spec(json!({"/pets": {"get": {"responses": {"200": {"description": "", "headers": {
+            "X-Rate": {"schema": {"type": "integer"}},
+            "x-rate": {"schema": {"type": "string"}},
+            "X-Next": {"schema": {"type": "string"}}
+        }}}}}}), json!({}));
+        assert_eq!(c.canonicalize(), [
+            "/paths/~1pets/get/responses/200/headers/X-Rate",
+            "/paths/~1pets/get/responses/200/headers/x-rate",
+        ]);
+        let response = c.paths.get_op("/pets", &http::Method::GET).unwrap().responses.responses[&StatusCode::Code(200)].as_item().unwrap();
+        let headers: Vec<&str> = response.headers.keys().map(String::as_str).collect();
+        assert_eq!(headers, ["X-Rate", "x-rate", "x-next"]);
+    }
+}
@@ -0,0 +1,5 @@
+//! Source code for the component schemas, for contract-first projects that don't
+//! want to run an external generator.
+
+pub mod rust;
+pub mod typescript;
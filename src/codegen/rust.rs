@@ -0,0 +1,448 @@
+//! Rust types for the component schemas, with serde attributes matching their
+//! JSON form.
+//!
+//! ```
+//! # let spec = openapiv3::OpenAPI::default();
+//! use openapiv3::codegen::rust::{generate, RustOptions};
+//!
+//! let source = generate(&spec, &RustOptions::default());
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::util::{pascal_case, snake_case, unique_name};
+use crate::*;
+
+/// Choices for [generate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustOptions {
+    /// The derives of every type, as written in `#[derive(...)]`.
+    pub derives: Vec<String>,
+    /// A serde `rename_all` rule, e.g. `camelCase`, put on every struct and enum.
+    /// Fields and variants whose name in the spec it doesn't produce get a
+    /// `rename` of their own, so any rule, or none, reads the same JSON.
+    pub rename_all: Option<String>,
+    /// Put `#[serde(skip_serializing_if = "Option::is_none")]` on optional fields,
+    /// so that absent and `null` values serialize as absent.
+    pub skip_serializing_none: bool,
+}
+
+impl Default for RustOptions {
+    fn default() -> Self {
+        RustOptions {
+            derives: ["Debug", "Clone", "PartialEq", "serde::Serialize", "serde::Deserialize"].iter().map(ToString::to_string).collect(),
+            rename_all: None,
+            skip_serializing_none: true,
+        }
+    }
+}
+
+/// A Rust type for each component schema, in document order:
+///
+/// - objects become structs. Fields are `Option`s unless required and not nullable,
+///   arrays are `Vec`s, maps are `HashMap`s, and free-form values `serde_json::Value`s.
+/// - `allOf` becomes a struct with a `#[serde(flatten)]` field for each referenced part,
+///   and the properties of the inline parts.
+/// - string enums become enums of unit variants.
+/// - `oneOf` and `anyOf` become `#[serde(untagged)]` enums, with a variant per member.
+/// - anything else becomes a type alias.
+///
+/// Inline objects and enums become types named after their place, e.g. `PetOwner`
+/// for the `owner` property of `Pet`, and fields referring to their own struct are boxed.
+pub fn generate(spec: &OpenAPI, options: &RustOptions) -> String {
+    let components = spec.schemas.keys().map(|name| type_name(name)).collect();
+    let mut generator = Generator { spec, options, components, root: "", items: IndexMap::new() };
+    for (key, schema) in &spec.schemas {
+        generator.root = key;
+        let name = type_name(key);
+        match schema {
+            RefOr::Item(schema) => generator.item(&name, schema),
+            RefOr::Reference { .. } => {
+                let target = generator.type_of(schema, &name);
+                generator.items.insert(name.clone(), format!("pub type {} = {};\n", name, target));
+            }
+        }
+    }
+    generator.items.into_values().collect::<Vec<_>>().join("\n")
+}
+
+struct Generator<'a> {
+    spec: &'a OpenAPI,
+    options: &'a RustOptions,
+    /// The type names of the component schemas, which inline types mustn't take.
+    components: HashSet<String>,
+    /// The component schema being generated.
+    root: &'a str,
+    /// The items by type name, in the order they're started, so that inline
+    /// types follow the type they're part of.
+    items: IndexMap<String, String>,
+}
+
+impl<'a> Generator<'a> {
+    fn item(&mut self, name: &str, schema: &'a Schema) {
+        if self.items.contains_key(name) {
+            return;
+        }
+        self.items.insert(name.to_string(), String::new());
+        let mut out = doc_comment(schema.description.as_deref(), "");
+        match &schema.kind {
+            SchemaKind::Type(Type::String(s)) if !s.enumeration.is_empty() => self.enumeration(&mut out, name, &s.enumeration),
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => self.union(&mut out, name, members),
+            SchemaKind::AllOf { all_of } if all_of.len() > 1 => self.structure(&mut out, name, schema, all_of),
+            _ if is_struct(schema) => self.structure(&mut out, name, schema, &[]),
+            _ => {
+                let typ = self.schema_type(schema, name);
+                let _ = writeln!(out, "pub type {} = {};", name, typ);
+            }
+        }
+        self.items.insert(name.to_string(), out);
+    }
+
+    fn attributes(&self, out: &mut String) {
+        if !self.options.derives.is_empty() {
+            let _ = writeln!(out, "#[derive({})]", self.options.derives.join(", "));
+        }
+    }
+
+    fn rename_all(&self, out: &mut String) {
+        if let Some(rule) = &self.options.rename_all {
+            let _ = writeln!(out, "#[serde(rename_all = {})]", quote(rule));
+        }
+    }
+
+    /// A `rename` attribute, if `ident` doesn't become `original` by the
+    /// `rename_all` rule.
+    fn rename(&self, out: &mut String, indent: &str, ident: &str, original: &str) {
+        let ident = ident.trim_start_matches("r#");
+        let renamed = match &self.options.rename_all {
+            Some(rule) => rename(rule, ident),
+            None => ident.to_string(),
+        };
+        if renamed != original {
+            let _ = writeln!(out, "{}#[serde(rename = {})]", indent, quote(original));
+        }
+    }
+
+    fn enumeration(&mut self, out: &mut String, name: &str, values: &[String]) {
+        self.attributes(out);
+        self.rename_all(out);
+        let _ = writeln!(out, "pub enum {} {{", name);
+        for value in values {
+            let variant = type_name(value);
+            self.rename(out, "    ", &variant, value);
+            let _ = writeln!(out, "    {},", variant);
+        }
+        out.push_str("}\n");
+    }
+
+    fn union(&mut self, out: &mut String, name: &str, members: &'a [RefOr<Schema>]) {
+        self.attributes(out);
+        out.push_str("#[serde(untagged)]\n");
+        let _ = writeln!(out, "pub enum {} {{", name);
+        for (i, member) in members.iter().enumerate() {
+            let variant = match member.as_ref_str().and_then(component_name) {
+                Some(component) => type_name(component),
+                None => format!("Variant{}", i + 1),
+            };
+            let typ = self.type_of(member, &format!("{}{}", name, variant));
+            let _ = writeln!(out, "    {}({}),", variant, self.boxed(member, typ));
+        }
+        out.push_str("}\n");
+    }
+
+    fn structure(&mut self, out: &mut String, name: &str, schema: &'a Schema, all_of: &'a [RefOr<Schema>]) {
+        let mut fields = String::new();
+        for part in all_of {
+            match part {
+                RefOr::Reference { reference } => {
+                    let typ = self.type_of(part, name);
+                    let typ = self.boxed(part, typ);
+                    let field = field_name(component_name(reference).unwrap_or("base"));
+                    let _ = writeln!(fields, "    #[serde(flatten)]\n    pub {}: {},", field, typ);
+                }
+                RefOr::Item(part) => self.fields(&mut fields, name, part),
+            }
+        }
+        self.fields(&mut fields, name, schema);
+        self.attributes(out);
+        self.rename_all(out);
+        let _ = write!(out, "pub struct {} {{\n{}}}\n", name, fields);
+    }
+
+    fn fields(&mut self, out: &mut String, owner: &str, schema: &'a Schema) {
+        for (property, value) in schema.get_properties().into_iter().flatten() {
+            let ident = field_name(property);
+            out.push_str(&doc_comment(value.as_item().and_then(|s| s.description.as_deref()), "    "));
+            self.rename(out, "    ", &ident, property);
+            let typ = self.type_of(value, &format!("{}{}", owner, pascal_case(property)));
+            let mut typ = self.boxed(value, typ);
+            let nullable = match value {
+                RefOr::Item(s) => s.nullable,
+                RefOr::Reference { reference } => component_name(reference)
+                    .and_then(|name| self.spec.schemas.get(name))
+                    .and_then(RefOr::as_item)
+                    .is_some_and(|s| s.nullable),
+            };
+            if !schema.is_required(property) || nullable {
+                if self.options.skip_serializing_none {
+                    out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                }
+                typ = format!("Option<{}>", typ);
+            }
+            let _ = writeln!(out, "    pub {}: {},", ident, typ);
+        }
+    }
+
+    /// `typ`, boxed if `schema` refers to a component containing the one being
+    /// generated, which would make it infinitely large.
+    fn boxed(&self, schema: &RefOr<Schema>, typ: String) -> String {
+        let Some(target) = schema.as_ref_str().and_then(component_name) else { return typ };
+        if target == self.root || self.contains(target, &mut HashSet::new()) {
+            format!("Box<{}>", typ)
+        } else {
+            typ
+        }
+    }
+
+    /// Whether the component `name` contains the one being generated, other than
+    /// through a `Vec` or a map.
+    fn contains(&self, name: &'a str, seen: &mut HashSet<&'a str>) -> bool {
+        if !seen.insert(name) {
+            return false;
+        }
+        let Some(schema) = self.spec.schemas.get(name).and_then(RefOr::as_item) else { return false };
+        let mut refs = Vec::new();
+        direct_refs(schema, &mut refs);
+        refs.into_iter().any(|r| r == self.root || self.contains(r, seen))
+    }
+
+    /// The Rust type of `schema`. `name` names the type defined for it if it needs one.
+    fn type_of(&mut self, schema: &'a RefOr<Schema>, name: &str) -> String {
+        match schema {
+            RefOr::Reference { reference } => match component_name(reference) {
+                Some(component) if self.spec.schemas.contains_key(component) => type_name(component),
+                _ => "serde_json::Value".to_string(),
+            },
+            RefOr::Item(schema) => self.schema_type(schema, name),
+        }
+    }
+
+    fn schema_type(&mut self, schema: &'a Schema, name: &str) -> String {
+        let defines_type = match &schema.kind {
+            SchemaKind::Type(Type::String(s)) => !s.enumeration.is_empty(),
+            SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => true,
+            SchemaKind::AllOf { all_of } => all_of.len() > 1,
+            _ => is_struct(schema),
+        };
+        if defines_type {
+            let name = unique_name(name, |n| self.components.contains(n) || self.items.contains_key(n));
+            self.item(&name, schema);
+            return name;
+        }
+        match &schema.kind {
+            SchemaKind::Type(Type::String(_)) => "String".to_string(),
+            SchemaKind::Type(Type::Integer(i)) => match format(&i.format).as_deref() {
+                Some("int32") => "i32".to_string(),
+                _ => "i64".to_string(),
+            },
+            SchemaKind::Type(Type::Number(n)) => match format(&n.format).as_deref() {
+                Some("float") => "f32".to_string(),
+                _ => "f64".to_string(),
+            },
+            SchemaKind::Type(Type::Boolean {}) => "bool".to_string(),
+            SchemaKind::Type(Type::Array(a)) => match &a.items {
+                Some(items) => format!("Vec<{}>", self.type_of(items.as_ref(), &format!("{}Item", name))),
+                None => "Vec<serde_json::Value>".to_string(),
+            },
+            SchemaKind::Type(Type::Object(o)) => match &o.additional_properties {
+                Some(AdditionalProperties::Schema(values)) => {
+                    format!("std::collections::HashMap<String, {}>", self.type_of(values, &format!("{}Value", name)))
+                }
+                _ => "serde_json::Map<String, serde_json::Value>".to_string(),
+            },
+            SchemaKind::AllOf { all_of } if all_of.len() == 1 => self.type_of(&all_of[0], name),
+            SchemaKind::Any(AnySchema { typ: Some(typ), .. }) if typ == "string" => "String".to_string(),
+            _ => "serde_json::Value".to_string(),
+        }
+    }
+}
+
+/// The components `schema` holds by value: those of its properties and of the
+/// parts of its `allOf`, `oneOf` or `anyOf`, at any depth of inline schemas.
+fn direct_refs<'a>(schema: &'a Schema, refs: &mut Vec<&'a str>) {
+    let parts: Box<dyn Iterator<Item=&RefOr<Schema>>> = match &schema.kind {
+        SchemaKind::AllOf { all_of: parts } | SchemaKind::OneOf { one_of: parts } | SchemaKind::AnyOf { any_of: parts } => Box::new(parts.iter()),
+        _ => Box::new(schema.get_properties().into_iter().flat_map(|p| p.values())),
+    };
+    for part in parts {
+        match part {
+            RefOr::Reference { reference } => refs.extend(component_name(reference)),
+            RefOr::Item(part) => direct_refs(part, refs),
+        }
+    }
+}
+
+fn is_struct(schema: &Schema) -> bool {
+    schema.get_properties().is_some_and(|p| !p.is_empty())
+}
+
+fn format(format: &impl serde::Serialize) -> Option<String> {
+    serde_json::to_value(format).ok().and_then(|v| v.as_str().map(ToString::to_string))
+}
+
+fn component_name(reference: &str) -> Option<&str> {
+    reference.strip_prefix("#/components/schemas/").filter(|name| !name.contains('/'))
+}
+
+/// A `PascalCase` identifier for a type or variant.
+fn type_name(name: &str) -> String {
+    let name = pascal_case(name);
+    if name.starts_with(|c: char| c.is_ascii_digit()) || name.is_empty() {
+        format!("_{}", name)
+    } else if name == "Self" {
+        "Self_".to_string()
+    } else {
+        name
+    }
+}
+
+/// A `snake_case` identifier for a field.
+fn field_name(name: &str) -> String {
+    let name: String = snake_case(name).chars().filter(|c| c.is_ascii_alphanumeric() || *c == '_').collect();
+    match name.as_str() {
+        "" => "_".to_string(),
+        "self" | "super" | "crate" => format!("{}_", name),
+        _ if name.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", name),
+        _ if is_keyword(&name) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+fn is_keyword(name: &str) -> bool {
+    [
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+        "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+        "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen",
+        "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ].contains(&name)
+}
+
+/// The serialized name serde gives `ident`, a field in `snake_case` or a variant
+/// in `PascalCase`, under `rule`.
+fn rename(rule: &str, ident: &str) -> String {
+    let words: Vec<String> = snake_case(ident).split('_').map(ToString::to_string).collect();
+    let capitalized = || words.iter().map(|w| pascal_case(w)).collect::<String>();
+    match rule {
+        "lowercase" => ident.to_lowercase(),
+        "UPPERCASE" => ident.to_uppercase(),
+        "PascalCase" => capitalized(),
+        "camelCase" => {
+            let pascal = capitalized();
+            let mut chars = pascal.chars();
+            chars.next().map(|c| c.to_ascii_lowercase()).into_iter().chain(chars).collect()
+        }
+        "snake_case" => words.join("_"),
+        "SCREAMING_SNAKE_CASE" => words.join("_").to_uppercase(),
+        "kebab-case" => words.join("-"),
+        "SCREAMING-KEBAB-CASE" => words.join("-").to_uppercase(),
+        _ => ident.to_string(),
+    }
+}
+
+fn doc_comment(description: Option<&str>, indent: &str) -> String {
+    match description.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(d) => d.lines().map(|line| format!("{}/// {}\n", indent, line).replace("/// \n", "///\n")).collect(),
+        None => String::new(),
+    }
+}
+
+fn quote(s: &str) -> String {
+    Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn spec() -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "description": "A pet.",
+                    "required": ["petId", "type"],
+                    "properties": {
+                        "petId": {"type": "integer", "format": "int32"},
+                        "type": {"type": "string", "enum": ["cat", "big-dog"]},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "parent": {"$ref": "#/components/schemas/Pet"},
+                        "extra": {"type": "object", "additionalProperties": {"type": "number"}}
+                    }
+                },
+                "Dog": {"allOf": [{"$ref": "#/components/schemas/Pet"}, {"type": "object", "required": ["barks"], "properties": {"barks": {"type": "boolean"}}}]},
+                "Animal": {"oneOf": [{"$ref": "#/components/schemas/Dog"}, {"type": "string"}]},
+                "Code": {"type": "string", "pattern": "^[A-Z]+$"}
+            }}
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_generate() {
+        let options = RustOptions { derives: vec!["Debug".to_string()], skip_serializing_none: false, ..RustOptions::default() };
+        assert_eq!(generate(&spec(), &options), r#"/// A pet.
+#[derive(Debug)]
+pub struct Pet {
+    #[serde(rename = "petId")]
+    pub pet_id: i32,
+    pub r#type: PetType,
+    pub tags: Option<Vec<String>>,
+    pub parent: Option<Box<Pet>>,
+    pub extra: Option<std::collections::HashMap<String, f64>>,
+}
+
+#[derive(Debug)]
+pub enum PetType {
+    #[serde(rename = "cat")]
+    Cat,
+    #[serde(rename = "big-dog")]
+    BigDog,
+}
+
+#[derive(Debug)]
+pub struct Dog {
+    #[serde(flatten)]
+    pub pet: Pet,
+    pub barks: bool,
+}
+
+#[derive(Debug)]
+#[serde(untagged)]
+pub enum Animal {
+    Dog(Dog),
+    Variant2(String),
+}
+
+pub type Code = String;
+"#);
+    }
+
+    #[test]
+    fn test_rename_all() {
+        let options = RustOptions { derives: Vec::new(), rename_all: Some("camelCase".to_string()), ..RustOptions::default() };
+        let source = generate(&spec(), &options);
+        assert!(source.starts_with("/// A pet.\n#[serde(rename_all = \"camelCase\")]\npub struct Pet {\n    pub pet_id: i32,\n    pub r#type: PetType,\n"));
+        assert!(source.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub tags: Option<Vec<String>>,\n"));
+        assert!(source.contains("pub enum PetType {\n    Cat,\n    #[serde(rename = \"big-dog\")]\n    BigDog,\n}"));
+        assert_eq!(rename("SCREAMING-KEBAB-CASE", "pet_id"), "PET-ID");
+        assert_eq!(rename("snake_case", "BigDog"), "big_dog");
+    }
+}
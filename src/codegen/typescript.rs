@@ -0,0 +1,241 @@
+//! TypeScript declarations for the component schemas.
+//!
+//! ```
+//! # let spec = openapiv3::OpenAPI::default();
+//! let source = openapiv3::codegen::typescript::generate(&spec);
+//! ```
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::util::pascal_case;
+use crate::*;
+
+/// A TypeScript declaration for each component schema, in document order:
+///
+/// - objects become `interface`s, with `?` for optional properties, `readonly`
+///   for `readOnly` ones and an index signature for `additionalProperties`.
+/// - string enums become `enum`s, with members named after their values.
+/// - `oneOf` and `anyOf` become union types. With a discriminator, each member
+///   is narrowed to its discriminator value, e.g. `({ kind: "cat" } & Cat)`.
+/// - `allOf` becomes an intersection type, and anything else a type alias.
+///
+/// Nullable schemas add `| null`, and descriptions become doc comments.
+pub fn generate(spec: &OpenAPI) -> String {
+    let mut out = String::new();
+    for (name, schema) in &spec.schemas {
+        let name = identifier(name);
+        let Some(schema) = schema.as_item() else {
+            let _ = writeln!(out, "export type {} = {};\n", name, type_of(spec, schema, 0));
+            continue;
+        };
+        out.push_str(&doc_comment(schema.description.as_deref(), ""));
+        match &schema.kind {
+            SchemaKind::Type(Type::String(s)) if !s.enumeration.is_empty() && !schema.nullable => {
+                let _ = writeln!(out, "export enum {} {{", name);
+                for value in &s.enumeration {
+                    let member = pascal_case(value);
+                    // Numeric names, even quoted, aren't allowed for members.
+                    let member = if is_identifier(&member) { member } else { identifier(&member) };
+                    let _ = writeln!(out, "  {} = {},", member, quote(value));
+                }
+                out.push_str("}\n\n");
+            }
+            SchemaKind::Type(Type::Object(_)) | SchemaKind::Any(_) if schema.get_properties().is_some_and(|p| !p.is_empty()) && !schema.nullable => {
+                let _ = writeln!(out, "export interface {} {}\n", name, object(spec, schema, 0));
+            }
+            _ => {
+                let _ = writeln!(out, "export type {} = {};\n", name, schema_type(spec, schema, 0));
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn type_of(spec: &OpenAPI, schema: &RefOr<Schema>, depth: usize) -> String {
+    match schema {
+        RefOr::Reference { reference } => match reference.strip_prefix("#/components/schemas/") {
+            Some(name) if !name.contains('/') => identifier(name),
+            _ => "unknown".to_string(),
+        },
+        RefOr::Item(schema) => schema_type(spec, schema, depth),
+    }
+}
+
+fn schema_type(spec: &OpenAPI, schema: &Schema, depth: usize) -> String {
+    let typ = match &schema.kind {
+        SchemaKind::Type(Type::String(s)) if !s.enumeration.is_empty() => s.enumeration.iter().map(|v| quote(v)).collect::<Vec<_>>().join(" | "),
+        SchemaKind::Type(Type::String(_)) => "string".to_string(),
+        SchemaKind::Type(Type::Number(n)) if !n.enumeration.is_empty() => literals(&n.enumeration),
+        SchemaKind::Type(Type::Integer(i)) if !i.enumeration.is_empty() => literals(&i.enumeration),
+        SchemaKind::Type(Type::Number(_)) | SchemaKind::Type(Type::Integer(_)) => "number".to_string(),
+        SchemaKind::Type(Type::Boolean {}) => "boolean".to_string(),
+        SchemaKind::Type(Type::Array(a)) => {
+            let items = a.items.as_ref().map_or_else(|| "unknown".to_string(), |items| type_of(spec, items.as_ref(), depth));
+            if items.contains(' ') { format!("({})[]", items) } else { format!("{}[]", items) }
+        }
+        SchemaKind::Type(Type::Object(_)) => object(spec, schema, depth),
+        SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => union(spec, schema, members, depth),
+        SchemaKind::AllOf { all_of } => {
+            let parts: Vec<_> = all_of.iter().map(|part| type_of(spec, part, depth)).collect();
+            if parts.len() == 1 { parts.concat() } else { parts.join(" & ") }
+        }
+        SchemaKind::Any(any) if !any.properties.is_empty() => object(spec, schema, depth),
+        SchemaKind::Any(_) | SchemaKind::Not { .. } => "unknown".to_string(),
+    };
+    if schema.nullable { format!("{} | null", typ) } else { typ }
+}
+
+fn literals(values: &[Option<impl serde::Serialize>]) -> String {
+    values.iter()
+        .map(|v| serde_json::to_value(v).map_or_else(|_| "unknown".to_string(), |v| v.to_string()))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// An object type literal for the properties and `additionalProperties` of `schema`.
+fn object(spec: &OpenAPI, schema: &Schema, depth: usize) -> String {
+    let indent = "  ".repeat(depth + 1);
+    let mut out = String::from("{\n");
+    for (name, property) in schema.get_properties().into_iter().flatten() {
+        let item = property.as_item();
+        out.push_str(&doc_comment(item.and_then(|p| p.description.as_deref()), &indent));
+        let readonly = if item.is_some_and(|p| p.read_only) { "readonly " } else { "" };
+        let optional = if schema.is_required(name) { "" } else { "?" };
+        let key = if is_identifier(name) { name.clone() } else { quote(name) };
+        let _ = writeln!(out, "{}{}{}{}: {};", indent, readonly, key, optional, type_of(spec, property, depth + 1));
+    }
+    let additional = match &schema.kind {
+        SchemaKind::Type(Type::Object(o)) => o.additional_properties.as_ref(),
+        _ => None,
+    };
+    match additional {
+        Some(AdditionalProperties::Schema(values)) => {
+            let _ = writeln!(out, "{}[key: string]: {};", indent, type_of(spec, values, depth + 1));
+        }
+        Some(AdditionalProperties::Any(true)) => {
+            let _ = writeln!(out, "{}[key: string]: unknown;", indent);
+        }
+        Some(AdditionalProperties::Any(false)) | None if schema.get_properties().is_none_or(|p| p.is_empty()) => {
+            return "Record<string, unknown>".to_string();
+        }
+        _ => {}
+    }
+    out.push_str(&"  ".repeat(depth));
+    out.push('}');
+    out
+}
+
+fn union(spec: &OpenAPI, schema: &Schema, members: &[RefOr<Schema>], depth: usize) -> String {
+    let Some(discriminator) = &schema.discriminator else {
+        return members.iter().map(|m| type_of(spec, m, depth)).collect::<Vec<_>>().join(" | ");
+    };
+    members.iter().map(|member| {
+        let typ = type_of(spec, member, depth);
+        let Some(reference) = member.as_ref_str() else { return typ };
+        // The mapped values, else the component name as the implicit value.
+        let mut values: Vec<&str> = discriminator.mapping.iter()
+            .filter(|(_, target)| *target == reference || reference.rsplit('/').next() == Some(target.as_str()))
+            .map(|(value, _)| value.as_str())
+            .collect();
+        if values.is_empty() {
+            values.push(reference.rsplit('/').next().unwrap_or(reference));
+        }
+        let values = values.iter().map(|v| quote(v)).collect::<Vec<_>>().join(" | ");
+        let key = if is_identifier(&discriminator.property_name) { discriminator.property_name.clone() } else { quote(&discriminator.property_name) };
+        format!("({{ {}: {} }} & {})", key, values, typ)
+    }).collect::<Vec<_>>().join(" | ")
+}
+
+fn doc_comment(description: Option<&str>, indent: &str) -> String {
+    let Some(description) = description.map(str::trim).filter(|d| !d.is_empty()) else { return String::new() };
+    let description = description.replace("*/", "*\\/");
+    if description.contains('\n') {
+        let lines: String = description.lines().map(|line| format!("{} * {}\n", indent, line).replace(" * \n", " *\n")).collect();
+        format!("{}/**\n{}{} */\n", indent, lines, indent)
+    } else {
+        format!("{}/** {} */\n", indent, description)
+    }
+}
+
+/// A valid TypeScript identifier for a component name.
+fn identifier(name: &str) -> String {
+    let mut ident: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '$' { c } else { '_' }).collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) || ident.is_empty() {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn quote(s: &str) -> String {
+    Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "description": "A pet.",
+                    "required": ["id", "kind"],
+                    "properties": {
+                        "id": {"type": "integer", "readOnly": true},
+                        "kind": {"type": "string"},
+                        "nickname": {"type": "string", "nullable": true, "description": "What it answers to."},
+                        "tags": {"type": "array", "items": {"oneOf": [{"type": "string"}, {"type": "integer"}]}},
+                        "x-ray": {"type": "object", "additionalProperties": {"type": "number"}}
+                    }
+                },
+                "Kind": {"type": "string", "enum": ["cat", "big-dog", "2"]},
+                "Cat": {"allOf": [{"$ref": "#/components/schemas/Pet"}, {"type": "object", "properties": {"indoor": {"type": "boolean"}}}]},
+                "Animal": {
+                    "oneOf": [{"$ref": "#/components/schemas/Cat"}, {"$ref": "#/components/schemas/Pet"}],
+                    "discriminator": {"propertyName": "kind", "mapping": {"cat": "#/components/schemas/Cat"}}
+                }
+            }}
+        })).unwrap();
+        assert_eq!(generate(&spec), r#"/** A pet. */
+export interface Pet {
+  readonly id: number;
+  kind: string;
+  /** What it answers to. */
+  nickname?: string | null;
+  tags?: (string | number)[];
+  "x-ray"?: {
+    [key: string]: number;
+  };
+}
+
+export enum Kind {
+  Cat = "cat",
+  BigDog = "big-dog",
+  _2 = "2",
+}
+
+export type Cat = Pet & {
+  indoor?: boolean;
+};
+
+export type Animal = ({ kind: "cat" } & Cat) | ({ kind: "Pet" } & Pet);
+"#);
+    }
+}
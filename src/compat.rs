@@ -0,0 +1,300 @@
+// Conversions with the types of other OpenAPI crates: both ways with the
+// `openapiv3` crate this one is forked from, for codebases that use both, and
+// from the specs `utoipa` generates at runtime. These crates model the same
+// documents, so values are converted through their JSON form. Fields that only
+// one side models are dropped on the way, e.g. the 3.1 keywords of `Schema`
+// going to `openapiv3`, or operation callbacks coming from it. Schemas also
+// convert both ways with those `schemars` derives, which are JSON Schemas.
+
+use std::convert::TryFrom;
+
+#[cfg(feature = "upstream")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "upstream")]
+use serde::Serialize;
+
+#[cfg(feature = "schemars")]
+use serde_json::Value;
+
+use crate::*;
+
+#[cfg(feature = "upstream")]
+fn convert<A: Serialize, B: DeserializeOwned>(value: A) -> Result<B, serde_json::Error> {
+    serde_json::from_value(serde_json::to_value(value)?)
+}
+
+#[cfg(feature = "upstream")]
+macro_rules! upstream {
+    ($($name:ident),* $(,)?) => {$(
+        impl TryFrom<upstream::$name> for $name {
+            type Error = serde_json::Error;
+
+            fn try_from(value: upstream::$name) -> Result<Self, Self::Error> {
+                convert(value)
+            }
+        }
+
+        impl TryFrom<$name> for upstream::$name {
+            type Error = serde_json::Error;
+
+            fn try_from(value: $name) -> Result<Self, Self::Error> {
+                convert(value)
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "upstream")]
+upstream!(
+    OpenAPI,
+    Components,
+    Info,
+    Server,
+    Tag,
+    PathItem,
+    Operation,
+    Parameter,
+    RequestBody,
+    Response,
+    MediaType,
+    Header,
+    Example,
+    Schema,
+    SecurityScheme,
+);
+
+#[cfg_attr(docsrs, doc(cfg(feature = "utoipa")))]
+#[cfg(feature = "utoipa")]
+impl TryFrom<utoipa::openapi::OpenApi> for OpenAPI {
+    type Error = serde_json::Error;
+
+    /// Converts a spec generated by `utoipa`, e.g. from `ApiDoc::openapi()`, to be
+    /// merged, validated or diffed with the rest of this crate.
+    fn try_from(value: utoipa::openapi::OpenApi) -> Result<Self, Self::Error> {
+        let mut value = serde_json::to_value(value)?;
+        nullable_types(&mut value);
+        serde_json::from_value(value)
+    }
+}
+
+/// Rewrites the `type: [T, "null"]` that `utoipa` emits for `Option<T>` fields into
+/// the `nullable` form that [Schema] models.
+#[cfg(feature = "utoipa")]
+fn nullable_types(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            let typ = match map.get("type") {
+                Some(Value::Array(types)) => match types.as_slice() {
+                    [typ, null] | [null, typ] if null == "null" && typ != "null" => Some(typ.clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(typ) = typ {
+                map.insert("type".to_string(), typ);
+                map.insert("nullable".to_string(), Value::Bool(true));
+            }
+            map.values_mut().for_each(nullable_types);
+        }
+        Value::Array(values) => values.iter_mut().for_each(nullable_types),
+        _ => {}
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+#[cfg(feature = "schemars")]
+impl TryFrom<schemars::Schema> for Schema {
+    type Error = serde_json::Error;
+
+    /// Converts a JSON Schema, with refs to its `$defs` or `definitions` pointing
+    /// into `components.schemas` instead. The definitions themselves are dropped,
+    /// [Components::insert_schemars] adds them too.
+    fn try_from(value: schemars::Schema) -> Result<Self, Self::Error> {
+        let mut value = value.to_value();
+        if let Value::Object(map) = &mut value {
+            map.remove("$defs");
+            map.remove("definitions");
+        }
+        from_schemars(&mut value);
+        serde_json::from_value(value)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl Schema {
+    /// This schema as a `schemars` JSON Schema (draft 2020-12), converted as by
+    /// [OpenAPI::export_schema_bundle], with the component schemas of `components`
+    /// as `$defs`, so its refs to them resolve.
+    #[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+    pub fn to_schemars(&self, components: &Components) -> schemars::Schema {
+        let value = crate::json_schema::to_json_schema(self, components);
+        schemars::Schema::try_from(value).expect("schemas convert to objects")
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl Components {
+    /// Adds a schema `schemars` generated as `name`, and its `$defs` or
+    /// `definitions` alongside it, replacing any of the same names. Returns a
+    /// `$ref` to it, for use in operations.
+    #[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+    pub fn insert_schemars(&mut self, name: impl Into<String>, schema: schemars::Schema) -> Result<RefOr<Schema>, serde_json::Error> {
+        let mut value = schema.to_value();
+        if let Value::Object(map) = &mut value {
+            let defs = ["$defs", "definitions"].iter().filter_map(|key| map.remove(*key)).collect::<Vec<_>>();
+            for (def, mut schema) in defs.into_iter().filter_map(|defs| Some(defs.as_object()?.clone())).flatten() {
+                from_schemars(&mut schema);
+                self.schemas.insert(def, serde_json::from_value::<RefOr<Schema>>(schema)?);
+            }
+        }
+        from_schemars(&mut value);
+        let name = name.into();
+        self.schemas.insert(name.clone(), serde_json::from_value::<RefOr<Schema>>(value)?);
+        Ok(RefOr::schema_ref(&name))
+    }
+
+    /// [Components::insert_schemars] for the schema of `T`, named as `schemars`
+    /// names it, e.g. `Pet` for `#[derive(JsonSchema)] struct Pet`.
+    #[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+    pub fn insert_json_schema<T: schemars::JsonSchema>(&mut self) -> Result<RefOr<Schema>, serde_json::Error> {
+        let schema = schemars::SchemaGenerator::default().into_root_schema_for::<T>();
+        self.insert_schemars(T::schema_name(), schema)
+    }
+}
+
+/// Rewrites a JSON Schema, and its subschemas, into the form of an OpenAPI
+/// schema: refs to `$defs` and `definitions` point into `components.schemas`,
+/// an `anyOf` with a `null` schema, as `schemars` emits for `Option`, becomes
+/// `nullable`, and boolean schemas become objects.
+#[cfg(feature = "schemars")]
+fn from_schemars(value: &mut Value) {
+    use serde_json::json;
+
+    match value {
+        Value::Bool(true) => *value = json!({}),
+        Value::Bool(false) => *value = json!({"not": {}}),
+        _ => {}
+    }
+    let Value::Object(map) = value else { return };
+    if let Some(Value::String(reference)) = map.get_mut("$ref") {
+        let name = reference.strip_prefix("#/$defs/").or_else(|| reference.strip_prefix("#/definitions/"));
+        if let Some(name) = name {
+            *reference = format!("#/components/schemas/{}", name);
+        }
+    }
+    map.remove("$schema");
+    let null = json!({"type": "null"});
+    let nullable = match map.get("anyOf") {
+        Some(Value::Array(schemas)) => match schemas.as_slice() {
+            [schema, other] | [other, schema] if *other == null && *schema != null => Some(schema.clone()),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(schema) = nullable {
+        map.remove("anyOf");
+        match schema {
+            Value::Object(schema) if !schema.contains_key("$ref") => {
+                for (keyword, value) in schema {
+                    map.entry(keyword).or_insert(value);
+                }
+            }
+            schema => {
+                map.insert("allOf".to_string(), json!([schema]));
+            }
+        }
+        map.insert("nullable".to_string(), Value::Bool(true));
+    }
+    // `additionalProperties` is the one place booleans are kept.
+    let additional = map.get("additionalProperties").filter(|v| v.is_boolean()).cloned();
+    crate::json_schema::for_each_subschema(map, &mut from_schemars);
+    if let Some(additional) = additional {
+        map.insert("additionalProperties".to_string(), additional);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "upstream")]
+    #[test]
+    fn test_round_trip() {
+        for fixture in [include_str!("../fixtures/petstore.yaml"), include_str!("../fixtures/api-with-examples.yaml")] {
+            let theirs: upstream::OpenAPI = serde_yaml::from_str(fixture).unwrap();
+            let ours = OpenAPI::try_from(theirs.clone()).unwrap();
+            assert_eq!(ours, serde_yaml::from_str::<OpenAPI>(fixture).unwrap());
+            assert_eq!(upstream::OpenAPI::try_from(ours).unwrap(), theirs);
+        }
+        let schema = Schema::new_array(RefOr::schema_ref("Pet"));
+        let theirs = upstream::Schema::try_from(schema.clone()).unwrap();
+        assert!(matches!(theirs.schema_kind, upstream::SchemaKind::Type(upstream::Type::Array(_))));
+        assert_eq!(Schema::try_from(theirs).unwrap(), schema);
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_from_utoipa() {
+        use utoipa::openapi::*;
+
+        let pet = schema::ObjectBuilder::new()
+            .property("name", schema::ObjectBuilder::new().schema_type(schema::Type::String))
+            .property("tag", schema::ObjectBuilder::new().schema_type(vec![schema::Type::String, schema::Type::Null].into_iter().collect::<schema::SchemaType>()))
+            .required("name");
+        let ok = ResponseBuilder::new().description("ok")
+            .content("application/json", ContentBuilder::new().schema(Some(Ref::from_schema_name("Pet"))).build());
+        let list = path::OperationBuilder::new().operation_id(Some("listPets")).response("200", ok);
+        let generated = OpenApiBuilder::new()
+            .info(InfoBuilder::new().title("pets").version("1"))
+            .paths(PathsBuilder::new().path("/pets", PathItem::new(HttpMethod::Get, list)))
+            .components(Some(ComponentsBuilder::new().schema("Pet", pet).build()))
+            .build();
+        let spec = OpenAPI::try_from(generated).unwrap();
+        assert_eq!(spec.openapi, "3.1.0");
+        assert!(spec.get_operation("listPets").is_some());
+        assert!(spec.validate_examples().is_ok());
+        let pet = spec.schemas["Pet"].as_item().unwrap();
+        let tag = pet.get_properties().unwrap()["tag"].as_item().unwrap();
+        assert!(tag.nullable && matches!(tag.kind, SchemaKind::Type(crate::Type::String(_))));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_schemars() {
+        #[allow(dead_code)]
+        #[derive(schemars::JsonSchema)]
+        struct Pet {
+            name: String,
+            tag: Option<String>,
+            owner: Option<Owner>,
+            data: serde_json::Value,
+        }
+
+        #[allow(dead_code)]
+        #[derive(schemars::JsonSchema)]
+        #[schemars(deny_unknown_fields)]
+        struct Owner {
+            id: u32,
+        }
+
+        let mut components = Components::default();
+        assert_eq!(components.insert_json_schema::<Pet>().unwrap(), RefOr::schema_ref("Pet"));
+        assert_eq!(components.schemas.keys().collect::<Vec<_>>(), ["Owner", "Pet"]);
+        let pet = components.schemas["Pet"].as_item().unwrap();
+        let properties = pet.get_properties().unwrap();
+        let tag = properties["tag"].as_item().unwrap();
+        assert!(tag.nullable && matches!(tag.kind, SchemaKind::Type(crate::Type::String(_))));
+        let owner = serde_json::to_value(&properties["owner"]).unwrap();
+        assert_eq!(owner, serde_json::json!({"allOf": [{"$ref": "#/components/schemas/Owner"}], "nullable": true}));
+        assert_eq!(serde_json::to_value(&properties["data"]).unwrap(), serde_json::json!({}));
+        let owner = serde_json::to_value(&components.schemas["Owner"]).unwrap();
+        assert_eq!(owner["additionalProperties"], false);
+
+        let back = pet.to_schemars(&components);
+        assert_eq!(back.get("properties").unwrap()["owner"]["anyOf"][0]["allOf"][0]["$ref"], "#/$defs/Owner");
+        assert!(back.get("$defs").unwrap().get("Owner").is_some());
+        assert_eq!(&Schema::try_from(back).unwrap(), pet);
+    }
+}
@@ -8,6 +8,8 @@ use crate::map::RefOrMap;
 /// on the API unless they are explicitly referenced from properties
 /// outside the components object.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Components {
     /// An object to hold reusable Security Scheme Objects.
@@ -37,8 +39,12 @@ pub struct Components {
     /// An object to hold reusable Callback Objects.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub callbacks: RefOrMap<Callback>,
+    /// An object to hold reusable Path Item Objects. Introduced in OpenAPI 3.1.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub path_items: RefOrMap<PathItem>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
@@ -53,6 +59,16 @@ impl Components {
             && self.schemas.is_empty()
             && self.links.is_empty()
             && self.callbacks.is_empty()
+            && self.path_items.is_empty()
             && self.extensions.is_empty()
     }
+
+    /// Adds a schema under `name`, replacing any of that name, and returns a
+    /// reference to it.
+    pub fn add_schema(&mut self, name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> RefOr<Schema> {
+        let name = name.into();
+        let reference = RefOr::schema_ref(&name);
+        self.schemas.insert(name, schema.into());
+        reference
+    }
 }
\ No newline at end of file
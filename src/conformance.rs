@@ -0,0 +1,294 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::instance_validation::validate_ref_value;
+use crate::mock::matching;
+use crate::parameter_style::is_json;
+use crate::*;
+
+/// An actual request, to check with [Operation::check_request].
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceRequest {
+    pub method: http::Method,
+    /// The path as requested, relative to the server URL, e.g. `/pets/5`.
+    pub path: String,
+    /// The raw query string, without the `?`.
+    pub query: String,
+    /// The headers, `Cookie` and `Content-Type` included.
+    pub headers: http::HeaderMap,
+    /// The body, in the media type the `Content-Type` header gives.
+    pub body: Vec<u8>,
+}
+
+impl<B: AsRef<[u8]>> From<&http::Request<B>> for ConformanceRequest {
+    /// The request, whose path is taken as is, so the server URL must have none.
+    fn from(request: &http::Request<B>) -> Self {
+        ConformanceRequest {
+            method: request.method().clone(),
+            path: request.uri().path().to_string(),
+            query: request.uri().query().unwrap_or_default().to_string(),
+            headers: request.headers().clone(),
+            body: request.body().as_ref().to_vec(),
+        }
+    }
+}
+
+/// An actual response, to check with [Operation::check_response].
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceResponse {
+    pub status: http::StatusCode,
+    /// The headers, `Content-Type` included.
+    pub headers: http::HeaderMap,
+    /// The body, in the media type the `Content-Type` header gives.
+    pub body: Vec<u8>,
+}
+
+impl<B: AsRef<[u8]>> From<&http::Response<B>> for ConformanceResponse {
+    fn from(response: &http::Response<B>) -> Self {
+        ConformanceResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.body().as_ref().to_vec(),
+        }
+    }
+}
+
+/// A way in which a request or response doesn't conform to an operation, from
+/// [Operation::check_request] or [Operation::check_response].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConformanceError {
+    /// The operation isn't part of the spec, or a `$ref` could not be followed.
+    Reference(String),
+    /// The request's method isn't the operation's.
+    Method(http::Method),
+    /// A parameter or response header that is missing, unknown or doesn't parse.
+    Parameter(ParameterError),
+    /// A value that doesn't match its schema, at a location such as
+    /// `query parameter "limit"`, `header "X-Rate-Limit"` or `body`.
+    Schema { location: String, error: SchemaError },
+    /// A status the operation declares no response for.
+    Status(http::StatusCode),
+    /// A required body that is empty, or a response without its declared content.
+    MissingBody,
+    /// A body where the operation or response declares none.
+    UnexpectedBody,
+    /// A `Content-Type` the body or response doesn't declare, `None` if missing.
+    ContentType(Option<String>),
+    /// A body that doesn't parse as its media type.
+    InvalidBody(String),
+}
+
+impl fmt::Display for ConformanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConformanceError::Reference(msg) => write!(f, "{}", msg),
+            ConformanceError::Method(method) => write!(f, "method {} is not the operation's", method),
+            ConformanceError::Parameter(e) => write!(f, "{}", e),
+            ConformanceError::Schema { location, error } => write!(f, "{}: {}", location, error),
+            ConformanceError::Status(status) => write!(f, "no response is declared for status {}", status.as_u16()),
+            ConformanceError::MissingBody => write!(f, "the body is missing"),
+            ConformanceError::UnexpectedBody => write!(f, "no body is declared"),
+            ConformanceError::ContentType(None) => write!(f, "the Content-Type header is missing"),
+            ConformanceError::ContentType(Some(content_type)) => write!(f, "content type {:?} is not declared", content_type),
+            ConformanceError::InvalidBody(msg) => write!(f, "invalid body: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConformanceError {}
+
+impl Operation {
+    /// Checks that `request` calls this operation of `spec` as it's declared: its
+    /// method, its path, query, header and cookie parameters, which must parse
+    /// as their `style` says and match their schemas, and its body, whose
+    /// `Content-Type` must be declared. JSON bodies are validated against their
+    /// schema, and text bodies as strings; others are only checked to be declared.
+    /// All problems are reported, rather than only the first.
+    pub fn check_request(&self, spec: &OpenAPI, request: &ConformanceRequest) -> Result<(), Vec<ConformanceError>> {
+        let op = find(self, spec)?;
+        let mut errors = Vec::new();
+        if !request.method.as_str().eq_ignore_ascii_case(op.method) {
+            errors.push(ConformanceError::Method(request.method.clone()));
+        }
+
+        let cookies = request.headers.get_all(http::header::COOKIE).iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let parsed = [
+            op.parse_path(&request.path),
+            op.parse_query(&request.query),
+            op.parse_headers(&request.headers),
+            op.parse_cookies(&cookies),
+        ];
+        let mut values = IndexMap::new();
+        for result in IntoIterator::into_iter(parsed) {
+            match result {
+                Ok(parsed) => values.extend(parsed),
+                Err(e) => errors.extend(e.into_iter().map(ConformanceError::Parameter)),
+            }
+        }
+        let parameters = op.parameters().map_err(|e| vec![ConformanceError::Reference(e.to_string())])?;
+        for parameter in parameters {
+            let Some(value) = values.get(&parameter.name) else { continue };
+            let location = format!("{} parameter {:?}", parameter.kind.location(), parameter.name);
+            let failures = match &parameter.format {
+                ParameterSchemaOrContent::Schema(schema) => validate_ref_value(schema, value, &spec.components),
+                ParameterSchemaOrContent::Content(_) => parameter.format.validate_content(spec, value).err().unwrap_or_default(),
+            };
+            errors.extend(failures.into_iter().map(|error| ConformanceError::Schema { location: location.clone(), error }));
+        }
+
+        match op.request_body() {
+            Ok(Some(body)) => check_body(spec, &body.content, body.required, &request.headers, &request.body, &mut errors),
+            Ok(None) if !request.body.is_empty() => errors.push(ConformanceError::UnexpectedBody),
+            Ok(None) => {}
+            Err(e) => errors.push(ConformanceError::Reference(e.to_string())),
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Checks that `response` is one this operation of `spec` declares: its status,
+    /// by the exact code, its range or `default`, its headers, which must parse
+    /// and match their schemas, and its body, as [Operation::check_request] does.
+    pub fn check_response(&self, spec: &OpenAPI, response: &ConformanceResponse) -> Result<(), Vec<ConformanceError>> {
+        find(self, spec)?;
+        let declared = self.responses.for_status(response.status)
+            .ok_or_else(|| vec![ConformanceError::Status(response.status)])?;
+        let declared = declared.resolve(spec).map_err(|e| vec![ConformanceError::Reference(e.to_string())])?;
+        let mut errors = Vec::new();
+        match declared.parse_headers(spec, &response.headers) {
+            Ok(values) => {
+                for (name, value) in &values {
+                    let Ok(header) = declared.headers[name].resolve(spec) else { continue };
+                    let failures = match &header.format {
+                        ParameterSchemaOrContent::Schema(schema) => validate_ref_value(schema, value, &spec.components),
+                        ParameterSchemaOrContent::Content(_) => header.format.validate_content(spec, value).err().unwrap_or_default(),
+                    };
+                    let location = format!("header {:?}", name);
+                    errors.extend(failures.into_iter().map(|error| ConformanceError::Schema { location: location.clone(), error }));
+                }
+            }
+            Err(e) => errors.extend(e.into_iter().map(ConformanceError::Parameter)),
+        }
+        if declared.content.is_empty() {
+            if !response.body.is_empty() {
+                errors.push(ConformanceError::UnexpectedBody);
+            }
+        } else {
+            check_body(spec, &declared.content, true, &response.headers, &response.body, &mut errors);
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+fn find<'a>(operation: &Operation, spec: &'a OpenAPI) -> Result<ResolvedOperation<'a>, Vec<ConformanceError>> {
+    spec.resolved().operations().find(|op| std::ptr::eq(op.operation, operation))
+        .ok_or_else(|| vec![ConformanceError::Reference("Operation is not part of the spec".to_string())])
+}
+
+fn check_body(spec: &OpenAPI, content: &Content, required: bool, headers: &http::HeaderMap, body: &[u8], errors: &mut Vec<ConformanceError>) {
+    if body.is_empty() {
+        if required {
+            errors.push(ConformanceError::MissingBody);
+        }
+        return;
+    }
+    let Some(content_type) = headers.get(http::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+        errors.push(ConformanceError::ContentType(None));
+        return;
+    };
+    let Some((_, media)) = matching(content, content_type) else {
+        errors.push(ConformanceError::ContentType(Some(content_type.to_string())));
+        return;
+    };
+    let value = if is_json(content_type) {
+        match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(e) => return errors.push(ConformanceError::InvalidBody(e.to_string())),
+        }
+    } else if content_type.trim_start().starts_with("text/") {
+        match std::str::from_utf8(body) {
+            Ok(text) => Value::String(text.to_string()),
+            Err(e) => return errors.push(ConformanceError::InvalidBody(e.to_string())),
+        }
+    } else {
+        return;
+    };
+    if let Some(schema) = &media.schema {
+        let failures = validate_ref_value(schema, &value, &spec.components);
+        errors.extend(failures.into_iter().map(|error| ConformanceError::Schema { location: "body".to_string(), error }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_check_request_and_response() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets/{id}": {"put": {
+                "parameters": [
+                    {"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}},
+                    {"name": "limit", "in": "query", "schema": {"type": "integer", "maximum": 10}},
+                    {"name": "X-Trace", "in": "header", "required": true, "schema": {"type": "string"}}
+                ],
+                "requestBody": {"required": true, "content": {"application/json": {"schema": {
+                    "type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}
+                }}}},
+                "responses": {
+                    "200": {
+                        "description": "",
+                        "headers": {"X-Rate-Limit": {"required": true, "schema": {"type": "integer", "minimum": 0}}},
+                        "content": {"application/json": {"schema": {"type": "object"}}}
+                    },
+                    "4XX": {"description": ""}
+                }
+            }}}
+        })).unwrap();
+        let op = spec.paths.get_op("/pets/{id}", &http::Method::PUT).unwrap();
+
+        let request = http::Request::put("/pets/5?limit=3")
+            .header("X-Trace", "abc")
+            .header("Content-Type", "application/json")
+            .body(br#"{"name": "Rex"}"#.to_vec())
+            .unwrap();
+        assert_eq!(op.check_request(&spec, &(&request).into()), Ok(()));
+
+        let request = ConformanceRequest {
+            method: http::Method::POST,
+            path: "/pets/x".to_string(),
+            query: "limit=30".to_string(),
+            headers: IntoIterator::into_iter([(http::header::CONTENT_TYPE, "application/json".parse().unwrap())]).collect(),
+            body: br#"{"name": 1}"#.to_vec(),
+        };
+        let errors: Vec<String> = op.check_request(&spec, &request).unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, [
+            "method POST is not the operation's",
+            r#"invalid value "x" for parameter "id""#,
+            r#"required parameter "X-Trace" is missing"#,
+            r#"query parameter "limit": 30 is greater than 10"#,
+            "body: /name: expected string, found number",
+        ]);
+
+        let response = http::Response::builder().header("X-Rate-Limit", "5").header("Content-Type", "application/json").body(b"{}").unwrap();
+        assert_eq!(op.check_response(&spec, &(&response).into()), Ok(()));
+        let response = ConformanceResponse { status: http::StatusCode::NOT_FOUND, body: b"{}".to_vec(), ..ConformanceResponse::default() };
+        assert_eq!(op.check_response(&spec, &response), Err(vec![ConformanceError::UnexpectedBody]));
+        let response = ConformanceResponse { status: http::StatusCode::INTERNAL_SERVER_ERROR, ..ConformanceResponse::default() };
+        assert_eq!(op.check_response(&spec, &response), Err(vec![ConformanceError::Status(http::StatusCode::INTERNAL_SERVER_ERROR)]));
+        let response = ConformanceResponse {
+            headers: IntoIterator::into_iter([(http::HeaderName::from_static("x-rate-limit"), "-1".parse().unwrap())]).collect(),
+            ..ConformanceResponse::default()
+        };
+        let errors: Vec<String> = op.check_response(&spec, &response).unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, [r#"header "X-Rate-Limit": -1 is less than 0"#, "the body is missing"]);
+    }
+}
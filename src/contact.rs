@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 /// Contact information for the exposed API.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Contact {
     /// The identifying name of the contact person/organization.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -17,5 +19,19 @@ pub struct Contact {
     pub email: Option<String>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+#[cfg(feature = "url")]
+#[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+impl Contact {
+    pub fn from_url(url: url::Url) -> Self {
+        Contact { url: Some(url.into()), ..Contact::default() }
+    }
+
+    /// The contact URL, if there is one.
+    pub fn parsed_url(&self) -> Option<Result<url::Url, url::ParseError>> {
+        self.url.as_deref().map(url::Url::parse)
+    }
+}
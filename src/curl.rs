@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::example::{declared_example, parameter_example};
+use crate::*;
+
+/// Where [Operation::to_curl] takes the values it fills in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExampleStrategy {
+    /// The declared examples, else those of the schemas or their defaults, else
+    /// placeholders like `<id>`. Optional parameters are only included when they
+    /// have an example.
+    #[default]
+    Declared,
+    /// Placeholders like `<id>` throughout, for a template to fill in. Only the
+    /// required parameters are included.
+    Placeholders,
+}
+
+impl Operation {
+    /// A runnable curl command calling this operation of `spec` on its server at
+    /// `server_index`, for documentation snippets. Credentials are left as
+    /// placeholders, e.g. `Authorization: Bearer <token>` or `--cert '<client-cert>'`,
+    /// for the first of the operation's security requirements. Fails if the
+    /// operation isn't one of `spec`'s, or has no such server; an operation
+    /// without servers is called on a relative URL.
+    pub fn to_curl(&self, spec: &OpenAPI, server_index: usize, strategy: ExampleStrategy) -> Result<String> {
+        let op = spec.resolved().operations().find(|op| std::ptr::eq(op.operation, self))
+            .ok_or_else(|| anyhow!("Operation is not part of the spec"))?;
+        let servers = op.servers();
+        let base = match servers.get(server_index) {
+            Some(server) => server.url_with_defaults(),
+            None if servers.is_empty() && server_index == 0 => String::new(),
+            None => return Err(anyhow!("No server at index {} for {} {}", server_index, op.method, op.path)),
+        };
+
+        let value = |parameter: &Parameter| match strategy {
+            ExampleStrategy::Declared => parameter_example(spec, parameter).map(|v| text(&v)),
+            ExampleStrategy::Placeholders => None,
+        };
+        let mut path = op.path.to_string();
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        let mut cookies = Vec::new();
+        for parameter in op.parameters()? {
+            let example = value(parameter);
+            if !parameter.required && example.is_none() {
+                continue;
+            }
+            let value = example.unwrap_or_else(|| format!("<{}>", parameter.name));
+            match parameter.kind {
+                ParameterKind::Path { .. } => path = path.replace(&format!("{{{}}}", parameter.name), &encode(&value)),
+                ParameterKind::Query { .. } => query.push(format!("{}={}", encode(&parameter.name), encode(&value))),
+                ParameterKind::Header { .. } => headers.push(format!("{}: {}", parameter.name, value)),
+                ParameterKind::Cookie { .. } => cookies.push(format!("{}={}", parameter.name, value)),
+            }
+        }
+        // Templated segments without a declared parameter.
+        let path = path.replace('{', "<").replace('}', ">");
+        let mut user = None;
+        let mut certificate = false;
+        for (name, _) in op.security().first().into_iter().flatten() {
+            let Some(scheme) = spec.security_schemes.get(name).and_then(RefOr::as_item) else { continue };
+            match scheme {
+                SecurityScheme::APIKey { location, name, .. } => {
+                    let placeholder = format!("<{}>", name.to_lowercase());
+                    match location {
+                        APIKeyLocation::Query => query.push(format!("{}={}", encode(name), placeholder)),
+                        APIKeyLocation::Header => headers.push(format!("{}: {}", name, placeholder)),
+                        APIKeyLocation::Cookie => cookies.push(format!("{}={}", name, placeholder)),
+                    }
+                }
+                SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => {
+                    user = Some("<username>:<password>".to_string());
+                }
+                SecurityScheme::HTTP { scheme, .. } if !scheme.eq_ignore_ascii_case("bearer") => {
+                    headers.push(format!("Authorization: {} <credentials>", scheme));
+                }
+                SecurityScheme::HTTP { .. } | SecurityScheme::OAuth2 { .. } | SecurityScheme::OpenIDConnect { .. } => {
+                    headers.push("Authorization: Bearer <token>".to_string());
+                }
+                SecurityScheme::MutualTLS { .. } => certificate = true,
+            }
+        }
+
+        let mut url = format!("{}{}", base.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query.join("&"));
+        }
+        let mut args = Vec::new();
+        if op.method != "get" {
+            args.push(format!("-X {}", op.method.to_uppercase()));
+        }
+        args.push(quote(&url));
+        if let Some(user) = user {
+            args.push(format!("-u {}", quote(&user)));
+        }
+        if certificate {
+            args.push(format!("--cert {} --key {}", quote("<client-cert>"), quote("<client-key>")));
+        }
+        for header in headers {
+            args.push(format!("-H {}", quote(&header)));
+        }
+        if !cookies.is_empty() {
+            args.push(format!("-b {}", quote(&cookies.join("; "))));
+        }
+        if let Some((content_type, media)) = op.request_body()?.and_then(|body| body.content.first()) {
+            let example = match strategy {
+                ExampleStrategy::Declared => declared_example(spec, media.example.as_ref(), &media.examples, media.schema.as_ref()),
+                ExampleStrategy::Placeholders => None,
+            };
+            args.extend(body(content_type, example));
+        }
+        Ok(format!("curl {}", args.join(" \\\n  ")))
+    }
+}
+
+fn body(content_type: &str, example: Option<Value>) -> Vec<String> {
+    let fields = |flag: &str, example: Option<Value>| match example {
+        Some(Value::Object(fields)) => fields.iter().map(|(k, v)| format!("{} {}", flag, quote(&format!("{}={}", k, text(v))))).collect(),
+        _ => vec![format!("{} {}", flag, quote("<field>=<value>"))],
+    };
+    match content_type {
+        "multipart/form-data" => fields("-F", example),
+        "application/x-www-form-urlencoded" => fields("--data-urlencode", example),
+        _ => {
+            let data = match example {
+                None => "<body>".to_string(),
+                Some(Value::String(s)) if !content_type.contains("json") => s,
+                Some(value) => serde_json::to_string(&value).expect("values serialize"),
+            };
+            vec![format!("-H {}", quote(&format!("Content-Type: {}", content_type))), format!("-d {}", quote(&data))]
+        }
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Quotes `s` as a single shell word.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Percent-encodes all but the unreserved characters, and the `<>` of placeholders
+/// so that they stay readable.
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'<' | b'>' => encoded.push(b as char),
+            b => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_to_curl() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "servers": [{"url": "https://{region}.example.com/v1/", "variables": {"region": {"default": "eu"}}}],
+            "security": [{"key": []}],
+            "paths": {"/pets/{id}": {
+                "put": {
+                    "operationId": "updatePet",
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}, "example": "a b"},
+                        {"name": "dryRun", "in": "query", "schema": {"type": "boolean", "default": false}},
+                        {"name": "verbose", "in": "query", "schema": {"type": "boolean"}},
+                        {"name": "X-Request-Id", "in": "header", "required": true, "schema": {"type": "string"}}
+                    ],
+                    "requestBody": {"content": {"application/json": {"example": {"name": "O'Neil"}}}},
+                    "responses": {}
+                },
+                "get": {"security": [{"basic": []}], "responses": {}}
+            }},
+            "components": {"securitySchemes": {
+                "key": {"type": "apiKey", "in": "header", "name": "X-API-Key"},
+                "basic": {"type": "http", "scheme": "basic"}
+            }}
+        })).unwrap();
+        let (put, _) = spec.get_operation("updatePet").unwrap();
+        assert_eq!(put.to_curl(&spec, 0, ExampleStrategy::Declared).unwrap(), r#"curl -X PUT \
+  'https://eu.example.com/v1/pets/a%20b?dryRun=false' \
+  -H 'X-Request-Id: <X-Request-Id>' \
+  -H 'X-API-Key: <x-api-key>' \
+  -H 'Content-Type: application/json' \
+  -d '{"name":"O'\''Neil"}'"#);
+        assert_eq!(put.to_curl(&spec, 0, ExampleStrategy::Placeholders).unwrap(), r#"curl -X PUT \
+  'https://eu.example.com/v1/pets/<id>' \
+  -H 'X-Request-Id: <X-Request-Id>' \
+  -H 'X-API-Key: <x-api-key>' \
+  -H 'Content-Type: application/json' \
+  -d '<body>'"#);
+        let get = spec.paths["/pets/{id}"].as_item().unwrap().get.as_ref().unwrap();
+        assert_eq!(get.to_curl(&spec, 0, ExampleStrategy::Declared).unwrap(), "curl 'https://eu.example.com/v1/pets/<id>' \\\n  -u '<username>:<password>'");
+        assert!(get.to_curl(&spec, 1, ExampleStrategy::Declared).is_err());
+        assert!(Operation::default().to_curl(&spec, 0, ExampleStrategy::Declared).is_err());
+    }
+}
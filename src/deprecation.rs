@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::example_validation::{child, unescape};
+use crate::*;
+
+/// The deprecated parts of a spec, from [OpenAPI::deprecations], e.g. to generate
+/// migration notices.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeprecationReport {
+    /// In document order of the operations using them, then of the unused ones.
+    pub deprecations: Vec<Deprecation>,
+}
+
+/// Something deprecated, where it is declared.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Deprecation {
+    /// The JSON pointer to its declaration, e.g. `/components/schemas/Pet/properties/tag`.
+    pub pointer: String,
+    pub kind: DeprecationKind,
+    pub description: Option<String>,
+    /// Its `x-sunset` extension, e.g. a date, and any other whose name starts
+    /// with `x-deprecat`, e.g. `x-deprecated-since`.
+    pub metadata: IndexMap<String, Value>,
+    /// The operations using it, directly or through `$ref`s, as lowercase method
+    /// and path. For an operation, itself.
+    pub operations: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DeprecationKind {
+    Operation,
+    Parameter { name: String, location: String },
+    /// A property of an object schema.
+    Property { name: String },
+    /// Any other schema, e.g. a component.
+    Schema,
+}
+
+impl DeprecationReport {
+    /// The deprecations affecting the operation, as [ResolvedOperation] names it.
+    pub fn for_operation<'a>(&'a self, method: &'a str, path: &'a str) -> impl Iterator<Item=&'a Deprecation> + 'a {
+        self.deprecations.iter().filter(move |d| d.operations.iter().any(|(m, p)| m == method && p == path))
+    }
+}
+
+impl OpenAPI {
+    /// Every deprecated operation, parameter and schema, properties included,
+    /// with the operations using them. Those reached through `$ref`s are found
+    /// where the component declares them, and those no operation uses are listed
+    /// too.
+    pub fn deprecations(&self) -> DeprecationReport {
+        let Ok(doc) = serde_json::to_value(self) else { return DeprecationReport::default() };
+        let mut report = Report { doc: &doc, entries: IndexMap::new() };
+        for op in self.resolved().operations() {
+            let usage = (op.method.to_string(), op.path.to_string());
+            let pointer = child(&child("/paths", op.path), op.method);
+            if let Some(operation) = doc.pointer(&pointer) {
+                report.operation(&pointer, operation, &usage);
+            }
+        }
+        for name in keys(&doc, "/components/parameters") {
+            report.parameter(&child("/components/parameters", &name), None, &mut HashSet::new());
+        }
+        for name in keys(&doc, "/components/schemas") {
+            report.schema(&child("/components/schemas", &name), None, &mut HashSet::new());
+        }
+        DeprecationReport { deprecations: report.entries.into_values().collect() }
+    }
+}
+
+struct Report<'a> {
+    doc: &'a Value,
+    entries: IndexMap<String, Deprecation>,
+}
+
+impl<'a> Report<'a> {
+    /// The pointer and value of what `pointer` points to, following local `$ref`s.
+    fn resolve(&self, pointer: &str) -> Option<(String, &'a Value)> {
+        let mut pointer = pointer.to_string();
+        let mut seen = HashSet::new();
+        loop {
+            let value = self.doc.pointer(&pointer)?;
+            match value.get("$ref").and_then(Value::as_str) {
+                Some(reference) if seen.insert(pointer.clone()) => pointer = reference.strip_prefix('#')?.to_string(),
+                Some(_) => return None,
+                None => return Some((pointer, value)),
+            }
+        }
+    }
+
+    fn record(&mut self, pointer: &str, value: &Value, kind: impl FnOnce() -> DeprecationKind, usage: Option<&(String, String)>) {
+        if value.get("deprecated") != Some(&Value::Bool(true)) {
+            return;
+        }
+        let entry = self.entries.entry(pointer.to_string()).or_insert_with(|| Deprecation {
+            pointer: pointer.to_string(),
+            kind: kind(),
+            description: value.get("description").and_then(Value::as_str).map(str::to_string),
+            metadata: value.as_object().into_iter().flatten()
+                .filter(|(key, _)| *key == "x-sunset" || key.starts_with("x-deprecat"))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            operations: Vec::new(),
+        });
+        if let Some(usage) = usage.filter(|usage| !entry.operations.contains(usage)) {
+            entry.operations.push(usage.clone());
+        }
+    }
+
+    fn operation(&mut self, pointer: &str, operation: &Value, usage: &(String, String)) {
+        self.record(pointer, operation, || DeprecationKind::Operation, Some(usage));
+        let seen = &mut HashSet::new();
+        // Parameters of the operation override those of the path item of the same name and location.
+        let own: Vec<String> = indexed(operation, "parameters", pointer).collect();
+        let key = |pointer: &str| self.resolve(pointer).map(|(_, p)| (p.get("name").cloned(), p.get("in").cloned()));
+        let own_keys: Vec<_> = own.iter().map(|pointer| key(pointer)).collect();
+        let item = pointer.rsplit_once('/').map_or("", |(item, _)| item);
+        let inherited: Vec<String> = self.doc.pointer(item).into_iter()
+            .flat_map(|value| indexed(value, "parameters", item))
+            .filter(|pointer| !own_keys.contains(&key(pointer)))
+            .collect();
+        for parameter in inherited.iter().chain(&own) {
+            self.parameter(parameter, Some(usage), seen);
+        }
+        if let Some((body, _)) = self.resolve(&child(pointer, "requestBody")) {
+            self.content(&child(&body, "content"), Some(usage), seen);
+        }
+        let responses = child(pointer, "responses");
+        for status in keys(self.doc, &responses) {
+            let Some((response, value)) = self.resolve(&child(&responses, &status)) else { continue };
+            self.content(&child(&response, "content"), Some(usage), seen);
+            for name in keys(value, "/headers") {
+                if let Some((header, _)) = self.resolve(&child(&child(&response, "headers"), &name)) {
+                    self.schemas_of(&header, Some(usage), seen);
+                }
+            }
+        }
+    }
+
+    fn parameter(&mut self, pointer: &str, usage: Option<&(String, String)>, seen: &mut HashSet<String>) {
+        let Some((pointer, parameter)) = self.resolve(pointer) else { return };
+        let name = |key| parameter.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+        self.record(&pointer, parameter, || DeprecationKind::Parameter { name: name("name"), location: name("in") }, usage);
+        self.schemas_of(&pointer, usage, seen);
+    }
+
+    /// The schemas of a parameter or header: its `schema`, or those of its `content`.
+    fn schemas_of(&mut self, pointer: &str, usage: Option<&(String, String)>, seen: &mut HashSet<String>) {
+        self.schema(&child(pointer, "schema"), usage, seen);
+        self.content(&child(pointer, "content"), usage, seen);
+    }
+
+    fn content(&mut self, pointer: &str, usage: Option<&(String, String)>, seen: &mut HashSet<String>) {
+        for media_type in keys(self.doc, pointer) {
+            self.schema(&child(&child(pointer, &media_type), "schema"), usage, seen);
+        }
+    }
+
+    fn schema(&mut self, pointer: &str, usage: Option<&(String, String)>, seen: &mut HashSet<String>) {
+        let Some((pointer, schema)) = self.resolve(pointer) else { return };
+        if !seen.insert(pointer.clone()) {
+            return;
+        }
+        let (parent, name) = pointer.rsplit_once('/').unwrap_or_default();
+        let kind = || match parent.rsplit_once('/') {
+            Some((_, "properties")) => DeprecationKind::Property { name: unescape(name) },
+            _ => DeprecationKind::Schema,
+        };
+        self.record(&pointer, schema, kind, usage);
+        let Some(map) = schema.as_object() else { return };
+        for (keyword, nested) in map {
+            let pointer = child(&pointer, keyword);
+            match (keyword.as_str(), nested) {
+                ("properties" | "patternProperties" | "$defs" | "dependentSchemas", Value::Object(schemas)) => {
+                    for name in schemas.keys() {
+                        self.schema(&child(&pointer, name), usage, seen);
+                    }
+                }
+                ("allOf" | "anyOf" | "oneOf" | "prefixItems", Value::Array(schemas)) => {
+                    for i in 0..schemas.len() {
+                        self.schema(&child(&pointer, &i.to_string()), usage, seen);
+                    }
+                }
+                ("items" | "additionalProperties" | "not" | "if" | "then" | "else" | "contains" | "propertyNames"
+                | "unevaluatedItems" | "unevaluatedProperties", Value::Object(_)) => self.schema(&pointer, usage, seen),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The pointers to the items of the array `key` of `value`, found at `pointer`.
+fn indexed<'a>(value: &'a Value, key: &'a str, pointer: &'a str) -> impl Iterator<Item=String> + 'a {
+    let len = value.get(key).and_then(Value::as_array).map_or(0, Vec::len);
+    (0..len).map(move |i| child(&child(pointer, key), &i.to_string()))
+}
+
+/// The keys of the object at `pointer` in `value`.
+fn keys(value: &Value, pointer: &str) -> Vec<String> {
+    value.pointer(pointer).and_then(Value::as_object).map(|map| map.keys().cloned().collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deprecations() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {
+                "parameters": [
+                    {"name": "limit", "in": "query", "deprecated": true, "schema": {"type": "integer"}},
+                    {"name": "page", "in": "query", "deprecated": true, "schema": {"type": "integer"}}
+                ],
+                "get": {
+                    "deprecated": true,
+                    "description": "Use /animals",
+                    "x-sunset": "2025-01-01",
+                    "parameters": [
+                        {"$ref": "#/components/parameters/Sort"},
+                        {"name": "page", "in": "query", "schema": {"type": "integer"}}
+                    ],
+                    "responses": {"200": {"description": "", "content": {"application/json": {
+                        "schema": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}}
+                    }}}}
+                },
+                "post": {
+                    "requestBody": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}},
+                    "responses": {}
+                }
+            }},
+            "components": {
+                "parameters": {"Sort": {"name": "sort", "in": "query", "deprecated": true, "x-deprecated-since": "2.0", "schema": {"type": "string"}}},
+                "schemas": {
+                    "Pet": {"type": "object", "properties": {
+                        "name": {"type": "string"},
+                        "tag": {"type": "string", "deprecated": true},
+                        "parent": {"$ref": "#/components/schemas/Pet"}
+                    }},
+                    "OldPet": {"type": "object", "deprecated": true}
+                }
+            }
+        })).unwrap();
+        let report = spec.deprecations();
+        let found: Vec<(&str, &DeprecationKind, Vec<&str>)> = report.deprecations.iter()
+            .map(|d| (d.pointer.as_str(), &d.kind, d.operations.iter().map(|(method, _)| method.as_str()).collect()))
+            .collect();
+        let parameter = |name: &str| DeprecationKind::Parameter { name: name.to_string(), location: "query".to_string() };
+        assert_eq!(found, vec![
+            ("/paths/~1pets/get", &DeprecationKind::Operation, vec!["get"]),
+            ("/paths/~1pets/parameters/0", &parameter("limit"), vec!["get", "post"]),
+            ("/components/parameters/Sort", &parameter("sort"), vec!["get"]),
+            ("/components/schemas/Pet/properties/tag", &DeprecationKind::Property { name: "tag".to_string() }, vec!["get", "post"]),
+            ("/paths/~1pets/parameters/1", &parameter("page"), vec!["post"]),
+            ("/components/schemas/OldPet", &DeprecationKind::Schema, vec![]),
+        ]);
+        let get = &report.deprecations[0];
+        assert_eq!(get.description.as_deref(), Some("Use /animals"));
+        assert_eq!(get.metadata, IndexMap::from([("x-sunset".to_string(), json!("2025-01-01"))]));
+        assert_eq!(report.deprecations[2].metadata["x-deprecated-since"], json!("2.0"));
+        assert_eq!(report.for_operation("post", "/pets").count(), 3);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::*;
+
+/// What [OpenAPI::dereference] does with references that lead back to themselves,
+/// which can't be inlined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CyclicRefs {
+    /// Fail with [ResolveError::Circular].
+    Error,
+    /// Leave the `$ref` where it cycles, pointing into `components`, which is
+    /// kept for them.
+    Keep,
+}
+
+/// Why a spec could not be dereferenced, from [OpenAPI::dereference].
+#[derive(Debug)]
+pub enum DereferenceError {
+    Resolve(ResolveError),
+    /// A reference points to an object of the wrong kind for where it's used.
+    Invalid(serde_json::Error),
+}
+
+impl fmt::Display for DereferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DereferenceError::Resolve(e) => write!(f, "{}", e),
+            DereferenceError::Invalid(e) => write!(f, "dereferenced spec is invalid: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DereferenceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DereferenceError::Resolve(e) => Some(e),
+            DereferenceError::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl From<ResolveError> for DereferenceError {
+    fn from(e: ResolveError) -> Self {
+        DereferenceError::Resolve(e)
+    }
+}
+
+/// What a value is in a serialized document, which tells the traversals of one
+/// keywords from names, like those of `properties` or `responses`, and both from
+/// user data, like examples, whose `$ref`s are not references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueRole {
+    /// An object of keywords, like an operation or a response.
+    Object,
+    Schema,
+    Link,
+    /// A map from names to objects, like `responses` or `content`.
+    Names,
+    /// A map from names to schemas, like `properties`.
+    SchemaNames,
+    LinkNames,
+    /// A map from names to callbacks, which map expressions to path items.
+    CallbackNames,
+    /// User data, like an example or a `default`.
+    Data,
+}
+
+impl ValueRole {
+    /// Whether the keys of the value are keywords, so that a `$ref` among them
+    /// is a reference.
+    pub(crate) fn is_keywords(self) -> bool {
+        matches!(self, ValueRole::Object | ValueRole::Schema | ValueRole::Link)
+    }
+
+    /// The role of the value under `key`.
+    pub(crate) fn child(self, key: &str) -> ValueRole {
+        use ValueRole::*;
+        match self {
+            Data => Data,
+            SchemaNames => Schema,
+            LinkNames => Link,
+            CallbackNames => Names,
+            Names if key.starts_with("x-") => Data,
+            Names => Object,
+            _ if key.starts_with("x-") => Data,
+            Object => match key {
+                "schema" => Schema,
+                "schemas" => SchemaNames,
+                "links" => LinkNames,
+                "callbacks" => CallbackNames,
+                // Those of server variables, besides examples.
+                "example" | "value" | "default" | "enum" => Data,
+                "paths" | "webhooks" | "responses" | "content" | "headers" | "encoding" | "examples"
+                | "parameters" | "requestBodies" | "securitySchemes" | "pathItems" | "variables"
+                | "scopes" | "mapping" => Names,
+                _ => Object,
+            },
+            Schema => match key {
+                "example" | "examples" | "default" | "enum" | "const" => Data,
+                "properties" | "patternProperties" | "$defs" | "definitions" | "dependentSchemas" => SchemaNames,
+                "items" | "additionalItems" | "prefixItems" | "additionalProperties" | "not" | "allOf"
+                | "oneOf" | "anyOf" | "if" | "then" | "else" | "contains" | "propertyNames"
+                | "contentSchema" | "unevaluatedItems" | "unevaluatedProperties" => Schema,
+                _ => Object,
+            },
+            Link => match key {
+                "parameters" | "requestBody" => Data,
+                _ => Object,
+            },
+        }
+    }
+
+    /// The role of the items of an array with this role, e.g. the parameters
+    /// of an operation or the members of `allOf`.
+    pub(crate) fn item(self) -> ValueRole {
+        match self {
+            ValueRole::Names => ValueRole::Object,
+            ValueRole::SchemaNames => ValueRole::Schema,
+            ValueRole::LinkNames => ValueRole::Link,
+            role => role,
+        }
+    }
+}
+
+impl OpenAPI {
+    /// A copy of the spec with every `$ref` replaced by a deep copy of what it
+    /// points to, anywhere in the document, for code generators that can't follow
+    /// references. Keys besides `$ref` in a reference object, like `description`,
+    /// override those of the copy. Cyclic references are handled as `cycles` says;
+    /// `components` is kept, dereferenced too, as discriminator mappings and kept
+    /// cyclic references still point into it.
+    pub fn dereference(&self, cycles: CyclicRefs) -> Result<OpenAPI, DereferenceError> {
+        let source = serde_json::to_value(self).map_err(DereferenceError::Invalid)?;
+        let inliner = || Inliner { source: &source, cycles, stack: Vec::new(), done: HashMap::new() };
+        let mut value = source.clone();
+        // With the `rayon` feature, path items are inlined in parallel, each
+        // thread with its own cache, and the rest of the document beside them.
+        #[cfg(feature = "rayon")]
+        let paths = value.as_object_mut().and_then(|map| map.remove("paths"));
+        inliner().expand(&mut value, ValueRole::Object)?;
+        #[cfg(feature = "rayon")]
+        if let Some(mut paths) = paths {
+            use rayon::prelude::*;
+            if let Value::Object(items) = &mut paths {
+                let mut items: Vec<_> = items.iter_mut().filter(|(path, _)| !path.starts_with("x-")).map(|(_, item)| item).collect();
+                items.par_iter_mut()
+                    .map_init(inliner, |inliner, item| inliner.expand(item, ValueRole::Object))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .collect::<Result<(), _>>()?;
+            } else {
+                inliner().expand(&mut paths, ValueRole::Names)?;
+            }
+            value["paths"] = paths;
+        }
+        serde_json::from_value(value).map_err(DereferenceError::Invalid)
+    }
+}
+
+struct Inliner<'a> {
+    source: &'a Value,
+    cycles: CyclicRefs,
+    /// The references being inlined, outermost first.
+    stack: Vec<String>,
+    done: HashMap<String, Value>,
+}
+
+impl Inliner<'_> {
+    /// Inlines the references in `value`, which has the given role.
+    fn expand(&mut self, value: &mut Value, role: ValueRole) -> Result<(), ResolveError> {
+        match value {
+            Value::Object(map) => {
+                if let (true, Some(Value::String(reference))) = (role.is_keywords(), map.get("$ref")) {
+                    let reference = reference.clone();
+                    let Some(mut target) = self.target(&reference, role)? else { return Ok(()) };
+                    if let Value::Object(target) = &mut target {
+                        for (key, sibling) in map.iter().filter(|(key, _)| *key != "$ref") {
+                            target.insert(key.clone(), sibling.clone());
+                        }
+                    }
+                    *value = target;
+                    return Ok(());
+                }
+                for (key, child) in map.iter_mut() {
+                    let role = role.child(key);
+                    if role != ValueRole::Data {
+                        self.expand(child, role)?;
+                    }
+                }
+                Ok(())
+            }
+            Value::Array(items) => items.iter_mut().try_for_each(|item| self.expand(item, role.item())),
+            _ => Ok(()),
+        }
+    }
+
+    /// The dereferenced value `reference` points to, `None` for a cycle to keep.
+    /// `role` is that of the reference, which the target takes.
+    fn target(&mut self, reference: &str, role: ValueRole) -> Result<Option<Value>, ResolveError> {
+        if self.stack.iter().any(|r| r == reference) {
+            return match self.cycles {
+                CyclicRefs::Error => Err(ResolveError::Circular(reference.to_string())),
+                CyclicRefs::Keep => Ok(None),
+            };
+        }
+        if let Some(done) = self.done.get(reference) {
+            return Ok(Some(done.clone()));
+        }
+        let pointer = reference.strip_prefix('#').ok_or_else(|| ResolveError::Unsupported(reference.to_string()))?;
+        let mut target = self.source.pointer(pointer).cloned().ok_or_else(|| ResolveError::NotFound(reference.to_string()))?;
+        self.stack.push(reference.to_string());
+        let expanded = self.expand(&mut target, role);
+        self.stack.pop();
+        expanded?;
+        self.done.insert(reference.to_string(), target.clone());
+        Ok(Some(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_dereference() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {
+                "parameters": [{"$ref": "#/components/parameters/Limit"}],
+                "responses": {
+                    "200": {"description": "", "content": {"application/json": {
+                        "schema": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                        "example": [{"$ref": "not a reference"}]
+                    }}},
+                    "default": {"$ref": "#/components/responses/Error"}
+                }
+            }}},
+            "components": {
+                "responses": {"Error": {"description": "error"}},
+                "parameters": {"Limit": {"name": "limit", "in": "query", "schema": {"$ref": "#/components/schemas/Limit"}}},
+                "schemas": {
+                    "Limit": {"type": "integer", "maximum": 100},
+                    "Pet": {"type": "object", "properties": {"example": {"$ref": "#/components/schemas/Limit"}}},
+                    "Node": {"type": "object", "properties": {"next": {"$ref": "#/components/schemas/Node"}}}
+                }
+            }
+        })).unwrap();
+        let dereferenced = spec.dereference(CyclicRefs::Keep).unwrap();
+        let json = serde_json::to_value(&dereferenced).unwrap();
+        let get = &json["paths"]["/pets"]["get"];
+        assert_eq!(get["parameters"][0]["schema"], json!({"type": "integer", "maximum": 100}));
+        let media = &get["responses"]["200"]["content"]["application/json"];
+        assert_eq!(media["schema"]["items"]["properties"]["example"]["maximum"], 100);
+        assert_eq!(media["example"], json!([{"$ref": "not a reference"}]));
+        assert_eq!(get["responses"]["default"], json!({"description": "error"}));
+        let node = &json["components"]["schemas"]["Node"];
+        assert_eq!(node["properties"]["next"]["properties"]["next"], json!({"$ref": "#/components/schemas/Node"}));
+
+        assert!(matches!(spec.dereference(CyclicRefs::Error), Err(DereferenceError::Resolve(ResolveError::Circular(_)))));
+        let mut dangling = spec.clone();
+        dangling.schemas.insert("Bad".to_string(), RefOr::schema_ref("Missing"));
+        assert!(matches!(dangling.dereference(CyclicRefs::Keep), Err(DereferenceError::Resolve(ResolveError::NotFound(_)))));
+    }
+}
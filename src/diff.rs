@@ -0,0 +1,440 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::*;
+
+/// The changes from one version of a spec to the next, from [diff].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    /// Whether any change may break clients of the old version, e.g. to fail CI on.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.breaking)
+    }
+
+    pub fn breaking_changes(&self) -> impl Iterator<Item=&Change> {
+        self.changes.iter().filter(|change| change.breaking)
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// A change found by [diff].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    /// JSON pointer to what changed, in the new spec, or in the old one for what
+    /// was removed. Pointers into schemas go through `$ref`s as if they were
+    /// inlined, e.g. `/paths/~1pets/get/responses/200/content/application~1json/schema/properties/name`.
+    pub pointer: String,
+    pub kind: ChangeKind,
+    /// Whether clients written against the old spec may fail against the new one.
+    pub breaking: bool,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.kind)?;
+        if self.breaking {
+            f.write_str(" (breaking)")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a [Change] is. Parameters are identified by name and location, e.g.
+/// `query`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+pub enum ChangeKind {
+    PathAdded,
+    PathRemoved,
+    OperationAdded,
+    OperationRemoved,
+    ParameterAdded { name: String, location: String, required: bool },
+    ParameterRemoved { name: String, location: String },
+    /// An optional parameter became required.
+    ParameterRequired { name: String, location: String },
+    /// A required parameter became optional.
+    ParameterOptional { name: String, location: String },
+    RequestBodyAdded { required: bool },
+    RequestBodyRemoved,
+    RequestBodyRequired,
+    RequestBodyOptional,
+    ResponseAdded { status: String },
+    ResponseRemoved { status: String },
+    MediaTypeAdded { media_type: String },
+    MediaTypeRemoved { media_type: String },
+    /// The schema's type changed, e.g. from `string` to `integer`, or from a
+    /// type to a composition like `oneOf`.
+    TypeChanged { from: String, to: String },
+    EnumValuesAdded { values: Vec<Value> },
+    /// The enum was narrowed.
+    EnumValuesRemoved { values: Vec<Value> },
+    PropertyAdded { name: String, required: bool },
+    PropertyRemoved { name: String },
+    /// An optional property became required.
+    PropertyRequired { name: String },
+    /// A required property became optional.
+    PropertyOptional { name: String },
+    NullableAdded,
+    NullableRemoved,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = |values: &[Value]| values.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        match self {
+            ChangeKind::PathAdded => write!(f, "path added"),
+            ChangeKind::PathRemoved => write!(f, "path removed"),
+            ChangeKind::OperationAdded => write!(f, "operation added"),
+            ChangeKind::OperationRemoved => write!(f, "operation removed"),
+            ChangeKind::ParameterAdded { name, location, required: true } => write!(f, "required {} parameter {:?} added", location, name),
+            ChangeKind::ParameterAdded { name, location, required: false } => write!(f, "{} parameter {:?} added", location, name),
+            ChangeKind::ParameterRemoved { name, location } => write!(f, "{} parameter {:?} removed", location, name),
+            ChangeKind::ParameterRequired { name, location } => write!(f, "{} parameter {:?} became required", location, name),
+            ChangeKind::ParameterOptional { name, location } => write!(f, "{} parameter {:?} became optional", location, name),
+            ChangeKind::RequestBodyAdded { required: true } => write!(f, "required request body added"),
+            ChangeKind::RequestBodyAdded { required: false } => write!(f, "request body added"),
+            ChangeKind::RequestBodyRemoved => write!(f, "request body removed"),
+            ChangeKind::RequestBodyRequired => write!(f, "request body became required"),
+            ChangeKind::RequestBodyOptional => write!(f, "request body became optional"),
+            ChangeKind::ResponseAdded { status } => write!(f, "{} response added", status),
+            ChangeKind::ResponseRemoved { status } => write!(f, "{} response removed", status),
+            ChangeKind::MediaTypeAdded { media_type } => write!(f, "media type {} added", media_type),
+            ChangeKind::MediaTypeRemoved { media_type } => write!(f, "media type {} removed", media_type),
+            ChangeKind::TypeChanged { from, to } => write!(f, "type changed from {} to {}", from, to),
+            ChangeKind::EnumValuesAdded { values: added } => write!(f, "enum values added: {}", values(added)),
+            ChangeKind::EnumValuesRemoved { values: removed } => write!(f, "enum values removed: {}", values(removed)),
+            ChangeKind::PropertyAdded { name, required: true } => write!(f, "required property {:?} added", name),
+            ChangeKind::PropertyAdded { name, required: false } => write!(f, "property {:?} added", name),
+            ChangeKind::PropertyRemoved { name } => write!(f, "property {:?} removed", name),
+            ChangeKind::PropertyRequired { name } => write!(f, "property {:?} became required", name),
+            ChangeKind::PropertyOptional { name } => write!(f, "property {:?} became optional", name),
+            ChangeKind::NullableAdded => write!(f, "became nullable"),
+            ChangeKind::NullableRemoved => write!(f, "is no longer nullable"),
+        }
+    }
+}
+
+/// Whether a schema describes what clients send or what they receive, which
+/// decides whether narrowing or widening it breaks them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Request,
+    Response,
+}
+
+/// Compares two versions of a spec, operation by operation: their parameters,
+/// request bodies and responses, down to the schemas of each, following `$ref`s.
+/// A change is breaking if clients of `old` may fail against `new`: anything
+/// removed, new requirements on requests, such as a required parameter or a
+/// narrowed enum, and fewer guarantees about responses, such as a property no
+/// longer required or a new enum value.
+pub fn diff(old: &OpenAPI, new: &OpenAPI) -> Diff {
+    let mut differ = Differ { old, new, changes: Vec::new(), seen: HashSet::new() };
+    differ.paths();
+    Diff { changes: differ.changes }
+}
+
+struct Differ<'a> {
+    old: &'a OpenAPI,
+    new: &'a OpenAPI,
+    changes: Vec<Change>,
+    /// Pairs of referenced schemas already compared, so that each is reported
+    /// once, and cycles end.
+    seen: HashSet<(String, String, Direction)>,
+}
+
+impl<'a> Differ<'a> {
+    fn change(&mut self, pointer: String, kind: ChangeKind, breaking: bool) {
+        self.changes.push(Change { pointer, kind, breaking });
+    }
+
+    fn paths(&mut self) {
+        let (old_spec, new_spec) = (self.old, self.new);
+        let (old, new) = (old_spec.resolved(), new_spec.resolved());
+        for path in old_spec.paths.paths.keys().filter(|p| !new_spec.paths.paths.contains_key(*p)) {
+            self.change(child("/paths", path), ChangeKind::PathRemoved, true);
+        }
+        for (path, item) in &new_spec.paths.paths {
+            let pointer = child("/paths", path);
+            let Some(old_item) = old_spec.paths.paths.get(path) else {
+                self.change(pointer, ChangeKind::PathAdded, false);
+                continue;
+            };
+            let (Some(old_item), Some(item)) = (old_item.as_item(), item.as_item()) else { continue };
+            for (method, _) in old_item.iter().filter(|(m, _)| !item.iter().any(|(method, _)| method == *m)) {
+                self.change(child(&pointer, method), ChangeKind::OperationRemoved, true);
+            }
+            for (method, _) in item.iter() {
+                let pointer = child(&pointer, method);
+                match (old.operation(path, method), new.operation(path, method)) {
+                    (Some(old_op), Some(new_op)) => self.operation(&pointer, &old_op, &new_op),
+                    (None, Some(_)) => self.change(pointer, ChangeKind::OperationAdded, false),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn operation(&mut self, pointer: &str, old: &ResolvedOperation<'a>, new: &ResolvedOperation<'a>) {
+        if let (Ok(old_params), Ok(new_params)) = (old.parameters(), new.parameters()) {
+            self.parameters(pointer, &old_params, &new_params);
+        }
+        if let (Ok(old_body), Ok(new_body)) = (old.request_body(), new.request_body()) {
+            let body = child(pointer, "requestBody");
+            match (old_body, new_body) {
+                (Some(_), None) => self.change(body, ChangeKind::RequestBodyRemoved, true),
+                (None, Some(new_body)) => self.change(body, ChangeKind::RequestBodyAdded { required: new_body.required }, new_body.required),
+                (Some(old_body), Some(new_body)) => {
+                    if !old_body.required && new_body.required {
+                        self.change(body.clone(), ChangeKind::RequestBodyRequired, true);
+                    } else if old_body.required && !new_body.required {
+                        self.change(body.clone(), ChangeKind::RequestBodyOptional, false);
+                    }
+                    self.content(&child(&body, "content"), &old_body.content, &new_body.content, Direction::Request);
+                }
+                (None, None) => {}
+            }
+        }
+        if let (Ok(old_responses), Ok(new_responses)) = (old.responses(), new.responses()) {
+            let responses = child(pointer, "responses");
+            let status = |status: Option<&StatusCode>| status.map_or_else(|| "default".to_string(), ToString::to_string);
+            for (code, _) in old_responses.iter().filter(|(code, _)| !new_responses.iter().any(|(c, _)| c == code)) {
+                let status = status(*code);
+                self.change(child(&responses, &status), ChangeKind::ResponseRemoved { status }, true);
+            }
+            for (code, response) in &new_responses {
+                let status = status(*code);
+                let pointer = child(&responses, &status);
+                match old_responses.iter().find(|(c, _)| c == code) {
+                    Some((_, old_response)) => self.content(&child(&pointer, "content"), &old_response.content, &response.content, Direction::Response),
+                    None => self.change(pointer, ChangeKind::ResponseAdded { status }, false),
+                }
+            }
+        }
+    }
+
+    fn parameters(&mut self, pointer: &str, old: &[&Parameter], new: &[&Parameter]) {
+        let pointer = child(pointer, "parameters");
+        let same = |a: &Parameter, b: &Parameter| a.name == b.name && a.kind.location() == b.kind.location();
+        for parameter in old.iter().filter(|p| !new.iter().any(|n| same(p, n))) {
+            let (name, location) = (parameter.name.clone(), parameter.kind.location().to_string());
+            self.change(child(&pointer, &name), ChangeKind::ParameterRemoved { name, location }, true);
+        }
+        for parameter in new {
+            let (name, location) = (parameter.name.clone(), parameter.kind.location().to_string());
+            let at = child(&pointer, &name);
+            let Some(old_parameter) = old.iter().find(|o| same(o, parameter)) else {
+                let required = parameter.required;
+                self.change(at, ChangeKind::ParameterAdded { name, location, required }, required);
+                continue;
+            };
+            if !old_parameter.required && parameter.required {
+                self.change(at.clone(), ChangeKind::ParameterRequired { name, location }, true);
+            } else if old_parameter.required && !parameter.required {
+                self.change(at.clone(), ChangeKind::ParameterOptional { name, location }, false);
+            }
+            match (&old_parameter.format, &parameter.format) {
+                (ParameterSchemaOrContent::Schema(old_schema), ParameterSchemaOrContent::Schema(new_schema)) => {
+                    self.schema(&child(&at, "schema"), old_schema, new_schema, Direction::Request);
+                }
+                (ParameterSchemaOrContent::Content(old_content), ParameterSchemaOrContent::Content(new_content)) => {
+                    self.content(&child(&at, "content"), old_content, new_content, Direction::Request);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn content(&mut self, pointer: &str, old: &Content, new: &Content, direction: Direction) {
+        for media_type in old.keys().filter(|m| !new.contains_key(*m)) {
+            self.change(child(pointer, media_type), ChangeKind::MediaTypeRemoved { media_type: media_type.clone() }, true);
+        }
+        for (media_type, media) in new {
+            let at = child(pointer, media_type);
+            let Some(old_media) = old.get(media_type) else {
+                self.change(at, ChangeKind::MediaTypeAdded { media_type: media_type.clone() }, false);
+                continue;
+            };
+            if let (Some(old_schema), Some(new_schema)) = (&old_media.schema, &media.schema) {
+                self.schema(&child(&at, "schema"), old_schema, new_schema, direction);
+            }
+        }
+    }
+
+    fn schema(&mut self, pointer: &str, old: &RefOr<Schema>, new: &RefOr<Schema>, direction: Direction) {
+        if let (Some(old_ref), Some(new_ref)) = (old.as_ref_str(), new.as_ref_str()) {
+            if !self.seen.insert((old_ref.to_string(), new_ref.to_string(), direction)) {
+                return;
+            }
+        }
+        let (Ok(old), Ok(new)) = (old.try_resolve(self.old), new.try_resolve(self.new)) else { return };
+        let request = direction == Direction::Request;
+
+        let (from, to) = (kind_name(&old.kind), kind_name(&new.kind));
+        if from != to {
+            // Integers are numbers, so requests may widen to them and responses narrow.
+            let widened = from == "integer" && to == "number";
+            let narrowed = from == "number" && to == "integer";
+            let breaking = !(request && widened || !request && narrowed);
+            self.change(pointer.to_string(), ChangeKind::TypeChanged { from: from.to_string(), to: to.to_string() }, breaking);
+            return;
+        }
+        if old.nullable != new.nullable {
+            let kind = if new.nullable { ChangeKind::NullableAdded } else { ChangeKind::NullableRemoved };
+            self.change(pointer.to_string(), kind, new.nullable != request);
+        }
+
+        let old_values = old.enum_values().unwrap_or_default();
+        let new_values = new.enum_values().unwrap_or_default();
+        if !old_values.is_empty() || !new_values.is_empty() {
+            // No enum at all admits every value.
+            let removed: Vec<Value> = if new_values.is_empty() { vec![] } else { old_values.iter().filter(|v| !new_values.contains(v)).cloned().collect() };
+            let added: Vec<Value> = if old_values.is_empty() { vec![] } else { new_values.iter().filter(|v| !old_values.contains(v)).cloned().collect() };
+            if !removed.is_empty() {
+                self.change(child(pointer, "enum"), ChangeKind::EnumValuesRemoved { values: removed }, request);
+            }
+            if !added.is_empty() {
+                self.change(child(pointer, "enum"), ChangeKind::EnumValuesAdded { values: added }, !request);
+            }
+        }
+
+        if let (Some(old_properties), Some(new_properties)) = (old.get_properties(), new.get_properties()) {
+            let properties = child(pointer, "properties");
+            let old_required = old.get_required().map_or(&[][..], Vec::as_slice);
+            let new_required = new.get_required().map_or(&[][..], Vec::as_slice);
+            for name in old_properties.keys().filter(|name| !new_properties.contains_key(*name)) {
+                self.change(child(&properties, name), ChangeKind::PropertyRemoved { name: name.clone() }, !request);
+            }
+            for (name, property) in new_properties {
+                let at = child(&properties, name);
+                let required = new_required.contains(name);
+                let Some(old_property) = old_properties.get(name) else {
+                    self.change(at, ChangeKind::PropertyAdded { name: name.clone(), required }, request && required);
+                    continue;
+                };
+                let was_required = old_required.contains(name);
+                if !was_required && required {
+                    self.change(at.clone(), ChangeKind::PropertyRequired { name: name.clone() }, request);
+                } else if was_required && !required {
+                    self.change(at.clone(), ChangeKind::PropertyOptional { name: name.clone() }, !request);
+                }
+                self.schema(&at, old_property, property, direction);
+            }
+        }
+
+        if let (Some(old_items), Some(new_items)) = (items(old), items(new)) {
+            self.schema(&child(pointer, "items"), old_items, new_items, direction);
+        }
+    }
+}
+
+fn kind_name(kind: &SchemaKind) -> &'static str {
+    match kind {
+        SchemaKind::Type(Type::String(_)) => "string",
+        SchemaKind::Type(Type::Number(_)) => "number",
+        SchemaKind::Type(Type::Integer(_)) => "integer",
+        SchemaKind::Type(Type::Boolean {}) => "boolean",
+        SchemaKind::Type(Type::Object(_)) => "object",
+        SchemaKind::Type(Type::Array(_)) => "array",
+        SchemaKind::OneOf { .. } => "oneOf",
+        SchemaKind::AllOf { .. } => "allOf",
+        SchemaKind::AnyOf { .. } => "anyOf",
+        SchemaKind::Not { .. } => "not",
+        SchemaKind::Any(_) => "any",
+    }
+}
+
+fn items(schema: &Schema) -> Option<&RefOr<Schema>> {
+    match &schema.kind {
+        SchemaKind::Type(Type::Array(a)) => a.items.as_deref(),
+        SchemaKind::Any(a) => a.items.as_deref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let spec = |pet: Value, parameters: Value| -> OpenAPI {
+            serde_json::from_value(json!({
+                "openapi": "3.0.3",
+                "info": {"title": "t", "version": "1"},
+                "paths": {
+                    "/pets": {
+                        "get": {
+                            "parameters": parameters,
+                            "responses": {"200": {"description": "", "content": {"application/json": {"schema": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}}}}}}
+                        },
+                        "post": {
+                            "requestBody": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}},
+                            "responses": {"201": {"description": ""}}
+                        }
+                    }
+                },
+                "components": {"schemas": {"Pet": pet}}
+            })).unwrap()
+        };
+        let old = spec(
+            json!({"type": "object", "required": ["name", "tag"], "properties": {
+                "name": {"type": "string"},
+                "tag": {"type": "string"},
+                "kind": {"type": "string", "enum": ["cat", "dog"]}
+            }}),
+            json!([{"name": "limit", "in": "query", "schema": {"type": "integer"}}]),
+        );
+        let new = spec(
+            json!({"type": "object", "required": ["name"], "properties": {
+                "name": {"type": "string"},
+                "tag": {"type": "string"},
+                "kind": {"type": "string", "enum": ["cat", "bird"]}
+            }}),
+            json!([
+                {"name": "limit", "in": "query", "required": true, "schema": {"type": "number"}},
+                {"name": "cursor", "in": "query", "schema": {"type": "string"}}
+            ]),
+        );
+        assert_eq!(diff(&old, &old), Diff::default());
+
+        let diff = diff(&old, &new);
+        let changes: Vec<String> = diff.changes.iter().map(ToString::to_string).collect();
+        assert_eq!(changes, [
+            "/paths/~1pets/get/parameters/limit: query parameter \"limit\" became required (breaking)",
+            "/paths/~1pets/get/parameters/limit/schema: type changed from integer to number",
+            "/paths/~1pets/get/parameters/cursor: query parameter \"cursor\" added",
+            "/paths/~1pets/get/responses/200/content/application~1json/schema/items/properties/tag: property \"tag\" became optional (breaking)",
+            "/paths/~1pets/get/responses/200/content/application~1json/schema/items/properties/kind/enum: enum values removed: \"dog\"",
+            "/paths/~1pets/get/responses/200/content/application~1json/schema/items/properties/kind/enum: enum values added: \"bird\" (breaking)",
+            "/paths/~1pets/post/requestBody/content/application~1json/schema/properties/tag: property \"tag\" became optional",
+            "/paths/~1pets/post/requestBody/content/application~1json/schema/properties/kind/enum: enum values removed: \"dog\" (breaking)",
+            "/paths/~1pets/post/requestBody/content/application~1json/schema/properties/kind/enum: enum values added: \"bird\"",
+        ]);
+        assert!(diff.is_breaking());
+
+        let mut removed = new.clone();
+        removed.paths.paths["/pets"].as_mut().unwrap().post = None;
+        removed.paths.paths.insert("/owners".to_string(), RefOr::Item(PathItem::default()));
+        let changes: Vec<String> = super::diff(&new, &removed).changes.iter().map(ToString::to_string).collect();
+        assert_eq!(changes, ["/paths/~1pets/post: operation removed (breaking)", "/paths/~1owners: path added"]);
+    }
+}
@@ -1,6 +1,9 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::instance_validation::resolve_schema;
+use crate::*;
+
 /// When request bodies or response payloads may be one of a number of different schemas,
 /// a discriminator object can be used to aid in serialization, deserialization,
 /// and validation. The discriminator is a specific object in a schema which is
@@ -9,6 +12,8 @@ use serde::{Deserialize, Serialize};
 ///
 /// When using the discriminator, inline schemas will not be considered.
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Discriminator {
     /// REQUIRED. The name of the property in the payload that
@@ -19,5 +24,83 @@ pub struct Discriminator {
     pub mapping: IndexMap<String, String>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+impl Discriminator {
+    /// The `$ref` of the schema for payloads whose discriminator property is
+    /// `value`: the schema its `mapping` gives, by name or reference, or else
+    /// the component schema named `value`.
+    pub fn reference_for(&self, value: &str) -> String {
+        let target = self.mapping.get(value).map(String::as_str).unwrap_or(value);
+        if target.starts_with('#') {
+            target.to_string()
+        } else {
+            format!("#/components/schemas/{}", target)
+        }
+    }
+
+    /// The member among `members`, of a `oneOf` or `anyOf`, for payloads whose
+    /// discriminator property is `value`, see [Discriminator::reference_for].
+    /// Inline members aren't considered.
+    pub fn select<'a>(&self, value: &str, members: &'a [RefOr<Schema>]) -> Option<&'a RefOr<Schema>> {
+        let reference = self.reference_for(value);
+        members.iter().find(|member| member.as_ref_str() == Some(reference.as_str()))
+    }
+}
+
+impl Schema {
+    /// The concrete schema for payloads whose discriminator property is
+    /// `value`: the member of the `oneOf` or `anyOf` of the schema that its
+    /// discriminator selects, or, for a discriminator on a base schema that
+    /// others extend with `allOf`, the component schema `value` maps to.
+    /// `None` if the schema has no discriminator or `value` selects nothing.
+    pub fn resolve_discriminator<'a>(&'a self, value: &str, components: &'a Components) -> Option<&'a Schema> {
+        let discriminator = self.discriminator.as_ref()?;
+        let member = match &self.kind {
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => discriminator.select(value, members)?,
+            _ => return resolve_schema(&discriminator.reference_for(value), components),
+        };
+        match member {
+            RefOr::Reference { reference } => resolve_schema(reference, components),
+            RefOr::Item(schema) => Some(schema),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_discriminator() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "oneOf": [{"$ref": "#/components/schemas/Cat"}, {"$ref": "#/components/schemas/Dog"}],
+                    "discriminator": {"propertyName": "kind", "mapping": {"dog": "Dog", "cat": "#/components/schemas/Cat"}}
+                },
+                "Base": {"type": "object", "discriminator": {"propertyName": "kind"}},
+                "Cat": {"title": "Cat", "type": "object"},
+                "Dog": {"title": "Dog", "type": "object"}
+            }}
+        })).unwrap();
+        let components = &spec.components;
+        let pet = spec.schemas["Pet"].as_item().unwrap();
+        let title = |schema: Option<&Schema>| schema.and_then(|s| s.title.clone());
+        assert_eq!(title(pet.resolve_discriminator("dog", components)), Some("Dog".to_string()));
+        assert_eq!(title(pet.resolve_discriminator("cat", components)), Some("Cat".to_string()));
+        assert_eq!(title(pet.resolve_discriminator("Dog", components)), Some("Dog".to_string()));
+        assert_eq!(pet.resolve_discriminator("Fish", components), None);
+
+        let base = spec.schemas["Base"].as_item().unwrap();
+        assert_eq!(title(base.resolve_discriminator("Cat", components)), Some("Cat".to_string()));
+        assert_eq!(base.discriminator.as_ref().unwrap().reference_for("Cat"), "#/components/schemas/Cat");
+    }
+}
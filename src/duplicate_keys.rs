@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Error as _, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+
+use crate::ParseError;
+use crate::example_validation::child;
+
+/// Walks a document without building it, returning an error for every object key
+/// that appears more than once in the same object. Positions aren't tracked, so
+/// only the pointer of each duplicate is reported.
+pub(crate) fn find_duplicate_keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ParseError>, D::Error> {
+    let mut found = Vec::new();
+    Node { pointer: String::new(), found: &mut found }.deserialize(deserializer)?;
+    Ok(found)
+}
+
+struct Node<'a> {
+    pointer: String,
+    found: &'a mut Vec<ParseError>,
+}
+
+impl<'de> DeserializeSeed<'de> for Node<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Node<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> { Ok(()) }
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> { Ok(()) }
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> { Ok(()) }
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> { Ok(()) }
+    fn visit_str<E>(self, _: &str) -> Result<(), E> { Ok(()) }
+    fn visit_unit<E>(self) -> Result<(), E> { Ok(()) }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        while seq.next_element_seed(Node { pointer: format!("{}/{}", self.pointer, index), found: &mut *self.found })?.is_some() {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = HashSet::new();
+        while let Some(Key(key)) = map.next_key()? {
+            let pointer = child(&self.pointer, &key);
+            if !seen.insert(key.clone()) {
+                self.found.push(ParseError {
+                    pointer: pointer.clone(),
+                    line: None,
+                    column: None,
+                    message: format!("duplicate key `{}`", key),
+                });
+            }
+            map.next_value_seed(Node { pointer, found: &mut *self.found })?;
+        }
+        Ok(())
+    }
+}
+
+/// An object key. YAML allows scalar keys of any type, e.g. status codes read as
+/// integers, so they are compared by their text.
+struct Key(String);
+
+impl<'de> serde::Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a scalar key")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Key, E> { Ok(Key(v.to_string())) }
+    fn visit_i64<E>(self, v: i64) -> Result<Key, E> { Ok(Key(v.to_string())) }
+    fn visit_u64<E>(self, v: u64) -> Result<Key, E> { Ok(Key(v.to_string())) }
+    fn visit_f64<E>(self, v: f64) -> Result<Key, E> { Ok(Key(v.to_string())) }
+    fn visit_str<E>(self, v: &str) -> Result<Key, E> { Ok(Key(v.to_string())) }
+    fn visit_unit<E>(self) -> Result<Key, E> { Ok(Key("null".to_string())) }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, _: A) -> Result<Key, A::Error> {
+        Err(A::Error::custom("sequences are not supported as keys"))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, _: A) -> Result<Key, A::Error> {
+        Err(A::Error::custom("mappings are not supported as keys"))
+    }
+}
@@ -0,0 +1,480 @@
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::dereference::ValueRole;
+use crate::{Format, OpenAPI};
+
+/// Formatting choices for [OpenAPI::to_string_with].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerializeOptions {
+    pub format: Format,
+    /// Spaces per nesting level. For JSON, `0` writes everything on one line.
+    pub indent: usize,
+    pub key_order: KeyOrder,
+    /// Write `components: {}` even when there are no components.
+    pub emit_empty_components: bool,
+    /// How YAML strings are quoted. Ignored for JSON.
+    pub yaml_quote_style: QuoteStyle,
+    /// Quote YAML strings that look like version numbers, e.g. `openapi: "3.0.3"`,
+    /// though plain `3.0.3` reads as a string too, for tools that read it otherwise.
+    pub quote_versions: bool,
+    /// Leave out fields equal to their specification default (`required: false`,
+    /// `style: form` on a query parameter, ...) and empty optional collections.
+    /// Fields the model keeps as written, like a parameter's `explode`, are kept,
+    /// so that the output parses back to the same spec.
+    pub omit_defaults: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            format: Format::Json,
+            indent: 2,
+            key_order: KeyOrder::Preserve,
+            emit_empty_components: false,
+            yaml_quote_style: QuoteStyle::Minimal,
+            quote_versions: false,
+            omit_defaults: false,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// The smallest output: single-line JSON without default-valued fields.
+    pub fn compact() -> Self {
+        SerializeOptions { indent: 0, omit_defaults: true, ..SerializeOptions::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// Keys in model order: fixed fields in specification order, maps in insertion order.
+    Preserve,
+    /// Keys sorted alphabetically at every level.
+    Alphabetical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Quote only strings that would otherwise read as another type or be invalid
+    /// YAML, using double quotes.
+    Minimal,
+    /// Single-quote every string, falling back to double quotes for strings with
+    /// line breaks or control characters.
+    Single,
+    /// Double-quote every string.
+    Double,
+}
+
+impl OpenAPI {
+    /// Serializes the spec according to `options`.
+    pub fn to_string_with(&self, options: &SerializeOptions) -> Result<String, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        if let Value::Object(map) = &mut value {
+            if options.emit_empty_components && !map.contains_key("components") {
+                map.insert("components".to_string(), Value::Object(Map::new()));
+            }
+        }
+        write_value(value, options)
+    }
+
+    /// [OpenAPI::to_string_with] in YAML, whatever the format of `options`.
+    pub fn to_yaml_string_with(&self, options: &SerializeOptions) -> Result<String, serde_json::Error> {
+        self.to_string_with(&SerializeOptions { format: Format::Yaml, ..options.clone() })
+    }
+
+    /// [OpenAPI::to_string_with] in JSON, whatever the format of `options`.
+    pub fn to_json_string_with(&self, options: &SerializeOptions) -> Result<String, serde_json::Error> {
+        self.to_string_with(&SerializeOptions { format: Format::Json, ..options.clone() })
+    }
+
+    /// Serializes the spec into a byte-stable form, suitable for hashing, caching
+    /// and signing: object keys sorted, no whitespace, and numbers normalized so that
+    /// integral values are written without a fraction (`1.0` becomes `1`).
+    pub fn to_canonical_json(&self) -> Result<String, serde_json::Error> {
+        let value = serde_json::to_value(self)?;
+        serde_json::to_string(&canonicalize(value))
+    }
+}
+
+/// Writes an already serialized document (or a fragment of one) according to `options`.
+pub(crate) fn write_value(mut value: Value, options: &SerializeOptions) -> Result<String, serde_json::Error> {
+    if options.omit_defaults {
+        omit_defaults(&mut value, ValueRole::Object, None);
+    }
+    if options.key_order == KeyOrder::Alphabetical {
+        value = sort_keys(value);
+    }
+    match options.format {
+        Format::Json if options.indent == 0 => serde_json::to_string(&value),
+        Format::Json => {
+            let indent = " ".repeat(options.indent);
+            let mut out = Vec::new();
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            value.serialize(&mut serde_json::Serializer::with_formatter(&mut out, formatter))?;
+            Ok(String::from_utf8(out).expect("serde_json writes UTF-8"))
+        }
+        Format::Yaml => {
+            let mut writer = YamlWriter {
+                out: String::new(),
+                indent: options.indent.max(1),
+                quote: options.yaml_quote_style,
+                quote_versions: options.quote_versions,
+            };
+            writer.document(&value);
+            Ok(writer.out)
+        }
+    }
+}
+
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => sort_entries(map, canonicalize),
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        Value::Number(n) => Value::Number(normalize_number(n)),
+        v => v,
+    }
+}
+
+/// Optional collections the specification treats the same whether empty or absent.
+/// `paths`, `responses`, `scopes`, `security` and schema-valued keys are not listed,
+/// since an empty value there is meaningful, and neither are server `variables`,
+/// which the model keeps apart from absent ones.
+const OPTIONAL_COLLECTIONS: &[&str] = &[
+    "components", "schemas", "securitySchemes", "requestBodies", "servers", "tags", "parameters",
+    "examples", "headers", "links", "callbacks", "encoding", "content", "properties",
+    "required", "allOf", "oneOf", "anyOf", "mapping", "extensions",
+];
+
+/// Flags defaulting to false that read back the same when left out. `deprecated`
+/// and `allowEmptyValue` of parameters and headers, like a parameter's `explode`,
+/// are kept as written by the model, so they are kept here too.
+const FALSE_DEFAULTS: &[&str] = &["required", "allowReserved", "nullable", "readOnly", "writeOnly"];
+
+/// Removes default-valued fields from a serialized document. `role` is that of
+/// `value`, and `parent_key` the key of the keyword under which it (or the map
+/// of names containing it) appears, which tells header objects apart from other
+/// objects. The keys of maps of names, like `properties`, are left alone,
+/// as only keywords have defaults.
+fn omit_defaults(value: &mut Value, role: ValueRole, parent_key: Option<&str>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let child_role = role.child(key);
+                if child_role != ValueRole::Data {
+                    omit_defaults(child, child_role, if role.is_keywords() { Some(key) } else { parent_key });
+                }
+            }
+            if !role.is_keywords() {
+                return;
+            }
+            let default_style = match (map.get("in").and_then(Value::as_str), parent_key) {
+                (Some("query"), _) | (Some("cookie"), _) => Some("form"),
+                (Some("path"), _) | (Some("header"), _) => Some("simple"),
+                (None, Some("headers")) => Some("simple"),
+                _ => None,
+            };
+            if default_style.is_some() && map.get("style").and_then(Value::as_str) == default_style {
+                map.remove("style");
+            }
+            map.retain(|key, v| {
+                let default_false = FALSE_DEFAULTS.contains(&key.as_str()) && v == &Value::Bool(false);
+                let empty = OPTIONAL_COLLECTIONS.contains(&key.as_str()) && match v {
+                    Value::Object(m) => m.is_empty(),
+                    Value::Array(a) => a.is_empty(),
+                    _ => false,
+                };
+                !(default_false || empty)
+            });
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| omit_defaults(v, role.item(), parent_key)),
+        _ => {}
+    }
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => sort_entries(map, sort_keys),
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        v => v,
+    }
+}
+
+fn sort_entries(map: Map<String, Value>, f: fn(Value) -> Value) -> Value {
+    let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Value::Object(entries.into_iter().map(|(k, v)| (k, f(v))).collect())
+}
+
+fn normalize_number(n: Number) -> Number {
+    match n.as_f64() {
+        Some(f) if !n.is_i64() && !n.is_u64() && f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 => {
+            Number::from(f as i64)
+        }
+        _ => n,
+    }
+}
+
+/// Writes block-style YAML. Empty collections are written in flow style (`{}`, `[]`).
+struct YamlWriter {
+    out: String,
+    indent: usize,
+    quote: QuoteStyle,
+    quote_versions: bool,
+}
+
+impl YamlWriter {
+    fn document(&mut self, value: &Value) {
+        if is_block(value) {
+            self.block(value, 0, false);
+        } else {
+            self.scalar(value);
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes a non-empty mapping or sequence whose lines start at column `col`.
+    /// With `inline_first`, the first line continues the current one (after `- `).
+    fn block(&mut self, value: &Value, col: usize, inline_first: bool) {
+        match value {
+            Value::Object(map) => {
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 || !inline_first {
+                        self.pad(col);
+                    }
+                    self.string(key);
+                    self.out.push(':');
+                    self.child(value, col + self.indent);
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 || !inline_first {
+                        self.pad(col);
+                    }
+                    self.out.push_str("- ");
+                    if is_block(item) {
+                        self.block(item, col + 2, true);
+                    } else {
+                        self.scalar(item);
+                        self.out.push('\n');
+                    }
+                }
+            }
+            _ => unreachable!("block() is only called on collections"),
+        }
+    }
+
+    fn child(&mut self, value: &Value, col: usize) {
+        if is_block(value) {
+            self.out.push('\n');
+            self.block(value, col, false);
+        } else {
+            self.out.push(' ');
+            self.scalar(value);
+            self.out.push('\n');
+        }
+    }
+
+    fn pad(&mut self, col: usize) {
+        self.out.push_str(&" ".repeat(col));
+    }
+
+    fn scalar(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.out.push_str("null"),
+            Value::Bool(b) => self.out.push_str(if *b { "true" } else { "false" }),
+            Value::Number(n) => self.out.push_str(&n.to_string()),
+            Value::String(s) => self.string(s),
+            Value::Array(_) => self.out.push_str("[]"),
+            Value::Object(_) => self.out.push_str("{}"),
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        let plain_safe = is_plain_safe(s) && !(self.quote_versions && is_version(s));
+        match self.quote {
+            QuoteStyle::Minimal if plain_safe => self.out.push_str(s),
+            QuoteStyle::Single if !s.chars().any(char::is_control) => {
+                self.out.push('\'');
+                self.out.push_str(&s.replace('\'', "''"));
+                self.out.push('\'');
+            }
+            // A JSON string literal is also a valid YAML double-quoted scalar.
+            _ => self.out.push_str(&Value::from(s).to_string()),
+        }
+    }
+}
+
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether `s` looks like a version number: dot-separated digits, optionally
+/// with a `v` and a pre-release or build suffix, e.g. `3.0.3` or `v2.1-beta`.
+fn is_version(s: &str) -> bool {
+    let s = s.strip_prefix('v').unwrap_or(s);
+    let core = s.split(['-', '+']).next().unwrap_or_default();
+    core.contains('.') && core.split('.').all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Whether `s` can be written as a plain YAML scalar and still read back as the same string.
+fn is_plain_safe(s: &str) -> bool {
+    let first = match s.chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    if s.trim() != s || "-?:,[]{}#&*!|>'\"%@`".contains(first) {
+        return false;
+    }
+    if s.contains(": ") || s.contains(" #") || s.ends_with(':') || s.chars().any(char::is_control) {
+        return false;
+    }
+    let lower = s.to_ascii_lowercase();
+    let reserved = ["true", "false", "yes", "no", "y", "n", "on", "off", "null", "~", ".inf", "+.inf", "-.inf", ".nan"];
+    !(reserved.contains(&lower.as_str()) || lower.parse::<f64>().is_ok() || lower.starts_with("0x") || lower.starts_with("0o"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::Schema;
+
+    #[test]
+    fn test_canonical_json() {
+        let mut a = OpenAPI::default();
+        a.schemas.insert("B", Schema::new_number());
+        a.schemas.insert("A", Schema::new_string());
+        let mut b = OpenAPI::default();
+        b.schemas.insert("A", Schema::new_string());
+        b.schemas.insert("B", Schema::new_number());
+        assert_eq!(a.to_canonical_json().unwrap(), b.to_canonical_json().unwrap());
+        assert_eq!(
+            a.to_canonical_json().unwrap(),
+            r#"{"components":{"schemas":{"A":{"type":"string"},"B":{"type":"number"}}},"info":{"title":"","version":""},"openapi":"3.0.3","paths":{}}"#
+        );
+    }
+
+    fn sample() -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets: the API", "version": "1.0"},
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "tags": ["pets", "it's"],
+                        "responses": {"200": {"description": "line one\nline two"}}
+                    }
+                }
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_to_string_with_json() {
+        let spec = sample();
+        let options = SerializeOptions { indent: 4, key_order: KeyOrder::Alphabetical, emit_empty_components: true, ..SerializeOptions::default() };
+        let out = spec.to_string_with(&options).unwrap();
+        assert!(out.starts_with("{\n    \"components\": {},\n    \"info\": {"));
+        let compact = spec.to_string_with(&SerializeOptions { indent: 0, ..SerializeOptions::default() }).unwrap();
+        assert_eq!(compact, serde_json::to_string(&spec).unwrap());
+    }
+
+    #[test]
+    fn test_to_string_with_yaml() {
+        let spec = sample();
+        let options = SerializeOptions { format: Format::Yaml, ..SerializeOptions::default() };
+        let out = spec.to_string_with(&options).unwrap();
+        assert_eq!(out, r#"openapi: 3.0.3
+info:
+  title: "Pets: the API"
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      tags:
+        - pets
+        - it's
+      responses:
+        "200":
+          description: "line one\nline two"
+"#);
+        for quote in [QuoteStyle::Minimal, QuoteStyle::Single, QuoteStyle::Double] {
+            for indent in [1, 2, 4] {
+                let options = SerializeOptions { format: Format::Yaml, indent, yaml_quote_style: quote, ..SerializeOptions::default() };
+                let out = spec.to_string_with(&options).unwrap();
+                assert_eq!(serde_yaml::from_str::<OpenAPI>(&out).unwrap(), spec, "{}", out);
+            }
+        }
+        let options = SerializeOptions { format: Format::Yaml, yaml_quote_style: QuoteStyle::Single, ..SerializeOptions::default() };
+        assert!(spec.to_string_with(&options).unwrap().contains("- 'it''s'"));
+
+        let options = SerializeOptions { quote_versions: true, ..SerializeOptions::default() };
+        let out = spec.to_yaml_string_with(&options).unwrap();
+        assert!(out.starts_with("openapi: \"3.0.3\"\n") && out.contains("- pets\n"), "{}", out);
+        assert_eq!(spec.to_json_string_with(&options).unwrap(), spec.to_string_with(&SerializeOptions::default()).unwrap());
+    }
+
+    #[test]
+    fn test_omit_defaults() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets/{id}": {
+                    "parameters": [{"name": "id", "in": "path", "required": true, "style": "simple", "schema": {"type": "string"}}],
+                    "get": {
+                        "deprecated": false,
+                        "parameters": [
+                            {"name": "q", "in": "query", "style": "form", "explode": true, "allowReserved": false, "schema": {"type": "string"}},
+                            {"name": "ids", "in": "query", "style": "spaceDelimited", "explode": true, "schema": {"type": "array"}}
+                        ],
+                        "responses": {"200": {
+                            "description": "ok",
+                            "headers": {"X-Rate": {"style": "simple", "schema": {"type": "integer"}}},
+                            "content": {"application/json": {
+                                "schema": {"properties": {"content": {}, "headers": {}, "required": {"nullable": false}}},
+                                "example": {"deprecated": false}
+                            }}
+                        }},
+                        "security": []
+                    }
+                }
+            }
+        })).unwrap();
+        let compact = spec.to_string_with(&SerializeOptions::compact()).unwrap();
+        let out: Value = serde_json::from_str(&compact).unwrap();
+        assert_eq!(out["paths"]["/pets/{id}"], json!({
+            "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}],
+            "get": {
+                "parameters": [
+                    {"name": "q", "in": "query", "explode": true, "schema": {"type": "string"}},
+                    {"name": "ids", "in": "query", "style": "spaceDelimited", "explode": true, "schema": {"type": "array"}}
+                ],
+                "responses": {"200": {
+                    "description": "ok",
+                    "headers": {"X-Rate": {"schema": {"type": "integer"}}},
+                    "content": {"application/json": {
+                        "schema": {"properties": {"content": {}, "headers": {}, "required": {}}},
+                        "example": {"deprecated": false}
+                    }}
+                }},
+                "security": []
+            }
+        }));
+        let reparsed: OpenAPI = serde_json::from_value(out).unwrap();
+        assert_eq!(reparsed, spec);
+        assert_eq!(reparsed.to_string_with(&SerializeOptions::compact()).unwrap(), compact);
+    }
+
+    #[test]
+    fn test_normalize_numbers() {
+        assert_eq!(canonicalize(json!({"b": [10.0, 0.5], "a": -3.0})).to_string(), r#"{"a":-3,"b":[10,0.5]}"#);
+    }
+}
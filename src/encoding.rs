@@ -1,9 +1,16 @@
+use std::fmt;
+
+use crate::body::{array_items, binary};
+use crate::example_validation::child;
+use crate::parameter_style::property_schema;
 use crate::*;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 /// A single encoding definition applied to a single schema property.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Encoding {
     /// The Content-Type for encoding a specific property.
@@ -36,9 +43,8 @@ pub struct Encoding {
     /// SHALL be ignored if the request body media type is
     /// not application/x-www-form-urlencoded.
     ///
-    /// In this Library this value defaults to false always despite the specification.
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub explode: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
     /// Determines whether the parameter value SHOULD allow reserved characters,
     /// as defined by RFC3986 :/?#[]@!$&'()*+,;= to be included without percent-encoding.
     /// The default value is false. This property SHALL be ignored if the request
@@ -47,5 +53,218 @@ pub struct Encoding {
     pub allow_reserved: bool,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+/// How a property of a form body is sent, from [MediaType::part_encoding], with
+/// the defaults of the specification filled in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartEncoding<'a> {
+    /// The `Content-Type` of its `multipart` part: the first media type of the
+    /// encoding's `contentType` that isn't a wildcard, else the default for the
+    /// property's schema, see [default_part_content_type].
+    pub content_type: String,
+    /// The headers of its `multipart` part, by name, without `Content-Type`,
+    /// which the specification says to ignore. Those whose `$ref` doesn't resolve
+    /// are left out.
+    pub headers: IndexMap<&'a str, &'a Header>,
+    /// The style of an `application/x-www-form-urlencoded` field, `form` by default.
+    pub style: QueryStyle,
+    /// Whether arrays and objects are exploded, as the encoding says, else `true`
+    /// for the `form` style and `false` for the others.
+    pub explode: bool,
+    pub allow_reserved: bool,
+}
+
+impl MediaType {
+    /// How the property `name` of this form media type is sent, by its encoding
+    /// and its schema.
+    pub fn part_encoding<'a>(&'a self, spec: &'a OpenAPI, name: &str) -> PartEncoding<'a> {
+        let view = spec.resolved();
+        let encoding = self.encoding.get(name);
+        let property = self.schema.as_ref()
+            .and_then(|schema| view.schema(schema).ok())
+            .and_then(|schema| property_schema(view, schema, name));
+        let style = encoding.and_then(|encoding| encoding.style.clone()).unwrap_or(QueryStyle::Form);
+        let explode = encoding.and_then(|encoding| encoding.explode).unwrap_or(style == QueryStyle::Form);
+        PartEncoding {
+            content_type: encoding.and_then(Encoding::declared_content_type)
+                .map_or_else(|| default_part_content_type(spec, property), str::to_string),
+            headers: encoding.into_iter()
+                .flat_map(|encoding| &encoding.headers)
+                .filter(|(name, _)| !name.eq_ignore_ascii_case("content-type"))
+                .filter_map(|(name, header)| Some((name.as_str(), header.resolve(spec).ok()?)))
+                .collect(),
+            style,
+            explode,
+            allow_reserved: encoding.is_some_and(|encoding| encoding.allow_reserved),
+        }
+    }
+}
+
+impl Encoding {
+    /// The first media type of `contentType` that isn't a wildcard like `image/*`.
+    pub fn declared_content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()?.split(',').map(str::trim).find(|t| !t.is_empty() && !t.contains('*'))
+    }
+}
+
+/// The default `Content-Type` of a form part with `schema`: that of binary strings,
+/// `application/octet-stream` unless `contentMediaType` says otherwise; `text/plain`
+/// for other primitive types; `application/json` for objects and combinations of
+/// schemas; for arrays, that of their items. Without a schema or a type,
+/// `application/octet-stream`.
+pub fn default_part_content_type(spec: &OpenAPI, schema: Option<&Schema>) -> String {
+    let view = spec.resolved();
+    let Some(schema) = schema else { return "application/octet-stream".to_string() };
+    if let Some(media_type) = binary(schema) {
+        return media_type.to_string();
+    }
+    let typ = match &schema.kind {
+        SchemaKind::Type(Type::Object(_)) => "object",
+        SchemaKind::Type(Type::Array(_)) => "array",
+        SchemaKind::Type(_) => "",
+        SchemaKind::Any(any) => match any.typ.as_deref() {
+            Some(typ) => typ,
+            None if !any.properties.is_empty() || any.additional_properties.is_some() => "object",
+            None if any.items.is_some() => "array",
+            None if any.all_of.is_empty() && any.one_of.is_empty() && any.any_of.is_empty() => "unknown",
+            None => "object",
+        },
+        SchemaKind::OneOf { .. } | SchemaKind::AllOf { .. } | SchemaKind::AnyOf { .. } | SchemaKind::Not { .. } => "object",
+    };
+    match typ {
+        "object" => "application/json".to_string(),
+        "array" => default_part_content_type(spec, array_items(view, schema)),
+        "unknown" => "application/octet-stream".to_string(),
+        _ => "text/plain".to_string(),
+    }
+}
+
+/// An [Encoding] that doesn't fit its media type, from [OpenAPI::validate_encodings].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodingError {
+    /// JSON pointer to the encoding, e.g. `/paths/~1pets/post/requestBody/content/multipart~1form-data/encoding/photo`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+impl OpenAPI {
+    /// Checks that the `encoding`s of request bodies, in operations and the
+    /// components, are of `multipart` or `application/x-www-form-urlencoded`
+    /// media types, the only ones they apply to, and that each names a property
+    /// of the schema. Referenced request bodies are checked where declared.
+    pub fn validate_encodings(&self) -> Result<(), Vec<EncodingError>> {
+        let mut errors = Vec::new();
+        let view = self.resolved();
+        let mut check = |pointer: String, body: &RefOr<RequestBody>| {
+            let Some(body) = body.as_item() else { return };
+            for (media_type, media) in &body.content {
+                let pointer = child(&child(&child(&pointer, "content"), media_type), "encoding");
+                let essence = media_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+                if !media.encoding.is_empty() && !essence.starts_with("multipart/") && essence != "application/x-www-form-urlencoded" {
+                    let message = format!("encoding doesn't apply to {} bodies", essence);
+                    errors.push(EncodingError { pointer, message });
+                    continue;
+                }
+                let schema = match &media.schema {
+                    Some(schema) => match view.schema(schema) {
+                        Ok(schema) => Some(schema),
+                        // Dangling `$ref`s are for [OpenAPI::validate] to report.
+                        Err(_) => continue,
+                    },
+                    None => None,
+                };
+                for name in media.encoding.keys() {
+                    if schema.and_then(|schema| property_schema(view, schema, name)).is_none() {
+                        let message = format!("property {:?} is not in the schema", name);
+                        errors.push(EncodingError { pointer: child(&pointer, name), message });
+                    }
+                }
+            }
+        };
+        for (name, body) in &self.components.request_bodies {
+            check(child("/components/requestBodies", name), body);
+        }
+        for (path, method, operation, _) in self.operations() {
+            if let Some(body) = &operation.request_body {
+                check(child(&child(&child("/paths", path), method), "requestBody"), body);
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_part_encoding() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"post": {
+                "requestBody": {"content": {
+                    "multipart/form-data": {
+                        "schema": {"$ref": "#/components/schemas/Upload"},
+                        "encoding": {
+                            "photo": {"contentType": "image/*, image/png", "headers": {
+                                "Content-Type": {"schema": {"type": "string"}},
+                                "X-Rate": {"$ref": "#/components/headers/Rate"}
+                            }},
+                            "ids": {"style": "spaceDelimited"},
+                            "scans": {"style": "form", "explode": false},
+                            "owner": {"contentType": "application/xml"}
+                        }
+                    },
+                    "application/json": {"schema": {"type": "object"}, "encoding": {"a": {}}}
+                }},
+                "responses": {}
+            }}},
+            "components": {
+                "schemas": {"Upload": {"type": "object", "properties": {
+                    "name": {"type": "string"},
+                    "photo": {"type": "string", "format": "binary"},
+                    "meta": {"type": "object"},
+                    "ids": {"type": "array", "items": {"type": "integer"}},
+                    "scans": {"type": "array", "items": {"type": "string", "contentMediaType": "application/pdf"}}
+                }}},
+                "headers": {"Rate": {"schema": {"type": "integer"}}}
+            }
+        })).unwrap();
+        let body = spec.paths.get_op("/pets", &http::Method::POST).unwrap().request_body.as_ref().unwrap().as_item().unwrap();
+        let media = &body.content["multipart/form-data"];
+
+        let photo = media.part_encoding(&spec, "photo");
+        assert_eq!(photo.content_type, "image/png");
+        assert_eq!(photo.headers.keys().collect::<Vec<_>>(), [&"X-Rate"]);
+        assert_eq!((photo.style, photo.explode), (QueryStyle::Form, true));
+        let content_type = |name| media.part_encoding(&spec, name).content_type;
+        assert_eq!(content_type("name"), "text/plain");
+        assert_eq!(content_type("meta"), "application/json");
+        assert_eq!(content_type("ids"), "text/plain");
+        assert_eq!(content_type("scans"), "application/pdf");
+        assert_eq!(content_type("unknown"), "application/octet-stream");
+        assert!(media.part_encoding(&spec, "name").explode);
+        assert!(!media.part_encoding(&spec, "ids").explode);
+        assert!(!media.part_encoding(&spec, "scans").explode);
+
+        let errors: Vec<String> = spec.validate_encodings().unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, [
+            "/paths/~1pets/post/requestBody/content/multipart~1form-data/encoding/owner: property \"owner\" is not in the schema",
+            "/paths/~1pets/post/requestBody/content/application~1json/encoding: encoding doesn't apply to application/json bodies",
+        ]);
+    }
+}
@@ -1,7 +1,16 @@
+use std::collections::HashMap;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::util::{pascal_case, unique_name};
+use crate::walk::for_each_example_mut;
+use crate::{OpenAPI, Parameter, ParameterSchemaOrContent, RefOr, Schema};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Example {
     /// Short description for the example.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -15,6 +24,7 @@ pub struct Example {
     /// media types that cannot naturally represented in JSON or YAML,
     /// use a string value to contain the example, escaping where necessary.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub value: Option<serde_json::Value>,
     /// A URL that points to the literal example.
     /// This provides the capability to reference examples that cannot
@@ -25,5 +35,157 @@ pub struct Example {
     pub external_value: Option<String>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+/// Which inline examples [OpenAPI::hoist_examples] moves to `components.examples`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoistOptions {
+    /// Hoist examples given in at least this many places.
+    pub min_uses: usize,
+    /// Hoist examples whose JSON is at least this many bytes, however often
+    /// they're used. `None` hoists by uses only.
+    pub min_size: Option<usize>,
+}
+
+impl Default for HoistOptions {
+    fn default() -> Self {
+        HoistOptions { min_uses: 2, min_size: Some(1024) }
+    }
+}
+
+impl OpenAPI {
+    /// Moves repeated or large inline examples of parameters, headers and media
+    /// types into `components.examples`, and refers to them with `$ref`s instead.
+    /// Identical examples share one component, as do those identical to an
+    /// existing component. A single `example` becomes an entry of `examples`, as
+    /// only those can be references. Components are named after the entry, else
+    /// the referenced schema, e.g. `Pet` or `Pet2`. Returns the names of the
+    /// components added.
+    pub fn hoist_examples(&mut self, options: &HoistOptions) -> Vec<String> {
+        let key = |example: &Example| serde_json::to_string(example).expect("examples serialize");
+        let bare = |value: &Value| Example { value: Some(value.clone()), ..Example::default() };
+        let mut uses = HashMap::<String, usize>::new();
+        for_each_example_mut(self, &mut |example, examples, _| {
+            let inline = examples.values().filter_map(RefOr::as_item).map(key);
+            for key in example.iter().map(|value| key(&bare(value))).chain(inline) {
+                *uses.entry(key).or_default() += 1;
+            }
+        });
+        let hoisted = |key: &str| uses[key] >= options.min_uses || options.min_size.is_some_and(|size| key.len() >= size);
+
+        let mut names: HashMap<String, String> = self.components.examples.iter()
+            .filter_map(|(name, example)| Some((key(example.as_item()?), name.clone())))
+            .collect();
+        let mut components = std::mem::take(&mut self.components.examples);
+        let mut added = Vec::new();
+        let mut hoist = |example: Example, hint: &str| {
+            let key = key(&example);
+            let name = names.entry(key).or_insert_with(|| {
+                let base = pascal_case(hint);
+                let name = unique_name(&base, |name| components.contains_key(name));
+                components.insert(name.clone(), RefOr::Item(example));
+                added.push(name.clone());
+                name
+            });
+            RefOr::ref_(&format!("#/components/examples/{}", name))
+        };
+        for_each_example_mut(self, &mut |example, examples, schema| {
+            let schema_name = match schema {
+                Some(RefOr::Reference { reference }) => reference.rsplit('/').next().unwrap_or("example"),
+                _ => "example",
+            };
+            for (name, entry) in examples.iter_mut() {
+                if let Some(example) = entry.as_item().filter(|example| hoisted(&key(example))) {
+                    *entry = hoist(example.clone(), name);
+                }
+            }
+            if let Some(value) = example.take_if(|value| hoisted(&key(&bare(value)))) {
+                let name = unique_name("example", |name| examples.contains_key(name));
+                examples.insert(name, hoist(bare(&value), schema_name));
+            }
+        });
+        self.components.examples = components;
+        added
+    }
+}
+
+/// The example declared for a value: the explicit one, else the first of the
+/// named ones, else that of the schema or its default.
+pub(crate) fn declared_example(spec: &OpenAPI, example: Option<&Value>, examples: &IndexMap<String, RefOr<Example>>, schema: Option<&RefOr<Schema>>) -> Option<Value> {
+    if let Some(example) = example {
+        return Some(example.clone());
+    }
+    let named = examples.values().next().and_then(|example| match example {
+        RefOr::Item(example) => example.value.as_ref(),
+        RefOr::Reference { reference } => {
+            let name = reference.strip_prefix("#/components/examples/")?;
+            spec.examples.get(name)?.as_item()?.value.as_ref()
+        }
+    });
+    if let Some(named) = named {
+        return Some(named.clone());
+    }
+    let schema = spec.resolved().schema(schema?).ok()?;
+    schema.example.as_ref().or(schema.default.as_ref()).cloned()
+}
+
+pub(crate) fn parameter_example(spec: &OpenAPI, parameter: &Parameter) -> Option<Value> {
+    let schema = match &parameter.format {
+        ParameterSchemaOrContent::Schema(schema) => Some(schema),
+        ParameterSchemaOrContent::Content(content) => content.first().and_then(|(_, media)| media.schema.as_ref()),
+    };
+    declared_example(spec, parameter.example.as_ref(), &parameter.examples, schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_hoist_examples() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {
+                "get": {
+                    "parameters": [{"name": "limit", "in": "query", "schema": {"type": "integer"}, "example": 10}],
+                    "responses": {"200": {"description": "", "content": {"application/json": {
+                        "schema": {"$ref": "#/components/schemas/Pet"},
+                        "example": {"name": "Rex"}
+                    }}}}
+                },
+                "post": {
+                    "requestBody": {"content": {"application/json": {
+                        "schema": {"$ref": "#/components/schemas/Pet"},
+                        "examples": {"rex": {"value": {"name": "Rex"}}, "tom": {"summary": "A cat", "value": {"name": "Tom"}}}
+                    }}},
+                    "responses": {"201": {"description": "", "content": {"application/json": {
+                        "examples": {"tom": {"summary": "A cat", "value": {"name": "Tom"}}}
+                    }}}}
+                }
+            }},
+            "components": {
+                "schemas": {"Pet": {"type": "object"}},
+                "examples": {"Rex": {"value": 1}}
+            }
+        })).unwrap();
+        let added = spec.hoist_examples(&HoistOptions::default());
+        assert_eq!(added, vec!["Pet", "Tom"]);
+        let value = serde_json::to_value(&spec).unwrap();
+        let get = &value["paths"]["/pets"]["get"];
+        assert_eq!(get["parameters"][0]["example"], 10);
+        assert_eq!(get["responses"]["200"]["content"]["application/json"]["examples"], json!({"example": {"$ref": "#/components/examples/Pet"}}));
+        let post = &value["paths"]["/pets"]["post"];
+        assert_eq!(post["requestBody"]["content"]["application/json"]["examples"], json!({
+            "rex": {"$ref": "#/components/examples/Pet"},
+            "tom": {"$ref": "#/components/examples/Tom"}
+        }));
+        assert_eq!(value["components"]["examples"]["Pet"], json!({"value": {"name": "Rex"}}));
+        assert_eq!(value["components"]["examples"]["Tom"]["summary"], "A cat");
+        assert!(spec.hoist_examples(&HoistOptions::default()).is_empty());
+    }
+}
@@ -0,0 +1,864 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use regex::Regex;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde_json::{Map, Number, Value};
+
+use crate::example_validation::child;
+use crate::util::unique_name;
+use crate::walk::for_each_body_media_type_mut;
+use crate::*;
+
+/// How [Schema::generate_example] fills in what a schema leaves open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExampleOptions {
+    /// Include the optional properties of objects, not only the required ones.
+    pub optional_properties: bool,
+    /// Use the `example` and `default` that schemas declare, rather than always
+    /// generating values.
+    pub use_declared: bool,
+    /// How deep objects and arrays nest before only what their schemas require is
+    /// generated, no optional properties and arrays as short as they may be, which
+    /// ends recursive schemas unless they're required all the way down.
+    pub max_depth: usize,
+    /// Vary examples within what their schemas allow, picking enum values, `oneOf`
+    /// members, numbers and booleans at random from this seed: the same seed gives
+    /// the same examples on every run, for snapshot tests. With the `fake` feature,
+    /// strings are also filled with plausible names, emails, addresses and the
+    /// like, by their property names and formats. `None` always makes the first
+    /// choice, e.g. the first enum value, and keeps placeholders like `string`.
+    pub seed: Option<u64>,
+}
+
+impl Default for ExampleOptions {
+    fn default() -> Self {
+        ExampleOptions {
+            optional_properties: true,
+            use_declared: true,
+            max_depth: 8,
+            seed: None,
+        }
+    }
+}
+
+/// The extension [OpenAPI::ensure_examples] marks the examples it adds with.
+pub const GENERATED_EXAMPLE: &str = "x-generated-example";
+
+impl OpenAPI {
+    /// Sets a generated example, see [Schema::generate_example], on every request
+    /// body and response media type that has a schema but neither `example` nor
+    /// `examples`. With `mark`, those media types also get `x-generated-example: true`,
+    /// for [OpenAPI::strip_generated_examples]. Running it again adds nothing.
+    /// Returns how many examples were added.
+    pub fn ensure_examples(&mut self, options: &ExampleOptions, mark: bool) -> usize {
+        let spec = self.clone();
+        let view = spec.resolved();
+        let mut added = 0;
+        for_each_body_media_type_mut(self, &mut |media| {
+            if media.example.is_some() || !media.examples.is_empty() {
+                return;
+            }
+            let Some(schema) = media.schema.as_ref().and_then(|schema| view.schema(schema).ok()) else { return };
+            media.example = Some(schema.generate_example(&spec, options));
+            if mark {
+                media.extensions.insert(GENERATED_EXAMPLE.to_string(), Value::Bool(true));
+            }
+            added += 1;
+        });
+        added
+    }
+
+    /// Removes the examples [OpenAPI::ensure_examples] added and marked, with
+    /// their marks. Returns how many were removed.
+    pub fn strip_generated_examples(&mut self) -> usize {
+        let mut removed = 0;
+        for_each_body_media_type_mut(self, &mut |media| {
+            if media.extensions.shift_remove(GENERATED_EXAMPLE).is_some() {
+                media.example = None;
+                removed += 1;
+            }
+        });
+        removed
+    }
+}
+
+impl Schema {
+    /// A value valid against this schema, resolving `$ref`s against `spec`, for mock
+    /// servers and documentation. Constants, declared examples and defaults are used
+    /// as given, enums take their first value, strings match their `pattern` and
+    /// look like their `format` (dates, UUIDs, emails, URIs, ...) where its length
+    /// bounds allow, numbers and lengths keep within their bounds, and arrays have
+    /// as many items as they must, at least one. Of a `oneOf` or `anyOf`, the first
+    /// member is used, with its discriminator property set to the value that
+    /// selects it.
+    pub fn generate_example(&self, spec: &OpenAPI, options: &ExampleOptions) -> Value {
+        Generator {
+            view: spec.resolved(),
+            options,
+            rng: options.seed.map(|seed| RefCell::new(SplitMix64(seed))),
+            patterns: RefCell::default(),
+            #[cfg(feature = "fake")]
+            faker: options.seed.map(|seed| RefCell::new(crate::fake_data::Faker::new(seed))),
+        }.schema(self, None, 0)
+    }
+}
+
+struct Generator<'a> {
+    view: ResolvedSpec<'a>,
+    options: &'a ExampleOptions,
+    rng: Option<RefCell<SplitMix64>>,
+    /// The compiled `pattern`s, `None` for those that don't compile.
+    patterns: RefCell<HashMap<String, Option<Regex>>>,
+    #[cfg(feature = "fake")]
+    faker: Option<RefCell<crate::fake_data::Faker>>,
+}
+
+impl Generator<'_> {
+    fn reference(&self, schema: &RefOr<Schema>, name: Option<&str>, depth: usize) -> Value {
+        match self.view.schema(schema) {
+            Ok(schema) => self.schema(schema, name, depth),
+            Err(_) => Value::Null,
+        }
+    }
+
+    /// `name` is that of the property the value is for, if any.
+    fn schema(&self, schema: &Schema, name: Option<&str>, depth: usize) -> Value {
+        if let Some(value) = &schema.const_value {
+            return value.clone();
+        }
+        if self.options.use_declared {
+            if let Some(value) = schema.example.as_ref().or(schema.default.as_ref()) {
+                return value.clone();
+            }
+        }
+        match &schema.kind {
+            SchemaKind::Type(Type::String(s)) => match self.choose(&s.enumeration) {
+                Some(value) => Value::String(value.clone()),
+                None => Value::String(self.string(name, s.format.as_str(), s.pattern.as_deref(), s.min_length, s.max_length)),
+            },
+            SchemaKind::Type(Type::Integer(i)) => match self.choose(&i.enumeration.iter().flatten().collect::<Vec<_>>()) {
+                Some(value) => Value::from(**value),
+                None => Value::from(self.integer(i.lower_bound(), i.upper_bound(), i.multiple_of)),
+            },
+            SchemaKind::Type(Type::Number(n)) => match self.choose(&n.enumeration.iter().flatten().collect::<Vec<_>>()) {
+                Some(value) => number(**value),
+                None => number(self.float(n.lower_bound(), n.upper_bound(), n.multiple_of)),
+            },
+            SchemaKind::Type(Type::Boolean {}) => Value::Bool(self.pick(2) == 0),
+            SchemaKind::Type(Type::Array(a)) => self.array(a.items.as_deref(), a.min_items, a.max_items, depth),
+            SchemaKind::Type(Type::Object(o)) => self.object(&o.properties, &o.required, o.additional_properties.as_ref(), depth),
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => self.union(schema, members, name, depth),
+            SchemaKind::AllOf { all_of } => {
+                let mut merged = Map::new();
+                for member in all_of {
+                    match self.reference(member, name, depth) {
+                        Value::Object(properties) => merged.extend(properties),
+                        value if all_of.len() == 1 => return value,
+                        _ => {}
+                    }
+                }
+                Value::Object(merged)
+            }
+            SchemaKind::Not { .. } => Value::Null,
+            SchemaKind::Any(any) => self.any(schema, any, name, depth),
+        }
+    }
+
+    fn any(&self, schema: &Schema, any: &AnySchema, name: Option<&str>, depth: usize) -> Value {
+        if let Some(value) = self.choose(&any.enumeration) {
+            return value.clone();
+        }
+        if !any.one_of.is_empty() || !any.any_of.is_empty() {
+            let members = if any.one_of.is_empty() { &any.any_of } else { &any.one_of };
+            return self.union(schema, members, name, depth);
+        }
+        match any.typ.as_deref() {
+            Some("string") => {
+                Value::String(self.string(name, any.format.as_deref().unwrap_or(""), any.pattern.as_deref(), any.min_length, any.max_length))
+            }
+            Some("integer") => {
+                let bound = |b: Option<(f64, bool)>| b.map(|(v, exclusive)| (v as i64, exclusive));
+                Value::from(self.integer(bound(any.lower_bound()), bound(any.upper_bound()), any.multiple_of.map(|m| m as i64)))
+            }
+            Some("number") => number(self.float(any.lower_bound(), any.upper_bound(), any.multiple_of)),
+            Some("boolean") => Value::Bool(self.pick(2) == 0),
+            Some("array") => self.array(any.items.as_deref(), any.min_items, any.max_items, depth),
+            Some("null") => Value::Null,
+            _ if any.items.is_some() => self.array(any.items.as_deref(), any.min_items, any.max_items, depth),
+            _ if !any.all_of.is_empty() => self.schema(&Schema::new_all_of(any.all_of.clone()), name, depth),
+            _ => self.object(&any.properties, &any.required, any.additional_properties.as_ref(), depth),
+        }
+    }
+
+    /// A string of `format` within the length bounds and matching `pattern`, else
+    /// one generated from `pattern`, else a placeholder padded or cut to the length
+    /// bounds. `name` is that of the property the value is for, if any.
+    #[cfg_attr(not(feature = "fake"), allow(unused_variables))]
+    fn string(&self, name: Option<&str>, format: &str, pattern: Option<&str>, min_length: Option<usize>, max_length: Option<usize>) -> String {
+        let regex = pattern.and_then(|pattern| {
+            self.patterns.borrow_mut().entry(pattern.to_string()).or_insert_with(|| Regex::new(pattern).ok()).clone()
+        });
+        let valid = |s: &str| within(s, min_length, max_length) && regex.as_ref().is_none_or(|regex| regex.is_match(s));
+        #[cfg(feature = "fake")]
+        if let Some(fake) = self.faker.as_ref().and_then(|faker| faker.borrow_mut().string(name, format)) {
+            let fake = fit(fake, min_length, max_length);
+            if valid(&fake) {
+                return fake;
+            }
+        }
+        if let Some(s) = formatted(format).filter(|s| valid(s)) {
+            return s.to_string();
+        }
+        regex.and_then(|regex| matching(&regex, min_length, max_length))
+            .unwrap_or_else(|| fit("string".to_string(), min_length, max_length))
+    }
+
+    /// Past `max_depth`, only what's required is generated, and past twice that
+    /// nothing, for schemas that require themselves.
+    fn limited(&self, depth: usize) -> bool {
+        depth >= self.options.max_depth
+    }
+
+    /// A random index below `n`, `0` without a seed.
+    fn pick(&self, n: usize) -> usize {
+        match &self.rng {
+            Some(rng) if n > 1 => (rng.borrow_mut().next() % n as u64) as usize,
+            _ => 0,
+        }
+    }
+
+    fn choose<'v, T>(&self, values: &'v [T]) -> Option<&'v T> {
+        values.get(self.pick(values.len()))
+    }
+
+    /// [integer], or with a seed one of the next hundred multiples within the bounds.
+    fn integer(&self, lower: Option<(i64, bool)>, upper: Option<(i64, bool)>, multiple_of: Option<i64>) -> i64 {
+        let value = integer(lower, upper, multiple_of);
+        let step = multiple_of.filter(|&m| m > 0).unwrap_or(1);
+        let upper = inclusive_upper(upper).unwrap_or(i64::MAX);
+        let room = (upper.saturating_sub(value) / step).clamp(0, 100);
+        value + self.pick(room as usize + 1) as i64 * step
+    }
+
+    /// [float], or with a seed one of the next hundred multiples, of one without
+    /// `multiple_of`, within the bounds.
+    fn float(&self, lower: Option<(f64, bool)>, upper: Option<(f64, bool)>, multiple_of: Option<f64>) -> f64 {
+        let value = float(lower, upper, multiple_of);
+        let step = multiple_of.filter(|&m| m > 0.0).unwrap_or(1.0);
+        let room = upper.map_or(100.0, |(u, exclusive)| {
+            let steps = ((u - value) / step).floor();
+            if exclusive && value + steps * step >= u { steps - 1.0 } else { steps }
+        });
+        let room = room.clamp(0.0, 100.0) as usize;
+        value + self.pick(room + 1) as f64 * step
+    }
+
+    fn array(&self, items: Option<&RefOr<Schema>>, min_items: Option<usize>, max_items: Option<usize>, depth: usize) -> Value {
+        let min = min_items.unwrap_or(0);
+        let count = if self.limited(depth) { min } else { min.max(1).min(max_items.unwrap_or(usize::MAX)) };
+        match items {
+            Some(items) if depth < 2 * self.options.max_depth => {
+                Value::Array((0..count).map(|_| self.reference(items, None, depth + 1)).collect())
+            }
+            _ => Value::Array(Vec::new()),
+        }
+    }
+
+    fn object(&self, properties: &RefOrMap<Schema>, required: &[String], additional: Option<&AdditionalProperties>, depth: usize) -> Value {
+        let mut object = Map::new();
+        if depth >= 2 * self.options.max_depth {
+            return Value::Object(object);
+        }
+        let optional = self.options.optional_properties && !self.limited(depth);
+        for (key, property) in properties.iter() {
+            if optional || required.contains(key) {
+                object.insert(key.clone(), self.reference(property, Some(key), depth + 1));
+            }
+        }
+        if let (true, true, Some(AdditionalProperties::Schema(values))) = (optional, properties.is_empty(), additional) {
+            object.insert("key".to_string(), self.reference(values, None, depth + 1));
+        }
+        Value::Object(object)
+    }
+
+    /// A member of a `oneOf` or `anyOf`, the first without a seed, its discriminator
+    /// property set to the mapping key of the member, else its schema name.
+    fn union(&self, schema: &Schema, members: &[RefOr<Schema>], name: Option<&str>, depth: usize) -> Value {
+        let Some(member) = self.choose(members) else { return Value::Null };
+        let mut value = self.reference(member, name, depth);
+        if let (Some(discriminator), RefOr::Reference { reference }, Value::Object(object)) = (&schema.discriminator, member, &mut value) {
+            let tag = discriminator.mapping.iter()
+                .find(|(_, target)| *target == reference || reference.rsplit('/').next() == Some(target.as_str()))
+                .map(|(tag, _)| tag.as_str())
+                .or_else(|| reference.rsplit('/').next());
+            if let Some(tag) = tag {
+                object.insert(discriminator.property_name.clone(), Value::String(tag.to_string()));
+            }
+        }
+        value
+    }
+}
+
+/// A value that fails its schema by one constraint, from
+/// [Schema::generate_invalid_examples].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidExample {
+    /// JSON pointer to the part of the value that's invalid, as a
+    /// [SchemaError]'s `instance_path`. Empty for the value itself.
+    pub instance_path: String,
+    /// The schema keyword the value fails, e.g. `required` or `maximum`.
+    pub keyword: &'static str,
+    pub value: Value,
+}
+
+impl Schema {
+    /// Values each failing this schema by exactly one constraint, for negative
+    /// tests of an implementation: [generated](Schema::generate_example) valid
+    /// examples with a required property removed, a value of the wrong type, one
+    /// outside an enum, a number out of range, or a string or array too short or
+    /// too long, at the top level and in the properties and items within. Only
+    /// `type`d schemas are broken, not `oneOf` and the like, whose failures can't
+    /// be pinned to one constraint.
+    pub fn generate_invalid_examples(&self, spec: &OpenAPI, options: &ExampleOptions) -> Vec<InvalidExample> {
+        let valid = self.generate_example(spec, options);
+        let mut broken = Vec::new();
+        Breaker { view: spec.resolved(), broken: &mut broken }.schema(self, &valid, "");
+        broken.into_iter().map(|(instance_path, keyword, part)| {
+            let mut value = valid.clone();
+            if let Some(target) = value.pointer_mut(&instance_path) {
+                *target = part;
+            }
+            InvalidExample { instance_path, keyword, value }
+        }).collect()
+    }
+}
+
+/// Collects `(instance path, keyword, invalid replacement)` for the parts of a
+/// valid value.
+struct Breaker<'a, 'b> {
+    view: ResolvedSpec<'a>,
+    broken: &'b mut Vec<(String, &'static str, Value)>,
+}
+
+impl Breaker<'_, '_> {
+    fn push(&mut self, path: &str, keyword: &'static str, value: Value) {
+        self.broken.push((path.to_string(), keyword, value));
+    }
+
+    fn reference(&mut self, schema: &RefOr<Schema>, value: &Value, path: &str) {
+        if let Ok(schema) = self.view.schema(schema) {
+            self.schema(schema, value, path);
+        }
+    }
+
+    fn schema(&mut self, schema: &Schema, value: &Value, path: &str) {
+        if schema.const_value.is_some() {
+            return;
+        }
+        // Of another type than the schema's, a value fails `enum` too.
+        let wrong_type = |enumerated: bool, wrong: Value| (!enumerated).then_some(wrong);
+        let SchemaKind::Type(typ) = &schema.kind else { return };
+        let wrong = match typ {
+            Type::String(s) => {
+                let enumerated = !s.enumeration.is_empty();
+                if enumerated {
+                    let outside = outside_strings(s).find(|c| !s.enumeration.contains(c));
+                    if let Some(outside) = outside {
+                        self.push(path, "enum", Value::String(outside));
+                    }
+                } else if formatted(s.format.as_str()).is_none() {
+                    // Only strings that match the pattern fail by their length alone.
+                    let regex = s.pattern.as_deref().and_then(|pattern| Regex::new(pattern).ok());
+                    let matches = |s: &String| regex.as_ref().is_none_or(|regex| regex.is_match(s));
+                    if let Some(short) = s.min_length.filter(|&min| min > 0).map(|min| "x".repeat(min - 1)).filter(matches) {
+                        self.push(path, "minLength", Value::String(short));
+                    }
+                    if let Some(long) = s.max_length.map(|max| "x".repeat(max + 1)).filter(matches) {
+                        self.push(path, "maxLength", Value::String(long));
+                    }
+                }
+                wrong_type(enumerated, Value::from(0))
+            }
+            Type::Integer(i) => {
+                let enumerated = !i.enumeration.is_empty();
+                let step = i.multiple_of.filter(|&m| m > 0).unwrap_or(1);
+                if enumerated {
+                    let start = integer(i.lower_bound(), i.upper_bound(), i.multiple_of);
+                    let upper = inclusive_upper(i.upper_bound());
+                    let outside = (0..=i.enumeration.len() as i64)
+                        .filter_map(|n| start.checked_add(n.checked_mul(step)?))
+                        .take_while(|v| upper.is_none_or(|u| *v <= u))
+                        .find(|v| !i.enumeration.contains(&Some(*v)));
+                    if let Some(outside) = outside {
+                        self.push(path, "enum", Value::from(outside));
+                    }
+                } else {
+                    if let Some((lower, exclusive)) = i.lower_bound() {
+                        let edge = if exclusive { Some(lower) } else { lower.checked_sub(1) };
+                        if let Some(below) = edge.and_then(|edge| edge.checked_sub(edge.rem_euclid(step))) {
+                            self.push(path, if exclusive { "exclusiveMinimum" } else { "minimum" }, Value::from(below));
+                        }
+                    }
+                    if let Some((upper, exclusive)) = i.upper_bound() {
+                        let edge = if exclusive { Some(upper) } else { upper.checked_add(1) };
+                        if let Some(above) = edge.and_then(|edge| edge.checked_add((step - edge.rem_euclid(step)) % step)) {
+                            self.push(path, if exclusive { "exclusiveMaximum" } else { "maximum" }, Value::from(above));
+                        }
+                    }
+                }
+                wrong_type(enumerated, Value::String("string".to_string()))
+            }
+            Type::Number(n) => {
+                let enumerated = !n.enumeration.is_empty();
+                let step = n.multiple_of.filter(|&m| m > 0.0).unwrap_or(1.0);
+                if enumerated {
+                    let start = float(n.lower_bound(), n.upper_bound(), n.multiple_of);
+                    let outside = (0..=n.enumeration.len())
+                        .map(|k| start + k as f64 * step)
+                        .take_while(|v| n.upper_bound().is_none_or(|(u, exclusive)| if exclusive { *v < u } else { *v <= u }))
+                        .find(|v| !n.enumeration.contains(&Some(*v)));
+                    if let Some(outside) = outside {
+                        self.push(path, "enum", number(outside));
+                    }
+                } else {
+                    if let Some((lower, exclusive)) = n.lower_bound() {
+                        let below = if exclusive { (lower / step).floor() } else { (lower / step).ceil() - 1.0 };
+                        self.push(path, if exclusive { "exclusiveMinimum" } else { "minimum" }, number(below * step));
+                    }
+                    if let Some((upper, exclusive)) = n.upper_bound() {
+                        let above = if exclusive { (upper / step).ceil() } else { (upper / step).floor() + 1.0 };
+                        self.push(path, if exclusive { "exclusiveMaximum" } else { "maximum" }, number(above * step));
+                    }
+                }
+                wrong_type(enumerated, Value::String("string".to_string()))
+            }
+            Type::Boolean {} => Some(Value::String("string".to_string())),
+            Type::Object(o) => {
+                if let Value::Object(object) = value {
+                    for key in o.required.iter().filter(|key| object.contains_key(*key)) {
+                        let mut missing = object.clone();
+                        missing.shift_remove(key);
+                        self.push(path, "required", Value::Object(missing));
+                    }
+                    if let Some(AdditionalProperties::Any(false)) = o.additional_properties {
+                        let extra = unique_name("unexpected", |key| o.properties.contains_key(key));
+                        let mut extended = object.clone();
+                        extended.insert(extra, Value::Bool(true));
+                        self.push(path, "additionalProperties", Value::Object(extended));
+                    }
+                    for (key, value) in object {
+                        if let Some(property) = o.properties.get(key) {
+                            self.reference(property, value, &child(path, key));
+                        }
+                    }
+                }
+                Some(Value::String("string".to_string()))
+            }
+            Type::Array(a) => {
+                if let Value::Array(items) = value {
+                    if let Some(min) = a.min_items.filter(|&min| min > 0 && items.len() >= min) {
+                        self.push(path, "minItems", Value::Array(items[..min - 1].to_vec()));
+                    }
+                    if let (Some(max), Some(first), false) = (a.max_items, items.first(), a.unique_items) {
+                        let mut longer = items.clone();
+                        longer.resize(max + 1, first.clone());
+                        self.push(path, "maxItems", Value::Array(longer));
+                    }
+                    if let (Some(schema), Some(first)) = (a.items.as_deref(), items.first()) {
+                        self.reference(schema, first, &child(path, "0"));
+                    }
+                }
+                Some(Value::String("string".to_string()))
+            }
+        };
+        if let Some(wrong) = wrong {
+            self.push(path, "type", wrong);
+        }
+    }
+}
+
+/// Strings within the length bounds of `s` that differ from one another, for
+/// one outside its enum.
+fn outside_strings(s: &StringType) -> impl Iterator<Item=String> + '_ {
+    let base = fit("invalid".to_string(), s.min_length, s.max_length);
+    std::iter::once(base.clone()).chain(('a'..='z').filter_map(move |c| {
+        let mut variant = base.clone();
+        variant.pop()?;
+        variant.push(c);
+        Some(variant)
+    }))
+}
+
+/// The SplitMix64 generator, whose output is fixed for a seed on every platform
+/// and release, unlike those of `rand`.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// A value of the string `format`, for those that have a fixed shape.
+fn formatted(format: &str) -> Option<&'static str> {
+    Some(match format {
+        "date" => "2024-01-01",
+        "date-time" => "2024-01-01T00:00:00Z",
+        "time" => "00:00:00Z",
+        "duration" => "P1D",
+        "uuid" => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+        "email" | "idn-email" => "user@example.com",
+        "uri" | "url" | "iri" => "https://example.com",
+        "uri-reference" | "iri-reference" => "/example",
+        "hostname" | "idn-hostname" => "example.com",
+        "ipv4" => "192.0.2.1",
+        "ipv6" => "2001:db8::1",
+        "byte" => "c3RyaW5n",
+        "password" => "********",
+        _ => return None,
+    })
+}
+
+/// Whether `s` is within the length bounds, counted in characters.
+fn within(s: &str, min_length: Option<usize>, max_length: Option<usize>) -> bool {
+    let len = s.chars().count();
+    min_length.is_none_or(|min| len >= min) && max_length.is_none_or(|max| len <= max)
+}
+
+/// A string matching `regex` within the length bounds, generated from the pattern:
+/// the first alternative, a letter or digit of a class if it has one, and each
+/// repetition as few times as it may, then more until the string is long enough,
+/// else padded like [fit]. `None` if the pattern can't be parsed or nothing fits.
+fn matching(regex: &Regex, min_length: Option<usize>, max_length: Option<usize>) -> Option<String> {
+    let hir = regex_syntax::Parser::new().parse(regex.as_str()).ok()?;
+    let extra = min_length.unwrap_or(0).min(256) as u32;
+    let strings: Vec<String> = (0..=extra).map(|extra| {
+        let mut s = String::new();
+        generate(&hir, extra, &mut s);
+        s
+    }).collect();
+    let valid = |s: &String| within(s, min_length, max_length) && regex.is_match(s);
+    strings.iter().find(|s| valid(s)).cloned()
+        .or_else(|| Some(fit(strings[0].clone(), min_length, max_length)).filter(valid))
+}
+
+/// Appends a string matching `hir` to `out`, each repetition taken `extra` more
+/// times than it must, as far as it may.
+fn generate(hir: &Hir, extra: u32, out: &mut String) {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => {}
+        HirKind::Literal(literal) => out.push_str(&String::from_utf8_lossy(&literal.0)),
+        HirKind::Class(Class::Unicode(class)) => {
+            let contains = |c: &char| class.ranges().iter().any(|range| range.start() <= *c && *c <= range.end());
+            let preferred = ('a'..='z').chain('0'..='9').chain('A'..='Z').find(contains);
+            out.extend(preferred.or_else(|| class.ranges().first().map(|range| range.start())));
+        }
+        HirKind::Class(Class::Bytes(class)) => out.extend(class.ranges().first().map(|range| char::from(range.start()))),
+        HirKind::Repetition(repetition) => {
+            let count = repetition.min.saturating_add(extra);
+            for _ in 0..repetition.max.map_or(count, |max| max.min(count)) {
+                generate(&repetition.sub, extra, out);
+            }
+        }
+        HirKind::Capture(capture) => generate(&capture.sub, extra, out),
+        HirKind::Concat(hirs) => hirs.iter().for_each(|hir| generate(hir, extra, out)),
+        HirKind::Alternation(hirs) => generate(&hirs[0], extra, out),
+    }
+}
+
+/// `s` padded with `x` or cut to the length bounds, counted in characters.
+fn fit(mut s: String, min_length: Option<usize>, max_length: Option<usize>) -> String {
+    let len = s.chars().count();
+    let min = min_length.unwrap_or(0);
+    if len < min {
+        s.push_str(&"x".repeat(min - len));
+    }
+    if let Some((end, _)) = max_length.and_then(|max| s.char_indices().nth(max)) {
+        s.truncate(end);
+    }
+    s
+}
+
+/// The integer closest to zero within the bounds, on a multiple of `multiple_of`
+/// unless the bounds leave none that fits in an `i64`.
+fn integer(lower: Option<(i64, bool)>, upper: Option<(i64, bool)>, multiple_of: Option<i64>) -> i64 {
+    let lower = lower.map(|(v, exclusive)| if exclusive { v.saturating_add(1) } else { v });
+    let upper = inclusive_upper(upper);
+    let value = 0.max(lower.unwrap_or(i64::MIN)).min(upper.unwrap_or(i64::MAX));
+    match multiple_of.filter(|&m| m > 0) {
+        Some(m) if value % m != 0 => {
+            let up = value.checked_add(m - value.rem_euclid(m)).filter(|up| upper.is_none_or(|u| *up <= u));
+            let down = value.checked_sub(value.rem_euclid(m)).filter(|down| lower.is_none_or(|l| *down >= l));
+            up.or(down).unwrap_or(value)
+        }
+        _ => value,
+    }
+}
+
+/// The largest integer an upper bound allows.
+fn inclusive_upper(upper: Option<(i64, bool)>) -> Option<i64> {
+    upper.map(|(v, exclusive)| if exclusive { v.saturating_sub(1) } else { v })
+}
+
+/// The number closest to zero within the bounds, a `multiple_of` (else one) past
+/// an exclusive bound, or halfway between bounds too close for that.
+fn float(lower: Option<(f64, bool)>, upper: Option<(f64, bool)>, multiple_of: Option<f64>) -> f64 {
+    let multiple_of = multiple_of.filter(|&m| m > 0.0);
+    let step = multiple_of.unwrap_or(1.0);
+    let above = |v: f64| lower.is_none_or(|(lo, exclusive)| if exclusive { v > lo } else { v >= lo });
+    let below = |v: f64| upper.is_none_or(|(hi, exclusive)| if exclusive { v < hi } else { v <= hi });
+    let mut value = 0.0;
+    if let Some((lo, exclusive)) = lower.filter(|_| !above(value)) {
+        value = if exclusive { lo + step } else { lo };
+    }
+    if let Some((hi, exclusive)) = upper.filter(|_| !below(value)) {
+        value = if exclusive { hi - step } else { hi };
+    }
+    if let (Some(m), true) = (multiple_of, value % step != 0.0) {
+        value = (value / m).ceil() * m;
+    }
+    match (lower, upper) {
+        (Some((lo, _)), Some((hi, _))) if !above(value) || !below(value) => (lo + hi) / 2.0,
+        _ => value,
+    }
+}
+
+fn number(value: f64) -> Value {
+    Number::from_f64(value).map(Value::Number).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_generate_example() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "oneOf": [{"$ref": "#/components/schemas/Dog"}, {"$ref": "#/components/schemas/Cat"}],
+                    "discriminator": {"propertyName": "kind", "mapping": {"dog": "#/components/schemas/Dog"}}
+                },
+                "Dog": {
+                    "type": "object",
+                    "required": ["kind", "id", "born"],
+                    "properties": {
+                        "kind": {"type": "string"},
+                        "id": {"type": "string", "format": "uuid"},
+                        "born": {"type": "string", "format": "date"},
+                        "owner": {"type": "string", "format": "email"},
+                        "size": {"type": "string", "enum": ["small", "large"]},
+                        "age": {"type": "integer", "minimum": 3, "maximum": 10, "multipleOf": 2},
+                        "weight": {"type": "number", "exclusiveMinimum": true, "minimum": 0},
+                        "tags": {"type": "array", "minItems": 2, "items": {"type": "string", "minLength": 8}},
+                        "name": {"type": "string", "example": "Rex"},
+                        "friends": {"type": "array", "items": {"$ref": "#/components/schemas/Dog"}}
+                    }
+                },
+                "Cat": {"type": "object"}
+            }}
+        })).unwrap();
+        let pet = spec.schemas["Pet"].as_item().unwrap();
+        let options = ExampleOptions { max_depth: 2, ..ExampleOptions::default() };
+        let example = pet.generate_example(&spec, &options);
+        assert_eq!(example, json!({
+            "kind": "dog",
+            "id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+            "born": "2024-01-01",
+            "owner": "user@example.com",
+            "size": "small",
+            "age": 4,
+            "weight": 1.0,
+            "tags": ["stringxx", "stringxx"],
+            "name": "Rex",
+            "friends": [{"kind": "string", "id": "3fa85f64-5717-4562-b3fc-2c963f66afa6", "born": "2024-01-01"}]
+        }));
+
+        let options = ExampleOptions { optional_properties: false, use_declared: false, ..ExampleOptions::default() };
+        let dog = spec.schemas["Dog"].as_item().unwrap();
+        assert_eq!(dog.generate_example(&spec, &options), json!({
+            "kind": "string",
+            "id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+            "born": "2024-01-01"
+        }));
+        assert_eq!(dog.validate_value(&dog.generate_example(&spec, &ExampleOptions::default()), &spec.components), Ok(()));
+    }
+
+    #[test]
+    fn test_generate_constrained_example() {
+        let spec = OpenAPI::default();
+        let schema: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "code": {"type": "string", "pattern": "^[A-Z]{3}-\\d{2,}$", "minLength": 8},
+                "slug": {"type": "string", "pattern": "ab", "minLength": 5},
+                "stamp": {"type": "string", "format": "date-time", "maxLength": 10},
+                "id": {"type": "string", "format": "uuid", "minLength": 40},
+                "big": {"type": "integer", "minimum": 9223372036854775806_i64, "multipleOf": 4},
+                "small": {"type": "integer", "maximum": i64::MIN, "exclusiveMaximum": true}
+            }
+        })).unwrap();
+        let expected = json!({
+            "code": "AAA-0000",
+            "slug": "abxxx",
+            "stamp": "string",
+            "id": format!("string{}", "x".repeat(34)),
+            "big": 9223372036854775806_i64,
+            "small": i64::MIN
+        });
+        assert_eq!(schema.generate_example(&spec, &ExampleOptions::default()), expected);
+        assert_eq!(schema.generate_example(&spec, &ExampleOptions { seed: Some(1), ..ExampleOptions::default() })["big"], expected["big"]);
+
+        let lengths = |pattern: &str| -> Vec<&'static str> {
+            let schema: Schema = serde_json::from_value(json!({"type": "string", "pattern": pattern, "minLength": 2, "maxLength": 3})).unwrap();
+            schema.generate_invalid_examples(&spec, &ExampleOptions::default()).into_iter().map(|e| e.keyword).collect()
+        };
+        assert_eq!(lengths("^x*$"), ["minLength", "maxLength", "type"]);
+        assert_eq!(lengths("^a"), ["type"]);
+    }
+
+    #[test]
+    fn test_generate_invalid_examples() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "required": ["name", "age"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "name": {"type": "string", "minLength": 2, "maxLength": 8},
+                        "age": {"type": "integer", "minimum": 1, "exclusiveMaximum": 30, "multipleOf": 2},
+                        "size": {"type": "string", "enum": ["invalid", "small"]},
+                        "tags": {"type": "array", "maxItems": 2, "items": {"$ref": "#/components/schemas/Tag"}}
+                    }
+                },
+                "Tag": {"type": "number", "nullable": true, "exclusiveMinimum": true, "minimum": 0.5}
+            }}
+        })).unwrap();
+        let pet = spec.schemas["Pet"].as_item().unwrap();
+        let invalid = pet.generate_invalid_examples(&spec, &ExampleOptions::default());
+        let found: Vec<(&str, &str)> = invalid.iter().map(|e| (e.instance_path.as_str(), e.keyword)).collect();
+        assert_eq!(found, vec![
+            ("", "required"),
+            ("", "required"),
+            ("", "additionalProperties"),
+            ("/name", "minLength"),
+            ("/name", "maxLength"),
+            ("/name", "type"),
+            ("/age", "minimum"),
+            ("/age", "exclusiveMaximum"),
+            ("/age", "type"),
+            ("/size", "enum"),
+            ("/tags", "maxItems"),
+            ("/tags/0", "exclusiveMinimum"),
+            ("/tags/0", "type"),
+            ("/tags", "type"),
+            ("", "type"),
+        ]);
+        assert_eq!(invalid[6].value["age"], json!(0));
+        assert_eq!(invalid[7].value["age"], json!(30));
+        assert_eq!(invalid[9].value["size"], json!("invalia"));
+        for example in &invalid {
+            let errors = pet.validate_value(&example.value, &spec.components).unwrap_err();
+            let errors: Vec<(&str, &str)> = errors.iter().map(|e| (e.instance_path.as_str(), e.keyword)).collect();
+            assert_eq!(errors, vec![(example.instance_path.as_str(), example.keyword)], "{}", example.value);
+        }
+    }
+
+    #[test]
+    fn test_seeded_examples() {
+        let spec = OpenAPI::default();
+        let schema: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "size": {"type": "string", "enum": ["small", "medium", "large"]},
+                "count": {"type": "integer", "minimum": 1, "maximum": 50, "multipleOf": 3},
+                "weight": {"type": "number", "exclusiveMaximum": true, "maximum": 2.5},
+                "ok": {"type": "boolean"},
+                "tag": {"oneOf": [{"type": "string", "format": "date"}, {"type": "integer"}]}
+            }
+        })).unwrap();
+        let seeded = |seed| schema.generate_example(&spec, &ExampleOptions { seed: Some(seed), ..ExampleOptions::default() });
+        let examples: Vec<Value> = (0..20).map(seeded).collect();
+        assert_eq!(examples, (0..20).map(seeded).collect::<Vec<_>>());
+        assert!(examples.iter().any(|example| *example != examples[0]));
+        for example in &examples {
+            assert_eq!(schema.validate_value(example, &spec.components), Ok(()), "{}", example);
+        }
+        assert_eq!(seeded(1), json!({"size": "large", "count": 24, "weight": 0.0, "ok": false, "tag": 87}));
+        assert_eq!(schema.generate_example(&spec, &ExampleOptions::default()), json!({
+            "size": "small", "count": 3, "weight": 0.0, "ok": true, "tag": "2024-01-01"
+        }));
+    }
+
+    #[test]
+    fn test_ensure_examples() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"post": {
+                "requestBody": {"content": {
+                    "application/json": {"schema": {"$ref": "#/components/schemas/Pet"}},
+                    "text/plain": {}
+                }},
+                "responses": {
+                    "200": {"description": "", "content": {"application/json": {"schema": {"type": "integer"}, "example": 7}}},
+                    "default": {"$ref": "#/components/responses/Error"}
+                }
+            }}},
+            "components": {
+                "schemas": {"Pet": {"type": "object", "properties": {"name": {"type": "string"}}}},
+                "responses": {"Error": {"description": "", "content": {"application/json": {"schema": {"type": "string"}}}}}
+            }
+        })).unwrap();
+        let original = spec.clone();
+        assert_eq!(spec.ensure_examples(&ExampleOptions::default(), true), 2);
+        assert_eq!(spec.ensure_examples(&ExampleOptions::default(), true), 0);
+        let value = serde_json::to_value(&spec).unwrap();
+        let body = &value["paths"]["/pets"]["post"]["requestBody"]["content"]["application/json"];
+        assert_eq!(body["example"], json!({"name": "string"}));
+        assert_eq!(body[GENERATED_EXAMPLE], json!(true));
+        assert_eq!(value["components"]["responses"]["Error"]["content"]["application/json"]["example"], "string");
+        assert_eq!(value["paths"]["/pets"]["post"]["responses"]["200"]["content"]["application/json"]["example"], 7);
+
+        assert_eq!(spec.strip_generated_examples(), 2);
+        assert_eq!(spec, original);
+    }
+
+    #[cfg(feature = "fake")]
+    #[test]
+    fn test_fake_examples() {
+        let spec = OpenAPI::default();
+        let schema: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "firstName": {"type": "string"},
+                "contact_email": {"type": "string"},
+                "city": {"type": "string", "maxLength": 3},
+                "code": {"type": "string"},
+                "id": {"type": "string", "format": "uuid"}
+            }
+        })).unwrap();
+        let options = ExampleOptions { seed: Some(7), ..ExampleOptions::default() };
+        let example = schema.generate_example(&spec, &options);
+        assert_eq!(example, schema.generate_example(&spec, &options));
+        assert_ne!(example["firstName"], "string");
+        assert!(example["contact_email"].as_str().unwrap().contains('@'));
+        assert!(example["city"].as_str().unwrap().chars().count() <= 3);
+        assert_eq!(example["code"], "string");
+        assert_eq!(example["id"], "3fa85f64-5717-4562-b3fc-2c963f66afa6");
+    }
+}
@@ -0,0 +1,410 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::*;
+
+/// An `example`, `examples` or `default` value in the document which doesn't match
+/// the schema it illustrates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExampleError {
+    /// JSON pointer to the value in the document, e.g.
+    /// `/paths/~1pets/get/responses/200/content/application~1json/example`.
+    pub pointer: String,
+    pub error: SchemaError,
+}
+
+impl fmt::Display for ExampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.error)
+    }
+}
+
+impl std::error::Error for ExampleError {}
+
+impl OpenAPI {
+    /// Validates every example and schema default in the document against its
+    /// schema: `example` and `default` on schemas at any depth, and `example` and
+    /// `examples` on parameters, headers and media types. Examples given by
+    /// `externalValue`, or by a `$ref` that doesn't resolve, are skipped; the
+    /// `external-examples` feature checks the former, see `validate_external_examples`.
+    ///
+    /// With the `rayon` feature the values are validated in parallel. Errors are
+    /// returned in document order either way.
+    pub fn validate_examples(&self) -> Result<(), Vec<ExampleError>> {
+        let checks: Vec<_> = collect(self).into_iter().flat_map(|region| region.checks).collect();
+        let errors = run(&checks, &self.components);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Validates `checks`, in parallel with the `rayon` feature, keeping their order.
+pub(crate) fn run(checks: &[Check], components: &Components) -> Vec<ExampleError> {
+    let run = |check: &Check| check.run(components);
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        checks.par_iter().flat_map_iter(run).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    checks.iter().flat_map(run).collect()
+}
+
+/// The checks for one top-level entry of the document: a path, or a component.
+pub(crate) struct Region<'a> {
+    /// JSON pointer to the entry, e.g. `/paths/~1pets` or `/components/schemas/Pet`.
+    pub(crate) pointer: String,
+    pub(crate) checks: Vec<Check<'a>>,
+    /// The `$ref`s to examples used by the checks.
+    pub(crate) example_refs: Vec<&'a str>,
+}
+
+/// Collects the checks of every region, in document order.
+pub(crate) fn collect(spec: &OpenAPI) -> Vec<Region<'_>> {
+    let mut collector = Collector { spec, regions: Vec::new(), media_type: None, externals: Vec::new() };
+    collector.document();
+    collector.regions
+}
+
+/// The examples given by `externalValue`, with the schemas they illustrate, in
+/// document order.
+#[cfg(feature = "external-examples")]
+pub(crate) fn collect_external(spec: &OpenAPI) -> Vec<External<'_>> {
+    let mut collector = Collector { spec, regions: Vec::new(), media_type: None, externals: Vec::new() };
+    collector.document();
+    collector.externals
+}
+
+/// An example given by `externalValue`.
+#[cfg_attr(not(feature = "external-examples"), allow(dead_code))]
+pub(crate) struct External<'a> {
+    /// JSON pointer to the `externalValue`.
+    pub(crate) pointer: String,
+    pub(crate) schema: &'a RefOr<Schema>,
+    pub(crate) example: &'a Example,
+    /// The media type of the example, `None` for parameters and headers.
+    pub(crate) media_type: Option<&'a str>,
+}
+
+/// A value to validate. Collecting these is cheap next to validating them, so it
+/// is done up front and only the validation is spread across threads.
+pub(crate) struct Check<'a> {
+    pointer: String,
+    pub(crate) schema: &'a RefOr<Schema>,
+    value: &'a Value,
+}
+
+impl Check<'_> {
+    fn run(&self, components: &Components) -> Vec<ExampleError> {
+        validate_ref_value(self.schema, self.value, components)
+            .into_iter()
+            .map(|error| ExampleError { pointer: self.pointer.clone(), error })
+            .collect()
+    }
+}
+
+struct Collector<'a> {
+    spec: &'a OpenAPI,
+    regions: Vec<Region<'a>>,
+    /// The media type whose examples are being collected.
+    media_type: Option<&'a str>,
+    externals: Vec<External<'a>>,
+}
+
+impl<'a> Collector<'a> {
+    fn document(&mut self) {
+        let spec = self.spec;
+        for (path, item) in spec.paths.paths.iter() {
+            let pointer = self.begin(child("/paths", path));
+            if let RefOr::Item(item) = item {
+                self.path_item(&pointer, item);
+            }
+        }
+        let components = &spec.components;
+        for (name, schema) in components.schemas.iter() {
+            let pointer = self.begin(child("/components/schemas", name));
+            self.ref_or_schema(&pointer, schema);
+        }
+        for (name, parameter) in components.parameters.iter() {
+            let pointer = self.begin(child("/components/parameters", name));
+            if let RefOr::Item(parameter) = parameter {
+                self.parameter(&pointer, parameter);
+            }
+        }
+        for (name, response) in components.responses.iter() {
+            let pointer = self.begin(child("/components/responses", name));
+            if let RefOr::Item(response) = response {
+                self.response(&pointer, response);
+            }
+        }
+        for (name, body) in components.request_bodies.iter() {
+            let pointer = self.begin(child("/components/requestBodies", name));
+            if let RefOr::Item(body) = body {
+                self.content(&child(&pointer, "content"), &body.content);
+            }
+        }
+        for (name, header) in components.headers.iter() {
+            let pointer = self.begin(child("/components/headers", name));
+            if let RefOr::Item(header) = header {
+                self.header(&pointer, header);
+            }
+        }
+        for (name, callback) in components.callbacks.iter() {
+            let pointer = self.begin(child("/components/callbacks", name));
+            if let RefOr::Item(callback) = callback {
+                for (expression, item) in callback.iter() {
+                    self.path_item(&child(&pointer, expression), item);
+                }
+            }
+        }
+    }
+
+    /// Starts the region at `pointer`, which the checks collected next belong to.
+    fn begin(&mut self, pointer: String) -> String {
+        self.regions.push(Region { pointer: pointer.clone(), checks: Vec::new(), example_refs: Vec::new() });
+        pointer
+    }
+
+    fn push(&mut self, pointer: String, schema: &'a RefOr<Schema>, value: &'a Value) {
+        let region = self.regions.last_mut().expect("checks belong to a region");
+        region.checks.push(Check { pointer, schema, value });
+    }
+
+    fn path_item(&mut self, pointer: &str, item: &'a PathItem) {
+        self.parameters(pointer, &item.parameters);
+        for (method, operation) in item.iter() {
+            let pointer = child(pointer, method);
+            self.parameters(&pointer, &operation.parameters);
+            if let Some(RefOr::Item(body)) = &operation.request_body {
+                self.content(&child(&child(&pointer, "requestBody"), "content"), &body.content);
+            }
+            let responses = child(&pointer, "responses");
+            if let Some(RefOr::Item(response)) = &operation.responses.default {
+                self.response(&child(&responses, "default"), response);
+            }
+            for (status, response) in operation.responses.responses.iter() {
+                if let RefOr::Item(response) = response {
+                    self.response(&child(&responses, &status.to_string()), response);
+                }
+            }
+        }
+    }
+
+    fn parameters(&mut self, pointer: &str, parameters: &'a [RefOr<Parameter>]) {
+        for (i, parameter) in parameters.iter().enumerate() {
+            if let RefOr::Item(parameter) = parameter {
+                self.parameter(&child(&child(pointer, "parameters"), &i.to_string()), parameter);
+            }
+        }
+    }
+
+    fn parameter(&mut self, pointer: &str, parameter: &'a Parameter) {
+        let data = &parameter.data;
+        self.format(pointer, &data.format, data.example.as_ref(), &data.examples);
+    }
+
+    fn header(&mut self, pointer: &str, header: &'a Header) {
+        self.format(pointer, &header.format, header.example.as_ref(), &header.examples);
+    }
+
+    fn format(&mut self, pointer: &str, format: &'a ParameterSchemaOrContent, example: Option<&'a Value>, examples: &'a IndexMap<String, RefOr<Example>>) {
+        match format {
+            ParameterSchemaOrContent::Schema(schema) => {
+                self.examples(pointer, schema, example, examples);
+                self.ref_or_schema(&child(pointer, "schema"), schema);
+            }
+            ParameterSchemaOrContent::Content(content) => self.content(&child(pointer, "content"), content),
+        }
+    }
+
+    fn response(&mut self, pointer: &str, response: &'a Response) {
+        for (name, header) in response.headers.iter() {
+            if let RefOr::Item(header) = header {
+                self.header(&child(&child(pointer, "headers"), name), header);
+            }
+        }
+        self.content(&child(pointer, "content"), &response.content);
+    }
+
+    fn content(&mut self, pointer: &str, content: &'a Content) {
+        for (media_type, media) in content.iter() {
+            let pointer = child(pointer, media_type);
+            if let Some(schema) = &media.schema {
+                self.media_type = Some(media_type);
+                self.examples(&pointer, schema, media.example.as_ref(), &media.examples);
+                self.media_type = None;
+                self.ref_or_schema(&child(&pointer, "schema"), schema);
+            }
+            for (name, encoding) in media.encoding.iter() {
+                for (header_name, header) in encoding.headers.iter() {
+                    if let RefOr::Item(header) = header {
+                        let pointer = child(&child(&child(&pointer, "encoding"), name), "headers");
+                        self.header(&child(&pointer, header_name), header);
+                    }
+                }
+            }
+        }
+    }
+
+    fn examples(&mut self, pointer: &str, schema: &'a RefOr<Schema>, example: Option<&'a Value>, examples: &'a IndexMap<String, RefOr<Example>>) {
+        if let Some(value) = example {
+            self.push(child(pointer, "example"), schema, value);
+        }
+        for (name, example) in examples.iter() {
+            let example = match example {
+                RefOr::Item(example) => Some(example),
+                RefOr::Reference { reference } => {
+                    self.regions.last_mut().expect("checks belong to a region").example_refs.push(reference);
+                    reference.strip_prefix("#/components/examples/")
+                        .and_then(|name| self.spec.components.examples.get(name))
+                        .and_then(RefOr::as_item)
+                }
+            };
+            let pointer = child(&child(pointer, "examples"), name);
+            if let Some(value) = example.and_then(|e| e.value.as_ref()) {
+                self.push(child(&pointer, "value"), schema, value);
+            }
+            if let Some(example) = example.filter(|e| e.external_value.is_some()) {
+                let pointer = child(&pointer, "externalValue");
+                self.externals.push(External { pointer, schema, example, media_type: self.media_type });
+            }
+        }
+    }
+
+    fn ref_or_schema(&mut self, pointer: &str, schema: &'a RefOr<Schema>) {
+        let RefOr::Item(item) = schema else { return };
+        let data = &item.data;
+        for (keyword, value) in [("example", &data.example), ("default", &data.default)] {
+            if let Some(value) = value {
+                self.push(child(pointer, keyword), schema, value);
+            }
+        }
+        for (path, subschema) in subschemas(item) {
+            self.ref_or_schema(&format!("{}{}", pointer, path), subschema);
+        }
+    }
+}
+
+/// The schemas directly nested in `schema`, with their pointers relative to it.
+pub(crate) fn subschemas(schema: &Schema) -> Vec<(String, &RefOr<Schema>)> {
+    fn list<'a>(out: &mut Vec<(String, &'a RefOr<Schema>)>, keyword: &str, schemas: &'a [RefOr<Schema>]) {
+        out.extend(schemas.iter().enumerate().map(|(i, s)| (format!("/{}/{}", keyword, i), s)));
+    }
+    fn additional<'a>(out: &mut Vec<(String, &'a RefOr<Schema>)>, keyword: &str, additional: &'a Option<AdditionalProperties>) {
+        if let Some(AdditionalProperties::Schema(s)) = additional {
+            out.push((format!("/{}", keyword), s));
+        }
+    }
+    fn properties<'a>(out: &mut Vec<(String, &'a RefOr<Schema>)>, properties: &'a RefOrMap<Schema>) {
+        out.extend(properties.iter().map(|(name, s)| (child("/properties", name), s)));
+    }
+    let mut out = Vec::new();
+    let data = &schema.data;
+    for (keyword, s) in [("if", &data.if_), ("then", &data.then), ("else", &data.else_)] {
+        if let Some(s) = s {
+            out.push((format!("/{}", keyword), &**s));
+        }
+    }
+    additional(&mut out, "unevaluatedProperties", &data.unevaluated_properties);
+    additional(&mut out, "unevaluatedItems", &data.unevaluated_items);
+    for (keyword, s) in [("contains", &data.contains), ("propertyNames", &data.property_names), ("contentSchema", &data.content_schema)] {
+        if let Some(s) = s {
+            out.push((format!("/{}", keyword), &**s));
+        }
+    }
+    list(&mut out, "prefixItems", &data.prefix_items);
+    for (keyword, schemas) in [("$defs", &data.defs), ("patternProperties", &data.pattern_properties), ("dependentSchemas", &data.dependent_schemas)] {
+        out.extend(schemas.iter().map(|(name, s)| (child(&format!("/{}", keyword), name), s)));
+    }
+    match &schema.kind {
+        SchemaKind::Type(Type::Object(o)) => {
+            properties(&mut out, &o.properties);
+            additional(&mut out, "additionalProperties", &o.additional_properties);
+        }
+        SchemaKind::Type(Type::Array(a)) => out.extend(a.items.as_deref().map(|s| ("/items".to_string(), s))),
+        SchemaKind::Type(_) => {}
+        SchemaKind::OneOf { one_of } => list(&mut out, "oneOf", one_of),
+        SchemaKind::AllOf { all_of } => list(&mut out, "allOf", all_of),
+        SchemaKind::AnyOf { any_of } => list(&mut out, "anyOf", any_of),
+        SchemaKind::Not { not } => out.push(("/not".to_string(), not)),
+        SchemaKind::Any(a) => {
+            properties(&mut out, &a.properties);
+            additional(&mut out, "additionalProperties", &a.additional_properties);
+            out.extend(a.items.as_deref().map(|s| ("/items".to_string(), s)));
+            list(&mut out, "oneOf", &a.one_of);
+            list(&mut out, "allOf", &a.all_of);
+            list(&mut out, "anyOf", &a.any_of);
+            out.extend(a.not.as_deref().map(|s| ("/not".to_string(), s)));
+        }
+    }
+    out
+}
+
+/// `pointer` extended by the token `segment`, escaped.
+pub(crate) fn child(pointer: &str, segment: &str) -> String {
+    format!("{}/{}", pointer, escape(segment))
+}
+
+/// A JSON pointer token for `segment`, with `~` and `/` escaped as `~0` and `~1`.
+pub(crate) fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// The segment a JSON pointer token stands for, undoing [escape].
+pub(crate) fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_examples() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets/{id}": {
+                    "get": {
+                        "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}, "example": "one"}],
+                        "responses": {
+                            "200": {
+                                "description": "ok",
+                                "content": {"application/json": {
+                                    "schema": {"$ref": "#/components/schemas/Pet"},
+                                    "examples": {"good": {"value": {"name": "Rex"}}, "bad": {"$ref": "#/components/examples/Nameless"}}
+                                }}
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {"name": {"type": "string", "default": 1}},
+                        "example": {"name": "Rex"}
+                    }
+                },
+                "examples": {"Nameless": {"value": {}}}
+            }
+        })).unwrap();
+        let errors = spec.validate_examples().unwrap_err();
+        let found: Vec<_> = errors.iter().map(|e| (e.pointer.as_str(), e.error.keyword)).collect();
+        assert_eq!(found, vec![
+            ("/paths/~1pets~1{id}/get/parameters/0/example", "type"),
+            ("/paths/~1pets~1{id}/get/responses/200/content/application~1json/examples/bad/value", "required"),
+            ("/components/schemas/Pet/properties/name/default", "type"),
+        ]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::*;
+
+/// A typed mapping for a vendor extension, registered by implementing this for
+/// the type its values deserialize into, for [Extensible::ext].
+///
+/// ```
+/// use openapiv3::{Extensible, Extension, Operation};
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct RateLimit { requests: u32, per_seconds: u32 }
+///
+/// impl Extension for RateLimit {
+///     const KEY: &'static str = "x-rate-limit";
+/// }
+///
+/// let mut operation = Operation::default();
+/// operation.set_ext(&RateLimit { requests: 10, per_seconds: 60 }).unwrap();
+/// assert_eq!(operation.ext::<RateLimit>().unwrap().unwrap().requests, 10);
+/// ```
+pub trait Extension: Serialize + DeserializeOwned {
+    /// The key of the extension, starting with `x-`.
+    const KEY: &'static str;
+}
+
+/// An extension value that doesn't have the shape of its [Extension] type.
+#[derive(Debug)]
+pub struct ExtensionError {
+    pub key: String,
+    pub error: serde_json::Error,
+}
+
+impl fmt::Display for ExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {} extension: {}", self.key, self.error)
+    }
+}
+
+impl std::error::Error for ExtensionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The objects of a spec that take `x-` extensions, with typed access to them.
+pub trait Extensible {
+    fn extensions(&self) -> &IndexMap<String, Value>;
+
+    fn extensions_mut(&mut self) -> &mut IndexMap<String, Value>;
+
+    /// The value of the extension `key`, e.g. `x-rate-limit`, as a `T`, `None` if
+    /// absent. Prefer [Extensible::ext] for extensions used in several places.
+    fn get_extension<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, ExtensionError> {
+        self.extensions().get(key)
+            .map(|value| T::deserialize(value).map_err(|error| ExtensionError { key: key.to_string(), error }))
+            .transpose()
+    }
+
+    /// Sets the extension `key` to `value`, replacing any previous value.
+    fn set_extension(&mut self, key: &str, value: &impl Serialize) -> Result<(), ExtensionError> {
+        let value = serde_json::to_value(value).map_err(|error| ExtensionError { key: key.to_string(), error })?;
+        self.extensions_mut().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    /// The value of the extension `E`, `None` if absent.
+    fn ext<E: Extension>(&self) -> Result<Option<E>, ExtensionError> {
+        self.get_extension(E::KEY)
+    }
+
+    /// Sets the extension `E` to `value`, replacing any previous value.
+    fn set_ext<E: Extension>(&mut self, value: &E) -> Result<(), ExtensionError> {
+        self.set_extension(E::KEY, value)
+    }
+
+    /// Removes the extension `E`, returning its value, if it had a valid one.
+    fn remove_ext<E: Extension>(&mut self) -> Result<Option<E>, ExtensionError> {
+        self.extensions_mut().shift_remove(E::KEY)
+            .map(|value| serde_json::from_value(value).map_err(|error| ExtensionError { key: E::KEY.to_string(), error }))
+            .transpose()
+    }
+}
+
+macro_rules! extensible {
+    ($($name:ident),* $(,)?) => {$(
+        impl Extensible for $name {
+            fn extensions(&self) -> &IndexMap<String, Value> {
+                &self.extensions
+            }
+
+            fn extensions_mut(&mut self) -> &mut IndexMap<String, Value> {
+                &mut self.extensions
+            }
+        }
+    )*};
+}
+
+extensible!(
+    OpenAPI,
+    Components,
+    Contact,
+    Discriminator,
+    Encoding,
+    Example,
+    ExternalDocumentation,
+    Header,
+    Info,
+    License,
+    Link,
+    MediaType,
+    Operation,
+    Parameter,
+    PathItem,
+    Paths,
+    RequestBody,
+    Response,
+    Responses,
+    Schema,
+    Server,
+    ServerVariable,
+    Tag,
+    Xml,
+);
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Internal(bool);
+
+    impl Extension for Internal {
+        const KEY: &'static str = "x-internal";
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct RateLimit {
+        requests: u32,
+    }
+
+    impl Extension for RateLimit {
+        const KEY: &'static str = "x-rate-limit";
+    }
+
+    #[test]
+    fn test_typed_extensions() {
+        let mut operation: Operation = serde_json::from_value(json!({
+            "x-internal": true,
+            "x-rate-limit": {"requests": "many"},
+            "responses": {}
+        })).unwrap();
+        assert_eq!(operation.ext::<Internal>().unwrap(), Some(Internal(true)));
+        let error = operation.ext::<RateLimit>().unwrap_err();
+        assert!(error.to_string().starts_with("invalid x-rate-limit extension: "), "{}", error);
+
+        operation.set_ext(&RateLimit { requests: 10 }).unwrap();
+        assert_eq!(operation.extensions["x-rate-limit"], json!({"requests": 10}));
+        assert_eq!(operation.remove_ext::<Internal>().unwrap(), Some(Internal(true)));
+        assert_eq!(operation.ext::<Internal>().unwrap(), None);
+
+        let mut schema: Schema = serde_json::from_value(json!({"type": "string", "x-internal": false})).unwrap();
+        assert_eq!(schema.ext::<Internal>().unwrap(), Some(Internal(false)));
+
+        schema.set_extension("x-codegen-name", &"PetName").unwrap();
+        assert_eq!(schema.get_extension::<String>("x-codegen-name").unwrap().as_deref(), Some("PetName"));
+        assert_eq!(schema.get_extension::<u32>("x-missing").unwrap(), None);
+        assert_eq!(schema.get_extension::<u32>("x-codegen-name").unwrap_err().key, "x-codegen-name");
+    }
+}
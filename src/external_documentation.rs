@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 /// Allows referencing an external resource for extended documentation.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ExternalDocumentation {
     /// A short description of the target documentation.
     /// CommonMark syntax MAY be used for rich text representation.
@@ -13,5 +15,18 @@ pub struct ExternalDocumentation {
     pub url: String,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+#[cfg(feature = "url")]
+#[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+impl ExternalDocumentation {
+    pub fn from_url(url: url::Url) -> Self {
+        ExternalDocumentation { url: url.into(), ..ExternalDocumentation::default() }
+    }
+
+    pub fn parsed_url(&self) -> Result<url::Url, url::ParseError> {
+        url::Url::parse(&self.url)
+    }
+}
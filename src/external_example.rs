@@ -0,0 +1,185 @@
+use std::fmt;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::example_validation::collect_external;
+use crate::instance_validation::validate_ref_value;
+use crate::parameter_style::is_json;
+use crate::*;
+
+/// Why the content of an `externalValue` could not be loaded.
+#[cfg_attr(docsrs, doc(cfg(feature = "external-examples")))]
+#[derive(Debug)]
+pub enum ExternalValueError {
+    /// The file could not be read, or its JSON or YAML could not be parsed.
+    Load(LoadError),
+    Http(Box<ureq::Error>),
+}
+
+impl fmt::Display for ExternalValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalValueError::Load(e) => write!(f, "{}", e),
+            ExternalValueError::Http(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExternalValueError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExternalValueError::Load(e) => Some(e),
+            ExternalValueError::Http(e) => Some(e),
+        }
+    }
+}
+
+impl From<ureq::Error> for ExternalValueError {
+    fn from(e: ureq::Error) -> Self {
+        ExternalValueError::Http(Box::new(e))
+    }
+}
+
+impl From<LoadError> for ExternalValueError {
+    fn from(e: LoadError) -> Self {
+        ExternalValueError::Load(e)
+    }
+}
+
+/// An example given by `externalValue` which couldn't be loaded, or doesn't match
+/// the schema it illustrates, from [OpenAPI::validate_external_examples].
+#[cfg_attr(docsrs, doc(cfg(feature = "external-examples")))]
+#[derive(Debug)]
+pub enum ExternalExampleError {
+    Unavailable {
+        /// JSON pointer to the `externalValue`.
+        pointer: String,
+        error: ExternalValueError,
+    },
+    Invalid(ExampleError),
+}
+
+impl fmt::Display for ExternalExampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalExampleError::Unavailable { pointer, error } => write!(f, "{}: cannot load example: {}", pointer, error),
+            ExternalExampleError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExternalExampleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExternalExampleError::Unavailable { error, .. } => Some(error),
+            ExternalExampleError::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl Example {
+    /// Loads the content `externalValue` points to, `None` without one. URLs are
+    /// fetched over HTTP, and other values read as file paths relative to `base`,
+    /// the directory of the spec, `file://` URLs included. Content that parses as
+    /// JSON, or as YAML for `.yaml` and `.yml` files with the `yaml` feature, is
+    /// returned as its value, anything else as a string.
+    pub fn load_external_value(&self, base: &Path) -> Result<Option<Value>, ExternalValueError> {
+        let Some(location) = &self.external_value else { return Ok(None) };
+        let content = if location.starts_with("http://") || location.starts_with("https://") {
+            ureq::get(location).call()?.body_mut().read_to_string()?
+        } else {
+            let path = base.join(location.strip_prefix("file://").unwrap_or(location));
+            let bytes = std::fs::read(&path).map_err(LoadError::Io)?;
+            let content = String::from_utf8(bytes)
+                .map_err(|e| LoadError::Encoding(format!("{} is not valid UTF-8: {}", path.display(), e)))?;
+            #[cfg(feature = "yaml")]
+            if Format::from_path(&path) == Some(Format::Yaml) {
+                return Ok(Some(serde_yaml::from_str(&content).map_err(LoadError::Yaml)?));
+            }
+            content
+        };
+        Ok(Some(serde_json::from_str(&content).unwrap_or(Value::String(content))))
+    }
+}
+
+impl OpenAPI {
+    /// Loads the examples given by `externalValue`, see
+    /// [Example::load_external_value], and validates them against the schemas
+    /// they illustrate, as [OpenAPI::validate_examples] does inline ones. Those
+    /// of media types other than JSON are only loaded, as their text can't be
+    /// checked against a schema. Errors are returned in document order.
+    pub fn validate_external_examples(&self, base: &Path) -> Result<(), Vec<ExternalExampleError>> {
+        let mut errors = Vec::new();
+        for external in collect_external(self) {
+            let value = match external.example.load_external_value(base) {
+                Ok(value) => value,
+                Err(error) => {
+                    errors.push(ExternalExampleError::Unavailable { pointer: external.pointer, error });
+                    continue;
+                }
+            };
+            let checked = external.media_type.is_none_or(is_json);
+            let Some(value) = value.filter(|_| checked) else { continue };
+            errors.extend(validate_ref_value(external.schema, &value, &self.components).into_iter()
+                .map(|error| ExternalExampleError::Invalid(ExampleError { pointer: external.pointer.clone(), error })));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_external_examples() {
+        let dir = std::env::temp_dir().join(format!("openapiv3-external-examples-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("examples")).unwrap();
+        std::fs::write(dir.join("examples/good.json"), r#"{"name": "Rex"}"#).unwrap();
+        std::fs::write(dir.join("examples/bad.json"), r#"{"name": 7}"#).unwrap();
+        std::fs::write(dir.join("examples/pet.xml"), "<Pet/>").unwrap();
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"post": {
+                "requestBody": {"content": {
+                    "application/json": {
+                        "schema": {"$ref": "#/components/schemas/Pet"},
+                        "examples": {
+                            "good": {"externalValue": "examples/good.json"},
+                            "bad": {"$ref": "#/components/examples/Bad"},
+                            "missing": {"externalValue": "file://examples/missing.json"}
+                        }
+                    },
+                    "application/xml": {
+                        "schema": {"$ref": "#/components/schemas/Pet"},
+                        "examples": {"xml": {"externalValue": "examples/pet.xml"}}
+                    }
+                }},
+                "responses": {}
+            }}},
+            "components": {
+                "schemas": {"Pet": {"type": "object", "properties": {"name": {"type": "string"}}}},
+                "examples": {"Bad": {"externalValue": "examples/bad.json"}}
+            }
+        })).unwrap();
+
+        let examples = &spec.paths.paths["/pets"].as_item().unwrap().post.as_ref().unwrap()
+            .request_body.as_ref().unwrap().as_item().unwrap().content["application/json"].examples;
+        let good = examples["good"].as_item().unwrap().load_external_value(&dir).unwrap();
+        assert_eq!(good, Some(json!({"name": "Rex"})));
+
+        let errors: Vec<String> = spec.validate_external_examples(&dir).unwrap_err().iter().map(ToString::to_string).collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].starts_with("/paths/~1pets/post/requestBody/content/application~1json/examples/bad/externalValue: "), "{}", errors[0]);
+        assert!(errors[1].starts_with("/paths/~1pets/post/requestBody/content/application~1json/examples/missing/externalValue: cannot load example: "));
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+
+use crate::example_validation::child;
+use crate::util::{pascal_case, unique_name};
+use crate::*;
+
+/// Where [OpenAPI::extract_inline_schemas] found a schema, to name it by.
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaContext<'a> {
+    /// The JSON pointer to the schema, where it was declared.
+    pub pointer: &'a str,
+    /// The operation it belongs to, if declared in one.
+    pub operation_id: Option<&'a str>,
+    /// The lowercase method of that operation.
+    pub method: Option<&'a str>,
+    /// The path it was declared under, if any.
+    pub path: Option<&'a str>,
+    /// The name of the component schema it is nested in, if any, e.g. `Pet`
+    /// for a property of `Pet`, or of a schema extracted from that. Between
+    /// that and this schema, the [SchemaContext::default_name] of the schemas
+    /// left in place stands for it, e.g. `PetTags` for the items of property
+    /// `tags` of `Pet`.
+    pub parent: Option<&'a str>,
+    pub location: SchemaLocation<'a>,
+    pub schema: &'a Schema,
+}
+
+/// What a schema found by [OpenAPI::extract_inline_schemas] is the schema of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaLocation<'a> {
+    RequestBody { media_type: &'a str },
+    /// `status` is `default` for the default response.
+    Response { status: &'a str, media_type: &'a str },
+    Parameter { name: &'a str },
+    Property { name: &'a str },
+    Items,
+    AdditionalProperties,
+    /// A branch of `oneOf` or `anyOf`, by position.
+    Variant { index: usize },
+}
+
+impl SchemaContext<'_> {
+    /// A name for the schema, after the enclosing schema, else the operation:
+    /// `PetOwner` for property `owner` of `Pet`, `AddPetRequest` for the request
+    /// body of `addPet`, `AddPetResponse` for its 2XX responses and e.g.
+    /// `AddPet404Response` for others, `PetItem` for the items of an array
+    /// nested in `Pet`, and `PetVariant1` for the first branch of its `oneOf`.
+    /// Operations without an `operationId` are named after method and path,
+    /// e.g. `GetPetsId`.
+    pub fn default_name(&self) -> String {
+        let owner = match (self.parent, self.operation_id, self.method, self.path) {
+            (Some(parent), ..) => parent.to_string(),
+            (None, Some(id), ..) => pascal_case(id),
+            (None, None, method, path) => pascal_case(&format!("{} {}", method.unwrap_or_default(), path.unwrap_or_default())),
+        };
+        let suffix = match self.location {
+            SchemaLocation::RequestBody { .. } => "Request".to_string(),
+            SchemaLocation::Response { status, .. } if status.starts_with('2') => "Response".to_string(),
+            SchemaLocation::Response { status, .. } => format!("{}Response", pascal_case(status)),
+            SchemaLocation::Parameter { name } | SchemaLocation::Property { name } => pascal_case(name),
+            SchemaLocation::Items => "Item".to_string(),
+            SchemaLocation::AdditionalProperties => "Value".to_string(),
+            SchemaLocation::Variant { index } => format!("Variant{}", index + 1),
+        };
+        owner + &suffix
+    }
+}
+
+impl OpenAPI {
+    /// Moves inline schemas that code generators would declare a type for into
+    /// `components.schemas`, and refers to them with `$ref`s instead, for those
+    /// to name the types well. These are schemas with properties, and `oneOf`,
+    /// `anyOf` and `allOf` compositions, found in the request bodies, responses
+    /// and parameters of operations, and nested in component schemas or in the
+    /// schemas extracted: as properties, `items`, `additionalProperties`, or
+    /// `oneOf`/`anyOf` branches. The branches of `allOf`, which are parts of one
+    /// type, are left in place, as are component request bodies, responses and
+    /// parameters, which are named already.
+    ///
+    /// `naming` names each schema, before those nested in it; a name already
+    /// taken gets a number, e.g. `Pet2`. [SchemaContext::default_name] is a
+    /// good choice. Returns the names of the schemas added, in the order found.
+    ///
+    /// ```
+    /// # use openapiv3::*;
+    /// # let mut spec = OpenAPI::default();
+    /// let added = spec.extract_inline_schemas(&|context| context.default_name());
+    /// ```
+    pub fn extract_inline_schemas(&mut self, naming: &dyn Fn(&SchemaContext) -> String) -> Vec<String> {
+        let mut extractor = Extractor {
+            naming,
+            taken: self.schemas.keys().cloned().collect(),
+            added: Vec::new(),
+        };
+        for (name, schema) in self.components.schemas.iter_mut() {
+            if let RefOr::Item(schema) = schema {
+                let owner = Owner { parent: Some(name), ..Owner::default() };
+                extractor.nested(&child("/components/schemas", name), owner, schema);
+            }
+        }
+        for (path, item) in self.paths.paths.iter_mut() {
+            let Some(item) = item.as_mut() else { continue };
+            let pointer = child("/paths", path);
+            let owner = Owner { path: Some(path), ..Owner::default() };
+            extractor.parameters(&child(&pointer, "parameters"), owner, &mut item.parameters);
+            for (method, operation) in item.iter_mut() {
+                let operation_id = operation.operation_id.clone();
+                let owner = Owner { operation_id: operation_id.as_deref(), method: Some(method), ..owner };
+                extractor.operation(&child(&pointer, method), owner, operation);
+            }
+        }
+        let added: Vec<String> = extractor.added.iter().map(|(name, _)| name.clone()).collect();
+        self.schemas.extend(extractor.added.into_iter().map(|(name, schema)| (name, RefOr::Item(schema))));
+        added
+    }
+}
+
+/// What the schemas being extracted are nested in, see [SchemaContext].
+#[derive(Clone, Copy, Default)]
+struct Owner<'a> {
+    operation_id: Option<&'a str>,
+    method: Option<&'a str>,
+    path: Option<&'a str>,
+    parent: Option<&'a str>,
+}
+
+struct Extractor<'n> {
+    naming: &'n dyn Fn(&SchemaContext) -> String,
+    /// The names of the component schemas, including those added.
+    taken: HashSet<String>,
+    added: Vec<(String, Schema)>,
+}
+
+impl Extractor<'_> {
+    fn operation(&mut self, pointer: &str, owner: Owner<'_>, operation: &mut Operation) {
+        self.parameters(&child(pointer, "parameters"), owner, &mut operation.parameters);
+        if let Some(RefOr::Item(body)) = &mut operation.request_body {
+            let pointer = child(pointer, "requestBody");
+            self.content(&child(&pointer, "content"), owner, &mut body.content, |media_type| SchemaLocation::RequestBody { media_type });
+        }
+        let responses = &mut operation.responses;
+        let default = responses.default.as_mut().map(|response| ("default".to_string(), response));
+        let codes = responses.responses.iter_mut().map(|(status, response)| (status.to_string(), response));
+        for (status, response) in default.into_iter().chain(codes) {
+            if let RefOr::Item(response) = response {
+                let pointer = child(&child(&child(pointer, "responses"), &status), "content");
+                self.content(&pointer, owner, &mut response.content, |media_type| SchemaLocation::Response { status: &status, media_type });
+            }
+        }
+    }
+
+    fn parameters(&mut self, pointer: &str, owner: Owner<'_>, parameters: &mut [RefOr<Parameter>]) {
+        for (i, parameter) in parameters.iter_mut().enumerate() {
+            let Some(parameter) = parameter.as_mut() else { continue };
+            let pointer = child(pointer, &i.to_string());
+            let name = parameter.name.clone();
+            let location = SchemaLocation::Parameter { name: &name };
+            match &mut parameter.format {
+                ParameterSchemaOrContent::Schema(schema) => self.slot(&child(&pointer, "schema"), owner, location, schema),
+                ParameterSchemaOrContent::Content(content) => self.content(&child(&pointer, "content"), owner, content, |_| location),
+            }
+        }
+    }
+
+    fn content<'l>(&mut self, pointer: &str, owner: Owner<'_>, content: &'l mut Content, location: impl Fn(&'l str) -> SchemaLocation<'l>) {
+        for (media_type, media) in content.iter_mut() {
+            if let Some(schema) = &mut media.schema {
+                self.slot(&child(&child(pointer, media_type), "schema"), owner, location(media_type), schema);
+            }
+        }
+    }
+
+    /// Extracts the schema in `slot`, if it is one to extract, else the schemas
+    /// nested in it.
+    fn slot(&mut self, pointer: &str, owner: Owner<'_>, location: SchemaLocation<'_>, slot: &mut RefOr<Schema>) {
+        let RefOr::Item(schema) = slot else { return };
+        let context = SchemaContext {
+            pointer,
+            operation_id: owner.operation_id,
+            method: owner.method,
+            path: owner.path,
+            parent: owner.parent,
+            location,
+            schema,
+        };
+        if !extracted(schema) {
+            let parent = context.default_name();
+            self.nested(pointer, Owner { parent: Some(&parent), ..owner }, schema);
+            return;
+        }
+        let base = (self.naming)(&context);
+        let name = unique_name(&base, |name| self.taken.contains(name));
+        self.taken.insert(name.clone());
+        let mut schema = std::mem::replace(slot, RefOr::schema_ref(&name)).into_item().unwrap();
+        let index = self.added.len();
+        self.added.push((name.clone(), Schema::new_any()));
+        self.nested(&child("/components/schemas", &name), Owner { parent: Some(&name), ..owner }, &mut schema);
+        self.added[index].1 = schema;
+    }
+
+    fn nested(&mut self, pointer: &str, owner: Owner<'_>, schema: &mut Schema) {
+        let (properties, additional, items, variants, all_of) = match &mut schema.kind {
+            SchemaKind::Type(Type::Object(o)) => (Some(&mut o.properties), o.additional_properties.as_mut(), None, None, None),
+            SchemaKind::Type(Type::Array(a)) => (None, None, a.items.as_deref_mut(), None, None),
+            SchemaKind::OneOf { one_of } => (None, None, None, Some(("oneOf", one_of)), None),
+            SchemaKind::AnyOf { any_of } => (None, None, None, Some(("anyOf", any_of)), None),
+            SchemaKind::AllOf { all_of } => (None, None, None, None, Some(all_of)),
+            SchemaKind::Any(a) => {
+                let variants = match (a.one_of.is_empty(), a.any_of.is_empty()) {
+                    (false, _) => Some(("oneOf", &mut a.one_of)),
+                    (true, false) => Some(("anyOf", &mut a.any_of)),
+                    (true, true) => None,
+                };
+                (Some(&mut a.properties), a.additional_properties.as_mut(), a.items.as_deref_mut(), variants, Some(&mut a.all_of))
+            }
+            SchemaKind::Type(_) | SchemaKind::Not { .. } => return,
+        };
+        for (name, property) in properties.into_iter().flatten() {
+            let pointer = child(&child(pointer, "properties"), name);
+            self.slot(&pointer, owner, SchemaLocation::Property { name }, property);
+        }
+        if let Some(AdditionalProperties::Schema(schema)) = additional {
+            self.slot(&child(pointer, "additionalProperties"), owner, SchemaLocation::AdditionalProperties, schema);
+        }
+        if let Some(items) = items {
+            self.slot(&child(pointer, "items"), owner, SchemaLocation::Items, items);
+        }
+        if let Some((keyword, variants)) = variants {
+            for (index, variant) in variants.iter_mut().enumerate() {
+                self.slot(&child(&child(pointer, keyword), &index.to_string()), owner, SchemaLocation::Variant { index }, variant);
+            }
+        }
+        for (i, branch) in all_of.into_iter().flatten().enumerate() {
+            if let RefOr::Item(branch) = branch {
+                self.nested(&child(&child(pointer, "allOf"), &i.to_string()), owner, branch);
+            }
+        }
+    }
+}
+
+/// Whether code generators would declare a type for the schema.
+fn extracted(schema: &Schema) -> bool {
+    match &schema.kind {
+        SchemaKind::Type(Type::Object(o)) => !o.properties.is_empty(),
+        SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } | SchemaKind::AllOf { .. } => true,
+        SchemaKind::Any(a) => !a.properties.is_empty() || !a.one_of.is_empty() || !a.any_of.is_empty() || !a.all_of.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::*;
+
+    #[test]
+    fn test_extract_inline_schemas() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {
+                "get": {
+                    "parameters": [{"name": "filter", "in": "query", "schema": {"type": "object", "properties": {"tag": {"type": "string"}}}}],
+                    "responses": {"200": {"description": "", "content": {"application/json": {"schema": {
+                        "type": "array",
+                        "items": {"$ref": "#/components/schemas/Pet"}
+                    }}}}}
+                },
+                "post": {
+                    "operationId": "addPet",
+                    "requestBody": {"content": {"application/json": {"schema": {"type": "object", "properties": {
+                        "name": {"type": "string"},
+                        "owner": {"type": "object", "properties": {"name": {"type": "string"}}}
+                    }}}}},
+                    "responses": {"400": {"description": "", "content": {"application/json": {"schema": {
+                        "oneOf": [{"type": "object", "properties": {"code": {"type": "integer"}}}, {"type": "string"}]
+                    }}}}}
+                }
+            }},
+            "components": {"schemas": {
+                "Pet": {"type": "object", "properties": {"owner": {"type": "array", "items": {"type": "object", "properties": {"id": {"type": "string"}}}}}},
+                "GetPetsFilter": {"type": "string"}
+            }}
+        })).unwrap();
+        let added = spec.extract_inline_schemas(&|context| match context.schema.title.as_deref() {
+            Some(title) => title.to_string(),
+            None => context.default_name(),
+        });
+        assert_eq!(added, ["PetOwnerItem", "GetPetsFilter2", "AddPetRequest", "AddPetRequestOwner", "AddPet400Response", "AddPet400ResponseVariant1"]);
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["components"]["schemas"]["Pet"]["properties"]["owner"]["items"], json!({"$ref": "#/components/schemas/PetOwnerItem"}));
+        assert_eq!(json["components"]["schemas"]["AddPetRequest"]["properties"]["owner"], json!({"$ref": "#/components/schemas/AddPetRequestOwner"}));
+        assert_eq!(json["components"]["schemas"]["AddPet400Response"]["oneOf"], json!([{"$ref": "#/components/schemas/AddPet400ResponseVariant1"}, {"type": "string"}]));
+        let get = &json["paths"]["/pets"]["get"];
+        assert_eq!(get["parameters"][0]["schema"], json!({"$ref": "#/components/schemas/GetPetsFilter2"}));
+        assert_eq!(get["responses"]["200"]["content"]["application/json"]["schema"]["items"], json!({"$ref": "#/components/schemas/Pet"}));
+        assert!(spec.extract_inline_schemas(&|context| context.default_name()).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+//! Plausible strings for generated examples, see [ExampleOptions::seed](crate::ExampleOptions::seed).
+
+use fake::faker::address::en::{BuildingNumber, CityName, CountryName, StateName, StreetName, ZipCode};
+use fake::faker::company::en::CompanyName;
+use fake::faker::internet::en::{SafeEmail, Username};
+use fake::faker::lorem::en::Sentence;
+use fake::faker::name::en::{FirstName, LastName, Name};
+use fake::faker::phone_number::en::PhoneNumber;
+use fake::rand::rngs::StdRng;
+use fake::rand::SeedableRng;
+use fake::Fake;
+
+pub(crate) struct Faker {
+    rng: StdRng,
+}
+
+impl Faker {
+    pub(crate) fn new(seed: u64) -> Self {
+        Faker { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// A string fitting the property `name` or the `format`, `None` if neither
+    /// suggests anything, e.g. `Jane Doe` for `fullName`.
+    pub(crate) fn string(&mut self, name: Option<&str>, format: &str) -> Option<String> {
+        let rng = &mut self.rng;
+        if matches!(format, "email" | "idn-email") {
+            return Some(SafeEmail().fake_with_rng(rng));
+        }
+        if !format.is_empty() {
+            return None;
+        }
+        let name: String = name?.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_ascii_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        let s = if has(&["email"]) {
+            SafeEmail().fake_with_rng(rng)
+        } else if has(&["firstname", "givenname"]) {
+            FirstName().fake_with_rng(rng)
+        } else if has(&["lastname", "surname", "familyname"]) {
+            LastName().fake_with_rng(rng)
+        } else if has(&["username", "login", "handle"]) {
+            Username().fake_with_rng(rng)
+        } else if has(&["company", "organization", "organisation", "employer"]) {
+            CompanyName().fake_with_rng(rng)
+        } else if has(&["phone", "mobile", "fax"]) {
+            PhoneNumber().fake_with_rng(rng)
+        } else if has(&["street", "address"]) {
+            let number: String = BuildingNumber().fake_with_rng(rng);
+            let street: String = StreetName().fake_with_rng(rng);
+            format!("{} {}", number, street)
+        } else if has(&["city", "town"]) {
+            CityName().fake_with_rng(rng)
+        } else if has(&["country"]) {
+            CountryName().fake_with_rng(rng)
+        } else if has(&["state", "province", "region"]) {
+            StateName().fake_with_rng(rng)
+        } else if has(&["zip", "postcode", "postalcode"]) {
+            ZipCode().fake_with_rng(rng)
+        } else if has(&["name"]) {
+            Name().fake_with_rng(rng)
+        } else if has(&["description", "summary", "comment", "bio", "message"]) {
+            Sentence(6..12).fake_with_rng(rng)
+        } else if has(&["title", "subject"]) {
+            let title: String = Sentence(2..5).fake_with_rng(rng);
+            title.trim_end_matches('.').to_string()
+        } else {
+            return None;
+        };
+        Some(s)
+    }
+}
@@ -0,0 +1,119 @@
+use http::Method;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::*;
+
+/// A predicate on the `x-` extensions of an operation, for [FilterOptions].
+pub type ExtensionFilter = Box<dyn Fn(&IndexMap<String, Value>) -> bool>;
+
+/// Which operations [OpenAPI::filter] keeps. An operation is kept if it meets
+/// every criterion that is set: it has one of `tags`, its path starts with one
+/// of `path_prefixes`, it's for one of `methods`, and `extension` accepts its
+/// extensions. Empty criteria accept every operation.
+#[derive(Default)]
+pub struct FilterOptions {
+    pub tags: Vec<String>,
+    pub path_prefixes: Vec<String>,
+    pub methods: Vec<Method>,
+    /// Called with the `x-` extensions of each operation, e.g. to keep those
+    /// without `x-internal: true`.
+    pub extension: Option<ExtensionFilter>,
+}
+
+impl FilterOptions {
+    fn keeps(&self, path: Option<&str>, method: &str, operation: &Operation) -> bool {
+        (self.tags.is_empty() || operation.tags.iter().any(|tag| self.tags.contains(tag)))
+            && (self.path_prefixes.is_empty() || path.is_none_or(|path| self.path_prefixes.iter().any(|prefix| path.starts_with(prefix.as_str()))))
+            && (self.methods.is_empty() || self.methods.iter().any(|m| m.as_str().eq_ignore_ascii_case(method)))
+            && self.extension.as_ref().is_none_or(|extension| extension(&operation.extensions))
+    }
+
+    /// Whether a path item behind a `$ref`, whose operations can't be looked
+    /// at, is kept.
+    fn keeps_reference(&self, path: Option<&str>) -> bool {
+        self.tags.is_empty() && self.methods.is_empty() && self.extension.is_none()
+            && (self.path_prefixes.is_empty() || path.is_none_or(|path| self.path_prefixes.iter().any(|prefix| path.starts_with(prefix.as_str()))))
+    }
+}
+
+impl OpenAPI {
+    /// A copy of the document with only the operations `options` keeps, e.g. to
+    /// publish the public part of an internal API. Path items left without
+    /// operations are removed, and so are the tags and components nothing uses
+    /// anymore, see [OpenAPI::prune_unused]. Webhooks are filtered the same way,
+    /// except by path prefix.
+    pub fn filter(&self, options: FilterOptions) -> OpenAPI {
+        let mut spec = self.clone();
+        retain(&mut spec.paths.paths, &options, true);
+        retain(&mut spec.webhooks, &options, false);
+
+        let used: Vec<String> = spec.operations().flat_map(|(_, _, operation, _)| operation.tags.iter().cloned()).collect();
+        spec.tags.retain(|tag| used.contains(&tag.name));
+        spec.prune_unused();
+        spec
+    }
+}
+
+fn retain(items: &mut IndexMap<String, RefOr<PathItem>>, options: &FilterOptions, by_path: bool) {
+    items.retain(|path, item| {
+        let path = by_path.then_some(path.as_str());
+        let item = match item {
+            RefOr::Item(item) => item,
+            RefOr::Reference { .. } => return options.keeps_reference(path),
+        };
+        let removed: Vec<String> = item.iter()
+            .filter(|(method, operation)| !options.keeps(path, method, operation))
+            .map(|(method, _)| method.to_string())
+            .collect();
+        for method in &removed {
+            item.remove_operation(method);
+        }
+        item.iter().next().is_some()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets": {
+                    "get": {"tags": ["pets"], "responses": {"200": {"$ref": "#/components/responses/Pets"}}},
+                    "post": {"tags": ["pets"], "x-internal": true, "responses": {"200": {"$ref": "#/components/responses/Pet"}}}
+                },
+                "/admin/users": {"get": {"tags": ["admin"], "responses": {}}}
+            },
+            "components": {"responses": {
+                "Pets": {"description": ""},
+                "Pet": {"description": ""}
+            }},
+            "tags": [{"name": "pets"}, {"name": "admin"}]
+        })).unwrap();
+
+        let public = spec.filter(FilterOptions {
+            extension: Some(Box::new(|extensions| extensions.get("x-internal") != Some(&Value::Bool(true)))),
+            path_prefixes: vec!["/pets".to_string()],
+            ..FilterOptions::default()
+        });
+        assert_eq!(serde_json::to_value(&public).unwrap(), json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {"tags": ["pets"], "responses": {"200": {"$ref": "#/components/responses/Pets"}}}}},
+            "components": {"responses": {"Pets": {"description": ""}}},
+            "tags": [{"name": "pets"}]
+        }));
+
+        let admin = spec.filter(FilterOptions { tags: vec!["admin".to_string()], methods: vec![Method::GET], ..FilterOptions::default() });
+        let paths: Vec<&str> = admin.paths.keys().map(String::as_str).collect();
+        assert_eq!(paths, ["/admin/users"]);
+        assert!(admin.components.responses.is_empty());
+    }
+}
@@ -0,0 +1,487 @@
+use std::fmt;
+use std::mem::take;
+
+use crate::*;
+use crate::example_validation::unescape;
+
+/// Why `allOf` branches can't be flattened into one schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlattenError {
+    /// A `$ref` to a schema that isn't among the components.
+    Unresolved(String),
+    /// A reference to a schema whose `allOf` includes itself.
+    Cycle(String),
+    /// Branches no value is valid against together, e.g. of different types,
+    /// by the keyword they disagree on.
+    Incompatible(String),
+    /// Branches that can't be expressed as one schema, e.g. with two different
+    /// `pattern`s, or that aren't flattened, like `oneOf`, by the keyword.
+    Unsupported(String),
+}
+
+impl fmt::Display for FlattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlattenError::Unresolved(reference) => write!(f, "unresolved reference {}", reference),
+            FlattenError::Cycle(reference) => write!(f, "{} includes itself through allOf", reference),
+            FlattenError::Incompatible(keyword) => write!(f, "allOf branches have incompatible `{}`", keyword),
+            FlattenError::Unsupported(keyword) => write!(f, "allOf branches with `{}` can't be flattened", keyword),
+        }
+    }
+}
+
+impl std::error::Error for FlattenError {}
+
+impl Schema {
+    /// Merges the `allOf` branches of the schema into one object, or other
+    /// typed, schema, for code generators and validators that don't handle
+    /// `allOf`: their properties and `required` are combined, and so are their
+    /// constraints, taking the stricter bounds and the common `enum` values.
+    /// Branches that are references are resolved in `components`, and branches
+    /// with `allOf` of their own are flattened first. Where two branches declare
+    /// the same property differently, the property is flattened from both.
+    ///
+    /// Other nested schemas are left as they are, see [OpenAPI::flatten_all_of].
+    /// Note that `additionalProperties: false` in one branch applies to all the
+    /// properties of the flattened schema, which the branch alone would reject.
+    pub fn flatten_all_of(&self, components: &Components) -> Result<Schema, FlattenError> {
+        Flattener { components, stack: Vec::new() }.flatten(self)
+    }
+}
+
+impl OpenAPI {
+    /// Flattens every schema with `allOf` in the document, with
+    /// [Schema::flatten_all_of]. Those that can't be flattened are left as they
+    /// are, and returned with the JSON pointer to each.
+    pub fn flatten_all_of(&mut self) -> Result<(), Vec<(String, FlattenError)>> {
+        let components = self.components.clone();
+        let mut flatten = Flatten { components: &components, errors: Vec::new() };
+        self.transform(&mut flatten);
+        if flatten.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(flatten.errors)
+        }
+    }
+}
+
+struct Flatten<'a> {
+    components: &'a Components,
+    errors: Vec<(String, FlattenError)>,
+}
+
+impl Transform for Flatten<'_> {
+    fn schema(&mut self, pointer: &str, schema: &mut Schema) {
+        let has_all_of = match &schema.kind {
+            SchemaKind::AllOf { .. } => true,
+            SchemaKind::Any(any) => !any.all_of.is_empty(),
+            _ => false,
+        };
+        if has_all_of {
+            match schema.flatten_all_of(self.components) {
+                Ok(flattened) => *schema = flattened,
+                Err(e) => self.errors.push((pointer.to_string(), e)),
+            }
+        }
+    }
+}
+
+struct Flattener<'a> {
+    components: &'a Components,
+    /// The references being flattened, to stop at cycles.
+    stack: Vec<String>,
+}
+
+impl<'a> Flattener<'a> {
+    fn flatten(&mut self, schema: &Schema) -> Result<Schema, FlattenError> {
+        let (rest, branches) = match &schema.kind {
+            SchemaKind::AllOf { all_of } => (None, all_of),
+            SchemaKind::Any(any) if !any.all_of.is_empty() => {
+                let rest = AnySchema { all_of: Vec::new(), ..any.clone() };
+                (Some(Schema::new_kind(SchemaKind::Any(rest))), &any.all_of)
+            }
+            _ => return Ok(schema.clone()),
+        };
+        // Null is valid if the schema says so, or if every branch allows it.
+        let mut flattened = Schema { data: SchemaData { nullable: !branches.is_empty(), ..SchemaData::default() }, kind: SchemaKind::Any(AnySchema::default()) };
+        if let Some(rest) = rest {
+            flattened = self.merge(flattened, rest)?;
+        }
+        for branch in branches {
+            let branch = self.flatten_ref(branch)?;
+            flattened = self.merge(flattened, branch)?;
+        }
+        let nullable = schema.nullable || flattened.nullable;
+        let mut data = merge_data(schema.data.clone(), take(&mut flattened.data))?;
+        data.nullable = nullable;
+        Ok(Schema { data, kind: flattened.kind })
+    }
+
+    fn flatten_ref(&mut self, schema: &RefOr<Schema>) -> Result<Schema, FlattenError> {
+        let reference = match schema {
+            RefOr::Item(schema) => return self.flatten(schema),
+            RefOr::Reference { reference } => reference,
+        };
+        if self.stack.contains(reference) {
+            return Err(FlattenError::Cycle(reference.clone()));
+        }
+        let resolved = self.resolve(reference)?;
+        self.stack.push(reference.clone());
+        let flattened = self.flatten(resolved);
+        self.stack.pop();
+        flattened
+    }
+
+    fn resolve(&self, reference: &str) -> Result<&'a Schema, FlattenError> {
+        let mut current = reference;
+        // Chains of references longer than the components go round in circles.
+        for _ in 0..=self.components.schemas.len() {
+            let name = current.strip_prefix("#/components/schemas/").map(unescape);
+            match name.and_then(|name| self.components.schemas.get(&name)) {
+                Some(RefOr::Item(schema)) => return Ok(schema),
+                Some(RefOr::Reference { reference }) => current = reference,
+                None => break,
+            }
+        }
+        Err(FlattenError::Unresolved(reference.to_string()))
+    }
+
+    /// Merges two flattened schemas into one, what is valid against both.
+    fn merge(&mut self, a: Schema, b: Schema) -> Result<Schema, FlattenError> {
+        let nullable = a.nullable && b.nullable;
+        let mut data = merge_data(a.data, b.data)?;
+        data.nullable = nullable;
+        let kind = match (typed(a.kind)?, typed(b.kind)?) {
+            (Some(a), Some(b)) => SchemaKind::Type(self.merge_types(a, b)?),
+            (Some(t), None) | (None, Some(t)) => SchemaKind::Type(t),
+            (None, None) => SchemaKind::Any(AnySchema::default()),
+        };
+        Ok(Schema { data, kind })
+    }
+
+    fn merge_refs(&mut self, a: &RefOr<Schema>, b: &RefOr<Schema>) -> Result<RefOr<Schema>, FlattenError> {
+        if a == b {
+            return Ok(a.clone());
+        }
+        let (a, b) = (self.flatten_ref(a)?, self.flatten_ref(b)?);
+        Ok(RefOr::Item(self.merge(a, b)?))
+    }
+
+    fn merge_types(&mut self, a: Type, b: Type) -> Result<Type, FlattenError> {
+        Ok(match (a, b) {
+            (Type::String(a), Type::String(b)) => Type::String(StringType {
+                format: same_format(a.format, b.format)?,
+                pattern: one("pattern", a.pattern, b.pattern)?,
+                enumeration: intersect(a.enumeration, b.enumeration)?,
+                min_length: max(a.min_length, b.min_length),
+                max_length: min(a.max_length, b.max_length),
+                content_encoding: same("contentEncoding", a.content_encoding, b.content_encoding)?,
+                content_media_type: same("contentMediaType", a.content_media_type, b.content_media_type)?,
+            }),
+            (Type::Number(a), Type::Number(b)) => {
+                let (lower, upper) = bounds(a.lower_bound(), b.lower_bound(), a.upper_bound(), b.upper_bound())?;
+                Type::Number(NumberType {
+                    format: same_format(a.format, b.format)?,
+                    multiple_of: one("multipleOf", a.multiple_of, b.multiple_of)?,
+                    minimum: lower.map(|(v, _)| v),
+                    exclusive_minimum: ExclusiveBound::Flag(lower.is_some_and(|(_, e)| e)),
+                    maximum: upper.map(|(v, _)| v),
+                    exclusive_maximum: ExclusiveBound::Flag(upper.is_some_and(|(_, e)| e)),
+                    enumeration: intersect(a.enumeration, b.enumeration)?,
+                })
+            }
+            (Type::Integer(a), Type::Integer(b)) => {
+                let (lower, upper) = bounds(a.lower_bound(), b.lower_bound(), a.upper_bound(), b.upper_bound())?;
+                Type::Integer(IntegerType {
+                    format: same_format(a.format, b.format)?,
+                    multiple_of: one("multipleOf", a.multiple_of, b.multiple_of)?,
+                    minimum: lower.map(|(v, _)| v),
+                    exclusive_minimum: ExclusiveBound::Flag(lower.is_some_and(|(_, e)| e)),
+                    maximum: upper.map(|(v, _)| v),
+                    exclusive_maximum: ExclusiveBound::Flag(upper.is_some_and(|(_, e)| e)),
+                    enumeration: intersect(a.enumeration, b.enumeration)?,
+                })
+            }
+            // Integers are numbers, but number constraints aren't integer ones.
+            (Type::Integer(i), Type::Number(n)) | (Type::Number(n), Type::Integer(i)) => {
+                if n != NumberType::default() {
+                    return Err(FlattenError::Unsupported("type".to_string()));
+                }
+                Type::Integer(i)
+            }
+            (Type::Boolean {}, Type::Boolean {}) => Type::Boolean {},
+            (Type::Object(mut a), Type::Object(b)) => {
+                for (name, property) in b.properties {
+                    match a.properties.get_mut(&name) {
+                        Some(existing) => *existing = self.merge_refs(existing, &property)?,
+                        None => {
+                            a.properties.insert(name, property);
+                        }
+                    }
+                }
+                for name in b.required {
+                    if !a.required.contains(&name) {
+                        a.required.push(name);
+                    }
+                }
+                a.additional_properties = match (a.additional_properties, b.additional_properties) {
+                    (None, other) | (other, None) | (Some(AdditionalProperties::Any(true)), other) | (other, Some(AdditionalProperties::Any(true))) => other,
+                    (Some(AdditionalProperties::Any(false)), _) | (_, Some(AdditionalProperties::Any(false))) => Some(AdditionalProperties::Any(false)),
+                    (Some(AdditionalProperties::Schema(a)), Some(AdditionalProperties::Schema(b))) => {
+                        Some(AdditionalProperties::Schema(Box::new(self.merge_refs(&a, &b)?)))
+                    }
+                };
+                a.min_properties = max(a.min_properties, b.min_properties);
+                a.max_properties = min(a.max_properties, b.max_properties);
+                if a.min_properties.zip(a.max_properties).is_some_and(|(min, max)| min > max) {
+                    return Err(FlattenError::Incompatible("maxProperties".to_string()));
+                }
+                Type::Object(a)
+            }
+            (Type::Array(a), Type::Array(b)) => {
+                let items = match (a.items, b.items) {
+                    (Some(a), Some(b)) => Some(Box::new(self.merge_refs(&a, &b)?)),
+                    (a, b) => a.or(b),
+                };
+                let array = ArrayType {
+                    items,
+                    min_items: max(a.min_items, b.min_items),
+                    max_items: min(a.max_items, b.max_items),
+                    unique_items: a.unique_items || b.unique_items,
+                };
+                if array.min_items.zip(array.max_items).is_some_and(|(min, max)| min > max) {
+                    return Err(FlattenError::Incompatible("maxItems".to_string()));
+                }
+                Type::Array(array)
+            }
+            _ => return Err(FlattenError::Incompatible("type".to_string())),
+        })
+    }
+}
+
+/// The type of a flattened schema, or `None` if it allows any. Untyped schemas
+/// with only object keywords, as `allOf` branches that add `required` often are,
+/// are objects.
+fn typed(kind: SchemaKind) -> Result<Option<Type>, FlattenError> {
+    let mut any = match kind {
+        SchemaKind::Type(t) => return Ok(Some(t)),
+        SchemaKind::Any(any) => any,
+        SchemaKind::OneOf { .. } => return Err(FlattenError::Unsupported("oneOf".to_string())),
+        SchemaKind::AnyOf { .. } => return Err(FlattenError::Unsupported("anyOf".to_string())),
+        SchemaKind::Not { .. } => return Err(FlattenError::Unsupported("not".to_string())),
+        SchemaKind::AllOf { .. } => return Err(FlattenError::Unsupported("allOf".to_string())),
+    };
+    let object = ObjectType {
+        properties: take(&mut any.properties),
+        required: take(&mut any.required),
+        additional_properties: any.additional_properties.take(),
+        min_properties: any.min_properties.take(),
+        max_properties: any.max_properties.take(),
+    };
+    let typ = any.typ.take();
+    if any != AnySchema::default() || typ.as_deref().is_some_and(|t| t != "object") {
+        return Err(FlattenError::Unsupported("type".to_string()));
+    }
+    Ok((typ.is_some() || object != ObjectType::default()).then_some(Type::Object(object)))
+}
+
+/// Merges the annotations and other keywords of two schemas, keeping those of
+/// `a` where both have one.
+fn merge_data(mut a: SchemaData, b: SchemaData) -> Result<SchemaData, FlattenError> {
+    a.read_only |= b.read_only;
+    a.write_only |= b.write_only;
+    a.deprecated |= b.deprecated;
+    a.external_docs = a.external_docs.or(b.external_docs);
+    a.example = a.example.or(b.example);
+    a.title = a.title.or(b.title);
+    a.description = a.description.or(b.description);
+    a.discriminator = a.discriminator.or(b.discriminator);
+    a.xml = a.xml.or(b.xml);
+    a.default = a.default.or(b.default);
+    a.const_value = same("const", a.const_value, b.const_value)?;
+    a.if_ = one("if", a.if_, b.if_)?;
+    a.then = one("then", a.then, b.then)?;
+    a.else_ = one("else", a.else_, b.else_)?;
+    a.unevaluated_properties = one("unevaluatedProperties", a.unevaluated_properties, b.unevaluated_properties)?;
+    a.unevaluated_items = one("unevaluatedItems", a.unevaluated_items, b.unevaluated_items)?;
+    a.dialect = a.dialect.or(b.dialect);
+    a.id = a.id.or(b.id);
+    a.anchor = a.anchor.or(b.anchor);
+    a.comment = a.comment.or(b.comment);
+    for (name, schema) in b.defs {
+        a.defs.entry(name).or_insert(schema);
+    }
+    if a.examples.is_empty() {
+        a.examples = b.examples;
+    }
+    if !a.prefix_items.is_empty() && !b.prefix_items.is_empty() && a.prefix_items != b.prefix_items {
+        return Err(FlattenError::Unsupported("prefixItems".to_string()));
+    }
+    if a.prefix_items.is_empty() {
+        a.prefix_items = b.prefix_items;
+    }
+    a.contains = one("contains", a.contains, b.contains)?;
+    a.min_contains = one("minContains", a.min_contains, b.min_contains)?;
+    a.max_contains = one("maxContains", a.max_contains, b.max_contains)?;
+    for (pattern, schema) in b.pattern_properties {
+        if a.pattern_properties.get(&pattern).is_some_and(|s| *s != schema) {
+            return Err(FlattenError::Unsupported("patternProperties".to_string()));
+        }
+        a.pattern_properties.insert(pattern, schema);
+    }
+    a.property_names = one("propertyNames", a.property_names, b.property_names)?;
+    for (property, schema) in b.dependent_schemas {
+        if a.dependent_schemas.get(&property).is_some_and(|s| *s != schema) {
+            return Err(FlattenError::Unsupported("dependentSchemas".to_string()));
+        }
+        a.dependent_schemas.insert(property, schema);
+    }
+    for (property, required) in b.dependent_required {
+        let existing = a.dependent_required.entry(property).or_default();
+        existing.extend(required.into_iter().filter(|r| !existing.contains(r)).collect::<Vec<_>>());
+    }
+    a.content_schema = one("contentSchema", a.content_schema, b.content_schema)?;
+    for (key, value) in b.extensions {
+        a.extensions.entry(key).or_insert(value);
+    }
+    Ok(a)
+}
+
+/// Either keyword, which must be the same if both are set, as values can't meet
+/// both otherwise.
+fn same<T: PartialEq>(keyword: &str, a: Option<T>, b: Option<T>) -> Result<Option<T>, FlattenError> {
+    match (a, b) {
+        (Some(a), Some(b)) if a != b => Err(FlattenError::Incompatible(keyword.to_string())),
+        (a, b) => Ok(a.or(b)),
+    }
+}
+
+/// Either keyword, which must be the same if both are set, as one keyword can't
+/// express both otherwise.
+fn one<T: PartialEq>(keyword: &str, a: Option<T>, b: Option<T>) -> Result<Option<T>, FlattenError> {
+    match (a, b) {
+        (Some(a), Some(b)) if a != b => Err(FlattenError::Unsupported(keyword.to_string())),
+        (a, b) => Ok(a.or(b)),
+    }
+}
+
+fn same_format<T: PartialEq>(a: VariantOrUnknownOrEmpty<T>, b: VariantOrUnknownOrEmpty<T>) -> Result<VariantOrUnknownOrEmpty<T>, FlattenError> {
+    match (a, b) {
+        (VariantOrUnknownOrEmpty::Empty, f) | (f, VariantOrUnknownOrEmpty::Empty) => Ok(f),
+        (a, b) if a == b => Ok(a),
+        _ => Err(FlattenError::Incompatible("format".to_string())),
+    }
+}
+
+/// The values both enums allow, or those of either if the other allows any.
+fn intersect<T: PartialEq>(mut a: Vec<T>, b: Vec<T>) -> Result<Vec<T>, FlattenError> {
+    if a.is_empty() {
+        return Ok(b);
+    }
+    if !b.is_empty() {
+        a.retain(|v| b.contains(v));
+        if a.is_empty() {
+            return Err(FlattenError::Incompatible("enum".to_string()));
+        }
+    }
+    Ok(a)
+}
+
+fn max<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn min<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+type Bound<T> = Option<(T, bool)>;
+
+/// The stricter of each pair of bounds, as `(value, exclusive)`, unless no value
+/// is within them.
+fn bounds<T: PartialOrd + Copy>(a_lower: Bound<T>, b_lower: Bound<T>, a_upper: Bound<T>, b_upper: Bound<T>) -> Result<(Bound<T>, Bound<T>), FlattenError> {
+    let stricter = |a: Bound<T>, b: Bound<T>, lower: bool| match (a, b) {
+        (Some(a), Some(b)) => {
+            let b_stricter = if lower { b.0 > a.0 } else { b.0 < a.0 };
+            Some(if b_stricter || (b.0 == a.0 && b.1) { b } else { a })
+        }
+        (a, b) => a.or(b),
+    };
+    let (lower, upper) = (stricter(a_lower, b_lower, true), stricter(a_upper, b_upper, false));
+    if let (Some((l, le)), Some((u, ue))) = (lower, upper) {
+        if l > u || (l == u && (le || ue)) {
+            return Err(FlattenError::Incompatible("maximum".to_string()));
+        }
+    }
+    Ok((lower, upper))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_flatten_all_of() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {"type": "object", "required": ["name"], "properties": {
+                    "name": {"type": "string", "maxLength": 50},
+                    "age": {"type": "integer", "minimum": 0}
+                }},
+                "Cat": {"description": "A cat", "allOf": [
+                    {"$ref": "#/components/schemas/Pet"},
+                    {"required": ["age"], "properties": {
+                        "name": {"type": "string", "minLength": 1, "maxLength": 20},
+                        "lives": {"type": "integer", "enum": [9]}
+                    }}
+                ]},
+                "Kitten": {"allOf": [{"$ref": "#/components/schemas/Cat"}, {"properties": {"age": {"type": "integer", "maximum": 1}}}]},
+                "Broken": {"allOf": [{"type": "string"}, {"type": "integer"}]},
+                "Loop": {"allOf": [{"$ref": "#/components/schemas/Loop"}]}
+            }}
+        })).unwrap();
+
+        let cat = spec.schemas["Cat"].as_item().unwrap().flatten_all_of(&spec.components).unwrap();
+        assert_eq!(serde_json::to_value(&cat).unwrap(), json!({
+            "description": "A cat",
+            "type": "object",
+            "required": ["name", "age"],
+            "properties": {
+                "name": {"type": "string", "minLength": 1, "maxLength": 20},
+                "age": {"type": "integer", "minimum": 0},
+                "lives": {"type": "integer", "enum": [9]}
+            }
+        }));
+
+        let kitten = spec.schemas["Kitten"].as_item().unwrap().flatten_all_of(&spec.components).unwrap();
+        let age = serde_json::to_value(&kitten.properties()["age"]).unwrap();
+        assert_eq!(age, json!({"type": "integer", "minimum": 0, "maximum": 1}));
+
+        let broken = spec.schemas["Broken"].as_item().unwrap().flatten_all_of(&spec.components);
+        assert_eq!(broken, Err(FlattenError::Incompatible("type".to_string())));
+        let bounds = Schema::new_all_of(vec![
+            serde_json::from_value(json!({"type": "number", "minimum": 5})).unwrap(),
+            serde_json::from_value(json!({"type": "number", "maximum": 5, "exclusiveMaximum": true})).unwrap(),
+        ]);
+        assert_eq!(bounds.flatten_all_of(&spec.components), Err(FlattenError::Incompatible("maximum".to_string())));
+
+        let errors = spec.flatten_all_of().unwrap_err();
+        assert_eq!(errors, [
+            ("/components/schemas/Broken".to_string(), FlattenError::Incompatible("type".to_string())),
+            ("/components/schemas/Loop".to_string(), FlattenError::Cycle("#/components/schemas/Loop".to_string())),
+        ]);
+        assert_eq!(spec.schemas["Cat"].as_item().unwrap(), &cat);
+        assert!(matches!(spec.schemas["Broken"].as_item().unwrap().kind, SchemaKind::AllOf { .. }));
+    }
+}
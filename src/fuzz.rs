@@ -0,0 +1,175 @@
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use indexmap::IndexMap;
+use serde_json::{Map, Number, Value};
+
+use crate::{Callback, RefOr, Schema, SchemaData, SchemaKind, StatusCode, Type};
+
+/// How deep generated JSON values (examples, defaults, extensions, ...) nest.
+const MAX_VALUE_DEPTH: usize = 3;
+
+/// How deep generated schemas nest through their subschemas (`items`,
+/// `properties`, `allOf`, `if`, ...).
+const MAX_SCHEMA_DEPTH: usize = 3;
+
+/// How deep generated operations nest through their callbacks.
+const MAX_CALLBACK_DEPTH: usize = 1;
+
+// Schemas and callbacks are reached through the derived impls of the types in
+// between, so unlike for values their depth can't be passed down as an argument.
+thread_local! {
+    static SCHEMA_DEPTH: Cell<usize> = const { Cell::new(0) };
+    static CALLBACK_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Generates with `f` one level deeper into `depth`, or `None` at `max`.
+fn nested<'a, T>(
+    depth: &'static LocalKey<Cell<usize>>,
+    max: usize,
+    u: &mut Unstructured<'a>,
+    f: impl FnOnce(&mut Unstructured<'a>) -> Result<T>,
+) -> Result<Option<T>> {
+    let current = depth.with(Cell::get);
+    if current >= max {
+        return Ok(None);
+    }
+    depth.with(|d| d.set(current + 1));
+    let value = f(u);
+    depth.with(|d| d.set(current));
+    value.map(Some)
+}
+
+impl<'a> Arbitrary<'a> for StatusCode {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(if u.arbitrary()? {
+            StatusCode::Code(u.int_in_range(100..=599)?)
+        } else {
+            StatusCode::Range(u.int_in_range(1..=5)?)
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Schema {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let schema = nested(&SCHEMA_DEPTH, MAX_SCHEMA_DEPTH, u, |u| {
+            Ok(Schema { data: u.arbitrary()?, kind: u.arbitrary()? })
+        })?;
+        match schema {
+            Some(schema) => Ok(schema),
+            None => leaf_schema(u),
+        }
+    }
+}
+
+/// A schema of a scalar type, without subschemas.
+fn leaf_schema(u: &mut Unstructured) -> Result<Schema> {
+    let typ = match u.choose_index(4)? {
+        0 => Type::String(u.arbitrary()?),
+        1 => Type::Number(u.arbitrary()?),
+        2 => Type::Integer(u.arbitrary()?),
+        _ => Type::Boolean {},
+    };
+    let data = SchemaData {
+        nullable: u.arbitrary()?,
+        title: u.arbitrary()?,
+        description: u.arbitrary()?,
+        ..SchemaData::default()
+    };
+    Ok(Schema { data, kind: SchemaKind::Type(typ) })
+}
+
+/// A proptest strategy for any type implementing [Arbitrary], such as
+/// [OpenAPI](crate::OpenAPI), generated from random bytes so it shrinks with them.
+///
+/// ```
+/// # use openapiv3::{arbitrary_strategy, OpenAPI};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(spec in arbitrary_strategy::<OpenAPI>())| {
+///     serde_json::to_string(&spec).unwrap();
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub fn arbitrary_strategy<T>() -> impl proptest::strategy::Strategy<Value=T>
+where
+    T: for<'a> Arbitrary<'a> + std::fmt::Debug,
+{
+    use proptest::prelude::*;
+    proptest::collection::vec(any::<u8>(), 0..8192)
+        .prop_filter_map("not enough bytes", |bytes| T::arbitrary_take_rest(Unstructured::new(&bytes)).ok())
+}
+
+pub(crate) fn callbacks(u: &mut Unstructured) -> Result<IndexMap<String, RefOr<Callback>>> {
+    Ok(nested(&CALLBACK_DEPTH, MAX_CALLBACK_DEPTH, u, |u| u.arbitrary())?.unwrap_or_default())
+}
+
+pub(crate) fn value(u: &mut Unstructured) -> Result<Value> {
+    value_at(u, 0)
+}
+
+fn value_at(u: &mut Unstructured, depth: usize) -> Result<Value> {
+    let kinds = if depth < MAX_VALUE_DEPTH { 6 } else { 4 };
+    Ok(match u.choose_index(kinds)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 if u.arbitrary()? => Value::Number(u.arbitrary::<i64>()?.into()),
+        // JSON has no NaN or infinity.
+        2 => Value::Number(Number::from_f64(u.arbitrary()?).unwrap_or_else(|| 0.into())),
+        3 => Value::String(u.arbitrary()?),
+        4 => Value::Array((0..u.int_in_range(0..=3)?).map(|_| value_at(u, depth + 1)).collect::<Result<_>>()?),
+        _ => {
+            let mut map = Map::new();
+            for _ in 0..u.int_in_range(0..=3)? {
+                map.insert(u.arbitrary()?, value_at(u, depth + 1)?);
+            }
+            Value::Object(map)
+        }
+    })
+}
+
+pub(crate) fn option_value(u: &mut Unstructured) -> Result<Option<Value>> {
+    Ok(if u.arbitrary()? { Some(value(u)?) } else { None })
+}
+
+pub(crate) fn values(u: &mut Unstructured) -> Result<Vec<Value>> {
+    (0..u.int_in_range(0..=3)?).map(|_| value(u)).collect()
+}
+
+pub(crate) fn value_map(u: &mut Unstructured) -> Result<IndexMap<String, Value>> {
+    (0..u.int_in_range(0..=3)?).map(|_| Ok((u.arbitrary()?, value(u)?))).collect()
+}
+
+/// Extension maps, whose keys have to start with `x-` to survive a round trip.
+pub(crate) fn extensions(u: &mut Unstructured) -> Result<IndexMap<String, Value>> {
+    (0..u.int_in_range(0..=2)?).map(|_| Ok((format!("x-{}", u.arbitrary::<String>()?), value(u)?))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpenAPI;
+
+    /// Deterministic pseudo-random bytes, so failures reproduce.
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..len).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as u8
+        }).collect()
+    }
+
+    #[test]
+    fn test_arbitrary_documents_reparse() {
+        for seed in 0..200 {
+            let data = bytes(seed, 4096);
+            let spec = OpenAPI::arbitrary_take_rest(Unstructured::new(&data)).unwrap();
+            let json = serde_json::to_string(&spec).unwrap();
+            if let Err(e) = serde_json::from_str::<OpenAPI>(&json) {
+                panic!("seed {}: {}\n{}", seed, e, json);
+            }
+        }
+    }
+}
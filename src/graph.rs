@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use indexmap::{IndexMap, IndexSet};
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::prune::uses;
+use crate::*;
+
+/// Which components refer to which, from [OpenAPI::schema_graph]. Components are
+/// identified by JSON pointer, e.g. `/components/responses/NotFound`; where a
+/// method takes one, the bare name of a schema, e.g. `User`, will do too.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaGraph {
+    /// Every component, in document order, with those it refers to.
+    edges: IndexMap<String, IndexSet<String>>,
+}
+
+impl OpenAPI {
+    /// The graph of `$ref`s between components, of every section. References to
+    /// components that don't exist, and to other documents, are left out, and so
+    /// are discriminator mappings, which point from a base schema to the schemas
+    /// that usually refer back to it through `allOf`.
+    pub fn schema_graph(&self) -> SchemaGraph {
+        let Ok(Value::Object(components)) = serde_json::to_value(&self.components) else { return SchemaGraph::default() };
+        let mut edges = IndexMap::new();
+        for (section, items) in &components {
+            for (name, component) in items.as_object().into_iter().flatten() {
+                let mut targets = Vec::new();
+                uses(component, ValueRole::Object.child(section).child(name), false, &mut targets);
+                let targets = targets.into_iter()
+                    .filter(|(section, name)| components.get(section).and_then(|s| s.get(name)).is_some())
+                    .map(|(section, name)| pointer(&section, &name))
+                    .collect();
+                edges.insert(pointer(section, name), targets);
+            }
+        }
+        SchemaGraph { edges }
+    }
+}
+
+fn pointer(section: &str, name: &str) -> String {
+    child(&child("/components", section), name)
+}
+
+impl SchemaGraph {
+    fn key<'a>(&self, component: &'a str) -> std::borrow::Cow<'a, str> {
+        if component.starts_with('/') {
+            component.into()
+        } else {
+            pointer("schemas", component).into()
+        }
+    }
+
+    /// Every component, in document order.
+    pub fn components(&self) -> impl Iterator<Item=&str> {
+        self.edges.keys().map(String::as_str)
+    }
+
+    /// The components `component` refers to directly.
+    pub fn dependencies_of(&self, component: &str) -> impl Iterator<Item=&str> {
+        self.edges.get(self.key(component).as_ref()).into_iter().flatten().map(String::as_str)
+    }
+
+    /// The components referring directly to `component`.
+    pub fn dependents_of<'s>(&'s self, component: &str) -> impl Iterator<Item=&'s str> {
+        let key = self.key(component).into_owned();
+        self.edges.iter().filter(move |(_, targets)| targets.contains(&key)).map(|(source, _)| source.as_str())
+    }
+
+    /// Every component after those it refers to, e.g. to emit types in an order
+    /// where each is declared before it is used. The components of a cycle, which
+    /// can't be ordered so, are next to each other, as [SchemaGraph::cycles] lists
+    /// them. Otherwise, document order is kept where possible.
+    pub fn topological_order(&self) -> Vec<&str> {
+        self.strongly_connected().into_iter().flatten().collect()
+    }
+
+    /// The groups of components referring to each other in a cycle, including any
+    /// that refers to itself, in topological order.
+    pub fn cycles(&self) -> Vec<Vec<&str>> {
+        self.strongly_connected().into_iter()
+            .filter(|group| group.len() > 1 || self.edges[group[0]].contains(group[0]))
+            .collect()
+    }
+
+    /// The strongly connected components, by Tarjan's algorithm, which finds them
+    /// after every one they refer to.
+    fn strongly_connected(&self) -> Vec<Vec<&str>> {
+        let mut tarjan = Tarjan { graph: self, index: HashMap::new(), low: HashMap::new(), stack: IndexSet::new(), groups: Vec::new() };
+        for node in self.edges.keys() {
+            if !tarjan.index.contains_key(node.as_str()) {
+                tarjan.visit(node);
+            }
+        }
+        tarjan.groups
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a SchemaGraph,
+    index: HashMap<&'a str, usize>,
+    low: HashMap<&'a str, usize>,
+    stack: IndexSet<&'a str>,
+    groups: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, node: &'a str) {
+        let index = self.index.len();
+        self.index.insert(node, index);
+        self.low.insert(node, index);
+        self.stack.insert(node);
+        for target in &self.graph.edges[node] {
+            let target = target.as_str();
+            if !self.index.contains_key(target) {
+                self.visit(target);
+                let low = self.low[node].min(self.low[target]);
+                self.low.insert(node, low);
+            } else if self.stack.contains(target) {
+                let low = self.low[node].min(self.index[target]);
+                self.low.insert(node, low);
+            }
+        }
+        if self.low[node] == index {
+            let start = self.stack.get_index_of(node).unwrap();
+            let mut group: Vec<_> = self.stack.drain(start..).collect();
+            group.sort_by_key(|member| self.graph.edges.get_index_of(*member));
+            self.groups.push(group);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_schema_graph() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {
+                "responses": {"Users": {"description": "", "content": {"application/json": {
+                    "schema": {"type": "array", "items": {"$ref": "#/components/schemas/User"}}
+                }}}},
+                "schemas": {
+                    "User": {"type": "object", "properties": {
+                        "address": {"$ref": "#/components/schemas/Address"},
+                        "manager": {"$ref": "#/components/schemas/User"},
+                        "missing": {"$ref": "#/components/schemas/Missing"}
+                    }},
+                    "Node": {"type": "object", "properties": {"children": {"$ref": "#/components/schemas/Children"}}},
+                    "Children": {"type": "array", "items": {"$ref": "#/components/schemas/Node"}},
+                    "Address": {"type": "object", "properties": {"$ref": {"type": "string"}}},
+                    "Pet": {"oneOf": [{"$ref": "#/components/schemas/Dog"}], "discriminator": {"propertyName": "kind", "mapping": {"dog": "Dog"}}},
+                    "Dog": {"allOf": [{"$ref": "#/components/schemas/Pet"}]}
+                }
+            }
+        })).unwrap();
+        let graph = spec.schema_graph();
+        let dependencies: Vec<_> = graph.dependencies_of("User").collect();
+        assert_eq!(dependencies, ["/components/schemas/Address", "/components/schemas/User"]);
+        let dependents: Vec<_> = graph.dependents_of("/components/schemas/User").collect();
+        assert_eq!(dependents, ["/components/responses/Users", "/components/schemas/User"]);
+
+        assert_eq!(graph.topological_order(), [
+            "/components/schemas/Address",
+            "/components/schemas/User",
+            "/components/responses/Users",
+            "/components/schemas/Node",
+            "/components/schemas/Children",
+            "/components/schemas/Pet",
+            "/components/schemas/Dog",
+        ]);
+        assert_eq!(graph.cycles(), [
+            vec!["/components/schemas/User"],
+            vec!["/components/schemas/Node", "/components/schemas/Children"],
+            vec!["/components/schemas/Pet", "/components/schemas/Dog"],
+        ]);
+    }
+}
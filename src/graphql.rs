@@ -0,0 +1,418 @@
+use std::fmt::{self, Write};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::util::pascal_case;
+use crate::*;
+
+/// A part of a schema [OpenAPI::to_graphql_sdl] couldn't map as it is, and how
+/// it was approximated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQLWarning {
+    /// JSON pointer to the schema in the document, e.g. `/components/schemas/Pet/properties/tags`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for GraphQLWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+/// The result of [OpenAPI::to_graphql_sdl].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQLExport {
+    pub sdl: String,
+    pub warnings: Vec<GraphQLWarning>,
+}
+
+impl OpenAPI {
+    /// The component schemas as GraphQL SDL type definitions, as a starting point
+    /// for a GraphQL gateway in front of the API:
+    ///
+    /// - objects become `type`s, with required, non-nullable properties as `!` fields,
+    ///   and `allOf` combinations become a `type` with the fields of all their parts.
+    /// - string enums become `enum`s.
+    /// - `oneOf` and `anyOf` of objects, or the objects of a discriminator's mapping,
+    ///   become `union`s.
+    /// - inline objects, enums and unions become definitions named after their
+    ///   place, e.g. `PetOwner` for the `owner` property of `Pet`.
+    /// - other component schemas, e.g. strings with a pattern, stand for their
+    ///   type wherever they're referenced.
+    ///
+    /// What has no GraphQL counterpart, like maps, free-form objects and unions of
+    /// scalars, becomes a `JSON` scalar, and names that aren't valid GraphQL names
+    /// are rewritten; each of these is reported as a warning.
+    pub fn to_graphql_sdl(&self) -> GraphQLExport {
+        let mut exporter = Exporter { spec: self, definitions: IndexMap::new(), warnings: Vec::new(), json: false };
+        for (name, schema) in &self.schemas {
+            exporter.component(name, schema);
+        }
+        let mut sdl = String::new();
+        if exporter.json {
+            sdl.push_str("\"\"\"Any JSON value.\"\"\"\nscalar JSON\n\n");
+        }
+        for (_, definition) in exporter.definitions {
+            sdl.push_str(&definition);
+            sdl.push('\n');
+        }
+        sdl.truncate(sdl.trim_end().len());
+        sdl.push('\n');
+        GraphQLExport { sdl, warnings: exporter.warnings }
+    }
+}
+
+struct Exporter<'a> {
+    spec: &'a OpenAPI,
+    /// The definitions by type name, in the order they're started, so that
+    /// nested ones follow the type they're nested in.
+    definitions: IndexMap<String, String>,
+    warnings: Vec<GraphQLWarning>,
+    /// Whether the `JSON` scalar is used.
+    json: bool,
+}
+
+impl<'a> Exporter<'a> {
+    fn warn(&mut self, pointer: &str, message: impl Into<String>) {
+        let warning = GraphQLWarning { pointer: pointer.to_string(), message: message.into() };
+        // Properties inherited through `allOf` are seen once per type.
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    fn json(&mut self, pointer: &str, message: &str) -> String {
+        self.warn(pointer, format!("{}, mapped to JSON", message));
+        self.json = true;
+        "JSON".to_string()
+    }
+
+    fn component(&mut self, name: &str, schema: &'a RefOr<Schema>) {
+        let pointer = child("/components/schemas", name);
+        if let RefOr::Item(schema) = schema {
+            if defines_type(self.spec, schema, 0) {
+                let type_name = self.type_name(name, &pointer);
+                self.define(&type_name, schema, &pointer);
+            }
+        }
+    }
+
+    /// A valid GraphQL type name for the component `name`.
+    fn type_name(&mut self, name: &str, pointer: &str) -> String {
+        let valid = graphql_name(name);
+        if valid != name {
+            self.warn(pointer, format!("renamed to `{}`", valid));
+        }
+        valid
+    }
+
+    /// Adds the definition of `schema` as the type `name`, unless it's already there.
+    fn define(&mut self, name: &str, schema: &'a Schema, pointer: &str) {
+        if self.definitions.contains_key(name) {
+            return;
+        }
+        self.definitions.insert(name.to_string(), String::new());
+        let body = match &schema.kind {
+            SchemaKind::Type(Type::String(s)) if !s.enumeration.is_empty() => self.enumeration(name, &s.enumeration, pointer),
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => self.union(name, schema, members, pointer),
+            _ => self.object(name, schema, pointer),
+        };
+        let mut definition = description(schema.description.as_deref(), "");
+        definition.push_str(&body);
+        self.definitions.insert(name.to_string(), definition);
+    }
+
+    fn enumeration(&mut self, name: &str, values: &[String], pointer: &str) -> String {
+        let mut out = format!("enum {} {{\n", name);
+        for value in values {
+            let valid = graphql_name(value);
+            let valid = if ["true", "false", "null"].contains(&valid.as_str()) { format!("_{}", valid) } else { valid };
+            if &valid != value {
+                self.warn(pointer, format!("enum value `{}` renamed to `{}`", value, valid));
+            }
+            let _ = writeln!(out, "  {}", valid);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn union(&mut self, name: &str, schema: &'a Schema, members: &'a [RefOr<Schema>], pointer: &str) -> String {
+        let keyword = if matches!(schema.kind, SchemaKind::OneOf { .. }) { "oneOf" } else { "anyOf" };
+        let mapping: Vec<&str> = schema.discriminator.iter().flat_map(|d| d.mapping.values()).map(String::as_str).collect();
+        let mut names = Vec::new();
+        let mut unmapped = 0;
+        for (i, member) in members.iter().enumerate() {
+            match member {
+                RefOr::Item(member) if is_object(self.spec, member, 0) => {
+                    let member_name = format!("{}{}", name, i + 1);
+                    self.define(&member_name, member, &child(&child(pointer, keyword), &i.to_string()));
+                    names.push(member_name);
+                }
+                RefOr::Reference { reference } => match self.referenced(reference) {
+                    Some((component, member)) if is_object(self.spec, member, 0) => names.push(graphql_name(component)),
+                    _ => unmapped += 1,
+                },
+                RefOr::Item(_) => unmapped += 1,
+            }
+        }
+        if members.is_empty() {
+            for reference in mapping {
+                match self.referenced(reference) {
+                    Some((component, member)) if is_object(self.spec, member, 0) => names.push(graphql_name(component)),
+                    _ => unmapped += 1,
+                }
+            }
+        }
+        if names.is_empty() || unmapped > 0 {
+            self.warn(pointer, "union members that aren't objects, defined as a scalar");
+            return format!("scalar {}\n", name);
+        }
+        format!("union {} = {}\n", name, names.join(" | "))
+    }
+
+    fn object(&mut self, name: &str, schema: &'a Schema, pointer: &str) -> String {
+        let mut fields = IndexMap::new();
+        self.fields(schema, name, pointer, &mut fields, 0);
+        let mut out = format!("type {} {{\n", name);
+        for (field, Field { schema: property, required, pointer: property_pointer, owner }) in fields {
+            let valid = graphql_name(field);
+            if valid != field {
+                self.warn(&property_pointer, format!("field renamed to `{}`", valid));
+            }
+            let typ = self.field_type(property, &format!("{}{}", owner, pascal_case(field)), &property_pointer);
+            let non_null = if required && !nullable(self.spec, property) { "!" } else { "" };
+            let description = description(property.as_item().and_then(|p| p.description.as_deref()), "  ");
+            let _ = writeln!(out, "{}  {}: {}{}", description, valid, typ, non_null);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Collects the properties of an object, or of all the parts of an `allOf`.
+    fn fields(&mut self, schema: &'a Schema, owner: &str, pointer: &str, fields: &mut IndexMap<&'a str, Field<'a>>, depth: usize) {
+        if depth > 16 {
+            return;
+        }
+        if let SchemaKind::AllOf { all_of } = &schema.kind {
+            for (i, part) in all_of.iter().enumerate() {
+                match part {
+                    RefOr::Item(part) => self.fields(part, owner, &child(&child(pointer, "allOf"), &i.to_string()), fields, depth + 1),
+                    RefOr::Reference { reference } => match self.referenced(reference) {
+                        Some((name, part)) => self.fields(part, &graphql_name(name), &child("/components/schemas", name), fields, depth + 1),
+                        None => self.warn(pointer, format!("`{}` can't be resolved, its fields are missing", reference)),
+                    },
+                }
+            }
+            return;
+        }
+        for (name, property) in schema.get_properties().into_iter().flatten() {
+            let property_pointer = child(&child(pointer, "properties"), name);
+            let field = Field { schema: property, required: schema.is_required(name), pointer: property_pointer, owner: owner.to_string() };
+            fields.insert(name.as_str(), field);
+        }
+    }
+
+    /// The GraphQL type of a field, without the `!`. `name` names its definition
+    /// if it needs one.
+    fn field_type(&mut self, schema: &'a RefOr<Schema>, name: &str, pointer: &str) -> String {
+        match schema {
+            RefOr::Reference { reference } => match self.referenced(reference) {
+                Some((component, target)) if defines_type(self.spec, target, 0) => graphql_name(component),
+                Some((component, target)) => {
+                    let pointer = child("/components/schemas", component);
+                    self.inline_type(target, &graphql_name(component), &pointer)
+                }
+                None => self.json(pointer, &format!("`{}` isn't a component schema", reference)),
+            },
+            RefOr::Item(schema) => self.inline_type(schema, name, pointer),
+        }
+    }
+
+    fn inline_type(&mut self, schema: &'a Schema, name: &str, pointer: &str) -> String {
+        if defines_type(self.spec, schema, 0) {
+            self.define(name, schema, pointer);
+            return name.to_string();
+        }
+        match &schema.kind {
+            SchemaKind::Type(Type::String(_)) => "String".to_string(),
+            SchemaKind::Type(Type::Integer(i)) => {
+                if serde_json::to_value(&i.format).ok().as_ref().and_then(Value::as_str) == Some("int64") {
+                    self.warn(pointer, "int64 exceeds GraphQL's 32-bit Int, mapped to Int");
+                }
+                "Int".to_string()
+            }
+            SchemaKind::Type(Type::Number(_)) => "Float".to_string(),
+            SchemaKind::Type(Type::Boolean {}) => "Boolean".to_string(),
+            SchemaKind::Type(Type::Array(a)) => match &a.items {
+                Some(items) => {
+                    let items_pointer = child(pointer, "items");
+                    let typ = self.field_type(items.as_ref(), &format!("{}Item", name), &items_pointer);
+                    let non_null = if nullable(self.spec, items.as_ref()) { "" } else { "!" };
+                    format!("[{}{}]", typ, non_null)
+                }
+                None => format!("[{}]", self.json(pointer, "an array without items")),
+            },
+            SchemaKind::AllOf { all_of } if all_of.len() == 1 => self.field_type(&all_of[0], name, &child(&child(pointer, "allOf"), "0")),
+            SchemaKind::Type(Type::Object(o)) if o.additional_properties.is_some() => self.json(pointer, "a map"),
+            SchemaKind::Type(Type::Object(_)) => self.json(pointer, "an object without properties"),
+            SchemaKind::Any(AnySchema { typ: Some(typ), .. }) if typ == "string" => "String".to_string(),
+            SchemaKind::Any(_) => self.json(pointer, "a schema without a type"),
+            SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => self.json(pointer, "a union of scalars"),
+            SchemaKind::AllOf { .. } => self.json(pointer, "an allOf without objects"),
+            SchemaKind::Not { .. } => self.json(pointer, "a `not` schema"),
+        }
+    }
+
+    fn referenced(&self, reference: &'a str) -> Option<(&'a str, &'a Schema)> {
+        let name = component_ref(reference)?;
+        let (name, schema) = self.spec.schemas.get_key_value(name)?;
+        Some((name.as_str(), schema.as_item()?))
+    }
+}
+
+struct Field<'a> {
+    schema: &'a RefOr<Schema>,
+    required: bool,
+    pointer: String,
+    /// The type declaring the property, which names its inline definitions.
+    owner: String,
+}
+
+/// Whether a schema gets a definition of its own: objects, enums and unions.
+fn defines_type(spec: &OpenAPI, schema: &Schema, depth: usize) -> bool {
+    match &schema.kind {
+        SchemaKind::Type(Type::String(s)) => !s.enumeration.is_empty(),
+        SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => true,
+        _ => is_object(spec, schema, depth),
+    }
+}
+
+/// Whether a schema maps to a GraphQL object type, which needs at least one field.
+fn is_object(spec: &OpenAPI, schema: &Schema, depth: usize) -> bool {
+    match &schema.kind {
+        SchemaKind::AllOf { all_of } if all_of.len() > 1 && depth < 16 => all_of.iter().any(|part| match part {
+            RefOr::Item(part) => is_object(spec, part, depth + 1),
+            RefOr::Reference { reference } => component_ref(reference)
+                .and_then(|name| spec.schemas.get(name))
+                .and_then(RefOr::as_item)
+                .is_some_and(|part| is_object(spec, part, depth + 1)),
+        }),
+        _ => schema.get_properties().is_some_and(|p| !p.is_empty()),
+    }
+}
+
+fn nullable(spec: &OpenAPI, schema: &RefOr<Schema>) -> bool {
+    match schema {
+        RefOr::Reference { reference } => component_ref(reference)
+            .and_then(|name| spec.schemas.get(name))
+            .and_then(RefOr::as_item)
+            .is_some_and(|s| s.nullable),
+        RefOr::Item(schema) => schema.nullable,
+    }
+}
+
+/// The component name in a `#/components/schemas/<name>` reference.
+fn component_ref(reference: &str) -> Option<&str> {
+    reference.strip_prefix("#/components/schemas/").filter(|name| !name.contains('/'))
+}
+
+/// `name` with the characters GraphQL names don't allow replaced by `_`.
+fn graphql_name(name: &str) -> String {
+    let mut valid: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+    if !valid.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        valid.insert(0, '_');
+    }
+    valid
+}
+
+/// A block string description, indented by `indent`, or nothing.
+fn description(description: Option<&str>, indent: &str) -> String {
+    match description.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(d) => format!("{}\"\"\"{}\"\"\"\n", indent, d.replace("\"\"\"", "\\\"\"\"")),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_to_graphql_sdl() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "description": "A pet.",
+                    "required": ["name", "kind"],
+                    "properties": {
+                        "name": {"type": "string"},
+                        "kind": {"$ref": "#/components/schemas/Kind"},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "owner": {"type": "object", "properties": {"full-name": {"type": "string"}}},
+                        "attributes": {"type": "object", "additionalProperties": {"type": "string"}},
+                        "code": {"$ref": "#/components/schemas/Code"}
+                    }
+                },
+                "Kind": {"type": "string", "enum": ["cat", "dog", "2-legged"]},
+                "Code": {"type": "string", "pattern": "^[A-Z]+$"},
+                "Dog": {"allOf": [{"$ref": "#/components/schemas/Pet"}, {"type": "object", "required": ["barks"], "properties": {"barks": {"type": "boolean"}}}]},
+                "Animal": {"oneOf": [{"$ref": "#/components/schemas/Pet"}, {"$ref": "#/components/schemas/Dog"}]},
+                "Id": {"oneOf": [{"type": "string"}, {"type": "integer"}]}
+            }}
+        })).unwrap();
+        let export = spec.to_graphql_sdl();
+        assert_eq!(export.sdl, r#""""Any JSON value."""
+scalar JSON
+
+"""A pet."""
+type Pet {
+  name: String!
+  kind: Kind!
+  tags: [String!]
+  owner: PetOwner
+  attributes: JSON
+  code: String
+}
+
+type PetOwner {
+  full_name: String
+}
+
+enum Kind {
+  cat
+  dog
+  _2_legged
+}
+
+type Dog {
+  name: String!
+  kind: Kind!
+  tags: [String!]
+  owner: PetOwner
+  attributes: JSON
+  code: String
+  barks: Boolean!
+}
+
+union Animal = Pet | Dog
+
+scalar Id
+"#);
+        let warnings: Vec<String> = export.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(warnings, vec![
+            "/components/schemas/Pet/properties/owner/properties/full-name: field renamed to `full_name`",
+            "/components/schemas/Pet/properties/attributes: a map, mapped to JSON",
+            "/components/schemas/Kind: enum value `2-legged` renamed to `_2_legged`",
+            "/components/schemas/Id: union members that aren't objects, defined as a scalar",
+        ]);
+    }
+}
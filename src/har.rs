@@ -0,0 +1,297 @@
+use std::convert::TryFrom;
+
+use anyhow::{anyhow, Result};
+use http::Method;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::infer::{scalar_schema, widen};
+use crate::*;
+
+impl OpenAPI {
+    /// Drafts a spec from an HTTP Archive (HAR) recording, e.g. exported from a
+    /// browser's network panel or a proxy, for documenting a service from its traffic.
+    ///
+    /// Requests are grouped by method and path, where numeric and UUID segments
+    /// are taken for path parameters named after the segment before them, e.g.
+    /// `/users/7` becomes `/users/{userId}`. Every observation of a route then
+    /// contributes to its schemas, and parameters seen in only some of them are
+    /// optional. Only `x-` headers are kept as parameters, the others being too
+    /// generic to document. Each origin seen becomes a server.
+    pub fn from_har(har: &Value) -> Result<OpenAPI> {
+        let entries = har.pointer("/log/entries").and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("Not a HAR recording: missing log.entries"))?;
+        let mut spec = OpenAPI::default();
+        spec.info.title = har.pointer("/log/pages/0/title").and_then(Value::as_str).unwrap_or("Recorded API").to_string();
+        spec.info.version = "1.0.0".to_string();
+        let mut routes = IndexMap::<(String, String), Observed>::new();
+        for entry in entries {
+            let request = entry.get("request").ok_or_else(|| anyhow!("HAR entry without a request"))?;
+            let url = request.get("url").and_then(Value::as_str).ok_or_else(|| anyhow!("HAR request without a url"))?;
+            let method = request.get("method").and_then(Value::as_str).unwrap_or("GET").to_uppercase();
+            let (origin, path) = split_url(url);
+            if !origin.is_empty() && !spec.servers.iter().any(|s| s.url == origin) {
+                spec.servers.push(Server { url: origin.to_string(), ..Server::default() });
+            }
+            let (path, variables) = template(path);
+            routes.entry((method, path)).or_default().observe(entry, variables);
+        }
+        for ((method, path), route) in routes {
+            let method = Method::from_bytes(method.as_bytes())?;
+            spec.paths.insert_operation(path, method, route.into_operation())?;
+        }
+        Ok(spec)
+    }
+}
+
+/// What was seen of one route.
+#[derive(Default)]
+struct Observed {
+    count: usize,
+    /// Values by name and location (`path`, `query` or `header`).
+    parameters: IndexMap<(String, &'static str), Vec<String>>,
+    bodies: IndexMap<String, Vec<Value>>,
+    responses: IndexMap<u16, (String, IndexMap<String, Vec<Value>>)>,
+}
+
+impl Observed {
+    fn observe(&mut self, entry: &Value, variables: Vec<(String, String)>) {
+        self.count += 1;
+        let request = &entry["request"];
+        let mut seen = Vec::new();
+        for (name, value) in variables {
+            seen.push((name, "path", value));
+        }
+        for (name, value) in pairs(request.get("queryString")) {
+            seen.push((name, "query", value));
+        }
+        for (name, value) in pairs(request.get("headers")) {
+            if name.len() > 2 && name[..2].eq_ignore_ascii_case("x-") {
+                seen.push((name, "header", value));
+            }
+        }
+        for (name, location, value) in seen {
+            let values = self.parameters.entry((name, location)).or_default();
+            // A value for each observation, so that repeats don't count as more.
+            if values.len() < self.count {
+                values.push(value);
+            }
+        }
+        if let Some((mime, body)) = request.get("postData").and_then(post_data) {
+            self.bodies.entry(mime).or_default().push(body);
+        }
+        let Some(response) = entry.get("response") else { return };
+        let Some(status) = response.get("status").and_then(Value::as_u64).and_then(|s| u16::try_from(s).ok()) else { return };
+        // Requests that never got a response are recorded with status 0.
+        if status == 0 {
+            return;
+        }
+        let text = response.get("statusText").and_then(Value::as_str).unwrap_or_default();
+        let (_, content) = self.responses.entry(status).or_insert_with(|| (text.to_string(), IndexMap::new()));
+        if let Some((mime, body)) = response.get("content").and_then(content_body) {
+            content.entry(mime).or_default().push(body);
+        }
+    }
+
+    fn into_operation(self) -> Operation {
+        let mut operation = Operation::default();
+        for ((name, location), values) in self.parameters {
+            let schema = values.iter().map(|v| scalar_schema(v)).reduce(widen).unwrap_or_else(Schema::new_string);
+            let mut parameter = match location {
+                "path" => Parameter::path(name, schema),
+                "query" => Parameter::query(name, schema),
+                _ => Parameter::header(name, schema),
+            };
+            parameter.required |= values.len() == self.count;
+            parameter.example = values.into_iter().next().filter(|v| !v.is_empty()).map(Value::String);
+            operation.parameters.push(RefOr::Item(parameter));
+        }
+        if !self.bodies.is_empty() {
+            operation.request_body = Some(RefOr::Item(RequestBody {
+                content: self.bodies.into_iter().map(|(mime, bodies)| (mime, media(bodies))).collect(),
+                required: true,
+                ..RequestBody::default()
+            }));
+        }
+        for (status, (description, content)) in self.responses {
+            let response = Response {
+                description,
+                content: content.into_iter().map(|(mime, bodies)| (mime, media(bodies))).collect(),
+                ..Response::default()
+            };
+            operation.responses.responses.insert(StatusCode::Code(status), RefOr::Item(response));
+        }
+        operation
+    }
+}
+
+fn media(bodies: Vec<Value>) -> MediaType {
+    MediaType {
+        schema: Schema::infer_all(&bodies).map(RefOr::Item),
+        example: bodies.into_iter().next(),
+        ..MediaType::default()
+    }
+}
+
+fn post_data(post: &Value) -> Option<(String, Value)> {
+    let mime = essence(post.get("mimeType").and_then(Value::as_str)?);
+    if let Some(params) = post.get("params").and_then(Value::as_array).filter(|p| !p.is_empty()) {
+        let fields = params.iter().filter_map(|p| {
+            let name = p.get("name").and_then(Value::as_str)?;
+            Some((name.to_string(), Value::String(p.get("value").and_then(Value::as_str).unwrap_or_default().to_string())))
+        });
+        return Some((mime, Value::Object(fields.collect())));
+    }
+    let text = post.get("text").and_then(Value::as_str)?;
+    Some((mime.clone(), body(&mime, text)))
+}
+
+fn content_body(content: &Value) -> Option<(String, Value)> {
+    let mime = essence(content.get("mimeType").and_then(Value::as_str)?);
+    // Binary bodies are recorded base64 encoded, and have no schema to infer.
+    if mime.is_empty() || content.get("encoding").and_then(Value::as_str) == Some("base64") {
+        return None;
+    }
+    let text = content.get("text").and_then(Value::as_str)?;
+    Some((mime.clone(), body(&mime, text)))
+}
+
+fn body(mime: &str, text: &str) -> Value {
+    match serde_json::from_str(text) {
+        // `application/json`, and suffixed types like `application/problem+json`.
+        Ok(value) if mime.ends_with("json") => value,
+        _ => Value::String(text.to_string()),
+    }
+}
+
+/// The media type without its parameters, e.g. `application/json` for
+/// `application/json; charset=utf-8`.
+fn essence(mime: &str) -> String {
+    mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+fn pairs(values: Option<&Value>) -> Vec<(String, String)> {
+    values.and_then(Value::as_array).into_iter().flatten().filter_map(|entry| {
+        let name = entry.get("name").and_then(Value::as_str)?;
+        let value = entry.get("value").and_then(Value::as_str).unwrap_or_default();
+        Some((name.to_string(), value.to_string()))
+    }).collect()
+}
+
+/// The scheme and host of `url`, and its path without query or fragment.
+fn split_url(url: &str) -> (&str, &str) {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let Some(start) = url.find("://").map(|i| i + 3) else { return ("", url) };
+    match url[start..].find('/') {
+        Some(i) => url.split_at(start + i),
+        None => (url, "/"),
+    }
+}
+
+/// The templated path, with the values taken for its parameters.
+fn template(path: &str) -> (String, Vec<(String, String)>) {
+    let mut templated = String::new();
+    let mut variables = Vec::new();
+    let mut previous = "";
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        templated.push('/');
+        if is_identifier(segment) {
+            let base = previous.strip_suffix('s').unwrap_or(previous);
+            let mut name = if base.is_empty() { "id".to_string() } else { format!("{}Id", base) };
+            if variables.iter().any(|(n, _)| *n == name) {
+                name = format!("{}{}", name, variables.len() + 1);
+            }
+            templated.push_str(&format!("{{{}}}", name));
+            variables.push((name, segment.to_string()));
+            previous = "";
+        } else {
+            templated.push_str(segment);
+            previous = segment;
+        }
+    }
+    if templated.is_empty() {
+        templated.push('/');
+    }
+    (templated, variables)
+}
+
+fn is_identifier(segment: &str) -> bool {
+    let uuid = segment.len() == 36 && segment.bytes().enumerate().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => c == b'-',
+        _ => c.is_ascii_hexdigit(),
+    });
+    uuid || segment.bytes().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(method: &str, url: &str, headers: Value, post: Option<Value>, status: u16, body: &str) -> Value {
+        let mut request = json!({"method": method, "url": url, "headers": headers, "queryString": []});
+        if let Some((_, query)) = url.split_once('?') {
+            let query: Vec<Value> = query.split('&').map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                json!({"name": name, "value": value})
+            }).collect();
+            request["queryString"] = json!(query);
+        }
+        if let Some(post) = post {
+            request["postData"] = post;
+        }
+        json!({
+            "request": request,
+            "response": {"status": status, "statusText": "OK", "content": {"mimeType": "application/json; charset=utf-8", "text": body}}
+        })
+    }
+
+    #[test]
+    fn test_from_har() {
+        let har = json!({"log": {"entries": [
+            entry("GET", "https://api.shop.com/users/7/orders?page=1", json!([{"name": "X-Trace", "value": "a"}, {"name": "Accept", "value": "*/*"}]), None, 200, r#"[{"id": 1, "total": 9.5}]"#),
+            entry("GET", "https://api.shop.com/users/8/orders", json!([{"name": "X-Trace", "value": "b"}]), None, 200, r#"[{"id": 2, "total": 3, "note": null}]"#),
+            entry("POST", "https://api.shop.com/users/7/orders", json!([]), Some(json!({"mimeType": "application/json", "text": r#"{"item": "book"}"#})), 201, r#"{"id": 3}"#),
+            entry("GET", "https://cdn.shop.com/", json!([]), None, 0, ""),
+        ]}});
+        let spec = OpenAPI::from_har(&har).unwrap();
+        assert_eq!(spec.servers.iter().map(|s| s.url.as_str()).collect::<Vec<_>>(), vec!["https://api.shop.com", "https://cdn.shop.com"]);
+        assert_eq!(spec.paths.keys().collect::<Vec<_>>(), vec!["/users/{userId}/orders", "/"]);
+
+        let item = spec.paths["/users/{userId}/orders"].as_item().unwrap();
+        let get = item.get.as_ref().unwrap();
+        let parameters: Vec<_> = get.parameters.iter().map(|p| {
+            let p = p.as_item().unwrap();
+            (p.name.as_str(), p.required, p.example.clone())
+        }).collect();
+        assert_eq!(parameters, vec![
+            ("userId", true, Some(json!("7"))),
+            ("page", false, Some(json!("1"))),
+            ("X-Trace", true, Some(json!("a"))),
+        ]);
+        let ok = get.responses.responses[&StatusCode::Code(200)].as_item().unwrap();
+        assert_eq!(serde_json::to_value(&ok.content["application/json"].schema).unwrap(), json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {"id": {"type": "integer"}, "total": {"type": "number"}, "note": {"nullable": true}},
+                "required": ["id", "total"]
+            }
+        }));
+
+        let post = item.post.as_ref().unwrap();
+        let body = post.request_body.as_ref().unwrap().as_item().unwrap();
+        assert_eq!(body.content["application/json"].example, Some(json!({"item": "book"})));
+        assert!(post.responses.responses.contains_key(&StatusCode::Code(201)));
+        // The request that failed has no response to document.
+        assert!(spec.paths["/"].as_item().unwrap().get.as_ref().unwrap().responses.responses.is_empty());
+    }
+
+    #[test]
+    fn test_template() {
+        assert_eq!(template("/users/7/orders/0b0c3c7e-2f6a-4d83-9a4f-3a6f2a4f0a11").0, "/users/{userId}/orders/{orderId}");
+        assert_eq!(template("/7/8").0, "/{id}/{id2}");
+        assert_eq!(template("/v1/items"), ("/v1/items".to_string(), vec![]));
+    }
+}
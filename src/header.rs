@@ -1,3 +1,7 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::parameter_style::{parse_simple, Style};
 use crate::*;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
@@ -8,6 +12,8 @@ use serde::{Deserialize, Serialize};
 /// 2) in MUST NOT be specified, it is implicitly in header.
 /// 3) All traits that are affected by the location MUST be applicable to a location of header (for example, style).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Header {
     /// A brief description of the parameter. This could
@@ -31,10 +37,137 @@ pub struct Header {
     #[serde(flatten)]
     pub format: ParameterSchemaOrContent,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub example: Option<serde_json::Value>,
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub examples: IndexMap<String, RefOr<Example>>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+impl Header {
+    /// Serializes `value` as the header's value, in `simple` style, or as its
+    /// media type says for a header with `content`, falling back to JSON.
+    pub fn serialize_value(&self, value: &Value) -> String {
+        match &self.format {
+            ParameterSchemaOrContent::Content(_) => self.format.serialize_content(value).unwrap_or_else(|_| value.to_string()),
+            ParameterSchemaOrContent::Schema(_) => Style::Simple.serialize("", value, false, &str::to_string),
+        }
+    }
+
+    /// Parses a value of the header, typed by its schema, the inverse of
+    /// [Header::serialize_value]. `None` if it doesn't parse as the schema says.
+    pub fn parse_value(&self, spec: &OpenAPI, raw: &str) -> Option<Value> {
+        match &self.format {
+            ParameterSchemaOrContent::Content(_) => self.format.parse_content(raw).ok(),
+            ParameterSchemaOrContent::Schema(schema) => {
+                let view = spec.resolved();
+                parse_simple(view, view.schema(schema).ok(), raw, false)
+            }
+        }
+    }
+}
+
+impl Response {
+    /// The headers of an HTTP response sending `values` as this response's
+    /// headers, by name. Values for headers the response doesn't declare are
+    /// sent as text.
+    pub fn header_map(&self, spec: &OpenAPI, values: &IndexMap<String, Value>) -> Result<http::HeaderMap> {
+        let mut map = http::HeaderMap::new();
+        for (name, value) in values {
+            let serialized = match self.headers.get(name) {
+                Some(header) => header.resolve(spec)?.serialize_value(value),
+                None => Style::Simple.serialize("", value, false, &str::to_string),
+            };
+            map.append(http::HeaderName::from_bytes(name.as_bytes())?, http::HeaderValue::from_str(&serialized)?);
+        }
+        Ok(map)
+    }
+
+    /// The values of this response's declared headers in an actual HTTP
+    /// response, typed by their schemas. Repeated headers are joined with commas,
+    /// as HTTP allows, and undeclared ones are ignored.
+    pub fn parse_headers(&self, spec: &OpenAPI, headers: &http::HeaderMap) -> Result<IndexMap<String, Value>, Vec<ParameterError>> {
+        let mut values = IndexMap::new();
+        let mut errors = Vec::new();
+        for (name, header) in &self.headers {
+            let header = match header.resolve(spec) {
+                Ok(header) => header,
+                Err(e) => {
+                    errors.push(ParameterError::Reference(e.to_string()));
+                    continue;
+                }
+            };
+            let raw: Vec<&str> = headers.get_all(name.as_str()).iter().filter_map(|v| v.to_str().ok()).collect();
+            if raw.is_empty() {
+                if header.required {
+                    errors.push(ParameterError::Missing(name.clone()));
+                }
+                continue;
+            }
+            let raw = raw.join(",");
+            match header.parse_value(spec, &raw) {
+                Some(value) => { values.insert(name.clone(), value); }
+                None => errors.push(ParameterError::Invalid { parameter: name.clone(), value: raw }),
+            }
+        }
+        if errors.is_empty() { Ok(values) } else { Err(errors) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_schema_or_content() {
+        let header: Header = serde_json::from_value(json!({"content": {"application/json": {"schema": {"type": "object"}}}})).unwrap();
+        assert!(matches!(header.format, ParameterSchemaOrContent::Content(_)));
+        let err = serde_json::from_value::<Header>(json!({"schema": {"type": "string"}, "content": {"text/plain": {}}})).unwrap_err();
+        assert_eq!(err.to_string(), "only one of `schema` and `content` may be given");
+        assert!(serde_json::from_value::<Header>(json!({"description": "d"})).is_err());
+    }
+
+    #[test]
+    fn test_response_headers() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"headers": {
+                "RateLimit": {"required": true, "schema": {"type": "integer"}}
+            }}
+        })).unwrap();
+        let response: Response = serde_json::from_value(json!({
+            "description": "ok",
+            "headers": {
+                "X-Rate-Limit": {"$ref": "#/components/headers/RateLimit"},
+                "X-Tags": {"schema": {"type": "array", "items": {"type": "string"}}},
+                "X-Meta": {"content": {"application/json": {"schema": {"type": "object"}}}}
+            }
+        })).unwrap();
+        let values = IndexMap::from([
+            ("X-Rate-Limit".to_string(), json!(100)),
+            ("X-Tags".to_string(), json!(["a", "b"])),
+            ("X-Meta".to_string(), json!({"page": 2})),
+        ]);
+        let map = response.header_map(&spec, &values).unwrap();
+        assert_eq!(map["x-tags"], "a,b");
+        assert_eq!(map["x-meta"], r#"{"page":2}"#);
+        assert_eq!(response.parse_headers(&spec, &map).unwrap(), values);
+
+        let mut map = http::HeaderMap::new();
+        map.insert("x-tags", http::HeaderValue::from_static("a"));
+        map.append("x-tags", http::HeaderValue::from_static("b"));
+        map.insert("x-meta", http::HeaderValue::from_static("{"));
+        let errors = response.parse_headers(&spec, &map).unwrap_err();
+        assert_eq!(errors, vec![
+            ParameterError::Missing("X-Rate-Limit".to_string()),
+            ParameterError::Invalid { parameter: "X-Meta".to_string(), value: "{".to_string() },
+        ]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use http::Method;
+use indexmap::IndexMap;
+
+use crate::paths::key_of;
+use crate::*;
+
+/// Lookup tables over the operations of a spec, built once by [OpenAPI::index] so
+/// that repeated lookups don't each scan every path. The index borrows the spec,
+/// which therefore can't change while it's in use; to see changes, build it again.
+#[derive(Debug, Clone)]
+pub struct OperationIndex<'a> {
+    operations: Vec<ResolvedOperation<'a>>,
+    by_id: HashMap<&'a str, usize>,
+    by_route: HashMap<(&'a str, &'a str), usize>,
+    by_tag: IndexMap<&'a str, Vec<usize>>,
+}
+
+impl OpenAPI {
+    /// Indexes the operations by `operationId`, by method and path, and by tag.
+    /// Path items containing `$ref`s are skipped, as in [OpenAPI::operations].
+    pub fn index(&self) -> OperationIndex<'_> {
+        let operations: Vec<_> = self.resolved().operations().collect();
+        let mut by_id = HashMap::new();
+        let mut by_route = HashMap::with_capacity(operations.len());
+        let mut by_tag = IndexMap::<_, Vec<_>>::new();
+        for (i, op) in operations.iter().enumerate() {
+            if let Some(id) = op.operation.operation_id.as_deref() {
+                by_id.entry(id).or_insert(i);
+            }
+            by_route.insert((op.method, op.path), i);
+            for tag in op.operation.tags.iter() {
+                by_tag.entry(tag.as_str()).or_default().push(i);
+            }
+        }
+        OperationIndex { operations, by_id, by_route, by_tag }
+    }
+}
+
+impl<'a> OperationIndex<'a> {
+    /// The same as [OpenAPI::index].
+    pub fn build(spec: &'a OpenAPI) -> Self {
+        spec.index()
+    }
+
+    /// The operation with this `operationId`. Should the id be reused, against the
+    /// spec's requirement that it be unique, the first such operation.
+    pub fn get_by_id(&self, operation_id: &str) -> Option<ResolvedOperation<'a>> {
+        self.by_id.get(operation_id).map(|&i| self.operations[i])
+    }
+
+    /// The operation for `method` (lowercase, e.g. `get`) on `path`, as written in the spec.
+    pub fn get(&self, method: &str, path: &str) -> Option<ResolvedOperation<'a>> {
+        self.by_route.get(&(method, path)).map(|&i| self.operations[i])
+    }
+
+    /// [OperationIndex::get], for an [http::Method].
+    pub fn get_op(&self, method: &Method, path: &str) -> Option<ResolvedOperation<'a>> {
+        self.get(key_of(method), path)
+    }
+
+    /// The operations tagged with `tag`, in document order.
+    pub fn tagged<'s>(&'s self, tag: &str) -> impl Iterator<Item=ResolvedOperation<'a>> + 's {
+        self.by_tag.get(tag).into_iter().flatten().map(move |&i| self.operations[i])
+    }
+
+    /// The tags used by operations, in order of first use.
+    pub fn tags(&self) -> impl Iterator<Item=&'a str> + '_ {
+        self.by_tag.keys().copied()
+    }
+
+    /// Every indexed operation, in document order.
+    pub fn operations(&self) -> &[ResolvedOperation<'a>] {
+        &self.operations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets": {
+                    "get": {"operationId": "listPets", "tags": ["pets"], "responses": {}},
+                    "post": {"tags": ["pets", "admin"], "responses": {}}
+                },
+                "/users": {"get": {"operationId": "listPets", "tags": ["users"], "responses": {}}}
+            }
+        })).unwrap();
+        let index = spec.index();
+        assert_eq!(index.operations().len(), 3);
+        let first = index.get_by_id("listPets").unwrap();
+        assert_eq!((first.method, first.path), ("get", "/pets"));
+        assert!(index.get_by_id("missing").is_none());
+        // The linear lookup skips operations without an id.
+        assert!(spec.get_operation("missing").is_none());
+        assert!(index.get("post", "/pets").unwrap().operation.operation_id.is_none());
+        assert!(index.get("put", "/pets").is_none());
+        assert_eq!(OperationIndex::build(&spec).get_op(&Method::POST, "/pets").unwrap().method, "post");
+        assert_eq!(index.tagged("pets").map(|op| op.method).collect::<Vec<_>>(), vec!["get", "post"]);
+        assert_eq!(index.tagged("none").count(), 0);
+        assert_eq!(index.tags().collect::<Vec<_>>(), vec!["pets", "admin", "users"]);
+    }
+}
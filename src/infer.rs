@@ -0,0 +1,163 @@
+use serde_json::Value;
+
+use crate::*;
+
+impl Schema {
+    /// A schema describing `example`, for drafting specs from observed data.
+    /// Objects require every key they have, arrays take the widest schema of
+    /// their elements, and strings that look like a date, date-time or UUID get
+    /// that format. `null` gives a nullable schema of any type.
+    pub fn infer(example: &Value) -> Schema {
+        match example {
+            Value::Null => {
+                let mut schema = Schema::new_any();
+                schema.nullable = true;
+                schema
+            }
+            Value::Bool(_) => Schema::new_bool(),
+            Value::Number(n) if n.is_i64() || n.is_u64() => Schema::new_integer(),
+            Value::Number(_) => Schema::new_number(),
+            Value::String(s) => match string_format(s) {
+                Some(format) => Schema::new_string().with_format(format),
+                None => Schema::new_string(),
+            },
+            Value::Array(items) => match Schema::infer_all(items) {
+                Some(items) => Schema::new_array(items),
+                None => Schema::new_array_any(),
+            },
+            Value::Object(map) => {
+                let mut schema = Schema::new_object();
+                for (key, value) in map {
+                    schema.properties_mut().insert(key.clone(), RefOr::Item(Schema::infer(value)));
+                }
+                *schema.required_mut() = map.keys().cloned().collect();
+                schema
+            }
+        }
+    }
+
+    /// The narrowest schema describing all of `examples`, or `None` if there are
+    /// none. Properties not present in every object are optional, integers next
+    /// to other numbers widen to numbers, nulls make the schema nullable, and
+    /// values of unrelated types give a schema of any type.
+    pub fn infer_all<'a>(examples: impl IntoIterator<Item=&'a Value>) -> Option<Schema> {
+        examples.into_iter().map(Schema::infer).reduce(widen)
+    }
+}
+
+/// The narrowest schema describing the values of both `a` and `b`.
+pub(crate) fn widen(a: Schema, b: Schema) -> Schema {
+    // A `null` adds nothing but nullability, which a schema of any type already has.
+    match (is_null(&a), is_null(&b)) {
+        (true, _) if is_any(&b) => return b,
+        (true, _) => return Schema { data: SchemaData { nullable: true, ..b.data }, ..b },
+        (_, true) if is_any(&a) => return a,
+        (_, true) => return Schema { data: SchemaData { nullable: true, ..a.data }, ..a },
+        _ => {}
+    }
+    let nullable = a.nullable || b.nullable;
+    let mut schema = match (a.kind, b.kind) {
+        (SchemaKind::Type(Type::Object(a)), SchemaKind::Type(Type::Object(b))) => {
+            let required = a.required.into_iter().filter(|key| b.required.contains(key)).collect();
+            let mut properties = a.properties;
+            for (key, schema) in b.properties {
+                match (properties.get_mut(&key), schema) {
+                    (Some(RefOr::Item(existing)), RefOr::Item(schema)) => {
+                        *existing = widen(std::mem::replace(existing, Schema::new_any()), schema);
+                    }
+                    (Some(_), _) => {}
+                    (None, schema) => {
+                        properties.insert(key, schema);
+                    }
+                }
+            }
+            Schema::new_kind(SchemaKind::Type(Type::Object(ObjectType { properties, required, ..ObjectType::default() })))
+        }
+        (SchemaKind::Type(Type::Array(a)), SchemaKind::Type(Type::Array(b))) => match (a.items, b.items) {
+            (Some(a), Some(b)) => match (*a, *b) {
+                (RefOr::Item(a), RefOr::Item(b)) => Schema::new_array(widen(a, b)),
+                (a, _) => Schema::new_array(a),
+            },
+            (Some(items), None) | (None, Some(items)) => Schema::new_array(*items),
+            (None, None) => Schema::new_array_any(),
+        },
+        (SchemaKind::Type(Type::String(a)), SchemaKind::Type(Type::String(b))) if a.format == b.format => {
+            Schema::new_kind(SchemaKind::Type(Type::String(a)))
+        }
+        (SchemaKind::Type(Type::String(_)), SchemaKind::Type(Type::String(_))) => Schema::new_string(),
+        (SchemaKind::Type(Type::Integer(_)), SchemaKind::Type(Type::Integer(_))) => Schema::new_integer(),
+        (SchemaKind::Type(Type::Integer(_) | Type::Number(_)), SchemaKind::Type(Type::Integer(_) | Type::Number(_))) => Schema::new_number(),
+        (SchemaKind::Type(Type::Boolean {}), SchemaKind::Type(Type::Boolean {})) => Schema::new_bool(),
+        _ => return Schema::new_any(),
+    };
+    schema.nullable = nullable;
+    schema
+}
+
+/// A schema for a value only known as a string, like those of query and path
+/// parameters, which is a number or boolean if it reads as one.
+pub(crate) fn scalar_schema(value: &str) -> Schema {
+    match serde_json::from_str::<Value>(value) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_))) => Schema::infer(&value),
+        _ => Schema::infer(&Value::String(value.to_string())),
+    }
+}
+
+fn is_any(schema: &Schema) -> bool {
+    matches!(&schema.kind, SchemaKind::Any(any) if *any == AnySchema::default())
+}
+
+fn is_null(schema: &Schema) -> bool {
+    schema.nullable && is_any(schema)
+}
+
+fn string_format(s: &str) -> Option<&'static str> {
+    let b = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| b[range].iter().all(u8::is_ascii_digit);
+    let date = b.len() >= 10 && digits(0..4) && b[4] == b'-' && digits(5..7) && b[7] == b'-' && digits(8..10);
+    if date && b.len() == 10 {
+        return Some("date");
+    }
+    if date && b.len() >= 20 && (b[10] == b'T' || b[10] == b't') && digits(11..13) && b[13] == b':' && digits(14..16) {
+        return Some("date-time");
+    }
+    let hex = b.len() == 36 && b.iter().enumerate().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => *c == b'-',
+        _ => c.is_ascii_hexdigit(),
+    });
+    hex.then_some("uuid")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_infer() {
+        let schema = Schema::infer_all(&[
+            json!({"id": 1, "name": "Rex", "born": "2020-01-31", "tags": ["a"], "owner": null}),
+            json!({"id": 2.5, "born": "2020-02-01", "tags": [], "owner": {"id": "0b0c3c7e-2f6a-4d83-9a4f-3a6f2a4f0a11"}}),
+        ]).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "number"},
+                "name": {"type": "string"},
+                "born": {"type": "string", "format": "date"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "owner": {
+                    "type": "object",
+                    "nullable": true,
+                    "properties": {"id": {"type": "string", "format": "uuid"}},
+                    "required": ["id"]
+                }
+            },
+            "required": ["id", "born", "tags", "owner"]
+        }));
+        assert_eq!(serde_json::to_value(Schema::infer(&json!([1, "a", null]))).unwrap(), json!({"type": "array", "items": {}}));
+        assert_eq!(serde_json::to_value(Schema::infer(&json!("2020-01-31T10:00:00Z"))).unwrap(), json!({"type": "string", "format": "date-time"}));
+        assert!(Schema::infer_all(&[]).is_none());
+    }
+}
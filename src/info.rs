@@ -6,9 +6,14 @@ use serde::{Deserialize, Serialize};
 /// The metadata MAY be used by the clients if needed,
 /// and MAY be presented in editing or documentation generation tools for convenience.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Info {
     /// REQUIRED. The title of the application.
     pub title: String,
+    /// A short summary of the API. Introduced in OpenAPI 3.1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     /// A short description of the application.
     /// CommonMark syntax MAY be used for rich text representation.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,5 +33,6 @@ pub struct Info {
     pub version: String,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
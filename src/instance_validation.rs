@@ -0,0 +1,949 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+
+use crate::*;
+use crate::example_validation::child;
+
+/// A single way in which a value fails to match a [Schema].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    /// JSON pointer to the offending part of the value, e.g. `/items/0/name`.
+    /// Empty for the value itself.
+    pub instance_path: String,
+    /// The schema keyword that failed, e.g. `required` or `maximum`.
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.instance_path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.instance_path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl Schema {
+    /// Validates `value` against this schema, resolving `$ref`s through `components`.
+    /// Returns every failure found, not just the first.
+    ///
+    /// Of the formats, `date`, `date-time`, `byte`, `email`, `uuid`, `ipv4`,
+    /// `ipv6`, `int32` and `int64` are checked, others are not. A `pattern` the
+    /// `regex` crate can't compile, such as one with lookarounds, is ignored, and
+    /// such a `patternProperties` key matches no property. A
+    /// `oneOf` or `anyOf` with a discriminator validates an object against the
+    /// member its discriminator property selects only.
+    pub fn validate_value(&self, value: &Value, components: &Components) -> Result<(), Vec<SchemaError>> {
+        let mut validator = Validator::new(components);
+        let mut errors = Vec::new();
+        validator.validate(self, value, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Validates `value` against a schema that may itself be a `$ref`, returning the
+/// failures.
+pub(crate) fn validate_ref_value(schema: &RefOr<Schema>, value: &Value, components: &Components) -> Vec<SchemaError> {
+    let mut validator = Validator::new(components);
+    let mut errors = Vec::new();
+    validator.validate_ref(schema, value, "", &mut errors);
+    errors
+}
+
+/// What a successful validation evaluated, which `unevaluatedProperties` and
+/// `unevaluatedItems` consult. Annotations of failed branches are discarded.
+#[derive(Default)]
+struct Evaluated {
+    properties: HashSet<String>,
+    /// How many of the leading array items were evaluated.
+    items: usize,
+}
+
+impl Evaluated {
+    fn extend(&mut self, other: Evaluated) {
+        self.properties.extend(other.properties);
+        self.items = self.items.max(other.items);
+    }
+}
+
+struct Validator<'a> {
+    components: &'a Components,
+    /// `(reference, instance path)` pairs being expanded, to stop on cycles which
+    /// don't consume any of the value.
+    active: HashSet<(String, String)>,
+    /// Compiled `pattern`s, `None` for those that don't compile.
+    patterns: HashMap<String, Option<Regex>>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(components: &'a Components) -> Self {
+        Validator { components, active: HashSet::new(), patterns: HashMap::new() }
+    }
+
+    fn validate_ref(&mut self, schema: &RefOr<Schema>, value: &Value, path: &str, errors: &mut Vec<SchemaError>) -> Evaluated {
+        let reference = match schema {
+            RefOr::Item(schema) => return self.validate(schema, value, path, errors),
+            RefOr::Reference { reference } => reference,
+        };
+        let components = self.components;
+        let schema = match resolve_schema(reference, components) {
+            Some(schema) => schema,
+            None => {
+                errors.push(error(path, "$ref", format!("cannot resolve {}", reference)));
+                return Evaluated::default();
+            }
+        };
+        let key = (reference.clone(), path.to_string());
+        if !self.active.insert(key.clone()) {
+            return Evaluated::default();
+        }
+        let evaluated = self.validate(schema, value, path, errors);
+        self.active.remove(&key);
+        evaluated
+    }
+
+    /// Validates without reporting errors, returning the annotations on success.
+    fn passes(&mut self, schema: &RefOr<Schema>, value: &Value, path: &str) -> Option<Evaluated> {
+        let mut errors = Vec::new();
+        let evaluated = self.validate_ref(schema, value, path, &mut errors);
+        if errors.is_empty() {
+            Some(evaluated)
+        } else {
+            None
+        }
+    }
+
+    fn validate(&mut self, schema: &Schema, value: &Value, path: &str, errors: &mut Vec<SchemaError>) -> Evaluated {
+        let mut evaluated = Evaluated::default();
+        if value.is_null() && schema.data.nullable {
+            return evaluated;
+        }
+        if let Some(allowed) = schema.allowed_values() {
+            if !allowed.iter().any(|v| json_eq(v, value)) {
+                let keyword = if schema.data.const_value.is_some() { "const" } else { "enum" };
+                errors.push(error(path, keyword, format!("{} is not one of the allowed values", value)));
+            }
+        }
+        let discriminated = match (&schema.data.discriminator, &schema.kind, value) {
+            (Some(discriminator), SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members }, Value::Object(map)) => {
+                evaluated.extend(self.discriminated(discriminator, members, map, value, path, errors));
+                true
+            }
+            _ => false,
+        };
+        match &schema.kind {
+            _ if discriminated => {}
+            SchemaKind::Type(typ) => self.validate_type(typ, &schema.data, value, path, errors, &mut evaluated),
+            SchemaKind::OneOf { one_of } => self.one_of(one_of, value, path, errors, &mut evaluated),
+            SchemaKind::AllOf { all_of } => self.all_of(all_of, value, path, errors, &mut evaluated),
+            SchemaKind::AnyOf { any_of } => self.any_of(any_of, value, path, errors, &mut evaluated),
+            SchemaKind::Not { not } => self.not(not, value, path, errors),
+            SchemaKind::Any(any) => self.validate_any(any, &schema.data, value, path, errors, &mut evaluated),
+        }
+        match value {
+            Value::Object(map) => self.check_object_applicators(&schema.data, map, value, path, errors, &mut evaluated),
+            Value::Array(items) => self.check_array_applicators(&schema.data, items, path, errors, &mut evaluated),
+            _ => {}
+        }
+        if let Some(condition) = &schema.data.if_ {
+            match self.passes(condition, value, path) {
+                Some(annotations) => {
+                    evaluated.extend(annotations);
+                    if let Some(then) = &schema.data.then {
+                        evaluated.extend(self.validate_ref(then, value, path, errors));
+                    }
+                }
+                None => {
+                    if let Some(else_) = &schema.data.else_ {
+                        evaluated.extend(self.validate_ref(else_, value, path, errors));
+                    }
+                }
+            }
+        }
+        self.unevaluated(&schema.data, value, path, errors, &mut evaluated);
+        evaluated
+    }
+
+    fn validate_type(&mut self, typ: &Type, data: &SchemaData, value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        match (typ, value) {
+            (Type::String(s), Value::String(v)) => {
+                check_string(v, s.min_length, s.max_length, path, errors);
+                self.check_pattern(s.pattern.as_deref(), v, path, errors);
+                check_format(s.format.as_str(), value, path, errors);
+            }
+            (Type::Number(n), Value::Number(v)) => {
+                check_number(v, n.lower_bound(), n.upper_bound(), n.multiple_of, path, errors)
+            }
+            (Type::Integer(i), Value::Number(v)) if is_integer(v) => {
+                let to_f64 = |(b, exclusive): (i64, bool)| (b as f64, exclusive);
+                let multiple_of = i.multiple_of.map(|m| m as f64);
+                check_number(v, i.lower_bound().map(to_f64), i.upper_bound().map(to_f64), multiple_of, path, errors);
+                match &i.format {
+                    VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => check_format("int32", value, path, errors),
+                    VariantOrUnknownOrEmpty::Item(IntegerFormat::Int64) => check_format("int64", value, path, errors),
+                    _ => {}
+                }
+            }
+            (Type::Object(o), Value::Object(map)) => {
+                let keywords = ObjectKeywords {
+                    properties: &o.properties,
+                    pattern_properties: &data.pattern_properties,
+                    required: &o.required,
+                    additional_properties: o.additional_properties.as_ref(),
+                    min_properties: o.min_properties,
+                    max_properties: o.max_properties,
+                };
+                evaluated.extend(self.check_object(keywords, map, path, errors));
+            }
+            (Type::Array(a), Value::Array(items)) => {
+                let keywords = ArrayKeywords {
+                    items: a.items.as_deref(),
+                    prefix_items: data.prefix_items.len(),
+                    min_items: a.min_items,
+                    max_items: a.max_items,
+                    unique_items: a.unique_items,
+                };
+                evaluated.extend(self.check_array(keywords, items, path, errors));
+            }
+            (Type::Boolean {}, Value::Bool(_)) => {}
+            _ => {
+                let expected = match typ {
+                    Type::String(_) => "string",
+                    Type::Number(_) => "number",
+                    Type::Integer(_) => "integer",
+                    Type::Object(_) => "object",
+                    Type::Array(_) => "array",
+                    Type::Boolean {} => "boolean",
+                };
+                errors.push(type_error(path, expected, value));
+            }
+        }
+    }
+
+    fn validate_any(&mut self, any: &AnySchema, data: &SchemaData, value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        if let Some(typ) = &any.typ {
+            if !type_matches(typ, value) {
+                errors.push(type_error(path, typ, value));
+                return;
+            }
+        }
+        if !any.types.is_empty() && !any.types.iter().any(|typ| type_matches(typ, value)) {
+            errors.push(type_error(path, &any.types.join(" or "), value));
+            return;
+        }
+        match value {
+            Value::String(v) => {
+                check_string(v, any.min_length, any.max_length, path, errors);
+                self.check_pattern(any.pattern.as_deref(), v, path, errors);
+            }
+            Value::Number(v) => check_number(v, any.lower_bound(), any.upper_bound(), any.multiple_of, path, errors),
+            Value::Object(map) => {
+                let keywords = ObjectKeywords {
+                    properties: &any.properties,
+                    pattern_properties: &data.pattern_properties,
+                    required: &any.required,
+                    additional_properties: any.additional_properties.as_ref(),
+                    min_properties: any.min_properties,
+                    max_properties: any.max_properties,
+                };
+                evaluated.extend(self.check_object(keywords, map, path, errors));
+            }
+            Value::Array(items) => {
+                let keywords = ArrayKeywords {
+                    items: any.items.as_deref(),
+                    prefix_items: data.prefix_items.len(),
+                    min_items: any.min_items,
+                    max_items: any.max_items,
+                    unique_items: any.unique_items.unwrap_or(false),
+                };
+                evaluated.extend(self.check_array(keywords, items, path, errors));
+            }
+            _ => {}
+        }
+        if let Some(format) = &any.format {
+            check_format(format, value, path, errors);
+        }
+        if !any.all_of.is_empty() {
+            self.all_of(&any.all_of, value, path, errors, evaluated);
+        }
+        if !any.one_of.is_empty() {
+            self.one_of(&any.one_of, value, path, errors, evaluated);
+        }
+        if !any.any_of.is_empty() {
+            self.any_of(&any.any_of, value, path, errors, evaluated);
+        }
+        if let Some(not) = &any.not {
+            self.not(not, value, path, errors);
+        }
+    }
+
+    /// Validates an object against the member of a `oneOf` or `anyOf` that its
+    /// discriminator property names, through the mapping or else by component name.
+    fn discriminated(&mut self, discriminator: &Discriminator, members: &[RefOr<Schema>], map: &Map<String, Value>, value: &Value, path: &str, errors: &mut Vec<SchemaError>) -> Evaluated {
+        let property = &discriminator.property_name;
+        let Some(Value::String(tag)) = map.get(property) else {
+            errors.push(error(path, "discriminator", format!("missing discriminator property {}", property)));
+            return Evaluated::default();
+        };
+        match discriminator.select(tag, members) {
+            Some(member) => self.validate_ref(member, value, path, errors),
+            None => {
+                errors.push(error(path, "discriminator", format!("{} {:?} does not select a schema", property, tag)));
+                Evaluated::default()
+            }
+        }
+    }
+
+    fn check_pattern(&mut self, pattern: Option<&str>, value: &str, path: &str, errors: &mut Vec<SchemaError>) {
+        let Some(pattern) = pattern else { return };
+        if self.matches(pattern, value) == Some(false) {
+            errors.push(error(path, "pattern", format!("{:?} does not match {}", value, pattern)));
+        }
+    }
+
+    /// Whether `value` matches `pattern`, `None` if the pattern doesn't compile.
+    fn matches(&mut self, pattern: &str, value: &str) -> Option<bool> {
+        let regex = self.patterns.entry(pattern.to_string()).or_insert_with(|| Regex::new(pattern).ok());
+        regex.as_ref().map(|regex| regex.is_match(value))
+    }
+
+    /// The `patternProperties` schemas that apply to the property `name`.
+    fn pattern_schemas<'s>(&mut self, pattern_properties: &'s RefOrMap<Schema>, name: &str) -> Vec<&'s RefOr<Schema>> {
+        pattern_properties.iter()
+            .filter(|(pattern, _)| self.matches(pattern, name) == Some(true))
+            .map(|(_, schema)| schema)
+            .collect()
+    }
+
+    fn all_of(&mut self, schemas: &[RefOr<Schema>], value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        for schema in schemas {
+            evaluated.extend(self.validate_ref(schema, value, path, errors));
+        }
+    }
+
+    fn any_of(&mut self, schemas: &[RefOr<Schema>], value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        let mut matched = false;
+        for schema in schemas {
+            if let Some(annotations) = self.passes(schema, value, path) {
+                evaluated.extend(annotations);
+                matched = true;
+            }
+        }
+        if !matched {
+            errors.push(error(path, "anyOf", "value does not match any schema in anyOf".to_string()));
+        }
+    }
+
+    fn one_of(&mut self, schemas: &[RefOr<Schema>], value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        let mut matches: Vec<Evaluated> = schemas.iter().filter_map(|s| self.passes(s, value, path)).collect();
+        if matches.len() == 1 {
+            evaluated.extend(matches.remove(0));
+        } else {
+            let message = format!("value matches {} schemas in oneOf, expected exactly one", matches.len());
+            errors.push(error(path, "oneOf", message));
+        }
+    }
+
+    fn not(&mut self, schema: &RefOr<Schema>, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+        if self.passes(schema, value, path).is_some() {
+            errors.push(error(path, "not", "value matches the schema in not".to_string()));
+        }
+    }
+
+    fn check_object(&mut self, keywords: ObjectKeywords, map: &Map<String, Value>, path: &str, errors: &mut Vec<SchemaError>) -> Evaluated {
+        let mut evaluated = Evaluated::default();
+        for name in keywords.required {
+            if !map.contains_key(name) {
+                errors.push(error(path, "required", format!("missing required property {}", name)));
+            }
+        }
+        for (name, value) in map {
+            let child = child(path, name);
+            if let Some(schema) = keywords.properties.get(name) {
+                self.validate_ref(schema, value, &child, errors);
+                evaluated.properties.insert(name.clone());
+                continue;
+            }
+            if !self.pattern_schemas(keywords.pattern_properties, name).is_empty() {
+                continue;
+            }
+            match keywords.additional_properties {
+                Some(AdditionalProperties::Any(false)) => {
+                    errors.push(error(path, "additionalProperties", format!("property {} is not allowed", name)));
+                }
+                Some(AdditionalProperties::Any(true)) => {
+                    evaluated.properties.insert(name.clone());
+                }
+                Some(AdditionalProperties::Schema(schema)) => {
+                    self.validate_ref(schema, value, &child, errors);
+                    evaluated.properties.insert(name.clone());
+                }
+                None => {}
+            }
+        }
+        if let Some(min) = keywords.min_properties.filter(|min| map.len() < *min) {
+            errors.push(error(path, "minProperties", format!("expected at least {} properties, found {}", min, map.len())));
+        }
+        if let Some(max) = keywords.max_properties.filter(|max| map.len() > *max) {
+            errors.push(error(path, "maxProperties", format!("expected at most {} properties, found {}", max, map.len())));
+        }
+        evaluated
+    }
+
+    fn check_array(&mut self, keywords: ArrayKeywords, items: &[Value], path: &str, errors: &mut Vec<SchemaError>) -> Evaluated {
+        let mut evaluated = Evaluated::default();
+        if let Some(schema) = keywords.items {
+            // `items` applies to the items after those of `prefixItems`.
+            for (i, item) in items.iter().enumerate().skip(keywords.prefix_items) {
+                self.validate_ref(schema, item, &child(path, &i.to_string()), errors);
+            }
+            evaluated.items = items.len();
+        }
+        if let Some(min) = keywords.min_items.filter(|min| items.len() < *min) {
+            errors.push(error(path, "minItems", format!("expected at least {} items, found {}", min, items.len())));
+        }
+        if let Some(max) = keywords.max_items.filter(|max| items.len() > *max) {
+            errors.push(error(path, "maxItems", format!("expected at most {} items, found {}", max, items.len())));
+        }
+        if keywords.unique_items {
+            let duplicate = items.iter().enumerate().any(|(i, a)| items[..i].iter().any(|b| json_eq(a, b)));
+            if duplicate {
+                errors.push(error(path, "uniqueItems", "array items are not unique".to_string()));
+            }
+        }
+        evaluated
+    }
+
+    /// The object keywords of JSON Schema 2020-12 kept in [SchemaData], which
+    /// apply whatever the kind of the schema.
+    fn check_object_applicators(&mut self, data: &SchemaData, map: &Map<String, Value>, value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        for (name, property) in map {
+            for schema in self.pattern_schemas(&data.pattern_properties, name) {
+                self.validate_ref(schema, property, &child(path, name), errors);
+                evaluated.properties.insert(name.clone());
+            }
+            if let Some(schema) = &data.property_names {
+                if self.passes(schema, &Value::String(name.clone()), path).is_none() {
+                    errors.push(error(path, "propertyNames", format!("property name {:?} is not valid", name)));
+                }
+            }
+        }
+        for (name, required) in &data.dependent_required {
+            if !map.contains_key(name) {
+                continue;
+            }
+            for missing in required.iter().filter(|r| !map.contains_key(*r)) {
+                errors.push(error(path, "dependentRequired", format!("property {} requires property {}", name, missing)));
+            }
+        }
+        for (name, schema) in &data.dependent_schemas {
+            if map.contains_key(name) {
+                evaluated.extend(self.validate_ref(schema, value, path, errors));
+            }
+        }
+    }
+
+    /// The array keywords of JSON Schema 2020-12 kept in [SchemaData], which
+    /// apply whatever the kind of the schema.
+    fn check_array_applicators(&mut self, data: &SchemaData, items: &[Value], path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        for (i, (schema, item)) in data.prefix_items.iter().zip(items).enumerate() {
+            self.validate_ref(schema, item, &child(path, &i.to_string()), errors);
+        }
+        evaluated.items = evaluated.items.max(data.prefix_items.len().min(items.len()));
+        let Some(schema) = &data.contains else { return };
+        let count = items.iter().enumerate()
+            .filter(|(i, item)| self.passes(schema, item, &child(path, &i.to_string())).is_some())
+            .count();
+        let min = data.min_contains.unwrap_or(1);
+        if count < min {
+            let keyword = if data.min_contains.is_some() { "minContains" } else { "contains" };
+            errors.push(error(path, keyword, format!("expected at least {} items matching contains, found {}", min, count)));
+        }
+        if let Some(max) = data.max_contains.filter(|max| count > *max) {
+            errors.push(error(path, "maxContains", format!("expected at most {} items matching contains, found {}", max, count)));
+        }
+    }
+
+    fn unevaluated(&mut self, data: &SchemaData, value: &Value, path: &str, errors: &mut Vec<SchemaError>, evaluated: &mut Evaluated) {
+        if let (Some(rule), Value::Object(map)) = (&data.unevaluated_properties, value) {
+            for (name, value) in map.iter().filter(|(name, _)| !evaluated.properties.contains(*name)) {
+                match rule {
+                    AdditionalProperties::Any(true) => {}
+                    AdditionalProperties::Any(false) => {
+                        errors.push(error(path, "unevaluatedProperties", format!("property {} is not allowed", name)));
+                    }
+                    AdditionalProperties::Schema(schema) => {
+                        self.validate_ref(schema, value, &child(path, name), errors);
+                    }
+                }
+            }
+            evaluated.properties.extend(map.keys().cloned());
+        }
+        if let (Some(rule), Value::Array(items)) = (&data.unevaluated_items, value) {
+            for (i, item) in items.iter().enumerate().skip(evaluated.items) {
+                match rule {
+                    AdditionalProperties::Any(true) => {}
+                    AdditionalProperties::Any(false) => {
+                        errors.push(error(path, "unevaluatedItems", format!("item {} is not allowed", i)));
+                    }
+                    AdditionalProperties::Schema(schema) => {
+                        self.validate_ref(schema, item, &child(path, &i.to_string()), errors);
+                    }
+                }
+            }
+            evaluated.items = items.len();
+        }
+    }
+}
+
+struct ObjectKeywords<'s> {
+    properties: &'s RefOrMap<Schema>,
+    /// Properties matching these aren't subject to `additionalProperties`.
+    pattern_properties: &'s RefOrMap<Schema>,
+    required: &'s [String],
+    additional_properties: Option<&'s AdditionalProperties>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
+}
+
+struct ArrayKeywords<'s> {
+    items: Option<&'s RefOr<Schema>>,
+    /// How many items `prefixItems` covers, which `items` doesn't apply to.
+    prefix_items: usize,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    unique_items: bool,
+}
+
+/// Follows `#/components/schemas/...` references, including chains of them.
+pub(crate) fn resolve_schema<'a>(reference: &str, components: &'a Components) -> Option<&'a Schema> {
+    let mut seen = HashSet::new();
+    let mut reference = reference;
+    loop {
+        if !seen.insert(reference) {
+            return None;
+        }
+        let name = reference.strip_prefix("#/components/schemas/")?;
+        match components.schemas.get(name)? {
+            RefOr::Item(schema) => return Some(schema),
+            RefOr::Reference { reference: next } => reference = next,
+        }
+    }
+}
+
+fn check_string(value: &str, min_length: Option<usize>, max_length: Option<usize>, path: &str, errors: &mut Vec<SchemaError>) {
+    let len = value.chars().count();
+    if let Some(min) = min_length.filter(|min| len < *min) {
+        errors.push(error(path, "minLength", format!("expected at least {} characters, found {}", min, len)));
+    }
+    if let Some(max) = max_length.filter(|max| len > *max) {
+        errors.push(error(path, "maxLength", format!("expected at most {} characters, found {}", max, len)));
+    }
+}
+
+/// Checks the formats listed on [Schema::validate_value], passing any other.
+fn check_format(format: &str, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    let valid = match (format, value) {
+        ("date", Value::String(s)) => is_date(s),
+        ("date-time", Value::String(s)) => is_date_time(s),
+        ("byte", Value::String(s)) => is_base64(s),
+        ("email", Value::String(s)) => s.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && !domain.is_empty() && !domain.contains('@') && !s.contains(char::is_whitespace)
+        }),
+        ("uuid", Value::String(s)) => s.len() == 36 && s.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        }),
+        ("ipv4", Value::String(s)) => s.parse::<std::net::Ipv4Addr>().is_ok(),
+        ("ipv6", Value::String(s)) => s.parse::<std::net::Ipv6Addr>().is_ok(),
+        ("int32", Value::Number(n)) => n.as_i64().is_some_and(|n| (i32::MIN as i64..=i32::MAX as i64).contains(&n)),
+        ("int64", Value::Number(n)) => n.is_i64(),
+        _ => true,
+    };
+    if !valid {
+        errors.push(error(path, "format", format!("{} is not a valid {}", value, format)));
+    }
+}
+
+/// A `full-date` of RFC 3339, e.g. `2024-02-29`.
+fn is_date(s: &str) -> bool {
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let [year, month, day] = match s.split('-').collect::<Vec<_>>()[..] {
+        [y, m, d] if y.len() == 4 && m.len() == 2 && d.len() == 2 && digits(y) && digits(m) && digits(d) => {
+            [y, m, d].map(|part| part.parse::<u32>().unwrap_or_default())
+        }
+        _ => return false,
+    };
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// A `date-time` of RFC 3339, e.g. `2024-02-29T12:30:00.5+01:00`.
+fn is_date_time(s: &str) -> bool {
+    let Some((date, time)) = s.split_once(['T', 't']) else { return false };
+    let two_digits = |s: &str, max: u32| s.len() == 2 && s.bytes().all(|b| b.is_ascii_digit()) && s.parse::<u32>().is_ok_and(|n| n <= max);
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return false,
+    };
+    let offset_valid = match offset {
+        "Z" | "z" => true,
+        _ => matches!(offset[1..].split_once(':'), Some((h, m)) if two_digits(h, 23) && two_digits(m, 59)),
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    // A second of 60 is a leap second.
+    let time_valid = matches!(time.split(':').collect::<Vec<_>>()[..], [h, m, sec] if two_digits(h, 23) && two_digits(m, 59) && two_digits(sec, 60));
+    is_date(date) && offset_valid && time_valid && !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Standard base64, padded.
+fn is_base64(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    s.len().is_multiple_of(4) && s.len() - data.len() <= 2
+        && data.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
+
+fn check_number(value: &Number, lower: Option<(f64, bool)>, upper: Option<(f64, bool)>, multiple_of: Option<f64>, path: &str, errors: &mut Vec<SchemaError>) {
+    let v = match value.as_f64() {
+        Some(v) => v,
+        None => return,
+    };
+    match lower {
+        Some((min, true)) if v <= min => errors.push(error(path, "exclusiveMinimum", format!("{} is not greater than {}", v, min))),
+        Some((min, false)) if v < min => errors.push(error(path, "minimum", format!("{} is less than {}", v, min))),
+        _ => {}
+    }
+    match upper {
+        Some((max, true)) if v >= max => errors.push(error(path, "exclusiveMaximum", format!("{} is not less than {}", v, max))),
+        Some((max, false)) if v > max => errors.push(error(path, "maximum", format!("{} is greater than {}", v, max))),
+        _ => {}
+    }
+    if let Some(m) = multiple_of.filter(|m| *m != 0.0) {
+        let quotient = v / m;
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            errors.push(error(path, "multipleOf", format!("{} is not a multiple of {}", v, m)));
+        }
+    }
+}
+
+fn is_integer(n: &Number) -> bool {
+    n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+}
+
+fn type_matches(typ: &str, value: &Value) -> bool {
+    match typ {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => matches!(value, Value::Number(n) if is_integer(n)),
+        _ => true,
+    }
+}
+
+/// JSON equality, except that numbers compare by value, so `1` equals `1.0`.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(k, v)| b.get(k).is_some_and(|w| json_eq(v, w)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn error(path: &str, keyword: &'static str, message: String) -> SchemaError {
+    SchemaError { instance_path: path.to_string(), keyword, message }
+}
+
+fn type_error(path: &str, expected: &str, value: &Value) -> SchemaError {
+    let found = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    error(path, "type", format!("expected {}, found {}", expected, found))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema(value: Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn keywords(result: Result<(), Vec<SchemaError>>) -> Vec<(String, &'static str)> {
+        result.err().unwrap_or_default().into_iter().map(|e| (e.instance_path, e.keyword)).collect()
+    }
+
+    #[test]
+    fn test_basic_keywords() {
+        let components = Components::default();
+        let person = schema(json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "nickname": {"type": "string", "nullable": true},
+                "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true}
+            },
+            "additionalProperties": false
+        }));
+        assert!(person.validate_value(&json!({"name": "Ann", "age": 3, "nickname": null}), &components).is_ok());
+        assert_eq!(keywords(person.validate_value(&json!({"age": -1.5, "tags": ["a", 1, "a"], "extra": true}), &components)), vec![
+            ("".to_string(), "required"),
+            ("/age".to_string(), "type"),
+            ("/tags/1".to_string(), "type"),
+            ("/tags".to_string(), "uniqueItems"),
+            ("".to_string(), "additionalProperties"),
+        ]);
+    }
+
+    #[test]
+    fn test_unevaluated_properties_across_all_of() {
+        let mut components = Components::default();
+        components.schemas.insert("Base", schema(json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}}
+        })));
+        let closed = schema(json!({
+            "allOf": [
+                {"$ref": "#/components/schemas/Base"},
+                {"properties": {"name": {"type": "string"}}}
+            ],
+            "unevaluatedProperties": false
+        }));
+        assert!(closed.validate_value(&json!({"id": "1", "name": "x"}), &components).is_ok());
+        assert_eq!(keywords(closed.validate_value(&json!({"id": "1", "other": 2}), &components)), vec![
+            ("".to_string(), "unevaluatedProperties"),
+        ]);
+
+        // Properties from a failed anyOf branch don't count as evaluated.
+        let any_of = schema(json!({
+            "anyOf": [
+                {"properties": {"a": {"type": "string"}}},
+                {"properties": {"b": {"type": "string"}}}
+            ],
+            "unevaluatedProperties": false
+        }));
+        assert!(any_of.validate_value(&json!({"a": "x"}), &components).is_ok());
+        assert_eq!(keywords(any_of.validate_value(&json!({"a": "x", "b": 1}), &components)), vec![
+            ("".to_string(), "unevaluatedProperties"),
+        ]);
+    }
+
+    #[test]
+    fn test_unevaluated_items() {
+        let components = Components::default();
+        let schema = schema(json!({
+            "allOf": [{"type": "array", "minItems": 1}],
+            "unevaluatedItems": {"type": "integer"}
+        }));
+        assert!(schema.validate_value(&json!([1, 2]), &components).is_ok());
+        assert_eq!(keywords(schema.validate_value(&json!([1, "two"]), &components)), vec![
+            ("/1".to_string(), "type"),
+        ]);
+    }
+
+    #[test]
+    fn test_conditional() {
+        let components = Components::default();
+        let schema = schema(json!({
+            "type": "object",
+            "if": {"properties": {"country": {"const": "US"}}, "required": ["country"]},
+            "then": {"required": ["zip"]},
+            "else": {"required": ["postcode"]}
+        }));
+        assert!(schema.validate_value(&json!({"country": "US", "zip": "12345"}), &components).is_ok());
+        assert!(schema.validate_value(&json!({"country": "NL", "postcode": "1234AB"}), &components).is_ok());
+        assert_eq!(keywords(schema.validate_value(&json!({"country": "NL", "zip": "12345"}), &components)), vec![
+            ("".to_string(), "required"),
+        ]);
+    }
+
+    #[test]
+    fn test_type_list() {
+        let components = Components::default();
+        let schema = schema(json!({"type": ["string", "integer", "null"]}));
+        assert!(schema.validate_value(&json!("a"), &components).is_ok());
+        assert!(schema.validate_value(&json!(1), &components).is_ok());
+        assert!(schema.validate_value(&Value::Null, &components).is_ok());
+        let errors = schema.validate_value(&json!(true), &components).unwrap_err();
+        assert_eq!(errors.iter().map(ToString::to_string).collect::<Vec<_>>(), ["expected string or integer, found boolean"]);
+    }
+
+    #[test]
+    fn test_pattern_properties() {
+        let components = Components::default();
+        let schema = schema(json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "patternProperties": {"^x-": {"type": "integer"}},
+            "additionalProperties": false
+        }));
+        assert!(schema.validate_value(&json!({"id": "a", "x-count": 1}), &components).is_ok());
+        assert_eq!(keywords(schema.validate_value(&json!({"x-count": "one", "other": 1}), &components)), vec![
+            ("".to_string(), "additionalProperties"),
+            ("/x-count".to_string(), "type"),
+        ]);
+    }
+
+    #[test]
+    fn test_property_names() {
+        let components = Components::default();
+        let schema = schema(json!({"type": "object", "propertyNames": {"pattern": "^[a-z]+$"}}));
+        assert!(schema.validate_value(&json!({"abc": 1}), &components).is_ok());
+        assert_eq!(keywords(schema.validate_value(&json!({"abc": 1, "Abc": 2}), &components)), vec![
+            ("".to_string(), "propertyNames"),
+        ]);
+    }
+
+    #[test]
+    fn test_dependent_keywords() {
+        let components = Components::default();
+        let schema = schema(json!({
+            "type": "object",
+            "dependentRequired": {"card": ["billing"]},
+            "dependentSchemas": {"coupon": {"properties": {"total": {"minimum": 10}}}}
+        }));
+        assert!(schema.validate_value(&json!({"card": 1, "billing": "x"}), &components).is_ok());
+        assert!(schema.validate_value(&json!({"total": 5}), &components).is_ok());
+        assert_eq!(keywords(schema.validate_value(&json!({"card": 1, "coupon": "c", "total": 5}), &components)), vec![
+            ("".to_string(), "dependentRequired"),
+            ("/total".to_string(), "minimum"),
+        ]);
+    }
+
+    #[test]
+    fn test_prefix_items() {
+        let components = Components::default();
+        let point = schema(json!({
+            "type": "array",
+            "prefixItems": [{"type": "number"}, {"type": "string"}],
+            "items": false
+        }));
+        assert!(point.validate_value(&json!([1, "a"]), &components).is_ok());
+        assert!(point.validate_value(&json!([1]), &components).is_ok());
+        assert_eq!(keywords(point.validate_value(&json!(["a", "b", 3]), &components)), vec![
+            ("/2".to_string(), "not"),
+            ("/0".to_string(), "type"),
+        ]);
+        let open = schema(json!({"prefixItems": [{"type": "integer"}], "unevaluatedItems": {"type": "string"}}));
+        assert!(open.validate_value(&json!([1, "a", "b"]), &components).is_ok());
+        assert_eq!(keywords(open.validate_value(&json!([1, 2]), &components)), vec![("/1".to_string(), "type")]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let components = Components::default();
+        let contains = schema(json!({"type": "array", "contains": {"type": "integer"}}));
+        assert!(contains.validate_value(&json!(["a", 1]), &components).is_ok());
+        assert_eq!(keywords(contains.validate_value(&json!(["a"]), &components)), vec![("".to_string(), "contains")]);
+        let bounded = schema(json!({"type": "array", "contains": {"type": "integer"}, "minContains": 2, "maxContains": 3}));
+        assert!(bounded.validate_value(&json!([1, 2, "a"]), &components).is_ok());
+        assert_eq!(keywords(bounded.validate_value(&json!([1, "a"]), &components)), vec![("".to_string(), "minContains")]);
+        assert_eq!(keywords(bounded.validate_value(&json!([1, 2, 3, 4]), &components)), vec![("".to_string(), "maxContains")]);
+        let optional = schema(json!({"contains": {"type": "integer"}, "minContains": 0}));
+        assert!(optional.validate_value(&json!(["a"]), &components).is_ok());
+    }
+
+    #[test]
+    fn test_formats_and_patterns() {
+        let components = Components::default();
+        let schema = schema(json!({
+            "type": "object",
+            "properties": {
+                "born": {"type": "string", "format": "date"},
+                "seen": {"type": "string", "format": "date-time"},
+                "id": {"type": "string", "format": "uuid"},
+                "code": {"type": "string", "pattern": "^[A-Z]{3}$"},
+                "count": {"type": "integer", "format": "int32"},
+                "custom": {"type": "string", "format": "color", "pattern": "(?=lookahead)"}
+            }
+        }));
+        let valid = json!({
+            "born": "2024-02-29",
+            "seen": "2024-02-29T23:59:60.25+01:00",
+            "id": "123e4567-e89b-12d3-a456-426614174000",
+            "code": "ABC",
+            "count": 2147483647,
+            "custom": "anything"
+        });
+        assert!(schema.validate_value(&valid, &components).is_ok());
+        let invalid = json!({
+            "born": "2023-02-29",
+            "seen": "2024-02-29 12:00:00Z",
+            "id": "123e4567",
+            "code": "abc",
+            "count": 2147483648_i64
+        });
+        assert_eq!(keywords(schema.validate_value(&invalid, &components)), vec![
+            ("/born".to_string(), "format"),
+            ("/seen".to_string(), "format"),
+            ("/id".to_string(), "format"),
+            ("/code".to_string(), "pattern"),
+            ("/count".to_string(), "format"),
+        ]);
+    }
+
+    #[test]
+    fn test_discriminator() {
+        let mut components = Components::default();
+        components.schemas.insert("Cat", schema(json!({
+            "type": "object", "required": ["kind", "lives"], "properties": {"lives": {"type": "integer"}}
+        })));
+        components.schemas.insert("Dog", schema(json!({
+            "type": "object", "required": ["kind"], "properties": {"bark": {"type": "string"}}
+        })));
+        let pet = schema(json!({
+            "oneOf": [{"$ref": "#/components/schemas/Cat"}, {"$ref": "#/components/schemas/Dog"}],
+            "discriminator": {"propertyName": "kind", "mapping": {"dog": "Dog"}}
+        }));
+        assert!(pet.validate_value(&json!({"kind": "Cat", "lives": 9}), &components).is_ok());
+        // The members alone can't tell this dog from a cat, but the discriminator can.
+        assert!(pet.validate_value(&json!({"kind": "dog", "lives": 9}), &components).is_ok());
+        let errors = pet.validate_value(&json!({"kind": "Cat", "lives": "nine"}), &components).unwrap_err();
+        assert_eq!(errors.iter().map(ToString::to_string).collect::<Vec<_>>(), ["/lives: expected integer, found string"]);
+        assert_eq!(keywords(pet.validate_value(&json!({"kind": "Fish"}), &components)), vec![("".to_string(), "discriminator")]);
+        assert_eq!(keywords(pet.validate_value(&json!({"lives": 9}), &components)), vec![("".to_string(), "discriminator")]);
+    }
+
+    #[test]
+    fn test_unresolved_reference() {
+        let schema = schema(json!({
+            "type": "object",
+            "properties": {"pet": {"$ref": "#/components/schemas/Missing"}}
+        }));
+        assert_eq!(keywords(schema.validate_value(&json!({"pet": 1}), &Components::default())), vec![
+            ("/pet".to_string(), "$ref"),
+        ]);
+    }
+}
@@ -0,0 +1,609 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use serde::Deserializer;
+
+use crate::duplicate_keys::find_duplicate_keys;
+use crate::unknown_fields::find_unknown_fields;
+use crate::OpenAPI;
+use crate::example_validation::child;
+
+/// The serialization format of a spec document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// The format implied by a file extension, if any. A compression extension is
+    /// looked through, so `api.json.gz` is JSON.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let mut ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        if ext == "gz" || ext == "zst" {
+            ext = Path::new(path.file_stem()?).extension()?.to_str()?.to_ascii_lowercase();
+        }
+        match ext.as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from a file extension, falling back to the content:
+    /// a document whose first non-whitespace character is `{` is JSON.
+    pub fn detect(path: Option<&Path>, content: &str) -> Self {
+        match path.and_then(Format::from_path) {
+            Some(format) => format,
+            None if content.trim_start().starts_with('{') => Format::Json,
+            None => Format::Yaml,
+        }
+    }
+}
+
+/// A failure to parse a spec document, with the location of the offending node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// JSON pointer to the node being deserialized when parsing failed, e.g.
+    /// `/info/title`. Errors below a [RefOr](crate::RefOr) are reported at the `RefOr`
+    /// itself, since serde doesn't track paths through untagged enums.
+    pub pointer: String,
+    /// 1-based line of the error in the input text, when known.
+    pub line: Option<usize>,
+    /// 1-based column of the error in the input text, when known.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(path: Option<&serde_path_to_error::Path>, message: String, location: Option<(usize, usize)>) -> Self {
+        use serde_path_to_error::Segment;
+        let pointer = path.into_iter().flat_map(|p| p.iter()).filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(format!("/{}", index)),
+            Segment::Map { key } => Some(child("", key)),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        }).collect();
+        // Both serde_json and serde_yaml append the position to the message.
+        let message = match location {
+            Some((line, column)) => {
+                let suffix = format!(" at line {} column {}", line, column);
+                message.strip_suffix(&suffix).map(String::from).unwrap_or(message)
+            }
+            None => message,
+        };
+        ParseError { pointer, line: location.map(|l| l.0), column: location.map(|l| l.1), message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.pointer.is_empty() {
+            write!(f, " at {}", self.pointer)?;
+        }
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " (line {}, column {})", line, column)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// The document could not be parsed into an [OpenAPI].
+    Parse(ParseError),
+    /// The document could not be read or written as JSON, outside of parsing it into an [OpenAPI].
+    Json(serde_json::Error),
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    /// The document could not be read or written as YAML, outside of parsing it into an [OpenAPI].
+    Yaml(serde_yaml::Error),
+    /// The document is YAML, but the `yaml` feature is disabled.
+    YamlUnsupported,
+    /// The document is not UTF-8, e.g. UTF-16 as exported by some Windows tools.
+    Encoding(String),
+    /// A `$ref` into another file could not be followed.
+    Reference(String),
+    /// The file is compressed with the named algorithm, whose feature (`gzip` or `zstd`) is disabled.
+    CompressionUnsupported(&'static str),
+    /// Objects in the document repeat keys, which [ParseOptions::deny_duplicate_keys] rejects.
+    DuplicateKeys(Vec<ParseError>),
+    /// Objects in the document have fields the model doesn't know, which
+    /// [ParseOptions::deny_unknown_fields] rejects.
+    UnknownFields(Vec<ParseError>),
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    CborEncode(ciborium::ser::Error<std::io::Error>),
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    CborDecode(ciborium::de::Error<std::io::Error>),
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    MessagePackEncode(rmp_serde::encode::Error),
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    MessagePackDecode(rmp_serde::decode::Error),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse(e) => write!(f, "invalid spec: {}", e),
+            LoadError::Json(e) => write!(f, "{}", e),
+            #[cfg(feature = "yaml")]
+            LoadError::Yaml(e) => write!(f, "{}", e),
+            LoadError::YamlUnsupported => write!(f, "YAML specs require the `yaml` feature"),
+            LoadError::Encoding(msg) => write!(f, "{}", msg),
+            LoadError::Reference(msg) => write!(f, "{}", msg),
+            LoadError::CompressionUnsupported(algorithm) => write!(f, "{} compressed specs require the `{}` feature", algorithm, algorithm),
+            LoadError::DuplicateKeys(errors) | LoadError::UnknownFields(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", errors.join("; "))
+            }
+            #[cfg(feature = "cbor")]
+            LoadError::CborEncode(e) => write!(f, "{}", e),
+            #[cfg(feature = "cbor")]
+            LoadError::CborDecode(e) => write!(f, "{}", e),
+            #[cfg(feature = "msgpack")]
+            LoadError::MessagePackEncode(e) => write!(f, "{}", e),
+            #[cfg(feature = "msgpack")]
+            LoadError::MessagePackDecode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Parse(e) => Some(e),
+            LoadError::Json(e) => Some(e),
+            #[cfg(feature = "yaml")]
+            LoadError::Yaml(e) => Some(e),
+            #[cfg(feature = "cbor")]
+            LoadError::CborEncode(e) => Some(e),
+            #[cfg(feature = "cbor")]
+            LoadError::CborDecode(e) => Some(e),
+            #[cfg(feature = "msgpack")]
+            LoadError::MessagePackEncode(e) => Some(e),
+            #[cfg(feature = "msgpack")]
+            LoadError::MessagePackDecode(e) => Some(e),
+            LoadError::YamlUnsupported
+            | LoadError::Encoding(_)
+            | LoadError::Reference(_)
+            | LoadError::CompressionUnsupported(_)
+            | LoadError::DuplicateKeys(_)
+            | LoadError::UnknownFields(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(e: ParseError) -> Self {
+        LoadError::Parse(e)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> Self {
+        LoadError::Json(e)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for LoadError {
+    fn from(e: serde_yaml::Error) -> Self {
+        LoadError::Yaml(e)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl From<rmp_serde::encode::Error> for LoadError {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        LoadError::MessagePackEncode(e)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl From<rmp_serde::decode::Error> for LoadError {
+    fn from(e: rmp_serde::decode::Error) -> Self {
+        LoadError::MessagePackDecode(e)
+    }
+}
+
+/// How [OpenAPI::from_str_with] and [OpenAPI::load_file_with] parse a document.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Fail with [LoadError::DuplicateKeys] when an object repeats a key, e.g. the
+    /// same path or schema name written twice. By default the last one wins.
+    pub deny_duplicate_keys: bool,
+    /// Fail with [LoadError::UnknownFields] when an object has a field that is
+    /// neither an `x-` extension nor one the model knows, e.g. a misspelled
+    /// `requestBodies` in an operation. By default such fields are dropped.
+    pub deny_unknown_fields: bool,
+}
+
+/// Reads a file, decompressing it when it starts with a gzip or zstd header.
+fn read_file(path: &Path) -> Result<Vec<u8>, LoadError> {
+    let bytes = std::fs::read(path)?;
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        gunzip(&bytes)
+    } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        unzstd(&bytes)
+    } else {
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, LoadError> {
+    let mut out = Vec::new();
+    flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_: &[u8]) -> Result<Vec<u8>, LoadError> {
+    Err(LoadError::CompressionUnsupported("gzip"))
+}
+
+#[cfg(feature = "zstd")]
+fn unzstd(bytes: &[u8]) -> Result<Vec<u8>, LoadError> {
+    Ok(zstd::decode_all(bytes)?)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn unzstd(_: &[u8]) -> Result<Vec<u8>, LoadError> {
+    Err(LoadError::CompressionUnsupported("zstd"))
+}
+
+/// Rejects UTF-16 (and UTF-32) input, recognized by its byte order mark or by
+/// the zero bytes interleaved with an ASCII first character.
+fn check_not_utf16(head: &[u8]) -> Result<(), LoadError> {
+    let utf16 = head.starts_with(&[0xFF, 0xFE])
+        || head.starts_with(&[0xFE, 0xFF])
+        || (head.len() >= 2 && (head[0] == 0) != (head[1] == 0));
+    if utf16 {
+        Err(LoadError::Encoding("the document appears to be UTF-16 encoded; convert it to UTF-8".to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<&str, LoadError> {
+    check_not_utf16(bytes)?;
+    std::str::from_utf8(bytes).map_err(|e| LoadError::Encoding(format!("the document is not valid UTF-8: {}", e)))
+}
+
+/// Strips a leading byte order mark and converts `\r\n` and lone `\r` line endings to `\n`.
+pub(crate) fn normalize_text(s: &str) -> Cow<'_, str> {
+    let s = s.strip_prefix('\u{FEFF}').unwrap_or(s);
+    if s.contains('\r') {
+        Cow::Owned(s.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Reads a file of either format into a JSON value, without interpreting it.
+pub(crate) fn read_value(path: &Path) -> Result<serde_json::Value, LoadError> {
+    parse_value(&read_file(path)?, Some(path))
+}
+
+/// Parses a document of either format, picked by the extension of `path` if
+/// any, into a JSON value, without interpreting it.
+pub(crate) fn parse_value(bytes: &[u8], path: Option<&Path>) -> Result<serde_json::Value, LoadError> {
+    let content = normalize_text(decode(bytes)?);
+    match Format::detect(path, &content) {
+        Format::Json => Ok(serde_json::from_str(&content)?),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => Ok(serde_yaml::from_str(&content)?),
+        #[cfg(not(feature = "yaml"))]
+        Format::Yaml => Err(LoadError::YamlUnsupported),
+    }
+}
+
+pub(crate) fn from_value(value: serde_json::Value) -> Result<OpenAPI, ParseError> {
+    deserialize_tracked(value, |_| None)
+}
+
+fn deserialize_tracked<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    location: impl Fn(&D::Error) -> Option<(usize, usize)>,
+) -> Result<OpenAPI, ParseError> {
+    serde_path_to_error::deserialize(deserializer)
+        .map_err(|e| ParseError::new(Some(e.path()), e.inner().to_string(), location(e.inner())))
+}
+
+fn json_location(e: &serde_json::Error) -> Option<(usize, usize)> {
+    Some((e.line(), e.column())).filter(|(line, _)| *line > 0)
+}
+
+fn from_json<'de, R: serde_json::de::Read<'de>>(mut deserializer: serde_json::Deserializer<R>) -> Result<OpenAPI, ParseError> {
+    let spec = deserialize_tracked(&mut deserializer, json_location)?;
+    deserializer.end().map_err(|e| ParseError::new(None, e.to_string(), json_location(&e)))?;
+    Ok(spec)
+}
+
+#[cfg(feature = "yaml")]
+fn from_yaml<'de>(deserializer: serde_yaml::Deserializer<'de>) -> Result<OpenAPI, ParseError> {
+    deserialize_tracked(deserializer, |e| e.location().map(|l| (l.line(), l.column())))
+}
+
+impl OpenAPI {
+    /// Parses a spec in the given format. A leading byte order mark is ignored and
+    /// Windows line endings are normalized.
+    pub fn from_str_format(s: &str, format: Format) -> Result<Self, LoadError> {
+        let s = normalize_text(s);
+        match format {
+            Format::Json => Ok(from_json(serde_json::Deserializer::from_str(&s))?),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Ok(from_yaml(serde_yaml::Deserializer::from_str(&s))?),
+            #[cfg(not(feature = "yaml"))]
+            Format::Yaml => Err(LoadError::YamlUnsupported),
+        }
+    }
+
+    /// Parses a spec in the given format according to `options`.
+    pub fn from_str_with(s: &str, format: Format, options: &ParseOptions) -> Result<Self, LoadError> {
+        let s = normalize_text(s);
+        if options.deny_duplicate_keys {
+            let duplicates = match format {
+                Format::Json => find_duplicate_keys(&mut serde_json::Deserializer::from_str(&s))?,
+                #[cfg(feature = "yaml")]
+                Format::Yaml => find_duplicate_keys(serde_yaml::Deserializer::from_str(&s))?,
+                #[cfg(not(feature = "yaml"))]
+                Format::Yaml => return Err(LoadError::YamlUnsupported),
+            };
+            if !duplicates.is_empty() {
+                return Err(LoadError::DuplicateKeys(duplicates));
+            }
+        }
+        if options.deny_unknown_fields {
+            let document: serde_json::Value = match format {
+                Format::Json => serde_json::from_str(&s)?,
+                #[cfg(feature = "yaml")]
+                Format::Yaml => serde_yaml::from_str(&s)?,
+                #[cfg(not(feature = "yaml"))]
+                Format::Yaml => return Err(LoadError::YamlUnsupported),
+            };
+            let unknown = find_unknown_fields(&document);
+            if !unknown.is_empty() {
+                return Err(LoadError::UnknownFields(unknown));
+            }
+        }
+        Self::from_str_format(&s, format)
+    }
+
+    /// Parses a spec from `reader` without first collecting it into a string.
+    /// A leading UTF-8 byte order mark is skipped, and UTF-16 input is rejected.
+    pub fn from_reader(reader: impl Read, format: Format) -> Result<Self, LoadError> {
+        let mut reader = BufReader::new(reader);
+        let head = reader.fill_buf()?;
+        check_not_utf16(head)?;
+        if head.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            reader.consume(3);
+        }
+        match format {
+            Format::Json => Ok(from_json(serde_json::Deserializer::from_reader(reader))?),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Ok(from_yaml(serde_yaml::Deserializer::from_reader(reader))?),
+            #[cfg(not(feature = "yaml"))]
+            Format::Yaml => Err(LoadError::YamlUnsupported),
+        }
+    }
+
+    /// Serializes the spec straight into `writer`.
+    pub fn to_writer(&self, writer: impl Write, format: Format) -> Result<(), LoadError> {
+        match format {
+            Format::Json => Ok(serde_json::to_writer(writer, self)?),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Ok(serde_yaml::to_writer(writer, self)?),
+            #[cfg(not(feature = "yaml"))]
+            Format::Yaml => Err(LoadError::YamlUnsupported),
+        }
+    }
+
+    /// Reads and parses a spec file, detecting JSON vs YAML with [Format::detect].
+    /// Byte order marks and Windows line endings are handled as in [OpenAPI::from_str_format].
+    ///
+    /// With the `gzip` or `zstd` feature, compressed files (e.g. `api.yaml.gz`) are
+    /// decompressed first; they are recognized by content, whatever their name.
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        Self::load_file_with(path, &ParseOptions::default())
+    }
+
+    /// Reads and parses a spec file like [OpenAPI::load_file], according to `options`.
+    pub fn load_file_with(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let bytes = read_file(path)?;
+        let content = decode(&bytes)?;
+        let format = Format::detect(Some(path), normalize_text(content).as_ref());
+        Self::from_str_with(content, format, options)
+    }
+
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    pub fn from_yaml_str(s: &str) -> Result<Self, LoadError> {
+        Self::from_str_format(s, Format::Yaml)
+    }
+
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    pub fn to_yaml_string(&self) -> Result<String, LoadError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(Format::detect(Some(Path::new("api.JSON")), "openapi: 3.0.0"), Format::Json);
+        assert_eq!(Format::detect(Some(Path::new("api.yml")), "{}"), Format::Yaml);
+        assert_eq!(Format::detect(None, "  {\"openapi\": \"3.0.0\"}"), Format::Json);
+        assert_eq!(Format::detect(Some(Path::new("api.txt")), "openapi: 3.0.0"), Format::Yaml);
+        assert_eq!(Format::detect(Some(Path::new("api.json.gz")), "openapi: 3.0.0"), Format::Json);
+        assert_eq!(Format::detect(Some(Path::new("api.gz")), "openapi: 3.0.0"), Format::Yaml);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_load_gzip() {
+        let json = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/xkcd.json")).unwrap();
+        let path = std::env::temp_dir().join(format!("openapiv3-{}.json.gz", std::process::id()));
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&json).unwrap();
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+        let loaded = OpenAPI::load_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), serde_json::from_slice::<OpenAPI>(&json).unwrap());
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_unsupported() {
+        let path = std::env::temp_dir().join(format!("openapiv3-{}.json.gz", std::process::id()));
+        std::fs::write(&path, [0x1f, 0x8b, 0x08, 0x00]).unwrap();
+        let loaded = OpenAPI::load_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(loaded, Err(LoadError::CompressionUnsupported("gzip"))));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_load_zstd() {
+        let json = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/xkcd.json")).unwrap();
+        let path = std::env::temp_dir().join(format!("openapiv3-{}.json.zst", std::process::id()));
+        std::fs::write(&path, zstd::encode_all(json.as_slice(), 3).unwrap()).unwrap();
+        let loaded = OpenAPI::load_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), serde_json::from_slice::<OpenAPI>(&json).unwrap());
+    }
+
+    #[test]
+    fn test_load_file() {
+        let spec = OpenAPI::load_file(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/xkcd.json")).unwrap();
+        assert!(!spec.paths.paths.is_empty());
+    }
+
+    #[test]
+    fn test_reader_writer() {
+        let spec = OpenAPI::load_file(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/xkcd.json")).unwrap();
+        let mut buf = Vec::new();
+        spec.to_writer(&mut buf, Format::Json).unwrap();
+        assert_eq!(OpenAPI::from_reader(buf.as_slice(), Format::Json).unwrap(), spec);
+        #[cfg(feature = "yaml")]
+        {
+            buf.clear();
+            spec.to_writer(&mut buf, Format::Yaml).unwrap();
+            assert_eq!(OpenAPI::from_reader(buf.as_slice(), Format::Yaml).unwrap(), spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let input = "{\n  \"openapi\": \"3.0.3\",\n  \"info\": {\"title\": 1, \"version\": \"1\"},\n  \"paths\": {}\n}";
+        let Err(LoadError::Parse(e)) = OpenAPI::from_str_format(input, Format::Json) else { panic!() };
+        assert_eq!(e.pointer, "/info/title");
+        assert_eq!((e.line, e.column), (Some(3), Some(21)));
+        assert_eq!(e.to_string(), "invalid type: integer `1`, expected a string at /info/title (line 3, column 21)");
+    }
+
+    #[test]
+    fn test_text_normalization() {
+        let input = "\u{FEFF}{\r\n  \"openapi\": \"3.0.3\",\r\n  \"info\": {\"title\": \"t\", \"version\": \"1\"},\r\n  \"paths\": {}\r\n}";
+        let spec = OpenAPI::from_str_format(input, Format::Json).unwrap();
+        assert_eq!(spec.info.title, "t");
+        assert_eq!(OpenAPI::from_reader(input.as_bytes(), Format::Json).unwrap(), spec);
+
+        let utf16: Vec<u8> = "{}".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        assert!(matches!(OpenAPI::from_reader(utf16.as_slice(), Format::Json), Err(LoadError::Encoding(_))));
+        assert!(matches!(decode(&[0xFF, 0xFE, b'{', 0]), Err(LoadError::Encoding(_))));
+    }
+
+    #[test]
+    fn test_deny_duplicate_keys() {
+        let input = r#"{"openapi": "3.0.3", "info": {"title": "t", "version": "1"},
+            "paths": {"/a/b": {}, "/c": {}, "/a/b": {"get": {"responses": {}}}},
+            "components": {"schemas": {"Pet": {}, "Pet": {"type": "string"}}}}"#;
+        let spec = OpenAPI::from_str_format(input, Format::Json).unwrap();
+        assert!(spec.paths.paths["/a/b"].as_item().unwrap().get.is_some());
+
+        let options = ParseOptions { deny_duplicate_keys: true, ..ParseOptions::default() };
+        let Err(LoadError::DuplicateKeys(errors)) = OpenAPI::from_str_with(input, Format::Json, &options) else { panic!() };
+        let pointers: Vec<&str> = errors.iter().map(|e| e.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/paths/~1a~1b", "/components/schemas/Pet"]);
+        assert_eq!(errors[1].to_string(), "duplicate key `Pet` at /components/schemas/Pet");
+
+        let valid = include_str!("../fixtures/xkcd.json");
+        assert!(OpenAPI::from_str_with(valid, Format::Json, &options).is_ok());
+    }
+
+    #[test]
+    fn test_deny_unknown_fields() {
+        let input = r##"{"openapi": "3.1.0", "info": {"title": "t", "version": "1", "x-audience": "public", "license": {"name": "MIT", "identifier": "MIT"}},
+            "paths": {"/pets": {"post": {"requestBodies": {}, "responses": {"200": {"$ref": "#/components/responses/Ok", "headers": {}}}}}},
+            "components": {"securitySchemes": {"key": {"type": "apiKey", "in": "header", "name": "X-Key", "scheme": "basic"}}}}"##;
+        assert!(OpenAPI::from_str_format(input, Format::Json).is_ok());
+
+        let options = ParseOptions { deny_unknown_fields: true, ..ParseOptions::default() };
+        let Err(LoadError::UnknownFields(errors)) = OpenAPI::from_str_with(input, Format::Json, &options) else { panic!() };
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(errors, vec![
+            "unknown field `requestBodies` at /paths/~1pets/post/requestBodies",
+            "unknown field `headers` at /paths/~1pets/post/responses/200/headers",
+            "unknown field `scheme` at /components/securitySchemes/key/scheme",
+        ]);
+
+        let valid = include_str!("../fixtures/slack.json");
+        assert!(OpenAPI::from_str_with(valid, Format::Json, &options).is_ok());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_deny_duplicate_yaml_keys() {
+        let input = "openapi: 3.0.3\ninfo:\n  title: t\n  version: '1'\npaths:\n  /a:\n    get:\n      responses:\n        200: {description: a}\n        '200': {description: b}\n";
+        let options = ParseOptions { deny_duplicate_keys: true, ..ParseOptions::default() };
+        let Err(LoadError::DuplicateKeys(errors)) = OpenAPI::from_str_with(input, Format::Yaml, &options) else { panic!() };
+        assert_eq!(errors[0].pointer, "/paths/~1a/get/responses/200");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_crlf() {
+        let spec = OpenAPI::from_yaml_str("\u{FEFF}openapi: 3.0.3\r\ninfo:\r\n  title: t\r\n  description: |\r\n    a\r\n    b\r\n  version: '1'\r\npaths: {}\r\n").unwrap();
+        assert_eq!(spec.info.description.as_deref(), Some("a\nb\n"));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_parse_error_location() {
+        let input = "openapi: 3.0.3\ninfo:\n  title: t\n  version: [1]\npaths: {}\n";
+        let Err(LoadError::Parse(e)) = OpenAPI::from_yaml_str(input) else { panic!() };
+        assert_eq!(e.pointer, "/info/version");
+        assert_eq!(e.line, Some(4));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_round_trip() {
+        let spec = OpenAPI::load_file(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/petstore.yaml")).unwrap();
+        let yaml = spec.to_yaml_string().unwrap();
+        assert_eq!(OpenAPI::from_yaml_str(&yaml).unwrap(), spec);
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::schema_ser::null_type;
+use crate::split::unique_file;
+use crate::*;
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Keywords of OpenAPI schemas that JSON Schema doesn't know.
+const OPENAPI_KEYWORDS: &[&str] = &["discriminator", "xml", "externalDocs"];
+
+impl OpenAPI {
+    /// Writes each component schema to `dir` as a standalone JSON Schema (draft
+    /// 2020-12) file, e.g. `Pet.json`, for tools that only speak JSON Schema. The
+    /// files refer to each other by relative path, and an `index.json` lists them
+    /// under `$defs`, so `index.json#/$defs/Pet` refers to any of them.
+    ///
+    /// The schemas are upgraded to their 3.1 form, `nullable` becomes a `null`
+    /// type, `example` becomes `examples`, and the OpenAPI-only `discriminator`,
+    /// `xml` and `externalDocs` are dropped. Refs to other components are kept as
+    /// they are.
+    pub fn export_schema_bundle(&self, dir: impl AsRef<Path>) -> Result<(), LoadError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for (path, value) in schema_bundle_files(self)? {
+            std::fs::write(dir.join(path), serde_json::to_string_pretty(&value)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// The files of a schema bundle, by name. The index comes first.
+pub(crate) fn schema_bundle_files(spec: &OpenAPI) -> Result<Vec<(PathBuf, Value)>, serde_json::Error> {
+    let mut used = HashSet::from(["index".to_string()]);
+    let files: HashMap<&str, PathBuf> = spec.schemas.keys()
+        .map(|name| (name.as_str(), unique_file(&mut used, Path::new(""), name, "json")))
+        .collect();
+    let mut defs = Map::new();
+    let mut out = Vec::new();
+    for (name, schema) in &spec.schemas {
+        let file = &files[name.as_str()];
+        let file_name = file.to_string_lossy().into_owned();
+        let mut schema = schema.clone();
+        if let Some(schema) = schema.as_mut() {
+            schema.upgrade_to_v3_1();
+        }
+        let mut value = serde_json::to_value(schema)?;
+        convert(&mut value, &|name, rest| {
+            let file = files.get(name)?.to_string_lossy();
+            Some(if rest.is_empty() { file.into_owned() } else { format!("{}#/{}", file, rest) })
+        });
+        if let Value::Object(map) = &mut value {
+            map.entry("title").or_insert_with(|| name.clone().into());
+            let mut head = Map::new();
+            head.insert("$schema".to_string(), DIALECT.into());
+            head.insert("$id".to_string(), file_name.clone().into());
+            head.extend(std::mem::take(map));
+            *map = head;
+        }
+        defs.insert(name.clone(), json!({"$ref": file_name}));
+        out.push((file.clone(), value));
+    }
+    let index = json!({
+        "$schema": DIALECT,
+        "$id": "index.json",
+        "title": spec.info.title,
+        "$defs": defs,
+    });
+    out.insert(0, (PathBuf::from("index.json"), index));
+    Ok(out)
+}
+
+/// Rewrites a serialized OpenAPI schema, and its subschemas, into JSON Schema.
+/// `relink` gives the new target of a ref to a component schema from its name
+/// and the pointer into it, if any, e.g. `Pet` and `properties/id`.
+pub(crate) fn convert(value: &mut Value, relink: &dyn Fn(&str, &str) -> Option<String>) {
+    let Value::Object(map) = value else { return };
+    if let Some(Value::String(reference)) = map.get_mut("$ref") {
+        if let Some(pointer) = reference.strip_prefix("#/components/schemas/") {
+            let (name, rest) = pointer.split_once('/').unwrap_or((pointer, ""));
+            let name = unescape(name);
+            if let Some(target) = relink(&name, rest) {
+                *reference = target;
+            }
+        }
+    }
+    for keyword in OPENAPI_KEYWORDS {
+        map.remove(*keyword);
+    }
+    if let Some(example) = map.remove("example") {
+        map.entry("examples").or_insert_with(|| json!([example]));
+    }
+    null_type(map);
+    for_each_subschema(map, &mut |s| convert(s, relink));
+}
+
+/// Calls `f` on each subschema of a serialized schema, not recursively.
+pub(crate) fn for_each_subschema(map: &mut Map<String, Value>, f: &mut dyn FnMut(&mut Value)) {
+    for (keyword, child) in map.iter_mut() {
+        match (keyword.as_str(), child) {
+            ("properties" | "patternProperties" | "$defs" | "dependentSchemas", Value::Object(schemas)) => {
+                schemas.values_mut().for_each(&mut *f);
+            }
+            ("allOf" | "anyOf" | "oneOf" | "prefixItems", Value::Array(schemas)) => {
+                schemas.iter_mut().for_each(&mut *f);
+            }
+            ("items" | "additionalProperties" | "not" | "if" | "then" | "else" | "contains" | "propertyNames"
+            | "unevaluatedItems" | "unevaluatedProperties", child) => f(child),
+            _ => {}
+        }
+    }
+}
+
+/// `schema` as a JSON Schema, converted as by [OpenAPI::export_schema_bundle],
+/// with the component schemas of `components` as `$defs`, so its refs to them
+/// resolve.
+#[cfg(any(feature = "jsonschema", feature = "schemars"))]
+pub(crate) fn to_json_schema(schema: &Schema, components: &Components) -> Value {
+    let relink = |name: &str, rest: &str| {
+        let name = escape(name);
+        Some(if rest.is_empty() { format!("#/$defs/{}", name) } else { format!("#/$defs/{}/{}", name, rest) })
+    };
+    let value = |schema: &RefOr<Schema>| {
+        let mut schema = schema.clone();
+        if let Some(schema) = schema.as_mut() {
+            schema.upgrade_to_v3_1();
+        }
+        let mut value = serde_json::to_value(schema).expect("schemas serialize");
+        convert(&mut value, &relink);
+        value
+    };
+    let defs: Map<String, Value> = components.schemas.iter().map(|(name, schema)| (name.clone(), value(schema))).collect();
+    let mut root = value(&RefOr::Item(schema.clone()));
+    if let Value::Object(map) = &mut root {
+        map.insert("$schema".to_string(), DIALECT.into());
+        if !defs.is_empty() {
+            map.insert("$defs".to_string(), Value::Object(defs));
+        }
+    }
+    root
+}
+
+#[cfg(feature = "jsonschema")]
+impl Schema {
+    /// Compiles this schema into a [jsonschema::Validator], for payload validation
+    /// with full keyword coverage, as an alternative to [Schema::validate_value].
+    /// The schema is converted to JSON Schema as by [OpenAPI::export_schema_bundle],
+    /// and the component schemas of `components` come along as `$defs`, so its
+    /// refs to them resolve.
+    #[cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+    pub fn compile(&self, components: &Components) -> Result<jsonschema::Validator, Box<jsonschema::ValidationError<'static>>> {
+        jsonschema::validator_for(&to_json_schema(self, components)).map_err(Box::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_bundle_files() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "discriminator": {"propertyName": "kind"},
+                    "properties": {
+                        "nullable": {"type": "string", "nullable": true, "example": "x"},
+                        "owner": {"allOf": [{"$ref": "#/components/schemas/index"}], "nullable": true},
+                        "age": {"$ref": "#/components/schemas/index/properties/age"}
+                    }
+                },
+                "index": {"type": "object", "properties": {"age": {"type": "integer", "minimum": 0, "exclusiveMinimum": true}}}
+            }}
+        })).unwrap();
+        let files = schema_bundle_files(&spec).unwrap();
+        assert_eq!(files[0], (PathBuf::from("index.json"), json!({
+            "$schema": DIALECT,
+            "$id": "index.json",
+            "title": "Pets",
+            "$defs": {"Pet": {"$ref": "Pet.json"}, "index": {"$ref": "index2.json"}},
+        })));
+        assert_eq!(files[1], (PathBuf::from("Pet.json"), json!({
+            "$schema": DIALECT,
+            "$id": "Pet.json",
+            "type": "object",
+            "properties": {
+                "nullable": {"type": ["string", "null"], "examples": ["x"]},
+                "owner": {"anyOf": [{"allOf": [{"$ref": "index2.json"}]}, {"type": "null"}]},
+                "age": {"$ref": "index2.json#/properties/age"}
+            },
+            "title": "Pet",
+        })));
+        assert_eq!(files[2].1["properties"]["age"], json!({"type": "integer", "exclusiveMinimum": 0}));
+    }
+
+    #[cfg(feature = "jsonschema")]
+    #[test]
+    fn test_compile() {
+        let components: Components = serde_json::from_value(json!({"schemas": {
+            "Pet": {"type": "object", "required": ["name"], "properties": {
+                "name": {"type": "string", "nullable": true},
+                "age": {"type": "integer", "minimum": 0, "exclusiveMinimum": true}
+            }}
+        }})).unwrap();
+        let schema: Schema = serde_json::from_value(json!({"type": "array", "items": {"$ref": "#/components/schemas/Pet"}})).unwrap();
+        let validator = schema.compile(&components).unwrap();
+        assert!(validator.is_valid(&json!([{"name": null}, {"name": "Rex", "age": 1}])));
+        assert!(!validator.is_valid(&json!([{"age": 1}])));
+        assert!(!validator.is_valid(&json!([{"name": "Rex", "age": 0}])));
+    }
+}
@@ -0,0 +1,107 @@
+use indexmap::IndexMap;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use serde_json::value::RawValue;
+
+use crate::{OpenAPI, PathItem, RefOr};
+
+/// A JSON spec whose path items are parsed only when accessed, for tools that
+/// need `info` or `components` but not the (often thousands of) operations.
+///
+/// Each access to a path item parses it again; keep the result if it's needed twice.
+///
+/// ```
+/// # use openapiv3::LazyOpenAPI;
+/// let input = r#"{"openapi": "3.0.3", "info": {"title": "t", "version": "1"},
+///     "paths": {"/pets": {"get": {"responses": {}}}}}"#;
+/// let spec = LazyOpenAPI::from_json(input).unwrap();
+/// assert_eq!(spec.document.info.title, "t");
+/// let item = spec.path_item("/pets").unwrap().unwrap();
+/// assert!(item.as_item().unwrap().get.is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyOpenAPI {
+    /// Everything but the path items. `document.paths` holds only the extensions
+    /// of the Paths Object.
+    pub document: OpenAPI,
+    paths: IndexMap<String, Box<RawValue>>,
+}
+
+impl LazyOpenAPI {
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// The paths, in document order.
+    pub fn paths(&self) -> impl Iterator<Item=&str> {
+        self.paths.keys().map(String::as_str)
+    }
+
+    /// The unparsed path item at `path`.
+    pub fn raw_path_item(&self, path: &str) -> Option<&RawValue> {
+        self.paths.get(path).map(|raw| &**raw)
+    }
+
+    /// Parses the path item at `path`, if there is one.
+    pub fn path_item(&self, path: &str) -> Option<Result<RefOr<PathItem>, serde_json::Error>> {
+        self.raw_path_item(path).map(|raw| serde_json::from_str(raw.get()))
+    }
+
+    /// Parses every path item, returning the complete spec.
+    pub fn into_openapi(self) -> Result<OpenAPI, serde_json::Error> {
+        let mut spec = self.document;
+        for (path, raw) in self.paths {
+            let item = serde_json::from_str(raw.get())?;
+            spec.paths.paths.insert(path, item);
+        }
+        Ok(spec)
+    }
+}
+
+impl<'de> Deserialize<'de> for LazyOpenAPI {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = IndexMap::<String, Box<RawValue>>::deserialize(deserializer)?;
+        let mut paths = IndexMap::new();
+        if let Some(raw) = fields.get_mut("paths") {
+            let entries = IndexMap::<String, Box<RawValue>>::deserialize(&mut serde_json::Deserializer::from_str(raw.get()))
+                .map_err(D::Error::custom)?;
+            // Extensions stay behind, to be parsed with the rest of the document.
+            let (extensions, items): (IndexMap<_, _>, IndexMap<_, _>) = entries.into_iter().partition(|(k, _)| k.starts_with("x-"));
+            paths = items;
+            *raw = serde_json::value::to_raw_value(&extensions).map_err(D::Error::custom)?;
+        }
+        // Reuse the model for everything else, so the two can't drift apart.
+        let rest = serde_json::to_string(&fields).map_err(D::Error::custom)?;
+        let document = serde_json::from_str(&rest).map_err(D::Error::custom)?;
+        Ok(LazyOpenAPI { document, paths })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_matches_eager() {
+        let input = include_str!("../fixtures/quayio.json");
+        let spec: OpenAPI = serde_json::from_str(input).unwrap();
+        let lazy = LazyOpenAPI::from_json(input).unwrap();
+        assert_eq!(lazy.document.components, spec.components);
+        assert!(lazy.document.paths.paths.is_empty());
+        assert_eq!(lazy.paths().collect::<Vec<_>>(), spec.paths.paths.keys().map(String::as_str).collect::<Vec<_>>());
+        let first = spec.paths.paths.keys().next().unwrap();
+        assert_eq!(&lazy.path_item(first).unwrap().unwrap(), &spec.paths.paths[first]);
+        assert!(lazy.path_item("/nope").is_none());
+        assert_eq!(lazy.into_openapi().unwrap(), spec);
+    }
+
+    #[test]
+    fn test_lazy_paths_extensions() {
+        let input = r#"{"openapi": "3.0.3", "info": {"title": "t", "version": "1"},
+            "paths": {"x-group": true, "/a": {"get": {"responses": {}}}}}"#;
+        let lazy = LazyOpenAPI::from_json(input).unwrap();
+        assert_eq!(lazy.paths().collect::<Vec<_>>(), vec!["/a"]);
+        assert_eq!(lazy.document.paths.extensions["x-group"], true);
+        assert_eq!(lazy.into_openapi().unwrap(), serde_json::from_str::<OpenAPI>(input).unwrap());
+    }
+}
@@ -1,66 +1,209 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod binary;
+mod body;
+mod builder;
+mod bundle;
 mod callback;
+mod canonicalize;
+pub mod codegen;
+#[cfg(any(feature = "upstream", feature = "utoipa", feature = "schemars"))]
+mod compat;
 mod components;
+mod conformance;
 mod contact;
+mod curl;
+mod dereference;
+mod deprecation;
+mod diff;
 mod discriminator;
+mod duplicate_keys;
+mod emit;
 mod encoding;
 mod example;
+mod example_generation;
+mod example_validation;
+#[cfg(feature = "fake")]
+mod fake_data;
+mod extension;
 mod external_documentation;
+#[cfg(feature = "external-examples")]
+mod external_example;
+mod extract;
+mod filter;
+mod flatten;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod graph;
+mod graphql;
+mod har;
 mod header;
+mod index;
+mod infer;
 mod info;
+mod instance_validation;
+mod io;
+mod json_schema;
+mod lazy;
 mod license;
 mod link;
+pub mod lint;
+#[cfg(feature = "mime")]
+mod media;
 mod media_type;
+mod merge;
+#[cfg(feature = "meta-schema")]
+mod meta_schema;
+mod migrate;
+mod mock;
+mod node;
+#[cfg(feature = "oidc")]
+mod oidc;
 mod openapi;
 mod operation;
+mod operation_id;
+mod overlay;
 mod parameter;
+mod parameter_style;
 mod paths;
+mod postman;
+mod preserve;
+#[cfg(feature = "arbitrary_precision")]
+mod precise;
+mod prune;
+#[cfg(feature = "pyo3")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pyo3")))]
+pub mod python;
+mod query;
+mod raw;
 mod reference;
+pub mod render;
+mod request;
 mod request_body;
+mod resolved;
+mod resolved_spec;
+mod resolver_cache;
 mod responses;
+mod routes;
+mod runtime_expression;
 mod schema;
+mod schema_de;
+mod schema_ser;
 mod security_requirement;
 mod security_scheme;
+mod semantic;
 mod server;
+mod session;
 mod server_variable;
+#[cfg(feature = "spanned")]
+#[cfg_attr(docsrs, doc(cfg(feature = "spanned")))]
+mod spanned;
+mod split;
+mod stats;
 mod status_code;
+mod stream;
 mod tag;
+mod transform;
+mod unknown_fields;
 mod util;
+mod validate;
 mod variant_or;
 #[cfg(feature = "v2")]
 #[cfg_attr(docsrs, doc(cfg(feature = "v2")))]
 pub mod v2;
 mod versioned;
+mod visit;
+mod walk;
+#[cfg(feature = "wasm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+pub mod wasm;
+mod xml;
 mod map;
 
+pub use self::body::*;
+pub use self::builder::*;
+pub use self::bundle::*;
 pub use self::callback::*;
 pub use self::components::*;
+pub use self::conformance::*;
 pub use self::contact::*;
+pub use self::curl::*;
+pub use self::dereference::*;
+pub use self::deprecation::*;
+pub use self::diff::*;
 pub use self::discriminator::*;
+pub use self::emit::*;
 pub use self::encoding::*;
 pub use self::example::*;
+pub use self::example_generation::*;
+pub use self::example_validation::*;
+pub use self::extension::*;
 pub use self::external_documentation::*;
+#[cfg(feature = "external-examples")]
+pub use self::external_example::*;
+pub use self::extract::*;
+pub use self::filter::*;
+pub use self::flatten::*;
+#[cfg(feature = "proptest")]
+pub use self::fuzz::*;
+pub use self::graph::*;
+pub use self::graphql::*;
 pub use self::header::*;
+pub use self::index::*;
 pub use self::info::*;
+pub use self::instance_validation::*;
+pub use self::io::*;
+pub use self::lazy::*;
 pub use self::license::*;
 pub use self::link::*;
+#[cfg(feature = "mime")]
+pub use self::media::*;
 pub use self::media_type::*;
+pub use self::merge::*;
+#[cfg(feature = "meta-schema")]
+pub use self::meta_schema::*;
+pub use self::mock::*;
+pub use self::node::*;
+#[cfg(feature = "oidc")]
+pub use self::oidc::*;
 pub use self::openapi::*;
 pub use self::operation::*;
+pub use self::operation_id::*;
+pub use self::overlay::*;
 pub use self::parameter::*;
+pub use self::parameter_style::{percent_decode, percent_encode, ContentError, StyleError};
 pub use self::paths::*;
+pub use self::preserve::*;
+pub use self::query::*;
+pub use self::raw::*;
 pub use self::reference::*;
+pub use self::request::*;
 pub use self::request_body::*;
+pub use self::resolved::*;
+pub use self::resolved_spec::*;
+pub use self::resolver_cache::*;
 pub use self::responses::*;
+pub use self::routes::*;
+pub use self::runtime_expression::*;
 pub use self::schema::*;
 pub use self::security_requirement::*;
 pub use self::security_scheme::*;
 pub use self::server::*;
+pub use self::session::*;
 pub use self::server_variable::*;
+#[cfg(feature = "spanned")]
+pub use self::spanned::*;
+pub use self::split::*;
+pub use self::stats::*;
 pub use self::status_code::*;
+pub use self::stream::*;
 pub use self::tag::*;
+pub use self::transform::*;
 pub use self::util::*;
+pub use self::validate::*;
 pub use self::variant_or::*;
+pub use self::visit::*;
+pub use self::xml::*;
 pub use map::*;
 pub use http::method::Method as PathMethod;
 pub use versioned::*;
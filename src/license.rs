@@ -3,13 +3,20 @@ use serde::{Deserialize, Serialize};
 
 /// License information for the exposed API.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct License {
     /// REQUIRED. The license name used for the API.
     pub name: String,
+    /// An SPDX license expression for the API, e.g. `Apache-2.0`. Mutually
+    /// exclusive with `url`. Introduced in OpenAPI 3.1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
     /// A URL to the license used for the API. MUST be in the format of a URL.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
@@ -1,6 +1,7 @@
 use crate::*;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// The Link object represents a possible design-time link for a response.
 /// The presence of a link does not guarantee the caller's ability to
@@ -14,6 +15,8 @@ use serde::{Deserialize, Serialize};
 /// a runtime expression is used for accessing values in an operation
 /// and using them as parameters while invoking the linked operation.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Link {
     /// A description of the link.
@@ -26,6 +29,7 @@ pub struct Link {
     /// A literal value or {expression} to use as a request body
     /// when calling the target operation.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub request_body: Option<serde_json::Value>,
     /// A map representing parameters to pass to an operation
     /// as specified with operationId or identified via operationRef.
@@ -35,16 +39,20 @@ pub struct Link {
     /// using the parameter location [{in}.]{name} for operations
     /// that use the same parameter name in different locations (e.g. path.id).
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::value_map))]
     pub parameters: IndexMap<String, serde_json::Value>,
     /// A server object to be used by the target operation.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server: Option<Server>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum LinkOperation {
     /// A relative or absolute reference to an OAS operation.
@@ -58,3 +66,77 @@ pub enum LinkOperation {
     /// mutually exclusive of the operationRef field.
     OperationId(String),
 }
+
+impl Link {
+    /// The values to call the linked operation with, by parameter name as
+    /// written, e.g. `path.id`: expressions evaluated in `context`, keeping the
+    /// JSON type of what they read, `{expression}`s embedded in strings
+    /// expanded as by [expand_expressions], and constants as they are.
+    pub fn evaluate_parameters(&self, context: &RuntimeContext) -> Result<IndexMap<String, Value>, RuntimeExpressionError> {
+        self.parameters.iter()
+            .map(|(name, value)| Ok((name.clone(), evaluate(value, context)?)))
+            .collect()
+    }
+
+    /// The request body to call the linked operation with, evaluated like
+    /// [Link::evaluate_parameters].
+    pub fn evaluate_request_body(&self, context: &RuntimeContext) -> Result<Option<Value>, RuntimeExpressionError> {
+        self.request_body.as_ref().map(|body| evaluate(body, context)).transpose()
+    }
+
+    /// The parameters whose values are runtime expressions, parsed. Constants
+    /// and strings with embedded `{expression}`s aren't included.
+    pub fn parameter_expressions(&self) -> impl Iterator<Item=(&str, Result<RuntimeExpression, RuntimeExpressionError>)> {
+        self.parameters.iter().filter_map(|(name, value)| match value {
+            Value::String(s) if s.starts_with('$') => Some((name.as_str(), RuntimeExpression::parse(s))),
+            _ => None,
+        })
+    }
+}
+
+fn evaluate(value: &Value, context: &RuntimeContext) -> Result<Value, RuntimeExpressionError> {
+    match value {
+        Value::String(s) if s.starts_with('$') => RuntimeExpression::parse(s)?.evaluate(context)
+            .ok_or_else(|| RuntimeExpressionError { expression: s.clone(), message: "no value in the context" }),
+        Value::String(s) if s.contains('{') => expand_expressions(s, context).map(Value::String),
+        value => Ok(value.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_evaluate_parameters() {
+        let link: Link = serde_json::from_value(json!({
+            "operationId": "getPet",
+            "parameters": {
+                "path.id": "$response.body#/id",
+                "owner": "$request.query.owner",
+                "trace": "pet-{$response.header.X-Trace}",
+                "limit": 10
+            },
+            "requestBody": {"constant": true}
+        })).unwrap();
+        let mut context = RuntimeContext::default();
+        context.request.query.insert("owner".to_string(), "ann".to_string());
+        context.response.headers.insert("x-trace".to_string(), "abc".to_string());
+        context.response.body = Some(json!({"id": 5}));
+
+        assert_eq!(serde_json::to_value(link.evaluate_parameters(&context).unwrap()).unwrap(), json!({
+            "path.id": 5,
+            "owner": "ann",
+            "trace": "pet-abc",
+            "limit": 10
+        }));
+        assert_eq!(link.evaluate_request_body(&context).unwrap(), Some(json!({"constant": true})));
+        let expressions: Vec<String> = link.parameter_expressions().map(|(_, e)| e.unwrap().to_string()).collect();
+        assert_eq!(expressions, ["$response.body#/id", "$request.query.owner"]);
+
+        context.response.body = None;
+        assert_eq!(link.evaluate_parameters(&context).unwrap_err().expression, "$response.body#/id");
+    }
+}
@@ -0,0 +1,156 @@
+//! Checks for specs that are valid but likely mistaken or unsafe, as a set of
+//! [Rule]s run by a [Linter], such as the rule packs [security] and [style].
+//!
+//! ```
+//! # let spec = openapiv3::OpenAPI::default();
+//! use openapiv3::lint::Linter;
+//!
+//! for finding in Linter::security().run(&spec) {
+//!     println!("{}", finding);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::OpenAPI;
+
+pub mod security;
+pub mod style;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        })
+    }
+}
+
+/// A problem a [Rule] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer to where the problem is, e.g. `/paths/~1pets/get`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(pointer: impl Into<String>, message: impl Into<String>) -> Self {
+        Violation { pointer: pointer.into(), message: message.into() }
+    }
+}
+
+/// A [Violation], with the rule that reported it and its severity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {} [{}]", self.pointer, self.severity, self.message, self.rule)
+    }
+}
+
+/// A check run by a [Linter]. Implement it for checks of your own.
+pub trait Rule: Send + Sync {
+    /// A kebab-case identifier, e.g. `oauth-implicit-flow`.
+    fn name(&self) -> &str;
+
+    fn severity(&self) -> Severity;
+
+    /// The problems in `spec`, in document order.
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation>;
+}
+
+/// A set of [Rule]s to run together.
+#[derive(Default)]
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+    /// Severities set by [Linter::severity], by rule name.
+    severities: HashMap<String, Severity>,
+}
+
+impl Linter {
+    /// A linter without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A linter with the rules of [security].
+    pub fn security() -> Self {
+        Linter { rules: security::rules(), ..Self::default() }
+    }
+
+    /// A linter with the rules of [style].
+    pub fn style() -> Self {
+        Linter { rules: style::rules(), ..Self::default() }
+    }
+
+    pub fn rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Adds the rules of `other`, keeping its severities.
+    pub fn extend(mut self, other: Linter) -> Self {
+        self.rules.extend(other.rules);
+        self.severities.extend(other.severities);
+        self
+    }
+
+    /// Reports what the rule named `rule` finds with `severity` rather than its own.
+    pub fn severity(mut self, rule: &str, severity: Severity) -> Self {
+        self.severities.insert(rule.to_string(), severity);
+        self
+    }
+
+    /// Removes the rule named `rule`.
+    pub fn without(mut self, rule: &str) -> Self {
+        self.rules.retain(|r| r.name() != rule);
+        self
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item=&dyn Rule> {
+        self.rules.iter().map(AsRef::as_ref)
+    }
+
+    /// Runs every rule on `spec`, in parallel with the `rayon` feature. Findings
+    /// are ordered by rule, then as each rule reports them.
+    pub fn run(&self, spec: &OpenAPI) -> Vec<Finding> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            self.rules.par_iter().flat_map_iter(|rule| self.run_rule(rule.as_ref(), spec)).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            self.rules.iter().flat_map(|rule| self.run_rule(rule.as_ref(), spec)).collect()
+        }
+    }
+
+    fn run_rule(&self, rule: &dyn Rule, spec: &OpenAPI) -> Vec<Finding> {
+        let name = rule.name();
+        let severity = self.severities.get(name).copied().unwrap_or_else(|| rule.severity());
+        rule.check(spec).into_iter().map(|Violation { pointer, message }| Finding {
+            rule: name.to_string(),
+            severity,
+            pointer,
+            message,
+        }).collect()
+    }
+}
@@ -0,0 +1,198 @@
+//! Rules for security reviews, see [Linter::security](super::Linter::security).
+
+use indexmap::IndexSet;
+
+use super::{Rule, Severity, Violation};
+use crate::example_validation::child;
+use crate::*;
+
+/// The rules of this module.
+pub fn rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(OperationSecurity),
+        Box::new(ApiKeyOverHttp),
+        Box::new(BasicAuth),
+        Box::new(OAuthImplicitFlow),
+        Box::new(WildcardScope),
+    ]
+}
+
+fn operation_pointer(path: &str, method: &str) -> String {
+    child(&child("/paths", path), method)
+}
+
+fn scheme_pointer(name: &str) -> String {
+    child("/components/securitySchemes", name)
+}
+
+fn schemes(spec: &OpenAPI) -> impl Iterator<Item=(&str, &SecurityScheme)> {
+    spec.security_schemes.iter().filter_map(|(name, scheme)| Some((name.as_str(), scheme.as_item()?)))
+}
+
+/// Operations that can be called without credentials, neither declaring security
+/// nor inheriting the spec's. Those opting out explicitly with `security: []` are
+/// taken to be public on purpose.
+pub struct OperationSecurity;
+
+impl Rule for OperationSecurity {
+    fn name(&self) -> &str {
+        "operation-security"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        spec.resolved().operations()
+            .filter(|op| op.operation.security.is_none() && op.security().is_empty())
+            .map(|op| Violation::new(operation_pointer(op.path, op.method), "operation has no security requirements"))
+            .collect()
+    }
+}
+
+/// API keys sent to a plain `http://` server, where anyone on the network can
+/// read them.
+pub struct ApiKeyOverHttp;
+
+impl Rule for ApiKeyOverHttp {
+    fn name(&self) -> &str {
+        "api-key-over-http"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        let mut reported = IndexSet::new();
+        for op in spec.resolved().operations() {
+            let insecure = op.servers().iter().filter(|server| server.url.starts_with("http://"));
+            for server in insecure {
+                let keys = op.security().iter()
+                    .flat_map(|requirement| requirement.keys())
+                    .filter(|name| matches!(spec.security_schemes.get2(name), Some(SecurityScheme::APIKey { .. })));
+                for name in keys {
+                    reported.insert((name.as_str(), server.url.as_str()));
+                }
+            }
+        }
+        reported.into_iter()
+            .map(|(name, url)| Violation::new(scheme_pointer(name), format!("API key is sent to {}, which is not HTTPS", url)))
+            .collect()
+    }
+}
+
+/// HTTP Basic authentication, which sends the password itself with every request.
+pub struct BasicAuth;
+
+impl Rule for BasicAuth {
+    fn name(&self) -> &str {
+        "basic-auth"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        schemes(spec)
+            .filter(|(_, scheme)| matches!(scheme, SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("basic")))
+            .map(|(name, _)| Violation::new(scheme_pointer(name), "basic authentication sends credentials with every request"))
+            .collect()
+    }
+}
+
+/// The OAuth2 implicit flow, which OAuth 2.0 Security Best Current Practice
+/// deprecates in favour of the authorization code flow with PKCE.
+pub struct OAuthImplicitFlow;
+
+impl Rule for OAuthImplicitFlow {
+    fn name(&self) -> &str {
+        "oauth-implicit-flow"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        schemes(spec)
+            .filter(|(_, scheme)| matches!(scheme, SecurityScheme::OAuth2 { flows, .. } if flows.implicit.is_some()))
+            .map(|(name, _)| Violation::new(
+                child(&child(&scheme_pointer(name), "flows"), "implicit"),
+                "the implicit flow is deprecated, use the authorization code flow instead",
+            ))
+            .collect()
+    }
+}
+
+/// Scopes containing `*`, which grant more than any one operation needs.
+pub struct WildcardScope;
+
+impl Rule for WildcardScope {
+    fn name(&self) -> &str {
+        "wildcard-scope"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (name, scheme) in schemes(spec) {
+            let SecurityScheme::OAuth2 { flows, .. } = scheme else { continue };
+            let flows = [
+                ("implicit", flows.implicit.as_ref().map(|f| &f.scopes)),
+                ("password", flows.password.as_ref().map(|f| &f.scopes)),
+                ("clientCredentials", flows.client_credentials.as_ref().map(|f| &f.scopes)),
+                ("authorizationCode", flows.authorization_code.as_ref().map(|f| &f.scopes)),
+            ];
+            for (flow, scopes) in IntoIterator::into_iter(flows) {
+                for scope in scopes.into_iter().flat_map(|s| s.keys()).filter(|s| s.contains('*')) {
+                    let pointer = child(&child(&child(&scheme_pointer(name), "flows"), flow), "scopes");
+                    violations.push(Violation::new(child(&pointer, scope), format!("scope {:?} is a wildcard", scope)));
+                }
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::lint::Linter;
+
+    #[test]
+    fn test_security_rules() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "servers": [{"url": "http://api.example.com"}, {"url": "https://api.example.com"}],
+            "paths": {"/pets": {
+                "get": {"security": [{"key": []}], "responses": {}},
+                "post": {"responses": {}},
+                "delete": {"security": [], "responses": {}}
+            }},
+            "components": {"securitySchemes": {
+                "key": {"type": "apiKey", "in": "header", "name": "X-Key"},
+                "basic": {"type": "http", "scheme": "Basic"},
+                "oauth": {"type": "oauth2", "flows": {
+                    "implicit": {"authorizationUrl": "https://a", "scopes": {"read": "", "admin:*": ""}}
+                }}
+            }}
+        })).unwrap();
+        let findings: Vec<String> = Linter::security().run(&spec).iter().map(ToString::to_string).collect();
+        assert_eq!(findings, vec![
+            "/paths/~1pets/post: warning: operation has no security requirements [operation-security]",
+            "/components/securitySchemes/key: error: API key is sent to http://api.example.com, which is not HTTPS [api-key-over-http]",
+            "/components/securitySchemes/basic: warning: basic authentication sends credentials with every request [basic-auth]",
+            "/components/securitySchemes/oauth/flows/implicit: warning: the implicit flow is deprecated, use the authorization code flow instead [oauth-implicit-flow]",
+            r#"/components/securitySchemes/oauth/flows/implicit/scopes/admin:*: warning: scope "admin:*" is a wildcard [wildcard-scope]"#,
+        ]);
+    }
+}
@@ -0,0 +1,280 @@
+//! Rules for consistent, well-documented specs, see [Linter::style](super::Linter::style).
+
+use serde_json::Value;
+
+use super::{Rule, Severity, Violation};
+use crate::dereference::ValueRole;
+use crate::example_validation::child;
+use crate::*;
+
+/// The rules of this module.
+pub fn rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(OperationId),
+        Box::new(NonEmptyDescription),
+        Box::new(DeclaredTags),
+        Box::new(SuccessResponse),
+        Box::new(InlineResponseSchema),
+        Box::new(KebabCasePaths),
+    ]
+}
+
+fn operation_pointer(path: &str, method: &str) -> String {
+    child(&child("/paths", path), method)
+}
+
+/// Operations without an `operationId`, which code generators name methods after.
+pub struct OperationId;
+
+impl Rule for OperationId {
+    fn name(&self) -> &str {
+        "operation-operation-id"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        spec.resolved().operations()
+            .filter(|op| op.operation.operation_id.as_deref().is_none_or(|id| id.trim().is_empty()))
+            .map(|op| Violation::new(operation_pointer(op.path, op.method), "operation has no operationId"))
+            .collect()
+    }
+}
+
+/// `description`s that are empty or only whitespace, anywhere in the spec but in
+/// examples and extensions.
+pub struct NonEmptyDescription;
+
+impl Rule for NonEmptyDescription {
+    fn name(&self) -> &str {
+        "description-non-empty"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Ok(value) = serde_json::to_value(spec) {
+            empty_descriptions("", &value, ValueRole::Object, &mut violations);
+        }
+        violations
+    }
+}
+
+fn empty_descriptions(pointer: &str, value: &Value, role: ValueRole, violations: &mut Vec<Violation>) {
+    match value {
+        Value::Object(map) => {
+            if let (true, Some(Value::String(description))) = (role.is_keywords(), map.get("description")) {
+                if description.trim().is_empty() {
+                    violations.push(Violation::new(child(pointer, "description"), "description is empty"));
+                }
+            }
+            for (key, value) in map {
+                let role = role.child(key);
+                if role != ValueRole::Data {
+                    empty_descriptions(&child(pointer, key), value, role, violations);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                empty_descriptions(&child(pointer, &i.to_string()), item, role.item(), violations);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Tags used by operations but missing from the top-level `tags`, where they
+/// would be described and ordered.
+pub struct DeclaredTags;
+
+impl Rule for DeclaredTags {
+    fn name(&self) -> &str {
+        "operation-tag-defined"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for op in spec.resolved().operations() {
+            for (i, tag) in op.operation.tags.iter().enumerate() {
+                if !spec.tags.iter().any(|declared| &declared.name == tag) {
+                    let pointer = child(&child(&operation_pointer(op.path, op.method), "tags"), &i.to_string());
+                    violations.push(Violation::new(pointer, format!("tag {:?} is not declared", tag)));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Operations without a 2xx response, whether a code or the `2XX` range, so
+/// that nothing describes what they return when they succeed.
+pub struct SuccessResponse;
+
+impl Rule for SuccessResponse {
+    fn name(&self) -> &str {
+        "operation-success-response"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        spec.resolved().operations()
+            .filter(|op| op.operation.responses.success().is_none())
+            .map(|op| Violation::new(
+                child(&operation_pointer(op.path, op.method), "responses"),
+                "operation has no 2xx response",
+            ))
+            .collect()
+    }
+}
+
+/// Object schemas declared inline in responses, rather than as components that
+/// generated clients can name and share.
+pub struct InlineResponseSchema;
+
+impl Rule for InlineResponseSchema {
+    fn name(&self) -> &str {
+        "no-inline-response-schema"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        let mut responses = Vec::new();
+        for op in spec.resolved().operations() {
+            let pointer = child(&operation_pointer(op.path, op.method), "responses");
+            let r = &op.operation.responses;
+            responses.extend(r.default.iter().map(|response| (child(&pointer, "default"), response)));
+            responses.extend(r.responses.iter().map(|(status, response)| (child(&pointer, &status.to_string()), response)));
+        }
+        responses.extend(spec.components.responses.iter().map(|(name, response)| (child("/components/responses", name), response)));
+
+        let mut violations = Vec::new();
+        for (pointer, response) in responses {
+            let Some(response) = response.as_item() else { continue };
+            for (media_type, content) in &response.content {
+                let Some(schema) = content.schema.as_ref().and_then(RefOr::as_item) else { continue };
+                if schema.get_properties().is_some_and(|properties| !properties.is_empty()) {
+                    let pointer = child(&child(&child(&pointer, "content"), media_type), "schema");
+                    violations.push(Violation::new(pointer, "response schema is inline, declare it as a component"));
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Paths with segments other than lowercase words joined by hyphens, e.g.
+/// `/userAccounts` or `/user_accounts` rather than `/user-accounts`. Path
+/// parameters and file extensions are allowed.
+pub struct KebabCasePaths;
+
+impl Rule for KebabCasePaths {
+    fn name(&self) -> &str {
+        "paths-kebab-case"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPI) -> Vec<Violation> {
+        spec.paths.paths.keys()
+            .filter(|path| !path.split('/').all(is_kebab_case))
+            .map(|path| Violation::new(child("/paths", path), format!("path {} is not kebab-case", path)))
+            .collect()
+    }
+}
+
+fn is_kebab_case(segment: &str) -> bool {
+    let mut literal = String::new();
+    let mut rest = segment;
+    while let Some(start) = rest.find('{') {
+        literal.push_str(&rest[..start]);
+        literal.push('-');
+        rest = rest[start..].split_once('}').map_or("", |(_, after)| after);
+    }
+    literal.push_str(rest);
+    literal.split(['-', '.']).all(|word| word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()))
+        && !segment.contains("--")
+        && !segment.starts_with('-')
+        && !segment.ends_with('-')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::lint::Linter;
+
+    #[test]
+    fn test_style_rules() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1", "description": " "},
+            "tags": [{"name": "pets"}],
+            "paths": {
+                "/pets/{petId}.json": {"get": {
+                    "operationId": "getPet",
+                    "tags": ["pets"],
+                    "responses": {"2XX": {"$ref": "#/components/responses/Pet"}}
+                }},
+                "/petOwners": {"post": {
+                    "tags": ["pets", "owners"],
+                    "responses": {
+                        "201": {"description": "", "content": {"application/json": {
+                            "schema": {"type": "object", "properties": {"id": {"type": "string"}}},
+                            "example": {"description": ""}
+                        }}},
+                        "default": {"description": "error"}
+                    }
+                }},
+                "/health": {"get": {"operationId": "health", "responses": {"default": {"description": "ok"}}}}
+            },
+            "components": {"responses": {"Pet": {"description": "a pet", "content": {"application/json": {
+                "schema": {"$ref": "#/components/schemas/Pet"}
+            }}}}}
+        })).unwrap();
+        let findings: Vec<String> = Linter::style().run(&spec).iter().map(ToString::to_string).collect();
+        assert_eq!(findings, vec![
+            "/paths/~1petOwners/post: warning: operation has no operationId [operation-operation-id]",
+            "/info/description: warning: description is empty [description-non-empty]",
+            "/paths/~1petOwners/post/responses/201/description: warning: description is empty [description-non-empty]",
+            r#"/paths/~1petOwners/post/tags/1: warning: tag "owners" is not declared [operation-tag-defined]"#,
+            "/paths/~1health/get/responses: warning: operation has no 2xx response [operation-success-response]",
+            "/paths/~1petOwners/post/responses/201/content/application~1json/schema: info: response schema is inline, declare it as a component [no-inline-response-schema]",
+            "/paths/~1petOwners: warning: path /petOwners is not kebab-case [paths-kebab-case]",
+        ]);
+
+        let findings = Linter::style().severity("paths-kebab-case", Severity::Error).without("description-non-empty").run(&spec);
+        assert_eq!(findings.len(), 5);
+        assert_eq!(findings[4].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_is_kebab_case() {
+        assert!(is_kebab_case("user-accounts"));
+        assert!(is_kebab_case("{userId}"));
+        assert!(is_kebab_case("v2"));
+        assert!(is_kebab_case("openapi.json"));
+        assert!(is_kebab_case(""));
+        assert!(!is_kebab_case("userAccounts"));
+        assert!(!is_kebab_case("user_accounts"));
+        assert!(!is_kebab_case("user--accounts"));
+    }
+}
@@ -6,6 +6,9 @@ use crate::RefOr;
 pub type RefOrMap<T> = RefMap<T>;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "RefOrMap_{T}"))]
 pub struct RefMap<T>(IndexMap<String, RefOr<T>>);
 
 impl<T> RefOrMap<T> {
@@ -0,0 +1,162 @@
+use mime::Mime;
+
+use crate::{Content, MediaType, RequestBody, Response};
+
+/// Typed lookups into a [Content] map, whose keys stay the strings written in the
+/// document so they round-trip unchanged.
+#[cfg_attr(docsrs, doc(cfg(feature = "mime")))]
+pub trait ContentExt {
+    /// The entry that best describes `mime`, with its key as written. Wildcard keys
+    /// match as in HTTP content negotiation, a more specific key winning over
+    /// `text/*`, and that over `*/*`. Keys with parameters match only when `mime`
+    /// has no conflicting value for them, and win ties by how many they share.
+    /// Keys that aren't valid media types are skipped.
+    fn get_media(&self, mime: &Mime) -> Option<(&str, &MediaType)>;
+
+    fn get_media_mut(&mut self, mime: &Mime) -> Option<(&str, &mut MediaType)>;
+}
+
+impl ContentExt for Content {
+    fn get_media(&self, mime: &Mime) -> Option<(&str, &MediaType)> {
+        let index = best_match(self.keys(), mime)?;
+        self.get_index(index).map(|(key, media)| (key.as_str(), media))
+    }
+
+    fn get_media_mut(&mut self, mime: &Mime) -> Option<(&str, &mut MediaType)> {
+        let index = best_match(self.keys(), mime)?;
+        self.get_index_mut(index).map(|(key, media)| (key.as_str(), media))
+    }
+}
+
+impl RequestBody {
+    /// The entry of `content` describing a body sent with this `Content-Type`,
+    /// as [ContentExt::get_media] finds it. `None` if it isn't a valid media type.
+    pub fn content_for(&self, content_type: &str) -> Option<(&str, &MediaType)> {
+        self.content.get_media(&content_type.parse().ok()?)
+    }
+}
+
+impl Response {
+    /// The entry of `content` to send for this `Accept` header. Its media ranges
+    /// are tried from the highest `q` down, skipping `q=0` and invalid ones, and
+    /// the first range any key matches wins. A range matches wildcard keys that
+    /// cover it, as in [ContentExt::get_media], and a wildcard range matches the
+    /// keys it covers, such as `application/json` for `application/*`; the most
+    /// specific such key is picked. An empty header accepts anything.
+    pub fn content_for(&self, accept: &str) -> Option<(&str, &MediaType)> {
+        let accept = if accept.trim().is_empty() { "*/*" } else { accept };
+        let mut ranges: Vec<(Mime, f32)> = accept.split(',')
+            .filter_map(|range| range.trim().parse::<Mime>().ok())
+            .map(|range| {
+                let q = range.get_param("q").and_then(|q| q.as_str().parse().ok()).unwrap_or(1.0);
+                (range, q)
+            })
+            .filter(|(_, q)| *q > 0.0)
+            .collect();
+        // Stable, keeping the header's order among equal weights.
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        ranges.iter().find_map(|(range, _)| {
+            let index = self.content.keys().enumerate()
+                .filter_map(|(i, key)| {
+                    let key: Mime = key.parse().ok()?;
+                    score(&key, range).or_else(|| score(range, &key).map(|(_, shared)| (specificity(&key), shared)))
+                        .map(|score| (score, i))
+                })
+                .max_by(|(a, i), (b, j)| a.cmp(b).then(j.cmp(i)))
+                .map(|(_, i)| i)?;
+            self.content.get_index(index).map(|(key, media)| (key.as_str(), media))
+        })
+    }
+}
+
+fn best_match<'a>(keys: impl Iterator<Item=&'a String>, mime: &Mime) -> Option<usize> {
+    keys.enumerate()
+        .filter_map(|(i, key)| Some((score(&key.parse().ok()?, mime)?, i)))
+        // The first of equally good keys.
+        .max_by(|(a, i), (b, j)| a.cmp(b).then(j.cmp(i)))
+        .map(|(_, i)| i)
+}
+
+/// How well `key` describes `mime`, higher being better, or `None` if it doesn't.
+fn score(key: &Mime, mime: &Mime) -> Option<(u8, usize)> {
+    let covers = if key.type_() == mime::STAR {
+        true
+    } else if key.type_() != mime.type_() {
+        false
+    } else if key.subtype() == mime::STAR {
+        // `application/*+json` covers the `+json` types and `application/json`.
+        key.suffix().is_none_or(|suffix| mime.suffix() == Some(suffix) || mime.subtype() == suffix)
+    } else {
+        key.subtype() == mime.subtype() && key.suffix() == mime.suffix()
+    };
+    if !covers {
+        return None;
+    }
+    let mut shared = 0;
+    for (name, value) in key.params() {
+        match mime.get_param(name) {
+            Some(other) if other == value || (name == mime::CHARSET && other.as_str().eq_ignore_ascii_case(value.as_str())) => shared += 1,
+            Some(_) => return None,
+            None => {}
+        }
+    }
+    Some((specificity(key), shared))
+}
+
+/// How specific a media type or range is: `*/*`, `text/*`, `application/*+json`
+/// and then a concrete type.
+fn specificity(mime: &Mime) -> u8 {
+    if mime.type_() == mime::STAR {
+        0
+    } else if mime.subtype() != mime::STAR {
+        3
+    } else if mime.suffix().is_some() {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(keys: &[&str]) -> Content {
+        keys.iter().map(|k| (k.to_string(), MediaType::default())).collect()
+    }
+
+    fn get<'a>(content: &'a Content, mime: &str) -> Option<&'a str> {
+        content.get_media(&mime.parse().unwrap()).map(|(key, _)| key)
+    }
+
+    #[test]
+    fn test_get_media() {
+        let content = content(&["*/*", "text/*", "Text/Plain; charset=UTF-8", "text/plain; charset=latin1", "application/json", "not a mime"]);
+        assert_eq!(get(&content, "application/json"), Some("application/json"));
+        assert_eq!(get(&content, "application/json; charset=utf-8"), Some("application/json"));
+        assert_eq!(get(&content, "text/plain; charset=utf-8"), Some("Text/Plain; charset=UTF-8"));
+        assert_eq!(get(&content, "text/plain; charset=latin1"), Some("text/plain; charset=latin1"));
+        assert_eq!(get(&content, "text/plain"), Some("Text/Plain; charset=UTF-8"));
+        assert_eq!(get(&content, "text/plain; charset=ascii"), Some("text/*"));
+        assert_eq!(get(&content, "image/png"), Some("*/*"));
+        assert_eq!(get(&self::content(&["application/json"]), "application/problem+json"), None);
+        assert_eq!(get(&self::content(&["application/*", "application/*+json"]), "application/vnd.pet+json"), Some("application/*+json"));
+        assert_eq!(get(&self::content(&["application/*+json"]), "application/xml"), None);
+    }
+
+    #[test]
+    fn test_content_for() {
+        let response = Response { content: content(&["application/xml", "application/vnd.pet+json", "text/*"]), ..Response::default() };
+        let get = |accept: &str| response.content_for(accept).map(|(key, _)| key);
+        assert_eq!(get("application/json, application/xml;q=0.5"), Some("application/xml"));
+        assert_eq!(get("application/*+json"), Some("application/vnd.pet+json"));
+        assert_eq!(get("text/html, application/*;q=0.9"), Some("text/*"));
+        assert_eq!(get("application/*"), Some("application/xml"));
+        assert_eq!(get("image/png, application/xml;q=0"), None);
+        assert_eq!(get(""), Some("application/xml"));
+
+        let body = RequestBody { content: content(&["application/*+json", "multipart/form-data"]), ..RequestBody::default() };
+        assert_eq!(body.content_for("application/merge-patch+json").map(|(key, _)| key), Some("application/*+json"));
+        assert_eq!(body.content_for("not a mime"), None);
+    }
+}
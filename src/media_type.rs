@@ -3,10 +3,13 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaType {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub schema: Option<RefOr<Schema>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub example: Option<serde_json::Value>,
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub examples: IndexMap<String, RefOr<Example>>,
@@ -14,5 +17,68 @@ pub struct MediaType {
     pub encoding: IndexMap<String, Encoding>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+/// The schema of the first JSON entry of `content`, `application/json` or
+/// another `+json` type.
+fn json_schema(content: &Content) -> Option<&RefOr<Schema>> {
+    content.iter().find(|(key, _)| crate::parameter_style::is_json(key)).and_then(|(_, media)| media.schema.as_ref())
+}
+
+/// The schema of the first form entry of `content`, `application/x-www-form-urlencoded`
+/// or `multipart/form-data`.
+fn form_schema(content: &Content) -> Option<&RefOr<Schema>> {
+    content.iter()
+        .find(|(key, _)| {
+            let essence = key.split(';').next().unwrap_or("").trim();
+            essence.eq_ignore_ascii_case("application/x-www-form-urlencoded") || essence.eq_ignore_ascii_case("multipart/form-data")
+        })
+        .and_then(|(_, media)| media.schema.as_ref())
+}
+
+impl RequestBody {
+    /// The schema of the body when sent as JSON, see [Response::json_schema].
+    pub fn json_schema(&self) -> Option<&RefOr<Schema>> {
+        json_schema(&self.content)
+    }
+
+    /// The schema of the body when sent as a form, see [Response::form_schema].
+    pub fn form_schema(&self) -> Option<&RefOr<Schema>> {
+        form_schema(&self.content)
+    }
+}
+
+impl Response {
+    /// The schema of the first of `application/json` and the `+json` types in
+    /// `content`, e.g. `application/problem+json`, if it has one.
+    pub fn json_schema(&self) -> Option<&RefOr<Schema>> {
+        json_schema(&self.content)
+    }
+
+    /// The schema of the first of `application/x-www-form-urlencoded` and
+    /// `multipart/form-data` in `content`, if it has one.
+    pub fn form_schema(&self) -> Option<&RefOr<Schema>> {
+        form_schema(&self.content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_json_and_form_schema() {
+        let body: RequestBody = serde_json::from_value(json!({"content": {
+            "text/plain": {"schema": {"type": "string"}},
+            "application/x-www-form-urlencoded": {"schema": {"$ref": "#/components/schemas/Form"}},
+            "application/problem+json; charset=utf-8": {"schema": {"$ref": "#/components/schemas/Problem"}}
+        }})).unwrap();
+        assert_eq!(body.json_schema().and_then(RefOr::as_ref_str), Some("#/components/schemas/Problem"));
+        assert_eq!(body.form_schema().and_then(RefOr::as_ref_str), Some("#/components/schemas/Form"));
+        assert_eq!(Response::default().json_schema(), None);
+    }
+}
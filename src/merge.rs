@@ -0,0 +1,562 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+use std::mem::take;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::openapi::{check_parameters, inline_for_merge, merge_vec, resolve_for_merge, same_security};
+use crate::*;
+
+/// How [OpenAPI::merge_with] resolves a conflict: a path, operation, component,
+/// tag or extension defined differently in both documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the definition in `self`.
+    KeepSelf,
+    /// Replace it with the definition in `other`.
+    KeepOther,
+    /// Fail the merge.
+    Error,
+    /// Merge both definitions: the operations of path items, or the tags,
+    /// parameters, request body, responses, callbacks and security of
+    /// operations, keeping what `self` defines where those conflict in turn.
+    /// Within merged operations, the content, examples and links of requests
+    /// and responses, and the path items of callbacks, are merged too. Path
+    /// items given by `$ref` are merged as the components they point to, in
+    /// their own document. Only path items and operations can be merged;
+    /// elsewhere this keeps `self`.
+    Merge,
+}
+
+/// The [MergeStrategy] for each section, for [OpenAPI::merge_with]. The default
+/// merges like [OpenAPI::merge]: path items at the same path are merged, and
+/// `self` is kept on any other conflict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// For path items at the same path.
+    pub paths: MergeStrategy,
+    /// For operations for the same method, in merged path items.
+    pub operations: MergeStrategy,
+    /// For webhooks of the same name.
+    pub webhooks: MergeStrategy,
+    /// For components of the same name, e.g. two `Error` schemas.
+    pub components: MergeStrategy,
+    /// For tags of the same name.
+    pub tags: MergeStrategy,
+    /// For `x-` extensions of the document, its info and components, and of
+    /// merged path items and operations.
+    pub extensions: MergeStrategy,
+    /// Prefixes the components of `other` that `self` defines differently with
+    /// this, e.g. `Billing` or `billing.` to merge its `Error` schema as
+    /// `BillingError` or `billing.Error`, and rewrites the references to them in
+    /// `other` to match. The same goes for their discriminator mappings and
+    /// security requirements. Renamed components that still conflict are
+    /// resolved by `components`.
+    pub rename_prefix: Option<String>,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            paths: MergeStrategy::Merge,
+            operations: MergeStrategy::KeepSelf,
+            webhooks: MergeStrategy::KeepSelf,
+            components: MergeStrategy::KeepSelf,
+            tags: MergeStrategy::KeepSelf,
+            extensions: MergeStrategy::KeepSelf,
+            rename_prefix: None,
+        }
+    }
+}
+
+/// The conflicts [OpenAPI::merge_with] resolved: renamed components first, then
+/// the others in document order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub conflicts: Vec<MergeConflict>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// The JSON pointer to the definition in the merged document, e.g.
+    /// `/components/schemas/Error`.
+    pub pointer: String,
+    pub resolution: MergeResolution,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.resolution)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeResolution {
+    KeptSelf,
+    KeptOther,
+    Merged,
+    /// The component of `other` was added under this name, see
+    /// [MergeOptions::rename_prefix].
+    Renamed(String),
+}
+
+impl fmt::Display for MergeResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MergeResolution::KeptSelf => "kept self",
+            MergeResolution::KeptOther => "kept other",
+            MergeResolution::Merged => "merged",
+            MergeResolution::Renamed(name) => return write!(f, "renamed to {}", name),
+        })
+    }
+}
+
+impl OpenAPI {
+    /// Like [OpenAPI::merge_in_place], but resolves conflicts by the strategies
+    /// in `options`, and reports each conflict and how it was resolved.
+    /// Definitions equal in both documents aren't conflicts. Fails with every
+    /// conflict whose strategy is [MergeStrategy::Error], and every pair of path
+    /// items that can't be merged, leaving `self` partially merged.
+    pub fn merge_with(&mut self, mut other: OpenAPI, options: MergeOptions) -> Result<MergeReport, MergeError> {
+        let mut conflicts = Vec::new();
+        if let Some(prefix) = &options.rename_prefix {
+            let names = conflicting_components(&self.components, &other.components);
+            other.transform(&mut Rename { prefix, names, conflicts: &mut conflicts });
+        }
+        let mut merger = Merger {
+            options: options.clone(),
+            conflicts,
+            errors: Vec::new(),
+            references: [references(&self.components), references(&other.components)],
+        };
+        let extensions = options.extensions;
+        merger.map("/info", extensions, &mut self.info.extensions, other.info.extensions, keep);
+
+        merge_vec(&mut self.servers, other.servers, |a, b| a.url == b.url);
+
+        merger.map("/paths", options.paths, &mut self.paths.paths, other.paths.paths, Merger::path_item);
+        merger.map("/webhooks", options.webhooks, &mut self.webhooks, other.webhooks, Merger::path_item);
+
+        let (c, o) = (&mut self.components, other.components);
+        merger.map("/components", extensions, &mut c.extensions, o.extensions, keep);
+        let strategy = options.components;
+        merger.map("/components/schemas", strategy, &mut c.schemas, o.schemas.into(), keep);
+        merger.map("/components/responses", strategy, &mut c.responses, o.responses.into(), keep);
+        merger.map("/components/parameters", strategy, &mut c.parameters, o.parameters.into(), keep);
+        merger.map("/components/examples", strategy, &mut c.examples, o.examples.into(), keep);
+        merger.map("/components/requestBodies", strategy, &mut c.request_bodies, o.request_bodies.into(), keep);
+        merger.map("/components/headers", strategy, &mut c.headers, o.headers.into(), keep);
+        merger.map("/components/securitySchemes", strategy, &mut c.security_schemes, o.security_schemes.into(), keep);
+        merger.map("/components/links", strategy, &mut c.links, o.links.into(), keep);
+        merger.map("/components/callbacks", strategy, &mut c.callbacks, o.callbacks.into(), keep);
+        merger.map("/components/pathItems", strategy, &mut c.path_items, o.path_items.into(), keep);
+
+        merge_vec(&mut self.security, other.security, same_security);
+        for tag in other.tags {
+            match self.tags.iter().position(|t| t.name == tag.name) {
+                Some(i) => merger.resolve(&child("/tags", &i.to_string()), options.tags, &mut self.tags[i], tag, keep),
+                None => self.tags.push(tag),
+            }
+        }
+
+        match self.external_docs.as_mut() {
+            Some(docs) => {
+                if let Some(other) = other.external_docs {
+                    merger.map("/externalDocs", extensions, &mut docs.extensions, other.extensions, keep);
+                }
+            }
+            None => self.external_docs = other.external_docs
+        }
+
+        merger.map("", extensions, &mut self.extensions, other.extensions, keep);
+        if merger.errors.is_empty() {
+            Ok(MergeReport { conflicts: merger.conflicts })
+        } else {
+            Err(MergeError::new(&merger.errors.join("; ")))
+        }
+    }
+}
+
+/// The sections and names of the components both define, differently.
+fn conflicting_components(ours: &Components, theirs: &Components) -> HashSet<(String, String)> {
+    let (Ok(Value::Object(ours)), Ok(Value::Object(theirs))) = (serde_json::to_value(ours), serde_json::to_value(theirs)) else {
+        return HashSet::new();
+    };
+    let mut names = HashSet::new();
+    for (section, components) in theirs.iter().filter(|(section, _)| !section.starts_with("x-")) {
+        for (name, component) in components.as_object().into_iter().flatten() {
+            if ours.get(section).and_then(|s| s.get(name)).is_some_and(|ours| ours != component) {
+                names.insert((section.clone(), name.clone()));
+            }
+        }
+    }
+    names
+}
+
+struct Rename<'a> {
+    prefix: &'a str,
+    names: HashSet<(String, String)>,
+    conflicts: &'a mut Vec<MergeConflict>,
+}
+
+impl Transform for Rename<'_> {
+    fn rename_component(&mut self, section: &str, name: &str) -> Option<String> {
+        if !self.names.contains(&(section.to_string(), name.to_string())) {
+            return None;
+        }
+        let renamed = format!("{}{}", self.prefix, name);
+        let pointer = child(&child("/components", section), name);
+        self.conflicts.push(MergeConflict { pointer, resolution: MergeResolution::Renamed(renamed.clone()) });
+        Some(renamed)
+    }
+}
+
+/// The components that `$ref`s are resolved in while merging: path items and
+/// parameters.
+fn references(components: &Components) -> Components {
+    Components {
+        parameters: components.parameters.clone(),
+        path_items: components.path_items.clone(),
+        ..Components::default()
+    }
+}
+
+/// Merges a conflicting definition from `other` into that of `self`, at a pointer.
+type MergeFn<T> = fn(&mut Merger, &str, &mut T, T) -> MergeResolution;
+
+fn keep<T>(_: &mut Merger, _: &str, _: &mut T, _: T) -> MergeResolution {
+    MergeResolution::KeptSelf
+}
+
+struct Merger {
+    options: MergeOptions,
+    conflicts: Vec<MergeConflict>,
+    errors: Vec<String>,
+    /// Those of `self` and `other`, see [references].
+    references: [Components; 2],
+}
+
+impl Merger {
+    fn resolve<T: PartialEq>(&mut self, pointer: &str, strategy: MergeStrategy, ours: &mut T, theirs: T, merge: MergeFn<T>) {
+        if *ours == theirs {
+            return;
+        }
+        let index = self.conflicts.len();
+        let resolution = match strategy {
+            MergeStrategy::KeepSelf => MergeResolution::KeptSelf,
+            MergeStrategy::KeepOther => {
+                *ours = theirs;
+                MergeResolution::KeptOther
+            }
+            MergeStrategy::Error => return self.errors.push(format!("conflicting definitions of {}", pointer)),
+            MergeStrategy::Merge => {
+                // Recorded before the conflicts within it.
+                self.conflicts.push(MergeConflict { pointer: pointer.to_string(), resolution: MergeResolution::Merged });
+                self.conflicts[index].resolution = merge(self, pointer, ours, theirs);
+                return;
+            }
+        };
+        self.conflicts.push(MergeConflict { pointer: pointer.to_string(), resolution });
+    }
+
+    fn map<K: Hash + Eq + ToString, V: PartialEq>(&mut self, pointer: &str, strategy: MergeStrategy, ours: &mut IndexMap<K, V>, theirs: IndexMap<K, V>, merge: MergeFn<V>) {
+        for (key, value) in theirs {
+            match ours.get_mut(&key) {
+                Some(existing) => self.resolve(&child(pointer, &key.to_string()), strategy, existing, value, merge),
+                None => {
+                    ours.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Keeps what `self` defines, where both do.
+    fn option<T: PartialEq>(&mut self, pointer: &str, ours: &mut Option<T>, theirs: Option<T>) {
+        if ours.is_none() {
+            *ours = theirs;
+        } else if theirs.is_some() && *ours != theirs {
+            self.conflicts.push(MergeConflict { pointer: pointer.to_string(), resolution: MergeResolution::KeptSelf });
+        }
+    }
+
+    fn path_item(&mut self, pointer: &str, ours: &mut RefOr<PathItem>, theirs: RefOr<PathItem>) -> MergeResolution {
+        let [self_refs, other_refs] = &self.references;
+        let theirs = match theirs {
+            RefOr::Item(item) => Ok(item),
+            reference => resolve_for_merge(&reference, other_refs).cloned(),
+        };
+        let items = theirs.and_then(|theirs| Ok((inline_for_merge(ours, self_refs)?, theirs)));
+        let (ours, theirs) = match items {
+            Ok(items) => items,
+            Err(e) => {
+                self.errors.push(e.to_string());
+                return MergeResolution::KeptSelf;
+            }
+        };
+        if let Err(e) = check_parameters(pointer, ours, self_refs, &theirs, other_refs) {
+            self.errors.push(e.to_string());
+            return MergeResolution::KeptSelf;
+        }
+        self.item(pointer, ours, theirs)
+    }
+
+    /// Merges path items of the same path, or callback expression.
+    fn item(&mut self, pointer: &str, ours: &mut PathItem, mut theirs: PathItem) -> MergeResolution {
+        self.option(&child(pointer, "summary"), &mut ours.summary, theirs.summary.take());
+        self.option(&child(pointer, "description"), &mut ours.description, theirs.description.take());
+        merge_vec(&mut ours.servers, take(&mut theirs.servers), |a, b| a.url == b.url);
+        self.map(pointer, self.options.extensions, &mut ours.extensions, take(&mut theirs.extensions), keep);
+        for (method, operation) in theirs {
+            let existing = ours.iter_mut().find(|(m, _)| *m == method).map(|(_, o)| o);
+            match existing {
+                Some(existing) => self.resolve(&child(pointer, &method), self.options.operations, existing, operation, Merger::operation),
+                None => {
+                    ours.insert_operation(&method, operation);
+                }
+            }
+        }
+        MergeResolution::Merged
+    }
+
+    fn operation(&mut self, pointer: &str, ours: &mut Operation, theirs: Operation) -> MergeResolution {
+        merge_vec(&mut ours.tags, theirs.tags, |a, b| a == b);
+        self.option(&child(pointer, "summary"), &mut ours.summary, theirs.summary);
+        self.option(&child(pointer, "description"), &mut ours.description, theirs.description);
+        self.option(&child(pointer, "externalDocs"), &mut ours.external_docs, theirs.external_docs);
+        self.option(&child(pointer, "operationId"), &mut ours.operation_id, theirs.operation_id);
+        for parameter in theirs.parameters {
+            let key = self.parameter_key(1, &parameter);
+            match ours.parameters.iter().position(|p| self.parameter_key(0, p) == key) {
+                Some(i) => {
+                    let pointer = child(&child(pointer, "parameters"), &i.to_string());
+                    self.resolve(&pointer, MergeStrategy::Merge, &mut ours.parameters[i], parameter, Merger::parameter);
+                }
+                None => ours.parameters.push(parameter),
+            }
+        }
+        match (&mut ours.request_body, theirs.request_body) {
+            (Some(ours), Some(theirs)) => self.resolve(&child(pointer, "requestBody"), MergeStrategy::Merge, ours, theirs, Merger::request_body),
+            (ours @ None, theirs) => *ours = theirs,
+            _ => {}
+        }
+        let responses = child(pointer, "responses");
+        match (&mut ours.responses.default, theirs.responses.default) {
+            (Some(ours), Some(theirs)) => self.resolve(&child(&responses, "default"), MergeStrategy::Merge, ours, theirs, Merger::response),
+            (ours @ None, theirs) => *ours = theirs,
+            _ => {}
+        }
+        self.map(&responses, MergeStrategy::Merge, &mut ours.responses.responses, theirs.responses.responses, Merger::response);
+        self.map(&child(pointer, "callbacks"), MergeStrategy::Merge, &mut ours.callbacks, theirs.callbacks, Merger::callback);
+        self.resolve(&child(pointer, "deprecated"), MergeStrategy::KeepSelf, &mut ours.deprecated, theirs.deprecated, keep);
+        match (&mut ours.security, theirs.security) {
+            (Some(ours), Some(theirs)) => merge_vec(ours, theirs, same_security),
+            (ours @ None, theirs) => *ours = theirs,
+            _ => {}
+        }
+        merge_vec(&mut ours.servers, theirs.servers, |a, b| a.url == b.url);
+        self.map(pointer, self.options.extensions, &mut ours.extensions, theirs.extensions, keep);
+        MergeResolution::Merged
+    }
+
+    /// What identifies a parameter of an operation of `self` (0) or `other` (1):
+    /// its location and name, also when given by a `$ref` that resolves, else
+    /// the reference.
+    fn parameter_key<'p>(&'p self, side: usize, parameter: &'p RefOr<Parameter>) -> (&'p str, &'p str) {
+        match parameter.resolve_in_components(&self.references[side]) {
+            Ok(parameter) => (parameter.kind.location(), &parameter.name),
+            Err(_) => ("$ref", parameter.as_ref_str().unwrap_or_default()),
+        }
+    }
+
+    /// Merges the examples of inline parameters, keeping the rest of `self`.
+    fn parameter(&mut self, pointer: &str, ours: &mut RefOr<Parameter>, theirs: RefOr<Parameter>) -> MergeResolution {
+        let (RefOr::Item(ours), RefOr::Item(mut theirs)) = (ours, theirs) else { return MergeResolution::KeptSelf };
+        self.option(&child(pointer, "example"), &mut ours.example, theirs.example.take());
+        self.map(&child(pointer, "examples"), MergeStrategy::KeepSelf, &mut ours.examples, take(&mut theirs.examples), keep);
+        if let (ParameterSchemaOrContent::Content(ours), ParameterSchemaOrContent::Content(theirs)) = (&mut ours.format, theirs.data.format) {
+            self.content(&child(pointer, "content"), ours, theirs);
+        }
+        MergeResolution::Merged
+    }
+
+    fn request_body(&mut self, pointer: &str, ours: &mut RefOr<RequestBody>, theirs: RefOr<RequestBody>) -> MergeResolution {
+        let (RefOr::Item(ours), RefOr::Item(theirs)) = (ours, theirs) else { return MergeResolution::KeptSelf };
+        self.option(&child(pointer, "description"), &mut ours.description, theirs.description);
+        self.content(&child(pointer, "content"), &mut ours.content, theirs.content);
+        self.resolve(&child(pointer, "required"), MergeStrategy::KeepSelf, &mut ours.required, theirs.required, keep);
+        self.map(pointer, self.options.extensions, &mut ours.extensions, theirs.extensions, keep);
+        MergeResolution::Merged
+    }
+
+    fn response(&mut self, pointer: &str, ours: &mut RefOr<Response>, theirs: RefOr<Response>) -> MergeResolution {
+        let (RefOr::Item(ours), RefOr::Item(theirs)) = (ours, theirs) else { return MergeResolution::KeptSelf };
+        self.resolve(&child(pointer, "description"), MergeStrategy::KeepSelf, &mut ours.description, theirs.description, keep);
+        self.map(&child(pointer, "headers"), MergeStrategy::KeepSelf, &mut ours.headers, theirs.headers, keep);
+        self.content(&child(pointer, "content"), &mut ours.content, theirs.content);
+        self.map(&child(pointer, "links"), MergeStrategy::KeepSelf, &mut ours.links, theirs.links, keep);
+        self.map(pointer, self.options.extensions, &mut ours.extensions, theirs.extensions, keep);
+        MergeResolution::Merged
+    }
+
+    fn content(&mut self, pointer: &str, ours: &mut Content, theirs: Content) {
+        self.map(pointer, MergeStrategy::Merge, ours, theirs, Merger::media_type);
+    }
+
+    fn media_type(&mut self, pointer: &str, ours: &mut MediaType, theirs: MediaType) -> MergeResolution {
+        self.option(&child(pointer, "schema"), &mut ours.schema, theirs.schema);
+        self.option(&child(pointer, "example"), &mut ours.example, theirs.example);
+        self.map(&child(pointer, "examples"), MergeStrategy::KeepSelf, &mut ours.examples, theirs.examples, keep);
+        self.map(&child(pointer, "encoding"), MergeStrategy::KeepSelf, &mut ours.encoding, theirs.encoding, keep);
+        self.map(pointer, self.options.extensions, &mut ours.extensions, theirs.extensions, keep);
+        MergeResolution::Merged
+    }
+
+    fn callback(&mut self, pointer: &str, ours: &mut RefOr<Callback>, theirs: RefOr<Callback>) -> MergeResolution {
+        let (RefOr::Item(ours), RefOr::Item(theirs)) = (ours, theirs) else { return MergeResolution::KeptSelf };
+        self.map(pointer, MergeStrategy::Merge, ours, theirs, Merger::item);
+        MergeResolution::Merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn spec(title: &str, error: serde_json::Value, get: serde_json::Value) -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": title, "version": "1"},
+            "paths": {"/pets": {"get": get}},
+            "components": {"schemas": {"Error": error}},
+            "tags": [{"name": "pets", "description": title}]
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_merge_with() {
+        let a = spec("a", json!({"type": "string"}), json!({
+            "tags": ["pets"],
+            "parameters": [{"name": "limit", "in": "query", "schema": {"type": "integer"}}],
+            "responses": {"200": {"description": "a"}}
+        }));
+        let b = spec("b", json!({"type": "object"}), json!({
+            "tags": ["animals"],
+            "parameters": [{"name": "limit", "in": "query", "schema": {"type": "integer"}}, {"name": "kind", "in": "query", "schema": {"type": "string"}}],
+            "responses": {"200": {"description": "b"}, "404": {"description": "b"}}
+        }));
+
+        let mut merged = a.clone();
+        let report = merged.merge_with(b.clone(), MergeOptions {
+            operations: MergeStrategy::Merge,
+            components: MergeStrategy::KeepOther,
+            ..MergeOptions::default()
+        }).unwrap();
+        let conflicts: Vec<String> = report.conflicts.iter().map(ToString::to_string).collect();
+        assert_eq!(conflicts, [
+            "/paths/~1pets: merged",
+            "/paths/~1pets/get: merged",
+            "/paths/~1pets/get/responses/200: merged",
+            "/paths/~1pets/get/responses/200/description: kept self",
+            "/components/schemas/Error: kept other",
+            "/tags/0: kept self",
+        ]);
+        let get = merged.paths["/pets"].as_item().unwrap().get.as_ref().unwrap();
+        assert_eq!(get.tags, ["pets", "animals"]);
+        assert_eq!(get.parameters.len(), 2);
+        assert_eq!(get.responses.responses.len(), 2);
+        assert!(matches!(merged.schemas["Error"].as_item().unwrap().kind, SchemaKind::Type(Type::Object(_))));
+
+        let mut merged = a.clone();
+        let report = merged.merge_with(b.clone(), MergeOptions::default()).unwrap();
+        assert_eq!(report.conflicts[1], MergeConflict { pointer: "/paths/~1pets/get".to_string(), resolution: MergeResolution::KeptSelf });
+        assert_eq!(merged, a.clone().merge(b.clone()).unwrap());
+
+        let error = a.clone().merge_with(b, MergeOptions { components: MergeStrategy::Error, tags: MergeStrategy::Error, ..MergeOptions::default() });
+        assert_eq!(error.unwrap_err().to_string(), "conflicting definitions of /components/schemas/Error; conflicting definitions of /tags/0");
+
+        let mut same = a.clone();
+        assert!(same.merge_with(a.clone(), MergeOptions::default()).unwrap().conflicts.is_empty());
+        assert_eq!(same, a);
+    }
+
+    #[test]
+    fn test_merge_references_and_callbacks() {
+        let a: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.1.0",
+            "info": {"title": "a", "version": "1"},
+            "paths": {"/pets": {"$ref": "#/components/pathItems/Pets"}},
+            "components": {
+                "pathItems": {"Pets": {"post": {
+                    "parameters": [{"$ref": "#/components/parameters/Limit"}],
+                    "requestBody": {"content": {"application/json": {"examples": {"cat": {"value": {"name": "Tom"}}}}}},
+                    "responses": {"201": {"description": "", "links": {"self": {"operationId": "getPet"}}}},
+                    "callbacks": {"onAdopt": {"{$request.body#/url}": {"post": {"responses": {"200": {"description": ""}}}}}}
+                }}},
+                "parameters": {"Limit": {"name": "limit", "in": "query", "schema": {"type": "integer"}}}
+            }
+        })).unwrap();
+        let b: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.1.0",
+            "info": {"title": "b", "version": "1"},
+            "paths": {"/pets": {"post": {
+                "parameters": [{"name": "limit", "in": "query", "schema": {"type": "integer"}, "example": 10}],
+                "requestBody": {"content": {"application/json": {"examples": {"dog": {"value": {"name": "Rex"}}}}}},
+                "responses": {"201": {"description": "", "links": {"owner": {"operationId": "getOwner"}}}},
+                "callbacks": {"onAdopt": {"{$request.body#/url}": {"put": {"responses": {}}}}}
+            }}}
+        })).unwrap();
+
+        let mut merged = a.clone();
+        merged.merge_with(b.clone(), MergeOptions { operations: MergeStrategy::Merge, ..MergeOptions::default() }).unwrap();
+        let post = merged.paths["/pets"].as_item().unwrap().post.as_ref().unwrap();
+        assert_eq!(post.parameters, a.path_items["Pets"].as_item().unwrap().post.as_ref().unwrap().parameters);
+        let examples = &post.request_body.as_ref().unwrap().as_item().unwrap().content["application/json"].examples;
+        assert_eq!(examples.keys().collect::<Vec<_>>(), ["cat", "dog"]);
+        let response = post.responses.responses[&StatusCode::Code(201)].as_item().unwrap();
+        assert_eq!(response.links.keys().collect::<Vec<_>>(), ["self", "owner"]);
+        let callback = post.callbacks["onAdopt"].as_item().unwrap();
+        let item = &callback["{$request.body#/url}"];
+        assert!(item.post.is_some() && item.put.is_some());
+
+        let mut merged = a.clone();
+        merged.merge_in_place(b.clone()).unwrap();
+        assert_eq!(merged.paths["/pets"], RefOr::Item(a.path_items["Pets"].as_item().unwrap().clone()));
+        let mut by_ref = a.clone();
+        by_ref.merge_ref(&b).unwrap();
+        assert_eq!(by_ref, merged);
+        let mut same = a.clone();
+        same.merge_in_place(a.clone()).unwrap();
+        assert_eq!(same, a);
+    }
+
+    #[test]
+    fn test_merge_with_rename_prefix() {
+        let mut a = spec("a", json!({"type": "string"}), json!({"responses": {}}));
+        a.schemas.insert("Money", Schema::new_integer());
+        let mut b: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "b", "version": "1"},
+            "paths": {"/invoices": {"get": {"responses": {"400": {"description": "", "content": {"application/json": {
+                "schema": {"$ref": "#/components/schemas/Error"}
+            }}}}}}},
+            "components": {"schemas": {
+                "Error": {"type": "object", "properties": {"amount": {"$ref": "#/components/schemas/Money"}}}
+            }}
+        })).unwrap();
+        b.schemas.insert("Money", Schema::new_integer());
+
+        let report = a.merge_with(b, MergeOptions { rename_prefix: Some("Billing".to_string()), ..MergeOptions::default() }).unwrap();
+        let conflicts: Vec<String> = report.conflicts.iter().map(ToString::to_string).collect();
+        assert_eq!(conflicts, ["/components/schemas/Error: renamed to BillingError"]);
+        let names: Vec<&str> = a.schemas.keys().map(String::as_str).collect();
+        assert_eq!(names, ["Error", "Money", "BillingError"]);
+        let get = a.paths["/invoices"].as_item().unwrap().get.as_ref().unwrap();
+        let response = get.responses.responses[&StatusCode::Code(400)].as_item().unwrap();
+        assert_eq!(response.content["application/json"].schema.as_ref().unwrap().as_ref_str(), Some("#/components/schemas/BillingError"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+use crate::{OpenAPI, SchemaError};
+
+/// The official JSON Schemas describing OpenAPI documents.
+#[cfg_attr(docsrs, doc(cfg(feature = "meta-schema")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetaSchema {
+    /// `https://spec.openapis.org/oas/3.0/schema/2021-09-28`
+    V3_0,
+    /// `https://spec.openapis.org/oas/3.1/schema/2022-10-07`. It leaves Schema
+    /// Objects to their JSON Schema dialect, so they are only checked to be objects
+    /// or booleans.
+    V3_1,
+}
+
+/// Keywords used by the meta-schemas, to report which one failed.
+const KEYWORDS: &[&str] = &[
+    "$ref", "additionalProperties", "allOf", "anyOf", "const", "contains", "dependentRequired",
+    "dependentSchemas", "else", "enum", "exclusiveMaximum", "exclusiveMinimum", "format", "if", "items",
+    "maxItems", "maxLength", "maxProperties", "maximum", "minItems", "minLength", "minProperties", "minimum",
+    "multipleOf", "not", "oneOf", "pattern", "patternProperties", "prefixItems", "propertyNames", "required",
+    "then", "type", "unevaluatedItems", "unevaluatedProperties", "uniqueItems",
+];
+
+impl MetaSchema {
+    /// The meta-schema for a value of the `openapi` field, e.g. `3.0.3`.
+    pub fn for_version(openapi: &str) -> Option<Self> {
+        if openapi.starts_with("3.0.") {
+            Some(MetaSchema::V3_0)
+        } else if openapi.starts_with("3.1.") {
+            Some(MetaSchema::V3_1)
+        } else {
+            None
+        }
+    }
+
+    /// The schema document itself.
+    pub fn schema(self) -> &'static str {
+        match self {
+            MetaSchema::V3_0 => include_str!("../schemas/openapi-3.0.json"),
+            MetaSchema::V3_1 => include_str!("../schemas/openapi-3.1.json"),
+        }
+    }
+
+    /// Validates a raw document, such as one parsed with `serde_json::from_str::<Value>`.
+    pub fn validate(self, document: &Value) -> Result<(), Vec<SchemaError>> {
+        static V3_0: OnceLock<jsonschema::Validator> = OnceLock::new();
+        static V3_1: OnceLock<jsonschema::Validator> = OnceLock::new();
+        let cell = match self {
+            MetaSchema::V3_0 => &V3_0,
+            MetaSchema::V3_1 => &V3_1,
+        };
+        let validator = cell.get_or_init(|| {
+            let schema: Value = serde_json::from_str(self.schema()).expect("embedded meta-schema is JSON");
+            jsonschema::validator_for(&schema).expect("embedded meta-schema compiles")
+        });
+        let errors: Vec<SchemaError> = validator.iter_errors(document).map(|e| {
+            let schema_path = e.schema_path.to_string();
+            let keyword = schema_path.rsplit('/')
+                .find_map(|segment| KEYWORDS.iter().find(|k| **k == segment))
+                .copied()
+                .unwrap_or("$ref");
+            SchemaError { instance_path: e.instance_path.to_string(), keyword, message: e.to_string() }
+        }).collect();
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Validates a raw document against the official meta-schema for its `openapi` version,
+/// independently of what the model enforces. Unlike
+/// [OpenAPI::validate_against_meta_schema], this sees fields the model drops.
+#[cfg_attr(docsrs, doc(cfg(feature = "meta-schema")))]
+pub fn validate_meta_schema(document: &Value) -> Result<(), Vec<SchemaError>> {
+    let version = document.get("openapi").and_then(Value::as_str).unwrap_or_default();
+    match MetaSchema::for_version(version) {
+        Some(meta) => meta.validate(document),
+        None => Err(vec![SchemaError {
+            instance_path: "/openapi".to_string(),
+            keyword: "pattern",
+            message: format!("no meta-schema for OpenAPI version {:?}", version),
+        }]),
+    }
+}
+
+impl OpenAPI {
+    /// Validates the spec, as serialized, against the official meta-schema for its
+    /// `openapi` version. See [validate_meta_schema] to check a document before parsing.
+    #[cfg_attr(docsrs, doc(cfg(feature = "meta-schema")))]
+    pub fn validate_against_meta_schema(&self) -> Result<(), Vec<SchemaError>> {
+        let document = serde_json::to_value(self).expect("OpenAPI serializes to JSON");
+        validate_meta_schema(&document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_fixture_is_valid() {
+        let spec: OpenAPI = serde_json::from_str(include_str!("../fixtures/xkcd.json")).unwrap();
+        assert_eq!(spec.validate_against_meta_schema(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        let errors = validate_meta_schema(&json!({
+            "openapi": "3.0.3",
+            "info": {"title": 1, "version": "1"},
+            "paths": {"pets": {}}
+        })).unwrap_err();
+        let found: Vec<(&str, &str)> = errors.iter().map(|e| (e.instance_path.as_str(), e.keyword)).collect();
+        assert_eq!(found, vec![("/info/title", "type"), ("/paths", "additionalProperties")]);
+
+        let errors = validate_meta_schema(&json!({"openapi": "3.1.0", "info": {"title": "t"}, "paths": {}})).unwrap_err();
+        assert!(errors.iter().any(|e| e.instance_path == "/info" && e.keyword == "required"));
+
+        let errors = validate_meta_schema(&json!({"openapi": "2.0"})).unwrap_err();
+        assert_eq!(errors[0].instance_path, "/openapi");
+    }
+}
@@ -0,0 +1,409 @@
+use serde_json::Value;
+
+use crate::example_validation::{child, unescape};
+use crate::*;
+
+/// The extension a 3.0 schema keeps `contentMediaType` in, see [Schema::downgrade_to_v3_0].
+const CONTENT_MEDIA_TYPE: &str = "x-content-media-type";
+
+impl OpenAPI {
+    /// Rewrites keywords that only exist in OpenAPI 3.1 into their closest 3.0
+    /// equivalent, everywhere in the document, and sets `openapi` to `3.0.3`.
+    ///
+    /// What 3.0 has no place for is removed, and its JSON pointers are returned:
+    /// `info.summary`, `info.license.identifier`, `jsonSchemaDialect`, `webhooks`
+    /// and `components.pathItems`.
+    /// Paths that `$ref` a component path item get a copy of it first.
+    pub fn downgrade_to_v3_0(&mut self) -> Vec<String> {
+        self.openapi = "3.0.3".to_string();
+        self.visit_schemas_mut(&mut |_: &str, s: &mut Schema| s.downgrade_keywords());
+
+        let path_items = std::mem::take(&mut *self.components.path_items);
+        for item in self.paths.paths.values_mut() {
+            let RefOr::Reference { reference } = item else { continue };
+            let Some(name) = reference.strip_prefix("#/components/pathItems/") else { continue };
+            if let Some(RefOr::Item(component)) = path_items.get(&unescape(name)) {
+                *item = RefOr::Item(component.clone());
+            }
+        }
+        let mut dropped = Vec::new();
+        if self.info.summary.take().is_some() {
+            dropped.push("/info/summary".to_string());
+        }
+        if self.info.license.as_mut().and_then(|license| license.identifier.take()).is_some() {
+            dropped.push("/info/license/identifier".to_string());
+        }
+        if self.json_schema_dialect.take().is_some() {
+            dropped.push("/jsonSchemaDialect".to_string());
+        }
+        dropped.extend(std::mem::take(&mut self.webhooks).keys().map(|name| child("/webhooks", name)));
+        dropped.extend(path_items.keys().map(|name| child("/components/pathItems", name)));
+        dropped
+    }
+
+    /// Rewrites 3.0-style keywords into their 3.1 form, everywhere in the
+    /// document, and sets `openapi` to `3.1.0`.
+    pub fn upgrade_to_v3_1(&mut self) {
+        self.openapi = "3.1.0".to_string();
+        self.visit_schemas_mut(&mut |_: &str, s: &mut Schema| s.upgrade_keywords());
+    }
+}
+
+impl Schema {
+    /// Rewrites 3.1-only keywords in this schema (and its subschemas) into 3.0 form.
+    ///
+    /// - `const` becomes a single-value `enum` (plus `nullable` for `const: null`).
+    /// - numeric `exclusiveMinimum`/`exclusiveMaximum` become `minimum`/`maximum`
+    ///   with the boolean flag.
+    /// - `contentEncoding: base64` becomes `format: byte`, and `contentMediaType`
+    ///   becomes `format: binary`. A `contentMediaType` that doesn't fit a format,
+    ///   like that of base64-encoded content, is kept as the extension
+    ///   `x-content-media-type`.
+    /// - `if`/`then`/`else` becomes `oneOf: [allOf: [if, then], allOf: [not: if, else]]`,
+    ///   combined with the rest of the schema through `allOf`.
+    /// - `unevaluatedProperties` becomes `additionalProperties` on a plain object
+    ///   schema, and is dropped elsewhere, as is `unevaluatedItems`.
+    /// - several `type`s become an `anyOf` with a schema of each type.
+    /// - `examples` becomes `example`, its first value, unless there is one.
+    pub fn downgrade_to_v3_0(&mut self) {
+        self.visit_schemas_mut(&mut |_: &str, s: &mut Schema| s.downgrade_keywords());
+    }
+
+    /// Rewrites 3.0-only keyword forms in this schema (and its subschemas) into 3.1 form.
+    ///
+    /// - boolean `exclusiveMinimum`/`exclusiveMaximum` become the numeric bound.
+    /// - `format: byte` / `format: binary` become `contentEncoding` / `contentMediaType`,
+    ///   and the extension `x-content-media-type` becomes `contentMediaType` again.
+    pub fn upgrade_to_v3_1(&mut self) {
+        self.visit_schemas_mut(&mut |_: &str, s: &mut Schema| s.upgrade_keywords());
+    }
+
+    /// The rewrites of [Schema::downgrade_to_v3_0] of this schema alone.
+    fn downgrade_keywords(&mut self) {
+        self.downgrade_conditional();
+        if let SchemaKind::Any(any) = &mut self.kind {
+            let types: Vec<RefOr<Schema>> = std::mem::take(&mut any.types).into_iter()
+                .map(|typ| RefOr::Item(Schema::new_kind(SchemaKind::Any(AnySchema { typ: Some(typ), ..AnySchema::default() }))))
+                .collect();
+            if !types.is_empty() && any.any_of.is_empty() {
+                any.any_of = types;
+            } else if !types.is_empty() {
+                any.all_of.push(RefOr::Item(Schema::new_any_of(types)));
+            }
+        }
+        let examples = std::mem::take(&mut self.data.examples);
+        if self.data.example.is_none() {
+            self.data.example = examples.into_iter().next();
+        }
+        if let Some(unevaluated) = self.data.unevaluated_properties.take() {
+            if let SchemaKind::Type(Type::Object(o)) = &mut self.kind {
+                o.additional_properties.get_or_insert(unevaluated);
+            }
+        }
+        self.data.unevaluated_items = None;
+        if let Some(value) = self.data.const_value.take() {
+            self.set_single_enum(value);
+        }
+        let media_type = match &mut self.kind {
+            SchemaKind::Type(Type::String(s)) => {
+                s.content_keywords_to_format();
+                s.content_media_type.take()
+            }
+            SchemaKind::Any(a) => {
+                a.content_keywords_to_format();
+                a.content_media_type.take()
+            }
+            _ => None,
+        };
+        if let Some(media_type) = media_type {
+            self.data.extensions.insert(CONTENT_MEDIA_TYPE.to_string(), Value::String(media_type));
+        }
+        self.map_bounds(false);
+    }
+
+    /// The rewrites of [Schema::upgrade_to_v3_1] of this schema alone.
+    fn upgrade_keywords(&mut self) {
+        let media_type = match &self.kind {
+            SchemaKind::Type(Type::String(_)) | SchemaKind::Any(_) => match self.data.extensions.shift_remove(CONTENT_MEDIA_TYPE) {
+                Some(Value::String(media_type)) => Some(media_type),
+                Some(other) => {
+                    self.data.extensions.insert(CONTENT_MEDIA_TYPE.to_string(), other);
+                    None
+                }
+                None => None,
+            },
+            _ => None,
+        };
+        match &mut self.kind {
+            SchemaKind::Type(Type::String(s)) => {
+                s.content_media_type = s.content_media_type.take().or(media_type);
+                s.format_to_content_keywords();
+            }
+            SchemaKind::Any(a) => {
+                a.content_media_type = a.content_media_type.take().or(media_type);
+                a.format_to_content_keywords();
+            }
+            _ => {}
+        }
+        self.map_bounds(true);
+    }
+
+    /// Rewrites `if`/`then`/`else` into an equivalent `oneOf`. The two branches
+    /// are mutually exclusive, since exactly one of `if` and `not: if` holds.
+    fn downgrade_conditional(&mut self) {
+        let Some(condition) = self.data.if_.take() else {
+            self.data.then = None;
+            self.data.else_ = None;
+            return;
+        };
+        let condition = *condition;
+        let mut when_true = vec![condition.clone()];
+        when_true.extend(self.data.then.take().map(|s| *s));
+        let mut when_false = vec![RefOr::Item(Schema::new_kind(SchemaKind::Not { not: condition.boxed() }))];
+        when_false.extend(self.data.else_.take().map(|s| *s));
+        let branches = Schema::new_one_of(vec![
+            RefOr::Item(Schema::new_all_of(when_true)),
+            RefOr::Item(Schema::new_all_of(when_false)),
+        ]);
+        let rest = std::mem::replace(&mut self.kind, SchemaKind::Any(AnySchema::default()));
+        self.kind = match rest {
+            SchemaKind::Any(a) if a == AnySchema::default() => branches.kind,
+            SchemaKind::AllOf { mut all_of } => {
+                all_of.push(RefOr::Item(branches));
+                SchemaKind::AllOf { all_of }
+            }
+            rest => SchemaKind::AllOf {
+                all_of: vec![RefOr::Item(Schema::new_kind(rest)), RefOr::Item(branches)],
+            },
+        };
+    }
+
+    /// Rewrites each `(exclusiveX, X)` keyword pair of a numeric schema into the
+    /// 3.1 form, a numeric `exclusiveX`, when `numeric`, else into the 3.0 form,
+    /// `X` with a boolean `exclusiveX`. Integer bounds stay `i64`, so they keep
+    /// their exact value.
+    fn map_bounds(&mut self, numeric: bool) {
+        match &mut self.kind {
+            SchemaKind::Type(Type::Number(n)) => {
+                rewrite_bound(&mut n.exclusive_minimum, &mut n.minimum, true, numeric);
+                rewrite_bound(&mut n.exclusive_maximum, &mut n.maximum, false, numeric);
+            }
+            SchemaKind::Type(Type::Integer(i)) => {
+                rewrite_bound(&mut i.exclusive_minimum, &mut i.minimum, true, numeric);
+                rewrite_bound(&mut i.exclusive_maximum, &mut i.maximum, false, numeric);
+            }
+            SchemaKind::Any(a) => {
+                for (bound, inclusive, lower) in [
+                    (&mut a.exclusive_minimum, &mut a.minimum, true),
+                    (&mut a.exclusive_maximum, &mut a.maximum, false),
+                ] {
+                    let mut b = bound.unwrap_or_default();
+                    rewrite_bound(&mut b, inclusive, lower, numeric);
+                    *bound = Some(b).filter(|b| !b.is_unset());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces the schema's `enum` with exactly `value`, switching to the
+    /// [AnySchema] form when the typed variant can't hold it.
+    fn set_single_enum(&mut self, value: Value) {
+        if value.is_null() {
+            self.data.nullable = true;
+        }
+        match (&mut self.kind, &value) {
+            (SchemaKind::Type(Type::String(s)), Value::String(v)) => s.enumeration = vec![v.clone()],
+            (SchemaKind::Type(Type::Number(n)), Value::Number(v)) => n.enumeration = vec![v.as_f64()],
+            (SchemaKind::Type(Type::Number(n)), Value::Null) => n.enumeration = vec![None],
+            (SchemaKind::Type(Type::Integer(i)), Value::Number(v)) if v.is_i64() => i.enumeration = vec![v.as_i64()],
+            (SchemaKind::Type(Type::Integer(i)), Value::Null) => i.enumeration = vec![None],
+            (SchemaKind::Any(a), _) => a.enumeration = vec![value],
+            (kind, _) => {
+                let mut any = std::mem::replace(kind, SchemaKind::Any(AnySchema::default())).into_any();
+                any.enumeration = vec![value];
+                *kind = SchemaKind::Any(any);
+            }
+        }
+    }
+}
+
+/// See [Schema::map_bounds]; `lower` for the minimum.
+fn rewrite_bound<T: Copy + PartialOrd>(bound: &mut ExclusiveBound<T>, inclusive: &mut Option<T>, lower: bool, numeric: bool) {
+    match bound.normalize(*inclusive, lower) {
+        Some((v, true)) if numeric => {
+            *inclusive = None;
+            *bound = ExclusiveBound::Value(v);
+        }
+        _ if numeric => {
+            if let ExclusiveBound::Flag(_) = bound {
+                *bound = ExclusiveBound::Flag(false);
+            }
+        }
+        Some((v, exclusive)) => {
+            *inclusive = Some(v);
+            *bound = ExclusiveBound::Flag(exclusive);
+        }
+        None => *bound = ExclusiveBound::Flag(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_downgrade_const() {
+        let mut schema: Schema = serde_json::from_value(json!({"type": "string", "const": "dog"})).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "string", "enum": ["dog"]}));
+
+        let mut schema: Schema = serde_json::from_value(json!({"type": "boolean", "const": true})).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "boolean", "enum": [true]}));
+
+        let mut schema: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {"kind": {"const": null}}
+        })).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({
+            "type": "object",
+            "properties": {"kind": {"nullable": true, "enum": [null]}}
+        }));
+    }
+
+    #[test]
+    fn test_exclusive_bounds_round_trip() {
+        let mut schema: Schema = serde_json::from_value(json!({"type": "number", "exclusiveMinimum": 0, "maximum": 5})).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "number", "minimum": 0.0, "exclusiveMinimum": true, "maximum": 5.0}));
+        schema.upgrade_to_v3_1();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "number", "exclusiveMinimum": 0.0, "maximum": 5.0}));
+
+        let mut schema: Schema = serde_json::from_value(json!({"type": "integer", "maximum": 9, "exclusiveMaximum": true})).unwrap();
+        schema.upgrade_to_v3_1();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "integer", "exclusiveMaximum": 9}));
+
+        // Above 2^53, where a round trip through f64 would round.
+        let mut schema: Schema = serde_json::from_value(json!({"type": "integer", "exclusiveMinimum": 9007199254740993_i64})).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "integer", "minimum": 9007199254740993_i64, "exclusiveMinimum": true}));
+        schema.upgrade_to_v3_1();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "integer", "exclusiveMinimum": 9007199254740993_i64}));
+    }
+
+    #[test]
+    fn test_content_keywords() {
+        let mut schema: Schema = serde_json::from_value(json!({"type": "string", "format": "binary"})).unwrap();
+        schema.upgrade_to_v3_1();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "string", "contentMediaType": "application/octet-stream"}));
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "string", "format": "binary"}));
+
+        let input = json!({"type": "string", "contentEncoding": "base64", "contentMediaType": "image/png"});
+        let mut schema: Schema = serde_json::from_value(input.clone()).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "string", "format": "byte", "x-content-media-type": "image/png"}));
+        schema.upgrade_to_v3_1();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), input);
+    }
+
+    #[test]
+    fn test_downgrade_conditional() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "description": "An address",
+            "type": "object",
+            "if": {"properties": {"country": {"const": "US"}}},
+            "then": {"required": ["zip"]}
+        })).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({
+            "description": "An address",
+            "allOf": [
+                {"type": "object"},
+                {"oneOf": [
+                    {"allOf": [
+                        {"properties": {"country": {"enum": ["US"]}}},
+                        {"required": ["zip"]}
+                    ]},
+                    {"allOf": [
+                        {"not": {"properties": {"country": {"enum": ["US"]}}}}
+                    ]}
+                ]}
+            ]
+        }));
+    }
+
+    #[test]
+    fn test_downgrade_unevaluated() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "unevaluatedProperties": false
+        })).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "additionalProperties": false
+        }));
+
+        let mut schema: Schema = serde_json::from_value(json!({
+            "allOf": [{"$ref": "#/components/schemas/Base"}],
+            "unevaluatedProperties": false
+        })).unwrap();
+        schema.downgrade_to_v3_0();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"allOf": [{"$ref": "#/components/schemas/Base"}]}));
+    }
+
+    #[test]
+    fn test_downgrade_document() {
+        let mut spec = OpenAPI {
+            openapi: "3.1.0".to_string(),
+            ..OpenAPI::default()
+        };
+        let mut schema = Schema::new_integer();
+        schema.const_value = Some(json!(3));
+        spec.schemas.insert("Three", schema);
+        spec.downgrade_to_v3_0();
+        assert_eq!(spec.openapi, "3.0.3");
+        let SchemaKind::Type(Type::Integer(i)) = &spec.schemas.index2("Three").kind else { panic!() };
+        assert_eq!(i.enumeration, vec![Some(3)]);
+    }
+
+    #[test]
+    fn test_downgrade_drops_v3_1_parts() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.1.0",
+            "info": {"title": "t", "summary": "Pets", "license": {"name": "Apache 2.0", "identifier": "Apache-2.0"}, "version": "1"},
+            "jsonSchemaDialect": "https://spec.openapis.org/oas/3.1/dialect/base",
+            "paths": {"/pets": {"$ref": "#/components/pathItems/Pets"}},
+            "webhooks": {"newPet": {"post": {
+                "requestBody": {"content": {"application/json": {"schema": {"type": ["string", "null"]}}}},
+                "responses": {}
+            }}},
+            "components": {"pathItems": {"Pets": {"get": {
+                "parameters": [{"name": "limit", "in": "query", "schema": {"const": 10}}],
+                "responses": {}
+            }}}}
+        })).unwrap();
+        let dropped = spec.downgrade_to_v3_0();
+        assert_eq!(dropped, [
+            "/info/summary",
+            "/info/license/identifier",
+            "/jsonSchemaDialect",
+            "/webhooks/newPet",
+            "/components/pathItems/Pets",
+        ]);
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["paths"]["/pets"]["get"]["parameters"][0]["schema"], json!({"enum": [10]}));
+        for key in ["webhooks", "jsonSchemaDialect", "components"] {
+            assert!(json.get(key).is_none(), "{}", key);
+        }
+        assert!(json["info"].get("summary").is_none());
+        assert_eq!(json["info"]["license"], json!({"name": "Apache 2.0"}));
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::example::declared_example;
+use crate::*;
+
+/// A response to serve for an operation, from [Operation::mock_response].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    pub status: u16,
+    /// The media type of the body, `None` for a response without content.
+    pub content_type: Option<String>,
+    pub body: Option<Value>,
+    /// Values for the declared headers, by name, for [Response::header_map].
+    pub headers: IndexMap<String, Value>,
+}
+
+impl Operation {
+    /// The response a mock server would send for this operation of `spec`, with
+    /// its declared example, else one generated from its schema. With a `status`,
+    /// the response for that code is used, else that for its range, e.g. `4XX`,
+    /// else the `default` one; without, the first success. With a `content_type`,
+    /// the media type it matches is used, `application/*` and `*/*` keys included;
+    /// without, the first. Headers get their examples, or generated values, by
+    /// `options`, whose seed makes them reproducible.
+    pub fn mock_response(&self, spec: &OpenAPI, status: Option<u16>, content_type: Option<&str>, options: &ExampleOptions) -> Result<MockResponse> {
+        let view = spec.resolved();
+        let responses = &self.responses;
+        let (status, response) = match status {
+            Some(status) => responses.for_code(status)
+                .map(|response| (status, response))
+                .ok_or_else(|| anyhow!("No response is declared for status {}", status))?,
+            None => responses.success().or_else(|| responses.responses.first())
+                .map(|(code, response)| (match code { StatusCode::Code(n) => *n, StatusCode::Range(n) => n * 100 }, response))
+                .or_else(|| responses.default.as_ref().map(|response| (200, response)))
+                .ok_or_else(|| anyhow!("The operation declares no responses"))?,
+        };
+        let response = view.response(response)?;
+
+        let media = match content_type {
+            _ if response.content.is_empty() => None,
+            Some(requested) => Some(matching(&response.content, requested)
+                .ok_or_else(|| anyhow!("No content of type {} is declared for status {}", requested, status))?),
+            None => response.content.first().map(|(key, media)| (key.as_str(), media)),
+        };
+        let example = |example: Option<&Value>, examples, schema: Option<&RefOr<Schema>>| {
+            declared_example(spec, example, examples, schema)
+                .or_else(|| Some(view.schema(schema?).ok()?.generate_example(spec, options)))
+        };
+        let body = media.and_then(|(_, media)| example(media.example.as_ref(), &media.examples, media.schema.as_ref()));
+
+        let mut headers = IndexMap::new();
+        for (name, header) in &response.headers {
+            let header = header.resolve(spec)?;
+            let schema = match &header.format {
+                ParameterSchemaOrContent::Schema(schema) => Some(schema),
+                ParameterSchemaOrContent::Content(content) => content.first().and_then(|(_, media)| media.schema.as_ref()),
+            };
+            if let Some(value) = example(header.example.as_ref(), &header.examples, schema) {
+                headers.insert(name.clone(), value);
+            }
+        }
+        Ok(MockResponse {
+            status,
+            content_type: media.map(|(key, _)| key.to_string()),
+            body,
+            headers,
+        })
+    }
+}
+
+/// The media type of `content` for `requested`: the exact key, else a wildcard
+/// key covering it, `type/*` before `*/*`. Parameters are ignored.
+pub(crate) fn matching<'a>(content: &'a Content, requested: &str) -> Option<(&'a str, &'a MediaType)> {
+    let essence = |s: &str| s.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    let requested = essence(requested);
+    let main = requested.split('/').next().unwrap_or("");
+    let candidates = [requested.clone(), format!("{}/*", main), "*/*".to_string()];
+    candidates.iter().find_map(|candidate| {
+        content.iter().find(|(key, _)| essence(key) == *candidate).map(|(key, media)| (key.as_str(), media))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_response() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets/{id}": {"get": {
+                "operationId": "getPet",
+                "responses": {
+                    "404": {"description": "", "content": {"*/*": {"schema": {"type": "string"}}}},
+                    "200": {
+                        "description": "",
+                        "headers": {"X-Rate-Limit": {"schema": {"type": "integer", "minimum": 1}}},
+                        "content": {
+                            "application/json": {"schema": {"$ref": "#/components/schemas/Pet"}, "example": {"name": "Rex"}},
+                            "application/xml": {"schema": {"$ref": "#/components/schemas/Pet"}}
+                        }
+                    },
+                    "5XX": {"description": ""},
+                    "default": {"$ref": "#/components/responses/Error"}
+                }
+            }}},
+            "components": {
+                "schemas": {"Pet": {"type": "object", "properties": {"name": {"type": "string"}}}},
+                "responses": {"Error": {"description": "", "content": {"application/json": {"schema": {"type": "object", "properties": {"code": {"type": "integer"}}}}}}}
+            }
+        })).unwrap();
+        let (op, _) = spec.get_operation("getPet").unwrap();
+        let options = ExampleOptions::default();
+
+        let ok = op.mock_response(&spec, None, None, &options).unwrap();
+        assert_eq!(ok, MockResponse {
+            status: 200,
+            content_type: Some("application/json".to_string()),
+            body: Some(json!({"name": "Rex"})),
+            headers: IndexMap::from([("X-Rate-Limit".to_string(), json!(1))]),
+        });
+        let xml = op.mock_response(&spec, Some(200), Some("application/xml; charset=utf-8"), &options).unwrap();
+        assert_eq!(xml.body, Some(json!({"name": "string"})));
+        assert_eq!(op.mock_response(&spec, Some(404), Some("text/plain"), &options).unwrap().content_type.as_deref(), Some("*/*"));
+        let unavailable = op.mock_response(&spec, Some(503), None, &options).unwrap();
+        assert_eq!((unavailable.status, unavailable.content_type, unavailable.body), (503, None, None));
+        let error = op.mock_response(&spec, Some(400), None, &options).unwrap();
+        assert_eq!(error.body, Some(json!({"code": 0})));
+        assert!(op.mock_response(&spec, None, Some("text/csv"), &options).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+use crate::*;
+use crate::example_validation::unescape;
+
+/// An object of a spec, from [OpenAPI::get_node].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeRef<'a> {
+    OpenAPI(&'a OpenAPI),
+    Info(&'a Info),
+    Server(&'a Server),
+    Paths(&'a Paths),
+    PathItem(&'a PathItem),
+    Operation(&'a Operation),
+    Parameter(&'a Parameter),
+    RequestBody(&'a RequestBody),
+    Responses(&'a Responses),
+    Response(&'a Response),
+    Header(&'a Header),
+    MediaType(&'a MediaType),
+    Encoding(&'a Encoding),
+    Schema(&'a Schema),
+    Example(&'a Example),
+    Link(&'a Link),
+    Callback(&'a Callback),
+    SecurityScheme(&'a SecurityScheme),
+    Components(&'a Components),
+    Tag(&'a Tag),
+    /// The `$ref` of an object given by reference, which isn't followed.
+    Reference(&'a str),
+}
+
+/// An object of a spec, from [OpenAPI::get_node_mut].
+#[derive(Debug, PartialEq)]
+pub enum NodeMut<'a> {
+    OpenAPI(&'a mut OpenAPI),
+    Info(&'a mut Info),
+    Server(&'a mut Server),
+    Paths(&'a mut Paths),
+    PathItem(&'a mut PathItem),
+    Operation(&'a mut Operation),
+    Parameter(&'a mut Parameter),
+    RequestBody(&'a mut RequestBody),
+    Responses(&'a mut Responses),
+    Response(&'a mut Response),
+    Header(&'a mut Header),
+    MediaType(&'a mut MediaType),
+    Encoding(&'a mut Encoding),
+    Schema(&'a mut Schema),
+    Example(&'a mut Example),
+    Link(&'a mut Link),
+    Callback(&'a mut Callback),
+    SecurityScheme(&'a mut SecurityScheme),
+    Components(&'a mut Components),
+    Tag(&'a mut Tag),
+    /// The `$ref` of an object given by reference, which isn't followed.
+    Reference(&'a mut String),
+}
+
+impl OpenAPI {
+    /// The object a JSON pointer like `/paths/~1users~1{id}/get/responses/200`
+    /// points to, or `None` when there is none, or it isn't one of those of
+    /// [NodeRef], e.g. a string or an extension. A leading `#` is allowed. Objects
+    /// given by reference are [NodeRef::Reference]s, so a pointer leading through
+    /// one, rather than to it, finds nothing.
+    pub fn get_node(&self, pointer: &str) -> Option<NodeRef<'_>> {
+        let mut tokens = tokens(pointer)?;
+        let mut node = NodeRef::OpenAPI(self);
+        while let Some(token) = tokens.next() {
+            node = step(node, &token, &mut tokens)?;
+        }
+        Some(node)
+    }
+
+    /// The object a JSON pointer points to, to edit in place, see [OpenAPI::get_node].
+    pub fn get_node_mut(&mut self, pointer: &str) -> Option<NodeMut<'_>> {
+        let mut tokens = tokens(pointer)?;
+        let mut node = NodeMut::OpenAPI(self);
+        while let Some(token) = tokens.next() {
+            node = step_mut(node, &token, &mut tokens)?;
+        }
+        Some(node)
+    }
+}
+
+/// The unescaped tokens of `pointer`, `None` if it isn't a JSON pointer.
+fn tokens(pointer: &str) -> Option<impl Iterator<Item=String> + '_> {
+    let pointer = pointer.strip_prefix('#').unwrap_or(pointer);
+    let rest = match pointer {
+        "" => None,
+        pointer => Some(pointer.strip_prefix('/')?),
+    };
+    Some(rest.into_iter().flat_map(|rest| rest.split('/')).map(unescape))
+}
+
+fn index(tokens: &mut dyn Iterator<Item=String>) -> Option<usize> {
+    tokens.next()?.parse().ok()
+}
+
+/// The node for `$item`, a `RefOr` of the type of `$variant`.
+macro_rules! ref_or {
+    ($node:ident, $item:expr, $variant:ident) => {
+        match $item {
+            RefOr::Item(item) => $node::$variant(item),
+            RefOr::Reference { reference } => $node::Reference(reference),
+        }
+    };
+}
+
+/// Defines `$name`, which steps from a node to the child that `token` names, taking
+/// the tokens of a name or index from `tokens` for children in collections, e.g.
+/// `parameters/0`. The shared and mutable versions differ in the methods borrowing
+/// the children, and in `mut`.
+macro_rules! step {
+    ($name:ident, $node:ident, $get:ident, $iter:ident, $as_ref:ident, $as_deref:ident $(, $mut:tt)?) => {
+        fn $name<'a>(node: $node<'a>, token: &str, tokens: &mut dyn Iterator<Item=String>) -> Option<$node<'a>> {
+            Some(match node {
+                $node::OpenAPI(spec) => match token {
+                    "info" => $node::Info(&$($mut)? spec.info),
+                    "servers" => $node::Server(spec.servers.$get(index(tokens)?)?),
+                    "paths" => $node::Paths(&$($mut)? spec.paths),
+                    "webhooks" => ref_or!($node, spec.webhooks.$get(&tokens.next()?)?, PathItem),
+                    "components" => $node::Components(&$($mut)? spec.components),
+                    "tags" => $node::Tag(spec.tags.$get(index(tokens)?)?),
+                    _ => return None,
+                },
+                $node::Paths(paths) => ref_or!($node, paths.paths.$get(token)?, PathItem),
+                $node::Components(components) => {
+                    let name = tokens.next()?;
+                    match token {
+                        "schemas" => ref_or!($node, components.schemas.$get(&name)?, Schema),
+                        "responses" => ref_or!($node, components.responses.$get(&name)?, Response),
+                        "parameters" => ref_or!($node, components.parameters.$get(&name)?, Parameter),
+                        "examples" => ref_or!($node, components.examples.$get(&name)?, Example),
+                        "requestBodies" => ref_or!($node, components.request_bodies.$get(&name)?, RequestBody),
+                        "headers" => ref_or!($node, components.headers.$get(&name)?, Header),
+                        "securitySchemes" => ref_or!($node, components.security_schemes.$get(&name)?, SecurityScheme),
+                        "links" => ref_or!($node, components.links.$get(&name)?, Link),
+                        "callbacks" => ref_or!($node, components.callbacks.$get(&name)?, Callback),
+                        "pathItems" => ref_or!($node, components.path_items.$get(&name)?, PathItem),
+                        _ => return None,
+                    }
+                }
+                $node::PathItem(item) => match token {
+                    "parameters" => ref_or!($node, item.parameters.$get(index(tokens)?)?, Parameter),
+                    "servers" => $node::Server(item.servers.$get(index(tokens)?)?),
+                    method => $node::Operation(item.$iter().find(|(m, _)| *m == method)?.1),
+                },
+                $node::Callback(callback) => $node::PathItem(callback.$get(token)?),
+                $node::Operation(op) => match token {
+                    "parameters" => ref_or!($node, op.parameters.$get(index(tokens)?)?, Parameter),
+                    "requestBody" => ref_or!($node, op.request_body.$as_ref()?, RequestBody),
+                    "responses" => $node::Responses(&$($mut)? op.responses),
+                    "callbacks" => ref_or!($node, op.callbacks.$get(&tokens.next()?)?, Callback),
+                    "servers" => $node::Server(op.servers.$get(index(tokens)?)?),
+                    _ => return None,
+                },
+                $node::Responses(responses) => match token {
+                    "default" => ref_or!($node, responses.default.$as_ref()?, Response),
+                    status => ref_or!($node, responses.responses.$iter().find(|(s, _)| s.to_string() == status)?.1, Response),
+                },
+                $node::Response(response) => match token {
+                    "headers" => ref_or!($node, response.headers.$get(&tokens.next()?)?, Header),
+                    "content" => $node::MediaType(response.content.$get(&tokens.next()?)?),
+                    "links" => ref_or!($node, response.links.$get(&tokens.next()?)?, Link),
+                    _ => return None,
+                },
+                $node::RequestBody(body) => match token {
+                    "content" => $node::MediaType(body.content.$get(&tokens.next()?)?),
+                    _ => return None,
+                },
+                $node::MediaType(media) => match token {
+                    "schema" => ref_or!($node, media.schema.$as_ref()?, Schema),
+                    "examples" => ref_or!($node, media.examples.$get(&tokens.next()?)?, Example),
+                    "encoding" => $node::Encoding(media.encoding.$get(&tokens.next()?)?),
+                    _ => return None,
+                },
+                $node::Encoding(encoding) => match token {
+                    "headers" => ref_or!($node, encoding.headers.$get(&tokens.next()?)?, Header),
+                    _ => return None,
+                },
+                $node::Parameter(parameter) => match (token, &$($mut)? parameter.data.format) {
+                    ("schema", ParameterSchemaOrContent::Schema(schema)) => ref_or!($node, schema, Schema),
+                    ("content", ParameterSchemaOrContent::Content(content)) => $node::MediaType(content.$get(&tokens.next()?)?),
+                    ("examples", _) => ref_or!($node, parameter.data.examples.$get(&tokens.next()?)?, Example),
+                    _ => return None,
+                },
+                $node::Header(header) => match (token, &$($mut)? header.format) {
+                    ("schema", ParameterSchemaOrContent::Schema(schema)) => ref_or!($node, schema, Schema),
+                    ("content", ParameterSchemaOrContent::Content(content)) => $node::MediaType(content.$get(&tokens.next()?)?),
+                    ("examples", _) => ref_or!($node, header.examples.$get(&tokens.next()?)?, Example),
+                    _ => return None,
+                },
+                $node::Schema(schema) => {
+                    let data = &$($mut)? schema.data;
+                    let nested = match token {
+                        "if" => data.if_.$as_deref(),
+                        "then" => data.then.$as_deref(),
+                        "else" => data.else_.$as_deref(),
+                        "contains" => data.contains.$as_deref(),
+                        "propertyNames" => data.property_names.$as_deref(),
+                        "contentSchema" => data.content_schema.$as_deref(),
+                        "unevaluatedProperties" => match data.unevaluated_properties.$as_ref()? {
+                            AdditionalProperties::Schema(schema) => Some(schema.$as_ref()),
+                            AdditionalProperties::Any(_) => None,
+                        },
+                        "unevaluatedItems" => match data.unevaluated_items.$as_ref()? {
+                            AdditionalProperties::Schema(schema) => Some(schema.$as_ref()),
+                            AdditionalProperties::Any(_) => None,
+                        },
+                        "prefixItems" => data.prefix_items.$get(index(tokens)?),
+                        "$defs" => data.defs.$get(&tokens.next()?),
+                        "patternProperties" => data.pattern_properties.$get(&tokens.next()?),
+                        "dependentSchemas" => data.dependent_schemas.$get(&tokens.next()?),
+                        _ => match (&$($mut)? schema.kind, token) {
+                            (SchemaKind::Type(Type::Object(o)), "properties") => o.properties.$get(&tokens.next()?),
+                            (SchemaKind::Type(Type::Object(o)), "additionalProperties") => match o.additional_properties.$as_ref()? {
+                                AdditionalProperties::Schema(schema) => Some(schema.$as_ref()),
+                                AdditionalProperties::Any(_) => None,
+                            },
+                            (SchemaKind::Type(Type::Array(a)), "items") => a.items.$as_deref(),
+                            (SchemaKind::OneOf { one_of }, "oneOf") => one_of.$get(index(tokens)?),
+                            (SchemaKind::AllOf { all_of }, "allOf") => all_of.$get(index(tokens)?),
+                            (SchemaKind::AnyOf { any_of }, "anyOf") => any_of.$get(index(tokens)?),
+                            (SchemaKind::Not { not }, "not") => Some(not.$as_ref()),
+                            (SchemaKind::Any(a), "properties") => a.properties.$get(&tokens.next()?),
+                            (SchemaKind::Any(a), "additionalProperties") => match a.additional_properties.$as_ref()? {
+                                AdditionalProperties::Schema(schema) => Some(schema.$as_ref()),
+                                AdditionalProperties::Any(_) => None,
+                            },
+                            (SchemaKind::Any(a), "items") => a.items.$as_deref(),
+                            (SchemaKind::Any(a), "oneOf") => a.one_of.$get(index(tokens)?),
+                            (SchemaKind::Any(a), "allOf") => a.all_of.$get(index(tokens)?),
+                            (SchemaKind::Any(a), "anyOf") => a.any_of.$get(index(tokens)?),
+                            (SchemaKind::Any(a), "not") => a.not.$as_deref(),
+                            _ => None,
+                        },
+                    };
+                    ref_or!($node, nested?, Schema)
+                }
+                $node::Info(_)
+                | $node::Server(_)
+                | $node::Example(_)
+                | $node::Link(_)
+                | $node::SecurityScheme(_)
+                | $node::Tag(_)
+                | $node::Reference(_) => return None,
+            })
+        }
+    };
+}
+
+step!(step, NodeRef, get, iter, as_ref, as_deref);
+step!(step_mut, NodeMut, get_mut, iter_mut, as_mut, as_deref_mut, mut);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_get_node() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/users/{id}": {"get": {
+                "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}}],
+                "responses": {"200": {"description": "a user", "content": {"application/json": {
+                    "schema": {"$ref": "#/components/schemas/User"}
+                }}}}
+            }}},
+            "components": {"schemas": {"User": {"type": "object", "properties": {
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }}}}
+        })).unwrap();
+
+        assert!(matches!(spec.get_node(""), Some(NodeRef::OpenAPI(_))));
+        let Some(NodeRef::Response(response)) = spec.get_node("/paths/~1users~1{id}/get/responses/200") else { panic!() };
+        assert_eq!(response.description, "a user");
+        assert!(matches!(spec.get_node("/paths/~1users~1{id}/get/parameters/0"), Some(NodeRef::Parameter(p)) if p.data.name == "id"));
+        assert_eq!(
+            spec.get_node("/paths/~1users~1{id}/get/responses/200/content/application~1json/schema"),
+            Some(NodeRef::Reference("#/components/schemas/User")),
+        );
+        assert!(matches!(spec.get_node("#/components/schemas/User/properties/tags/items"), Some(NodeRef::Schema(_))));
+        assert_eq!(spec.get_node("/paths/~1users~1{id}/post"), None);
+        assert_eq!(spec.get_node("/paths/~1users~1{id}/get/parameters/1"), None);
+        assert_eq!(spec.get_node("/info/title"), None);
+        assert_eq!(spec.get_node("paths"), None);
+
+        let Some(NodeMut::Operation(op)) = spec.get_node_mut("/paths/~1users~1{id}/get") else { panic!() };
+        op.summary = Some("Get a user".to_string());
+        assert_eq!(spec.paths.get_op("/users/{id}", &http::Method::GET).unwrap().summary.as_deref(), Some("Get a user"));
+    }
+}
@@ -0,0 +1,247 @@
+//! OpenID Connect discovery, for tooling that provisions clients from the
+//! `openIdConnectUrl` a spec declares rather than from hand-copied endpoints.
+
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{AuthCodeOAuth2Flow, ImplicitOAuth2Flow, OAuth2Flow, OAuth2Flows, OpenAPI, SecurityScheme};
+
+const WELL_KNOWN: &str = "/.well-known/openid-configuration";
+
+/// An OpenID Provider's discovery document, with the metadata tooling commonly
+/// needs typed and the rest kept in `extensions`.
+#[cfg_attr(docsrs, doc(cfg(feature = "oidc")))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenIdConfiguration {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes_supported: Vec<String>,
+    pub response_types_supported: Vec<String>,
+    /// Defaults to `authorization_code` and `implicit` when absent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grant_types_supported: Vec<String>,
+    #[serde(flatten)]
+    pub extensions: IndexMap<String, Value>,
+}
+
+/// Why a discovery document could not be fetched or isn't valid.
+#[cfg_attr(docsrs, doc(cfg(feature = "oidc")))]
+#[derive(Debug)]
+pub enum DiscoveryError {
+    Http(Box<ureq::Error>),
+    Json(serde_json::Error),
+    /// The document parsed, but breaks a requirement of OpenID Connect Discovery.
+    Invalid(String),
+}
+
+impl fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiscoveryError::Http(e) => write!(f, "{}", e),
+            DiscoveryError::Json(e) => write!(f, "invalid discovery document: {}", e),
+            DiscoveryError::Invalid(msg) => write!(f, "invalid discovery document: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DiscoveryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DiscoveryError::Http(e) => Some(e),
+            DiscoveryError::Json(e) => Some(e),
+            DiscoveryError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for DiscoveryError {
+    fn from(e: ureq::Error) -> Self {
+        DiscoveryError::Http(Box::new(e))
+    }
+}
+
+impl OpenIdConfiguration {
+    /// Fetches and validates the discovery document at `url`.
+    pub fn fetch(url: &str) -> Result<Self, DiscoveryError> {
+        let body = ureq::get(url).call()?.body_mut().read_to_string()?;
+        Self::from_json(url, &body)
+    }
+
+    /// Parses and validates a discovery document retrieved from `url`.
+    pub fn from_json(url: &str, json: &str) -> Result<Self, DiscoveryError> {
+        let config: Self = serde_json::from_str(json).map_err(DiscoveryError::Json)?;
+        config.validate(url)?;
+        Ok(config)
+    }
+
+    /// Checks the document retrieved from `url` against OpenID Connect Discovery:
+    /// its issuer is the URL it was published under, its endpoints are absolute
+    /// URLs, a token endpoint is given unless only the implicit flow is supported,
+    /// and any listed scopes include `openid`.
+    pub fn validate(&self, url: &str) -> Result<(), DiscoveryError> {
+        let invalid = |msg: String| Err(DiscoveryError::Invalid(msg));
+        let expected = url.strip_suffix(WELL_KNOWN).unwrap_or(url);
+        if self.issuer.trim_end_matches('/') != expected.trim_end_matches('/') {
+            return invalid(format!("issuer {:?} does not match {:?}", self.issuer, url));
+        }
+        let endpoints = [
+            ("authorization_endpoint", Some(&self.authorization_endpoint)),
+            ("token_endpoint", self.token_endpoint.as_ref()),
+            ("userinfo_endpoint", self.userinfo_endpoint.as_ref()),
+            ("jwks_uri", Some(&self.jwks_uri)),
+        ];
+        for (name, endpoint) in IntoIterator::into_iter(endpoints) {
+            if let Some(endpoint) = endpoint.filter(|e| !e.starts_with("https://") && !e.starts_with("http://")) {
+                return invalid(format!("{} {:?} is not an absolute URL", name, endpoint));
+            }
+        }
+        if self.response_types_supported.is_empty() {
+            return invalid("response_types_supported is empty".to_string());
+        }
+        if self.token_endpoint.is_none() && self.grant_types().any(|grant| grant != "implicit") {
+            return invalid("token_endpoint is missing".to_string());
+        }
+        if !self.scopes_supported.is_empty() && !self.scopes_supported.iter().any(|s| s == "openid") {
+            return invalid("scopes_supported does not include openid".to_string());
+        }
+        Ok(())
+    }
+
+    fn grant_types(&self) -> impl Iterator<Item=&str> {
+        let defaults = ["authorization_code", "implicit"];
+        let listed = self.grant_types_supported.iter().map(String::as_str);
+        listed.chain(IntoIterator::into_iter(defaults).filter(move |_| self.grant_types_supported.is_empty()))
+    }
+
+    /// The OAuth2 flows the provider supports, with its endpoints and scopes, as
+    /// they would be declared by an `oauth2` security scheme.
+    pub fn flows(&self) -> OAuth2Flows {
+        let scopes: IndexMap<String, String> = self.scopes_supported.iter().map(|s| (s.clone(), String::new())).collect();
+        let mut flows = OAuth2Flows::default();
+        for grant in self.grant_types() {
+            match (grant, &self.token_endpoint) {
+                ("implicit", _) => flows.implicit = Some(ImplicitOAuth2Flow {
+                    authorization_url: self.authorization_endpoint.clone(),
+                    refresh_url: None,
+                    scopes: scopes.clone(),
+                }),
+                ("authorization_code", Some(token_url)) => flows.authorization_code = Some(AuthCodeOAuth2Flow {
+                    authorization_url: self.authorization_endpoint.clone(),
+                    token_url: token_url.clone(),
+                    refresh_url: None,
+                    scopes: scopes.clone(),
+                }),
+                ("client_credentials", Some(token_url)) => flows.client_credentials = Some(OAuth2Flow {
+                    refresh_url: None,
+                    token_url: token_url.clone(),
+                    scopes: scopes.clone(),
+                }),
+                ("password", Some(token_url)) => flows.password = Some(OAuth2Flow {
+                    refresh_url: None,
+                    token_url: token_url.clone(),
+                    scopes: scopes.clone(),
+                }),
+                _ => {}
+            }
+        }
+        flows
+    }
+}
+
+impl SecurityScheme {
+    /// Fetches the discovery document of an `openIdConnect` scheme, `None` for
+    /// other kinds of schemes.
+    #[cfg_attr(docsrs, doc(cfg(feature = "oidc")))]
+    pub fn discover(&self) -> Option<Result<OpenIdConfiguration, DiscoveryError>> {
+        match self {
+            SecurityScheme::OpenIDConnect { open_id_connect_url, .. } => Some(OpenIdConfiguration::fetch(open_id_connect_url)),
+            _ => None,
+        }
+    }
+}
+
+impl OpenAPI {
+    /// Fetches the discovery documents of the `openIdConnect` security schemes,
+    /// by scheme name.
+    #[cfg_attr(docsrs, doc(cfg(feature = "oidc")))]
+    pub fn discover_openid(&self) -> IndexMap<&str, Result<OpenIdConfiguration, DiscoveryError>> {
+        self.security_schemes.iter()
+            .filter_map(|(name, scheme)| Some((name.as_str(), scheme.as_item().and_then(SecurityScheme::discover)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn document(issuer: &str) -> Value {
+        json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+            "scopes_supported": ["openid", "profile"],
+            "response_types_supported": ["code"],
+            "grant_types_supported": ["authorization_code", "client_credentials"],
+            "claims_supported": ["sub"]
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let url = "https://id.example.com/.well-known/openid-configuration";
+        let config = OpenIdConfiguration::from_json(url, &document("https://id.example.com").to_string()).unwrap();
+        assert_eq!(config.extensions["claims_supported"], json!(["sub"]));
+        let flows = config.flows();
+        assert_eq!(flows.authorization_code.unwrap().token_url, "https://id.example.com/token");
+        assert!(flows.client_credentials.is_some());
+        assert!(flows.implicit.is_none());
+
+        let err = OpenIdConfiguration::from_json(url, &document("https://evil.example.com").to_string()).unwrap_err();
+        assert_eq!(err.to_string(), r#"invalid discovery document: issuer "https://evil.example.com" does not match "https://id.example.com/.well-known/openid-configuration""#);
+        let mut missing = document("https://id.example.com");
+        missing["scopes_supported"] = json!(["profile"]);
+        assert!(matches!(OpenIdConfiguration::from_json(url, &missing.to_string()), Err(DiscoveryError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_discover() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let body = document(&issuer).to_string();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+        });
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.1.0",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"securitySchemes": {
+                "oidc": {"type": "openIdConnect", "openIdConnectUrl": format!("{}{}", issuer, WELL_KNOWN)},
+                "tls": {"type": "mutualTLS"}
+            }}
+        })).unwrap();
+        let discovered = spec.discover_openid();
+        server.join().unwrap();
+        assert_eq!(discovered.len(), 1);
+        assert_eq!(discovered["oidc"].as_ref().unwrap().issuer, issuer);
+    }
+}
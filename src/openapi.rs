@@ -2,7 +2,11 @@ use crate::*;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Serialized by hand, so that its schemas take the form of its version, see
+/// `schema_ser.rs`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct OpenAPI {
     /// REQUIRED. This string MUST be the semantic version number of the
     /// OpenAPI Specification version that the OpenAPI document uses.
@@ -13,13 +17,24 @@ pub struct OpenAPI {
     /// REQUIRED. Provides metadata about the API.
     /// The metadata MAY be used by tooling as required.
     pub info: Info,
+    /// The default value for the `$schema` keyword within Schema Objects
+    /// contained within this OAS document. Introduced in OpenAPI 3.1.
+    #[serde(rename = "jsonSchemaDialect", skip_serializing_if = "Option::is_none")]
+    pub json_schema_dialect: Option<String>,
     /// An array of Server Objects, which provide connectivity information to a
     /// target server. If the servers property is not provided, or is an empty
     /// array, the default value would be a Server Object with a url value of /.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub servers: Vec<Server>,
-    /// REQUIRED. The available paths and operations for the API.
+    /// REQUIRED. The available paths and operations for the API. Optional in
+    /// OpenAPI 3.1, where a document may only have webhooks or components.
+    #[serde(default)]
     pub paths: Paths,
+    /// The incoming webhooks that MAY be received as part of this API and that
+    /// the API consumer MAY choose to implement. The key is a unique name for
+    /// the webhook, not a path. Introduced in OpenAPI 3.1.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub webhooks: IndexMap<String, RefOr<PathItem>>,
     /// An element to hold various schemas for the specification.
     #[serde(default, skip_serializing_if = "Components::is_empty")]
     pub components: Components,
@@ -44,6 +59,7 @@ pub struct OpenAPI {
     pub external_docs: Option<ExternalDocumentation>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
@@ -61,13 +77,47 @@ impl std::ops::DerefMut for OpenAPI {
     }
 }
 
+/// Where an operation yielded by [OpenAPI::all_operations] is declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationOrigin<'a> {
+    /// Under `paths`, at this path.
+    Path(&'a str),
+    /// Under `webhooks`, by this name.
+    Webhook(&'a str),
+    /// In the callback `name` of the operation for `method` at `parent`, under
+    /// the runtime `expression` giving the callback's URL.
+    Callback {
+        parent: Box<OperationOrigin<'a>>,
+        method: &'a str,
+        name: &'a str,
+        expression: &'a str,
+    },
+}
+
+impl OperationOrigin<'_> {
+    /// The JSON pointer to the path item holding the operation, e.g.
+    /// `/paths/~1subscribe/post/callbacks/onEvent/{$request.body#~1url}`. For a
+    /// callback given by `$ref`, the pointer goes through the reference.
+    pub fn pointer(&self) -> String {
+        match self {
+            OperationOrigin::Path(path) => child("/paths", path),
+            OperationOrigin::Webhook(name) => child("/webhooks", name),
+            OperationOrigin::Callback { parent, method, name, expression } => {
+                child(&child(&child(&child(&parent.pointer(), method), "callbacks"), name), expression)
+            }
+        }
+    }
+}
+
 impl OpenAPI {
     /// Iterates through all [Operation]s in this API.
     ///
     /// The iterated items are tuples of `(&str, &str, &Operation, &PathItem)` containing
     /// the path, method, and the operation.
     ///
-    /// Path items containing `$ref`s are skipped.
+    /// Path items containing `$ref`s are skipped. The method is the key of the
+    /// operation in its [PathItem], e.g. `get`, see [OpenAPI::operations_with_methods]
+    /// for [http::Method]s.
     pub fn operations(&self) -> impl Iterator<Item=(&str, &str, &Operation, &PathItem)> {
         self.paths
             .iter()
@@ -78,6 +128,76 @@ impl OpenAPI {
             })
     }
 
+    /// Like [OpenAPI::operations], with the [http::Method] of each operation, as
+    /// [PathItem::iter_methods] gives it.
+    pub fn operations_with_methods(&self) -> impl Iterator<Item=(&str, http::Method, &Operation, &PathItem)> {
+        self.paths
+            .iter()
+            .filter_map(|(path, item)| item.as_item().map(|i| (path, i)))
+            .flat_map(|(path, item)| {
+                item.iter_methods()
+                    .map(move |(method, op)| (path.as_str(), method, op, item))
+            })
+    }
+
+    /// Consumes the API into its [Operation]s, with path and [Method], without
+    /// cloning them. See [Paths::into_operations].
+    pub fn into_operations(self) -> impl Iterator<Item=(String, http::Method, Operation)> {
+        self.paths.into_operations()
+    }
+
+    /// Like [OpenAPI::operations], as a rayon `ParallelIterator`, for expensive
+    /// passes over every operation of large specs, such as validating or
+    /// dereferencing them. Collecting keeps document order.
+    #[cfg(feature = "rayon")]
+    pub fn par_operations(&self) -> impl rayon::iter::IndexedParallelIterator<Item=(&str, &str, &Operation, &PathItem)> {
+        use rayon::prelude::*;
+        self.operations().collect::<Vec<_>>().into_par_iter()
+    }
+
+    /// Iterates through every [Operation] in this API, like [OpenAPI::operations],
+    /// but also those of webhooks and of callbacks, however deeply nested, with
+    /// where each is declared. Callbacks given by `$ref` are resolved, once per
+    /// nesting chain, so recursive callbacks end. Each operation is followed by
+    /// those of its callbacks.
+    pub fn all_operations(&self) -> impl Iterator<Item=(OperationOrigin<'_>, &str, &Operation, &PathItem)> {
+        let mut found = Vec::new();
+        for (path, item) in self.paths.iter().filter_map(|(path, item)| Some((path, item.as_item()?))) {
+            self.collect_operations(OperationOrigin::Path(path), item, &mut Vec::new(), &mut found);
+        }
+        for (name, item) in self.webhooks.iter().filter_map(|(name, item)| Some((name, item.as_item()?))) {
+            self.collect_operations(OperationOrigin::Webhook(name), item, &mut Vec::new(), &mut found);
+        }
+        found.into_iter()
+    }
+
+    fn collect_operations<'a>(
+        &'a self,
+        origin: OperationOrigin<'a>,
+        item: &'a PathItem,
+        references: &mut Vec<&'a str>,
+        found: &mut Vec<(OperationOrigin<'a>, &'a str, &'a Operation, &'a PathItem)>,
+    ) {
+        for (method, operation) in item.iter() {
+            found.push((origin.clone(), method, operation, item));
+            for (name, callback) in &operation.callbacks {
+                let reference = callback.as_ref_str();
+                if reference.is_some_and(|r| references.contains(&r)) {
+                    continue;
+                }
+                let Ok(resolved) = callback.try_resolve(self) else { continue };
+                references.extend(reference);
+                for (expression, item) in resolved {
+                    let parent = Box::new(origin.clone());
+                    self.collect_operations(OperationOrigin::Callback { parent, method, name, expression }, item, references, found);
+                }
+                if reference.is_some() {
+                    references.pop();
+                }
+            }
+        }
+    }
+
     pub fn operations_mut(&mut self) -> impl Iterator<Item=(&str, &str, &mut Operation)> {
         self.paths
             .iter_mut()
@@ -90,27 +210,73 @@ impl OpenAPI {
 
     pub fn get_operation_mut(&mut self, operation_id: &str) -> Option<&mut Operation> {
         self.operations_mut()
-            .find(|(_, _, op)| op.operation_id.as_ref().unwrap() == operation_id)
+            .find(|(_, _, op)| op.operation_id.as_deref() == Some(operation_id))
             .map(|(_, _, op)| op)
     }
 
+    /// Finds an operation by `operationId`, scanning every operation. To look up
+    /// many operations, build an [OperationIndex] with [OpenAPI::index] instead.
     pub fn get_operation(&self, operation_id: &str) -> Option<(&Operation, &PathItem)> {
         self.operations()
-            .find(|(_, _, op, _)| op.operation_id.as_ref().unwrap() == operation_id)
+            .find(|(_, _, op, _)| op.operation_id.as_deref() == Some(operation_id))
             .map(|(_, _, op, item)| (op, item))
     }
 
+    /// Adds `parameter` to every operation for which `selector`, given its path,
+    /// method and itself, returns true, e.g. an `X-Request-Id` header shared
+    /// through a `$ref`. Operations that already have a parameter with its name
+    /// and location, their own or their path item's, are left as they are.
+    /// Returns how many operations it was added to.
+    pub fn inject_parameter(&mut self, selector: impl Fn(&str, &str, &Operation) -> bool, parameter: RefOr<Parameter>) -> anyhow::Result<usize> {
+        let (name, kind) = {
+            let p = self.resolved().parameter(&parameter)?;
+            (p.name.clone(), std::mem::discriminant(&p.kind))
+        };
+        let mut targets = Vec::new();
+        for (path, method, operation, item) in self.operations() {
+            if !selector(path, method, operation) {
+                continue;
+            }
+            let present = operation.effective_parameters(item, self)?.iter().any(|p| p.name == name && std::mem::discriminant(&p.kind) == kind);
+            if !present {
+                targets.push((path.to_string(), method.to_string()));
+            }
+        }
+        for (path, method, operation) in self.operations_mut() {
+            if targets.iter().any(|(p, m)| p == path && m == method) {
+                operation.parameters.push(parameter.clone());
+            }
+        }
+        Ok(targets.len())
+    }
+
     /// Merge another OpenAPI document into this one, keeping original schemas on conflict.
     /// `a.merge(b)` will have all schemas from `a` and `b`, but keep `a` for any duplicates.
+    /// See [OpenAPI::merge_with] to resolve conflicts otherwise.
     pub fn merge(mut self, other: OpenAPI) -> Result<Self, MergeError> {
+        self.merge_in_place(other)?;
+        Ok(self)
+    }
+
+    /// Like [OpenAPI::merge], but merges into `self` where it is, which avoids moving
+    /// the accumulated spec around when composing many documents. On error `self`
+    /// is left partially merged.
+    pub fn merge_in_place(&mut self, other: OpenAPI) -> Result<(), MergeError> {
         merge_map(&mut self.info.extensions, other.info.extensions);
 
         merge_vec(&mut self.servers, other.servers, |a, b| a.url == b.url);
 
         for (path, item) in other.paths {
-            let item = item.into_item().ok_or_else(|| MergeError::new("PathItem references are not yet supported. Please opena n issue if you need this feature."))?;
-            if self.paths.paths.contains_key(&path) {
-                let self_item = self.paths.paths.get_mut(&path).unwrap().as_mut().ok_or_else(|| MergeError::new("PathItem references are not yet supported. Please open an issue if you need this feature."))?;
+            if let Some(self_item) = self.paths.paths.get_mut(&path) {
+                if *self_item == item {
+                    continue;
+                }
+                let item = match item {
+                    RefOr::Item(item) => item,
+                    reference => resolve_for_merge(&reference, &other.components)?.clone(),
+                };
+                let self_item = inline_for_merge(self_item, &self.components)?;
+                check_parameters(&path, self_item, &self.components, &item, &other.components)?;
                 option_or(&mut self_item.get, item.get);
                 option_or(&mut self_item.put, item.put);
                 option_or(&mut self_item.post, item.post);
@@ -119,25 +285,17 @@ impl OpenAPI {
                 option_or(&mut self_item.head, item.head);
                 option_or(&mut self_item.patch, item.patch);
                 option_or(&mut self_item.trace, item.trace);
+                merge_map(&mut self_item.additional_operations, item.additional_operations);
 
                 merge_vec(&mut self_item.servers, item.servers, |a, b| a.url == b.url);
                 merge_map(&mut self_item.extensions, item.extensions);
-
-                if self_item.parameters.len() != item.parameters.len() {
-                    return Err(MergeError(format!("PathItem {} parameters do not have the same length", path)));
-                }
-                for (a, b) in self_item.parameters.iter_mut().zip(item.parameters) {
-                    let a = a.as_item().ok_or_else(|| MergeError::new("Parameter references are not yet supported. Please open an issue if you need this feature."))?;
-                    let b = b.as_item().ok_or_else(|| MergeError::new("Parameter references are not yet supported. Please open an issue if you need this feature."))?;
-                    if a.name != b.name {
-                        return Err(MergeError(format!("PathItem {} parameter {} does not have the same name as {}", path, a.name, b.name)));
-                    }
-                }
             } else {
-                self.paths.paths.insert(path, RefOr::Item(item));
+                self.paths.paths.insert(path, item);
             }
         }
 
+        merge_map(&mut self.webhooks, other.webhooks);
+
         merge_map(&mut self.components.extensions, other.components.extensions);
         merge_map(&mut self.components.schemas, other.components.schemas.into());
         merge_map(&mut self.components.responses, other.components.responses.into());
@@ -148,13 +306,9 @@ impl OpenAPI {
         merge_map(&mut self.components.security_schemes, other.components.security_schemes.into());
         merge_map(&mut self.components.links, other.components.links.into());
         merge_map(&mut self.components.callbacks, other.components.callbacks.into());
+        merge_map(&mut self.components.path_items, other.components.path_items.into());
 
-        merge_vec(&mut self.security, other.security, |a, b| {
-            if a.len() != b.len() {
-                return false;
-            }
-            a.iter().all(|(a, _)| b.contains_key(a))
-        });
+        merge_vec(&mut self.security, other.security, same_security);
         merge_vec(&mut self.tags, other.tags, |a, b| a.name == b.name);
 
         match self.external_docs.as_mut() {
@@ -167,7 +321,70 @@ impl OpenAPI {
         }
 
         merge_map(&mut self.extensions, other.extensions);
-        Ok(self)
+        Ok(())
+    }
+
+    /// Like [OpenAPI::merge_in_place], but borrows `other` and clones only what is
+    /// added to `self`, for merging a document that is reused afterwards.
+    pub fn merge_ref(&mut self, other: &OpenAPI) -> Result<(), MergeError> {
+        merge_map_ref(&mut self.info.extensions, &other.info.extensions);
+
+        merge_vec_ref(&mut self.servers, &other.servers, |a, b| a.url == b.url);
+
+        for (path, item) in other.paths.iter() {
+            if let Some(self_item) = self.paths.paths.get_mut(path) {
+                if self_item == item {
+                    continue;
+                }
+                let item = resolve_for_merge(item, &other.components)?;
+                let self_item = inline_for_merge(self_item, &self.components)?;
+                check_parameters(path, self_item, &self.components, item, &other.components)?;
+                option_or_ref(&mut self_item.get, &item.get);
+                option_or_ref(&mut self_item.put, &item.put);
+                option_or_ref(&mut self_item.post, &item.post);
+                option_or_ref(&mut self_item.delete, &item.delete);
+                option_or_ref(&mut self_item.options, &item.options);
+                option_or_ref(&mut self_item.head, &item.head);
+                option_or_ref(&mut self_item.patch, &item.patch);
+                option_or_ref(&mut self_item.trace, &item.trace);
+                merge_map_ref(&mut self_item.additional_operations, &item.additional_operations);
+
+                merge_vec_ref(&mut self_item.servers, &item.servers, |a, b| a.url == b.url);
+                merge_map_ref(&mut self_item.extensions, &item.extensions);
+            } else {
+                self.paths.paths.insert(path.clone(), item.clone());
+            }
+        }
+
+        merge_map_ref(&mut self.webhooks, &other.webhooks);
+
+        let (components, other_components) = (&mut self.components, &other.components);
+        merge_map_ref(&mut components.extensions, &other_components.extensions);
+        merge_map_ref(&mut components.schemas, &other_components.schemas);
+        merge_map_ref(&mut components.responses, &other_components.responses);
+        merge_map_ref(&mut components.parameters, &other_components.parameters);
+        merge_map_ref(&mut components.examples, &other_components.examples);
+        merge_map_ref(&mut components.request_bodies, &other_components.request_bodies);
+        merge_map_ref(&mut components.headers, &other_components.headers);
+        merge_map_ref(&mut components.security_schemes, &other_components.security_schemes);
+        merge_map_ref(&mut components.links, &other_components.links);
+        merge_map_ref(&mut components.callbacks, &other_components.callbacks);
+        merge_map_ref(&mut components.path_items, &other_components.path_items);
+
+        merge_vec_ref(&mut self.security, &other.security, same_security);
+        merge_vec_ref(&mut self.tags, &other.tags, |a, b| a.name == b.name);
+
+        match self.external_docs.as_mut() {
+            Some(ext) => {
+                if let Some(other) = &other.external_docs {
+                    merge_map_ref(&mut ext.extensions, &other.extensions)
+                }
+            }
+            None => self.external_docs = other.external_docs.clone()
+        }
+
+        merge_map_ref(&mut self.extensions, &other.extensions);
+        Ok(())
     }
 
     /// Merge another OpenAPI document into this one, replacing any duplicate schemas.
@@ -177,14 +394,54 @@ impl OpenAPI {
     }
 }
 
+impl Serialize for OpenAPI {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        crate::schema_ser::with_version(self.openapi.starts_with("3.1"), || {
+            let mut map = serializer.serialize_map(None)?;
+            map.serialize_entry("openapi", &self.openapi)?;
+            map.serialize_entry("info", &self.info)?;
+            if let Some(dialect) = &self.json_schema_dialect {
+                map.serialize_entry("jsonSchemaDialect", dialect)?;
+            }
+            if !self.servers.is_empty() {
+                map.serialize_entry("servers", &self.servers)?;
+            }
+            map.serialize_entry("paths", &self.paths)?;
+            if !self.webhooks.is_empty() {
+                map.serialize_entry("webhooks", &self.webhooks)?;
+            }
+            if !self.components.is_empty() {
+                map.serialize_entry("components", &self.components)?;
+            }
+            if !self.security.is_empty() {
+                map.serialize_entry("security", &self.security)?;
+            }
+            if !self.tags.is_empty() {
+                map.serialize_entry("tags", &self.tags)?;
+            }
+            if let Some(external_docs) = &self.external_docs {
+                map.serialize_entry("externalDocs", external_docs)?;
+            }
+            for (key, value) in &self.extensions {
+                map.serialize_entry(key, value)?;
+            }
+            map.end()
+        })
+    }
+}
+
 impl Default for OpenAPI {
     fn default() -> Self {
-        // 3.1 is a backwards incompatible change that we don't support yet.
+        // 3.0 remains the default, as the version most tools support.
         OpenAPI {
             openapi: "3.0.3".to_string(),
             info: default(),
+            json_schema_dialect: default(),
             servers: default(),
             paths: default(),
+            webhooks: default(),
             components: default(),
             security: default(),
             tags: default(),
@@ -194,7 +451,7 @@ impl Default for OpenAPI {
     }
 }
 
-fn merge_vec<T>(original: &mut Vec<T>, mut other: Vec<T>, cmp: fn(&T, &T) -> bool) {
+pub(crate) fn merge_vec<T>(original: &mut Vec<T>, mut other: Vec<T>, cmp: fn(&T, &T) -> bool) {
     other.retain(|o| !original.iter().any(|r| cmp(o, r)));
     original.extend(other);
 }
@@ -210,6 +467,63 @@ fn option_or<T>(original: &mut Option<T>, other: Option<T>) {
     }
 }
 
+fn merge_vec_ref<T: Clone>(original: &mut Vec<T>, other: &[T], cmp: fn(&T, &T) -> bool) {
+    let added: Vec<T> = other.iter().filter(|o| !original.iter().any(|r| cmp(o, r))).cloned().collect();
+    original.extend(added);
+}
+
+fn merge_map_ref<K, V>(original: &mut IndexMap<K, V>, other: &IndexMap<K, V>) where K: Eq + std::hash::Hash + Clone, V: Clone {
+    for (k, v) in other {
+        if !original.contains_key(k) {
+            original.insert(k.clone(), v.clone());
+        }
+    }
+}
+
+fn option_or_ref<T: Clone>(original: &mut Option<T>, other: &Option<T>) {
+    if original.is_none() {
+        *original = other.clone();
+    }
+}
+
+pub(crate) fn same_security(a: &SecurityRequirement, b: &SecurityRequirement) -> bool {
+    a.len() == b.len() && a.iter().all(|(a, _)| b.contains_key(a))
+}
+
+/// The item, or the component of `components` its `$ref` points to, for merging
+/// with an item of another document.
+pub(crate) fn resolve_for_merge<'a, T: Component>(item: &'a RefOr<T>, components: &'a Components) -> Result<&'a T, MergeError> {
+    item.resolve_in_components(components).map_err(|e| MergeError(format!("{} while merging", e)))
+}
+
+/// Replaces a `$ref` to a path item by a copy of the component, to merge the
+/// operations of another document into.
+pub(crate) fn inline_for_merge<'a>(item: &'a mut RefOr<PathItem>, components: &Components) -> Result<&'a mut PathItem, MergeError> {
+    if let RefOr::Reference { .. } = item {
+        *item = RefOr::Item(resolve_for_merge(item, components)?.clone());
+    }
+    Ok(item.as_mut().expect("inlined above"))
+}
+
+/// Path items can only be merged when they declare the same parameters, by
+/// reference or inline, each in the components of its document.
+pub(crate) fn check_parameters(path: &str, a: &PathItem, a_components: &Components, b: &PathItem, b_components: &Components) -> Result<(), MergeError> {
+    if a.parameters.len() != b.parameters.len() {
+        return Err(MergeError(format!("PathItem {} parameters do not have the same length", path)));
+    }
+    for (a, b) in a.parameters.iter().zip(b.parameters.iter()) {
+        if a == b {
+            continue;
+        }
+        let a = resolve_for_merge(a, a_components)?;
+        let b = resolve_for_merge(b, b_components)?;
+        if a.name != b.name {
+            return Err(MergeError(format!("PathItem {} parameter {} does not have the same name as {}", path, a.name, b.name)));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct MergeError(String);
 
@@ -247,4 +561,154 @@ mod tests {
         a = a.merge(b).unwrap();
         assert_eq!(a.servers.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merge_in_place_and_ref() {
+        let mut a = OpenAPI::default();
+        a.schemas.insert("Pet", Schema::new_string());
+        a.paths.insert_operation("/pets".to_string(), http::Method::GET, Operation::default()).unwrap();
+        let mut b = OpenAPI::default();
+        b.schemas.insert("Pet", Schema::new_integer());
+        b.schemas.insert("User", Schema::new_object());
+        b.paths.insert_operation("/pets".to_string(), http::Method::POST, Operation::default()).unwrap();
+        b.paths.insert_operation("/users".to_string(), http::Method::GET, Operation::default()).unwrap();
+
+        let merged = a.clone().merge(b.clone()).unwrap();
+        let mut by_ref = a.clone();
+        by_ref.merge_ref(&b).unwrap();
+        assert_eq!(by_ref, merged);
+        a.merge_in_place(b).unwrap();
+        assert_eq!(a, merged);
+        assert!(matches!(a.schemas["Pet"].as_item().unwrap().kind, SchemaKind::Type(Type::String(_))));
+        assert_eq!(a.operations().count(), 3);
+    }
+
+    #[test]
+    fn test_all_operations() {
+        let spec: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.1.0",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/subscribe": {"post": {
+                "responses": {},
+                "callbacks": {
+                    "onEvent": {"{$request.body#/url}": {"post": {"operationId": "event", "responses": {}}}},
+                    "onPing": {"$ref": "#/components/callbacks/Ping"}
+                }
+            }}},
+            "webhooks": {"newPet": {"post": {"operationId": "newPet", "responses": {}}}},
+            "components": {"callbacks": {"Ping": {"{$request.body#/ping}": {"get": {
+                "operationId": "ping",
+                "responses": {},
+                "callbacks": {"again": {"$ref": "#/components/callbacks/Ping"}}
+            }}}}}
+        })).unwrap();
+        assert_eq!(spec.operations().count(), 1);
+        let (path, method, ..) = spec.operations_with_methods().next().unwrap();
+        assert_eq!((path, method), ("/subscribe", http::Method::POST));
+
+        let all: Vec<(String, &str, Option<&str>)> = spec.all_operations()
+            .map(|(origin, method, operation, _)| (origin.pointer(), method, operation.operation_id.as_deref()))
+            .collect();
+        assert_eq!(all, vec![
+            ("/paths/~1subscribe".to_string(), "post", None),
+            ("/paths/~1subscribe/post/callbacks/onEvent/{$request.body#~1url}".to_string(), "post", Some("event")),
+            ("/paths/~1subscribe/post/callbacks/onPing/{$request.body#~1ping}".to_string(), "get", Some("ping")),
+            ("/webhooks/newPet".to_string(), "post", Some("newPet")),
+        ]);
+        let (origin, ..) = spec.all_operations().nth(1).unwrap();
+        assert_eq!(origin, OperationOrigin::Callback {
+            parent: Box::new(OperationOrigin::Path("/subscribe")),
+            method: "post",
+            name: "onEvent",
+            expression: "{$request.body#/url}",
+        });
+    }
+
+    #[test]
+    fn test_into_operations() {
+        let spec: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.1.0",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/a": {"get": {"operationId": "a", "responses": {}}, "post": {"operationId": "b", "responses": {}}},
+                "/ref": {"$ref": "#/components/pathItems/Ref"},
+                "/c": {"additionalOperations": {"QUERY": {"operationId": "c", "responses": {}}}}
+            }
+        })).unwrap();
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            let ids: Vec<_> = spec.par_operations().map(|(_, _, op, _)| op.operation_id.clone().unwrap()).collect();
+            assert_eq!(ids, ["a", "b", "c"]);
+        }
+        let operations: Vec<_> = spec.into_operations()
+            .map(|(path, method, op)| (path, method, op.operation_id.unwrap()))
+            .collect();
+        assert_eq!(operations, vec![
+            ("/a".to_string(), http::Method::GET, "a".to_string()),
+            ("/a".to_string(), http::Method::POST, "b".to_string()),
+            ("/c".to_string(), http::Method::from_bytes(b"QUERY").unwrap(), "c".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_inject_parameter() {
+        let mut spec: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets": {
+                    "get": {"responses": {}},
+                    "post": {"parameters": [{"name": "X-Request-Id", "in": "header", "schema": {"type": "string"}}], "responses": {}}
+                },
+                "/users": {
+                    "parameters": [{"$ref": "#/components/parameters/RequestId"}],
+                    "get": {"responses": {}}
+                },
+                "/health": {"get": {"responses": {}}}
+            },
+            "components": {"parameters": {
+                "RequestId": {"name": "X-Request-Id", "in": "header", "schema": {"type": "string"}}
+            }}
+        })).unwrap();
+        let parameter = RefOr::ref_("#/components/parameters/RequestId");
+        let added = spec.inject_parameter(|path, _, _| path != "/health", parameter.clone()).unwrap();
+        assert_eq!(added, 1);
+        let get = spec.paths["/pets"].as_item().unwrap().get.as_ref().unwrap();
+        assert_eq!(get.parameters, vec![parameter.clone()]);
+        assert_eq!(spec.paths["/pets"].as_item().unwrap().post.as_ref().unwrap().parameters.len(), 1);
+        assert!(spec.paths["/health"].as_item().unwrap().get.as_ref().unwrap().parameters.is_empty());
+        // Running it again changes nothing.
+        assert_eq!(spec.inject_parameter(|path, _, _| path != "/health", parameter).unwrap(), 0);
+        assert!(spec.inject_parameter(|_, _, _| true, RefOr::ref_("#/components/parameters/Missing")).is_err());
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(OpenAPI)).unwrap();
+        assert_eq!(schema["required"], serde_json::json!(["openapi", "info", "paths"]));
+        assert_eq!(schema["$defs"]["Paths"]["additionalProperties"]["$ref"], "#/$defs/RefOr_PathItem");
+        assert_eq!(schema["$defs"]["Responses"]["additionalProperties"]["$ref"], "#/$defs/RefOr_Response");
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_precise_numbers_round_trip() {
+        let input = r#"{"openapi":"3.0.3","info":{"title":"t","version":"1"},"paths":{"/a":{"get":{"parameters":[{"name":"id","in":"query","schema":{"type":"integer","format":"int64","default":18446744073709551617,"enum":[9007199254740993]},"example":0.10000000000000000001},{"name":"ratio","in":"query","schema":{"type":"number","minimum":0.5,"exclusiveMaximum":1.5}},{"name":"big","in":"query","schema":{"type":"number","maximum":9223372036854775807,"multipleOf":0.12345678901234567890123}},{"name":"huge","in":"query","schema":{"type":"integer","maximum":18446744073709551615}}],"responses":{}}}},"x-id":123456789012345678901234567890}"#;
+        let spec: OpenAPI = serde_json::from_str(input).unwrap();
+        let output = serde_json::to_string(&spec).unwrap();
+        for number in ["18446744073709551617", "9007199254740993", "0.10000000000000000001", "123456789012345678901234567890", "9223372036854775807", "0.12345678901234567890123", "18446744073709551615"] {
+            assert!(output.contains(number), "{} lost in {}", number, output);
+        }
+        // Typed bounds are still read, as f64.
+        assert!(output.contains(r#""exclusiveMaximum":1.5,"minimum":0.5"#), "{}", output);
+
+        // A bound changed since it was read is written from the typed field.
+        let mut schema: Schema = serde_json::from_str(r#"{"type":"number","maximum":9223372036854775807,"multipleOf":0.12345678901234567890123}"#).unwrap();
+        let SchemaKind::Type(Type::Number(number)) = &mut schema.kind else { panic!() };
+        number.maximum = Some(10.0);
+        let output = serde_json::to_string(&schema).unwrap();
+        assert_eq!(output, r#"{"type":"number","multipleOf":0.12345678901234567890123,"maximum":10.0}"#);
+    }
+}
@@ -1,9 +1,18 @@
+use std::fmt;
+use std::mem::discriminant;
+
 use crate::*;
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
+use serde::de::{Error as _, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Describes a single API operation on a path.
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+///
+/// Deserialized by hand rather than with `#[serde(flatten)]`, which would buffer
+/// every operation into an intermediate map just to pick out its extensions.
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Operation {
     /// A list of tags for API documentation control.
@@ -49,6 +58,11 @@ pub struct Operation {
     /// REQUIRED. The list of possible responses as they are returned
     /// from executing this operation.
     pub responses: Responses,
+    /// The out-of-band requests the API provider may send in relation to this
+    /// operation, by a name unique to it, e.g. a webhook registered by calling it.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::callbacks))]
+    pub callbacks: IndexMap<String, RefOr<Callback>>,
     /// Declares this operation to be deprecated.Default value is false.
     #[serde(default, skip_serializing_if = "is_false")]
     pub deprecated: bool,
@@ -67,10 +81,57 @@ pub struct Operation {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub servers: Vec<Server>,
     /// Inline extensions to this object.
-    #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
+impl<'de> Deserialize<'de> for Operation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OperationVisitor;
+
+        impl<'de> Visitor<'de> for OperationVisitor {
+            type Value = Operation;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an Operation Object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Operation, A::Error> {
+                let mut op = Operation::default();
+                let mut responses = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "tags" => op.tags = map.next_value()?,
+                        "summary" => op.summary = map.next_value()?,
+                        "description" => op.description = map.next_value()?,
+                        "externalDocs" => op.external_docs = map.next_value()?,
+                        "operationId" => op.operation_id = map.next_value()?,
+                        "parameters" => op.parameters = map.next_value()?,
+                        "requestBody" => op.request_body = map.next_value()?,
+                        "responses" => responses = Some(map.next_value()?),
+                        "callbacks" => op.callbacks = map.next_value()?,
+                        "deprecated" => op.deprecated = map.next_value()?,
+                        "security" => op.security = map.next_value()?,
+                        "servers" => op.servers = map.next_value()?,
+                        _ if key.starts_with("x-") => {
+                            let value = map.next_value()?;
+                            op.extensions.insert(key, value);
+                        }
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                op.responses = responses.ok_or_else(|| A::Error::missing_field("responses"))?;
+                Ok(op)
+            }
+        }
+
+        deserializer.deserialize_map(OperationVisitor)
+    }
+}
+
 impl Operation {
     pub fn add_response_success_json(&mut self, schema: Option<RefOr<Schema>>) {
         self.responses.responses.insert(StatusCode::Code(200), RefOr::Item({
@@ -98,11 +159,42 @@ impl Operation {
             ..RequestBody::default()
         }));
     }
+
+    /// Adds `scheme` with `scopes` as an alternative way of authorizing the operation.
+    /// The first one added replaces the spec's security for this operation.
+    pub fn add_security<S: Into<String>>(&mut self, scheme: impl Into<String>, scopes: impl IntoIterator<Item=S>) {
+        let requirement = IndexMap::from([(scheme.into(), scopes.into_iter().map(Into::into).collect())]);
+        self.security.get_or_insert_with(Vec::new).push(requirement);
+    }
+
+    /// Removes the operation's own security, so that the spec's applies.
+    pub fn clear_security(&mut self) {
+        self.security = None;
+    }
+
+    /// The parameters that apply to the operation on `path_item`, resolved against
+    /// `spec`: those of the path item, with any the operation redefines (same name
+    /// and location) replaced in place, followed by the operation's own. A
+    /// parameter declared twice at the same level is listed once, as the later
+    /// declaration.
+    pub fn effective_parameters<'a>(&'a self, path_item: &'a PathItem, spec: &'a OpenAPI) -> anyhow::Result<Vec<&'a Parameter>> {
+        let view = spec.resolved();
+        let same = |a: &Parameter, b: &Parameter| a.name == b.name && discriminant(&a.kind) == discriminant(&b.kind);
+        let mut parameters: Vec<&Parameter> = Vec::with_capacity(path_item.parameters.len() + self.parameters.len());
+        for parameter in path_item.parameters.iter().chain(&self.parameters) {
+            let parameter = view.parameter(parameter)?;
+            match parameters.iter_mut().find(|p| same(p, parameter)) {
+                Some(existing) => *existing = parameter,
+                None => parameters.push(parameter),
+            }
+        }
+        Ok(parameters)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Operation, RefOr, Responses, Schema, StatusCode};
+    use crate::{OpenAPI, Operation, RefOr, Responses, Schema, StatusCode};
     use indexmap::IndexMap;
     use serde_yaml::from_str;
 
@@ -163,4 +255,42 @@ mod tests {
         let mut op = Operation::default();
         op.add_request_body_json(Some(RefOr::Item(Schema::new_string())));
     }
+
+    #[test]
+    fn test_deserialize_fields() {
+        let op: Operation = from_str("{ operationId: get, x-internal: true, unknown: 1, tags: [a], responses: {} }").unwrap();
+        assert_eq!(op.operation_id.as_deref(), Some("get"));
+        assert_eq!(op.tags, vec!["a"]);
+        assert_eq!(op.extensions.keys().collect::<Vec<_>>(), vec!["x-internal"]);
+        let err = from_str::<Operation>("{ operationId: get }").unwrap_err();
+        assert!(err.to_string().contains("missing field `responses`"), "{}", err);
+    }
+
+    #[test]
+    fn test_effective_parameters() {
+        let spec: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets/{id}": {
+                "parameters": [
+                    {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}},
+                    {"$ref": "#/components/parameters/Limit"},
+                    {"name": "id", "in": "header", "schema": {"type": "string"}}
+                ],
+                "get": {
+                    "parameters": [
+                        {"name": "verbose", "in": "query", "schema": {"type": "boolean"}},
+                        {"name": "limit", "in": "query", "description": "own", "schema": {"type": "integer"}}
+                    ],
+                    "responses": {}
+                }
+            }},
+            "components": {"parameters": {"Limit": {"name": "limit", "in": "query", "schema": {"type": "integer"}}}}
+        })).unwrap();
+        let item = spec.paths["/pets/{id}"].as_item().unwrap();
+        let parameters = item.get.as_ref().unwrap().effective_parameters(item, &spec).unwrap();
+        let names: Vec<&str> = parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "limit", "id", "verbose"]);
+        assert_eq!(parameters[1].description.as_deref(), Some("own"));
+    }
 }
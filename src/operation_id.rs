@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::util::{snake_case, unique_name_with};
+use crate::*;
+
+/// The case [OpenAPI::ensure_operation_ids] writes operation ids in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OperationIdCasing {
+    /// `getUsersById`
+    #[default]
+    Camel,
+    /// `GetUsersById`
+    Pascal,
+    /// `get_users_by_id`
+    Snake,
+    /// `get-users-by-id`
+    Kebab,
+}
+
+impl OperationIdCasing {
+    fn join(self, words: &[String]) -> String {
+        let capitalized = |word: &String| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase()).into_iter().chain(chars).collect::<String>()
+        };
+        match self {
+            OperationIdCasing::Camel => words.iter().enumerate()
+                .map(|(i, word)| if i == 0 { word.clone() } else { capitalized(word) })
+                .collect(),
+            OperationIdCasing::Pascal => words.iter().map(capitalized).collect(),
+            OperationIdCasing::Snake => words.join("_"),
+            OperationIdCasing::Kebab => words.join("-"),
+        }
+    }
+
+    /// `id` with `n` appended, to tell it from an equal one.
+    fn numbered(self, id: &str, n: usize) -> String {
+        match self {
+            OperationIdCasing::Camel | OperationIdCasing::Pascal => format!("{}{}", id, n),
+            OperationIdCasing::Snake => format!("{}_{}", id, n),
+            OperationIdCasing::Kebab => format!("{}-{}", id, n),
+        }
+    }
+}
+
+/// How [OpenAPI::ensure_operation_ids] names operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationIdStrategy {
+    pub casing: OperationIdCasing,
+    /// Rewrite existing ids in `casing` too, e.g. `list_pets` to `listPets`.
+    /// Otherwise they are kept as written, unless taken by an earlier operation.
+    pub rewrite_existing: bool,
+}
+
+/// An operation id set by [OpenAPI::ensure_operation_ids].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationIdChange {
+    pub path: String,
+    /// The lowercase method.
+    pub method: String,
+    /// The id before, if the operation had one.
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl OpenAPI {
+    /// Gives every operation of the paths an `operationId` that no other one
+    /// has. Missing ids are generated from method and path, e.g. `getUsersById`
+    /// for `GET /users/{id}`, in the casing of `strategy`, which existing ids are
+    /// rewritten to as well if it says so. Where ids collide, the first
+    /// operation with an id of its own keeps it, in document order, then the
+    /// first generated, and the others are numbered, e.g. `getUsers2`. Links
+    /// naming an id by `operationId` that no operation has any more follow the
+    /// first operation that had it. Returns the ids changed.
+    pub fn ensure_operation_ids(&mut self, strategy: OperationIdStrategy) -> Vec<OperationIdChange> {
+        let casing = strategy.casing;
+        let wanted: Vec<(bool, String)> = self.operations()
+            .map(|(path, method, operation, _)| match &operation.operation_id {
+                Some(id) if strategy.rewrite_existing => (true, casing.join(&words(id))),
+                Some(id) => (true, id.clone()),
+                None => (false, casing.join(&generated_words(method, path))),
+            })
+            .collect();
+        let mut taken = HashSet::new();
+        let mut ids = vec![None; wanted.len()];
+        for own in [true, false] {
+            for (i, (_, id)) in wanted.iter().enumerate().filter(|(_, (o, _))| *o == own) {
+                let id = unique_name_with(id, |n| casing.numbered(id, n), |id| taken.contains(id));
+                taken.insert(id.clone());
+                ids[i] = Some(id);
+            }
+        }
+
+        let mut changes = Vec::new();
+        let mut renames = HashMap::new();
+        for ((path, method, operation), id) in self.operations_mut().zip(ids.into_iter().flatten()) {
+            if operation.operation_id.as_ref() == Some(&id) {
+                continue;
+            }
+            let old = operation.operation_id.replace(id.clone());
+            if let Some(old) = &old {
+                renames.entry(old.clone()).or_insert_with(|| id.clone());
+            }
+            changes.push(OperationIdChange { path: path.to_string(), method: method.to_string(), old, new: id });
+        }
+        // A link keeps to an id some operation still has.
+        renames.retain(|old, _| !taken.contains(old));
+        if !renames.is_empty() {
+            let mut relink = Relink { renames };
+            for link in self.components.links.values_mut() {
+                if let RefOr::Item(link) = link {
+                    relink.link(link);
+                }
+            }
+            self.transform(&mut relink);
+        }
+        changes
+    }
+}
+
+/// The lowercase words of an identifier in any case.
+fn words(id: &str) -> Vec<String> {
+    snake_case(id).split('_').filter(|word| !word.is_empty()).map(str::to_string).collect()
+}
+
+/// The words of the id of an operation: the method, the path segments, and
+/// `by` before each path parameter, e.g. `get users by id` for `/users/{id}`.
+fn generated_words(method: &str, path: &str) -> Vec<String> {
+    let mut words = vec![method.to_lowercase()];
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        if let Some(parameter) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            words.push("by".to_string());
+            words.extend(self::words(parameter));
+        } else {
+            words.extend(self::words(segment));
+        }
+    }
+    words
+}
+
+/// Points links at the new ids of renamed operations.
+struct Relink {
+    renames: HashMap<String, String>,
+}
+
+impl Relink {
+    fn link(&self, link: &mut Link) {
+        if let LinkOperation::OperationId(id) = &mut link.operation {
+            if let Some(new) = self.renames.get(id) {
+                *id = new.clone();
+            }
+        }
+    }
+}
+
+impl Transform for Relink {
+    fn response(&mut self, _pointer: &str, response: &mut Response) {
+        for link in response.links.values_mut() {
+            if let RefOr::Item(link) = link {
+                self.link(link);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_ensure_operation_ids() {
+        let input = json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/users": {"get": {"responses": {}}, "post": {"operationId": "get_users", "responses": {}}},
+                "/users/{userId}": {
+                    "get": {"responses": {"200": {"description": "", "links": {"self": {"operationId": "get_users"}}}}},
+                    "delete": {"operationId": "get_users", "responses": {}}
+                }
+            },
+            "components": {"links": {"Users": {"operationId": "get_users"}}}
+        });
+        let mut spec: OpenAPI = serde_json::from_value(input.clone()).unwrap();
+        let changes = spec.ensure_operation_ids(OperationIdStrategy::default());
+        let ids: Vec<_> = spec.operations().map(|(_, _, operation, _)| operation.operation_id.clone().unwrap()).collect();
+        assert_eq!(ids, ["getUsers", "get_users", "getUsersByUserId", "get_users2"]);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[2], OperationIdChange { path: "/users/{userId}".to_string(), method: "delete".to_string(), old: Some("get_users".to_string()), new: "get_users2".to_string() });
+        assert!(spec.ensure_operation_ids(OperationIdStrategy::default()).is_empty());
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["components"]["links"]["Users"]["operationId"], "get_users");
+
+        let mut spec: OpenAPI = serde_json::from_value(input).unwrap();
+        spec.ensure_operation_ids(OperationIdStrategy { casing: OperationIdCasing::Camel, rewrite_existing: true });
+        let ids: Vec<_> = spec.operations().map(|(_, _, operation, _)| operation.operation_id.clone().unwrap()).collect();
+        assert_eq!(ids, ["getUsers3", "getUsers", "getUsersByUserId", "getUsers2"]);
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["components"]["links"]["Users"]["operationId"], "getUsers");
+        assert_eq!(json["paths"]["/users/{userId}"]["get"]["responses"]["200"]["links"]["self"]["operationId"], "getUsers");
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::example_validation::{child, unescape};
+use crate::util::is_false;
+use crate::*;
+
+/// An OpenAPI Overlay document: changes to make to an OpenAPI document, such as
+/// the server URLs of an environment or the removal of internal operations,
+/// applied with [OpenAPI::apply_overlay].
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Overlay {
+    /// The version of the Overlay specification, e.g. `1.0.0`.
+    pub overlay: String,
+    pub info: OverlayInfo,
+    /// The URL of the document the overlay is meant for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// The changes, applied in order, each to the document as the previous left it.
+    pub actions: Vec<OverlayAction>,
+    /// Inline extensions to this object.
+    #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    pub extensions: IndexMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct OverlayInfo {
+    pub title: String,
+    /// The version of the overlay, not of the document it applies to.
+    pub version: String,
+    /// Inline extensions to this object.
+    #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    pub extensions: IndexMap<String, Value>,
+}
+
+/// A change of an [Overlay] to the nodes its `target` selects.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct OverlayAction {
+    /// A JSONPath expression selecting the nodes to change, e.g.
+    /// `$.paths.*[?@['x-internal'] == true]`. Supported are the root `$`,
+    /// names as `.name` or `['name']`, indexes, wildcards, descendants as
+    /// `..name`, and filters comparing a relative path like `@.name` with `==`
+    /// or `!=` to a literal, or testing that it exists.
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Merged into each selected object, recursively, objects being merged and
+    /// other values replaced. Appended to each selected array.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<Value>,
+    /// Removes the selected nodes. Takes precedence over `update`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub remove: bool,
+    /// Inline extensions to this object.
+    #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    pub extensions: IndexMap<String, Value>,
+}
+
+/// What [OpenAPI::apply_overlay] changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OverlayReport {
+    /// For each action, in order, the JSON pointers of the nodes it updated or
+    /// removed, as they were when it applied.
+    pub changed: Vec<Vec<String>>,
+}
+
+impl OverlayReport {
+    /// The indexes of the actions whose target selected nothing, which usually
+    /// means the overlay was written for another version of the document.
+    pub fn unmatched(&self) -> impl Iterator<Item=usize> + '_ {
+        self.changed.iter().enumerate().filter(|(_, pointers)| pointers.is_empty()).map(|(i, _)| i)
+    }
+}
+
+/// Why [OpenAPI::apply_overlay] failed.
+#[derive(Debug)]
+pub enum OverlayError {
+    /// The target of the action at this index isn't a supported JSONPath expression.
+    Target { action: usize, message: String },
+    /// The action at this index updates a node that is neither an object nor an
+    /// array, or merges something other than an object into an object.
+    Update { action: usize, pointer: String },
+    /// The document with the overlay applied isn't a valid OpenAPI document.
+    Document(serde_json::Error),
+}
+
+impl fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlayError::Target { action, message } => write!(f, "invalid target of action {}: {}", action, message),
+            OverlayError::Update { action, pointer } => write!(f, "action {} can't update {}", action, pointer),
+            OverlayError::Document(e) => write!(f, "the overlaid document is invalid: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for OverlayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OverlayError::Document(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl OpenAPI {
+    /// Applies the actions of `overlay` in order. On error, the document is left
+    /// unchanged.
+    pub fn apply_overlay(&mut self, overlay: &Overlay) -> Result<OverlayReport, OverlayError> {
+        let mut document = serde_json::to_value(&*self).map_err(OverlayError::Document)?;
+        let mut report = OverlayReport::default();
+        for (i, action) in overlay.actions.iter().enumerate() {
+            let path = parse(&action.target).map_err(|message| OverlayError::Target { action: i, message })?;
+            let pointers = select(&document, &path);
+            if action.remove {
+                // Last first, so that removals don't shift the indexes of the rest.
+                for pointer in pointers.iter().rev() {
+                    remove(&mut document, pointer);
+                }
+            } else if let Some(update) = &action.update {
+                for pointer in &pointers {
+                    let error = || OverlayError::Update { action: i, pointer: pointer.clone() };
+                    match (document.pointer_mut(pointer), update) {
+                        (Some(Value::Object(target)), Value::Object(update)) => merge(target, update),
+                        (Some(Value::Array(target)), update) => target.push(update.clone()),
+                        _ => return Err(error()),
+                    }
+                }
+            }
+            report.changed.push(pointers);
+        }
+        *self = serde_json::from_value(document).map_err(OverlayError::Document)?;
+        Ok(report)
+    }
+}
+
+fn merge(target: &mut Map<String, Value>, update: &Map<String, Value>) {
+    for (key, value) in update {
+        match (target.get_mut(key), value) {
+            (Some(Value::Object(target)), Value::Object(update)) => merge(target, update),
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn remove(document: &mut Value, pointer: &str) {
+    let Some((parent, key)) = pointer.rsplit_once('/') else { return };
+    let key = unescape(key);
+    match document.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.shift_remove(&key);
+        }
+        Some(Value::Array(items)) => {
+            if let Some(i) = key.parse::<usize>().ok().filter(|i| *i < items.len()) {
+                items.remove(i);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A step of a JSONPath expression.
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    /// Whether the selector applies to every descendant, as in `..name`.
+    descendant: bool,
+    selector: Selector,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    /// An array index, negative ones counting from the end.
+    Index(i64),
+    Wildcard,
+    /// The children for which the relative path exists, or compares as given.
+    Filter { path: Vec<Segment>, comparison: Option<(bool, Value)> },
+}
+
+fn parse(expression: &str) -> Result<Vec<Segment>, String> {
+    let rest = expression.trim().strip_prefix('$').ok_or("the expression must start with $")?;
+    segments(rest)
+}
+
+fn segments(mut rest: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        let descendant = rest.starts_with("..");
+        if descendant {
+            rest = &rest[2..];
+        } else if let Some(after) = rest.strip_prefix('.') {
+            rest = after;
+        } else if !rest.starts_with('[') {
+            return Err(format!("unexpected {:?}", rest));
+        }
+        let selector = if let Some(after) = rest.strip_prefix('[') {
+            let end = closing_bracket(after).ok_or("unclosed [")?;
+            rest = &after[end + 1..];
+            bracketed(after[..end].trim())?
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            match name {
+                "" => return Err("empty name".to_string()),
+                "*" => Selector::Wildcard,
+                name => Selector::Name(name.to_string()),
+            }
+        };
+        segments.push(Segment { descendant, selector });
+    }
+    Ok(segments)
+}
+
+/// The byte offset of the `]` closing a bracket whose contents start `s`.
+fn closing_bracket(s: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 0 => return Some(i),
+            (None, ']') => depth -= 1,
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn bracketed(inner: &str) -> Result<Selector, String> {
+    if inner == "*" {
+        return Ok(Selector::Wildcard);
+    }
+    if let Some(filter) = inner.strip_prefix('?') {
+        let filter = filter.trim();
+        let filter = filter.strip_prefix('(').and_then(|f| f.strip_suffix(')')).unwrap_or(filter).trim();
+        return self::filter(filter);
+    }
+    if inner.starts_with(['\'', '"']) {
+        return match string(inner)? {
+            (name, "") => Ok(Selector::Name(name)),
+            (_, rest) => Err(format!("unexpected {:?}", rest)),
+        };
+    }
+    inner.parse().map(Selector::Index).map_err(|_| format!("unsupported selector {:?}", inner))
+}
+
+fn filter(filter: &str) -> Result<Selector, String> {
+    let operator = operator(filter);
+    let (left, comparison) = match operator {
+        Some(i) => {
+            let right = filter[i + 2..].trim();
+            let value = if right.starts_with(['\'', '"']) {
+                match string(right)? {
+                    (s, "") => Value::String(s),
+                    (_, rest) => return Err(format!("unexpected {:?}", rest)),
+                }
+            } else {
+                serde_json::from_str(right).map_err(|_| format!("unsupported literal {:?}", right))?
+            };
+            (filter[..i].trim(), Some((&filter[i..i + 2] == "==", value)))
+        }
+        None => (filter, None),
+    };
+    let path = segments(left.strip_prefix('@').ok_or("filters must start with @")?)?;
+    if path.iter().any(|s| s.descendant || !matches!(s.selector, Selector::Name(_) | Selector::Index(_))) {
+        return Err(format!("unsupported filter path {:?}", left));
+    }
+    Ok(Selector::Filter { path, comparison })
+}
+
+/// The byte offset of the `==` or `!=` of a filter, outside quotes.
+fn operator(filter: &str) -> Option<usize> {
+    let mut quote = None;
+    let bytes = filter.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match quote {
+            Some(q) if b == q && bytes[i - 1] != b'\\' => quote = None,
+            Some(_) => {}
+            None if b == b'\'' || b == b'"' => quote = Some(b),
+            None if (b == b'=' || b == b'!') && bytes.get(i + 1) == Some(&b'=') => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+/// A quoted string at the start of `s`, unescaped, and what follows it.
+fn string(s: &str) -> Result<(String, &str), String> {
+    let quote = s.chars().next().ok_or("expected a string")?;
+    let mut out = String::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next().map(|(_, c)| c)),
+            c if c == quote => return Ok((out, s[i + 1..].trim())),
+            c => out.push(c),
+        }
+    }
+    Err(format!("unclosed string {}", s))
+}
+
+/// The JSON pointers of the nodes `path` selects, in document order.
+fn select(document: &Value, path: &[Segment]) -> Vec<String> {
+    let mut nodes = vec![(String::new(), document)];
+    for segment in path {
+        let mut next = Vec::new();
+        for (pointer, node) in nodes {
+            let mut candidates = vec![(pointer, node)];
+            if segment.descendant {
+                descendants(&candidates[0].0.clone(), node, &mut candidates);
+            }
+            for (pointer, node) in candidates {
+                apply(&segment.selector, &pointer, node, &mut next);
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        next.retain(|(pointer, _)| seen.insert(pointer.clone()));
+        nodes = next;
+    }
+    nodes.into_iter().map(|(pointer, _)| pointer).collect()
+}
+
+fn descendants<'a>(pointer: &str, node: &'a Value, out: &mut Vec<(String, &'a Value)>) {
+    for (pointer, child) in children(pointer, node) {
+        out.push((pointer.clone(), child));
+        descendants(&pointer, child, out);
+    }
+}
+
+fn children<'a>(pointer: &str, node: &'a Value) -> Vec<(String, &'a Value)> {
+    match node {
+        Value::Object(map) => map.iter().map(|(key, value)| (child(pointer, key), value)).collect(),
+        Value::Array(items) => items.iter().enumerate().map(|(i, item)| (child(pointer, &i.to_string()), item)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn apply<'a>(selector: &Selector, pointer: &str, node: &'a Value, out: &mut Vec<(String, &'a Value)>) {
+    match (selector, node) {
+        (Selector::Name(name), Value::Object(map)) => {
+            out.extend(map.get(name).map(|value| (child(pointer, name), value)));
+        }
+        (Selector::Index(i), Value::Array(items)) => {
+            let i = if *i < 0 { items.len() as i64 + i } else { *i };
+            if let Some(item) = (i >= 0).then(|| items.get(i as usize)).flatten() {
+                out.push((child(pointer, &i.to_string()), item));
+            }
+        }
+        (Selector::Wildcard, node) => out.extend(children(pointer, node)),
+        (Selector::Filter { path, comparison }, node) => {
+            for (pointer, child) in children(pointer, node) {
+                let found = select(child, path).first().and_then(|p| child.pointer(p));
+                let keep = match (comparison, found) {
+                    (None, found) => found.is_some(),
+                    (Some((true, value)), found) => found == Some(value),
+                    (Some((false, value)), found) => found != Some(value),
+                };
+                if keep {
+                    out.push((pointer, child));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("$.paths['/pets'].get").unwrap(), vec![
+            Segment { descendant: false, selector: Selector::Name("paths".to_string()) },
+            Segment { descendant: false, selector: Selector::Name("/pets".to_string()) },
+            Segment { descendant: false, selector: Selector::Name("get".to_string()) },
+        ]);
+        assert_eq!(parse("$..[?(@.name == 'a]b')]").unwrap(), vec![Segment {
+            descendant: true,
+            selector: Selector::Filter {
+                path: vec![Segment { descendant: false, selector: Selector::Name("name".to_string()) }],
+                comparison: Some((true, json!("a]b"))),
+            },
+        }]);
+        assert!(parse("paths").is_err());
+        assert!(parse("$.paths[").is_err());
+    }
+
+    #[test]
+    fn test_apply_overlay() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "servers": [{"url": "http://localhost"}],
+            "paths": {
+                "/pets": {
+                    "get": {"description": "List", "responses": {}},
+                    "delete": {"x-internal": true, "responses": {}}
+                },
+                "/admin": {"post": {"x-internal": true, "responses": {}}}
+            }
+        })).unwrap();
+        let overlay: Overlay = serde_yaml::from_str(r#"
+overlay: 1.0.0
+info:
+  title: Production
+  version: 1.0.0
+actions:
+  - target: $.servers[0]
+    update:
+      url: https://api.example.com
+  - target: $.paths.*[?@['x-internal'] == true]
+    remove: true
+  - target: $.paths['/pets'].get
+    update:
+      description: Lists the pets.
+  - target: $.tags
+    update:
+      name: pets
+  - target: $.paths.*.put
+    remove: true
+"#).unwrap();
+        let report = spec.apply_overlay(&overlay).unwrap();
+        assert_eq!(report.changed[1], ["/paths/~1pets/delete", "/paths/~1admin/post"]);
+        assert_eq!(report.unmatched().collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(spec.servers[0].url, "https://api.example.com");
+        let pets = spec.paths["/pets"].as_item().unwrap();
+        assert_eq!(pets.iter().map(|(method, _)| method).collect::<Vec<_>>(), ["get"]);
+        assert_eq!(pets.get.as_ref().unwrap().description.as_deref(), Some("Lists the pets."));
+        assert!(spec.paths["/admin"].as_item().unwrap().post.is_none());
+
+        let invalid = Overlay {
+            actions: vec![OverlayAction { target: "$.info.title".to_string(), update: Some(json!("x")), ..OverlayAction::default() }],
+            ..Overlay::default()
+        };
+        assert!(matches!(spec.apply_overlay(&invalid), Err(OverlayError::Update { action: 0, .. })));
+    }
+}
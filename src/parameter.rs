@@ -1,11 +1,16 @@
+use std::fmt;
+
 use crate::*;
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
+use serde::de::{Error as _, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Describes a single operation parameter.
 ///
 /// A unique parameter is defined by a combination of a name and location.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ParameterData {
     /// REQUIRED. The name of the parameter. Parameter names are case sensitive.
     /// If in is "path", the name field MUST correspond to the associated path
@@ -37,6 +42,7 @@ pub struct ParameterData {
     #[serde(flatten)]
     pub format: ParameterSchemaOrContent,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub example: Option<serde_json::Value>,
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub examples: IndexMap<String, RefOr<Example>>,
@@ -44,6 +50,7 @@ pub struct ParameterData {
     pub explode: Option<bool>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
@@ -59,7 +66,10 @@ impl ParameterData {
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+/// Exactly one of `schema` and `content` must be given, which deserialization checks.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum ParameterSchemaOrContent {
     /// The schema defining the type used for the parameter.
@@ -70,9 +80,46 @@ pub enum ParameterSchemaOrContent {
     Content(Content),
 }
 
+// By hand, as the derived impl takes whichever key comes first when both are given.
+impl<'de> Deserialize<'de> for ParameterSchemaOrContent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FormatVisitor;
+
+        impl<'de> Visitor<'de> for FormatVisitor {
+            type Value = ParameterSchemaOrContent;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a `schema` or `content`")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut format = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    let value = match key.as_str() {
+                        "schema" => ParameterSchemaOrContent::Schema(map.next_value()?),
+                        "content" => ParameterSchemaOrContent::Content(map.next_value()?),
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                            continue;
+                        }
+                    };
+                    if format.replace(value).is_some() {
+                        return Err(A::Error::custom("only one of `schema` and `content` may be given"));
+                    }
+                }
+                format.ok_or_else(|| A::Error::custom("one of `schema` and `content` must be given"))
+            }
+        }
+
+        deserializer.deserialize_map(FormatVisitor)
+    }
+}
+
 pub type Content = IndexMap<String, MediaType>;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Parameter {
     #[serde(flatten)]
     pub data: ParameterData,
@@ -95,6 +142,8 @@ impl std::ops::DerefMut for Parameter {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "in", rename_all = "camelCase")]
 pub enum ParameterKind {
     #[serde(rename_all = "camelCase")]
@@ -145,6 +194,18 @@ pub enum ParameterKind {
     },
 }
 
+impl ParameterKind {
+    /// The value of `in`, e.g. `query`.
+    pub fn location(&self) -> &'static str {
+        match self {
+            ParameterKind::Query { .. } => "query",
+            ParameterKind::Header { .. } => "header",
+            ParameterKind::Path { .. } => "path",
+            ParameterKind::Cookie { .. } => "cookie",
+        }
+    }
+}
+
 impl Parameter {
     fn new_kind(name: String, schema: RefOr<Schema>, kind: ParameterKind) -> Self {
         let required = match kind {
@@ -180,6 +241,12 @@ impl Parameter {
             style: PathStyle::Simple,
         })
     }
+
+    pub fn header(name: impl Into<String>, schema: impl Into<RefOr<Schema>>) -> Self {
+        Self::new_kind(name.into(), schema.into(), ParameterKind::Header {
+            style: HeaderStyle::Simple,
+        })
+    }
 }
 
 struct SkipSerializeIfDefault;
@@ -196,6 +263,8 @@ impl SkipSerializeIfDefault {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum PathStyle {
     Matrix,
@@ -205,6 +274,8 @@ pub enum PathStyle {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum QueryStyle {
     #[default]
@@ -215,12 +286,16 @@ pub enum QueryStyle {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum CookieStyle {
     #[default]
     Form,
 }
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum HeaderStyle {
     #[default]
@@ -0,0 +1,542 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::instance_validation::validate_ref_value;
+use crate::*;
+
+impl Parameter {
+    /// Serializes `value` as the parameter's `style`, `explode` and `allowReserved`
+    /// say: for a path parameter, what replaces its template; for a query one, all
+    /// of its `name=value` pairs, percent-encoded; for a header, its value; and for
+    /// a cookie, its `name=value` pairs for the `Cookie` header, with what can't be
+    /// in a cookie percent-encoded. A parameter with `content` instead of a
+    /// schema is serialized as its media type says, see
+    /// [ParameterSchemaOrContent::serialize_content], falling back to JSON.
+    pub fn serialize_value(&self, value: &Value) -> String {
+        let value = match &self.format {
+            ParameterSchemaOrContent::Content(_) => Value::String(self.format.serialize_content(value).unwrap_or_else(|_| value.to_string())),
+            ParameterSchemaOrContent::Schema(_) => value.clone(),
+        };
+        let style = self.style();
+        let explode = self.explode.unwrap_or(matches!(style, Style::Form | Style::DeepObject));
+        let serialized = style.serialize(&self.name, &value, explode, &|s| self.encode(s));
+        match &self.kind {
+            // Exploded values are sent as several cookies.
+            ParameterKind::Cookie { .. } => serialized.replace('&', "; "),
+            _ => serialized,
+        }
+    }
+
+    /// Percent-encodes `s` as it must be where the parameter goes: all but the
+    /// unreserved characters in paths, queries keeping the reserved ones with
+    /// `allowReserved`, what RFC 6265 doesn't allow in cookies, and nothing in
+    /// headers.
+    pub fn encode(&self, s: &str) -> String {
+        match &self.kind {
+            ParameterKind::Path { .. } => percent_encode(s, false),
+            ParameterKind::Query { allow_reserved, .. } => percent_encode(s, *allow_reserved),
+            ParameterKind::Header { .. } => s.to_string(),
+            ParameterKind::Cookie { .. } => encode_cookie(s),
+        }
+    }
+
+    /// Reverses [Parameter::encode], with `+` as a space only in queries.
+    pub fn decode(&self, s: &str) -> String {
+        match &self.kind {
+            ParameterKind::Query { .. } => percent_decode(s, true),
+            ParameterKind::Path { .. } | ParameterKind::Cookie { .. } => percent_decode(s, false),
+            ParameterKind::Header { .. } => s.to_string(),
+        }
+    }
+
+    pub(crate) fn style(&self) -> Style {
+        match &self.kind {
+            ParameterKind::Path { style: PathStyle::Simple } => Style::Simple,
+            ParameterKind::Path { style: PathStyle::Label } => Style::Label,
+            ParameterKind::Path { style: PathStyle::Matrix } => Style::Matrix,
+            ParameterKind::Query { style: QueryStyle::Form, .. } => Style::Form,
+            ParameterKind::Query { style: QueryStyle::SpaceDelimited, .. } => Style::SpaceDelimited,
+            ParameterKind::Query { style: QueryStyle::PipeDelimited, .. } => Style::PipeDelimited,
+            ParameterKind::Query { style: QueryStyle::DeepObject, .. } => Style::DeepObject,
+            ParameterKind::Header { .. } => Style::Simple,
+            ParameterKind::Cookie { .. } => Style::Form,
+        }
+    }
+}
+
+/// Why a value can't be serialized or parsed through a `content` media type.
+#[derive(Debug)]
+pub enum ContentError {
+    /// The parameter or header has a `schema` rather than `content`.
+    NoContent,
+    /// A media type that values can't be written in, neither JSON nor text.
+    UnsupportedMediaType(String),
+    /// A value of a `text/*` media type that isn't a string, number or boolean.
+    NotText,
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentError::NoContent => write!(f, "no content is declared"),
+            ContentError::UnsupportedMediaType(media_type) => write!(f, "unsupported media type {:?}", media_type),
+            ContentError::NotText => write!(f, "text values must be strings, numbers or booleans"),
+            ContentError::Json(e) => write!(f, "invalid JSON: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ContentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContentError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn is_json(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or("").trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+impl ParameterSchemaOrContent {
+    /// The media type a parameter or header with `content` is written in, and its
+    /// declaration. Only the first is used, as there must be only one.
+    pub fn media_type(&self) -> Option<(&str, &MediaType)> {
+        match self {
+            ParameterSchemaOrContent::Content(content) => content.first().map(|(name, media)| (name.as_str(), media)),
+            ParameterSchemaOrContent::Schema(_) => None,
+        }
+    }
+
+    /// Serializes `value` as the `content` media type: as JSON for `application/json`
+    /// and `+json` types, and as is for `text/*` ones. The result isn't yet
+    /// encoded for where it goes, see [Parameter::encode].
+    pub fn serialize_content(&self, value: &Value) -> Result<String, ContentError> {
+        let (media_type, _) = self.media_type().ok_or(ContentError::NoContent)?;
+        if is_json(media_type) {
+            return serde_json::to_string(value).map_err(ContentError::Json);
+        }
+        if !media_type.starts_with("text/") {
+            return Err(ContentError::UnsupportedMediaType(media_type.to_string()));
+        }
+        match value {
+            Value::String(_) | Value::Number(_) | Value::Bool(_) => Ok(text(value)),
+            _ => Err(ContentError::NotText),
+        }
+    }
+
+    /// Parses a decoded value written in the `content` media type, the inverse
+    /// of [ParameterSchemaOrContent::serialize_content]. Text is kept as a string.
+    pub fn parse_content(&self, raw: &str) -> Result<Value, ContentError> {
+        let (media_type, _) = self.media_type().ok_or(ContentError::NoContent)?;
+        if is_json(media_type) {
+            serde_json::from_str(raw).map_err(ContentError::Json)
+        } else if media_type.starts_with("text/") {
+            Ok(Value::String(raw.to_string()))
+        } else {
+            Err(ContentError::UnsupportedMediaType(media_type.to_string()))
+        }
+    }
+
+    /// Validates `value` against the schema of the `content` media type, if it
+    /// declares one.
+    pub fn validate_content(&self, spec: &OpenAPI, value: &Value) -> Result<(), Vec<SchemaError>> {
+        let schema = self.media_type().and_then(|(_, media)| media.schema.as_ref());
+        let errors = schema.map(|schema| validate_ref_value(schema, value, &spec.components)).unwrap_or_default();
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// A parameter whose `style` can't serialize its schema, from [OpenAPI::validate_parameter_styles].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleError {
+    /// JSON pointer to the parameter, e.g. `/paths/~1pets/get/parameters/0`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for StyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+impl std::error::Error for StyleError {}
+
+impl OpenAPI {
+    /// Checks that `deepObject` parameters, in operations, path items and the
+    /// components, have an object schema and aren't `explode: false`, the only
+    /// way the style is defined. Referenced parameters are checked where declared.
+    pub fn validate_parameter_styles(&self) -> Result<(), Vec<StyleError>> {
+        let mut errors = Vec::new();
+        let view = self.resolved();
+        let mut check = |pointer: String, parameter: &RefOr<Parameter>| {
+            let Some(parameter) = parameter.as_item() else { return };
+            if !matches!(parameter.kind, ParameterKind::Query { style: QueryStyle::DeepObject, .. }) {
+                return;
+            }
+            let message = match &parameter.format {
+                ParameterSchemaOrContent::Content(_) => Some("deepObject parameters must have a schema, not content"),
+                ParameterSchemaOrContent::Schema(schema) => match view.schema(schema).ok().map(|s| &s.kind) {
+                    Some(SchemaKind::Type(Type::Object(_))) | Some(SchemaKind::Any(_)) | None => None,
+                    Some(SchemaKind::Type(_)) => Some("deepObject parameters must have an object schema"),
+                    Some(_) => None,
+                },
+            };
+            let message = message.or_else(|| (parameter.explode == Some(false)).then_some("deepObject parameters can't be explode: false"));
+            if let Some(message) = message {
+                errors.push(StyleError { pointer, message: message.to_string() });
+            }
+        };
+        for (name, parameter) in &self.components.parameters {
+            check(child("/components/parameters", name), parameter);
+        }
+        for (path, item) in self.paths.iter() {
+            let Some(item) = item.as_item() else { continue };
+            let pointer = child("/paths", path);
+            for (i, parameter) in item.parameters.iter().enumerate() {
+                check(format!("{}/parameters/{}", pointer, i), parameter);
+            }
+            for (method, operation) in item.iter() {
+                for (i, parameter) in operation.parameters.iter().enumerate() {
+                    check(format!("{}/parameters/{}", child(&pointer, method), i), parameter);
+                }
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// The parameter styles of the spec, which follow RFC 6570.
+#[derive(Clone, Copy)]
+pub(crate) enum Style {
+    Simple,
+    Label,
+    Matrix,
+    Form,
+    SpaceDelimited,
+    PipeDelimited,
+    DeepObject,
+}
+
+impl Style {
+    /// Serializes the parameter `name` with `value`: for the path, what replaces its
+    /// template; for the query, all of its `name=value` pairs; for headers, the value.
+    pub(crate) fn serialize(self, name: &str, value: &Value, explode: bool, encode: &dyn Fn(&str) -> String) -> String {
+        let name = encode(name);
+        if let (Style::DeepObject, Value::Object(_)) = (self, value) {
+            let mut pairs = Vec::new();
+            deep_object(&name, value, encode, &mut pairs);
+            return pairs.join("&");
+        }
+        let (items, pairs) = match value {
+            Value::Array(items) => (Some(items.iter().map(|v| encode(&text(v))).collect::<Vec<_>>()), None),
+            Value::Object(map) => (None, Some(map.iter().map(|(k, v)| (encode(k), encode(&text(v)))).collect::<Vec<_>>())),
+            value => {
+                let value = encode(&text(value));
+                return match self {
+                    Style::Simple => value,
+                    Style::Label => format!(".{}", value),
+                    Style::Matrix => format!(";{}={}", name, value),
+                    _ => format!("{}={}", name, value),
+                };
+            }
+        };
+        // `k,v` pairs flattened, as non-exploded objects are.
+        let flat = |pairs: &[(String, String)]| pairs.iter().flat_map(|(k, v)| [k.clone(), v.clone()]).collect::<Vec<_>>();
+        let joined = |pairs: &[(String, String)], sep: &str| pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(sep);
+        match (self, items, pairs) {
+            (Style::Simple, Some(items), _) => items.join(","),
+            (Style::Simple, _, Some(pairs)) if explode => joined(&pairs, ","),
+            (Style::Simple, _, Some(pairs)) => flat(&pairs).join(","),
+            (Style::Label, Some(items), _) => format!(".{}", items.join(if explode { "." } else { "," })),
+            (Style::Label, _, Some(pairs)) if explode => format!(".{}", joined(&pairs, ".")),
+            (Style::Label, _, Some(pairs)) => format!(".{}", flat(&pairs).join(",")),
+            (Style::Matrix, Some(items), _) if explode => items.iter().map(|v| format!(";{}={}", name, v)).collect(),
+            (Style::Matrix, Some(items), _) => format!(";{}={}", name, items.join(",")),
+            (Style::Matrix, _, Some(pairs)) if explode => format!(";{}", joined(&pairs, ";")),
+            (Style::Matrix, _, Some(pairs)) => format!(";{}={}", name, flat(&pairs).join(",")),
+            (_, Some(items), _) if explode => items.iter().map(|v| format!("{}={}", name, v)).collect::<Vec<_>>().join("&"),
+            (_, _, Some(pairs)) if explode => joined(&pairs, "&"),
+            (style, items, pairs) => {
+                let sep = match style {
+                    Style::SpaceDelimited => "%20",
+                    Style::PipeDelimited => "|",
+                    _ => ",",
+                };
+                let values = items.unwrap_or_else(|| flat(&pairs.unwrap_or_default()));
+                format!("{}={}", name, values.join(sep))
+            }
+        }
+    }
+}
+
+pub(crate) fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Percent-encodes all but RFC 3986's unreserved characters. With
+/// `allow_reserved`, reserved characters and percent-encoded triplets are kept
+/// as they are, as by RFC 6570's reserved expansion, except `#`, which would end
+/// the URL.
+pub fn percent_encode(s: &str, allow_reserved: bool) -> String {
+    let bytes = s.as_bytes();
+    let mut encoded = String::with_capacity(s.len());
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(b as char),
+            b':' | b'/' | b'?' | b'[' | b']' | b'@' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' if allow_reserved => {
+                encoded.push(b as char)
+            }
+            b'%' if allow_reserved && bytes.get(i + 1..i + 3).is_some_and(|h| h.iter().all(u8::is_ascii_hexdigit)) => encoded.push('%'),
+            b => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// The `key=value` pairs of a `deepObject` value under `key`, nesting objects as
+/// `filter[author][name]=x` and repeating the key for each item of an array.
+fn deep_object(key: &str, value: &Value, encode: &dyn Fn(&str) -> String, pairs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                deep_object(&format!("{}[{}]", key, encode(k)), v, encode, pairs);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                deep_object(key, item, encode, pairs);
+            }
+        }
+        value => pairs.push(format!("{}={}", key, encode(&text(value)))),
+    }
+}
+
+/// The schema of the property `key` of `schema`, or of its `additionalProperties`.
+pub(crate) fn property_schema<'a>(spec: ResolvedSpec<'a>, schema: &'a Schema, key: &str) -> Option<&'a Schema> {
+    if let Some(property) = schema.get_properties().and_then(|p| p.get(key)) {
+        return spec.schema(property).ok();
+    }
+    match &schema.kind {
+        SchemaKind::Type(Type::Object(ObjectType { additional_properties: Some(AdditionalProperties::Schema(values)), .. })) => spec.schema(values).ok(),
+        _ => None,
+    }
+}
+
+/// Parses a `simple` style value, as in headers, typed by `schema`: arrays are
+/// comma-separated, as are the keys and values of objects, which `explode` joins
+/// with `=` instead.
+pub(crate) fn parse_simple<'a>(spec: ResolvedSpec<'a>, schema: Option<&'a Schema>, raw: &str, explode: bool) -> Option<Value> {
+    let Some(schema) = schema else { return Some(Value::String(raw.to_string())) };
+    let property = |key: &str| property_schema(spec, schema, key);
+    let typed = |schema: Option<&Schema>, value: &str| match schema {
+        Some(schema) => coerce(spec, schema, value),
+        None => Some(Value::String(value.to_string())),
+    };
+    match &schema.kind {
+        SchemaKind::Type(Type::Array(array)) => {
+            let items = array.items.as_deref().and_then(|items| spec.schema(items).ok());
+            raw.split(',').map(|item| typed(items, item.trim())).collect::<Option<Vec<_>>>().map(Value::Array)
+        }
+        SchemaKind::Type(Type::Object(_)) => {
+            let parts: Vec<&str> = raw.split(',').map(str::trim).collect();
+            let pairs: Vec<(&str, &str)> = if explode {
+                parts.iter().map(|part| part.split_once('=').unwrap_or((part, ""))).collect()
+            } else {
+                parts.chunks(2).map(|kv| (kv[0], kv.get(1).copied().unwrap_or(""))).collect()
+            };
+            pairs.into_iter().map(|(k, v)| Some((k.to_string(), typed(property(k), v)?))).collect::<Option<serde_json::Map<_, _>>>().map(Value::Object)
+        }
+        _ => coerce(spec, schema, raw.trim()),
+    }
+}
+
+/// Percent-encodes what isn't allowed in a cookie name or value by RFC 6265,
+/// and the `%` and `&` used to encode and to join values.
+fn encode_cookie(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            0x21..=0x7E if !matches!(b, b'"' | b',' | b';' | b'\\' | b'%' | b'&') => encoded.push(b as char),
+            b => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// Decodes percent-encoded bytes, and `+` as a space when `plus_as_space`, as in
+/// query strings but not paths. Invalid UTF-8 is replaced.
+pub fn percent_decode(s: &str, plus_as_space: bool) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+                continue;
+            }
+            (b'+', _) if plus_as_space => decoded.push(b' '),
+            (b, _) => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `s` as a value of `schema`: a number, integer or boolean if the schema says
+/// so, else a string. Members of `oneOf`, `anyOf` and `allOf` are tried in order.
+/// `None` if `s` doesn't parse as the type.
+pub(crate) fn coerce(spec: ResolvedSpec, schema: &Schema, s: &str) -> Option<Value> {
+    match &schema.kind {
+        SchemaKind::Type(Type::Integer(_)) => s.parse::<i64>().ok().map(Value::from),
+        SchemaKind::Type(Type::Number(_)) => s.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number),
+        SchemaKind::Type(Type::Boolean {}) => match s {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } | SchemaKind::AllOf { all_of: members } => {
+            let typed = members.iter()
+                .filter_map(|member| spec.schema(member).ok())
+                .filter_map(|member| coerce(spec, member, s))
+                .find(|value| !value.is_string());
+            Some(typed.unwrap_or_else(|| Value::String(s.to_string())))
+        }
+        _ => Some(Value::String(s.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_styles() {
+        let encode = |s: &str| percent_encode(s, false);
+        let array = json!([3, 4, 5]);
+        let object = json!({"role": "admin", "firstName": "Alex"});
+        let cases = [
+            (Style::Simple, &array, false, "3,4,5"),
+            (Style::Simple, &object, false, "role,admin,firstName,Alex"),
+            (Style::Simple, &object, true, "role=admin,firstName=Alex"),
+            (Style::Label, &array, true, ".3.4.5"),
+            (Style::Label, &object, false, ".role,admin,firstName,Alex"),
+            (Style::Matrix, &json!(5), false, ";id=5"),
+            (Style::Matrix, &array, true, ";id=3;id=4;id=5"),
+            (Style::Matrix, &object, true, ";role=admin;firstName=Alex"),
+            (Style::Form, &array, true, "id=3&id=4&id=5"),
+            (Style::Form, &array, false, "id=3,4,5"),
+            (Style::Form, &object, true, "role=admin&firstName=Alex"),
+            (Style::SpaceDelimited, &array, false, "id=3%204%205"),
+            (Style::PipeDelimited, &array, false, "id=3|4|5"),
+            (Style::DeepObject, &object, true, "id[role]=admin&id[firstName]=Alex"),
+            (Style::DeepObject, &json!({"author": {"name": "Jo Li"}, "tags": ["a", "b"]}), true, "id[author][name]=Jo%20Li&id[tags]=a&id[tags]=b"),
+        ];
+        for (style, value, explode, expected) in cases {
+            assert_eq!(style.serialize("id", value, explode, &encode), expected);
+        }
+    }
+
+    #[test]
+    fn test_validate_parameter_styles() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {
+                "parameters": [
+                    {"name": "filter", "in": "query", "style": "deepObject", "schema": {"type": "object"}},
+                    {"name": "ids", "in": "query", "style": "deepObject", "schema": {"type": "array", "items": {"type": "integer"}}},
+                    {"$ref": "#/components/parameters/Sort"}
+                ],
+                "responses": {}
+            }}},
+            "components": {"parameters": {
+                "Sort": {"name": "sort", "in": "query", "style": "deepObject", "explode": false, "schema": {"type": "object"}}
+            }}
+        })).unwrap();
+        let errors: Vec<String> = spec.validate_parameter_styles().unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, vec![
+            "/components/parameters/Sort: deepObject parameters can't be explode: false",
+            "/paths/~1pets/get/parameters/1: deepObject parameters must have an object schema",
+        ]);
+    }
+
+    #[test]
+    fn test_serialize_value() {
+        let mut query = Parameter::query("q", Schema::new_string());
+        assert_eq!(query.serialize_value(&json!(["a b", "c/d"])), "q=a%20b&q=c%2Fd");
+        query.explode = Some(false);
+        assert_eq!(query.serialize_value(&json!(["a b", "c/d"])), "q=a%20b,c%2Fd");
+        if let ParameterKind::Query { allow_reserved, .. } = &mut query.kind {
+            *allow_reserved = true;
+        }
+        assert_eq!(query.serialize_value(&json!("c/d?")), "q=c/d?");
+        assert_eq!(Parameter::path("id", Schema::new_string()).serialize_value(&json!(["a/b", 2])), "a%2Fb,2");
+        assert_eq!(Parameter::header("X-Ids", Schema::new_string()).serialize_value(&json!({"a": 1})), "a,1");
+    }
+
+    #[test]
+    fn test_content() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/search": {"get": {
+                "parameters": [
+                    {"name": "filter", "in": "query", "content": {"application/json": {"schema": {
+                        "type": "object", "properties": {"max": {"type": "integer"}}, "required": ["max"]
+                    }}}},
+                    {"name": "note", "in": "query", "content": {"text/plain": {}}}
+                ],
+                "responses": {}
+            }}}
+        })).unwrap();
+        let op = spec.resolved().operation("/search", "get").unwrap();
+        let parameters = op.parameters().unwrap();
+        let (filter, note) = (parameters[0], parameters[1]);
+        assert_eq!(filter.format.media_type().unwrap().0, "application/json");
+        let value = json!({"max": 5});
+        assert_eq!(filter.format.serialize_content(&value).unwrap(), r#"{"max":5}"#);
+        assert_eq!(filter.serialize_value(&value), "filter=%7B%22max%22%3A5%7D");
+        assert_eq!(filter.format.parse_content(r#"{"max":5}"#).unwrap(), value);
+        assert!(filter.format.validate_content(&spec, &value).is_ok());
+        assert_eq!(filter.format.validate_content(&spec, &json!({})).unwrap_err()[0].keyword, "required");
+
+        assert_eq!(note.serialize_value(&json!("a b")), "note=a%20b");
+        assert!(matches!(note.format.serialize_content(&json!([1])), Err(ContentError::NotText)));
+        assert_eq!(op.parse_query("note=a+b&filter=%7B%22max%22%3A5%7D").unwrap()["note"], json!("a b"));
+        assert!(matches!(Parameter::query("q", Schema::new_string()).format.parse_content("1"), Err(ContentError::NoContent)));
+    }
+
+    #[test]
+    fn test_percent_encoding() {
+        assert_eq!(percent_encode("a b/c%2F#", false), "a%20b%2Fc%252F%23");
+        assert_eq!(percent_encode("a b/c%2F#%zz", true), "a%20b/c%2F%23%25zz");
+        assert_eq!(percent_decode("a+b%2Bc%zz", true), "a b+c%zz");
+        assert_eq!(percent_decode("a+b", false), "a+b");
+
+        let mut query = Parameter::query("q", Schema::new_string());
+        if let ParameterKind::Query { allow_reserved, .. } = &mut query.kind {
+            *allow_reserved = true;
+        }
+        assert_eq!(query.encode("/files?x=1"), "/files?x=1");
+        assert_eq!(query.decode("a+b"), "a b");
+        let path = Parameter::path("p", Schema::new_string());
+        assert_eq!(path.encode("/files?x=1"), "%2Ffiles%3Fx%3D1");
+        assert_eq!(path.decode("a+b%20c"), "a+b c");
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::marker::PhantomData;
 
 use crate::*;
@@ -11,6 +13,8 @@ use serde::{Deserialize, Deserializer, Serialize};
 /// viewer but they will not know which operations and
 /// parameters are available.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PathItem {
     /// An optional, string summary, intended to apply to all operations in
     /// this path.
@@ -36,6 +40,11 @@ pub struct PathItem {
     pub patch: Option<Operation>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trace: Option<Operation>,
+    /// Operations for methods other than the eight above, such as `QUERY` or WebDAV's
+    /// `PROPFIND`, keyed by method name as written. Read and written as the
+    /// `additionalOperations` field of OpenAPI 3.2.
+    #[serde(rename = "additionalOperations", default, skip_serializing_if = "IndexMap::is_empty")]
+    pub additional_operations: IndexMap<String, Operation>,
     /// An alternative server array to service all operations in this path.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub servers: Vec<Server>,
@@ -51,11 +60,14 @@ pub struct PathItem {
     pub parameters: Vec<RefOr<Parameter>>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
 impl PathItem {
-    /// Returns an iterator of references to the [Operation]s in the [PathItem].
+    /// Returns an iterator of references to the [Operation]s in the [PathItem],
+    /// standard methods first, then [PathItem::additional_operations], by their
+    /// keys, e.g. `get`. [PathItem::iter_methods] gives their [Method]s instead.
     pub fn iter(&self) -> impl Iterator<Item=(&str, &'_ Operation)> {
         vec![
             ("get", &self.get),
@@ -69,6 +81,7 @@ impl PathItem {
         ]
             .into_iter()
             .filter_map(|(method, maybe_op)| maybe_op.as_ref().map(|op| (method, op)))
+            .chain(self.additional_operations.iter().map(|(method, op)| (method.as_str(), op)))
     }
 
     pub fn iter_mut(&mut self) -> impl Iterator<Item=(&str, &'_ mut Operation)> {
@@ -84,6 +97,73 @@ impl PathItem {
         ]
             .into_iter()
             .filter_map(|(method, maybe_op)| maybe_op.as_mut().map(|op| (method, op)))
+            .chain(self.additional_operations.iter_mut().map(|(method, op)| (method.as_str(), op)))
+    }
+
+    /// Removes the operation for `method` (its key, e.g. `get`, in any case for
+    /// the eight methods with fields), returning it.
+    pub fn remove_operation(&mut self, method: &str) -> Option<Operation> {
+        match field_of(method).unwrap_or(method) {
+            "get" => self.get.take(),
+            "put" => self.put.take(),
+            "post" => self.post.take(),
+            "delete" => self.delete.take(),
+            "options" => self.options.take(),
+            "head" => self.head.take(),
+            "patch" => self.patch.take(),
+            "trace" => self.trace.take(),
+            method => self.additional_operations.shift_remove(method),
+        }
+    }
+
+    /// Sets the operation for `method` (its key, e.g. `get`, in any case for the
+    /// eight methods with fields), returning any it replaces. Other methods go in
+    /// [PathItem::additional_operations] as written.
+    pub fn insert_operation(&mut self, method: &str, operation: Operation) -> Option<Operation> {
+        match field_of(method).unwrap_or(method) {
+            "get" => self.get.replace(operation),
+            "put" => self.put.replace(operation),
+            "post" => self.post.replace(operation),
+            "delete" => self.delete.replace(operation),
+            "options" => self.options.replace(operation),
+            "head" => self.head.replace(operation),
+            "patch" => self.patch.replace(operation),
+            "trace" => self.trace.replace(operation),
+            method => self.additional_operations.insert(method.to_string(), operation),
+        }
+    }
+
+    /// Returns an iterator of the [Operation]s in the [PathItem] with their
+    /// [Method]s, in the order of [PathItem::iter]. Additional operations whose
+    /// key isn't a valid method name are skipped.
+    pub fn iter_methods(&self) -> impl Iterator<Item=(Method, &'_ Operation)> {
+        self.iter().filter_map(|(method, op)| Some((method_of(method)?, op)))
+    }
+
+    pub fn iter_methods_mut(&mut self) -> impl Iterator<Item=(Method, &'_ mut Operation)> {
+        self.iter_mut().filter_map(|(method, op)| Some((method_of(method)?, op)))
+    }
+
+    /// The operation for `method`, if any.
+    pub fn get_op(&self, method: &Method) -> Option<&Operation> {
+        let key = key_of(method);
+        self.iter().find(|(m, _)| *m == key).map(|(_, op)| op)
+    }
+
+    pub fn get_op_mut(&mut self, method: &Method) -> Option<&mut Operation> {
+        let key = key_of(method);
+        self.iter_mut().find(|(m, _)| *m == key).map(|(_, op)| op)
+    }
+
+    /// Sets the operation for `method`, returning any it replaces. Methods other
+    /// than the eight with fields go in [PathItem::additional_operations].
+    pub fn set_op(&mut self, method: Method, operation: Operation) -> Option<Operation> {
+        self.insert_operation(key_of(&method), operation)
+    }
+
+    /// Removes the operation for `method`, returning it.
+    pub fn take_op(&mut self, method: &Method) -> Option<Operation> {
+        self.remove_operation(key_of(method))
     }
 
     pub fn get(operation: Operation) -> Self {
@@ -101,8 +181,47 @@ impl PathItem {
     }
 }
 
+/// The key of `method` in a [PathItem]: the lowercase field name for the eight
+/// methods with fields, the method name as written for others.
+pub(crate) fn key_of(method: &Method) -> &str {
+    match *method {
+        Method::GET => "get",
+        Method::PUT => "put",
+        Method::POST => "post",
+        Method::DELETE => "delete",
+        Method::OPTIONS => "options",
+        Method::HEAD => "head",
+        Method::PATCH => "patch",
+        Method::TRACE => "trace",
+        _ => method.as_str(),
+    }
+}
+
+/// The field of a method that has one, matched case-insensitively, e.g. `get` for `GET`.
+fn field_of(method: &str) -> Option<&'static str> {
+    ["get", "put", "post", "delete", "options", "head", "patch", "trace"]
+        .iter()
+        .copied()
+        .find(|field| field.eq_ignore_ascii_case(method))
+}
+
+/// The inverse of [key_of], `None` for a key that isn't a method name.
+fn method_of(key: &str) -> Option<Method> {
+    Some(match key {
+        "get" => Method::GET,
+        "put" => Method::PUT,
+        "post" => Method::POST,
+        "delete" => Method::DELETE,
+        "options" => Method::OPTIONS,
+        "head" => Method::HEAD,
+        "patch" => Method::PATCH,
+        "trace" => Method::TRACE,
+        key => Method::from_bytes(key.as_bytes()).ok()?,
+    })
+}
+
 impl IntoIterator for PathItem {
-    type Item = (&'static str, Operation);
+    type Item = (Cow<'static, str>, Operation);
 
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
@@ -119,7 +238,8 @@ impl IntoIterator for PathItem {
             ("trace", self.trace),
         ]
             .into_iter()
-            .filter_map(|(method, maybe_op)| maybe_op.map(|op| (method, op)))
+            .filter_map(|(method, maybe_op)| maybe_op.map(|op| (Cow::Borrowed(method), op)))
+            .chain(self.additional_operations.into_iter().map(|(method, op)| (Cow::Owned(method), op)))
             .collect::<Vec<_>>()
             .into_iter()
     }
@@ -130,12 +250,15 @@ impl IntoIterator for PathItem {
 /// Server Object in order to construct the full URL. The Paths
 /// MAY be empty, due to ACL constraints.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Paths {
     /// A map of PathItems or references to them.
     #[serde(flatten, deserialize_with = "deserialize_paths")]
     pub paths: IndexMap<String, RefOr<PathItem>>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
@@ -158,23 +281,62 @@ impl Paths {
         self.paths.insert(key, RefOr::Item(path_item))
     }
 
-    pub fn insert_operation(&mut self, path: String, method: Method, operation: Operation) -> Option<Operation> {
-        let item = self.paths.entry(path).or_default();
-        let item = item.as_mut().expect("Currently don't support references for PathItem");
-        match method {
-            Method::GET => item.get.replace(operation),
-            Method::PUT => item.put.replace(operation),
-            Method::POST => item.post.replace(operation),
-            Method::DELETE => item.delete.replace(operation),
-            Method::PATCH => item.patch.replace(operation),
-            Method::HEAD => item.head.replace(operation),
-            Method::OPTIONS => item.options.replace(operation),
-            Method::TRACE => item.trace.replace(operation),
-            _ => panic!("Unsupported method: {:?}", method),
+    /// Sets the operation for `method` on `path`, adding the path if need be,
+    /// and returns any it replaces. Fails if the path's item is a `$ref`.
+    pub fn insert_operation(&mut self, path: String, method: Method, operation: Operation) -> Result<Option<Operation>, InsertError> {
+        match self.paths.entry(path).or_default() {
+            RefOr::Item(item) => Ok(item.set_op(method, operation)),
+            RefOr::Reference { reference } => Err(InsertError::PathIsReference(reference.clone())),
+        }
+    }
+
+    /// The operation for `method` on `path`, `None` if there's none or the path's
+    /// item is a `$ref`.
+    pub fn get_op(&self, path: &str, method: &Method) -> Option<&Operation> {
+        self.paths.get(path)?.as_item()?.get_op(method)
+    }
+
+    pub fn get_op_mut(&mut self, path: &str, method: &Method) -> Option<&mut Operation> {
+        self.paths.get_mut(path)?.as_mut()?.get_op_mut(method)
+    }
+
+    /// Removes the operation for `method` on `path`, returning it. The path is
+    /// kept, even if left without operations.
+    pub fn take_op(&mut self, path: &str, method: &Method) -> Option<Operation> {
+        self.paths.get_mut(path)?.as_mut()?.take_op(method)
+    }
+
+    /// Consumes the paths into their operations, with path and [Method], in
+    /// document order. Path items that are `$ref`s are skipped, as are additional
+    /// operations whose key isn't a valid method name.
+    pub fn into_operations(self) -> impl Iterator<Item=(String, Method, Operation)> {
+        self.paths
+            .into_iter()
+            .filter_map(|(path, item)| item.into_item().map(|item| (path, item)))
+            .flat_map(|(path, item)| {
+                item.into_iter()
+                    .filter_map(move |(method, op)| Some((path.clone(), method_of(&method)?, op)))
+            })
+    }
+}
+
+/// Why [Paths::insert_operation] couldn't add an operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertError {
+    /// The path's item is a `$ref`, to this target, which isn't followed.
+    PathIsReference(String),
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InsertError::PathIsReference(reference) => write!(f, "the path item is a reference to {}", reference),
         }
     }
 }
 
+impl std::error::Error for InsertError {}
+
 impl IntoIterator for Paths {
     type Item = (String, RefOr<PathItem>);
 
@@ -220,10 +382,61 @@ mod tests {
         assert_eq!(path_item.iter().collect::<Vec<_>>(), expected);
 
         let expected = vec![
-            ("get", operation.clone()),
-            ("post", operation.clone()),
-            ("delete", operation.clone()),
+            ("get".into(), operation.clone()),
+            ("post".into(), operation.clone()),
+            ("delete".into(), operation.clone()),
         ];
         assert_eq!(path_item.into_iter().collect::<Vec<_>>(), expected);
     }
+
+    #[test]
+    fn test_additional_operations() {
+        let mut paths = Paths::default();
+        paths.insert_operation("/a".to_string(), Method::GET, Operation::default()).unwrap();
+        let query = Method::from_bytes(b"QUERY").unwrap();
+        paths.insert_operation("/a".to_string(), query, Operation { summary: Some("q".to_string()), ..Default::default() }).unwrap();
+        let item = paths["/a"].as_item().unwrap();
+        assert_eq!(item.iter().map(|(method, _)| method).collect::<Vec<_>>(), vec!["get", "QUERY"]);
+
+        let value = serde_json::to_value(item).unwrap();
+        assert_eq!(value["additionalOperations"]["QUERY"]["summary"], "q");
+        let item: PathItem = serde_json::from_value(value).unwrap();
+        let methods: Vec<_> = item.into_iter().map(|(method, _)| method).collect();
+        assert_eq!(methods, vec!["get", "QUERY"]);
+    }
+
+    #[test]
+    fn test_standard_method_case() {
+        let mut item = PathItem::default();
+        assert!(item.insert_operation("GET", Operation::default()).is_none());
+        assert!(item.insert_operation("get", Operation::default()).is_some());
+        assert!(item.get.is_some() && item.additional_operations.is_empty());
+        assert!(item.remove_operation("Get").is_some());
+        item.insert_operation("Query", Operation::default());
+        assert!(item.remove_operation("QUERY").is_none());
+        assert_eq!(item.additional_operations.keys().collect::<Vec<_>>(), ["Query"]);
+    }
+
+    #[test]
+    fn test_method_accessors() {
+        let mut paths = Paths::default();
+        let query = Method::from_bytes(b"QUERY").unwrap();
+        assert_eq!(paths.insert_operation("/a".to_string(), Method::GET, Operation::default()), Ok(None));
+        paths.insert_operation("/a".to_string(), query.clone(), Operation::default()).unwrap();
+        assert!(paths.get_op("/a", &Method::GET).is_some());
+        assert!(paths.get_op("/a", &Method::POST).is_none());
+        assert!(paths.get_op("/b", &Method::GET).is_none());
+        let item = paths["/a"].as_item().unwrap();
+        assert_eq!(item.iter_methods().map(|(method, _)| method).collect::<Vec<_>>(), vec![Method::GET, query.clone()]);
+
+        assert!(paths.take_op("/a", &query).is_some());
+        assert!(paths.get_op("/a", &query).is_none());
+
+        paths.paths.insert("/ref".to_string(), RefOr::ref_("#/components/pathItems/Ref"));
+        assert_eq!(
+            paths.insert_operation("/ref".to_string(), Method::GET, Operation::default()),
+            Err(InsertError::PathIsReference("#/components/pathItems/Ref".to_string())),
+        );
+        assert!(paths.get_op("/ref", &Method::GET).is_none());
+    }
 }
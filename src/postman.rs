@@ -0,0 +1,631 @@
+use std::convert::TryFrom;
+
+use anyhow::{anyhow, Result};
+use http::Method;
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::example::{declared_example, parameter_example};
+use crate::infer::{scalar_schema, widen};
+use crate::*;
+
+const SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+impl OpenAPI {
+    /// Exports the spec as a Postman v2.1 collection, ready to import.
+    ///
+    /// Requests are grouped into a folder per tag, under the first tag of each
+    /// operation, and untagged operations are left at the top level. The first
+    /// server becomes the `baseUrl` variable, alongside its server variables. The
+    /// first scheme of the first security requirement, at the top level and on
+    /// operations overriding it, becomes the matching Postman auth, with secrets
+    /// left as variables to fill in. Parameters and bodies are
+    /// prefilled from their examples, or the examples and defaults of their schemas.
+    pub fn to_postman_collection(&self) -> Result<Value> {
+        let view = self.resolved();
+        let mut folders = IndexMap::<&str, Vec<Value>>::new();
+        let mut items = Vec::new();
+        for op in view.operations() {
+            let request = request(self, &op)?;
+            match op.operation.tags.first() {
+                Some(tag) => folders.entry(tag.as_str()).or_default().push(request),
+                None => items.push(request),
+            }
+        }
+        let folders = folders.into_iter().map(|(name, item)| {
+            let mut folder = json!({"name": name, "item": item});
+            if let Some(description) = self.tags.iter().find(|t| t.name == name).and_then(|t| t.description.as_ref()) {
+                folder["description"] = json!(description);
+            }
+            folder
+        });
+        let items: Vec<Value> = folders.chain(items).collect();
+
+        let mut info = json!({"name": self.info.title, "schema": SCHEMA});
+        if let Some(description) = &self.info.description {
+            info["description"] = json!(description);
+        }
+        let mut collection = json!({"info": info, "item": items, "variable": variables(&self.servers)});
+        if let Some(auth) = auth(self, &self.security) {
+            collection["auth"] = auth;
+        }
+        Ok(collection)
+    }
+}
+
+fn variables(servers: &[Server]) -> Vec<Value> {
+    let Some(server) = servers.first() else { return Vec::new() };
+    let mut variables = vec![json!({"key": "baseUrl", "value": template(&server.url)})];
+    for (name, variable) in server.variables.iter().flatten() {
+        variables.push(json!({"key": name, "value": variable.default}));
+    }
+    variables
+}
+
+fn request(spec: &OpenAPI, op: &ResolvedOperation) -> Result<Value> {
+    let mut query = Vec::new();
+    let mut headers = Vec::new();
+    let mut path_variables = Vec::new();
+    for parameter in op.parameters()? {
+        let value = parameter_example(spec, parameter).map_or_else(String::new, |v| text(&v));
+        let mut entry = json!({"key": parameter.name, "value": value});
+        if let Some(description) = &parameter.description {
+            entry["description"] = json!(description);
+        }
+        match parameter.kind {
+            ParameterKind::Query { .. } => {
+                if !parameter.required {
+                    entry["disabled"] = json!(true);
+                }
+                query.push(entry);
+            }
+            ParameterKind::Header { .. } => headers.push(entry),
+            ParameterKind::Path { .. } => path_variables.push(entry),
+            // Postman manages cookies outside of the request.
+            ParameterKind::Cookie { .. } => {}
+        }
+    }
+
+    let path: Vec<String> = op.path.trim_start_matches('/').split('/')
+        .map(|segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(name) => format!(":{}", name),
+            None => segment.to_string(),
+        })
+        .collect();
+    let mut raw = format!("{{{{baseUrl}}}}/{}", path.join("/"));
+    if !query.is_empty() {
+        let pairs: Vec<String> = query.iter().filter(|q| q.get("disabled").is_none())
+            .map(|q| format!("{}={}", q["key"].as_str().unwrap_or_default(), q["value"].as_str().unwrap_or_default()))
+            .collect();
+        if !pairs.is_empty() {
+            raw = format!("{}?{}", raw, pairs.join("&"));
+        }
+    }
+    let mut url = json!({"raw": raw, "host": ["{{baseUrl}}"], "path": path});
+    if !query.is_empty() {
+        url["query"] = json!(query);
+    }
+    if !path_variables.is_empty() {
+        url["variable"] = json!(path_variables);
+    }
+
+    let mut request = json!({"method": op.method.to_uppercase(), "header": headers, "url": url});
+    if let Some(description) = op.operation.description.as_ref().or(op.operation.summary.as_ref()) {
+        request["description"] = json!(description);
+    }
+    if let Some((content_type, media)) = op.request_body()?.and_then(|body| body.content.first()) {
+        request["header"].as_array_mut().expect("header is an array")
+            .push(json!({"key": "Content-Type", "value": content_type}));
+        let sample = declared_example(spec, media.example.as_ref(), &media.examples, media.schema.as_ref());
+        request["body"] = body(content_type, sample);
+    }
+    if let Some(security) = &op.operation.security {
+        // An empty list makes the operation public, overriding the collection's auth.
+        request["auth"] = auth(spec, security).unwrap_or_else(|| json!({"type": "noauth"}));
+    }
+
+    let name = op.operation.summary.clone()
+        .or_else(|| op.operation.operation_id.clone())
+        .unwrap_or_else(|| format!("{} {}", op.method.to_uppercase(), op.path));
+    Ok(json!({"name": name, "request": request}))
+}
+
+fn body(content_type: &str, sample: Option<Value>) -> Value {
+    if content_type == "application/x-www-form-urlencoded" || content_type == "multipart/form-data" {
+        let mode = if content_type == "multipart/form-data" { "formdata" } else { "urlencoded" };
+        let fields: Vec<Value> = match sample {
+            Some(Value::Object(fields)) => fields.iter().map(|(k, v)| json!({"key": k, "value": text(v), "type": "text"})).collect(),
+            _ => Vec::new(),
+        };
+        return json!({"mode": mode, mode: fields});
+    }
+    let raw = match &sample {
+        None => String::new(),
+        Some(Value::String(s)) if !content_type.contains("json") => s.clone(),
+        Some(value) => serde_json::to_string_pretty(value).expect("values serialize"),
+    };
+    let mut body = json!({"mode": "raw", "raw": raw});
+    if content_type.contains("json") {
+        body["options"] = json!({"raw": {"language": "json"}});
+    } else if content_type.contains("xml") {
+        body["options"] = json!({"raw": {"language": "xml"}});
+    }
+    body
+}
+
+/// The Postman auth of the first security requirement, or `None` when there are
+/// none, its schemes aren't declared or it's mutual TLS.
+fn auth(spec: &OpenAPI, security: &[SecurityRequirement]) -> Option<Value> {
+    let (name, scopes) = security.first()?.first()?;
+    let scheme = spec.security_schemes.get(name)?.as_item()?;
+    let pair = |key: &str, value: &str| json!({"key": key, "value": value, "type": "string"});
+    let auth = match scheme {
+        SecurityScheme::APIKey { location, name, .. } => {
+            let location = match location {
+                APIKeyLocation::Query => "query",
+                // Postman has no cookie location, the header being the closest.
+                APIKeyLocation::Header | APIKeyLocation::Cookie => "header",
+            };
+            json!({"type": "apikey", "apikey": [pair("key", name), pair("value", "{{apiKey}}"), pair("in", location)]})
+        }
+        SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => {
+            json!({"type": "basic", "basic": [pair("username", "{{username}}"), pair("password", "{{password}}")]})
+        }
+        SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("digest") => {
+            json!({"type": "digest", "digest": [pair("username", "{{username}}"), pair("password", "{{password}}")]})
+        }
+        SecurityScheme::HTTP { .. } => json!({"type": "bearer", "bearer": [pair("token", "{{bearerToken}}")]}),
+        SecurityScheme::OAuth2 { flows, .. } => {
+            let mut settings = vec![pair("accessToken", "{{accessToken}}"), pair("addTokenTo", "header")];
+            if !scopes.is_empty() {
+                settings.push(pair("scope", &scopes.join(" ")));
+            }
+            if let Some(flow) = &flows.authorization_code {
+                settings.push(pair("grant_type", "authorization_code"));
+                settings.push(pair("authUrl", &flow.authorization_url));
+                settings.push(pair("accessTokenUrl", &flow.token_url));
+            } else if let Some(flow) = &flows.client_credentials {
+                settings.push(pair("grant_type", "client_credentials"));
+                settings.push(pair("accessTokenUrl", &flow.token_url));
+            } else if let Some(flow) = &flows.password {
+                settings.push(pair("grant_type", "password_credentials"));
+                settings.push(pair("accessTokenUrl", &flow.token_url));
+            } else if let Some(flow) = &flows.implicit {
+                settings.push(pair("grant_type", "implicit"));
+                settings.push(pair("authUrl", &flow.authorization_url));
+            }
+            json!({"type": "oauth2", "oauth2": settings})
+        }
+        SecurityScheme::OpenIDConnect { .. } => {
+            json!({"type": "oauth2", "oauth2": [pair("accessToken", "{{accessToken}}"), pair("addTokenTo", "header")]})
+        }
+
+        // Postman sends client certificates by host, from its settings.
+        SecurityScheme::MutualTLS { .. } => return None,
+    };
+    Some(auth)
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Turns the `{name}` placeholders of a server URL into Postman's `{{name}}`.
+fn template(url: &str) -> String {
+    url.replace('{', "{{").replace('}', "}}")
+}
+
+impl OpenAPI {
+    /// Drafts a spec from a Postman v2.1 collection, the reverse of
+    /// [OpenAPI::to_postman_collection], for moving a collection-first API to a spec.
+    ///
+    /// Each request becomes an operation on its URL's path, `:name` and `{{name}}`
+    /// segments becoming path parameters, and is tagged with its folder. Schemas
+    /// are inferred from the example bodies with [Schema::infer], and responses
+    /// from the examples saved with the request. The `baseUrl` variable, else the
+    /// first request's host, becomes the server. Of two requests for the same
+    /// route, the first is kept.
+    pub fn from_postman_collection(collection: &Value) -> Result<OpenAPI> {
+        let info = collection.get("info").ok_or_else(|| anyhow!("Not a Postman collection: missing info"))?;
+        let mut spec = OpenAPI::default();
+        spec.info.title = info.get("name").and_then(Value::as_str).unwrap_or("Untitled").to_string();
+        spec.info.description = description(info);
+        spec.info.version = info.get("version").and_then(Value::as_str).unwrap_or("1.0.0").to_string();
+        let variables = pairs(collection.get("variable"));
+        if let Some((_, url)) = variables.iter().find(|(key, _)| key == "baseUrl") {
+            spec.servers.push(Server { url: untemplate(url), ..Server::default() });
+        }
+        if let Some((scheme, name)) = collection.get("auth").and_then(self::scheme) {
+            spec.security.push(IndexMap::from([(name.clone(), Vec::new())]));
+            spec.components.security_schemes.entry(name).or_insert(RefOr::Item(scheme));
+        }
+        import_items(&mut spec, collection.get("item"), None)?;
+        Ok(spec)
+    }
+}
+
+fn import_items(spec: &mut OpenAPI, items: Option<&Value>, tag: Option<&str>) -> Result<()> {
+    for item in items.and_then(Value::as_array).into_iter().flatten() {
+        let name = item.get("name").and_then(Value::as_str);
+        if let Some(children) = item.get("item") {
+            let name = name.unwrap_or("Untitled");
+            if !spec.tags.iter().any(|t| t.name == name) {
+                spec.tags.push(Tag { name: name.to_string(), description: description(item), ..Tag::default() });
+            }
+            import_items(spec, Some(children), Some(name))?;
+        } else if let Some(request) = item.get("request") {
+            import_request(spec, item, request, name, tag)?;
+        }
+    }
+    Ok(())
+}
+
+fn import_request(spec: &mut OpenAPI, item: &Value, request: &Value, name: Option<&str>, tag: Option<&str>) -> Result<()> {
+    let method = request.get("method").and_then(Value::as_str).unwrap_or("GET");
+    let method = Method::from_bytes(method.to_uppercase().as_bytes())?;
+    let url = request.get("url").or(Some(request).filter(|r| r.is_string()));
+    let (raw, segments) = url_segments(url);
+
+    let mut operation = Operation { summary: name.map(str::to_string), description: description(request), ..Operation::default() };
+    operation.tags.extend(tag.map(str::to_string));
+    let variables = pairs(url.and_then(|url| url.get("variable")));
+    let mut path = String::new();
+    for segment in segments {
+        let name = segment.strip_prefix(':')
+            .or_else(|| segment.strip_prefix("{{").and_then(|s| s.strip_suffix("}}")));
+        path.push('/');
+        match name {
+            Some(name) => {
+                path.push_str(&format!("{{{}}}", name));
+                let value = variables.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+                let mut parameter = Parameter::path(name, value.map_or_else(Schema::new_string, scalar_schema));
+                parameter.example = value.filter(|v| !v.is_empty()).map(|v| Value::String(v.to_string()));
+                operation.parameters.push(RefOr::Item(parameter));
+            }
+            None => path.push_str(&segment),
+        }
+    }
+    if path.is_empty() {
+        path.push('/');
+    }
+    if spec.servers.is_empty() {
+        // An origin that is a variable left undefined by the collection is of no use.
+        if let Some(origin) = raw.as_deref().and_then(origin).filter(|o| !o.starts_with("{{")) {
+            spec.servers.push(Server { url: untemplate(origin), ..Server::default() });
+        }
+    }
+
+    for query in url.and_then(|url| url.get("query")).and_then(Value::as_array).into_iter().flatten() {
+        let Some(key) = query.get("key").and_then(Value::as_str) else { continue };
+        let value = query.get("value").and_then(Value::as_str).unwrap_or_default();
+        let mut parameter = Parameter::query(key, scalar_schema(value));
+        parameter.required = query.get("disabled") != Some(&Value::Bool(true));
+        parameter.description = description(query);
+        parameter.example = Some(value).filter(|v| !v.is_empty()).map(|v| Value::String(v.to_string()));
+        operation.parameters.push(RefOr::Item(parameter));
+    }
+    let headers = request.get("header").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    let mut content_type = None;
+    for header in headers {
+        let Some(key) = header.get("key").and_then(Value::as_str) else { continue };
+        let value = header.get("value").and_then(Value::as_str).unwrap_or_default();
+        // Described by the request body and security schemes instead.
+        if key.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value);
+            continue;
+        }
+        if key.eq_ignore_ascii_case("accept") || key.eq_ignore_ascii_case("authorization") {
+            continue;
+        }
+        let mut parameter = Parameter::header(key, Schema::new_string());
+        parameter.required = header.get("disabled") != Some(&Value::Bool(true));
+        parameter.example = Some(value).filter(|v| !v.is_empty()).map(|v| Value::String(v.to_string()));
+        operation.parameters.push(RefOr::Item(parameter));
+    }
+
+    if let Some((content_type, media)) = request.get("body").and_then(|body| import_body(body, content_type)) {
+        operation.request_body = Some(RefOr::Item(RequestBody {
+            content: IndexMap::from([(content_type, media)]),
+            required: true,
+            ..RequestBody::default()
+        }));
+    }
+    for response in item.get("response").and_then(Value::as_array).into_iter().flatten() {
+        import_response(&mut operation, response);
+    }
+    match request.get("auth") {
+        Some(auth) if auth.get("type").and_then(Value::as_str) == Some("noauth") => operation.security = Some(Vec::new()),
+        Some(auth) => {
+            if let Some((scheme, name)) = self::scheme(auth) {
+                operation.security = Some(vec![IndexMap::from([(name.clone(), Vec::new())])]);
+                spec.components.security_schemes.entry(name).or_insert(RefOr::Item(scheme));
+            }
+        }
+        None => {}
+    }
+
+    let exists = spec.paths.get(&path).and_then(RefOr::as_item)
+        .is_some_and(|item| item.iter().any(|(m, _)| m.eq_ignore_ascii_case(method.as_str())));
+    if !exists {
+        spec.paths.insert_operation(path, method, operation)?;
+    }
+    Ok(())
+}
+
+fn import_body(body: &Value, content_type: Option<&str>) -> Option<(String, MediaType)> {
+    let mode = body.get("mode").and_then(Value::as_str)?;
+    let (content_type, example) = match mode {
+        "raw" => {
+            let raw = body.get("raw").and_then(Value::as_str).unwrap_or_default();
+            let json = serde_json::from_str::<Value>(raw).ok();
+            let language = body.pointer("/options/raw/language").and_then(Value::as_str);
+            let content_type = content_type.map(str::to_string).unwrap_or_else(|| match (language, &json) {
+                (Some("json"), _) | (None, Some(_)) => "application/json".to_string(),
+                (Some("xml"), _) => "application/xml".to_string(),
+                (Some("html"), _) => "text/html".to_string(),
+                _ => "text/plain".to_string(),
+            });
+            let example = match json {
+                Some(json) if content_type.contains("json") => json,
+                _ if raw.is_empty() => return Some((content_type, MediaType::default())),
+                _ => Value::String(raw.to_string()),
+            };
+            (content_type, example)
+        }
+        "urlencoded" | "formdata" => {
+            let content_type = if mode == "formdata" { "multipart/form-data" } else { "application/x-www-form-urlencoded" };
+            let fields = pairs(body.get(mode));
+            let fields = fields.into_iter().map(|(key, value)| (key, Value::String(value))).collect();
+            (content_type.to_string(), Value::Object(fields))
+        }
+        _ => return None,
+    };
+    let media = MediaType { schema: Some(RefOr::Item(Schema::infer(&example))), example: Some(example), ..MediaType::default() };
+    Some((content_type, media))
+}
+
+/// Adds a saved example response, widening the schema of any earlier example with
+/// the same status.
+fn import_response(operation: &mut Operation, response: &Value) {
+    let code = response.get("code").and_then(Value::as_u64).and_then(|c| u16::try_from(c).ok()).unwrap_or(200);
+    let body = response.get("body").and_then(Value::as_str).unwrap_or_default();
+    let headers = pairs(response.get("header"));
+    let content_type = headers.iter().find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.split(';').next().unwrap_or_default().trim().to_string());
+    let json = serde_json::from_str::<Value>(body).ok();
+    let content_type = content_type.or_else(|| json.as_ref().map(|_| "application/json".to_string()));
+    let entry = operation.responses.responses.entry(StatusCode::Code(code)).or_insert_with(|| {
+        let description = response.get("status").or_else(|| response.get("name")).and_then(Value::as_str).unwrap_or_default();
+        RefOr::Item(Response { description: description.to_string(), ..Response::default() })
+    });
+    let (Some(content_type), Some(response)) = (content_type, entry.as_mut()) else { return };
+    let example = match json {
+        Some(json) if content_type.contains("json") => json,
+        _ => Value::String(body.to_string()),
+    };
+    let media = response.content.entry(content_type).or_default();
+    let schema = match media.schema.take() {
+        Some(RefOr::Item(schema)) => widen(schema, Schema::infer(&example)),
+        _ => Schema::infer(&example),
+    };
+    media.schema = Some(RefOr::Item(schema));
+    media.example.get_or_insert(example);
+}
+
+/// The security scheme of a Postman auth, with the name to declare it under.
+fn scheme(auth: &Value) -> Option<(SecurityScheme, String)> {
+    let typ = auth.get("type").and_then(Value::as_str)?;
+    let settings = pairs(auth.get(typ));
+    let setting = |key: &str| settings.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+    let http = |scheme: &str| SecurityScheme::HTTP { scheme: scheme.to_string(), bearer_format: None, description: None };
+    let scheme = match typ {
+        "bearer" => (http("bearer"), "bearerAuth"),
+        "basic" => (http("basic"), "basicAuth"),
+        "digest" => (http("digest"), "digestAuth"),
+        "apikey" => {
+            let location = match setting("in").as_deref() {
+                Some("query") => APIKeyLocation::Query,
+                _ => APIKeyLocation::Header,
+            };
+            let name = setting("key").unwrap_or_else(|| "X-API-Key".to_string());
+            (SecurityScheme::APIKey { location, name, description: None }, "apiKey")
+        }
+        _ => return None,
+    };
+    Some((scheme.0, scheme.1.to_string()))
+}
+
+/// The `{key, value}` entries Postman uses for variables, headers and form
+/// fields, leaving out those without a key.
+fn pairs(values: Option<&Value>) -> Vec<(String, String)> {
+    values.and_then(Value::as_array).into_iter().flatten().filter_map(|entry| {
+        let key = entry.get("key").and_then(Value::as_str)?;
+        let value = entry.get("value").map(text).unwrap_or_default();
+        Some((key.to_string(), value))
+    }).collect()
+}
+
+/// A description, which Postman writes either as a string or as `{content}`.
+fn description(value: &Value) -> Option<String> {
+    match value.get("description")? {
+        Value::String(s) => Some(s.clone()),
+        description => description.get("content").and_then(Value::as_str).map(str::to_string),
+    }
+}
+
+/// The raw URL, and the segments of its path.
+fn url_segments(url: Option<&Value>) -> (Option<String>, Vec<String>) {
+    let raw = url.and_then(|url| url.as_str().or_else(|| url.get("raw")?.as_str())).map(str::to_string);
+    if let Some(path) = url.and_then(|url| url.get("path")).and_then(Value::as_array) {
+        let segments = path.iter().filter_map(|segment| segment.as_str().or_else(|| segment.get("value")?.as_str()))
+            .map(str::to_string)
+            .collect();
+        return (raw, segments);
+    }
+    let Some(raw) = raw else { return (None, Vec::new()) };
+    let rest = raw.split(['?', '#']).next().unwrap_or_default();
+    let rest = match origin(rest) {
+        Some(origin) => &rest[origin.len()..],
+        None => rest,
+    };
+    let segments = rest.split('/').filter(|s| !s.is_empty()).map(str::to_string).collect();
+    (Some(raw), segments)
+}
+
+/// The scheme and host of a raw URL, or the variable it starts with.
+fn origin(raw: &str) -> Option<&str> {
+    if raw.starts_with("{{") {
+        let end = raw.find("}}")? + 2;
+        return Some(&raw[..end]);
+    }
+    let start = raw.find("://")? + 3;
+    let end = raw[start..].find(['/', '?', '#']).map_or(raw.len(), |i| start + i);
+    Some(&raw[..end])
+}
+
+/// Turns Postman's `{{name}}` variables back into `{name}` placeholders.
+fn untemplate(url: &str) -> String {
+    url.replace("{{", "{").replace("}}", "}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_postman_collection() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets", "version": "1"},
+            "servers": [{"url": "https://{env}.example.com/v1", "variables": {"env": {"default": "api"}}}],
+            "security": [{"token": []}],
+            "tags": [{"name": "pets", "description": "Everything about pets"}],
+            "paths": {
+                "/pets/{id}": {
+                    "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "integer", "example": 7}}],
+                    "get": {
+                        "tags": ["pets"],
+                        "summary": "Get a pet",
+                        "parameters": [{"name": "fields", "in": "query", "schema": {"type": "string"}}],
+                        "responses": {}
+                    },
+                    "put": {
+                        "tags": ["pets"],
+                        "operationId": "updatePet",
+                        "requestBody": {"content": {"application/json": {"example": {"name": "Rex"}}}},
+                        "responses": {}
+                    }
+                },
+                "/health": {"get": {"security": [], "responses": {}}}
+            },
+            "components": {"securitySchemes": {"token": {"type": "http", "scheme": "bearer"}}}
+        })).unwrap();
+        let collection = spec.to_postman_collection().unwrap();
+        assert_eq!(collection["info"]["schema"], SCHEMA);
+        assert_eq!(collection["variable"], json!([
+            {"key": "baseUrl", "value": "https://{{env}}.example.com/v1"},
+            {"key": "env", "value": "api"}
+        ]));
+        assert_eq!(collection["auth"]["type"], "bearer");
+
+        let folder = &collection["item"][0];
+        assert_eq!(folder["name"], "pets");
+        assert_eq!(folder["description"], "Everything about pets");
+        let get = &folder["item"][0];
+        assert_eq!(get["name"], "Get a pet");
+        assert_eq!(get["request"]["url"]["raw"], "{{baseUrl}}/pets/:id");
+        assert_eq!(get["request"]["url"]["variable"], json!([{"key": "id", "value": "7"}]));
+        assert_eq!(get["request"]["url"]["query"], json!([{"key": "fields", "value": "", "disabled": true}]));
+        assert!(get["request"].get("auth").is_none());
+
+        let put = &folder["item"][1];
+        assert_eq!(put["name"], "updatePet");
+        assert_eq!(put["request"]["method"], "PUT");
+        assert_eq!(put["request"]["header"], json!([{"key": "Content-Type", "value": "application/json"}]));
+        assert_eq!(put["request"]["body"]["raw"], "{\n  \"name\": \"Rex\"\n}");
+
+        let health = &collection["item"][1];
+        assert_eq!(health["name"], "GET /health");
+        assert_eq!(health["request"]["auth"], json!({"type": "noauth"}));
+    }
+
+    #[test]
+    fn test_from_postman_collection() {
+        let collection = json!({
+            "info": {"name": "Shop", "description": {"content": "The shop API"}, "schema": SCHEMA},
+            "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}"}]},
+            "variable": [{"key": "baseUrl", "value": "https://{{env}}.shop.com"}],
+            "item": [{
+                "name": "orders",
+                "item": [{
+                    "name": "Get an order",
+                    "request": {
+                        "method": "GET",
+                        "header": [{"key": "Accept", "value": "application/json"}, {"key": "X-Trace", "value": "abc"}],
+                        "url": {
+                            "raw": "{{baseUrl}}/orders/:id?expand=true",
+                            "path": ["orders", ":id"],
+                            "query": [{"key": "expand", "value": "true"}, {"key": "page", "value": "2", "disabled": true}],
+                            "variable": [{"key": "id", "value": "42"}]
+                        }
+                    },
+                    "response": [
+                        {"code": 200, "status": "OK", "header": [{"key": "Content-Type", "value": "application/json"}], "body": "{\"id\": 42, \"note\": \"x\"}"},
+                        {"code": 200, "status": "OK", "body": "{\"id\": 43}"}
+                    ]
+                }, {
+                    "name": "Create an order",
+                    "request": {
+                        "method": "POST",
+                        "auth": {"type": "noauth"},
+                        "url": "https://api.shop.com/orders",
+                        "body": {"mode": "raw", "raw": "{\"item\": \"book\", \"count\": 1}", "options": {"raw": {"language": "json"}}}
+                    }
+                }]
+            }]
+        });
+        let spec = OpenAPI::from_postman_collection(&collection).unwrap();
+        assert_eq!(spec.info.title, "Shop");
+        assert_eq!(spec.info.description.as_deref(), Some("The shop API"));
+        assert_eq!(spec.servers[0].url, "https://{env}.shop.com");
+        assert_eq!(spec.tags[0].name, "orders");
+        assert!(matches!(spec.security_schemes["bearerAuth"].as_item(), Some(SecurityScheme::HTTP { scheme, .. }) if scheme == "bearer"));
+
+        let item = spec.paths["/orders/{id}"].as_item().unwrap();
+        let get = item.get.as_ref().unwrap();
+        assert_eq!(get.tags, vec!["orders"]);
+        let parameters: Vec<_> = get.parameters.iter().map(|p| {
+            let p = p.as_item().unwrap();
+            (p.name.as_str(), p.required, serde_json::to_value(p.format.clone()).unwrap())
+        }).collect();
+        assert_eq!(parameters, vec![
+            ("id", true, json!({"schema": {"type": "integer"}})),
+            ("expand", true, json!({"schema": {"type": "boolean"}})),
+            ("page", false, json!({"schema": {"type": "integer"}})),
+            ("X-Trace", true, json!({"schema": {"type": "string"}})),
+        ]);
+        let ok = get.responses.responses[&StatusCode::Code(200)].as_item().unwrap();
+        assert_eq!(ok.description, "OK");
+        let media = &ok.content["application/json"];
+        assert_eq!(media.example, Some(json!({"id": 42, "note": "x"})));
+        assert_eq!(serde_json::to_value(&media.schema).unwrap(), json!({
+            "type": "object",
+            "properties": {"id": {"type": "integer"}, "note": {"type": "string"}},
+            "required": ["id"]
+        }));
+
+        let post = spec.paths["/orders"].as_item().unwrap().post.as_ref().unwrap();
+        assert_eq!(post.security, Some(Vec::new()));
+        let body = post.request_body.as_ref().unwrap().as_item().unwrap();
+        assert_eq!(body.content["application/json"].example, Some(json!({"item": "book", "count": 1})));
+
+        // What is exported comes back as the same routes.
+        let exported = spec.to_postman_collection().unwrap();
+        let imported = OpenAPI::from_postman_collection(&exported).unwrap();
+        assert_eq!(imported.paths.keys().collect::<Vec<_>>(), spec.paths.keys().collect::<Vec<_>>());
+        assert_eq!(imported.servers[0].url, "https://{env}.shop.com");
+    }
+}
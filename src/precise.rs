@@ -0,0 +1,47 @@
+//! With serde_json's `arbitrary_precision`, non-integral numbers reach buffered
+//! deserializers (`flatten`, `untagged`) in a private map form that `f64` doesn't
+//! accept. These read such fields through [Number], which understands the form.
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Number;
+
+use crate::ExclusiveBound;
+
+fn to_f64<E: serde::de::Error>(n: Number) -> Result<f64, E> {
+    n.as_f64().ok_or_else(|| E::custom(format!("{} is out of range for f64", n)))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Bound {
+    Flag(bool),
+    Value(Number),
+}
+
+impl Bound {
+    fn into_f64<E: serde::de::Error>(self) -> Result<ExclusiveBound<f64>, E> {
+        Ok(match self {
+            Bound::Flag(flag) => ExclusiveBound::Flag(flag),
+            Bound::Value(n) => ExclusiveBound::Value(to_f64(n)?),
+        })
+    }
+}
+
+pub(crate) fn option_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    Option::<Number>::deserialize(deserializer)?.map(to_f64).transpose()
+}
+
+pub(crate) fn option_f64_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Option<f64>>, D::Error> {
+    Vec::<Option<Number>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|n| n.map(to_f64).transpose())
+        .collect()
+}
+
+pub(crate) fn bound<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ExclusiveBound<f64>, D::Error> {
+    Bound::deserialize(deserializer)?.into_f64()
+}
+
+pub(crate) fn option_bound<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<ExclusiveBound<f64>>, D::Error> {
+    Option::<Bound>::deserialize(deserializer)?.map(Bound::into_f64).transpose()
+}
@@ -0,0 +1,169 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use crate::example_validation::child;
+
+/// Wraps a document so fields the model doesn't know (anything that isn't an
+/// `x-` extension or a modeled field, such as keywords from a newer spec version)
+/// survive a deserialize/serialize round trip.
+///
+/// Unknown fields are recorded by location when deserializing and written back on
+/// serialization, as long as the object that held them still exists at the same
+/// location. Fields the model already emits take precedence.
+///
+/// ```
+/// # use openapiv3::{OpenAPI, PreserveUnknown};
+/// let input = r#"{"openapi": "3.0.3", "info": {"title": "t", "version": "1", "summary": "new"}, "paths": {}}"#;
+/// let spec: PreserveUnknown<OpenAPI> = serde_json::from_str(input).unwrap();
+/// let output = serde_json::to_value(&spec).unwrap();
+/// assert_eq!(output["info"]["summary"], "new");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreserveUnknown<T> {
+    pub inner: T,
+    unknown: Vec<UnknownField>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct UnknownField {
+    /// Path from the document root to the object holding the field.
+    parent: Vec<String>,
+    key: String,
+    value: Value,
+}
+
+impl<T> PreserveUnknown<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner, unknown: Vec::new() }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// The retained fields, as `(JSON pointer, value)` pairs.
+    pub fn unknown_fields(&self) -> impl Iterator<Item=(String, &Value)> {
+        self.unknown.iter().map(|f| {
+            let pointer: String = f.parent.iter().chain(std::iter::once(&f.key))
+                .map(|t| child("", t))
+                .collect();
+            (pointer, &f.value)
+        })
+    }
+
+    /// Forgets all retained fields, so serialization writes only what the model holds.
+    pub fn clear_unknown_fields(&mut self) {
+        self.unknown.clear();
+    }
+}
+
+impl<T> std::ops::Deref for PreserveUnknown<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> std::ops::DerefMut for PreserveUnknown<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T> From<T> for PreserveUnknown<T> {
+    fn from(inner: T) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<'de, T: Serialize + DeserializeOwned> Deserialize<'de> for PreserveUnknown<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let original = Value::deserialize(deserializer)?;
+        let inner = T::deserialize(&original).map_err(D::Error::custom)?;
+        let known = serde_json::to_value(&inner).map_err(D::Error::custom)?;
+        let mut unknown = Vec::new();
+        collect_unknown(&original, &known, &mut Vec::new(), &mut unknown);
+        Ok(Self { inner, unknown })
+    }
+}
+
+impl<T: Serialize> Serialize for PreserveUnknown<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serde_json::to_value(&self.inner).map_err(S::Error::custom)?;
+        for field in &self.unknown {
+            let parent = field.parent.iter().try_fold(&mut value, |v, token| match v {
+                Value::Object(map) => map.get_mut(token),
+                Value::Array(items) => token.parse::<usize>().ok().and_then(move |i| items.get_mut(i)),
+                _ => None,
+            });
+            if let Some(Value::Object(map)) = parent {
+                map.entry(field.key.clone()).or_insert_with(|| field.value.clone());
+            }
+        }
+        value.serialize(serializer)
+    }
+}
+
+fn collect_unknown(original: &Value, known: &Value, path: &mut Vec<String>, out: &mut Vec<UnknownField>) {
+    match (original, known) {
+        (Value::Object(original), Value::Object(known)) => {
+            for (key, value) in original {
+                match known.get(key) {
+                    Some(known) => {
+                        path.push(key.clone());
+                        collect_unknown(value, known, path, out);
+                        path.pop();
+                    }
+                    None => out.push(UnknownField { parent: path.clone(), key: key.clone(), value: value.clone() }),
+                }
+            }
+        }
+        (Value::Array(original), Value::Array(known)) => {
+            for (i, (value, known)) in original.iter().zip(known).enumerate() {
+                path.push(i.to_string());
+                collect_unknown(value, known, path, out);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::OpenAPI;
+
+    #[test]
+    fn test_round_trip_unknown_fields() {
+        let input = json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1", "audience": "A future field"},
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "responses": {"200": {"description": "ok"}},
+                        "x-known": 1,
+                        "future": {"a": [1, 2]}
+                    }
+                }
+            },
+            "futureRoot": {"enabled": true}
+        });
+        let mut spec: PreserveUnknown<OpenAPI> = serde_json::from_value(input.clone()).unwrap();
+        let pointers: Vec<String> = spec.unknown_fields().map(|(p, _)| p).collect();
+        assert_eq!(pointers, vec!["/info/audience", "/paths/~1pets/get/future", "/futureRoot"]);
+        assert_eq!(serde_json::to_value(&spec).unwrap(), input);
+
+        spec.info.title = "changed".to_string();
+        spec.paths.paths.clear();
+        let output = serde_json::to_value(&spec).unwrap();
+        assert_eq!(output["info"]["title"], "changed");
+        assert_eq!(output["info"]["audience"], "A future field");
+        assert_eq!(output["paths"], json!({}));
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::dereference::ValueRole;
+use crate::example_validation::{child, unescape};
+use crate::*;
+
+impl OpenAPI {
+    /// Removes the components nothing uses, e.g. after merging documents. What
+    /// the paths, webhooks and top-level `security` refer to is used, and so is
+    /// whatever used components refer to in turn, through `$ref`s, discriminator
+    /// mappings and security requirements. Returns the JSON pointers of the
+    /// removed components, e.g. `/components/schemas/Unused`, section by section.
+    pub fn prune_unused(&mut self) -> Vec<String> {
+        let Ok(Value::Object(mut document)) = serde_json::to_value(&*self) else { return Vec::new() };
+        let components = document.remove("components").unwrap_or_default();
+
+        let mut used = HashSet::new();
+        let mut pending = Vec::new();
+        uses(&Value::Object(document), ValueRole::Object, true, &mut pending);
+        while let Some((section, name)) = pending.pop() {
+            if let Some(component) = components.get(&section).and_then(|s| s.get(&name)) {
+                let role = ValueRole::Object.child(&section).child(&name);
+                if used.insert((section, name)) {
+                    uses(component, role, true, &mut pending);
+                }
+            }
+        }
+
+        let mut removed = Vec::new();
+        let c = &mut self.components;
+        retain(&mut c.schemas, "schemas", &used, &mut removed);
+        retain(&mut c.responses, "responses", &used, &mut removed);
+        retain(&mut c.parameters, "parameters", &used, &mut removed);
+        retain(&mut c.examples, "examples", &used, &mut removed);
+        retain(&mut c.request_bodies, "requestBodies", &used, &mut removed);
+        retain(&mut c.headers, "headers", &used, &mut removed);
+        retain(&mut c.security_schemes, "securitySchemes", &used, &mut removed);
+        retain(&mut c.links, "links", &used, &mut removed);
+        retain(&mut c.callbacks, "callbacks", &used, &mut removed);
+        retain(&mut c.path_items, "pathItems", &used, &mut removed);
+        removed
+    }
+}
+
+fn retain<T>(map: &mut RefOrMap<T>, section: &str, used: &HashSet<(String, String)>, removed: &mut Vec<String>) {
+    map.retain(|name, _| {
+        let keep = used.contains(&(section.to_string(), name.clone()));
+        if !keep {
+            removed.push(child(&child("/components", section), name));
+        }
+        keep
+    });
+}
+
+/// Adds the components `value`, which has the given role, uses to `pending`, as
+/// section and name. `mappings` says whether the targets of discriminator mappings
+/// are used too.
+pub(crate) fn uses(value: &Value, role: ValueRole, mappings: bool, pending: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            if role.is_keywords() {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    pending.extend(component(reference));
+                }
+                let mapping = map.get("discriminator").filter(|_| mappings).and_then(|d| d.get("mapping")).and_then(Value::as_object);
+                for target in mapping.into_iter().flat_map(|m| m.values()).filter_map(Value::as_str) {
+                    if target.starts_with('#') {
+                        pending.extend(component(target));
+                    } else {
+                        pending.push(("schemas".to_string(), target.to_string()));
+                    }
+                }
+                if let Some(Value::Array(requirements)) = map.get("security") {
+                    for scheme in requirements.iter().filter_map(Value::as_object).flat_map(|r| r.keys()) {
+                        pending.push(("securitySchemes".to_string(), scheme.clone()));
+                    }
+                }
+            }
+            for (key, child) in map {
+                let role = role.child(key);
+                if role != ValueRole::Data {
+                    uses(child, role, mappings, pending);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| uses(item, role.item(), mappings, pending)),
+        _ => {}
+    }
+}
+
+/// The section and name of the component a local `$ref` points into.
+pub(crate) fn component(reference: &str) -> Option<(String, String)> {
+    let rest = reference.strip_prefix("#/components/")?;
+    let (section, rest) = rest.split_once('/')?;
+    let name = rest.split('/').next()?;
+    Some((section.to_string(), unescape(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_prune_unused() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {
+                "security": [{"oauth": ["read"]}],
+                "parameters": [{"$ref": "#/components/parameters/Limit"}],
+                "responses": {
+                    "200": {"$ref": "#/components/responses/Pets"},
+                    "default": {"$ref": "#/components/responses/Error"}
+                }
+            }}},
+            "components": {
+                "parameters": {
+                    "Limit": {"name": "limit", "in": "query", "schema": {"$ref": "#/components/schemas/Limit"}},
+                    "Offset": {"name": "offset", "in": "query", "schema": {"type": "integer"}}
+                },
+                "responses": {
+                    "Pets": {"description": "", "content": {"application/json": {
+                        "schema": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                        "example": {"$ref": "#/components/schemas/Example"}
+                    }}},
+                    "Error": {"description": "error"}
+                },
+                "schemas": {
+                    "Limit": {"type": "integer"},
+                    "Pet": {
+                        "oneOf": [{"$ref": "#/components/schemas/Cat/properties/self"}],
+                        "discriminator": {"propertyName": "kind", "mapping": {"dog": "Dog"}}
+                    },
+                    "Cat": {"type": "object", "properties": {"self": {"type": "string"}, "$ref": {"type": "string"}}},
+                    "Dog": {"type": "object"},
+                    "Example": {"type": "object"},
+                    "Orphan": {"$ref": "#/components/schemas/Pet"}
+                },
+                "securitySchemes": {
+                    "oauth": {"type": "oauth2", "flows": {}},
+                    "key": {"type": "apiKey", "in": "header", "name": "X-Key"}
+                }
+            }
+        })).unwrap();
+        assert_eq!(spec.prune_unused(), [
+            "/components/schemas/Example",
+            "/components/schemas/Orphan",
+            "/components/parameters/Offset",
+            "/components/securitySchemes/key",
+        ]);
+        let schemas: Vec<&str> = spec.schemas.keys().map(String::as_str).collect();
+        assert_eq!(schemas, ["Limit", "Pet", "Cat", "Dog"]);
+        assert!(spec.components.responses.contains_key("Error"));
+        assert!(spec.prune_unused().is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+//! Python bindings, for API governance scripts. Specs cross the boundary as JSON
+//! or YAML text, and come back as JSON text for `json.loads`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{CyclicRefs, Format, OpenAPI};
+
+fn load(source: &str) -> PyResult<OpenAPI> {
+    OpenAPI::from_str_format(source, Format::detect(None, source)).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn to_json(spec: &OpenAPI) -> PyResult<String> {
+    serde_json::to_string(spec).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Parses a JSON or YAML spec, raising `ValueError` on invalid ones, and returns it as JSON.
+#[pyfunction]
+fn parse(source: &str) -> PyResult<String> {
+    to_json(&load(source)?)
+}
+
+/// The problems of a JSON or YAML spec, as messages prefixed with a JSON pointer
+/// to where they are: the parse error of an invalid one, or else the errors of
+/// [OpenAPI::validate] and its examples that don't match their schemas. Empty
+/// for a valid spec.
+#[pyfunction]
+fn validate(source: &str) -> Vec<String> {
+    match OpenAPI::from_str_format(source, Format::detect(None, source)) {
+        Ok(spec) => {
+            let mut errors: Vec<String> = spec.validate().err().unwrap_or_default().iter().map(ToString::to_string).collect();
+            errors.extend(spec.validate_examples().err().unwrap_or_default().iter().map(ToString::to_string));
+            errors
+        }
+        Err(e) => vec![e.to_string()],
+    }
+}
+
+/// `base` with `other` merged in, keeping `base`'s components on conflict, as JSON.
+#[pyfunction]
+fn merge(base: &str, other: &str) -> PyResult<String> {
+    let merged = load(base)?.merge(load(other)?).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    to_json(&merged)
+}
+
+/// The spec with every `$ref` inlined, leaving cyclic ones in place, as JSON.
+#[pyfunction]
+fn dereference(source: &str) -> PyResult<String> {
+    let dereferenced = load(source)?.dereference(CyclicRefs::Keep).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    to_json(&dereferenced)
+}
+
+/// The changes from `old` to `new`, each with whether it is breaking, as JSON.
+#[pyfunction]
+fn diff(old: &str, new: &str) -> PyResult<String> {
+    let diff = crate::diff(&load(old)?, &load(new)?);
+    serde_json::to_string(&diff).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// The `openapiv3` Python module. Add it to a Python extension crate with
+/// `#[pymodule] fn openapiv3(m: &Bound<PyModule>) -> PyResult<()> { openapiv3::python::register(m) }`.
+pub fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse, module)?)?;
+    module.add_function(wrap_pyfunction!(validate, module)?)?;
+    module.add_function(wrap_pyfunction!(merge, module)?)?;
+    module.add_function(wrap_pyfunction!(dereference, module)?)?;
+    module.add_function(wrap_pyfunction!(diff, module)?)?;
+    Ok(())
+}
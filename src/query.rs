@@ -0,0 +1,624 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+
+use crate::parameter_style::{coerce, parse_simple, property_schema, Style};
+use crate::security_scheme::RESERVED_HEADERS;
+use crate::*;
+
+/// A problem with a query string, path or headers, from [ResolvedOperation::parse_query],
+/// [ResolvedOperation::parse_path], [ResolvedOperation::parse_headers] or
+/// [ResolvedOperation::parse_cookies].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterError {
+    /// A key that isn't one of the operation's parameters.
+    Unknown(String),
+    /// A parameter given more than once, which only exploded arrays can be.
+    Duplicate(String),
+    /// A required parameter that isn't given.
+    Missing(String),
+    /// A value that doesn't parse as the parameter's schema says.
+    Invalid { parameter: String, value: String },
+    /// A `$ref` to a parameter that could not be followed.
+    Reference(String),
+    /// A request path that doesn't match the operation's path template.
+    Path(String),
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::Unknown(name) => write!(f, "unknown parameter {:?}", name),
+            ParameterError::Duplicate(name) => write!(f, "parameter {:?} is given more than once", name),
+            ParameterError::Missing(name) => write!(f, "required parameter {:?} is missing", name),
+            ParameterError::Invalid { parameter, value } => write!(f, "invalid value {:?} for parameter {:?}", value, parameter),
+            ParameterError::Reference(msg) => write!(f, "{}", msg),
+            ParameterError::Path(path) => write!(f, "path {:?} does not match the operation", path),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+/// What a parameter's schema makes its value, preparing for its items or properties.
+enum Shape<'a> {
+    Scalar(Option<&'a Schema>),
+    Array(Option<&'a Schema>),
+    Object(&'a Schema),
+}
+
+impl<'a> Shape<'a> {
+    fn of(view: ResolvedSpec<'a>, parameter: &'a Parameter) -> Self {
+        let schema = match &parameter.format {
+            ParameterSchemaOrContent::Schema(schema) => view.schema(schema).ok(),
+            ParameterSchemaOrContent::Content(_) => None,
+        };
+        match schema.map(|s| (s, &s.kind)) {
+            Some((_, SchemaKind::Type(Type::Array(array)))) => Shape::Array(array.items.as_deref().and_then(|items| view.schema(items).ok())),
+            Some((schema, SchemaKind::Type(Type::Object(_)))) => Shape::Object(schema),
+            Some((schema, SchemaKind::Any(any))) if !any.properties.is_empty() => Shape::Object(schema),
+            Some((schema, _)) => Shape::Scalar(Some(schema)),
+            None => Shape::Scalar(None),
+        }
+    }
+}
+
+impl<'a> ResolvedOperation<'a> {
+    /// Parses a raw query string, without the `?`, into values of the operation's
+    /// query parameters by name, the inverse of [Parameter::serialize_value]. Each
+    /// value is typed by the parameter's schema: arrays and objects per its `style`
+    /// and `explode`, and numbers, integers and booleans parsed from their text.
+    /// Parameters with `content` are parsed as their media type says. All problems are reported,
+    /// rather than only the first.
+    pub fn parse_query(&self, query: &str) -> Result<IndexMap<String, Value>, Vec<ParameterError>> {
+        let pairs = query.split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key, true), value.to_string())
+            })
+            .collect();
+        self.parse_parameters(pairs, |kind| matches!(kind, ParameterKind::Query { .. }), true)
+    }
+
+    /// Parses a `Cookie` header into values of the operation's cookie parameters
+    /// by name, like [ResolvedOperation::parse_query]. Exploded arrays and objects
+    /// are spread over several cookies. Cookies that aren't parameters are ignored,
+    /// since browsers send every cookie of the site.
+    pub fn parse_cookies(&self, header: &str) -> Result<IndexMap<String, Value>, Vec<ParameterError>> {
+        // Unlike in queries, `+` isn't a space in cookies.
+        let pairs = header.split(';')
+            .map(str::trim)
+            .filter(|cookie| !cookie.is_empty())
+            .map(|cookie| {
+                let (key, value) = cookie.split_once('=').unwrap_or((cookie, ""));
+                (percent_decode(key, false), value.trim_matches('"').replace('+', "%2B"))
+            })
+            .collect();
+        self.parse_parameters(pairs, |kind| matches!(kind, ParameterKind::Cookie { .. }), false)
+    }
+
+    /// Parses a concrete request path, e.g. `/pets/5`, into values of the operation's
+    /// path parameters by name, like [ResolvedOperation::parse_query]. Values are
+    /// read as their `style` writes them: `5` for `simple`, `.5` for `label` and
+    /// `;id=5` for `matrix`, with arrays and objects per `explode`. A path that
+    /// doesn't match the operation's template gives [ParameterError::Path].
+    pub fn parse_path(&self, path: &str) -> Result<IndexMap<String, Value>, Vec<ParameterError>> {
+        let view = self.spec().resolved();
+        let Some(segments) = match_template(self.path, path) else {
+            return Err(vec![ParameterError::Path(path.to_string())]);
+        };
+        let parameters = self.parameters().map_err(|e| vec![ParameterError::Reference(e.to_string())])?;
+        let mut values = IndexMap::new();
+        let mut errors = Vec::new();
+        for (name, raw) in segments {
+            let Some(parameter) = parameters.iter().find(|p| p.name == name && matches!(p.kind, ParameterKind::Path { .. })) else {
+                values.insert(name.to_string(), Value::String(percent_decode(raw, false)));
+                continue;
+            };
+            let style = parameter.style();
+            let explode = parameter.explode.unwrap_or(false);
+            let value = match &parameter.format {
+                ParameterSchemaOrContent::Content(_) => parameter.format.parse_content(&percent_decode(raw, false)).ok(),
+                ParameterSchemaOrContent::Schema(_) => path_value(view, Shape::of(view, parameter), name, style, explode, raw),
+            };
+            match value {
+                Some(value) => { values.insert(name.to_string(), value); }
+                None => errors.push(ParameterError::Invalid { parameter: name.to_string(), value: percent_decode(raw, false) }),
+            }
+        }
+        if errors.is_empty() { Ok(values) } else { Err(errors) }
+    }
+
+    /// The values of the operation's header parameters in the headers of an
+    /// actual request, by name, like [ResolvedOperation::parse_query]. Values are
+    /// in `simple` style, with objects per `explode`, and repeated headers are
+    /// joined with commas, as HTTP allows. `Accept`, `Content-Type` and
+    /// `Authorization` parameters are ignored, as OpenAPI says, and so are
+    /// headers that aren't parameters.
+    pub fn parse_headers(&self, headers: &http::HeaderMap) -> Result<IndexMap<String, Value>, Vec<ParameterError>> {
+        let view = self.spec().resolved();
+        let parameters = self.parameters().map_err(|e| vec![ParameterError::Reference(e.to_string())])?;
+        let mut values = IndexMap::new();
+        let mut errors = Vec::new();
+        for parameter in parameters {
+            let name = parameter.name.as_str();
+            if !matches!(parameter.kind, ParameterKind::Header { .. }) || RESERVED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                continue;
+            }
+            let raw: Vec<&str> = headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).collect();
+            if raw.is_empty() {
+                if parameter.required {
+                    errors.push(ParameterError::Missing(name.to_string()));
+                }
+                continue;
+            }
+            let raw = raw.join(",");
+            let value = match &parameter.format {
+                ParameterSchemaOrContent::Content(_) => parameter.format.parse_content(&raw).ok(),
+                ParameterSchemaOrContent::Schema(schema) => parse_simple(view, view.schema(schema).ok(), &raw, parameter.explode.unwrap_or(false)),
+            };
+            match value {
+                Some(value) => { values.insert(name.to_string(), value); }
+                None => errors.push(ParameterError::Invalid { parameter: name.to_string(), value: raw }),
+            }
+        }
+        if errors.is_empty() { Ok(values) } else { Err(errors) }
+    }
+
+    /// The `Cookie` header sending `values` as the operation's cookie parameters,
+    /// `None` if none of them are given.
+    pub fn cookie_header(&self, values: &IndexMap<String, Value>) -> anyhow::Result<Option<String>> {
+        let cookies: Vec<String> = self.parameters()?.into_iter()
+            .filter(|p| matches!(p.kind, ParameterKind::Cookie { .. }))
+            .filter_map(|p| Some(p.serialize_value(values.get(&p.name)?)))
+            .collect();
+        Ok(Some(cookies.join("; ")).filter(|_| !cookies.is_empty()))
+    }
+
+    /// Parses the `(key, raw value)` pairs of the parameters in the location
+    /// `kind` matches. Keys are decoded, values still encoded.
+    fn parse_parameters(&self, pairs: Vec<(String, String)>, kind: fn(&ParameterKind) -> bool, report_unknown: bool) -> Result<IndexMap<String, Value>, Vec<ParameterError>> {
+        let view = self.spec().resolved();
+        let parameters = self.parameters().map_err(|e| vec![ParameterError::Reference(e.to_string())])?;
+        let parameters: Vec<&Parameter> = parameters.into_iter().filter(|p| kind(&p.kind)).collect();
+        let mut used = vec![false; pairs.len()];
+        let mut values = IndexMap::new();
+        let mut errors = Vec::new();
+        for parameter in &parameters {
+            let name = parameter.name.as_str();
+            let invalid = |value: &str| ParameterError::Invalid { parameter: name.to_string(), value: percent_decode(value, true) };
+            let style = parameter.style();
+            let explode = parameter.explode.unwrap_or(matches!(style, Style::Form | Style::DeepObject));
+            let shape = Shape::of(view, parameter);
+            let property = |schema: &'a Schema, key: &str, raw: &str| {
+                let value = percent_decode(raw, true);
+                match property_schema(view, schema, key) {
+                    Some(property) => coerce(view, property, &value),
+                    None => Some(Value::String(value)),
+                }
+            };
+
+            let mut claimed: Vec<usize> = Vec::new();
+            let value = match (&shape, style) {
+                (Shape::Object(schema), Style::DeepObject) => {
+                    let mut object = Value::Object(Map::new());
+                    for (i, (key, raw)) in pairs.iter().enumerate() {
+                        let Some(segments) = key.strip_prefix(name).and_then(brackets) else { continue };
+                        claimed.push(i);
+                        match insert_deep(view, &mut object, Some(schema), &segments, raw) {
+                            Ok(()) => {}
+                            Err(DeepError::Invalid) => errors.push(invalid(raw)),
+                            Err(DeepError::Duplicate) => {
+                                let duplicate = ParameterError::Duplicate(name.to_string());
+                                if !errors.contains(&duplicate) {
+                                    errors.push(duplicate);
+                                }
+                            }
+                        }
+                    }
+                    Some(object).filter(|_| !claimed.is_empty())
+                }
+                // Exploded objects spread their properties over the query.
+                (Shape::Object(schema), _) if explode => {
+                    let mut object = Map::new();
+                    let properties = schema.get_properties();
+                    for (i, (key, raw)) in pairs.iter().enumerate() {
+                        let declared = properties.is_some_and(|p| p.contains_key(key.as_str()));
+                        if !declared || parameters.iter().any(|p| p.name == *key) {
+                            continue;
+                        }
+                        claimed.push(i);
+                        match property(schema, key, raw) {
+                            Some(value) => { object.insert(key.clone(), value); }
+                            None => errors.push(invalid(raw)),
+                        }
+                    }
+                    Some(Value::Object(object)).filter(|_| !claimed.is_empty())
+                }
+                (Shape::Array(items), _) if explode => {
+                    claimed = (0..pairs.len()).filter(|&i| pairs[i].0 == name).collect();
+                    let items = claimed.iter().map(|&i| item(view, *items, &pairs[i].1).ok_or_else(|| invalid(&pairs[i].1))).collect::<Result<Vec<_>, _>>();
+                    match items {
+                        Ok(items) => Some(Value::Array(items)).filter(|_| !claimed.is_empty()),
+                        Err(e) => { errors.push(e); None }
+                    }
+                }
+                _ => {
+                    claimed = (0..pairs.len()).filter(|&i| pairs[i].0 == name).collect();
+                    if claimed.len() > 1 {
+                        errors.push(ParameterError::Duplicate(name.to_string()));
+                    }
+                    claimed.first().and_then(|&i| {
+                        let raw = pairs[i].1.as_str();
+                        let value = match &shape {
+                            Shape::Scalar(_) if matches!(parameter.format, ParameterSchemaOrContent::Content(_)) => parameter.format.parse_content(&percent_decode(raw, true)).ok(),
+                            Shape::Scalar(schema) => item(view, *schema, raw),
+                            Shape::Array(items) => split(raw, style).iter().map(|raw| item(view, *items, raw)).collect::<Option<Vec<_>>>().map(Value::Array),
+                            Shape::Object(schema) => {
+                                let parts = split(raw, style);
+                                parts.chunks(2).map(|kv| {
+                                    let key = percent_decode(kv[0], true);
+                                    let value = property(schema, &key, kv.get(1).copied().unwrap_or(""))?;
+                                    Some((key, value))
+                                }).collect::<Option<Map<_, _>>>().map(Value::Object)
+                            }
+                        };
+                        if value.is_none() {
+                            errors.push(invalid(raw));
+                        }
+                        value
+                    })
+                }
+            };
+            for i in claimed {
+                used[i] = true;
+            }
+            match value {
+                Some(value) => { values.insert(name.to_string(), value); }
+                None if parameter.required && !errors.iter().any(|e| matches!(e, ParameterError::Invalid { parameter, .. } if parameter == name)) => {
+                    errors.push(ParameterError::Missing(name.to_string()));
+                }
+                None => {}
+            }
+        }
+        let mut unknown: Vec<&str> = Vec::new();
+        for (i, (key, _)) in pairs.iter().enumerate() {
+            if report_unknown && !used[i] && !unknown.contains(&key.as_str()) {
+                unknown.push(key);
+            }
+        }
+        errors.extend(unknown.into_iter().map(|key| ParameterError::Unknown(key.to_string())));
+        if errors.is_empty() { Ok(values) } else { Err(errors) }
+    }
+}
+
+/// The segments of `[a][b]`, `None` unless the key is only bracketed segments.
+fn brackets(mut key: &str) -> Option<Vec<&str>> {
+    let mut segments = Vec::new();
+    while !key.is_empty() {
+        let (segment, rest) = key.strip_prefix('[')?.split_once(']')?;
+        segments.push(segment);
+        key = rest;
+    }
+    Some(segments).filter(|s| !s.is_empty())
+}
+
+/// Why [insert_deep] couldn't set a value.
+enum DeepError {
+    /// The value is invalid, or conflicts with an object or array set before.
+    Invalid,
+    /// A value was set at the same key before.
+    Duplicate,
+}
+
+/// Sets the value of `raw` at the path `segments` of a `deepObject` value, creating
+/// the objects along it. Where the schema says the value is an array, repeated keys
+/// add items instead.
+fn insert_deep(view: ResolvedSpec, target: &mut Value, schema: Option<&Schema>, segments: &[&str], raw: &str) -> Result<(), DeepError> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return match schema.map(|s| &s.kind) {
+            Some(SchemaKind::Type(Type::Array(array))) => {
+                let items = array.items.as_deref().and_then(|items| view.schema(items).ok());
+                let item = item(view, items, raw).ok_or(DeepError::Invalid)?;
+                match target {
+                    Value::Null => *target = Value::Array(vec![item]),
+                    Value::Array(existing) => existing.push(item),
+                    _ => return Err(DeepError::Invalid),
+                }
+                Ok(())
+            }
+            _ if target.is_null() => {
+                *target = item(view, schema, raw).ok_or(DeepError::Invalid)?;
+                Ok(())
+            }
+            _ if target.is_object() || target.is_array() => Err(DeepError::Invalid),
+            _ => Err(DeepError::Duplicate),
+        };
+    };
+    if target.is_null() {
+        *target = Value::Object(Map::new());
+    }
+    let object = target.as_object_mut().ok_or(DeepError::Invalid)?;
+    let property = schema.and_then(|schema| property_schema(view, schema, segment));
+    insert_deep(view, object.entry(segment.to_string()).or_insert(Value::Null), property, rest, raw)
+}
+
+/// A single value, typed by `schema`, e.g. an array's `items`.
+fn item(view: ResolvedSpec, items: Option<&Schema>, raw: &str) -> Option<Value> {
+    let value = percent_decode(raw, true);
+    match items {
+        Some(items) => coerce(view, items, &value),
+        None => Some(Value::String(value)),
+    }
+}
+
+/// The text each path template of `template` stands for in `path`, by parameter
+/// name. A template ends at the next literal text, and never spans a `/`.
+pub(crate) fn match_template<'t, 'p>(template: &'t str, path: &'p str) -> Option<Vec<(&'t str, &'p str)>> {
+    let mut matched = Vec::new();
+    let (mut template, mut path) = (template, path);
+    loop {
+        let Some(start) = template.find('{') else {
+            return Some(matched).filter(|_| template == path);
+        };
+        path = path.strip_prefix(&template[..start])?;
+        let (name, rest) = template[start + 1..].split_once('}')?;
+        let literal = &rest[..rest.find('{').unwrap_or(rest.len())];
+        let end = match literal {
+            "" if rest.is_empty() => path.len(),
+            "" => return None,
+            literal => path.find(literal)?,
+        };
+        let value = &path[..end];
+        if value.is_empty() || value.contains('/') {
+            return None;
+        }
+        matched.push((name, value));
+        template = rest;
+        path = &path[end..];
+    }
+}
+
+/// A path parameter's value, typed as `shape`, from the text its template
+/// matched: `simple` values are comma-separated, `label` ones start with `.`, which
+/// also separates them when exploded, and `matrix` ones are `;name=value` pairs.
+fn path_value(view: ResolvedSpec, shape: Shape, name: &str, style: Style, explode: bool, raw: &str) -> Option<Value> {
+    let typed = |schema: Option<&Schema>, raw: &str| {
+        let value = percent_decode(raw, false);
+        match schema {
+            Some(schema) => coerce(view, schema, &value),
+            None => Some(Value::String(value)),
+        }
+    };
+    let object = |schema: &Schema, pairs: Vec<(&str, &str)>| {
+        pairs.into_iter().map(|(key, raw)| {
+            let key = percent_decode(key, false);
+            let value = typed(property_schema(view, schema, &key), raw)?;
+            Some((key, value))
+        }).collect::<Option<Map<_, _>>>().map(Value::Object)
+    };
+    fn key_value(part: &str) -> (&str, &str) {
+        part.split_once('=').unwrap_or((part, ""))
+    }
+    let (parts, sep): (Vec<&str>, char) = match style {
+        Style::Label => (vec![raw.strip_prefix('.')?], if explode { '.' } else { ',' }),
+        Style::Matrix => (raw.strip_prefix(';')?.split(';').collect(), ','),
+        _ => (vec![raw], ','),
+    };
+    // Everything but exploded matrix objects and arrays is `name=` once, then
+    // the same as `simple`.
+    let body = match style {
+        Style::Matrix if explode && !matches!(shape, Shape::Scalar(_)) => None,
+        Style::Matrix => match parts.as_slice() {
+            [part] => Some(key_value(part)).filter(|(key, _)| *key == name).map(|(_, value)| value),
+            _ => return None,
+        },
+        _ => Some(parts[0]),
+    };
+    match (shape, body) {
+        (Shape::Scalar(schema), Some(body)) => typed(schema, body),
+        (Shape::Array(_), Some("")) => Some(Value::Array(Vec::new())),
+        (Shape::Array(items), Some(body)) => body.split(sep).map(|item| typed(items, item)).collect::<Option<Vec<_>>>().map(Value::Array),
+        (Shape::Object(schema), Some(body)) if explode => object(schema, body.split(sep).map(key_value).collect()),
+        (Shape::Object(schema), Some(body)) => {
+            let parts: Vec<&str> = body.split(',').collect();
+            object(schema, parts.chunks(2).map(|kv| (kv[0], kv.get(1).copied().unwrap_or(""))).collect())
+        }
+        (Shape::Array(items), None) => parts.into_iter().map(|part| {
+            let (key, value) = key_value(part);
+            typed(items, value).filter(|_| key == name)
+        }).collect::<Option<Vec<_>>>().map(Value::Array),
+        (Shape::Object(schema), None) => object(schema, parts.into_iter().map(key_value).collect()),
+        (Shape::Scalar(_), None) => None,
+    }
+}
+
+/// The delimited values of a non-exploded parameter, still encoded.
+fn split(raw: &str, style: Style) -> Vec<&str> {
+    match style {
+        Style::SpaceDelimited => raw.split([' ', '+']).flat_map(|part| part.split("%20")).collect(),
+        Style::PipeDelimited => raw.split('|').flat_map(|part| part.split("%7C")).collect(),
+        _ => raw.split(',').collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {
+                "parameters": [{"name": "limit", "in": "query", "required": true, "schema": {"type": "integer"}}],
+                "get": {
+                    "parameters": [
+                        {"name": "tags", "in": "query", "schema": {"type": "array", "items": {"type": "string"}}},
+                        {"name": "filter", "in": "query", "style": "deepObject", "schema": {"type": "object", "properties": {"age": {"type": "integer"}}}},
+                        {"name": "ids", "in": "query", "explode": false, "schema": {"type": "array", "items": {"type": "integer"}}},
+                        {"name": "sizes", "in": "query", "style": "pipeDelimited", "explode": false, "schema": {"type": "array", "items": {"type": "number"}}},
+                        {"name": "active", "in": "query", "schema": {"type": "boolean"}},
+                        {"name": "q", "in": "query", "content": {"application/json": {"schema": {"type": "object"}}}}
+                    ],
+                    "responses": {}
+                }
+            }}
+        })).unwrap();
+        let op = spec.resolved().operation("/pets", "get").unwrap();
+
+        let query = "limit=10&tags=a&tags=b+c&filter[age]=3&filter[name]=Rex&ids=1,2&sizes=1.5|2&active=true&q=%7B%22a%22%3A1%7D";
+        assert_eq!(serde_json::to_value(op.parse_query(query).unwrap()).unwrap(), json!({
+            "limit": 10,
+            "tags": ["a", "b c"],
+            "filter": {"age": 3, "name": "Rex"},
+            "ids": [1, 2],
+            "sizes": [1.5, 2.0],
+            "active": true,
+            "q": {"a": 1}
+        }));
+
+        let errors: Vec<String> = op.parse_query("limit=ten&ids=1,x&active=yes&active=no&other=1").unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, vec![
+            r#"invalid value "ten" for parameter "limit""#,
+            r#"invalid value "1,x" for parameter "ids""#,
+            r#"parameter "active" is given more than once"#,
+            r#"invalid value "yes" for parameter "active""#,
+            r#"unknown parameter "other""#,
+        ]);
+        assert_eq!(op.parse_query("").unwrap_err(), vec![ParameterError::Missing("limit".to_string())]);
+    }
+
+    #[test]
+    fn test_deep_object() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/books": {"get": {
+                "parameters": [{"name": "filter", "in": "query", "style": "deepObject", "schema": {
+                    "type": "object",
+                    "properties": {
+                        "author": {"type": "object", "properties": {"age": {"type": "integer"}}},
+                        "tags": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "additionalProperties": {"type": "boolean"}
+                }}],
+                "responses": {}
+            }}}
+        })).unwrap();
+        let op = spec.resolved().operation("/books", "get").unwrap();
+        let value = json!({"author": {"name": "Jo Li", "age": 40}, "tags": ["a", "b"], "used": true});
+        let parameter = op.parameters().unwrap()[0];
+        let query = parameter.serialize_value(&value);
+        assert_eq!(query, "filter[author][name]=Jo%20Li&filter[author][age]=40&filter[tags]=a&filter[tags]=b&filter[used]=true");
+        assert_eq!(op.parse_query(&query).unwrap()["filter"], value);
+
+        let errors = op.parse_query("filter[author]=x&filter[author][age]=1&filter[used]=maybe").unwrap_err();
+        assert_eq!(errors, vec![
+            ParameterError::Invalid { parameter: "filter".to_string(), value: "1".to_string() },
+            ParameterError::Invalid { parameter: "filter".to_string(), value: "maybe".to_string() },
+        ]);
+        let errors = op.parse_query("filter[used]=true&filter[used]=false&filter[used]=true").unwrap_err();
+        assert_eq!(errors, vec![ParameterError::Duplicate("filter".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets/{ids}/{point}/{color}/{tag}.json": {"get": {
+                "parameters": [
+                    {"name": "ids", "in": "path", "required": true, "style": "matrix", "explode": true, "schema": {"type": "array", "items": {"type": "integer"}}},
+                    {"name": "point", "in": "path", "required": true, "style": "label", "schema": {"type": "object", "properties": {"x": {"type": "number"}}}},
+                    {"name": "color", "in": "path", "required": true, "style": "matrix", "schema": {"type": "string"}},
+                    {"name": "tag", "in": "path", "required": true, "schema": {"type": "string"}}
+                ],
+                "responses": {}
+            }}}
+        })).unwrap();
+        let op = spec.resolved().operation("/pets/{ids}/{point}/{color}/{tag}.json", "get").unwrap();
+        let values = json!({"ids": [3, 4], "point": {"x": 1.5, "y": "a b"}, "color": "blue", "tag": "a+b"});
+        let path = op.parameters().unwrap().iter().fold(op.path.to_string(), |path, p| {
+            path.replace(&format!("{{{}}}", p.name), &p.serialize_value(&values[p.name.as_str()]))
+        });
+        assert_eq!(path, "/pets/;ids=3;ids=4/.x,1.5,y,a%20b/;color=blue/a%2Bb.json");
+        assert_eq!(Value::Object(op.parse_path(&path).unwrap().into_iter().collect()), values);
+
+        assert_eq!(op.parse_path("/pets/;ids=3;ids=x/.x,1/;colour=blue/t.json").unwrap_err(), vec![
+            ParameterError::Invalid { parameter: "ids".to_string(), value: ";ids=3;ids=x".to_string() },
+            ParameterError::Invalid { parameter: "color".to_string(), value: ";colour=blue".to_string() },
+        ]);
+        assert_eq!(op.parse_path("/pets/1/2").unwrap_err(), vec![ParameterError::Path("/pets/1/2".to_string())]);
+    }
+
+    #[test]
+    fn test_cookies() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/cart": {"get": {
+                "parameters": [
+                    {"name": "session", "in": "cookie", "required": true, "schema": {"type": "string"}},
+                    {"name": "seen", "in": "cookie", "schema": {"type": "array", "items": {"type": "integer"}}},
+                    {"name": "prefs", "in": "cookie", "explode": false, "schema": {"type": "object", "properties": {"size": {"type": "integer"}}}}
+                ],
+                "responses": {}
+            }}}
+        })).unwrap();
+        let op = spec.resolved().operation("/cart", "get").unwrap();
+        let values = IndexMap::from([
+            ("session".to_string(), json!("a+b; c")),
+            ("seen".to_string(), json!([1, 2])),
+            ("prefs".to_string(), json!({"size": 3, "theme": "dark"})),
+        ]);
+        let header = op.cookie_header(&values).unwrap().unwrap();
+        assert_eq!(header, "session=a+b%3B%20c; seen=1; seen=2; prefs=size,3,theme,dark");
+        assert_eq!(op.parse_cookies(&format!("_ga=1; {}", header)).unwrap(), values);
+        assert_eq!(op.parse_cookies("seen=x").unwrap_err(), vec![
+            ParameterError::Missing("session".to_string()),
+            ParameterError::Invalid { parameter: "seen".to_string(), value: "x".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {
+                "parameters": [
+                    {"name": "X-Request-Id", "in": "header", "required": true, "schema": {"type": "integer"}},
+                    {"name": "X-Tags", "in": "header", "schema": {"type": "array", "items": {"type": "string"}}},
+                    {"name": "X-Point", "in": "header", "explode": true, "schema": {"type": "object", "properties": {"x": {"type": "number"}}}},
+                    {"name": "Accept", "in": "header", "required": true, "schema": {"type": "string"}}
+                ],
+                "responses": {}
+            }}}
+        })).unwrap();
+        let op = spec.resolved().operation("/pets", "get").unwrap();
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-request-id", http::HeaderValue::from_static("7"));
+        headers.append("x-tags", http::HeaderValue::from_static("a, b"));
+        headers.append("x-tags", http::HeaderValue::from_static("c"));
+        headers.insert("x-point", http::HeaderValue::from_static("x=1.5,y=2"));
+        assert_eq!(serde_json::to_value(op.parse_headers(&headers).unwrap()).unwrap(), json!({
+            "X-Request-Id": 7,
+            "X-Tags": ["a", "b", "c"],
+            "X-Point": {"x": 1.5, "y": "2"}
+        }));
+
+        headers.insert("x-request-id", http::HeaderValue::from_static("seven"));
+        headers.remove("x-tags");
+        assert_eq!(op.parse_headers(&headers).unwrap_err(), [
+            ParameterError::Invalid { parameter: "X-Request-Id".to_string(), value: "seven".to_string() },
+        ]);
+        assert_eq!(op.parse_headers(&http::HeaderMap::new()).unwrap_err(), [ParameterError::Missing("X-Request-Id".to_string())]);
+    }
+}
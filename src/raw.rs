@@ -0,0 +1,332 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use indexmap::IndexMap;
+use serde::de::{Error as _, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::value::RawValue;
+
+use crate::{Operation, PathItem, RefOr, Schema};
+
+/// A read-only view of a JSON spec that borrows from the input instead of building
+/// the full model, for tools that only look at a few parts of large documents.
+///
+/// Path keys, operation ids, summaries and descriptions borrow from the input
+/// (unless they contain escapes), and everything else stays unparsed as a
+/// [RawValue] until asked for, e.g. with [RawOperation::parse] or [RawComponents::schema].
+///
+/// ```
+/// # use openapiv3::RawOpenAPI;
+/// let input = r#"{"openapi": "3.0.3", "info": {"title": "t", "version": "1"},
+///     "paths": {"/pets": {"get": {"operationId": "listPets", "responses": {}}}}}"#;
+/// let spec = RawOpenAPI::parse(input).unwrap();
+/// let ids: Vec<&str> = spec.operations().filter_map(|(_, _, op)| op.operation_id.as_deref()).collect();
+/// assert_eq!(ids, vec!["listPets"]);
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct RawOpenAPI<'a> {
+    #[serde(borrow)]
+    pub openapi: Cow<'a, str>,
+    #[serde(borrow)]
+    pub info: RawInfo<'a>,
+    /// Path items by path. Extensions on the Paths Object are left out.
+    #[serde(borrow, deserialize_with = "path_map")]
+    pub paths: IndexMap<Cow<'a, str>, RawPathItem<'a>>,
+    #[serde(borrow, default)]
+    pub components: RawComponents<'a>,
+}
+
+impl<'a> RawOpenAPI<'a> {
+    /// Parses the outline of a JSON spec, borrowing from `s`.
+    pub fn parse(s: &'a str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Iterates over `(path, method, operation)` for every operation in the spec.
+    pub fn operations(&self) -> impl Iterator<Item=(&str, &'static str, &RawOperation<'a>)> {
+        self.paths.iter().flat_map(|(path, item)| item.iter().map(move |(method, op)| (path.as_ref(), method, op)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawInfo<'a> {
+    #[serde(borrow)]
+    pub title: Cow<'a, str>,
+    #[serde(borrow)]
+    pub version: Cow<'a, str>,
+    #[serde(borrow, default, deserialize_with = "opt_str")]
+    pub description: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug)]
+pub struct RawPathItem<'a> {
+    /// The unparsed path item.
+    pub raw: &'a RawValue,
+    pub reference: Option<Cow<'a, str>>,
+    pub summary: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
+    pub get: Option<RawOperation<'a>>,
+    pub put: Option<RawOperation<'a>>,
+    pub post: Option<RawOperation<'a>>,
+    pub delete: Option<RawOperation<'a>>,
+    pub options: Option<RawOperation<'a>>,
+    pub head: Option<RawOperation<'a>>,
+    pub patch: Option<RawOperation<'a>>,
+    pub trace: Option<RawOperation<'a>>,
+}
+
+impl<'a> RawPathItem<'a> {
+    /// Returns an iterator of the operations in the path item, with their method names.
+    pub fn iter(&self) -> impl Iterator<Item=(&'static str, &RawOperation<'a>)> {
+        IntoIterator::into_iter([
+            ("get", &self.get),
+            ("put", &self.put),
+            ("post", &self.post),
+            ("delete", &self.delete),
+            ("options", &self.options),
+            ("head", &self.head),
+            ("patch", &self.patch),
+            ("trace", &self.trace),
+        ])
+            .filter_map(|(method, op)| op.as_ref().map(|op| (method, op)))
+    }
+
+    /// Parses the full path item.
+    pub fn parse(&self) -> Result<RefOr<PathItem>, serde_json::Error> {
+        serde_json::from_str(self.raw.get())
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawPathItem<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields<'a> {
+            #[serde(rename = "$ref", borrow, default, deserialize_with = "opt_str")]
+            reference: Option<Cow<'a, str>>,
+            #[serde(borrow, default, deserialize_with = "opt_str")]
+            summary: Option<Cow<'a, str>>,
+            #[serde(borrow, default, deserialize_with = "opt_str")]
+            description: Option<Cow<'a, str>>,
+            #[serde(borrow)]
+            get: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            put: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            post: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            delete: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            options: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            head: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            patch: Option<RawOperation<'a>>,
+            #[serde(borrow)]
+            trace: Option<RawOperation<'a>>,
+        }
+        let raw = <&RawValue>::deserialize(deserializer)?;
+        let f: Fields = serde_json::from_str(raw.get()).map_err(D::Error::custom)?;
+        Ok(RawPathItem {
+            raw,
+            reference: f.reference,
+            summary: f.summary,
+            description: f.description,
+            get: f.get,
+            put: f.put,
+            post: f.post,
+            delete: f.delete,
+            options: f.options,
+            head: f.head,
+            patch: f.patch,
+            trace: f.trace,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RawOperation<'a> {
+    /// The unparsed operation.
+    pub raw: &'a RawValue,
+    pub operation_id: Option<Cow<'a, str>>,
+    pub summary: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
+    pub tags: Vec<Cow<'a, str>>,
+    pub deprecated: bool,
+}
+
+impl RawOperation<'_> {
+    /// Parses the full operation.
+    pub fn parse(&self) -> Result<Operation, serde_json::Error> {
+        serde_json::from_str(self.raw.get())
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawOperation<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Fields<'a> {
+            #[serde(borrow, default, deserialize_with = "opt_str")]
+            operation_id: Option<Cow<'a, str>>,
+            #[serde(borrow, default, deserialize_with = "opt_str")]
+            summary: Option<Cow<'a, str>>,
+            #[serde(borrow, default, deserialize_with = "opt_str")]
+            description: Option<Cow<'a, str>>,
+            #[serde(borrow, default, deserialize_with = "str_vec")]
+            tags: Vec<Cow<'a, str>>,
+            #[serde(default)]
+            deprecated: bool,
+        }
+        let raw = <&RawValue>::deserialize(deserializer)?;
+        let f: Fields = serde_json::from_str(raw.get()).map_err(D::Error::custom)?;
+        Ok(RawOperation {
+            raw,
+            operation_id: f.operation_id,
+            summary: f.summary,
+            description: f.description,
+            tags: f.tags,
+            deprecated: f.deprecated,
+        })
+    }
+}
+
+/// The components of a [RawOpenAPI], by name and still unparsed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawComponents<'a> {
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub security_schemes: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub responses: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub parameters: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub examples: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub request_bodies: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub headers: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub schemas: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub links: IndexMap<Cow<'a, str>, &'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "map")]
+    pub callbacks: IndexMap<Cow<'a, str>, &'a RawValue>,
+}
+
+impl RawComponents<'_> {
+    /// Parses the named schema, if there is one.
+    pub fn schema(&self, name: &str) -> Option<Result<RefOr<Schema>, serde_json::Error>> {
+        self.schemas.get(name).map(|raw| serde_json::from_str(raw.get()))
+    }
+}
+
+/// A string that borrows from the input when it can. Serde only borrows a `Cow`
+/// that is a field by itself, not one in an `Option`, a `Vec` or a map key.
+struct Str<'a>(Cow<'a, str>);
+
+impl<'de> Deserialize<'de> for Str<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StrVisitor;
+
+        impl<'de> Visitor<'de> for StrVisitor {
+            type Value = Str<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E: serde::de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(Str(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Str(Cow::Owned(v.to_string())))
+            }
+
+            fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(Str(Cow::Owned(v)))
+            }
+        }
+
+        deserializer.deserialize_str(StrVisitor)
+    }
+}
+
+fn opt_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error> {
+    Ok(Option::<Str>::deserialize(deserializer)?.map(|s| s.0))
+}
+
+fn str_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Cow<'de, str>>, D::Error> {
+    Ok(Vec::<Str>::deserialize(deserializer)?.into_iter().map(|s| s.0).collect())
+}
+
+fn map<'de, D: Deserializer<'de>, V: Deserialize<'de>>(deserializer: D) -> Result<IndexMap<Cow<'de, str>, V>, D::Error> {
+    struct MapVisitor<V>(PhantomData<V>);
+
+    impl<'de, V: Deserialize<'de>> Visitor<'de> for MapVisitor<V> {
+        type Value = IndexMap<Cow<'de, str>, V>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+            let mut map = IndexMap::with_capacity(access.size_hint().unwrap_or(0));
+            while let Some((Str(key), value)) = access.next_entry()? {
+                map.insert(key, value);
+            }
+            Ok(map)
+        }
+    }
+
+    deserializer.deserialize_map(MapVisitor(PhantomData))
+}
+
+fn path_map<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IndexMap<Cow<'de, str>, RawPathItem<'de>>, D::Error> {
+    let entries: IndexMap<Cow<str>, &RawValue> = map(deserializer)?;
+    entries.into_iter()
+        .filter(|(path, _)| !path.starts_with("x-"))
+        .map(|(path, raw)| Ok((path, serde_json::from_str(raw.get()).map_err(D::Error::custom)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpenAPI;
+
+    #[test]
+    fn test_raw_matches_model() {
+        let input = include_str!("../fixtures/quayio.json");
+        let raw = RawOpenAPI::parse(input).unwrap();
+        let spec: OpenAPI = serde_json::from_str(input).unwrap();
+        assert_eq!(raw.info.title, spec.info.title);
+        assert_eq!(raw.paths.len(), spec.paths.paths.len());
+        assert!(raw.paths.keys().all(|path| matches!(path, Cow::Borrowed(_))));
+
+        let ops: Vec<_> = raw.operations().map(|(path, method, op)| (path, method, op.operation_id.as_deref())).collect();
+        let expected: Vec<_> = spec.operations().map(|(path, method, op, _)| (path, method, op.operation_id.as_deref())).collect();
+        assert_eq!(ops, expected);
+
+        for ((_, _, raw_op), (_, _, op, _)) in raw.operations().zip(spec.operations()) {
+            assert_eq!(&raw_op.parse().unwrap(), op);
+        }
+        for (name, schema) in &spec.components.schemas {
+            assert_eq!(&raw.components.schema(name).unwrap().unwrap(), schema);
+        }
+    }
+
+    #[test]
+    fn test_raw_escaped_strings() {
+        let input = r#"{"openapi": "3.0.3", "info": {"title": "t", "version": "1", "description": "a\nb"},
+            "paths": {"x-ext": 1, "/a": {"summary": "plain", "get": {"tags": ["t1"], "responses": {}}}}}"#;
+        let raw = RawOpenAPI::parse(input).unwrap();
+        assert!(matches!(raw.info.description, Some(Cow::Owned(ref d)) if d == "a\nb"));
+        assert_eq!(raw.paths.keys().collect::<Vec<_>>(), vec!["/a"]);
+        let item = &raw.paths["/a"];
+        assert!(matches!(item.summary, Some(Cow::Borrowed("plain"))));
+        assert_eq!(item.get.as_ref().unwrap().tags, vec!["t1"]);
+    }
+}
@@ -1,7 +1,13 @@
 use std::collections::HashSet;
-use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
-use crate::{OpenAPI, Parameter, RequestBody, Response, Schema};
+use std::fmt;
+use std::marker::PhantomData;
+use serde::de::value::{BorrowedStrDeserializer, MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{self, DeserializeSeed, IgnoredAny, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::util::Key;
+use crate::map::RefOrMap;
+use crate::*;
+use crate::example_validation::unescape;
 
 /// A structured enum of an OpenAPI reference.
 /// e.g. #/components/schemas/Account or #/components/schemas/Account/properties/name
@@ -52,7 +58,12 @@ impl std::fmt::Display for SchemaReference {
 pub type ReferenceOr<T> = RefOr<T>;
 pub type RefOr<T> = Ref<T>;
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+/// Deserialized by hand rather than as `#[serde(untagged)]`, which would buffer
+/// every object to look for `$ref` before reading it again as the item.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "RefOr_{T}"))]
 #[serde(untagged)]
 pub enum Ref<T> {
     Reference {
@@ -62,6 +73,101 @@ pub enum Ref<T> {
     Item(T),
 }
 
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Ref<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RefVisitor(PhantomData))
+    }
+}
+
+/// Reads an object as the item while it goes, setting `$ref` aside. An object
+/// with a string `$ref` is a reference, whatever else it holds.
+struct RefVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for RefVisitor<T> {
+    type Value = Ref<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Reference Object or the object it stands for")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Ref<T>, A::Error> {
+        let mut entries = Entries { map, reference: None };
+        let item = T::deserialize(MapAccessDeserializer::new(&mut entries));
+        match entries.reference {
+            Some(reference) => {
+                // The item may have given up on the entries after `$ref`.
+                if item.is_err() {
+                    while entries.map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+                }
+                Ok(Ref::Reference { reference })
+            }
+            None => item.map(Ref::Item),
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Ref<T>, A::Error> {
+        T::deserialize(SeqAccessDeserializer::new(seq)).map(Ref::Item)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Ref<T>, E> {
+        T::deserialize(v.into_deserializer()).map(Ref::Item)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Ref<T>, E> {
+        T::deserialize(v.into_deserializer()).map(Ref::Item)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Ref<T>, E> {
+        T::deserialize(v.into_deserializer()).map(Ref::Item)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Ref<T>, E> {
+        T::deserialize(v.into_deserializer()).map(Ref::Item)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Ref<T>, E> {
+        T::deserialize(v.into_deserializer()).map(Ref::Item)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Ref<T>, E> {
+        T::deserialize(BorrowedStrDeserializer::new(v)).map(Ref::Item)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Ref<T>, E> {
+        T::deserialize(v.into_deserializer()).map(Ref::Item)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Ref<T>, E> {
+        T::deserialize(().into_deserializer()).map(Ref::Item)
+    }
+}
+
+/// The entries of an object as the item sees them, without `$ref`.
+struct Entries<A> {
+    map: A,
+    reference: Option<String>,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for &mut Entries<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+        while let Some(key) = self.map.next_key::<Key>()? {
+            if key.as_str() != "$ref" {
+                return key.deserialize_into(seed).map(Some);
+            }
+            if let serde_json::Value::String(reference) = self.map.next_value()? {
+                self.reference = Some(reference);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.map.next_value_seed(seed)
+    }
+}
+
 impl<T> Ref<T> {
     pub fn ref_(r: &str) -> Self {
         Ref::Reference {
@@ -146,29 +252,29 @@ fn resolve_helper<'a>(reference: &str, spec: &'a OpenAPI, seen: &mut HashSet<Str
         panic!("Circular reference: {}", reference);
     }
     seen.insert(reference.to_string());
-    let reference = SchemaReference::from_str(&reference);
+    let reference = SchemaReference::from_str(reference);
     match &reference {
         SchemaReference::Schema { ref schema } => {
             let schema_ref = spec.schemas.get(schema)
-                .expect(&format!("Schema {} not found in OpenAPI spec.", schema));
+                .unwrap_or_else(|| panic!("Schema {} not found in OpenAPI spec.", schema));
             // In theory both this as_item and the one below could have continue to be references
             // but assum
             match schema_ref {
                 RefOr::Reference { reference } => {
-                    resolve_helper(&reference, spec, seen)
+                    resolve_helper(reference, spec, seen)
                 }
                 RefOr::Item(s) => s
             }
         }
         SchemaReference::Property { schema: schema_name, property } => {
             let schema = spec.schemas.get(schema_name)
-                .expect(&format!("Schema {} not found in OpenAPI spec.", schema_name))
+                .unwrap_or_else(|| panic!("Schema {} not found in OpenAPI spec.", schema_name))
                 .as_item()
-                .expect(&format!("The schema {} was used in a reference, but that schema is itself a reference to another schema.", schema_name));
+                .unwrap_or_else(|| panic!("The schema {} was used in a reference, but that schema is itself a reference to another schema.", schema_name));
             let prop_schema = schema
                 .properties()
                 .get(property)
-                .expect(&format!("Schema {} does not have property {}.", schema_name, property));
+                .unwrap_or_else(|| panic!("Schema {} does not have property {}.", schema_name, property));
             prop_schema.resolve(spec)
         }
     }
@@ -191,77 +297,192 @@ impl<T> From<T> for RefOr<T> {
     }
 }
 
-impl RefOr<Parameter> {
-    pub fn resolve<'a>(&'a self, spec: &'a OpenAPI) -> Result<&'a Parameter> {
-        match self {
-            RefOr::Reference { reference } => {
-                let name = get_parameter_name(&reference)?;
-                spec.parameters.get(name)
-                    .ok_or(anyhow!("{} not found in OpenAPI spec.", reference))?
-                    .as_item()
-                    .ok_or(anyhow!("{} is circular.", reference))
+macro_rules! resolve {
+    ($($name:ident),* $(,)?) => {$(
+        impl RefOr<$name> {
+            /// The item itself, or the component the reference points to, following
+            /// chains of references, see [RefOr::try_resolve].
+            pub fn resolve<'a>(&'a self, spec: &'a OpenAPI) -> Result<&'a $name, ResolveError> {
+                self.try_resolve(spec)
             }
-            RefOr::Item(parameter) => Ok(parameter),
         }
+    )*};
+}
+
+resolve!(Parameter, Header, Response, RequestBody, Example, Link, SecurityScheme, Callback);
+
+impl<T: Default> Default for RefOr<T> {
+    fn default() -> Self {
+        Ref::Item(T::default())
     }
 }
 
+/// Why a `$ref` could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// The reference isn't a `#/components/<section>/<name>` pointer into the
+    /// document, or points into another section than the one expected.
+    Unsupported(String),
+    /// Nothing is declared where the reference points.
+    NotFound(String),
+    /// The reference leads back to itself through a chain of references.
+    Circular(String),
+}
 
-impl RefOr<Response> {
-    pub fn resolve<'a>(&'a self, spec: &'a OpenAPI) -> Result<&'a Response> {
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RefOr::Reference { reference } => {
-                let name = get_response_name(&reference)?;
-                spec.responses.get(name)
-                    .ok_or(anyhow!("{} not found in OpenAPI spec.", reference))?
-                    .as_item()
-                    .ok_or(anyhow!("{} is circular.", reference))
-            }
-            RefOr::Item(response) => Ok(response),
+            ResolveError::Unsupported(reference) => write!(f, "Unsupported reference: {}", reference),
+            ResolveError::NotFound(reference) => write!(f, "Reference not found: {}", reference),
+            ResolveError::Circular(reference) => write!(f, "Circular reference: {}", reference),
         }
     }
 }
 
-impl Ref<RequestBody> {
-    pub fn resolve<'a>(&'a self, spec: &'a OpenAPI) -> Result<&'a RequestBody> {
-        match self {
-            Ref::Reference { reference } => {
-                let name = get_request_body_name(&reference)?;
-                spec.request_bodies.get(name)
-                    .ok_or(anyhow!("{} not found in OpenAPI spec.", reference))?
-                    .as_item()
-                    .ok_or(anyhow!("{} is circular.", reference))
+impl std::error::Error for ResolveError {}
+
+/// The kinds of objects declared in [Components], which references resolve to.
+pub trait Component: Sized {
+    /// The key of their section of `components`, e.g. `requestBodies`.
+    const SECTION: &'static str;
+
+    fn components(components: &Components) -> &RefOrMap<Self>;
+
+    /// The entry `path`, the part of a reference after the section, points to.
+    #[doc(hidden)]
+    fn lookup<'a>(spec: &'a OpenAPI, path: &str, _seen: &mut HashSet<String>) -> Result<Option<&'a RefOr<Self>>, ResolveError> {
+        Ok(Self::components(spec).get(unescape(path).as_str()))
+    }
+}
+
+macro_rules! component {
+    ($($name:ident: $section:literal => $field:ident),* $(,)?) => {$(
+        impl Component for $name {
+            const SECTION: &'static str = $section;
+
+            fn components(components: &Components) -> &RefOrMap<Self> {
+                &components.$field
             }
-            Ref::Item(request_body) => Ok(request_body),
         }
-    }
+    )*};
 }
 
-impl<T: Default> Default for RefOr<T> {
-    fn default() -> Self {
-        Ref::Item(T::default())
+component!(
+    Response: "responses" => responses,
+    Parameter: "parameters" => parameters,
+    Example: "examples" => examples,
+    RequestBody: "requestBodies" => request_bodies,
+    Header: "headers" => headers,
+    SecurityScheme: "securitySchemes" => security_schemes,
+    Link: "links" => links,
+    Callback: "callbacks" => callbacks,
+    PathItem: "pathItems" => path_items,
+);
+
+impl Component for Schema {
+    const SECTION: &'static str = "schemas";
+
+    fn components(components: &Components) -> &RefOrMap<Self> {
+        &components.schemas
     }
-}
 
-fn parse_reference<'a>(reference: &'a str, group: &str) -> Result<&'a str> {
-    let mut parts = reference.rsplitn(2, '/');
-    let name = parts.next();
-    name.filter(|_| matches!(parts.next(), Some(x) if format!("#/components/{group}") == x))
-        .ok_or(anyhow!("Invalid {} reference: {}", group, reference))
+    /// Also resolves references to a property of a schema, like
+    /// `#/components/schemas/Pet/properties/name`.
+    fn lookup<'a>(spec: &'a OpenAPI, path: &str, seen: &mut HashSet<String>) -> Result<Option<&'a RefOr<Self>>, ResolveError> {
+        let Some((name, property)) = path.split_once("/properties/") else {
+            return Ok(spec.schemas.get(unescape(path).as_str()));
+        };
+        let Some(schema) = spec.schemas.get(unescape(name).as_str()) else { return Ok(None) };
+        let schema = resolve_in(schema, spec, seen)?;
+        Ok(schema.get_properties().and_then(|properties| properties.get(unescape(property).as_str())))
+    }
 }
 
+impl<T: Component> RefOr<T> {
+    /// The item itself, or the component the reference points to, following
+    /// chains of references. Unlike [RefOr::resolve] for schemas, a missing or
+    /// circular reference is an error rather than a panic.
+    pub fn try_resolve<'a>(&'a self, spec: &'a OpenAPI) -> Result<&'a T, ResolveError> {
+        resolve_in(self, spec, &mut HashSet::new())
+    }
 
-fn get_response_name(reference: &str) -> Result<&str> {
-    parse_reference(reference, "responses")
+    /// Like [RefOr::try_resolve], but looks references up in `components` alone,
+    /// e.g. those of a document whose paths were moved out.
+    pub(crate) fn resolve_in_components<'a>(&'a self, components: &'a Components) -> Result<&'a T, ResolveError> {
+        let mut seen = HashSet::new();
+        let mut item = self;
+        loop {
+            let reference = match item {
+                RefOr::Item(item) => return Ok(item),
+                RefOr::Reference { reference } => reference,
+            };
+            if !seen.insert(reference) {
+                return Err(ResolveError::Circular(reference.to_string()));
+            }
+            let name = reference.strip_prefix("#/components/")
+                .and_then(|path| path.strip_prefix(T::SECTION))
+                .and_then(|path| path.strip_prefix('/'))
+                .ok_or_else(|| ResolveError::Unsupported(reference.to_string()))?;
+            item = T::components(components).get(unescape(name).as_str())
+                .ok_or_else(|| ResolveError::NotFound(reference.to_string()))?;
+        }
+    }
 }
 
+fn resolve_in<'a, T: Component>(item: &'a RefOr<T>, spec: &'a OpenAPI, seen: &mut HashSet<String>) -> Result<&'a T, ResolveError> {
+    match item {
+        RefOr::Item(item) => Ok(item),
+        RefOr::Reference { reference } => follow(reference, spec, seen),
+    }
+}
 
-fn get_request_body_name(reference: &str) -> Result<&str> {
-    parse_reference(reference, "requestBodies")
+fn follow<'a, T: Component>(reference: &str, spec: &'a OpenAPI, seen: &mut HashSet<String>) -> Result<&'a T, ResolveError> {
+    if !seen.insert(reference.to_string()) {
+        return Err(ResolveError::Circular(reference.to_string()));
+    }
+    let path = reference.strip_prefix("#/components/")
+        .and_then(|path| path.strip_prefix(T::SECTION))
+        .and_then(|path| path.strip_prefix('/'))
+        .ok_or_else(|| ResolveError::Unsupported(reference.to_string()))?;
+    let item = T::lookup(spec, path, seen)?.ok_or_else(|| ResolveError::NotFound(reference.to_string()))?;
+    resolve_in(item, spec, seen)
 }
 
-fn get_parameter_name(reference: &str) -> Result<&str> {
-    parse_reference(reference, "parameters")
+/// A component a reference points to, from [OpenAPI::resolve_reference].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentRef<'a> {
+    Schema(&'a Schema),
+    Response(&'a Response),
+    Parameter(&'a Parameter),
+    Example(&'a Example),
+    RequestBody(&'a RequestBody),
+    Header(&'a Header),
+    SecurityScheme(&'a SecurityScheme),
+    Link(&'a Link),
+    Callback(&'a Callback),
+}
+
+impl OpenAPI {
+    /// The component a `$ref` value like `#/components/parameters/Limit` points
+    /// to, of the kind its section declares, following chains of references.
+    pub fn resolve_reference(&self, reference: &str) -> Result<ComponentRef<'_>, ResolveError> {
+        let section = reference.strip_prefix("#/components/")
+            .and_then(|path| path.split('/').next())
+            .ok_or_else(|| ResolveError::Unsupported(reference.to_string()))?;
+        let seen = &mut HashSet::new();
+        Ok(match section {
+            Schema::SECTION => ComponentRef::Schema(follow(reference, self, seen)?),
+            Response::SECTION => ComponentRef::Response(follow(reference, self, seen)?),
+            Parameter::SECTION => ComponentRef::Parameter(follow(reference, self, seen)?),
+            Example::SECTION => ComponentRef::Example(follow(reference, self, seen)?),
+            RequestBody::SECTION => ComponentRef::RequestBody(follow(reference, self, seen)?),
+            Header::SECTION => ComponentRef::Header(follow(reference, self, seen)?),
+            SecurityScheme::SECTION => ComponentRef::SecurityScheme(follow(reference, self, seen)?),
+            Link::SECTION => ComponentRef::Link(follow(reference, self, seen)?),
+            Callback::SECTION => ComponentRef::Callback(follow(reference, self, seen)?),
+            _ => return Err(ResolveError::Unsupported(reference.to_string())),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -269,8 +490,44 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_request_body_name() {
-        assert!(matches!(get_request_body_name("#/components/requestBodies/Foo"), Ok("Foo")));
-        assert!(get_request_body_name("#/components/schemas/Foo").is_err());
+    fn test_resolve() {
+        let spec: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {
+                "schemas": {"Pet": {"type": "object", "properties": {"name": {"type": "string"}}}, "a/b": {"type": "string"}},
+                "requestBodies": {
+                    "Foo": {"content": {}},
+                    "Alias": {"$ref": "#/components/requestBodies/Foo"},
+                    "Loop": {"$ref": "#/components/requestBodies/Loop"}
+                }
+            }
+        })).unwrap();
+        let foo = spec.request_bodies["Foo"].as_item().unwrap();
+        assert!(std::ptr::eq(RefOr::<RequestBody>::ref_("#/components/requestBodies/Alias").resolve(&spec).unwrap(), foo));
+        assert_eq!(RefOr::<RequestBody>::ref_("#/components/schemas/Foo").resolve(&spec),
+            Err(ResolveError::Unsupported("#/components/schemas/Foo".to_string())));
+        assert_eq!(RefOr::<RequestBody>::ref_("#/components/requestBodies/Bar").resolve(&spec),
+            Err(ResolveError::NotFound("#/components/requestBodies/Bar".to_string())));
+        assert!(matches!(RefOr::<RequestBody>::ref_("#/components/requestBodies/Loop").resolve(&spec), Err(ResolveError::Circular(_))));
+
+        assert!(matches!(spec.resolve_reference("#/components/schemas/Pet/properties/name"), Ok(ComponentRef::Schema(_))));
+        assert!(matches!(spec.resolve_reference("#/components/schemas/a~1b"), Ok(ComponentRef::Schema(_))));
+        assert_eq!(spec.resolve_reference("#/components/requestBodies/Alias"), Ok(ComponentRef::RequestBody(foo)));
+        assert!(matches!(spec.resolve_reference("#/paths/~1pets"), Err(ResolveError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let parse = |input: &str| serde_json::from_str::<RefOr<Parameter>>(input);
+        let reference = parse(r##"{"description": "d", "$ref": "#/components/parameters/Id", "summary": "s"}"##).unwrap();
+        assert_eq!(reference.as_ref_str(), Some("#/components/parameters/Id"));
+        let item = parse(r#"{"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}"#).unwrap();
+        assert_eq!(item.as_item().map(|p| p.name.as_str()), Some("id"));
+        let input = "{\n  \"items\": {\n    \"title\": 1\n  }\n}";
+        let err = serde_json::from_str::<RefOr<Schema>>(input).unwrap_err();
+        assert_eq!((err.line(), err.column()), (3, 14), "{}", err);
+        assert!(serde_json::from_str::<RefOr<u8>>("7").unwrap().as_item() == Some(&7));
     }
-}
\ No newline at end of file
+}
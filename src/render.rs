@@ -0,0 +1,4 @@
+//! Human-readable documentation rendered from a spec.
+
+mod html;
+pub mod markdown;
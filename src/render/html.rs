@@ -0,0 +1,50 @@
+use crate::OpenAPI;
+
+const TEMPLATE: &str = include_str!("preview.html");
+
+impl OpenAPI {
+    /// A single HTML page presenting the spec: the operations grouped by tag,
+    /// expandable to their parameters, bodies and responses, and the component
+    /// schemas. The spec and the viewer script are embedded, so the page works
+    /// offline, e.g. opened from a temporary file for a local preview.
+    pub fn to_html_preview(&self) -> String {
+        let spec = serde_json::to_string(self).expect("specs serialize");
+        let (head, rest) = TEMPLATE.split_once("{{title}}").expect("template has a title");
+        let (middle, tail) = rest.split_once("{{spec}}").expect("template has a spec");
+        [head, &escape(&self.info.title), middle, &script_safe(&spec), tail].concat()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// `json` made safe to embed in a `<script>` element. A `<` only ever occurs
+/// inside strings, where the escape `\u003c` means the same, and it keeps
+/// `</script>` or `<!--` in the spec from ending the element early.
+fn script_safe(json: &str) -> String {
+    json.replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_to_html_preview() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets & <Toys>", "version": "1", "description": "</script><b>"},
+            "paths": {}
+        })).unwrap();
+        let html = spec.to_html_preview();
+        assert!(html.contains("<title>Pets &amp; &lt;Toys&gt;</title>"));
+        assert!(!html.contains("</script><b>"));
+        let start = html.find(r#"<script id="spec" type="application/json">"#).unwrap();
+        let embedded = &html[start..].split_once('>').unwrap().1;
+        let embedded = embedded.split_once("</script>").unwrap().0;
+        assert_eq!(serde_json::from_str::<OpenAPI>(embedded).unwrap(), spec);
+    }
+}
@@ -0,0 +1,347 @@
+//! API reference documentation as Markdown, e.g. for a README or a docs site
+//! that renders Markdown, without running external tooling.
+//!
+//! ```
+//! # let spec = openapiv3::OpenAPI::default();
+//! let docs = openapiv3::render::markdown::render(&spec);
+//! ```
+
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::example::declared_example;
+use crate::*;
+
+/// The reference documentation of `spec`: its info and servers, then the
+/// operations grouped by their first tag, with tables of their parameters and
+/// responses and the schemas and examples of their payloads, and finally the
+/// component schemas, which the types of the tables link to.
+pub fn render(spec: &OpenAPI) -> String {
+    let mut out = String::new();
+    let info = &spec.info;
+    let _ = writeln!(out, "# {}\n", info.title);
+    if !info.version.is_empty() {
+        let _ = writeln!(out, "Version {}\n", info.version);
+    }
+    if let Some(description) = &info.description {
+        let _ = writeln!(out, "{}\n", description.trim());
+    }
+    if !spec.servers.is_empty() {
+        out.push_str("## Servers\n\n");
+        for server in &spec.servers {
+            match &server.description {
+                Some(description) => {
+                    let _ = writeln!(out, "- `{}`: {}", server.url, cell(description));
+                }
+                None => {
+                    let _ = writeln!(out, "- `{}`", server.url);
+                }
+            }
+        }
+        out.push('\n');
+    }
+
+    let mut groups = IndexMap::<&str, Vec<ResolvedOperation>>::new();
+    // Declared tags first, in their order, then those only used by operations.
+    for tag in &spec.tags {
+        groups.insert(tag.name.as_str(), Vec::new());
+    }
+    for op in spec.resolved().operations() {
+        let tag = op.operation.tags.first().map_or("Other", String::as_str);
+        groups.entry(tag).or_default().push(op);
+    }
+    for (tag, operations) in groups.into_iter().filter(|(_, ops)| !ops.is_empty()) {
+        let _ = writeln!(out, "## {}\n", tag);
+        if let Some(description) = spec.tags.iter().find(|t| t.name == tag).and_then(|t| t.description.as_ref()) {
+            let _ = writeln!(out, "{}\n", description.trim());
+        }
+        for op in operations {
+            operation(&mut out, spec, &op);
+        }
+    }
+
+    let schemas: Vec<_> = spec.schemas.iter().filter_map(|(name, schema)| Some((name, schema.as_item()?))).collect();
+    if !schemas.is_empty() {
+        out.push_str("## Schemas\n\n");
+        for (name, schema) in schemas {
+            let _ = writeln!(out, "### {}\n", name);
+            schema_section(&mut out, schema);
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn operation(out: &mut String, spec: &OpenAPI, op: &ResolvedOperation) {
+    let operation = op.operation;
+    let _ = write!(out, "### `{} {}`", op.method.to_uppercase(), op.path);
+    if let Some(summary) = &operation.summary {
+        let _ = write!(out, " — {}", summary);
+    }
+    out.push_str("\n\n");
+    if operation.deprecated {
+        out.push_str("**Deprecated.**\n\n");
+    }
+    if let Some(description) = &operation.description {
+        let _ = writeln!(out, "{}\n", description.trim());
+    }
+
+    let parameters = op.parameters().unwrap_or_default();
+    if !parameters.is_empty() {
+        out.push_str("#### Parameters\n\n| Name | In | Type | Required | Description |\n| --- | --- | --- | --- | --- |\n");
+        for parameter in parameters {
+            let location = parameter.kind.location();
+            let typ = match &parameter.format {
+                ParameterSchemaOrContent::Schema(schema) => type_name(schema),
+                ParameterSchemaOrContent::Content(content) => content.first()
+                    .and_then(|(_, media)| media.schema.as_ref())
+                    .map_or_else(|| "any".to_string(), type_name),
+            };
+            let required = if parameter.required { "yes" } else { "no" };
+            let description = parameter.description.as_deref().map(cell).unwrap_or_default();
+            let _ = writeln!(out, "| `{}` | {} | {} | {} | {} |", parameter.name, location, typ, required, description);
+        }
+        out.push('\n');
+    }
+
+    if let Ok(Some(body)) = op.request_body() {
+        out.push_str("#### Request body\n\n");
+        if let Some(description) = &body.description {
+            let _ = writeln!(out, "{}\n", description.trim());
+        }
+        content(out, spec, &body.content);
+    }
+
+    let responses = op.responses().unwrap_or_default();
+    if !responses.is_empty() {
+        out.push_str("#### Responses\n\n| Status | Description |\n| --- | --- |\n");
+        for (status, response) in &responses {
+            let status = status.map_or_else(|| "default".to_string(), StatusCode::to_string);
+            let _ = writeln!(out, "| {} | {} |", status, cell(&response.description));
+        }
+        out.push('\n');
+        for (status, response) in responses.into_iter().filter(|(_, r)| !r.content.is_empty()) {
+            let status = status.map_or_else(|| "default".to_string(), StatusCode::to_string);
+            let _ = writeln!(out, "##### {}\n", status);
+            content(out, spec, &response.content);
+        }
+    }
+}
+
+fn content(out: &mut String, spec: &OpenAPI, content: &IndexMap<String, MediaType>) {
+    for (content_type, media) in content {
+        let _ = write!(out, "`{}`", content_type);
+        if let Some(schema) = &media.schema {
+            let _ = write!(out, ": {}", type_name(schema));
+        }
+        out.push_str("\n\n");
+        if let Some(schema) = media.schema.as_ref().and_then(RefOr::as_item).filter(|s| s.get_properties().is_some_and(|p| !p.is_empty())) {
+            properties(out, schema);
+        }
+        if let Some(example) = declared_example(spec, media.example.as_ref(), &media.examples, media.schema.as_ref()) {
+            example_block(out, content_type, &example);
+        }
+    }
+}
+
+fn schema_section(out: &mut String, schema: &Schema) {
+    if let Some(description) = &schema.description {
+        let _ = writeln!(out, "{}\n", description.trim());
+    }
+    match schema.get_properties() {
+        Some(props) if !props.is_empty() => properties(out, schema),
+        _ => {
+            let _ = writeln!(out, "Type: {}\n", describe(schema));
+        }
+    }
+    if let Some(example) = &schema.example {
+        example_block(out, "application/json", example);
+    }
+}
+
+fn properties(out: &mut String, schema: &Schema) {
+    out.push_str("| Property | Type | Required | Description |\n| --- | --- | --- | --- |\n");
+    for (name, property) in schema.get_properties().into_iter().flatten() {
+        let required = if schema.is_required(name) { "yes" } else { "no" };
+        let description = property.as_item().and_then(|p| p.description.as_deref()).map(cell).unwrap_or_default();
+        let _ = writeln!(out, "| `{}` | {} | {} | {} |", name, type_name(property), required, description);
+    }
+    out.push('\n');
+}
+
+fn example_block(out: &mut String, content_type: &str, example: &Value) {
+    let (language, text) = match example {
+        Value::String(s) if !content_type.contains("json") => ("", s.clone()),
+        value => ("json", serde_json::to_string_pretty(value).expect("values serialize")),
+    };
+    let _ = writeln!(out, "```{}\n{}\n```\n", language, text);
+}
+
+/// The type of a schema for a table cell, linking to referenced components.
+fn type_name(schema: &RefOr<Schema>) -> String {
+    match schema {
+        RefOr::Reference { reference } => {
+            let name = reference.rsplit('/').next().unwrap_or(reference);
+            match reference.strip_prefix("#/components/schemas/") {
+                Some(path) if !path.contains('/') => format!("[{}](#{})", name, anchor(name)),
+                _ => format!("`{}`", name),
+            }
+        }
+        RefOr::Item(schema) => describe(schema),
+    }
+}
+
+fn describe(schema: &Schema) -> String {
+    let mut name = match &schema.kind {
+        SchemaKind::Type(Type::String(s)) if !s.enumeration.is_empty() => {
+            s.enumeration.iter().map(|v| format!("`\"{}\"`", v)).collect::<Vec<_>>().join(" \\| ")
+        }
+        SchemaKind::Type(Type::String(s)) if !s.format.is_empty() => format!("string ({})", s.format.as_str()),
+        SchemaKind::Type(Type::String(_)) => "string".to_string(),
+        SchemaKind::Type(Type::Number(n)) => with_format("number", serde_json::to_value(&n.format).ok()),
+        SchemaKind::Type(Type::Integer(i)) => with_format("integer", serde_json::to_value(&i.format).ok()),
+        SchemaKind::Type(Type::Boolean {}) => "boolean".to_string(),
+        SchemaKind::Type(Type::Array(a)) => match &a.items {
+            Some(items) => format!("array of {}", type_name(items)),
+            None => "array".to_string(),
+        },
+        SchemaKind::Type(Type::Object(o)) => match &o.additional_properties {
+            Some(AdditionalProperties::Schema(values)) if o.properties.is_empty() => format!("map of {}", type_name(values)),
+            _ => "object".to_string(),
+        },
+        SchemaKind::OneOf { one_of } => union(one_of, " or "),
+        SchemaKind::AnyOf { any_of } => union(any_of, " or "),
+        SchemaKind::AllOf { all_of } => union(all_of, " and "),
+        SchemaKind::Not { not } => format!("not {}", type_name(not)),
+        SchemaKind::Any(any) => any.typ.clone().unwrap_or_else(|| "any".to_string()),
+    };
+    if schema.nullable {
+        name.push_str(", nullable");
+    }
+    name
+}
+
+fn with_format(typ: &str, format: Option<Value>) -> String {
+    match format.as_ref().and_then(Value::as_str) {
+        Some(format) => format!("{} ({})", typ, format),
+        None => typ.to_string(),
+    }
+}
+
+fn union(schemas: &[RefOr<Schema>], sep: &str) -> String {
+    schemas.iter().map(type_name).collect::<Vec<_>>().join(sep)
+}
+
+/// The anchor GitHub generates for a heading.
+fn anchor(heading: &str) -> String {
+    heading.chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+            ' ' => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Text fit for a table cell, on one line and without column separators.
+fn cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets", "version": "1.0", "description": "All about pets."},
+            "servers": [{"url": "https://api.example.com", "description": "Production"}],
+            "tags": [{"name": "pets", "description": "Pet operations."}],
+            "paths": {"/pets/{id}": {"get": {
+                "tags": ["pets"],
+                "summary": "Get a pet",
+                "parameters": [{"name": "id", "in": "path", "required": true, "description": "The | id", "schema": {"type": "integer", "format": "int64"}}],
+                "responses": {
+                    "200": {"description": "The pet", "content": {"application/json": {
+                        "schema": {"$ref": "#/components/schemas/Pet"},
+                        "example": {"name": "Rex"}
+                    }}},
+                    "404": {"description": "Not found"}
+                }
+            }}},
+            "components": {"schemas": {"Pet": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": {"type": "string", "description": "Its name"},
+                    "kind": {"type": "string", "enum": ["cat", "dog"], "nullable": true},
+                    "toys": {"type": "array", "items": {"$ref": "#/components/schemas/Toy"}}
+                }
+            }, "Toy": {"type": "string", "example": "ball"}}}
+        })).unwrap();
+        assert_eq!(render(&spec), r#"# Pets
+
+Version 1.0
+
+All about pets.
+
+## Servers
+
+- `https://api.example.com`: Production
+
+## pets
+
+Pet operations.
+
+### `GET /pets/{id}` — Get a pet
+
+#### Parameters
+
+| Name | In | Type | Required | Description |
+| --- | --- | --- | --- | --- |
+| `id` | path | integer (int64) | yes | The \| id |
+
+#### Responses
+
+| Status | Description |
+| --- | --- |
+| 200 | The pet |
+| 404 | Not found |
+
+##### 200
+
+`application/json`: [Pet](#pet)
+
+```json
+{
+  "name": "Rex"
+}
+```
+
+## Schemas
+
+### Pet
+
+| Property | Type | Required | Description |
+| --- | --- | --- | --- |
+| `name` | string | yes | Its name |
+| `kind` | `"cat"` \| `"dog"`, nullable | no |  |
+| `toys` | array of [Toy](#toy) | no |  |
+
+### Toy
+
+Type: string
+
+```json
+"ball"
+```
+"#);
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::example::{declared_example, parameter_example};
+use crate::*;
+
+/// The values [Operation::build_request] fills a request with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestParams {
+    /// Values of the operation's parameters by name, serialized as each one's
+    /// `style` and `explode` say. Required parameters left out take their example.
+    pub parameters: IndexMap<String, Value>,
+    /// The body, encoded for the first media type of the request body. When
+    /// `None`, the media type's example is sent, if any.
+    pub body: Option<Value>,
+    /// The index of the server to send the request to, among the operation's.
+    pub server: usize,
+}
+
+impl Operation {
+    /// Builds the request calling this operation of `spec` with `params`: the
+    /// server URL with its variables' defaults, the path and query expanded from
+    /// the parameters, header and cookie parameters, and the body with its
+    /// `Content-Type`. Credentials for the security requirements are left to the
+    /// caller to add. Fails if the operation isn't one of `spec`'s, has no such
+    /// server, or lacks a value for a required parameter.
+    pub fn build_request(&self, spec: &OpenAPI, params: &RequestParams) -> Result<http::Request<Vec<u8>>> {
+        let op = spec.resolved().operations().find(|op| std::ptr::eq(op.operation, self))
+            .ok_or_else(|| anyhow!("Operation is not part of the spec"))?;
+        let servers = op.servers();
+        let base = match servers.get(params.server) {
+            Some(server) => server.url_with_defaults(),
+            None if servers.is_empty() && params.server == 0 => String::new(),
+            None => return Err(anyhow!("No server at index {} for {} {}", params.server, op.method, op.path)),
+        };
+
+        let mut path = op.path.to_string();
+        let mut query = Vec::new();
+        let mut builder = http::Request::builder().method(op.method.to_uppercase().as_str());
+        let mut cookies = Vec::new();
+        for parameter in op.parameters()? {
+            let value = match params.parameters.get(&parameter.name) {
+                Some(value) => value.clone(),
+                None if !parameter.required => continue,
+                None => parameter_example(spec, parameter)
+                    .ok_or_else(|| anyhow!("No value for required parameter {}", parameter.name))?,
+            };
+            let serialized = parameter.serialize_value(&value);
+            match &parameter.kind {
+                ParameterKind::Path { .. } => path = path.replace(&format!("{{{}}}", parameter.name), &serialized),
+                ParameterKind::Query { .. } => query.push(serialized),
+                ParameterKind::Header { .. } => builder = builder.header(parameter.name.as_str(), serialized),
+                ParameterKind::Cookie { .. } => cookies.push(serialized),
+            }
+        }
+        if !cookies.is_empty() {
+            builder = builder.header(http::header::COOKIE, cookies.join("; "));
+        }
+
+        let mut uri = format!("{}{}", base.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            uri = format!("{}?{}", uri, query.join("&"));
+        }
+        builder = builder.uri(uri);
+
+        let mut body = Vec::new();
+        if let Some((content_type, media)) = op.request_body()?.and_then(|body| body.content.first()) {
+            let value = params.body.clone()
+                .or_else(|| declared_example(spec, media.example.as_ref(), &media.examples, media.schema.as_ref()));
+            if let Some(value) = value {
+                let (content_type, bytes) = media.encode_body(spec, content_type, &value);
+                builder = builder.header(http::header::CONTENT_TYPE, content_type);
+                body = bytes;
+            }
+        }
+        Ok(builder.body(body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_build_request() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "servers": [{"url": "https://api.example.com/v1"}],
+            "paths": {"/pets/{id}": {"post": {
+                "operationId": "updatePet",
+                "parameters": [
+                    {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}, "example": "a/b"},
+                    {"name": "tags", "in": "query", "schema": {"type": "array", "items": {"type": "string"}}},
+                    {"name": "filter", "in": "query", "style": "deepObject", "schema": {"type": "object"}},
+                    {"name": "X-Trace", "in": "header", "schema": {"type": "string"}},
+                    {"name": "session", "in": "cookie", "schema": {"type": "string"}}
+                ],
+                "requestBody": {"content": {"application/json": {"example": {"name": "Rex"}}}},
+                "responses": {}
+            }}}
+        })).unwrap();
+        let (op, _) = spec.get_operation("updatePet").unwrap();
+
+        let request = op.build_request(&spec, &RequestParams::default()).unwrap();
+        assert_eq!(request.method(), http::Method::POST);
+        assert_eq!(request.uri(), "https://api.example.com/v1/pets/a%2Fb");
+        assert_eq!(request.headers()["content-type"], "application/json");
+        assert_eq!(request.body(), br#"{"name":"Rex"}"#);
+
+        let params = RequestParams {
+            parameters: IndexMap::from([
+                ("id".to_string(), json!(7)),
+                ("tags".to_string(), json!(["a", "b c"])),
+                ("filter".to_string(), json!({"age": 3})),
+                ("X-Trace".to_string(), json!("abc")),
+                ("session".to_string(), json!("s1")),
+            ]),
+            body: Some(json!({"name": "Max"})),
+            server: 0,
+        };
+        let request = op.build_request(&spec, &params).unwrap();
+        assert_eq!(request.uri(), "https://api.example.com/v1/pets/7?tags=a&tags=b%20c&filter[age]=3");
+        assert_eq!(request.headers()["x-trace"], "abc");
+        assert_eq!(request.headers()["cookie"], "session=s1");
+        assert_eq!(request.body(), br#"{"name":"Max"}"#);
+
+        assert!(op.build_request(&spec, &RequestParams { server: 1, ..RequestParams::default() }).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+
+use crate::example_validation::unescape;
+use crate::{AdditionalProperties, AnySchema, OpenAPI, RefOr, Schema, SchemaKind, Type};
+
+/// A schema with its `$ref`s followed. Every component schema is resolved once and
+/// shared through an [Arc] wherever it is referenced, so resolving a document with
+/// heavily reused models costs one node per component, not one copy per use.
+///
+/// Nodes borrow from the spec; `schema` is the node as written, with its nested
+/// `$ref`s still in place.
+#[derive(Debug)]
+pub struct ResolvedSchema<'a> {
+    /// The component name, for a schema reached through a `$ref`.
+    pub name: Option<&'a str>,
+    pub schema: &'a Schema,
+    /// Set on the reference that closes a cycle, e.g. a `Node` whose children are
+    /// `Node`s. Its children are left empty; continue from [ResolvedSchemas::get] with `name`.
+    pub recursive: bool,
+    pub properties: IndexMap<&'a str, Arc<ResolvedSchema<'a>>>,
+    pub additional_properties: Option<Arc<ResolvedSchema<'a>>>,
+    pub items: Option<Arc<ResolvedSchema<'a>>>,
+    pub all_of: Vec<Arc<ResolvedSchema<'a>>>,
+    pub one_of: Vec<Arc<ResolvedSchema<'a>>>,
+    pub any_of: Vec<Arc<ResolvedSchema<'a>>>,
+    pub not: Option<Arc<ResolvedSchema<'a>>>,
+}
+
+/// The resolved component schemas of a spec, from [OpenAPI::resolve_schemas].
+#[derive(Debug)]
+pub struct ResolvedSchemas<'a> {
+    spec: &'a OpenAPI,
+    components: IndexMap<&'a str, Arc<ResolvedSchema<'a>>>,
+}
+
+impl<'a> ResolvedSchemas<'a> {
+    /// The resolved component schema called `name`.
+    pub fn get(&self, name: &str) -> Option<&Arc<ResolvedSchema<'a>>> {
+        self.components.get(name)
+    }
+
+    /// The component schemas, in document order.
+    pub fn iter(&self) -> impl Iterator<Item=(&'a str, &Arc<ResolvedSchema<'a>>)> {
+        self.components.iter().map(|(name, schema)| (*name, schema))
+    }
+
+    /// Resolves a schema found elsewhere in the spec, such as a parameter or
+    /// response schema, sharing the already resolved components.
+    pub fn resolve(&self, schema: &'a RefOr<Schema>) -> Result<Arc<ResolvedSchema<'a>>> {
+        let mut builder = Builder { spec: self.spec, done: self.components.clone(), active: Vec::new() };
+        builder.schema(schema)
+    }
+}
+
+impl OpenAPI {
+    /// Resolves every component schema. Fails on a `$ref` to a missing schema, or an
+    /// alias that refers back to itself (`A: {$ref: B}`, `B: {$ref: A}`).
+    pub fn resolve_schemas(&self) -> Result<ResolvedSchemas<'_>> {
+        let mut builder = Builder { spec: self, done: IndexMap::new(), active: Vec::new() };
+        for name in self.schemas.keys() {
+            builder.component(name)?;
+        }
+        // Order by the document rather than by resolution.
+        let components = self.schemas.keys()
+            .map(|name| (name.as_str(), builder.done[name.as_str()].clone()))
+            .collect();
+        Ok(ResolvedSchemas { spec: self, components })
+    }
+}
+
+struct Builder<'a> {
+    spec: &'a OpenAPI,
+    done: IndexMap<&'a str, Arc<ResolvedSchema<'a>>>,
+    /// Components being resolved, innermost last.
+    active: Vec<&'a str>,
+}
+
+impl<'a> Builder<'a> {
+    fn component(&mut self, name: &str) -> Result<Arc<ResolvedSchema<'a>>> {
+        if let Some(done) = self.done.get(name) {
+            return Ok(done.clone());
+        }
+        let (name, entry) = self.spec.schemas.get_key_value(name)
+            .ok_or_else(|| anyhow!("Schema {} not found in OpenAPI spec.", name))?;
+        let name = name.as_str();
+        if self.active.contains(&name) {
+            return match entry {
+                RefOr::Item(schema) => Ok(Arc::new(ResolvedSchema { recursive: true, ..ResolvedSchema::leaf(Some(name), schema) })),
+                RefOr::Reference { reference } => Err(anyhow!("Circular reference: {}", reference)),
+            };
+        }
+        self.active.push(name);
+        let resolved = match entry {
+            RefOr::Item(schema) => self.node(Some(name), schema).map(Arc::new),
+            RefOr::Reference { reference } => self.reference(reference),
+        };
+        self.active.pop();
+        let resolved = resolved?;
+        self.done.insert(name, resolved.clone());
+        Ok(resolved)
+    }
+
+    fn reference(&mut self, reference: &str) -> Result<Arc<ResolvedSchema<'a>>> {
+        let path = reference.strip_prefix("#/components/schemas/")
+            .ok_or_else(|| anyhow!("Unsupported schema reference: {}", reference))?;
+        match path.split_once("/properties/") {
+            None => self.component(&unescape(path)),
+            Some((name, property)) => {
+                let (name, property) = (unescape(name), unescape(property));
+                if let Some(done) = self.done.get(name.as_str()).and_then(|s| s.properties.get(property.as_str())) {
+                    return Ok(done.clone());
+                }
+                let property = self.spec.schemas.get(name.as_str())
+                    .and_then(RefOr::as_item)
+                    .and_then(Schema::get_properties)
+                    .and_then(|properties| properties.get(property.as_str()))
+                    .ok_or_else(|| anyhow!("Schema reference not found: {}", reference))?;
+                self.schema(property)
+            }
+        }
+    }
+
+    fn schema(&mut self, schema: &'a RefOr<Schema>) -> Result<Arc<ResolvedSchema<'a>>> {
+        match schema {
+            RefOr::Reference { reference } => self.reference(reference),
+            RefOr::Item(schema) => self.node(None, schema).map(Arc::new),
+        }
+    }
+
+    fn schemas(&mut self, schemas: &'a [RefOr<Schema>]) -> Result<Vec<Arc<ResolvedSchema<'a>>>> {
+        schemas.iter().map(|s| self.schema(s)).collect()
+    }
+
+    fn additional(&mut self, additional: &'a Option<AdditionalProperties>) -> Result<Option<Arc<ResolvedSchema<'a>>>> {
+        match additional {
+            Some(AdditionalProperties::Schema(schema)) => self.schema(schema).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn node(&mut self, name: Option<&'a str>, schema: &'a Schema) -> Result<ResolvedSchema<'a>> {
+        let mut node = ResolvedSchema::leaf(name, schema);
+        if let Some(properties) = schema.get_properties() {
+            for (key, property) in properties.iter() {
+                node.properties.insert(key.as_str(), self.schema(property)?);
+            }
+        }
+        match &schema.kind {
+            SchemaKind::Type(Type::Object(o)) => node.additional_properties = self.additional(&o.additional_properties)?,
+            SchemaKind::Type(Type::Array(a)) => node.items = a.items.as_deref().map(|s| self.schema(s)).transpose()?,
+            SchemaKind::Type(_) => {}
+            SchemaKind::AllOf { all_of } => node.all_of = self.schemas(all_of)?,
+            SchemaKind::OneOf { one_of } => node.one_of = self.schemas(one_of)?,
+            SchemaKind::AnyOf { any_of } => node.any_of = self.schemas(any_of)?,
+            SchemaKind::Not { not } => node.not = Some(self.schema(not)?),
+            SchemaKind::Any(AnySchema { additional_properties, items, all_of, one_of, any_of, not, .. }) => {
+                node.additional_properties = self.additional(additional_properties)?;
+                node.items = items.as_deref().map(|s| self.schema(s)).transpose()?;
+                node.all_of = self.schemas(all_of)?;
+                node.one_of = self.schemas(one_of)?;
+                node.any_of = self.schemas(any_of)?;
+                node.not = not.as_deref().map(|s| self.schema(s)).transpose()?;
+            }
+        }
+        Ok(node)
+    }
+}
+
+impl<'a> ResolvedSchema<'a> {
+    fn leaf(name: Option<&'a str>, schema: &'a Schema) -> Self {
+        ResolvedSchema {
+            name,
+            schema,
+            recursive: false,
+            properties: IndexMap::new(),
+            additional_properties: None,
+            items: None,
+            all_of: Vec::new(),
+            one_of: Vec::new(),
+            any_of: Vec::new(),
+            not: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn with_schemas(schemas: serde_json::Value) -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": schemas}
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_shared_components() {
+        let spec = with_schemas(json!({
+            "Pet": {"type": "object", "properties": {"owner": {"$ref": "#/components/schemas/Person"}}},
+            "Person": {"type": "object", "properties": {"name": {"type": "string"}}},
+            "Pets": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+            "Alias": {"$ref": "#/components/schemas/Pet"},
+            "Owner": {"$ref": "#/components/schemas/Pet/properties/owner"}
+        }));
+        let resolved = spec.resolve_schemas().unwrap();
+        let pet = resolved.get("Pet").unwrap();
+        let person = resolved.get("Person").unwrap();
+        assert!(Arc::ptr_eq(&pet.properties["owner"], person));
+        assert!(Arc::ptr_eq(resolved.get("Pets").unwrap().items.as_ref().unwrap(), pet));
+        assert!(Arc::ptr_eq(resolved.get("Alias").unwrap(), pet));
+        assert!(Arc::ptr_eq(resolved.get("Owner").unwrap(), person));
+        assert_eq!(person.properties["name"].name, None);
+        assert_eq!(resolved.iter().map(|(name, _)| name).collect::<Vec<_>>(), vec!["Pet", "Person", "Pets", "Alias", "Owner"]);
+
+        let inline = RefOr::Item(Schema::new_array(RefOr::schema_ref("Person")));
+        let inline = resolved.resolve(&inline).unwrap();
+        assert!(Arc::ptr_eq(inline.items.as_ref().unwrap(), person));
+    }
+
+    #[test]
+    fn test_recursive_and_missing() {
+        let spec = with_schemas(json!({
+            "Node": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/components/schemas/Node"}}}}
+        }));
+        let resolved = spec.resolve_schemas().unwrap();
+        let child = resolved.get("Node").unwrap().properties["children"].items.clone().unwrap();
+        assert!(child.recursive);
+        assert_eq!(child.name, Some("Node"));
+        assert!(child.properties.is_empty());
+
+        let spec = with_schemas(json!({"A": {"$ref": "#/components/schemas/B"}, "B": {"$ref": "#/components/schemas/A"}}));
+        assert!(spec.resolve_schemas().is_err());
+        let spec = with_schemas(json!({"A": {"type": "array", "items": {"$ref": "#/components/schemas/Missing"}}}));
+        assert!(spec.resolve_schemas().is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+
+use crate::example_validation::unescape;
+use crate::*;
+
+/// A read-only view of a spec which follows `$ref`s on demand, borrowing from the
+/// document instead of cloning it. Nothing is copied until [ResolvedOperation::to_operation]
+/// is called, so the view is cheap to create and to query repeatedly.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedSpec<'a> {
+    spec: &'a OpenAPI,
+}
+
+impl OpenAPI {
+    /// A view resolving references against this spec, see [ResolvedSpec].
+    pub fn resolved(&self) -> ResolvedSpec<'_> {
+        ResolvedSpec { spec: self }
+    }
+}
+
+impl<'a> ResolvedSpec<'a> {
+    pub fn spec(&self) -> &'a OpenAPI {
+        self.spec
+    }
+
+    /// Follows a schema `$ref`, including chains of them and references to a
+    /// property (`#/components/schemas/Pet/properties/name`). Unlike
+    /// [RefOr::resolve], a missing or circular reference is an error rather than a panic.
+    pub fn schema(&self, schema: &'a RefOr<Schema>) -> Result<&'a Schema> {
+        let mut seen = HashSet::new();
+        let mut schema = schema;
+        loop {
+            let reference = match schema {
+                RefOr::Item(schema) => return Ok(schema),
+                RefOr::Reference { reference } => reference,
+            };
+            if !seen.insert(reference.as_str()) {
+                return Err(anyhow!("Circular reference: {}", reference));
+            }
+            let path = reference.strip_prefix("#/components/schemas/")
+                .ok_or_else(|| anyhow!("Unsupported schema reference: {}", reference))?;
+            schema = match path.split_once("/properties/") {
+                None => self.spec.schemas.get(unescape(path).as_str()),
+                Some((name, property)) => self.spec.schemas.get(unescape(name).as_str())
+                    .map(|s| self.schema(s))
+                    .transpose()?
+                    .and_then(Schema::get_properties)
+                    .and_then(|properties| properties.get(unescape(property).as_str())),
+            }.ok_or_else(|| anyhow!("Schema reference not found: {}", reference))?;
+        }
+    }
+
+    pub fn parameter(&self, parameter: &'a RefOr<Parameter>) -> Result<&'a Parameter> {
+        Ok(parameter.resolve(self.spec)?)
+    }
+
+    pub fn request_body(&self, body: &'a RefOr<RequestBody>) -> Result<&'a RequestBody> {
+        Ok(body.resolve(self.spec)?)
+    }
+
+    pub fn response(&self, response: &'a RefOr<Response>) -> Result<&'a Response> {
+        Ok(response.resolve(self.spec)?)
+    }
+
+    /// Iterates through all operations, like [OpenAPI::operations].
+    pub fn operations(&self) -> impl Iterator<Item=ResolvedOperation<'a>> + 'a {
+        let view = *self;
+        self.spec.operations().map(move |(path, method, operation, item)| ResolvedOperation { view, path, method, operation, item })
+    }
+
+    /// The operation for `method` (lowercase, e.g. `get`) on `path`, as written in the spec.
+    pub fn operation(&self, path: &str, method: &str) -> Option<ResolvedOperation<'a>> {
+        self.operations().find(|op| op.path == path && op.method == method)
+    }
+}
+
+/// An operation together with the path item it belongs to, from [ResolvedSpec].
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedOperation<'a> {
+    view: ResolvedSpec<'a>,
+    pub path: &'a str,
+    pub method: &'a str,
+    pub operation: &'a Operation,
+    pub item: &'a PathItem,
+}
+
+impl<'a> ResolvedOperation<'a> {
+    pub fn spec(&self) -> &'a OpenAPI {
+        self.view.spec
+    }
+
+    /// The parameters that apply to the operation, see [Operation::effective_parameters].
+    pub fn parameters(&self) -> Result<Vec<&'a Parameter>> {
+        self.operation.effective_parameters(self.item, self.view.spec)
+    }
+
+    pub fn request_body(&self) -> Result<Option<&'a RequestBody>> {
+        self.operation.request_body.as_ref().map(|b| self.view.request_body(b)).transpose()
+    }
+
+    /// The responses by status code, `None` being the `default` response.
+    pub fn responses(&self) -> Result<Vec<(Option<&'a StatusCode>, &'a Response)>> {
+        self.operation.responses.iter()
+            .map(|(status, response)| Ok((status, self.view.response(response)?)))
+            .collect()
+    }
+
+    /// The operation's security requirements, or the spec's when it declares none.
+    pub fn security(&self) -> &'a [SecurityRequirement] {
+        self.operation.effective_security(self.view.spec)
+    }
+
+    /// The servers of the operation, else of its path item, else of the spec.
+    pub fn servers(&self) -> &'a [Server] {
+        [&self.operation.servers, &self.item.servers, &self.view.spec.servers]
+            .iter()
+            .find(|servers| !servers.is_empty())
+            .map_or(&[][..], |servers| servers.as_slice())
+    }
+
+    /// Materializes the operation as a standalone copy: its effective parameters,
+    /// request body and responses inlined, and security declared explicitly. Schemas
+    /// within them keep their `$ref`s.
+    pub fn to_operation(&self) -> Result<Operation> {
+        let mut operation = self.operation.clone();
+        operation.parameters = self.parameters()?.into_iter().cloned().map(RefOr::Item).collect();
+        operation.request_body = self.request_body()?.cloned().map(RefOr::Item);
+        let responses = &mut operation.responses;
+        for (status, response) in self.responses()? {
+            let response = RefOr::Item(response.clone());
+            match status {
+                Some(status) => responses.responses[status] = response,
+                None => responses.default = Some(response),
+            }
+        }
+        operation.security = Some(self.security().to_vec());
+        Ok(operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn spec() -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "security": [{"key": []}],
+            "paths": {
+                "/pets/{id}": {
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}},
+                        {"$ref": "#/components/parameters/Limit"}
+                    ],
+                    "get": {
+                        "parameters": [
+                            {"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}},
+                            {"name": "id", "in": "query", "schema": {"type": "string"}}
+                        ],
+                        "responses": {"200": {"$ref": "#/components/responses/Pet"}}
+                    }
+                }
+            },
+            "components": {
+                "parameters": {"Limit": {"name": "limit", "in": "query", "schema": {"type": "integer"}}},
+                "responses": {"Pet": {"description": "a pet", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Name"}}}}},
+                "schemas": {
+                    "Pet": {"type": "object", "properties": {"name": {"type": "string"}}},
+                    "Name": {"$ref": "#/components/schemas/Pet/properties/name"},
+                    "Loop": {"$ref": "#/components/schemas/Loop"},
+                    "a/b": {"type": "object", "properties": {"c~d": {"type": "boolean"}}}
+                }
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_effective_operation() {
+        let spec = spec();
+        let view = spec.resolved();
+        let op = view.operation("/pets/{id}", "get").unwrap();
+        let parameters = op.parameters().unwrap();
+        let found: Vec<_> = parameters.iter().map(|p| (p.name.as_str(), ptr(p, &op.operation.parameters))).collect();
+        assert_eq!(found, vec![("id", Some(0)), ("limit", None), ("id", Some(1))]);
+        assert!(std::ptr::eq(parameters[1], spec.parameters["Limit"].as_item().unwrap()));
+
+        let (status, response) = op.responses().unwrap()[0];
+        assert_eq!(status, Some(&StatusCode::Code(200)));
+        let schema = view.schema(response.content["application/json"].schema.as_ref().unwrap()).unwrap();
+        assert!(matches!(schema.kind, SchemaKind::Type(Type::String(_))));
+        assert_eq!(op.security().len(), 1);
+
+        let owned = op.to_operation().unwrap();
+        assert_eq!(owned.parameters.len(), 3);
+        assert!(owned.parameters.iter().all(|p| p.as_item().is_some()));
+        assert!(owned.responses.responses.values().all(|r| r.as_item().is_some()));
+
+        assert!(view.schema(&RefOr::schema_ref("Loop")).is_err());
+        assert!(view.schema(&RefOr::schema_ref("Missing")).is_err());
+        let escaped = RefOr::ref_("#/components/schemas/a~1b/properties/c~0d");
+        assert!(matches!(view.schema(&escaped).unwrap().kind, SchemaKind::Type(Type::Boolean {})));
+    }
+
+    fn ptr(parameter: &Parameter, own: &[RefOr<Parameter>]) -> Option<usize> {
+        own.iter().position(|p| p.as_item().is_some_and(|p| std::ptr::eq(p, parameter)))
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+
+use crate::example_validation::unescape;
+use crate::*;
+
+/// Memoizes schema `$ref` resolution, for tools that resolve the same references
+/// over and over, e.g. while validating or generating code. A cached reference is
+/// answered with one indexed lookup however long the chain of aliases it followed.
+///
+/// Entries remember the components they passed through, and [ResolverCache::invalidate]
+/// drops those affected by an edit. An entry whose target has moved, e.g. after a
+/// component was inserted or removed, is detected and resolved again, so stale
+/// entries never resolve to the wrong schema.
+#[derive(Debug, Clone, Default)]
+pub struct ResolverCache {
+    entries: HashMap<String, Entry>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    target: Target,
+    /// The component schemas followed to reach the target.
+    through: HashSet<String>,
+}
+
+/// Where a reference ends up, by position and name so a moved entry is noticed.
+#[derive(Debug, Clone)]
+struct Target {
+    index: usize,
+    name: String,
+    property: Option<(usize, String)>,
+}
+
+impl Target {
+    fn get<'a>(&self, spec: &'a OpenAPI) -> Option<&'a Schema> {
+        let (name, schema) = spec.schemas.get_index(self.index)?;
+        if *name != self.name {
+            return None;
+        }
+        match &self.property {
+            None => schema.as_item(),
+            Some((index, property)) => {
+                let (name, schema) = schema.as_item()?.get_properties()?.get_index(*index)?;
+                if name != property {
+                    return None;
+                }
+                schema.as_item()
+            }
+        }
+    }
+}
+
+impl ResolverCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follows `schema` if it is a reference, see [ResolverCache::resolve].
+    pub fn schema<'a>(&mut self, spec: &'a OpenAPI, schema: &'a RefOr<Schema>) -> Result<&'a Schema> {
+        match schema {
+            RefOr::Item(schema) => Ok(schema),
+            RefOr::Reference { reference } => self.resolve(spec, reference),
+        }
+    }
+
+    /// Resolves a schema reference against `spec`, including chains of them and
+    /// references to a property, like [ResolvedSpec::schema]. Failures aren't cached.
+    pub fn resolve<'a>(&mut self, spec: &'a OpenAPI, reference: &str) -> Result<&'a Schema> {
+        if let Some(schema) = self.entries.get(reference).and_then(|entry| entry.target.get(spec)) {
+            return Ok(schema);
+        }
+        let entry = find(spec, reference, &mut HashSet::new())?;
+        let schema = entry.target.get(spec).expect("target was just found");
+        self.entries.insert(reference.to_string(), entry);
+        Ok(schema)
+    }
+
+    /// Drops the entries that depend on the component at `pointer`, e.g.
+    /// `/components/schemas/Pet`, to be called after editing it.
+    pub fn invalidate(&mut self, pointer: &str) {
+        let Some(name) = pointer.strip_prefix("/components/schemas/") else { return };
+        let name = unescape(name);
+        self.entries.retain(|_, entry| !entry.through.contains(&name));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Resolves `reference`, failing on any reference in `seen`.
+fn find(spec: &OpenAPI, reference: &str, seen: &mut HashSet<String>) -> Result<Entry> {
+    let mut through = HashSet::new();
+    let mut next = reference;
+    loop {
+        if !seen.insert(next.to_string()) {
+            return Err(anyhow!("Circular reference: {}", reference));
+        }
+        let path = next.strip_prefix("#/components/schemas/")
+            .ok_or_else(|| anyhow!("Unsupported schema reference: {}", next))?;
+        let (name, property) = match path.split_once("/properties/") {
+            None => (unescape(path), None),
+            Some((name, property)) => (unescape(name), Some(unescape(property))),
+        };
+        let not_found = || anyhow!("Schema reference not found: {}", next);
+        let (index, key, schema) = spec.schemas.get_full(name.as_str()).ok_or_else(not_found)?;
+        through.insert(name);
+        let (index, key, schema) = match schema {
+            RefOr::Item(schema) => (index, key, schema),
+            RefOr::Reference { reference } if property.is_none() => {
+                next = reference;
+                continue;
+            }
+            // `Alias/properties/name`: the properties of whatever `Alias` resolves to.
+            RefOr::Reference { reference } => {
+                let entry = find(spec, reference, seen)?;
+                through.extend(entry.through);
+                let (key, schema) = spec.schemas.get_index(entry.target.index).expect("target was just found");
+                (entry.target.index, key, schema.as_item().expect("target is an item"))
+            }
+        };
+        let Some(property) = property else {
+            let target = Target { index, name: key.clone(), property: None };
+            return Ok(Entry { target, through });
+        };
+        match schema.get_properties().and_then(|properties| properties.get_full(property.as_str())).ok_or_else(not_found)? {
+            (_, _, RefOr::Reference { reference }) => next = reference,
+            (i, k, RefOr::Item(_)) => {
+                let target = Target { index, name: key.clone(), property: Some((i, k.clone())) };
+                return Ok(Entry { target, through });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_resolver_cache() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Alias": {"$ref": "#/components/schemas/Pet"},
+                "Pet": {"type": "object", "properties": {"name": {"type": "string"}}},
+                "Loop": {"$ref": "#/components/schemas/Loop"},
+                "Inner": {"$ref": "#/components/schemas/Inner/properties/a"}
+            }}
+        })).unwrap();
+        let mut cache = ResolverCache::new();
+        let pet: *const Schema = spec.schemas["Pet"].as_item().unwrap();
+        assert!(std::ptr::eq(cache.resolve(&spec, "#/components/schemas/Alias").unwrap(), pet));
+        let name = cache.resolve(&spec, "#/components/schemas/Alias/properties/name").unwrap();
+        assert!(matches!(name.kind, SchemaKind::Type(Type::String(_))));
+        assert!(cache.resolve(&spec, "#/components/schemas/Loop").is_err());
+        assert!(cache.resolve(&spec, "#/components/schemas/Inner/properties/a").is_err());
+        assert!(cache.resolve(&spec, "#/components/schemas/Missing").is_err());
+        assert_eq!(cache.len(), 2);
+
+        cache.invalidate("/components/schemas/Dog");
+        assert_eq!(cache.len(), 2);
+        cache.invalidate("/components/schemas/Pet");
+        assert!(cache.is_empty());
+
+        // An entry that moved is noticed rather than answered from its old position.
+        cache.resolve(&spec, "#/components/schemas/Pet").unwrap();
+        spec.schemas.shift_remove("Alias");
+        spec.schemas.insert("Cat".to_string(), RefOr::Item(Schema::new_string()));
+        spec.schemas.swap_indices(0, 3);
+        assert!(std::ptr::eq(cache.resolve(&spec, "#/components/schemas/Pet").unwrap(), spec.schemas["Pet"].as_item().unwrap()));
+    }
+}
@@ -5,6 +5,8 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Responses {
     /// The documentation of responses other than the ones declared
     /// for specific HTTP response codes. Use this field to cover
@@ -29,10 +31,13 @@ pub struct Responses {
     pub responses: IndexMap<StatusCode, RefOr<Response>>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Response {
     /// REQUIRED. A short description of the response.
     /// CommonMark syntax MAY be used for rich text representation.
@@ -61,9 +66,40 @@ pub struct Response {
 
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
+impl Responses {
+    /// The response declared for `status`, by the precedence the spec sets: that
+    /// for the exact code, else that for its range, e.g. `4XX`, else `default`.
+    pub fn for_status(&self, status: http::StatusCode) -> Option<&RefOr<Response>> {
+        self.for_code(status.as_u16())
+    }
+
+    /// [Responses::for_status], for a status code as a number.
+    pub fn for_code(&self, code: u16) -> Option<&RefOr<Response>> {
+        self.responses.get(&StatusCode::Code(code))
+            .or_else(|| self.responses.get(&StatusCode::Range(code / 100)))
+            .or(self.default.as_ref())
+    }
+
+    /// The first successful response, one for an exact 2xx code if any, else
+    /// that for the `2XX` range.
+    pub fn success(&self) -> Option<(&StatusCode, &RefOr<Response>)> {
+        self.responses.iter()
+            .filter(|(status, _)| status.is_success())
+            .min_by_key(|(status, _)| matches!(status, StatusCode::Range(_)))
+    }
+
+    /// The responses by status code, then the `default` one, as `None`.
+    pub fn iter(&self) -> impl Iterator<Item=(Option<&StatusCode>, &RefOr<Response>)> {
+        self.responses.iter()
+            .map(|(status, response)| (Some(status), response))
+            .chain(self.default.as_ref().map(|response| (None, response)))
+    }
+}
+
 fn deserialize_responses<'de, D>(
     deserializer: D,
 ) -> Result<IndexMap<StatusCode, RefOr<Response>>, D::Error>
@@ -103,4 +139,22 @@ mod tests {
         );
         assert_eq!(responses.extensions.get("x-foo"), Some(&json!("bar")));
     }
+
+    #[test]
+    fn test_for_status() {
+        let responses: Responses = serde_json::from_value(json!({
+            "2XX": {"description": "ok"},
+            "201": {"description": "created"},
+            "404": {"description": "not found"},
+            "4XX": {"description": "client error"},
+            "default": {"description": "error"}
+        })).unwrap();
+        let description = |status| responses.for_status(status).and_then(RefOr::as_item).map(|r| r.description.as_str());
+        assert_eq!(description(http::StatusCode::NOT_FOUND), Some("not found"));
+        assert_eq!(description(http::StatusCode::CONFLICT), Some("client error"));
+        assert_eq!(description(http::StatusCode::OK), Some("ok"));
+        assert_eq!(description(http::StatusCode::INTERNAL_SERVER_ERROR), Some("error"));
+        assert_eq!(responses.success().map(|(status, _)| status), Some(&StatusCode::Code(201)));
+        assert_eq!(responses.iter().map(|(status, _)| status.map(ToString::to_string)).last(), Some(None));
+    }
 }
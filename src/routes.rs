@@ -0,0 +1,233 @@
+use http::Method;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::paths::key_of;
+use crate::query::match_template;
+use crate::util::snake_case;
+use crate::*;
+
+/// How a [Route] writes path parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathSyntax {
+    /// `/pets/{id}`, as in the spec, axum 0.8 and actix-web.
+    #[default]
+    Braces,
+    /// `/pets/:id`, as in axum 0.7 and Express.
+    Colon,
+    /// `/pets/<id>`, as in Rocket.
+    Angle,
+}
+
+impl PathSyntax {
+    /// `path` with its `{name}` templates written in this syntax.
+    pub fn convert(self, path: &str) -> String {
+        if self == PathSyntax::Braces {
+            return path.to_string();
+        }
+        let mut out = String::with_capacity(path.len());
+        let mut rest = path;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else { break };
+            let name = &rest[start + 1..start + len];
+            out.push_str(&rest[..start]);
+            match self {
+                PathSyntax::Colon => out.push_str(&format!(":{}", name)),
+                _ => out.push_str(&format!("<{}>", name)),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// An operation as a router sees it, see [OpenAPI::route_table].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route<'a> {
+    pub method: Method,
+    /// The path pattern in the requested syntax.
+    pub path: String,
+    pub operation_id: Option<&'a str>,
+    /// A name for the handler function: the `operationId` in `snake_case`, else
+    /// one made from the method and path, e.g. `get_pets_by_id` for `GET /pets/{id}`.
+    pub handler: String,
+    /// The names of the path parameters, in the order they appear in the path.
+    pub path_parameters: Vec<&'a str>,
+    pub tags: &'a [String],
+    pub summary: Option<&'a str>,
+    pub deprecated: bool,
+}
+
+impl OpenAPI {
+    /// The operations as routes, in document order, for generating the router of
+    /// a web framework. Path items containing `$ref`s are skipped, as in [OpenAPI::operations],
+    /// and so are additional operations whose method isn't a valid HTTP token.
+    pub fn route_table(&self, syntax: PathSyntax) -> Vec<Route<'_>> {
+        self.operations().filter_map(|(path, method, operation, _)| {
+            let method = Method::from_bytes(method.to_uppercase().as_bytes()).ok()?;
+            let path_parameters = templates(path).collect();
+            let handler = match &operation.operation_id {
+                Some(id) => snake_case(id),
+                None => handler_name(method.as_str(), path),
+            };
+            Some(Route {
+                method,
+                path: syntax.convert(path),
+                operation_id: operation.operation_id.as_deref(),
+                handler,
+                path_parameters,
+                tags: &operation.tags,
+                summary: operation.summary.as_deref(),
+                deprecated: operation.deprecated,
+            })
+        }).collect()
+    }
+}
+
+/// The operation a request is for, from [OpenAPI::match_request].
+#[derive(Debug, Clone)]
+pub struct MatchedOperation<'a> {
+    pub operation: ResolvedOperation<'a>,
+    /// The values of the path parameters by name, typed by their schemas.
+    pub path_params: IndexMap<String, Value>,
+}
+
+impl OpenAPI {
+    /// The operation serving `method` (its key in the path item, e.g. `get`, or
+    /// `QUERY` for an additional operation) on a concrete request
+    /// path, with its path parameters parsed by [ResolvedOperation::parse_path], so
+    /// that `label` and `matrix` parameters match too. Where several paths match,
+    /// literal segments take precedence over templated ones, from the first segment
+    /// on, so that `/pets/mine` is preferred over `/pets/{id}`, and `/pets/{id}/toys`
+    /// over `/{kind}/mine/toys`. Paths whose parameters don't parse don't match.
+    pub fn match_route(&self, method: &str, path: &str) -> Option<(ResolvedOperation<'_>, IndexMap<String, Value>)> {
+        self.resolved().operations()
+            .filter(|op| op.method == method && match_template(op.path, path).is_some())
+            .filter_map(|op| Some((op, op.parse_path(path).ok()?)))
+            .min_by_key(|(op, _)| op.path.split('/').map(|segment| segment.contains('{')).collect::<Vec<_>>())
+    }
+
+    /// The operation a request is for, as by [OpenAPI::match_route], for gateways
+    /// and mock servers. A query string on `path` is ignored.
+    pub fn match_request(&self, method: &Method, path: &str) -> Option<MatchedOperation<'_>> {
+        let path = path.split_once('?').map_or(path, |(path, _)| path);
+        let (operation, path_params) = self.match_route(key_of(method), path)?;
+        Some(MatchedOperation { operation, path_params })
+    }
+}
+
+pub(crate) fn templates(path: &str) -> impl Iterator<Item=&str> {
+    path.split('{').skip(1).filter_map(|s| s.split_once('}').map(|(name, _)| name))
+}
+
+fn handler_name(method: &str, path: &str) -> String {
+    let mut name = method.to_lowercase();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        name.push('_');
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => name.push_str(&format!("by_{}", snake_case(param))),
+            None => name.push_str(&snake_case(segment)),
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_route_table() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets/{petId}/toys/{toyId}": {"get": {"operationId": "getPetToy", "tags": ["pets"], "responses": {}}},
+                "/pets/{id}": {"delete": {"deprecated": true, "summary": "Remove", "responses": {}}}
+            }
+        })).unwrap();
+        let routes = spec.route_table(PathSyntax::Colon);
+        assert_eq!(routes[0].method, Method::GET);
+        assert_eq!(routes[0].path, "/pets/:petId/toys/:toyId");
+        assert_eq!(routes[0].handler, "get_pet_toy");
+        assert_eq!(routes[0].path_parameters, vec!["petId", "toyId"]);
+        assert_eq!(routes[0].tags, ["pets"]);
+        assert_eq!(routes[1].handler, "delete_pets_by_id");
+        assert!(routes[1].deprecated && routes[1].operation_id.is_none());
+        assert_eq!(PathSyntax::Angle.convert("/a/{b}/c"), "/a/<b>/c");
+        assert_eq!(PathSyntax::Braces.convert("/a/{b}"), "/a/{b}");
+    }
+
+    #[test]
+    fn test_match_route() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets/{id}": {"get": {
+                    "parameters": [{"name": "id", "in": "path", "required": true, "style": "label", "schema": {"type": "integer"}}],
+                    "responses": {}
+                }},
+                "/pets/mine": {"get": {"operationId": "mine", "responses": {}}}
+            }
+        })).unwrap();
+        let (op, values) = spec.match_route("get", "/pets/.5").unwrap();
+        assert_eq!(op.path, "/pets/{id}");
+        assert_eq!(values["id"], json!(5));
+        assert_eq!(spec.match_route("get", "/pets/mine").unwrap().0.operation.operation_id.as_deref(), Some("mine"));
+        assert!(spec.match_route("get", "/pets/5").is_none());
+        assert!(spec.match_route("post", "/pets/.5").is_none());
+    }
+
+    #[test]
+    fn test_match_request() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/{kind}/mine/toys": {"get": {"operationId": "kindToys", "responses": {}}},
+                "/users/{id}/toys": {"get": {"operationId": "userToys", "responses": {}}},
+                "/users/{id}": {"get": {
+                    "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}}],
+                    "responses": {}
+                }}
+            }
+        })).unwrap();
+        let matched = spec.match_request(&Method::GET, "/users/42?verbose=true").unwrap();
+        assert_eq!(matched.operation.path, "/users/{id}");
+        assert_eq!(matched.path_params["id"], json!(42));
+        let toys = spec.match_request(&Method::GET, "/users/mine/toys").unwrap();
+        assert_eq!(toys.operation.operation.operation_id.as_deref(), Some("userToys"));
+        assert_eq!(toys.path_params["id"], json!("mine"));
+        assert!(spec.match_request(&Method::DELETE, "/users/42").is_none());
+        assert!(spec.match_request(&Method::GET, "/users/42/pets").is_none());
+    }
+
+    #[test]
+    fn test_match_request_custom_method() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.2.0",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/search": {
+                    "get": {"operationId": "list", "responses": {}},
+                    "additionalOperations": {"QUERY": {"operationId": "search", "responses": {}}}
+                }
+            }
+        })).unwrap();
+        let query = Method::from_bytes(b"QUERY").unwrap();
+        let matched = spec.match_request(&query, "/search").unwrap();
+        assert_eq!(matched.operation.operation.operation_id.as_deref(), Some("search"));
+        assert!(spec.match_request(&Method::from_bytes(b"PROPFIND").unwrap(), "/search").is_none());
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("getPetByID"), "get_pet_by_id");
+        assert_eq!(snake_case("HTTPServer-v2"), "http_server_v2");
+        assert_eq!(snake_case("list pets"), "list_pets");
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::parameter_style::text;
+
+/// An expression evaluated against an actual HTTP exchange, as used by `Link`
+/// parameters and request bodies and by `Callback` keys, e.g.
+/// `$request.body#/id` or `$response.header.Location`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RuntimeExpression {
+    /// `$url`, the full URL of the request.
+    Url,
+    /// `$method`, the HTTP method of the request.
+    Method,
+    /// `$statusCode`, the status code of the response.
+    StatusCode,
+    /// `$request.<source>`.
+    Request(ExpressionSource),
+    /// `$response.<source>`. Responses have no query or path.
+    Response(ExpressionSource),
+}
+
+/// The part of a request or response a [RuntimeExpression] reads.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExpressionSource {
+    /// `header.<name>`, matched case-insensitively.
+    Header(String),
+    /// `query.<name>`.
+    Query(String),
+    /// `path.<name>`, a path parameter.
+    Path(String),
+    /// `body`, or `body#<pointer>` for a part of it, the pointer being a JSON
+    /// pointer or empty for the whole body.
+    Body(String),
+}
+
+/// Why a string isn't a [RuntimeExpression].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeExpressionError {
+    pub expression: String,
+    pub message: &'static str,
+}
+
+impl fmt::Display for RuntimeExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid runtime expression {:?}: {}", self.expression, self.message)
+    }
+}
+
+impl std::error::Error for RuntimeExpressionError {}
+
+/// An HTTP exchange to evaluate [RuntimeExpression]s against. Fields unknown
+/// when evaluating, such as the response for a callback sent on the request,
+/// are left empty, and expressions reading them evaluate to `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuntimeContext {
+    pub url: String,
+    pub method: String,
+    pub request: RuntimeMessage,
+    pub status_code: Option<u16>,
+    pub response: RuntimeMessage,
+}
+
+/// The request or response of a [RuntimeContext].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuntimeMessage {
+    pub headers: IndexMap<String, String>,
+    /// Query parameters, decoded. Only requests have these.
+    pub query: IndexMap<String, String>,
+    /// Path parameters by name, decoded. Only requests have these.
+    pub path: IndexMap<String, String>,
+    pub body: Option<Value>,
+}
+
+impl RuntimeExpression {
+    pub fn parse(expression: &str) -> Result<Self, RuntimeExpressionError> {
+        let error = |message| RuntimeExpressionError { expression: expression.to_string(), message };
+        let source = |s: &str| -> Result<ExpressionSource, RuntimeExpressionError> {
+            if let Some(name) = s.strip_prefix("header.") {
+                if name.is_empty() || !name.bytes().all(is_tchar) {
+                    return Err(error("header names must be non-empty tokens"));
+                }
+                return Ok(ExpressionSource::Header(name.to_string()));
+            }
+            if let Some(name) = s.strip_prefix("query.") {
+                return Some(name).filter(|n| !n.is_empty()).map(|n| ExpressionSource::Query(n.to_string()))
+                    .ok_or_else(|| error("query parameter names must not be empty"));
+            }
+            if let Some(name) = s.strip_prefix("path.") {
+                return Some(name).filter(|n| !n.is_empty()).map(|n| ExpressionSource::Path(n.to_string()))
+                    .ok_or_else(|| error("path parameter names must not be empty"));
+            }
+            match s.strip_prefix("body") {
+                Some("") => Ok(ExpressionSource::Body(String::new())),
+                Some(fragment) => match fragment.strip_prefix('#') {
+                    Some(pointer) if is_pointer(pointer) => Ok(ExpressionSource::Body(pointer.to_string())),
+                    Some(_) => Err(error("body fragments must be JSON pointers")),
+                    None => Err(error("expected body or body#<pointer>")),
+                },
+                None => Err(error("expected header., query., path. or body")),
+            }
+        };
+        match expression {
+            "$url" => Ok(RuntimeExpression::Url),
+            "$method" => Ok(RuntimeExpression::Method),
+            "$statusCode" => Ok(RuntimeExpression::StatusCode),
+            _ => {
+                if let Some(rest) = expression.strip_prefix("$request.") {
+                    Ok(RuntimeExpression::Request(source(rest)?))
+                } else if let Some(rest) = expression.strip_prefix("$response.") {
+                    match source(rest)? {
+                        ExpressionSource::Query(_) | ExpressionSource::Path(_) => Err(error("responses have no query or path")),
+                        source => Ok(RuntimeExpression::Response(source)),
+                    }
+                } else {
+                    Err(error("expected $url, $method, $statusCode, $request. or $response."))
+                }
+            }
+        }
+    }
+
+    /// The value of the expression in `context`: a string for URLs, methods,
+    /// headers, queries and paths, a number for status codes, and the JSON of
+    /// bodies. `None` if `context` lacks it.
+    pub fn evaluate(&self, context: &RuntimeContext) -> Option<Value> {
+        let (message, source) = match self {
+            RuntimeExpression::Url => return Some(Value::String(context.url.clone())).filter(|_| !context.url.is_empty()),
+            RuntimeExpression::Method => return Some(Value::String(context.method.clone())).filter(|_| !context.method.is_empty()),
+            RuntimeExpression::StatusCode => return context.status_code.map(Value::from),
+            RuntimeExpression::Request(source) => (&context.request, source),
+            RuntimeExpression::Response(source) => (&context.response, source),
+        };
+        match source {
+            ExpressionSource::Header(name) => message.headers.iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| Value::String(value.clone())),
+            ExpressionSource::Query(name) => message.query.get(name).cloned().map(Value::String),
+            ExpressionSource::Path(name) => message.path.get(name).cloned().map(Value::String),
+            ExpressionSource::Body(pointer) => message.body.as_ref()?.pointer(pointer).cloned(),
+        }
+    }
+}
+
+impl FromStr for RuntimeExpression {
+    type Err = RuntimeExpressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RuntimeExpression::parse(s)
+    }
+}
+
+impl fmt::Display for RuntimeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, source) = match self {
+            RuntimeExpression::Url => return f.write_str("$url"),
+            RuntimeExpression::Method => return f.write_str("$method"),
+            RuntimeExpression::StatusCode => return f.write_str("$statusCode"),
+            RuntimeExpression::Request(source) => ("$request", source),
+            RuntimeExpression::Response(source) => ("$response", source),
+        };
+        match source {
+            ExpressionSource::Header(name) => write!(f, "{}.header.{}", prefix, name),
+            ExpressionSource::Query(name) => write!(f, "{}.query.{}", prefix, name),
+            ExpressionSource::Path(name) => write!(f, "{}.path.{}", prefix, name),
+            ExpressionSource::Body(pointer) if pointer.is_empty() => write!(f, "{}.body", prefix),
+            ExpressionSource::Body(pointer) => write!(f, "{}.body#{}", prefix, pointer),
+        }
+    }
+}
+
+/// `template` with each `{expression}` in it replaced by the value of the
+/// [RuntimeExpression] in `context`, strings as they are and other values as
+/// JSON, as in callback URLs like `{$request.body#/callbackUrl}/events`. A
+/// template that is a bare expression, without braces, is evaluated whole.
+pub fn expand_expressions(template: &str, context: &RuntimeContext) -> Result<String, RuntimeExpressionError> {
+    let value = |expression: &str| {
+        let parsed = RuntimeExpression::parse(expression)?;
+        parsed.evaluate(context).map(|value| text(&value))
+            .ok_or_else(|| RuntimeExpressionError { expression: expression.to_string(), message: "no value in the context" })
+    };
+    if template.starts_with('$') {
+        return value(template);
+    }
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}')
+            .ok_or_else(|| RuntimeExpressionError { expression: rest[start..].to_string(), message: "unterminated {" })?;
+        expanded.push_str(&value(&rest[start + 1..start + end])?);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// RFC 7230's `tchar`, of which header names are made.
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// Whether `s` is an RFC 6901 JSON pointer: empty, or `/`-separated tokens in
+/// which `~` only starts `~0` or `~1`.
+fn is_pointer(s: &str) -> bool {
+    (s.is_empty() || s.starts_with('/')) && s.split('~').skip(1).all(|rest| rest.starts_with(['0', '1']))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_runtime_expressions() {
+        let context = RuntimeContext {
+            url: "https://api.example.com/pets/7?fields=name".to_string(),
+            method: "POST".to_string(),
+            request: RuntimeMessage {
+                headers: IndexMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+                query: IndexMap::from([("fields".to_string(), "name".to_string())]),
+                path: IndexMap::from([("id".to_string(), "7".to_string())]),
+                body: Some(json!({"callbacks": [{"url": "https://client.example.com/hook"}], "a/b": 1})),
+            },
+            status_code: Some(201),
+            response: RuntimeMessage {
+                headers: IndexMap::from([("Location".to_string(), "/pets/7".to_string())]),
+                body: Some(json!({"id": 7})),
+                ..RuntimeMessage::default()
+            },
+        };
+        let cases = [
+            ("$url", Some(json!("https://api.example.com/pets/7?fields=name"))),
+            ("$method", Some(json!("POST"))),
+            ("$statusCode", Some(json!(201))),
+            ("$request.header.content-type", Some(json!("application/json"))),
+            ("$request.query.fields", Some(json!("name"))),
+            ("$request.path.id", Some(json!("7"))),
+            ("$request.body#/callbacks/0/url", Some(json!("https://client.example.com/hook"))),
+            ("$request.body#/a~1b", Some(json!(1))),
+            ("$request.body#/missing", None),
+            ("$response.header.Location", Some(json!("/pets/7"))),
+            ("$response.body", Some(json!({"id": 7}))),
+        ];
+        for (expression, expected) in cases {
+            let parsed: RuntimeExpression = expression.parse().unwrap();
+            assert_eq!(parsed.to_string(), expression);
+            assert_eq!(parsed.evaluate(&context), expected, "{}", expression);
+        }
+        assert_eq!(
+            RuntimeExpression::parse("$response.body#/id"),
+            Ok(RuntimeExpression::Response(ExpressionSource::Body("/id".to_string()))),
+        );
+        for invalid in ["url", "$request", "$request.header.", "$request.header.a b", "$request.body#id", "$request.body#/~2", "$response.query.q", "$request.cookie.c"] {
+            assert!(RuntimeExpression::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SchemaData {
     #[serde(default, skip_serializing_if = "is_false")]
@@ -17,6 +19,7 @@ pub struct SchemaData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_docs: Option<ExternalDocumentation>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub example: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -24,22 +27,104 @@ pub struct SchemaData {
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub discriminator: Option<Discriminator>,
+    /// How the value is laid out in XML, see [Schema::generate_xml_example].
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub xml: Option<Xml>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
     pub default: Option<serde_json::Value>,
+    /// Restricts the value to a single constant. Introduced in OpenAPI 3.1;
+    /// see [Schema::downgrade_to_v3_0] for how it is expressed in 3.0.
+    #[serde(rename = "const", default, deserialize_with = "crate::util::deserialize_some", skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::option_value))]
+    pub const_value: Option<serde_json::Value>,
+    /// Conditional subschema: when the value is valid against `if`, it must also
+    /// be valid against `then`, otherwise against `else`. Introduced in OpenAPI 3.1.
+    #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
+    pub if_: Option<Box<RefOr<Schema>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub then: Option<Box<RefOr<Schema>>>,
+    #[serde(rename = "else", skip_serializing_if = "Option::is_none")]
+    pub else_: Option<Box<RefOr<Schema>>>,
+    /// Applies to object properties that no other keyword evaluated, including
+    /// keywords in `allOf`/`anyOf`/`oneOf` branches. `false` closes the object.
+    /// Introduced in OpenAPI 3.1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unevaluated_properties: Option<AdditionalProperties>,
+    /// Applies to array items that no other keyword evaluated. Introduced in OpenAPI 3.1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unevaluated_items: Option<AdditionalProperties>,
+    /// The JSON Schema dialect of the schema, overriding the `jsonSchemaDialect`
+    /// of the spec. Introduced in OpenAPI 3.1, as are the keywords below.
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<String>,
+    #[serde(rename = "$id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "$anchor", skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    #[serde(rename = "$comment", skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Schemas for reuse within this one, see [Components::schemas] for those of the spec.
+    #[serde(rename = "$defs", default, skip_serializing_if = "IndexMap::is_empty")]
+    pub defs: RefOrMap<Schema>,
+    /// Example values, the JSON Schema counterpart of `example`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::values))]
+    pub examples: Vec<serde_json::Value>,
+    /// Schemas of the first array items, by position; `items` applies to the rest.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prefix_items: Vec<RefOr<Schema>>,
+    /// A schema at least one item of an array must be valid against, or between
+    /// `minContains` and `maxContains` of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains: Option<Box<RefOr<Schema>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_contains: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_contains: Option<usize>,
+    /// Schemas of the object properties whose names match a regex key.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub pattern_properties: RefOrMap<Schema>,
+    /// A schema every property name of an object must be valid against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub property_names: Option<Box<RefOr<Schema>>>,
+    /// Schemas an object must be valid against when it has the property of their key.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub dependent_schemas: RefOrMap<Schema>,
+    /// Properties an object must have when it has the property of their key.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub dependent_required: IndexMap<String, Vec<String>>,
+    /// The schema of the decoded content of a string with `contentMediaType`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_schema: Option<Box<RefOr<Schema>>>,
+    /// The numeric keywords (`minimum`, `maximum`, `exclusiveMinimum`,
+    /// `exclusiveMaximum`, `multipleOf`) as they were read. The typed fields hold
+    /// them as `f64`; these are written in their place while they still agree,
+    /// so bounds like `9223372036854775807` keep every digit.
+    #[cfg(feature = "arbitrary_precision")]
+    #[serde(skip)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    pub numbers: IndexMap<String, serde_json::Number>,
     /// All extensions must be prefixed with `x-`, see
     /// section Specification Extensions on https://swagger.io/specification/
     /// for more information. So you could add a custom field `name` like:
     /// `x-name: value` rather than `name: value`
     /// In code, the `x-` prefix remains as part of the key: `extensions.get("x-name")`
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Deserialized by hand, see `schema_de.rs`, and serialized in the form of the
+/// enclosing spec's version, see `schema_ser.rs`. Generated by hand under the
+/// `arbitrary` feature, see `fuzz.rs`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Schema {
-    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(flatten))]
     pub data: SchemaData,
-    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(flatten))]
     pub kind: SchemaKind,
 }
 
@@ -58,7 +143,10 @@ impl std::ops::DerefMut for Schema {
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum SchemaKind {
     Type(Type),
     OneOf {
@@ -81,7 +169,7 @@ pub enum SchemaKind {
 
 
 impl Schema {
-    fn new_kind(kind: SchemaKind) -> Self {
+    pub(crate) fn new_kind(kind: SchemaKind) -> Self {
         Self { data: SchemaData::default(), kind }
     }
 
@@ -144,6 +232,47 @@ impl Schema {
         })))
     }
 
+    /// Shorthand for [Schema::new_string].
+    pub fn string() -> Self {
+        Self::new_string()
+    }
+
+    /// Shorthand for [Schema::new_integer].
+    pub fn integer() -> Self {
+        Self::new_integer()
+    }
+
+    /// Shorthand for [Schema::new_array].
+    pub fn array(items: impl Into<RefOr<Schema>>) -> Self {
+        Self::new_array(items)
+    }
+
+    /// Create an object schema from `(name, schema, required)` triples, in order.
+    ///
+    /// ```
+    /// # use openapiv3::{RefOr, Schema};
+    /// let pet = Schema::object([
+    ///     ("name", RefOr::from(Schema::string()), true),
+    ///     ("owner", RefOr::schema_ref("User"), false),
+    /// ]);
+    /// assert_eq!(*pet.required(), ["name"]);
+    /// ```
+    pub fn object<N, S>(properties: impl IntoIterator<Item = (N, S, bool)>) -> Self
+    where
+        N: Into<String>,
+        S: Into<RefOr<Schema>>,
+    {
+        let mut object = ObjectType::default();
+        for (name, schema, required) in properties {
+            let name = name.into();
+            if required {
+                object.required.push(name.clone());
+            }
+            object.properties.insert(name, schema.into());
+        }
+        Self::new_kind(SchemaKind::Type(Type::Object(object)))
+    }
+
     pub fn new_one_of(one_of: Vec<RefOr<Schema>>) -> Self {
         Self::new_kind(SchemaKind::OneOf { one_of })
     }
@@ -182,6 +311,8 @@ impl Schema {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Type {
     String(StringType),
@@ -192,7 +323,10 @@ pub enum Type {
     Boolean {},
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Deserialized by hand, see `schema_de.rs`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum AdditionalProperties {
     Any(bool),
@@ -202,21 +336,35 @@ pub enum AdditionalProperties {
 /// Catch-all for any combination of properties that doesn't correspond to one
 /// of the predefined subsets.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AnySchema {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub typ: Option<String>,
+    /// The types of an OpenAPI 3.1 schema allowing several, like
+    /// `type: [string, integer]`, with `typ` unset. A `null` among them is
+    /// expressed by `nullable` instead.
+    #[serde(rename = "type", default, skip_serializing_if = "Vec::is_empty", skip_deserializing)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    pub types: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_f64"))]
     pub multiple_of: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub exclusive_minimum: Option<bool>,
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_bound"))]
+    pub exclusive_minimum: Option<ExclusiveBound<f64>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub exclusive_maximum: Option<bool>,
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_bound"))]
+    pub exclusive_maximum: Option<ExclusiveBound<f64>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_f64"))]
     pub minimum: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_f64"))]
     pub maximum: Option<f64>,
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub properties: RefOrMap<Schema>,
@@ -237,6 +385,7 @@ pub struct AnySchema {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unique_items: Option<bool>,
     #[serde(rename = "enum", default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::values))]
     pub enumeration: Vec<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
@@ -244,6 +393,10 @@ pub struct AnySchema {
     pub min_length: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_media_type: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub one_of: Vec<RefOr<Schema>>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -255,6 +408,8 @@ pub struct AnySchema {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct StringType {
     #[serde(default, skip_serializing_if = "VariantOrUnknownOrEmpty::is_empty")]
@@ -267,38 +422,56 @@ pub struct StringType {
     pub min_length: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_length: Option<usize>,
+    /// The encoding of binary data carried in the string, e.g. `base64`.
+    /// Introduced in OpenAPI 3.1, replacing `format: byte`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+    /// The media type of the string's contents, e.g. `image/png`.
+    /// Introduced in OpenAPI 3.1, replacing `format: binary`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_media_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct NumberType {
     #[serde(default, skip_serializing_if = "VariantOrUnknownOrEmpty::is_empty")]
     pub format: VariantOrUnknownOrEmpty<NumberFormat>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_f64"))]
     pub multiple_of: Option<f64>,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub exclusive_minimum: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub exclusive_maximum: bool,
+    #[serde(default, skip_serializing_if = "ExclusiveBound::is_unset")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(deserialize_with = "crate::precise::bound"))]
+    pub exclusive_minimum: ExclusiveBound<f64>,
+    #[serde(default, skip_serializing_if = "ExclusiveBound::is_unset")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(deserialize_with = "crate::precise::bound"))]
+    pub exclusive_maximum: ExclusiveBound<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_f64"))]
     pub minimum: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(default, deserialize_with = "crate::precise::option_f64"))]
     pub maximum: Option<f64>,
     #[serde(rename = "enum", default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "arbitrary_precision", serde(deserialize_with = "crate::precise::option_f64_vec"))]
     pub enumeration: Vec<Option<f64>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct IntegerType {
     #[serde(default, skip_serializing_if = "VariantOrUnknownOrEmpty::is_empty")]
     pub format: VariantOrUnknownOrEmpty<IntegerFormat>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub multiple_of: Option<i64>,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub exclusive_minimum: bool,
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub exclusive_maximum: bool,
+    #[serde(default, skip_serializing_if = "ExclusiveBound::is_unset")]
+    pub exclusive_minimum: ExclusiveBound<i64>,
+    #[serde(default, skip_serializing_if = "ExclusiveBound::is_unset")]
+    pub exclusive_maximum: ExclusiveBound<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub minimum: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -307,7 +480,167 @@ pub struct IntegerType {
     pub enumeration: Vec<Option<i64>>,
 }
 
+/// The `exclusiveMinimum` / `exclusiveMaximum` keywords.
+///
+/// OpenAPI 3.0 uses a boolean that makes the accompanying `minimum`/`maximum`
+/// exclusive; OpenAPI 3.1 uses the exclusive bound itself. Both forms are accepted,
+/// and [ExclusiveBound::normalize] computes the effective bound either way.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "ExclusiveBound_{T}"))]
+#[serde(untagged)]
+pub enum ExclusiveBound<T> {
+    /// 3.0 form: whether `minimum`/`maximum` is exclusive.
+    Flag(bool),
+    /// 3.1 form: the exclusive bound.
+    Value(T),
+}
+
+impl<T> Default for ExclusiveBound<T> {
+    fn default() -> Self {
+        ExclusiveBound::Flag(false)
+    }
+}
+
+impl StringType {
+    /// Replaces the 3.0 `format: byte` / `format: binary` convention with the
+    /// 3.1 `contentEncoding: base64` / `contentMediaType: application/octet-stream`.
+    pub fn format_to_content_keywords(&mut self) {
+        let mut format = Some(self.format.as_str().to_string()).filter(|f| !f.is_empty());
+        content_from_format(&mut format, &mut self.content_encoding, &mut self.content_media_type);
+        if format.is_none() {
+            self.format = VariantOrUnknownOrEmpty::Empty;
+        }
+    }
+
+    /// Replaces 3.1 `contentEncoding` / `contentMediaType` with the 3.0
+    /// `format: byte` / `format: binary` convention. The media type of base64
+    /// content has no 3.0 keyword, and is left as it is.
+    pub fn content_keywords_to_format(&mut self) {
+        let mut format = Some(self.format.as_str().to_string()).filter(|f| !f.is_empty());
+        format_from_content(&mut format, &mut self.content_encoding, &mut self.content_media_type);
+        match format.as_deref() {
+            Some("byte") => self.format = VariantOrUnknownOrEmpty::Item(StringFormat::Byte),
+            Some("binary") => self.format = VariantOrUnknownOrEmpty::Item(StringFormat::Binary),
+            _ => {}
+        }
+    }
+}
+
+impl AnySchema {
+    /// See [StringType::format_to_content_keywords].
+    pub fn format_to_content_keywords(&mut self) {
+        content_from_format(&mut self.format, &mut self.content_encoding, &mut self.content_media_type);
+    }
+
+    /// See [StringType::content_keywords_to_format].
+    pub fn content_keywords_to_format(&mut self) {
+        format_from_content(&mut self.format, &mut self.content_encoding, &mut self.content_media_type);
+    }
+}
+
+fn content_from_format(format: &mut Option<String>, encoding: &mut Option<String>, media_type: &mut Option<String>) {
+    match format.as_deref() {
+        Some("byte") => {
+            *format = None;
+            encoding.get_or_insert_with(|| "base64".to_string());
+        }
+        Some("binary") => {
+            *format = None;
+            media_type.get_or_insert_with(|| "application/octet-stream".to_string());
+        }
+        _ => {}
+    }
+}
+
+fn format_from_content(format: &mut Option<String>, encoding: &mut Option<String>, media_type: &mut Option<String>) {
+    if format.is_some() {
+        return;
+    }
+    if encoding.as_deref().is_some_and(|e| e.eq_ignore_ascii_case("base64")) {
+        *format = Some("byte".to_string());
+        *encoding = None;
+    } else if media_type.is_some() && encoding.is_none() {
+        *format = Some("binary".to_string());
+        *media_type = None;
+    }
+}
+
+impl<T> ExclusiveBound<T> {
+    /// Converts the bound value, keeping the boolean form as-is.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ExclusiveBound<U> {
+        match self {
+            ExclusiveBound::Flag(b) => ExclusiveBound::Flag(b),
+            ExclusiveBound::Value(v) => ExclusiveBound::Value(f(v)),
+        }
+    }
+}
+
+impl<T: Copy + PartialOrd> ExclusiveBound<T> {
+    pub fn is_unset(&self) -> bool {
+        matches!(self, ExclusiveBound::Flag(false))
+    }
+
+    /// Combines this keyword with the matching inclusive keyword (`minimum` when
+    /// `lower` is true, `maximum` otherwise) into the effective bound, returned as
+    /// `(value, exclusive)`. When both forms constrain the value, the stricter wins.
+    pub fn normalize(&self, inclusive: Option<T>, lower: bool) -> Option<(T, bool)> {
+        match (*self, inclusive) {
+            (ExclusiveBound::Flag(exclusive), Some(v)) => Some((v, exclusive)),
+            (ExclusiveBound::Flag(_), None) => None,
+            (ExclusiveBound::Value(e), None) => Some((e, true)),
+            (ExclusiveBound::Value(e), Some(v)) => {
+                let exclusive_is_stricter = if lower { e >= v } else { e <= v };
+                if exclusive_is_stricter {
+                    Some((e, true))
+                } else {
+                    Some((v, false))
+                }
+            }
+        }
+    }
+}
+
+impl NumberType {
+    /// The effective lower bound as `(value, exclusive)`, whichever form was used.
+    pub fn lower_bound(&self) -> Option<(f64, bool)> {
+        self.exclusive_minimum.normalize(self.minimum, true)
+    }
+
+    /// The effective upper bound as `(value, exclusive)`, whichever form was used.
+    pub fn upper_bound(&self) -> Option<(f64, bool)> {
+        self.exclusive_maximum.normalize(self.maximum, false)
+    }
+}
+
+impl IntegerType {
+    /// The effective lower bound as `(value, exclusive)`, whichever form was used.
+    pub fn lower_bound(&self) -> Option<(i64, bool)> {
+        self.exclusive_minimum.normalize(self.minimum, true)
+    }
+
+    /// The effective upper bound as `(value, exclusive)`, whichever form was used.
+    pub fn upper_bound(&self) -> Option<(i64, bool)> {
+        self.exclusive_maximum.normalize(self.maximum, false)
+    }
+}
+
+impl AnySchema {
+    /// The effective lower bound as `(value, exclusive)`, whichever form was used.
+    pub fn lower_bound(&self) -> Option<(f64, bool)> {
+        self.exclusive_minimum.unwrap_or_default().normalize(self.minimum, true)
+    }
+
+    /// The effective upper bound as `(value, exclusive)`, whichever form was used.
+    pub fn upper_bound(&self) -> Option<(f64, bool)> {
+        self.exclusive_maximum.unwrap_or_default().normalize(self.maximum, false)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectType {
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
@@ -323,6 +656,8 @@ pub struct ObjectType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ArrayType {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -336,6 +671,8 @@ pub struct ArrayType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum NumberFormat {
     Float,
@@ -343,6 +680,8 @@ pub enum NumberFormat {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum IntegerFormat {
     Int32,
@@ -350,6 +689,8 @@ pub enum IntegerFormat {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum StringFormat {
     Date,
@@ -410,8 +751,7 @@ impl Schema {
             SchemaKind::Any(AnySchema { properties, .. }) => Box::new(properties.iter()),
             SchemaKind::AllOf { all_of } => Box::new(all_of
                 .iter()
-                .map(move |schema| schema.resolve(spec).properties_iter(spec))
-                .flatten()),
+                .flat_map(move |schema| schema.resolve(spec).properties_iter(spec))),
             _ => Box::new(std::iter::empty())
         }
     }
@@ -473,6 +813,102 @@ impl Schema {
             _ => false,
         }
     }
+
+    /// The values declared by `enum`, if any, as JSON values.
+    pub fn enum_values(&self) -> Option<Vec<Value>> {
+        let values: Vec<Value> = match &self.kind {
+            SchemaKind::Type(Type::String(s)) => s.enumeration.iter().map(|v| Value::from(v.as_str())).collect(),
+            SchemaKind::Type(Type::Number(n)) => n.enumeration.iter().map(|v| v.map(Value::from).unwrap_or(Value::Null)).collect(),
+            SchemaKind::Type(Type::Integer(i)) => i.enumeration.iter().map(|v| v.map(Value::from).unwrap_or(Value::Null)).collect(),
+            SchemaKind::Any(a) => a.enumeration.clone(),
+            _ => return None,
+        };
+        if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        }
+    }
+
+    /// The values this schema admits once `enum` and `const` are combined.
+    ///
+    /// Returns `None` when neither keyword is present. When both are present the
+    /// result is their intersection, which is empty if `const` is not one of the
+    /// enumerated values.
+    pub fn allowed_values(&self) -> Option<Vec<Value>> {
+        match (&self.data.const_value, self.enum_values()) {
+            (Some(c), Some(values)) => Some(values.into_iter().filter(|v| v == c).take(1).collect()),
+            (Some(c), None) => Some(vec![c.clone()]),
+            (None, values) => values,
+        }
+    }
+}
+
+impl SchemaKind {
+    /// Converts this kind into the catch-all [AnySchema] form, keeping every constraint.
+    pub fn into_any(self) -> AnySchema {
+        match self {
+            SchemaKind::Type(Type::String(s)) => AnySchema {
+                typ: Some("string".to_string()),
+                format: if s.format.is_empty() { None } else { Some(s.format.as_str().to_string()) },
+                pattern: s.pattern,
+                enumeration: s.enumeration.into_iter().map(Value::from).collect(),
+                min_length: s.min_length,
+                max_length: s.max_length,
+                content_encoding: s.content_encoding,
+                content_media_type: s.content_media_type,
+                ..AnySchema::default()
+            },
+            SchemaKind::Type(Type::Number(n)) => AnySchema {
+                typ: Some("number".to_string()),
+                format: serde_json::to_value(&n.format).ok().and_then(|v| v.as_str().map(String::from)),
+                multiple_of: n.multiple_of,
+                exclusive_minimum: Some(n.exclusive_minimum).filter(|b| !b.is_unset()),
+                exclusive_maximum: Some(n.exclusive_maximum).filter(|b| !b.is_unset()),
+                minimum: n.minimum,
+                maximum: n.maximum,
+                enumeration: n.enumeration.into_iter().map(|v| v.map(Value::from).unwrap_or(Value::Null)).collect(),
+                ..AnySchema::default()
+            },
+            SchemaKind::Type(Type::Integer(i)) => AnySchema {
+                typ: Some("integer".to_string()),
+                format: serde_json::to_value(&i.format).ok().and_then(|v| v.as_str().map(String::from)),
+                multiple_of: i.multiple_of.map(|v| v as f64),
+                exclusive_minimum: Some(i.exclusive_minimum).filter(|b| !b.is_unset()).map(|b| b.map(|v| v as f64)),
+                exclusive_maximum: Some(i.exclusive_maximum).filter(|b| !b.is_unset()).map(|b| b.map(|v| v as f64)),
+                minimum: i.minimum.map(|v| v as f64),
+                maximum: i.maximum.map(|v| v as f64),
+                enumeration: i.enumeration.into_iter().map(|v| v.map(Value::from).unwrap_or(Value::Null)).collect(),
+                ..AnySchema::default()
+            },
+            SchemaKind::Type(Type::Object(o)) => AnySchema {
+                typ: Some("object".to_string()),
+                properties: o.properties,
+                required: o.required,
+                additional_properties: o.additional_properties,
+                min_properties: o.min_properties,
+                max_properties: o.max_properties,
+                ..AnySchema::default()
+            },
+            SchemaKind::Type(Type::Array(a)) => AnySchema {
+                typ: Some("array".to_string()),
+                items: a.items,
+                min_items: a.min_items,
+                max_items: a.max_items,
+                unique_items: Some(a.unique_items).filter(|b| *b),
+                ..AnySchema::default()
+            },
+            SchemaKind::Type(Type::Boolean {}) => AnySchema {
+                typ: Some("boolean".to_string()),
+                ..AnySchema::default()
+            },
+            SchemaKind::OneOf { one_of } => AnySchema { one_of, ..AnySchema::default() },
+            SchemaKind::AllOf { all_of } => AnySchema { all_of, ..AnySchema::default() },
+            SchemaKind::AnyOf { any_of } => AnySchema { any_of, ..AnySchema::default() },
+            SchemaKind::Not { not } => AnySchema { not: Some(not), ..AnySchema::default() },
+            SchemaKind::Any(a) => a,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -480,7 +916,7 @@ mod tests {
     use assert_matches::assert_matches;
     use serde_json::json;
 
-    use crate::{AnySchema, Schema, SchemaData, SchemaKind};
+    use crate::{AnySchema, RefOr, Schema, SchemaData, SchemaKind};
 
     #[test]
     fn test_schema_with_extensions() {
@@ -577,6 +1013,69 @@ allOf:
         }
     }
 
+    #[test]
+    fn test_const() {
+        let schema = serde_json::from_value::<Schema>(json!({"type": "string", "const": "dog"})).unwrap();
+        assert_eq!(schema.data.const_value, Some(json!("dog")));
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "string", "const": "dog"}));
+        assert_eq!(schema.allowed_values(), Some(vec![json!("dog")]));
+
+        let schema = serde_json::from_value::<Schema>(json!({"const": null})).unwrap();
+        assert_eq!(schema.data.const_value, Some(json!(null)));
+
+        let schema = serde_json::from_value::<Schema>(json!({"type": "string", "enum": ["cat"], "const": "dog"})).unwrap();
+        assert_eq!(schema.allowed_values(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_exclusive_bounds() {
+        let schema = serde_json::from_value::<Schema>(json!({"type": "number", "minimum": 1, "exclusiveMinimum": true})).unwrap();
+        let SchemaKind::Type(crate::Type::Number(n)) = &schema.kind else { panic!() };
+        assert_eq!(n.lower_bound(), Some((1.0, true)));
+        assert_eq!(n.upper_bound(), None);
+
+        let schema = serde_json::from_value::<Schema>(json!({"type": "integer", "exclusiveMaximum": 10, "maximum": 20})).unwrap();
+        let SchemaKind::Type(crate::Type::Integer(i)) = &schema.kind else { panic!() };
+        assert_eq!(i.upper_bound(), Some((10, true)));
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!({"type": "integer", "exclusiveMaximum": 10, "maximum": 20}));
+    }
+
+    #[test]
+    fn test_conditional() {
+        let value = json!({
+            "type": "object",
+            "if": {"properties": {"country": {"const": "US"}}},
+            "then": {"required": ["zip"]},
+            "else": {"required": ["postcode"]}
+        });
+        let schema = serde_json::from_value::<Schema>(value.clone()).unwrap();
+        assert!(matches!(schema.kind, SchemaKind::Type(crate::Type::Object(_))));
+        assert!(schema.data.if_.is_some() && schema.data.then.is_some() && schema.data.else_.is_some());
+        assert_eq!(serde_json::to_value(&schema).unwrap(), value);
+    }
+
+    #[test]
+    fn test_constructors() {
+        let mut components = crate::Components::default();
+        let user = components.add_schema("User", Schema::object([("id", Schema::integer(), true)]));
+        assert_eq!(user, RefOr::schema_ref("User"));
+        let pet = Schema::object([
+            ("name", RefOr::from(Schema::string()), true),
+            ("owner", user, false),
+            ("tags", Schema::array(Schema::string()).into(), false),
+        ]);
+        assert_eq!(serde_json::to_value(&pet).unwrap(), json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "owner": {"$ref": "#/components/schemas/User"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["name"]
+        }));
+        assert_eq!(*components.schemas["User"].as_item().unwrap().required(), ["id"]);
+    }
+
     #[test]
     fn test_with_format() {
         use crate::variant_or::VariantOrUnknownOrEmpty;
@@ -0,0 +1,270 @@
+use std::fmt;
+
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{Error as _, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Error, Map, Value};
+
+use crate::util::Key;
+use crate::{AdditionalProperties, AnySchema, ArrayType, ObjectType, RefOr, RefOrMap, Schema, SchemaData, SchemaKind, Type};
+
+// `Schema` flattens `SchemaData` and the untagged `SchemaKind`, and derived code
+// would buffer every schema object again at each level of nesting. Instead a
+// schema is read in one pass: the keywords of `SchemaData` and those taking
+// schemas go straight into place, and only the few scalar keywords that pick the
+// kind are held until the end of the object. Errors in nested schemas keep the
+// position the deserializer reports.
+//
+// JSON Schema 2020-12, and so OpenAPI 3.1, also allows `true` and `false`
+// wherever a schema goes. They are read as the schemas they are equivalent to,
+// `{}` and `{"not": {}}`.
+
+impl<'de> Deserialize<'de> for Schema {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SchemaVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for AdditionalProperties {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AdditionalVisitor;
+
+        impl<'de> Visitor<'de> for AdditionalVisitor {
+            type Value = AdditionalProperties;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a boolean, or a Schema Object or Reference")
+            }
+
+            fn visit_bool<E>(self, any: bool) -> Result<AdditionalProperties, E> {
+                Ok(AdditionalProperties::Any(any))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<AdditionalProperties, A::Error> {
+                let schema = RefOr::<Schema>::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(AdditionalProperties::Schema(Box::new(schema)))
+            }
+        }
+
+        deserializer.deserialize_any(AdditionalVisitor)
+    }
+}
+
+/// The keywords that pick the kind of a schema, kept as read until the kind is known.
+const KIND_KEYWORDS: [&str; 19] = [
+    "type", "format", "pattern", "enum", "multipleOf", "exclusiveMinimum", "exclusiveMaximum", "minimum", "maximum",
+    "required", "minProperties", "maxProperties", "minItems", "maxItems", "uniqueItems", "minLength", "maxLength",
+    "contentEncoding", "contentMediaType",
+];
+
+/// Keywords read into `f64`, whose exact values [SchemaData::numbers] keeps.
+#[cfg(feature = "arbitrary_precision")]
+const NUMERIC_KEYWORDS: [&str; 5] = ["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "multipleOf"];
+
+/// The keywords taking schemas that belong to some kinds only.
+#[derive(Default)]
+struct Nested {
+    properties: Option<RefOrMap<Schema>>,
+    additional_properties: Option<AdditionalProperties>,
+    items: Option<Box<RefOr<Schema>>>,
+    one_of: Option<Vec<RefOr<Schema>>>,
+    all_of: Option<Vec<RefOr<Schema>>>,
+    any_of: Option<Vec<RefOr<Schema>>>,
+    not: Option<Box<RefOr<Schema>>>,
+}
+
+struct SchemaVisitor;
+
+impl<'de> Visitor<'de> for SchemaVisitor {
+    type Value = Schema;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Schema Object or boolean")
+    }
+
+    fn visit_bool<E>(self, valid: bool) -> Result<Schema, E> {
+        Ok(if valid {
+            Schema::new_any()
+        } else {
+            Schema::new_kind(SchemaKind::Not { not: Box::new(RefOr::Item(Schema::new_any())) })
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Schema, A::Error> {
+        let mut data = SchemaData::default();
+        let mut nested = Nested::default();
+        let mut keywords = Map::new();
+        while let Some(key) = map.next_key::<Key>()? {
+            match key.as_str() {
+                k if k.starts_with("x-") => {
+                    let value = map.next_value()?;
+                    data.extensions.insert(key.into_string(), value);
+                }
+                "nullable" => data.nullable = map.next_value()?,
+                "readOnly" => data.read_only = map.next_value()?,
+                "writeOnly" => data.write_only = map.next_value()?,
+                "deprecated" => data.deprecated = map.next_value()?,
+                "externalDocs" => data.external_docs = map.next_value()?,
+                "example" => data.example = map.next_value()?,
+                "title" => data.title = map.next_value()?,
+                "description" => data.description = map.next_value()?,
+                "discriminator" => data.discriminator = map.next_value()?,
+                "xml" => data.xml = map.next_value()?,
+                "default" => data.default = map.next_value()?,
+                // An explicit `const: null` is kept.
+                "const" => data.const_value = Some(map.next_value()?),
+                "if" => data.if_ = map.next_value()?,
+                "then" => data.then = map.next_value()?,
+                "else" => data.else_ = map.next_value()?,
+                "unevaluatedProperties" => data.unevaluated_properties = map.next_value()?,
+                "unevaluatedItems" => data.unevaluated_items = map.next_value()?,
+                "$schema" => data.dialect = map.next_value()?,
+                "$id" => data.id = map.next_value()?,
+                "$anchor" => data.anchor = map.next_value()?,
+                "$comment" => data.comment = map.next_value()?,
+                "$defs" => data.defs = map.next_value()?,
+                "examples" => data.examples = map.next_value()?,
+                "prefixItems" => data.prefix_items = map.next_value()?,
+                "contains" => data.contains = map.next_value()?,
+                "minContains" => data.min_contains = map.next_value()?,
+                "maxContains" => data.max_contains = map.next_value()?,
+                "patternProperties" => data.pattern_properties = map.next_value()?,
+                "propertyNames" => data.property_names = map.next_value()?,
+                "dependentSchemas" => data.dependent_schemas = map.next_value()?,
+                "dependentRequired" => data.dependent_required = map.next_value()?,
+                "contentSchema" => data.content_schema = map.next_value()?,
+                "properties" => nested.properties = Some(map.next_value()?),
+                "additionalProperties" => nested.additional_properties = map.next_value()?,
+                "items" => nested.items = map.next_value()?,
+                "oneOf" => nested.one_of = Some(map.next_value()?),
+                "allOf" => nested.all_of = Some(map.next_value()?),
+                "anyOf" => nested.any_of = Some(map.next_value()?),
+                "not" => nested.not = Some(map.next_value()?),
+                k if KIND_KEYWORDS.contains(&k) => {
+                    let value = map.next_value()?;
+                    keywords.insert(key.into_string(), value);
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        finish(data, nested, keywords).map_err(A::Error::custom)
+    }
+}
+
+fn finish(mut data: SchemaData, nested: Nested, mut keywords: Map<String, Value>) -> Result<Schema, Error> {
+    // OpenAPI 3.1 lists types, `null` among them for what 3.0 calls `nullable`.
+    let mut types = Vec::new();
+    if let Some(Value::Array(_)) = keywords.get("type") {
+        let listed: Vec<String> = serde_json::from_value(keywords.remove("type").unwrap_or_default())?;
+        let null_type = listed.len() > 1 && listed.iter().any(|t| t == "null");
+        data.nullable |= null_type;
+        types = listed.into_iter().filter(|t| !null_type || t != "null").collect();
+        if types.len() == 1 {
+            keywords.insert("type".to_string(), types.remove(0).into());
+        }
+    }
+    #[cfg(feature = "arbitrary_precision")]
+    {
+        data.numbers = NUMERIC_KEYWORDS
+            .iter()
+            .filter_map(|&k| match keywords.get(k) {
+                Some(Value::Number(n)) => Some((k.to_string(), n.clone())),
+                _ => None,
+            })
+            .collect();
+    }
+    let mut kind = kind(Value::Object(keywords), nested)?;
+    if let SchemaKind::Any(any) = &mut kind {
+        any.types = types;
+    }
+    Ok(Schema { data, kind })
+}
+
+/// Picks the variant the way `#[serde(untagged)]` on [SchemaKind] would: the first
+/// one that deserializes, in declaration order.
+fn kind(keywords: Value, nested: Nested) -> Result<SchemaKind, Error> {
+    let Nested { properties, additional_properties, items, one_of, all_of, any_of, not } = nested;
+    match keywords.get("type").and_then(Value::as_str) {
+        Some("object") => {
+            if let Ok(object) = ObjectType::deserialize(&keywords) {
+                return Ok(SchemaKind::Type(Type::Object(ObjectType {
+                    properties: properties.unwrap_or_default(),
+                    additional_properties,
+                    ..object
+                })));
+            }
+        }
+        Some("array") => {
+            if let Ok(array) = ArrayType::deserialize(&keywords) {
+                return Ok(SchemaKind::Type(Type::Array(ArrayType { items, ..array })));
+            }
+        }
+        Some("string" | "number" | "integer" | "boolean") => {
+            if let Ok(typ) = Type::deserialize(&keywords) {
+                return Ok(SchemaKind::Type(typ));
+            }
+        }
+        _ => {}
+    }
+    if let Some(one_of) = one_of {
+        return Ok(SchemaKind::OneOf { one_of });
+    }
+    if let Some(all_of) = all_of {
+        return Ok(SchemaKind::AllOf { all_of });
+    }
+    if let Some(any_of) = any_of {
+        return Ok(SchemaKind::AnyOf { any_of });
+    }
+    if let Some(not) = not {
+        return Ok(SchemaKind::Not { not });
+    }
+    let any = AnySchema::deserialize(keywords)?;
+    Ok(SchemaKind::Any(AnySchema {
+        properties: properties.unwrap_or_default(),
+        additional_properties,
+        items,
+        ..any
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_kind_fallback() {
+        let kind = |value: Value| serde_json::from_value::<Schema>(value).unwrap().kind;
+        // Keywords that don't fit the declared type fall back to Any, as with `untagged`.
+        assert!(matches!(kind(json!({"type": "integer", "minimum": 1.5})), SchemaKind::Any(AnySchema { minimum: Some(_), .. })));
+        assert!(matches!(kind(json!({"type": "string", "enum": [1]})), SchemaKind::Any(_)));
+        assert!(matches!(kind(json!({"type": "object", "required": "a", "oneOf": []})), SchemaKind::OneOf { .. }));
+        assert!(matches!(kind(json!({"type": ["string", "integer"], "anyOf": [{"type": "string"}]})), SchemaKind::AnyOf { .. }));
+
+        let SchemaKind::Type(Type::Object(object)) = kind(json!({
+            "type": "object",
+            "properties": {"b": {"$ref": "#/components/schemas/B"}, "a": {"type": "array", "items": {"type": "string"}}},
+            "additionalProperties": false
+        })) else { panic!() };
+        assert_eq!(object.properties.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+        assert_eq!(object.properties["b"].as_ref_str(), Some("#/components/schemas/B"));
+        assert_eq!(object.additional_properties, Some(AdditionalProperties::Any(false)));
+
+        let schema = serde_json::from_value::<Schema>(json!({"x-b": 1, "title": "t", "x-a": 2})).unwrap();
+        assert_eq!(schema.data.extensions.keys().collect::<Vec<_>>(), vec!["x-b", "x-a"]);
+        assert!(serde_json::from_value::<Schema>(json!({"properties": {"a": 1}})).is_err());
+    }
+
+    #[test]
+    fn test_error_position() {
+        let input = "{\n  \"type\": \"object\",\n  \"properties\": {\n    \"a\": {\"type\": \"string\", \"minLength\": \"x\"}\n  }\n}";
+        let err = serde_json::from_str::<Schema>(input).unwrap_err();
+        assert_eq!((err.line(), err.column()), (4, 45), "{}", err);
+        let err = serde_json::from_str::<RefOr<Schema>>("{\"items\": {\"$ref\": \"#/a\", \"title\": 1}, \"type\": \"array\", \"maxItems\": -1}").unwrap_err();
+        assert_eq!(err.line(), 1, "{}", err);
+        assert!(err.column() > 0, "{}", err);
+    }
+}
@@ -0,0 +1,148 @@
+use std::cell::Cell;
+
+use serde::ser::Error as _;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::{Schema, SchemaData, SchemaKind};
+
+// Schemas are written in the form of the spec they belong to: `nullable` is a
+// keyword of OpenAPI 3.0, and 3.1 lists `null` among the types instead. Which
+// form is wanted is only known to the enclosing `OpenAPI`, so it is passed down
+// through a thread local while that serializes.
+
+thread_local! {
+    static V3_1: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f`, serializing schemas within it in their OpenAPI 3.1 form if `v3_1`.
+pub(crate) fn with_version<R>(v3_1: bool, f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            V3_1.with(|v| v.set(self.0));
+        }
+    }
+    let _restore = Restore(V3_1.with(|v| v.replace(v3_1)));
+    f()
+}
+
+impl Serialize for Schema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Fields<'a> {
+            #[serde(flatten)]
+            data: &'a SchemaData,
+            #[serde(flatten)]
+            kind: &'a SchemaKind,
+        }
+        let fields = Fields { data: &self.data, kind: &self.kind };
+        let null = self.data.nullable && V3_1.with(Cell::get);
+        #[cfg(feature = "arbitrary_precision")]
+        let exact = !self.data.numbers.is_empty();
+        #[cfg(not(feature = "arbitrary_precision"))]
+        let exact = false;
+        if !null && !exact {
+            return fields.serialize(serializer);
+        }
+        let mut value = serde_json::to_value(&fields).map_err(S::Error::custom)?;
+        if let Value::Object(map) = &mut value {
+            #[cfg(feature = "arbitrary_precision")]
+            exact_numbers(map, &self.data.numbers);
+            if null {
+                null_type(map);
+            }
+        }
+        value.serialize(serializer)
+    }
+}
+
+/// Writes the numeric keywords as they were read, unless they were changed since.
+#[cfg(feature = "arbitrary_precision")]
+fn exact_numbers(map: &mut Map<String, Value>, numbers: &indexmap::IndexMap<String, serde_json::Number>) {
+    for (key, number) in numbers {
+        if let Some(Value::Number(n)) = map.get_mut(key) {
+            if n.as_f64() == number.as_f64() {
+                *n = number.clone();
+            }
+        }
+    }
+}
+
+/// Replaces `nullable: true` in a serialized schema by a `null` type, or by an
+/// `anyOf` with a `null` schema for one without a `type`, and adds `null` to its
+/// `enum`, as in OpenAPI 3.1 and JSON Schema.
+pub(crate) fn null_type(map: &mut Map<String, Value>) {
+    if map.remove("nullable") != Some(Value::Bool(true)) {
+        return;
+    }
+    match map.get_mut("type") {
+        Some(Value::String(typ)) => {
+            let typ = std::mem::take(typ);
+            map.insert("type".to_string(), json!([typ, "null"]));
+        }
+        Some(Value::Array(types)) if !types.contains(&json!("null")) => types.push(json!("null")),
+        Some(_) => {}
+        None => {
+            let schema = Value::Object(std::mem::take(map));
+            map.insert("anyOf".to_string(), json!([schema, {"type": "null"}]));
+        }
+    }
+    if let Some(Value::Array(values)) = map.get_mut("enum") {
+        if !values.contains(&Value::Null) {
+            values.push(Value::Null);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::*;
+
+    #[test]
+    fn test_round_trip_v3_1() {
+        let input = json!({
+            "openapi": "3.1.0",
+            "info": {"title": "t", "summary": "A pet store", "version": "1"},
+            "jsonSchemaDialect": "https://spec.openapis.org/oas/3.1/dialect/base",
+            "paths": {},
+            "webhooks": {"newPet": {"post": {
+                "requestBody": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}},
+                "responses": {"200": {"description": "ok"}}
+            }}},
+            "components": {"schemas": {"Pet": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": ["string", "null"], "maxLength": 20},
+                    "tag": {"type": ["string", "integer"]},
+                    "owner": {"anyOf": [{"allOf": [{"$ref": "#/components/schemas/Pet/$defs/Owner"}]}, {"type": "null"}]},
+                    "point": {"type": "array", "prefixItems": [{"type": "number"}, {"type": "number"}], "minContains": 1, "contains": {"type": "number"}}
+                },
+                "$defs": {"Owner": {"type": "object", "examples": [{}]}},
+                "patternProperties": {"^x_": {"type": "string"}},
+                "dependentRequired": {"tag": ["name"]}
+            }}}
+        });
+        let spec: OpenAPI = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(spec.info.summary.as_deref(), Some("A pet store"));
+        assert!(spec.json_schema_dialect.is_some());
+        assert!(spec.webhooks["newPet"].as_item().unwrap().post.is_some());
+        let pet = spec.schemas["Pet"].as_item().unwrap();
+        let name = pet.properties()["name"].as_item().unwrap();
+        assert!(name.nullable && matches!(name.kind, SchemaKind::Type(Type::String(_))));
+        let tag = pet.properties()["tag"].as_item().unwrap();
+        assert!(matches!(&tag.kind, SchemaKind::Any(any) if any.types == ["string", "integer"]));
+        assert_eq!(pet.defs["Owner"].as_item().unwrap().examples, [json!({})]);
+        assert_eq!(serde_json::to_value(&spec).unwrap(), input);
+
+        let mut v3_0 = spec.clone();
+        v3_0.openapi = "3.0.3".to_string();
+        let json = serde_json::to_value(&v3_0).unwrap();
+        assert_eq!(json["components"]["schemas"]["Pet"]["properties"]["name"], json!({"type": "string", "nullable": true, "maxLength": 20}));
+        v3_0.downgrade_to_v3_0();
+        let tag = v3_0.schemas["Pet"].as_item().unwrap().properties()["tag"].as_item().unwrap();
+        assert_eq!(serde_json::to_value(tag).unwrap(), json!({"anyOf": [{"type": "string"}, {"type": "integer"}]}));
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use indexmap::IndexMap;
 
 /// Lists the required security schemes to execute this operation.
@@ -14,3 +16,66 @@ use indexmap::IndexMap;
 /// Security Requirement Objects in the list needs to be satisfied
 /// to authorize the request.
 pub type SecurityRequirement = IndexMap<String, Vec<String>>;
+
+/// The security schemes a client holds credentials for, and the scopes granted
+/// with each, to check against [SecurityRequirement]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Credentials {
+    pub schemes: HashMap<String, HashSet<String>>,
+}
+
+impl Credentials {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a credential for `scheme`, granting `scopes` besides any it already has.
+    pub fn with<S: Into<String>>(mut self, scheme: impl Into<String>, scopes: impl IntoIterator<Item=S>) -> Self {
+        self.schemes.entry(scheme.into()).or_default().extend(scopes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether any one of `requirements` is satisfied, as for the `security` of an
+    /// operation or spec. An empty list requires nothing.
+    pub fn satisfies(&self, requirements: &[SecurityRequirement]) -> bool {
+        requirements.is_empty() || requirements.iter().any(|requirement| requirement.is_satisfied_by(self))
+    }
+}
+
+/// Evaluation of a [SecurityRequirement], which can't have inherent methods as a type alias.
+pub trait SecurityRequirementExt {
+    /// Whether `credentials` hold every scheme of the requirement, each with all of
+    /// its scopes. The empty requirement `{}` is always satisfied.
+    fn is_satisfied_by(&self, credentials: &Credentials) -> bool;
+}
+
+impl SecurityRequirementExt for SecurityRequirement {
+    fn is_satisfied_by(&self, credentials: &Credentials) -> bool {
+        self.iter().all(|(scheme, scopes)| {
+            credentials.schemes.get(scheme).is_some_and(|granted| scopes.iter().all(|scope| granted.contains(scope)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_satisfied_by() {
+        let both: SecurityRequirement = IndexMap::from([
+            ("key".to_string(), vec![]),
+            ("oauth".to_string(), vec!["read".to_string(), "write".to_string()]),
+        ]);
+        let read: SecurityRequirement = IndexMap::from([("oauth".to_string(), vec!["read".to_string()])]);
+        let credentials = Credentials::new().with("oauth", ["read"]);
+        assert!(read.is_satisfied_by(&credentials));
+        assert!(!both.is_satisfied_by(&credentials));
+        assert!(both.is_satisfied_by(&credentials.clone().with("oauth", ["write"]).with("key", Vec::<String>::new())));
+        assert!(SecurityRequirement::new().is_satisfied_by(&Credentials::new()));
+
+        assert!(credentials.satisfies(&[both.clone(), read]));
+        assert!(!credentials.satisfies(&[both]));
+        assert!(Credentials::new().satisfies(&[]));
+    }
+}
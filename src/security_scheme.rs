@@ -1,11 +1,19 @@
+use std::fmt;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::example_validation::child;
+use crate::{Credentials, OpenAPI, Operation, RefOr, SecurityRequirement};
+
 /// Defines a security scheme that can be used by the operations.
 /// Supported schemes are HTTP authentication, an API key (either as a
 /// header or as a query parameter), OAuth2's common flows (implicit, password,
-/// application and access code) as defined in RFC6749, and OpenID Connect Discovery.
+/// application and access code) as defined in RFC6749, OpenID Connect Discovery,
+/// and mutual TLS.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type")]
 pub enum SecurityScheme {
     #[serde(rename = "apiKey")]
@@ -39,9 +47,50 @@ pub enum SecurityScheme {
         #[serde(skip_serializing_if = "Option::is_none")]
         description: Option<String>,
     },
+    /// Client certificates, checked by the TLS layer. New in OpenAPI 3.1.
+    #[serde(rename = "mutualTLS")]
+    MutualTLS {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+}
+
+impl SecurityScheme {
+    /// An API key sent in the header `name`.
+    pub fn api_key_header(name: impl Into<String>) -> Self {
+        Self::api_key(APIKeyLocation::Header, name)
+    }
+
+    /// An API key sent in the query parameter `name`.
+    pub fn api_key_query(name: impl Into<String>) -> Self {
+        Self::api_key(APIKeyLocation::Query, name)
+    }
+
+    /// An API key sent in the cookie `name`.
+    pub fn api_key_cookie(name: impl Into<String>) -> Self {
+        Self::api_key(APIKeyLocation::Cookie, name)
+    }
+
+    fn api_key(location: APIKeyLocation, name: impl Into<String>) -> Self {
+        SecurityScheme::APIKey { location, name: name.into(), description: None }
+    }
+
+    /// Whether this is an API key sent as `name` in `location`. Header names
+    /// compare case-insensitively, as in HTTP.
+    pub fn is_api_key(&self, location: &APIKeyLocation, name: &str) -> bool {
+        match self {
+            SecurityScheme::APIKey { location: l, name: n, .. } if l == location => match location {
+                APIKeyLocation::Header => n.eq_ignore_ascii_case(name),
+                APIKeyLocation::Query | APIKeyLocation::Cookie => n == name,
+            },
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum APIKeyLocation {
     Query,
@@ -50,6 +99,8 @@ pub enum APIKeyLocation {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct OAuth2Flows {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -63,6 +114,8 @@ pub struct OAuth2Flows {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ImplicitOAuth2Flow {
     pub authorization_url: String,
@@ -73,6 +126,8 @@ pub struct ImplicitOAuth2Flow {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct OAuth2Flow {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -83,6 +138,8 @@ pub struct OAuth2Flow {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct AuthCodeOAuth2Flow {
     pub authorization_url: String,
@@ -91,4 +148,570 @@ pub struct AuthCodeOAuth2Flow {
     pub refresh_url: Option<String>,
     #[serde(default)]
     pub scopes: IndexMap<String, String>,
-}
\ No newline at end of file
+}
+
+impl OAuth2Flows {
+    /// The scopes declared by any of the flows, with their descriptions, each once.
+    /// Where flows describe a scope differently, the first flow's description is kept.
+    pub fn scopes(&self) -> IndexMap<&str, &str> {
+        let mut scopes = IndexMap::new();
+        let flows = [
+            self.implicit.as_ref().map(|f| &f.scopes),
+            self.password.as_ref().map(|f| &f.scopes),
+            self.client_credentials.as_ref().map(|f| &f.scopes),
+            self.authorization_code.as_ref().map(|f| &f.scopes),
+        ];
+        for (scope, description) in IntoIterator::into_iter(flows).flatten().flatten() {
+            scopes.entry(scope.as_str()).or_insert(description.as_str());
+        }
+        scopes
+    }
+
+    /// Whether any of the flows declares `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().contains_key(scope)
+    }
+
+    /// The scopes added and removed in `newer`, a later version of these flows.
+    pub fn diff_scopes<'a>(&'a self, newer: &'a OAuth2Flows) -> ScopeDiff<'a> {
+        let (old, new) = (self.scopes(), newer.scopes());
+        ScopeDiff {
+            added: new.keys().filter(|s| !old.contains_key(*s)).copied().collect(),
+            removed: old.keys().filter(|s| !new.contains_key(*s)).copied().collect(),
+        }
+    }
+}
+
+/// The difference between the scopes of two versions of [OAuth2Flows], see
+/// [OAuth2Flows::diff_scopes].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScopeDiff<'a> {
+    pub added: Vec<&'a str>,
+    pub removed: Vec<&'a str>,
+}
+
+impl ScopeDiff<'_> {
+    /// Whether scopes were removed, which breaks clients still requesting them.
+    pub fn is_breaking(&self) -> bool {
+        !self.removed.is_empty()
+    }
+}
+
+/// A scope in a security requirement that its OAuth2 scheme doesn't declare.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeError {
+    /// JSON pointer to the requirement, e.g. `/paths/~1pets/get/security/0/petstore_auth`.
+    pub pointer: String,
+    pub scheme: String,
+    pub scope: String,
+}
+
+impl fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: scope {:?} is not declared by {:?}", self.pointer, self.scope, self.scheme)
+    }
+}
+
+impl std::error::Error for ScopeError {}
+
+/// A header-based API key scheme whose name can't be used, from [OpenAPI::validate_api_keys].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKeyError {
+    /// JSON pointer to the scheme, e.g. `/components/securitySchemes/api_key`.
+    pub pointer: String,
+    pub name: String,
+    pub message: String,
+}
+
+impl fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: header {:?} {}", self.pointer, self.name, self.message)
+    }
+}
+
+impl std::error::Error for ApiKeyError {}
+
+/// A security requirement naming a scheme the components don't declare.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndeclaredSchemeError {
+    pub scheme: String,
+}
+
+impl fmt::Display for UndeclaredSchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "security scheme {:?} is not declared in components", self.scheme)
+    }
+}
+
+impl std::error::Error for UndeclaredSchemeError {}
+
+/// Headers which OpenAPI ignores as parameters, being described by other means.
+/// `Authorization` in particular belongs to an `http` scheme.
+pub(crate) const RESERVED_HEADERS: [&str; 3] = ["Accept", "Content-Type", "Authorization"];
+
+/// A security scheme a caller must satisfy, with the scopes it must be granted,
+/// see [OpenAPI::required_scopes].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequiredScheme<'a> {
+    pub name: &'a str,
+    /// The declared scheme, `None` if it's missing from the components or a `$ref`.
+    pub scheme: Option<&'a SecurityScheme>,
+    pub scopes: &'a [String],
+}
+
+impl OpenAPI {
+    /// Checks that the scopes the security requirements, global and per operation,
+    /// ask of OAuth2 schemes are declared by one of the scheme's flows. Requirements
+    /// of other kinds of schemes, or of schemes that aren't declared, are skipped.
+    pub fn validate_scopes(&self) -> Result<(), Vec<ScopeError>> {
+        let mut errors = Vec::new();
+        let mut check = |pointer: &str, requirements: &[SecurityRequirement]| {
+            for (i, requirement) in requirements.iter().enumerate() {
+                for (scheme, scopes) in requirement {
+                    let Some(SecurityScheme::OAuth2 { flows, .. }) = self.security_schemes.get(scheme).and_then(RefOr::as_item) else { continue };
+                    let declared = flows.scopes();
+                    for scope in scopes.iter().filter(|s| !declared.contains_key(s.as_str())) {
+                        errors.push(ScopeError {
+                            pointer: child(&child(pointer, &i.to_string()), scheme),
+                            scheme: scheme.clone(),
+                            scope: scope.clone(),
+                        });
+                    }
+                }
+            }
+        };
+        check("/security", &self.security);
+        for (path, method, operation, _) in self.operations() {
+            if let Some(security) = &operation.security {
+                check(&child(&child(&child("/paths", path), method), "security"), security);
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Checks that API key schemes sent in headers name valid, unreserved headers.
+    /// A key in `Authorization` should be an `http` scheme instead, e.g. `bearer`.
+    pub fn validate_api_keys(&self) -> Result<(), Vec<ApiKeyError>> {
+        let mut errors = Vec::new();
+        for (scheme_name, scheme) in &self.security_schemes {
+            let Some(SecurityScheme::APIKey { location: APIKeyLocation::Header, name, .. }) = scheme.as_item() else { continue };
+            let message = if http::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                "is not a valid header name"
+            } else if RESERVED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                "is reserved"
+            } else {
+                continue;
+            };
+            errors.push(ApiKeyError {
+                pointer: child("/components/securitySchemes", scheme_name),
+                name: name.clone(),
+                message: message.to_string(),
+            });
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// The API key scheme sent as `name` in `location`, with its name in the
+    /// components, e.g. to find which scheme an incoming request's header belongs to.
+    pub fn api_key_scheme(&self, location: &APIKeyLocation, name: &str) -> Option<(&str, &SecurityScheme)> {
+        self.security_schemes.iter()
+            .filter_map(|(scheme_name, scheme)| Some((scheme_name.as_str(), scheme.as_item()?)))
+            .find(|(_, scheme)| scheme.is_api_key(location, name))
+    }
+
+    /// The ways a caller may authorize the operation with this `operationId`, taking
+    /// the operation's security over the spec's. Satisfying any one alternative
+    /// suffices, and satisfying one means satisfying all of its schemes. An empty
+    /// alternative allows anonymous calls, so an unsecured operation has exactly that
+    /// one. `None` if no operation has the id.
+    pub fn required_scopes(&self, operation_id: &str) -> Option<Vec<Vec<RequiredScheme<'_>>>> {
+        let operation = self.resolved().operations().find(|op| op.operation.operation_id.as_deref() == Some(operation_id))?;
+        Some(self.alternatives(operation.security()))
+    }
+
+    fn alternatives<'a>(&'a self, security: &'a [SecurityRequirement]) -> Vec<Vec<RequiredScheme<'a>>> {
+        if security.is_empty() {
+            return vec![Vec::new()];
+        }
+        security.iter().map(|requirement| {
+            requirement.iter().map(|(name, scopes)| RequiredScheme {
+                name,
+                scheme: self.security_schemes.get(name).and_then(RefOr::as_item),
+                scopes,
+            }).collect()
+        }).collect()
+    }
+
+    /// The effective security of every operation, in document order, for security
+    /// reviews. Its `Display` is a Markdown table.
+    pub fn security_summary(&self) -> SecuritySummary<'_> {
+        let operations = self.resolved().operations().map(|op| OperationSecurity {
+            path: op.path,
+            method: op.method,
+            operation_id: op.operation.operation_id.as_deref(),
+            alternatives: self.alternatives(op.security()),
+            opts_out: op.operation.security.as_ref().is_some_and(|s| s.is_empty() || s.iter().any(|r| r.is_empty())),
+        }).collect();
+        SecuritySummary { operations }
+    }
+
+    /// Makes `requirement` the spec's security, which every operation inherits
+    /// except those whose `operationId` is in `exceptions`, which become public with
+    /// `security: []`. Other operations opting out of security are made to inherit
+    /// it, while those declaring their own requirements keep them. Fails, changing
+    /// nothing, if `requirement` names a scheme the components don't declare.
+    pub fn require_security_everywhere(&mut self, requirement: SecurityRequirement, exceptions: &[&str]) -> Result<(), UndeclaredSchemeError> {
+        if let Some(scheme) = requirement.keys().find(|scheme| !self.security_schemes.contains_key(*scheme)) {
+            return Err(UndeclaredSchemeError { scheme: scheme.clone() });
+        }
+        self.security = vec![requirement];
+        for (_, _, operation) in self.operations_mut() {
+            if operation.operation_id.as_deref().is_some_and(|id| exceptions.contains(&id)) {
+                operation.security = Some(Vec::new());
+            } else if operation.security.as_ref().is_some_and(|s| s.is_empty() || s.iter().any(|r| r.is_empty())) {
+                operation.clear_security();
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes security schemes identical to an earlier one but for their name and
+    /// description, as often left behind by merges, and rewrites the security
+    /// requirements naming them to name the survivor instead. Returns the removed
+    /// names, each with the name of the scheme it now refers to.
+    pub fn dedup_security_schemes(&mut self) -> IndexMap<String, String> {
+        let mut renamed = IndexMap::new();
+        for (i, (name, scheme)) in self.security_schemes.iter().enumerate() {
+            let Some(scheme) = scheme.as_item() else { continue };
+            let survivor = self.security_schemes.iter().take(i)
+                .find(|(other, s)| !renamed.contains_key(*other) && s.as_item().is_some_and(|s| same_scheme(s, scheme)));
+            if let Some((survivor, _)) = survivor {
+                renamed.insert(name.clone(), survivor.clone());
+            }
+        }
+        if renamed.is_empty() {
+            return renamed;
+        }
+        self.security_schemes.retain(|name, _| !renamed.contains_key(name));
+        rename_requirements(&mut self.security, &renamed);
+        for (_, _, operation) in self.operations_mut() {
+            if let Some(security) = &mut operation.security {
+                rename_requirements(security, &renamed);
+            }
+        }
+        renamed
+    }
+}
+
+impl Operation {
+    /// The operation's security requirements, or those of `spec` when it declares
+    /// none. An empty list, or an empty requirement among them, allows anonymous calls.
+    pub fn effective_security<'a>(&'a self, spec: &'a OpenAPI) -> &'a [SecurityRequirement] {
+        self.security.as_deref().unwrap_or(&spec.security)
+    }
+
+    /// The ways a caller may authorize this operation of `spec`, each with the
+    /// declared schemes it joins, as [OpenAPI::required_scopes] lists them.
+    pub fn required_schemes<'a>(&'a self, spec: &'a OpenAPI) -> Vec<Vec<RequiredScheme<'a>>> {
+        spec.alternatives(self.effective_security(spec))
+    }
+
+    /// The scopes `scheme` must grant, for each alternative of the effective
+    /// security that uses it, e.g. `[["read"], ["read", "write"]]` when either
+    /// `read` with another scheme or `read` and `write` alone will do. Empty when
+    /// no alternative uses `scheme`.
+    pub fn scopes_for<'a>(&'a self, spec: &'a OpenAPI, scheme: &str) -> Vec<&'a [String]> {
+        self.effective_security(spec).iter()
+            .filter_map(|requirement| requirement.get(scheme).map(Vec::as_slice))
+            .collect()
+    }
+
+    /// Whether `credentials` satisfy the effective security of the operation.
+    pub fn is_authorized(&self, spec: &OpenAPI, credentials: &Credentials) -> bool {
+        credentials.satisfies(self.effective_security(spec))
+    }
+}
+
+fn same_scheme(a: &SecurityScheme, b: &SecurityScheme) -> bool {
+    let without_description = |scheme: &SecurityScheme| {
+        let mut scheme = scheme.clone();
+        match &mut scheme {
+            SecurityScheme::APIKey { description, .. }
+            | SecurityScheme::HTTP { description, .. }
+            | SecurityScheme::OAuth2 { description, .. }
+            | SecurityScheme::OpenIDConnect { description, .. }
+            | SecurityScheme::MutualTLS { description } => *description = None,
+        }
+        scheme
+    };
+    without_description(a) == without_description(b)
+}
+
+/// Renames schemes in `requirements`, merging the scopes of those renamed onto
+/// the same scheme, and drops requirements that become duplicates.
+fn rename_requirements(requirements: &mut Vec<SecurityRequirement>, renamed: &IndexMap<String, String>) {
+    let mut rewritten: Vec<SecurityRequirement> = Vec::new();
+    for requirement in requirements.drain(..) {
+        let mut new = SecurityRequirement::new();
+        for (name, scopes) in requirement {
+            let name = renamed.get(&name).cloned().unwrap_or(name);
+            let merged = new.entry(name).or_default();
+            for scope in scopes {
+                if !merged.contains(&scope) {
+                    merged.push(scope);
+                }
+            }
+        }
+        if !rewritten.contains(&new) {
+            rewritten.push(new);
+        }
+    }
+    *requirements = rewritten;
+}
+
+/// The effective security of an operation, see [OpenAPI::security_summary].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSecurity<'a> {
+    pub path: &'a str,
+    pub method: &'a str,
+    pub operation_id: Option<&'a str>,
+    /// As from [OpenAPI::required_scopes].
+    pub alternatives: Vec<Vec<RequiredScheme<'a>>>,
+    /// Whether the operation itself allows anonymous calls, with `security: []`
+    /// or an empty requirement, rather than inheriting that from the spec.
+    pub opts_out: bool,
+}
+
+impl OperationSecurity<'_> {
+    /// Whether the operation can be called without credentials.
+    pub fn is_anonymous(&self) -> bool {
+        self.alternatives.iter().any(Vec::is_empty)
+    }
+}
+
+/// The effective security of every operation of a spec, see [OpenAPI::security_summary].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecuritySummary<'a> {
+    pub operations: Vec<OperationSecurity<'a>>,
+}
+
+impl fmt::Display for SecuritySummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "| Operation | Operation ID | Security |")?;
+        writeln!(f, "| --- | --- | --- |")?;
+        for op in &self.operations {
+            let alternatives: Vec<String> = op.alternatives.iter().map(|alternative| {
+                if alternative.is_empty() {
+                    return if op.opts_out { "none (explicit)".to_string() } else { "none".to_string() };
+                }
+                alternative.iter().map(|required| match required.scopes {
+                    [] => required.name.to_string(),
+                    scopes => format!("{} ({})", required.name, scopes.join(", ")),
+                }).collect::<Vec<_>>().join(" and ")
+            }).collect();
+            writeln!(f, "| `{} {}` | {} | {} |", op.method.to_uppercase(), op.path, op.operation_id.unwrap_or(""), alternatives.join(" or ").replace('|', "\\|"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_scopes() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "security": [{"oauth": ["read"]}, {"key": ["ignored"]}],
+            "paths": {"/pets": {"post": {"security": [{"oauth": ["read", "admin"]}], "responses": {}}}},
+            "components": {"securitySchemes": {
+                "oauth": {"type": "oauth2", "flows": {
+                    "implicit": {"authorizationUrl": "https://a", "scopes": {"read": "Read", "write": "Write"}},
+                    "clientCredentials": {"tokenUrl": "https://t", "scopes": {"read": "Read all", "batch": "Batch"}}
+                }},
+                "key": {"type": "apiKey", "in": "header", "name": "X-Key"}
+            }}
+        })).unwrap();
+        let Some(RefOr::Item(SecurityScheme::OAuth2 { flows, .. })) = spec.security_schemes.get("oauth") else { panic!() };
+        assert_eq!(flows.scopes(), IndexMap::from([("read", "Read"), ("write", "Write"), ("batch", "Batch")]));
+        assert!(flows.has_scope("batch") && !flows.has_scope("admin"));
+        let errors: Vec<String> = spec.validate_scopes().unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, vec![r#"/paths/~1pets/post/security/0/oauth: scope "admin" is not declared by "oauth""#]);
+
+        let mut newer = flows.clone();
+        newer.implicit = None;
+        newer.authorization_code = Some(AuthCodeOAuth2Flow {
+            authorization_url: "https://a".to_string(),
+            token_url: "https://t".to_string(),
+            refresh_url: None,
+            scopes: IndexMap::from([("admin".to_string(), "Admin".to_string())]),
+        });
+        let diff = flows.diff_scopes(&newer);
+        assert_eq!(diff, ScopeDiff { added: vec!["admin"], removed: vec!["write"] });
+        assert!(diff.is_breaking());
+    }
+
+    #[test]
+    fn test_required_scopes() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "security": [{"oauth": ["read"]}, {"key": [], "oauth": []}],
+            "paths": {"/pets": {
+                "get": {"operationId": "listPets", "responses": {}},
+                "post": {"operationId": "createPet", "security": [{"oauth": ["write"]}], "responses": {}},
+                "delete": {"operationId": "deletePets", "security": [], "responses": {}}
+            }},
+            "components": {"securitySchemes": {
+                "key": {"type": "apiKey", "in": "header", "name": "X-Key"}
+            }}
+        })).unwrap();
+        let summary = |id: &str| -> Vec<Vec<(&str, bool, Vec<String>)>> {
+            spec.required_scopes(id).unwrap().iter()
+                .map(|alternative| alternative.iter().map(|r| (r.name, r.scheme.is_some(), r.scopes.to_vec())).collect())
+                .collect()
+        };
+        assert_eq!(summary("listPets"), vec![
+            vec![("oauth", false, vec!["read".to_string()])],
+            vec![("key", true, vec![]), ("oauth", false, vec![])],
+        ]);
+        assert_eq!(summary("createPet"), vec![vec![("oauth", false, vec!["write".to_string()])]]);
+        assert_eq!(summary("deletePets"), vec![vec![]]);
+        assert!(spec.required_scopes("missing").is_none());
+
+        let (list, _) = spec.get_operation("listPets").unwrap();
+        let (create, _) = spec.get_operation("createPet").unwrap();
+        assert_eq!(list.required_schemes(&spec), spec.required_scopes("listPets").unwrap());
+        assert_eq!(list.scopes_for(&spec, "oauth"), [&["read".to_string()][..], &[]]);
+        assert_eq!(create.scopes_for(&spec, "oauth"), [&["write".to_string()][..]]);
+        assert!(create.scopes_for(&spec, "key").is_empty());
+        let reader = Credentials::new().with("oauth", ["read"]);
+        assert!(list.is_authorized(&spec, &reader));
+        assert!(!create.is_authorized(&spec, &reader));
+        assert!(create.is_authorized(&spec, &reader.with("oauth", ["write"])));
+    }
+
+    #[test]
+    fn test_security_summary() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "security": [{"oauth": ["read"]}, {"key": []}],
+            "paths": {"/pets": {
+                "get": {"operationId": "listPets", "responses": {}},
+                "post": {"security": [{"oauth": ["read", "write"], "key": []}], "responses": {}},
+                "delete": {"security": [], "responses": {}}
+            }}
+        })).unwrap();
+        let summary = spec.security_summary();
+        assert_eq!(summary.operations.iter().map(|op| (op.is_anonymous(), op.opts_out)).collect::<Vec<_>>(), vec![
+            (false, false),
+            (false, false),
+            (true, true),
+        ]);
+        assert_eq!(summary.to_string(), "\
+| Operation | Operation ID | Security |
+| --- | --- | --- |
+| `GET /pets` | listPets | oauth (read) or key |
+| `POST /pets` |  | oauth (read, write) and key |
+| `DELETE /pets` |  | none (explicit) |
+");
+    }
+
+    #[test]
+    fn test_dedup_security_schemes() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "security": [{"bearer": []}, {"jwt": []}],
+            "paths": {"/pets": {"get": {"security": [{"api_key": [], "key": []}], "responses": {}}}},
+            "components": {"securitySchemes": {
+                "bearer": {"type": "http", "scheme": "bearer", "bearerFormat": "JWT"},
+                "api_key": {"type": "apiKey", "in": "header", "name": "X-Key"},
+                "jwt": {"type": "http", "scheme": "bearer", "bearerFormat": "JWT", "description": "From the users service."},
+                "key": {"type": "apiKey", "in": "header", "name": "X-Key"},
+                "query": {"type": "apiKey", "in": "query", "name": "X-Key"}
+            }}
+        })).unwrap();
+        let renamed = spec.dedup_security_schemes();
+        assert_eq!(renamed, IndexMap::from([
+            ("jwt".to_string(), "bearer".to_string()),
+            ("key".to_string(), "api_key".to_string()),
+        ]));
+        assert_eq!(spec.security_schemes.keys().collect::<Vec<_>>(), vec!["bearer", "api_key", "query"]);
+        assert_eq!(serde_json::to_value(&spec.security).unwrap(), json!([{"bearer": []}]));
+        assert_eq!(serde_json::to_value(&spec.paths["/pets"].as_item().unwrap().get).unwrap()["security"], json!([{"api_key": []}]));
+    }
+
+    #[test]
+    fn test_require_security_everywhere() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {
+                "get": {"operationId": "listPets", "responses": {}},
+                "post": {"security": [{"oauth": ["write"]}], "responses": {}},
+                "delete": {"security": [], "responses": {}}
+            }, "/health": {
+                "get": {"operationId": "health", "security": [{"oauth": []}], "responses": {}}
+            }},
+            "components": {"securitySchemes": {
+                "oauth": {"type": "oauth2", "flows": {}}
+            }}
+        })).unwrap();
+        let requirement = IndexMap::from([("oauth".to_string(), vec!["read".to_string()])]);
+        let err = spec.require_security_everywhere(IndexMap::from([("key".to_string(), vec![])]), &[]).unwrap_err();
+        assert_eq!(err.to_string(), r#"security scheme "key" is not declared in components"#);
+        assert!(spec.security.is_empty());
+
+        spec.require_security_everywhere(requirement.clone(), &["health"]).unwrap();
+        assert_eq!(spec.security, vec![requirement]);
+        let security: Vec<_> = spec.operations().map(|(_, _, op, _)| serde_json::to_value(&op.security).unwrap()).collect();
+        assert_eq!(security, vec![json!(null), json!([{"oauth": ["write"]}]), json!(null), json!([])]);
+
+        let operation = spec.get_operation_mut("listPets").unwrap();
+        operation.add_security("oauth", ["admin"]);
+        operation.add_security("key", Vec::<String>::new());
+        assert_eq!(serde_json::to_value(&operation.security).unwrap(), json!([{"oauth": ["admin"]}, {"key": []}]));
+        operation.clear_security();
+        assert!(operation.security.is_none());
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"securitySchemes": {
+                "key": {"type": "apiKey", "in": "header", "name": "X-API-Key"},
+                "query": {"type": "apiKey", "in": "query", "name": "api_key"},
+                "auth": {"type": "apiKey", "in": "header", "name": "authorization"},
+                "spaced": {"type": "apiKey", "in": "header", "name": "API Key"}
+            }}
+        })).unwrap();
+        assert_eq!(SecurityScheme::api_key_header("X-API-Key"), spec.security_schemes["key"].as_item().unwrap().clone());
+        assert_eq!(spec.api_key_scheme(&APIKeyLocation::Header, "x-api-key").unwrap().0, "key");
+        assert_eq!(spec.api_key_scheme(&APIKeyLocation::Query, "api_key").unwrap().0, "query");
+        assert!(spec.api_key_scheme(&APIKeyLocation::Query, "API_KEY").is_none());
+        assert!(spec.api_key_scheme(&APIKeyLocation::Cookie, "api_key").is_none());
+
+        let errors: Vec<String> = spec.validate_api_keys().unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, vec![
+            r#"/components/securitySchemes/auth: header "authorization" is reserved"#,
+            r#"/components/securitySchemes/spaced: header "API Key" is not a valid header name"#,
+        ]);
+    }
+
+    #[test]
+    fn test_mutual_tls() {
+        let value = json!({"type": "mutualTLS", "description": "Client certificate"});
+        let scheme: SecurityScheme = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(scheme, SecurityScheme::MutualTLS { description: Some("Client certificate".to_string()) });
+        assert_eq!(serde_json::to_value(&scheme).unwrap(), value);
+    }
+}
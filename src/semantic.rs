@@ -0,0 +1,121 @@
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::*;
+
+impl OpenAPI {
+    /// Whether the two documents describe the same API, unlike `==`, which compares
+    /// them as written. The order of maps and of the lists [OpenAPI::canonicalize]
+    /// sorts doesn't matter, nor does that of parameters, and a `$ref` to a
+    /// component is equal to a copy of the component inlined in its place. Since
+    /// components are compared through what refers to them, unused ones are
+    /// ignored. For test assertions, [OpenAPI::semantic_mismatch] says where the
+    /// documents differ.
+    pub fn semantically_equals(&self, other: &OpenAPI) -> bool {
+        self.semantic_mismatch(other).is_none()
+    }
+
+    /// The JSON pointer to the first place where the documents differ, as
+    /// [OpenAPI::semantically_equals] compares them, into the documents with
+    /// their `$ref`s inlined. `None` if they don't differ.
+    pub fn semantic_mismatch(&self, other: &OpenAPI) -> Option<String> {
+        mismatch("", &normalized(self), &normalized(other))
+    }
+}
+
+/// The document as [OpenAPI::semantically_equals] compares it. One that can't be
+/// dereferenced, having dangling references, is compared as written.
+fn normalized(spec: &OpenAPI) -> Value {
+    let mut spec = spec.dereference(CyclicRefs::Keep).unwrap_or_else(|_| spec.clone());
+    // What is left is only what cyclic references, discriminator mappings and
+    // security requirements use.
+    spec.prune_unused();
+    spec.canonicalize();
+    let mut value = serde_json::to_value(&spec).unwrap_or(Value::Null);
+    sort_parameters(&mut value);
+    value
+}
+
+fn sort_parameters(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if let ("parameters", Value::Array(parameters)) = (key.as_str(), &mut *value) {
+                    parameters.sort_by_cached_key(|parameter| parameter.to_string());
+                }
+                sort_parameters(value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(sort_parameters),
+        _ => {}
+    }
+}
+
+fn mismatch(pointer: &str, a: &Value, b: &Value) -> Option<String> {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            if let Some(key) = a.keys().find(|key| !b.contains_key(*key)).or_else(|| b.keys().find(|key| !a.contains_key(*key))) {
+                return Some(child(pointer, key));
+            }
+            a.iter().find_map(|(key, value)| mismatch(&child(pointer, key), value, &b[key]))
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            a.iter().zip(b).enumerate().find_map(|(i, (a, b))| mismatch(&child(pointer, &i.to_string()), a, b))
+        }
+        (a, b) if a == b => None,
+        _ => Some(pointer.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_semantically_equals() {
+        let inline: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {
+                "parameters": [
+                    {"name": "limit", "in": "query", "schema": {"type": "integer"}},
+                    {"name": "X-Trace", "in": "header", "schema": {"type": "string"}}
+                ],
+                "responses": {"200": {"description": "", "content": {"application/json": {"schema": {
+                    "type": "object", "required": ["id", "name"], "properties": {"id": {"type": "integer"}, "name": {"type": "string"}}
+                }}}}}
+            }}}
+        })).unwrap();
+        let referenced: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"version": "1", "title": "t"},
+            "paths": {"/pets": {"get": {
+                "parameters": [
+                    {"name": "x-trace", "in": "header", "schema": {"type": "string"}},
+                    {"$ref": "#/components/parameters/Limit"}
+                ],
+                "responses": {"200": {"description": "", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}}}
+            }}},
+            "components": {
+                "parameters": {"Limit": {"name": "limit", "in": "query", "schema": {"type": "integer"}}},
+                "schemas": {
+                    "Pet": {"type": "object", "required": ["name", "id"], "properties": {"id": {"type": "integer"}, "name": {"type": "string"}}},
+                    "Unused": {"type": "string"}
+                }
+            }
+        })).unwrap();
+        assert_ne!(inline, referenced);
+        assert!(inline.semantically_equals(&referenced));
+
+        let mut changed = referenced.clone();
+        let pet = changed.components.schemas["Pet"].as_mut().unwrap();
+        pet.properties_mut().insert("id".to_string(), RefOr::Item(Schema::new_string()));
+        assert!(!inline.semantically_equals(&changed));
+        assert_eq!(
+            inline.semantic_mismatch(&changed).as_deref(),
+            Some("/paths/~1pets/get/responses/200/content/application~1json/schema/properties/id/type"),
+        );
+    }
+}
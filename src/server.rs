@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use crate::*;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 /// An object representing a Server.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Server {
     /// REQUIRED. A URL to the target host.
     /// This URL supports Server Variables and MAY be relative,
@@ -23,5 +28,227 @@ pub struct Server {
     pub variables: Option<IndexMap<String, ServerVariable>>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+/// A problem with a server URL template or the values substituted into it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerError {
+    /// A `{` without its closing `}`, at this byte offset of the URL.
+    Unclosed(usize),
+    /// The URL uses a variable that `variables` doesn't define.
+    UndefinedVariable(String),
+    /// A variable is defined but the URL doesn't use it.
+    UnusedVariable(String),
+    /// The default of a variable isn't one of its `enum` values.
+    DefaultNotInEnum { variable: String, default: String },
+    /// A value given for a variable isn't one of its `enum` values.
+    NotInEnum { variable: String, value: String },
+    /// A value was given for a variable the server doesn't define.
+    UnknownVariable(String),
+    /// The URL with its variables substituted isn't an absolute URL.
+    #[cfg(feature = "url")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+    Url(url::ParseError),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerError::Unclosed(offset) => write!(f, "unclosed `{{` at offset {}", offset),
+            ServerError::UndefinedVariable(name) => write!(f, "variable {:?} is used but not defined", name),
+            ServerError::UnusedVariable(name) => write!(f, "variable {:?} is defined but not used", name),
+            ServerError::DefaultNotInEnum { variable, default } => {
+                write!(f, "default {:?} of variable {:?} is not one of its enum values", default, variable)
+            }
+            ServerError::NotInEnum { variable, value } => write!(f, "{:?} is not an allowed value of variable {:?}", value, variable),
+            ServerError::UnknownVariable(name) => write!(f, "the server has no variable {:?}", name),
+            #[cfg(feature = "url")]
+            ServerError::Url(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+/// A piece of a server URL template.
+enum Segment<'a> {
+    Literal(&'a str),
+    Variable(&'a str),
+}
+
+fn segments(url: &str) -> Result<Vec<Segment<'_>>, ServerError> {
+    let mut segments = Vec::new();
+    let mut rest = url;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or(ServerError::Unclosed(url.len() - rest.len() + start))?;
+        segments.push(Segment::Literal(&rest[..start]));
+        segments.push(Segment::Variable(&rest[start + 1..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    segments.push(Segment::Literal(rest));
+    Ok(segments)
+}
+
+impl Server {
+    /// The server URL, with each `{variable}` replaced by its default value.
+    pub fn url_with_defaults(&self) -> String {
+        let mut url = self.url.clone();
+        for (name, variable) in self.variables.iter().flatten() {
+            url = url.replace(&format!("{{{}}}", name), &variable.default);
+        }
+        url
+    }
+
+    /// The names of the `{variable}`s in the URL, in order of appearance.
+    pub fn variable_names(&self) -> Result<Vec<&str>, ServerError> {
+        Ok(segments(&self.url)?.into_iter().filter_map(|segment| match segment {
+            Segment::Variable(name) => Some(name),
+            Segment::Literal(_) => None,
+        }).collect())
+    }
+
+    /// Checks that the URL template is well-formed, that the variables it uses
+    /// and those defined match, and that each default is one of its variable's
+    /// `enum` values, if any.
+    pub fn validate(&self) -> Result<(), Vec<ServerError>> {
+        let used = self.variable_names().map_err(|e| vec![e])?;
+        let variables = self.variables.as_ref();
+        let mut errors: Vec<ServerError> = used.iter()
+            .filter(|name| variables.is_none_or(|v| !v.contains_key(**name)))
+            .map(|name| ServerError::UndefinedVariable(name.to_string()))
+            .collect();
+        for (name, variable) in variables.into_iter().flatten() {
+            if !used.contains(&name.as_str()) {
+                errors.push(ServerError::UnusedVariable(name.clone()));
+            }
+            if !variable.enumeration.is_empty() && !variable.enumeration.contains(&variable.default) {
+                errors.push(ServerError::DefaultNotInEnum { variable: name.clone(), default: variable.default.clone() });
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// The server URL with each `{variable}` replaced by its value in `values`, or
+    /// else by its default. Values must be among the variable's `enum`, if any.
+    pub fn substitute(&self, values: &HashMap<&str, &str>) -> Result<String, ServerError> {
+        let variables = self.variables.as_ref();
+        if let Some(name) = values.keys().find(|name| variables.is_none_or(|v| !v.contains_key(**name))) {
+            return Err(ServerError::UnknownVariable(name.to_string()));
+        }
+        let mut url = String::with_capacity(self.url.len());
+        for segment in segments(&self.url)? {
+            match segment {
+                Segment::Literal(text) => url.push_str(text),
+                Segment::Variable(name) => {
+                    let variable = variables.and_then(|v| v.get(name)).ok_or_else(|| ServerError::UndefinedVariable(name.to_string()))?;
+                    let value = values.get(name).copied().unwrap_or(&variable.default);
+                    if values.contains_key(name) && !variable.enumeration.is_empty() && !variable.enumeration.iter().any(|e| e == value) {
+                        return Err(ServerError::NotInEnum { variable: name.to_string(), value: value.to_string() });
+                    }
+                    url.push_str(value);
+                }
+            }
+        }
+        Ok(url)
+    }
+}
+
+impl OpenAPI {
+    /// The URLs of the servers the API is served from, with their variables set
+    /// to their defaults. A document without `servers` is served from `/`,
+    /// relative to where the document itself is served.
+    pub fn base_urls(&self) -> Vec<String> {
+        if self.servers.is_empty() {
+            return vec!["/".to_string()];
+        }
+        self.servers.iter().map(Server::url_with_defaults).collect()
+    }
+}
+
+#[cfg(feature = "url")]
+#[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+impl Server {
+    pub fn from_url(url: url::Url) -> Self {
+        Server { url: url.into(), ..Server::default() }
+    }
+
+    /// The server URL, with each `{variable}` replaced by its default value. A
+    /// relative URL such as `/v1`, which is resolved against the document's own
+    /// location, is an error.
+    pub fn parsed_url(&self) -> Result<url::Url, url::ParseError> {
+        url::Url::parse(&self.url_with_defaults())
+    }
+
+    /// The server URL with its variables substituted as by [Server::substitute],
+    /// parsed. A relative URL is an error, as for [Server::parsed_url].
+    pub fn resolve_url(&self, values: &HashMap<&str, &str>) -> Result<url::Url, ServerError> {
+        url::Url::parse(&self.substitute(values)?).map_err(ServerError::Url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let server: Server = serde_json::from_value(serde_json::json!({
+            "url": "https://{region}.example.com:{port}/{version}",
+            "variables": {
+                "region": {"default": "eu", "enum": ["eu", "us"]},
+                "port": {"default": "443"},
+                "version": {"default": "v3", "enum": ["v1", "v2"]},
+                "unused": {"default": ""}
+            }
+        })).unwrap();
+        assert_eq!(server.variable_names().unwrap(), ["region", "port", "version"]);
+        assert_eq!(server.validate(), Err(vec![
+            ServerError::DefaultNotInEnum { variable: "version".to_string(), default: "v3".to_string() },
+            ServerError::UnusedVariable("unused".to_string()),
+        ]));
+
+        let values = HashMap::from([("region", "us"), ("port", "8443")]);
+        assert_eq!(server.substitute(&values).unwrap(), "https://us.example.com:8443/v3");
+        assert_eq!(server.substitute(&HashMap::from([("region", "ap")])), Err(ServerError::NotInEnum { variable: "region".to_string(), value: "ap".to_string() }));
+        assert_eq!(server.substitute(&HashMap::from([("tenant", "a")])), Err(ServerError::UnknownVariable("tenant".to_string())));
+
+        let broken = Server { url: "https://{host".to_string(), ..Server::default() };
+        assert_eq!(broken.validate(), Err(vec![ServerError::Unclosed(8)]));
+        let undefined = Server { url: "https://{host}/".to_string(), ..Server::default() };
+        assert_eq!(undefined.substitute(&HashMap::new()), Err(ServerError::UndefinedVariable("host".to_string())));
+
+        let mut spec = OpenAPI::default();
+        assert_eq!(spec.base_urls(), ["/"]);
+        spec.servers.push(server);
+        assert_eq!(spec.base_urls(), ["https://eu.example.com:443/v3"]);
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_resolve_url() {
+        let server: Server = serde_json::from_value(serde_json::json!({
+            "url": "https://{region}.example.com/v1",
+            "variables": {"region": {"default": "eu", "enum": ["eu", "us"]}}
+        })).unwrap();
+        let url = server.resolve_url(&HashMap::from([("region", "us")])).unwrap();
+        assert_eq!(url.as_str(), "https://us.example.com/v1");
+        let relative = Server { url: "/v1".to_string(), ..Server::default() };
+        assert_eq!(relative.resolve_url(&HashMap::new()), Err(ServerError::Url(url::ParseError::RelativeUrlWithoutBase)));
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_parsed_url() {
+        let server: Server = serde_json::from_value(serde_json::json!({
+            "url": "https://{region}.example.com/v1",
+            "variables": {"region": {"default": "eu", "enum": ["eu", "us"]}}
+        })).unwrap();
+        assert_eq!(server.parsed_url().unwrap().as_str(), "https://eu.example.com/v1");
+        let relative = Server { url: "/v1".to_string(), ..Server::default() };
+        assert_eq!(relative.parsed_url(), Err(url::ParseError::RelativeUrlWithoutBase));
+        let url = url::Url::parse("https://example.com/").unwrap();
+        assert_eq!(Server::from_url(url.clone()).parsed_url(), Ok(url));
+    }
+}
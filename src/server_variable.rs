@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 /// An object representing a Server Variable
 /// for server URL template substitution.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ServerVariable {
     /// An enumeration of string values to be
     /// used if the substitution options are from a limited set.
@@ -22,5 +24,6 @@ pub struct ServerVariable {
     pub description: Option<String>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
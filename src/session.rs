@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::example_validation::{child, collect, run, subschemas, Region};
+use crate::*;
+
+/// A spec being edited, which remembers what changed so that [EditSession::validate]
+/// only revalidates the affected parts, e.g. for an editor re-checking a large
+/// document on every keystroke.
+///
+/// Results are kept per top-level entry: each path, and each component. An entry
+/// is revalidated when it was edited, or when anything it references through
+/// `$ref`s, directly or not, was.
+#[derive(Debug)]
+pub struct EditSession {
+    spec: OpenAPI,
+    /// Pointers of the entries edited since the last validation. `None` when any
+    /// part of the document may have changed.
+    dirty: Option<HashSet<String>>,
+    results: HashMap<String, Cached>,
+    /// How many entries the last validation had to revalidate.
+    revalidated: usize,
+    resolver: ResolverCache,
+}
+
+#[derive(Debug)]
+struct Cached {
+    errors: Vec<ExampleError>,
+    /// Pointers of the entries the results depend on.
+    depends: HashSet<String>,
+}
+
+impl EditSession {
+    pub fn new(spec: OpenAPI) -> Self {
+        EditSession { spec, dirty: None, results: HashMap::new(), revalidated: 0, resolver: ResolverCache::new() }
+    }
+
+    pub fn spec(&self) -> &OpenAPI {
+        &self.spec
+    }
+
+    pub fn into_spec(self) -> OpenAPI {
+        self.spec
+    }
+
+    /// Mutable access to the whole spec, after which everything is revalidated.
+    pub fn spec_mut(&mut self) -> &mut OpenAPI {
+        self.dirty = None;
+        self.resolver.clear();
+        &mut self.spec
+    }
+
+    pub fn path_mut(&mut self, path: &str) -> Option<&mut RefOr<PathItem>> {
+        self.mark(child("/paths", path));
+        self.spec.paths.paths.get_mut(path)
+    }
+
+    pub fn schema_mut(&mut self, name: &str) -> Option<&mut RefOr<Schema>> {
+        self.mark(child("/components/schemas", name));
+        self.spec.components.schemas.get_mut(name)
+    }
+
+    /// Edits the spec through `f`, which may add, change or remove the entry at
+    /// `pointer` (e.g. `/paths/~1pets` or `/components/schemas/Pet`), but nothing else.
+    pub fn edit<R>(&mut self, pointer: &str, f: impl FnOnce(&mut OpenAPI) -> R) -> R {
+        self.mark(pointer.to_string());
+        f(&mut self.spec)
+    }
+
+    /// Resolves a schema reference, through a [ResolverCache] which edits made
+    /// through the session keep up to date.
+    pub fn resolve_schema(&mut self, reference: &str) -> anyhow::Result<&Schema> {
+        self.resolver.resolve(&self.spec, reference)
+    }
+
+    fn mark(&mut self, pointer: String) {
+        self.resolver.invalidate(&pointer);
+        if let Some(dirty) = self.dirty.as_mut() {
+            dirty.insert(pointer);
+        }
+    }
+
+    /// Like [OpenAPI::validate_examples], reusing the results of entries that are
+    /// unaffected by the edits since the last call.
+    pub fn validate(&mut self) -> Result<(), Vec<ExampleError>> {
+        let dirty = self.dirty.replace(HashSet::new());
+        let spec = &self.spec;
+        let mut results = HashMap::new();
+        let mut errors = Vec::new();
+        let mut revalidated = 0;
+        for region in collect(spec) {
+            let cached = self.results.remove(&region.pointer).filter(|cached| match &dirty {
+                Some(dirty) => !dirty.contains(&region.pointer) && cached.depends.is_disjoint(dirty),
+                None => false,
+            });
+            let cached = cached.unwrap_or_else(|| {
+                revalidated += 1;
+                Cached { errors: run(&region.checks, &spec.components), depends: depends(spec, &region) }
+            });
+            errors.extend(cached.errors.iter().cloned());
+            results.insert(region.pointer, cached);
+        }
+        self.results = results;
+        self.revalidated = revalidated;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// The entries `region`'s checks reach through `$ref`s.
+fn depends(spec: &OpenAPI, region: &Region) -> HashSet<String> {
+    let mut depends: HashSet<String> = region.example_refs.iter().filter_map(|r| target(r)).collect();
+    let mut pending: Vec<&RefOr<Schema>> = region.checks.iter().map(|check| check.schema).collect();
+    while let Some(schema) = pending.pop() {
+        match schema {
+            RefOr::Reference { reference } => {
+                let Some(target) = target(reference) else { continue };
+                if depends.insert(target) {
+                    let path = reference.trim_start_matches("#/components/schemas/");
+                    let name = path.split('/').next().unwrap_or_default();
+                    pending.extend(spec.components.schemas.get(name));
+                }
+            }
+            RefOr::Item(schema) => pending.extend(subschemas(schema).into_iter().map(|(_, s)| s)),
+        }
+    }
+    depends
+}
+
+/// The pointer of the entry a local `$ref` points into, e.g. `/components/schemas/Pet`
+/// for `#/components/schemas/Pet/properties/name`.
+fn target(reference: &str) -> Option<String> {
+    let pointer = reference.strip_prefix('#')?;
+    let end = pointer.match_indices('/').nth(3).map_or(pointer.len(), |(i, _)| i);
+    Some(pointer[..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn session() -> EditSession {
+        EditSession::new(serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets": {"get": {"responses": {"200": {"description": "ok", "content": {"application/json": {
+                    "schema": {"$ref": "#/components/schemas/Pets"},
+                    "example": [{"name": "Rex"}]
+                }}}}}},
+                "/users": {"get": {"responses": {"200": {"description": "ok", "content": {"application/json": {
+                    "schema": {"type": "string"},
+                    "example": "ann"
+                }}}}}}
+            },
+            "components": {"schemas": {
+                "Pets": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                "Pet": {"type": "object", "properties": {"name": {"type": "string"}}}
+            }}
+        })).unwrap())
+    }
+
+    #[test]
+    fn test_incremental_validation() {
+        let mut session = session();
+        assert!(session.validate().is_ok());
+        assert_eq!(session.revalidated, 4);
+        assert!(session.validate().is_ok());
+        assert_eq!(session.revalidated, 0);
+
+        // `/pets` reaches `Pet` through `Pets`, `/users` doesn't, and `Pets` has no
+        // examples of its own to check.
+        let pet = session.schema_mut("Pet").unwrap().as_mut().unwrap();
+        pet.properties_mut().insert("name".to_string(), RefOr::Item(Schema::new_integer()));
+        let errors = session.validate().unwrap_err();
+        assert_eq!(session.revalidated, 2);
+        assert_eq!(errors[0].pointer, "/paths/~1pets/get/responses/200/content/application~1json/example");
+
+        session.edit("/paths/~1users", |spec| spec.paths.paths.shift_remove("/users"));
+        assert!(session.validate().is_err());
+        assert_eq!(session.revalidated, 0);
+        assert_eq!(session.results.len(), 3);
+
+        session.spec_mut();
+        assert!(session.validate().is_err());
+        assert_eq!(session.revalidated, 3);
+
+        assert!(session.resolve_schema("#/components/schemas/Pets").is_ok());
+        session.edit("/components/schemas/Pets", |spec| spec.schemas.insert("Pets", RefOr::schema_ref("Pet")));
+        assert!(matches!(session.resolve_schema("#/components/schemas/Pets").unwrap().kind, SchemaKind::Type(Type::Object(_))));
+    }
+
+    #[test]
+    fn test_target() {
+        assert_eq!(target("#/components/schemas/Pet/properties/name").as_deref(), Some("/components/schemas/Pet"));
+        assert_eq!(target("#/components/examples/A").as_deref(), Some("/components/examples/A"));
+        assert_eq!(target("other.yaml#/Pet"), None);
+    }
+}
@@ -0,0 +1,584 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::example_validation::child;
+use crate::io::normalize_text;
+use crate::*;
+
+/// A 1-based line and column in the text of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Where the values of a document start in its text, by JSON pointer: the key
+/// of an object member, or the first character of an array element. Built by
+/// scanning the text, so it knows nothing about the spec types. Anchors and
+/// aliases are not followed, and complex (`? `) YAML keys are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    positions: HashMap<String, Position>,
+}
+
+impl SourceMap {
+    pub fn new(text: &str, format: Format) -> Self {
+        let mut scanner = Scanner::new(text);
+        match format {
+            Format::Json => {
+                scanner.skip_space();
+                scanner.record("", scanner.offset);
+                scanner.flow("");
+            }
+            Format::Yaml => scanner.block(),
+        }
+        SourceMap { positions: scanner.positions }
+    }
+
+    /// The position of the value at `pointer`, if it's in the document.
+    pub fn position(&self, pointer: &str) -> Option<Position> {
+        self.positions.get(pointer).copied()
+    }
+
+    /// The position of the value at `pointer`, or else of its nearest ancestor
+    /// in the document, e.g. for a field reported missing.
+    pub fn locate(&self, pointer: &str) -> Option<Position> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(position) = self.position(pointer) {
+                return Some(position);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+
+    /// `error`, moved to the innermost node that failed to parse and located in
+    /// the text. serde can't see below a [RefOr], so its part of the document is
+    /// parsed again as the type its pointer implies, e.g. a [PathItem] for
+    /// `/paths/~1pets`, until the error is reported at the node itself.
+    fn explain(&self, mut error: ParseError, document: Option<&Value>) -> ParseError {
+        if let Some(document) = document {
+            while let Some((pointer, message)) = reparse(document, &error.pointer) {
+                if pointer.is_empty() && message == error.message {
+                    break;
+                }
+                error.pointer.push_str(&pointer);
+                error.message = message;
+                error.line = None;
+                error.column = None;
+                if pointer.is_empty() {
+                    break;
+                }
+            }
+        }
+        if error.line.is_none() {
+            if let Some(position) = self.locate(&error.pointer) {
+                error.line = Some(position.line);
+                error.column = Some(position.column);
+            }
+        }
+        error
+    }
+}
+
+/// A parsed value with the [SourceMap] of the text it was parsed from, to
+/// report where in the source each of its objects came from.
+#[derive(Debug, Clone)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub source_map: SourceMap,
+}
+
+impl<T> Spanned<T> {
+    /// The position of the value at `pointer`, see [SourceMap::position].
+    pub fn position(&self, pointer: &str) -> Option<Position> {
+        self.source_map.position(pointer)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl OpenAPI {
+    /// Parses a spec like [OpenAPI::from_str_format], keeping the position of
+    /// every value in the text. A [LoadError::Parse] points at the innermost node
+    /// that failed, including below `$ref`-able objects, with its line and column.
+    pub fn from_str_spanned(s: &str, format: Format) -> Result<Spanned<OpenAPI>, LoadError> {
+        let text = normalize_text(s);
+        let source_map = SourceMap::new(&text, format);
+        match OpenAPI::from_str_format(&text, format) {
+            Ok(value) => Ok(Spanned { value, source_map }),
+            Err(LoadError::Parse(error)) => {
+                let document = document(&text, format);
+                Err(LoadError::Parse(source_map.explain(error, document.as_ref())))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn document(text: &str, format: Format) -> Option<Value> {
+    match format {
+        Format::Json => serde_json::from_str(text).ok(),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => serde_yaml::from_str(text).ok(),
+        #[cfg(not(feature = "yaml"))]
+        Format::Yaml => None,
+    }
+}
+
+/// Parses the node of `document` at `pointer` again, returning the pointer
+/// below it and the message of the error, if any. Maps serde doesn't track
+/// paths through, like `paths`, are looked through to the member that fails.
+fn reparse(document: &Value, pointer: &str) -> Option<(String, String)> {
+    let found = typed(document, pointer);
+    if found.as_ref().is_some_and(|(below, _)| !below.is_empty()) {
+        return found;
+    }
+    let members: Vec<String> = match document.pointer(pointer)? {
+        Value::Object(map) => map.keys().map(|key| child("", key)).collect(),
+        Value::Array(items) => (0..items.len()).map(|i| format!("/{}", i)).collect(),
+        _ => Vec::new(),
+    };
+    for member in members {
+        if let Some((below, message)) = typed(document, &format!("{}{}", pointer, member)) {
+            return Some((member + &below, message));
+        }
+    }
+    found
+}
+
+/// Parses the node of `document` at `pointer` as the type its position implies,
+/// returning the pointer below it and the message of the error, if any.
+fn typed(document: &Value, pointer: &str) -> Option<(String, String)> {
+    let value = document.pointer(pointer)?;
+    let segments: Vec<&str> = pointer.split('/').skip(1).collect();
+    let last = *segments.last()?;
+    let parent = segments.len().checked_sub(2).map_or("", |i| segments[i]);
+    let grandparent = segments.len().checked_sub(3).map_or("", |i| segments[i]);
+    match (parent, last) {
+        ("paths" | "webhooks", _) if segments.len() == 2 => error::<PathItem>(value),
+        ("pathItems", _) => error::<PathItem>(value),
+        ("callbacks", _) => error::<Callback>(value),
+        ("parameters", _) => error::<Parameter>(value),
+        ("responses", _) => error::<Response>(value),
+        ("requestBodies", _) | (_, "requestBody") => error::<RequestBody>(value),
+        ("headers", _) => error::<Header>(value),
+        ("examples", _) => error::<Example>(value),
+        ("links", _) => error::<Link>(value),
+        ("securitySchemes", _) => error::<SecurityScheme>(value),
+        ("schemas" | "properties" | "patternProperties" | "$defs" | "dependentSchemas" | "allOf" | "oneOf" | "anyOf" | "prefixItems", _)
+        | (_, "schema" | "items" | "additionalProperties" | "not" | "if" | "then" | "else" | "contains" | "propertyNames") => error::<Schema>(value),
+        _ if grandparent == "callbacks" => error::<PathItem>(value),
+        _ => None,
+    }
+}
+
+fn error<T: DeserializeOwned>(value: &Value) -> Option<(String, String)> {
+    let e = serde_path_to_error::deserialize::<_, T>(value).err()?;
+    let error = ParseError::new(Some(e.path()), e.inner().to_string(), None);
+    Some((error.pointer, error.message))
+}
+
+/// A block of a YAML document: a mapping or a sequence, with the next index of
+/// the latter.
+struct Block {
+    indent: usize,
+    pointer: String,
+    index: Option<usize>,
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    offset: usize,
+    lines: Vec<usize>,
+    positions: HashMap<String, Position>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        let lines = std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        Scanner { text, offset: 0, lines, positions: HashMap::new() }
+    }
+
+    fn record(&mut self, pointer: &str, offset: usize) {
+        let line = self.lines.partition_point(|&start| start <= offset);
+        let column = self.text[self.lines[line - 1]..offset].chars().count() + 1;
+        self.positions.entry(pointer.to_string()).or_insert(Position { line, column });
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' => self.offset += 1,
+                b'#' => self.skip_until(b"\n"),
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_until(&mut self, stop: &[u8]) {
+        while self.peek().is_some_and(|c| !stop.contains(&c)) {
+            self.offset += 1;
+        }
+    }
+
+    /// Skips a quoted string, starting at its opening quote.
+    fn skip_quoted(&mut self) {
+        let quote = self.peek();
+        self.offset += 1;
+        while let Some(c) = self.peek() {
+            self.offset += 1;
+            if c == b'\\' && quote == Some(b'"') {
+                self.offset += 1;
+            } else if Some(c) == quote {
+                if quote == Some(b'\'') && self.peek() == Some(b'\'') {
+                    self.offset += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        self.offset = self.offset.min(self.text.len());
+    }
+
+    /// The key of a quoted or plain scalar starting at the current offset.
+    fn key(&mut self) -> String {
+        let start = self.offset;
+        match self.peek() {
+            Some(b'"') => {
+                self.skip_quoted();
+                serde_json::from_str(&self.text[start..self.offset]).unwrap_or_default()
+            }
+            Some(b'\'') => {
+                self.skip_quoted();
+                let quoted = &self.text[start..self.offset];
+                quoted.get(1..quoted.len() - 1).unwrap_or_default().replace("''", "'")
+            }
+            _ => {
+                self.skip_until(b":,}]\n");
+                self.text[start..self.offset].trim().to_string()
+            }
+        }
+    }
+
+    /// Records the members of the JSON (or YAML flow) value at the current
+    /// offset, whose pointer is `pointer`, and moves past it.
+    fn flow(&mut self, pointer: &str) {
+        match self.peek() {
+            Some(b'{') => {
+                self.offset += 1;
+                loop {
+                    self.skip_space();
+                    match self.peek() {
+                        None => break,
+                        Some(b'}') => {
+                            self.offset += 1;
+                            break;
+                        }
+                        Some(b',') => self.offset += 1,
+                        Some(_) => {
+                            let start = self.offset;
+                            let member = child(pointer, &self.key());
+                            self.record(&member, start);
+                            self.skip_space();
+                            if self.peek() == Some(b':') {
+                                self.offset += 1;
+                                self.skip_space();
+                                if !matches!(self.peek(), Some(b',' | b'}')) {
+                                    self.flow(&member);
+                                }
+                            }
+                            if self.offset == start {
+                                self.offset += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.offset += 1;
+                let mut index = 0;
+                loop {
+                    self.skip_space();
+                    match self.peek() {
+                        None => break,
+                        Some(b']') => {
+                            self.offset += 1;
+                            break;
+                        }
+                        Some(b',') => self.offset += 1,
+                        Some(_) => {
+                            let start = self.offset;
+                            let element = format!("{}/{}", pointer, index);
+                            self.record(&element, start);
+                            self.flow(&element);
+                            index += 1;
+                            if self.offset == start {
+                                self.offset += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            Some(b'"' | b'\'') => self.skip_quoted(),
+            _ => self.skip_until(b",}]\n"),
+        }
+    }
+
+    /// Records the values of a YAML document in block style, falling back to
+    /// [Scanner::flow] for flow collections.
+    fn block(&mut self) {
+        let mut blocks: Vec<Block> = Vec::new();
+        // The node whose value, if any, starts on a later line.
+        let mut pending = Some(String::new());
+        // The indentation of the node whose block or multi-line scalar is being skipped.
+        let mut scalar: Option<usize> = None;
+        let mut line = 0;
+        while line < self.lines.len() {
+            let start = self.lines[line];
+            line += 1;
+            if start < self.offset {
+                continue;
+            }
+            let end = self.text[start..].find('\n').map_or(self.text.len(), |i| start + i);
+            let content = &self.text[start..end];
+            let trimmed = content.trim_start_matches(' ');
+            let indent = content.len() - trimmed.len();
+            if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if let Some(parent) = scalar {
+                if indent > parent {
+                    continue;
+                }
+                scalar = None;
+            }
+            if indent == 0 && (trimmed.starts_with("---") || trimmed.starts_with("...") || trimmed.starts_with('%')) {
+                continue;
+            }
+            if blocks.is_empty() && pending.as_deref() == Some("") {
+                self.record("", start + indent);
+            }
+            self.line(start + indent, end, indent, &mut blocks, &mut pending, &mut scalar);
+        }
+    }
+
+    /// Records the node on the line from `offset` to `end`, indented by `indent`.
+    fn line(&mut self, mut offset: usize, end: usize, mut indent: usize, blocks: &mut Vec<Block>, pending: &mut Option<String>, scalar: &mut Option<usize>) {
+        loop {
+            let rest = &self.text[offset..end];
+            let item = rest == "-" || rest.starts_with("- ");
+            while let Some(top) = blocks.last() {
+                if top.indent > indent || (top.indent == indent && top.index.is_some() && !item) {
+                    blocks.pop();
+                } else {
+                    break;
+                }
+            }
+            let opens = match blocks.last() {
+                None => true,
+                Some(top) => top.indent < indent || (top.indent == indent && top.index.is_none() && item),
+            };
+            match pending.take() {
+                Some(pointer) if opens => blocks.push(Block { indent, pointer, index: item.then_some(0) }),
+                _ if opens => return,
+                _ => {}
+            }
+            let Some(top) = blocks.last_mut() else { return };
+
+            if let Some(index) = &mut top.index {
+                if !item {
+                    return;
+                }
+                let element = format!("{}/{}", top.pointer, index);
+                *index += 1;
+                let after = offset + 1 + rest[1..].len() - rest[1..].trim_start_matches(' ').len();
+                self.record(&element, after);
+                let value = &self.text[after..end];
+                if value.is_empty() || value.starts_with('#') {
+                    *pending = Some(element);
+                    return;
+                } else if self.is_block_key(after, end) {
+                    *pending = Some(element);
+                    indent += after - offset;
+                    offset = after;
+                    continue;
+                }
+                self.value(&element, after, end, indent, pending, scalar);
+                return;
+            }
+
+            if rest.starts_with("? ") {
+                *scalar = Some(indent);
+                return;
+            }
+            let (key, after) = if rest.starts_with(['"', '\'']) {
+                self.offset = offset;
+                (self.key(), self.offset)
+            } else {
+                match plain_key(rest) {
+                    Some(key) => (key.trim_end().to_string(), offset + key.len()),
+                    None => return,
+                }
+            };
+            let Some(value) = self.text[after..end].trim_start().strip_prefix(':') else {
+                // A continuation of a multi-line scalar, at the same indent as its key.
+                return;
+            };
+            let pointer = child(&top.pointer, &key);
+            self.record(&pointer, offset);
+            let value_start = end - value.trim_start().len();
+            self.value(&pointer, value_start, end, indent, pending, scalar);
+            return;
+        }
+    }
+
+    /// Whether the line from `offset` to `end` holds a mapping key or a sequence
+    /// item, rather than a scalar.
+    fn is_block_key(&self, offset: usize, end: usize) -> bool {
+        let rest = &self.text[offset..end];
+        if rest == "-" || rest.starts_with("- ") {
+            return true;
+        }
+        let after_key = match rest.as_bytes().first() {
+            Some(&quote @ (b'"' | b'\'')) => match rest[1..].find(quote as char) {
+                Some(i) => &rest[i + 2..],
+                None => return false,
+            },
+            _ => return plain_key(rest).is_some(),
+        };
+        after_key.trim_start().starts_with(':')
+    }
+
+    /// Records the value of `pointer` starting at `offset`, on a line ending at
+    /// `end` whose node is indented by `indent`.
+    fn value(&mut self, pointer: &str, mut offset: usize, end: usize, indent: usize, pending: &mut Option<String>, scalar: &mut Option<usize>) {
+        // Anchors and tags come before the value.
+        while matches!(self.text.as_bytes().get(offset), Some(b'&' | b'!')) {
+            let token = self.text[offset..end].find(' ').unwrap_or(end - offset);
+            offset = end - self.text[offset + token..end].trim_start().len();
+        }
+        let value = &self.text[offset..end];
+        if value.is_empty() || value.starts_with('#') {
+            *pending = Some(pointer.to_string());
+        } else if value.starts_with(['{', '[']) {
+            self.offset = offset;
+            self.flow(pointer);
+        } else {
+            *scalar = Some(indent);
+        }
+    }
+}
+
+/// The key of a plain `key: value` line, if it is one.
+fn plain_key(line: &str) -> Option<&str> {
+    let end = line.find(": ").or_else(|| line.strip_suffix(':').map(str::len))?;
+    let key = &line[..end];
+    (!key.contains(" #") && !key.starts_with(['{', '[', '#'])).then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_spanned_json() {
+        let text = r#"{
+  "openapi": "3.0.3",
+  "info": {"title": "t", "version": "1"},
+  "paths": {
+    "/pets": {
+      "get": {
+        "summary": "List pets"
+      }
+    }
+  }
+}"#;
+        let Err(LoadError::Parse(error)) = OpenAPI::from_str_spanned(text, Format::Json) else { panic!() };
+        assert_eq!(error.to_string(), "missing field `responses` at /paths/~1pets/get (line 6, column 7)");
+
+        let spanned = OpenAPI::from_str_spanned(&text.replace(r#""summary": "List pets""#, r#""responses": {"200": {"description": ""}}"#), Format::Json).unwrap();
+        assert_eq!(spanned.paths.len(), 1);
+        assert_eq!(spanned.position("/paths/~1pets/get/responses/200"), Some(Position { line: 7, column: 23 }));
+        assert_eq!(spanned.position("/info/version"), Some(Position { line: 3, column: 26 }));
+        assert_eq!(spanned.position("/servers"), None);
+    }
+
+    #[test]
+    fn test_source_map_yaml() {
+        let text = "\
+openapi: 3.0.3
+info:
+  title: t
+  description: |
+    key: not a key
+paths:
+  /pets:
+    get:
+      tags:
+      - pets
+      parameters:
+        - name: limit
+          in: query
+        - {name: offset, in: query}
+      responses: {'200': {description: ''}}
+";
+        let map = SourceMap::new(text, Format::Yaml);
+        let position = |pointer: &str| map.position(pointer).map(|p| (p.line, p.column));
+        assert_eq!(position(""), Some((1, 1)));
+        assert_eq!(position("/info/description"), Some((4, 3)));
+        assert_eq!(position("/info/description/key"), None);
+        assert_eq!(position("/info/key"), None);
+        assert_eq!(position("/paths/~1pets/get/tags/0"), Some((10, 9)));
+        assert_eq!(position("/paths/~1pets/get/parameters/0/in"), Some((13, 11)));
+        assert_eq!(position("/paths/~1pets/get/parameters/1/in"), Some((14, 26)));
+        assert_eq!(position("/paths/~1pets/get/responses/200/description"), Some((15, 27)));
+        assert_eq!(map.locate("/paths/~1pets/get/security").map(|p| p.line), Some(8));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_from_str_spanned_yaml() {
+        let text = "\
+openapi: 3.0.3
+info: {title: t, version: '1'}
+paths: {}
+components:
+  schemas:
+    Pet:
+      type: object
+  responses:
+    Pets:
+      content:
+        application/json:
+          schema:
+            $ref: '#/components/schemas/Pet'
+";
+        let Err(LoadError::Parse(error)) = OpenAPI::from_str_spanned(text, Format::Yaml) else { panic!() };
+        assert_eq!(error.to_string(), "missing field `description` at /components/responses/Pets (line 9, column 5)");
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::bundle::SECTIONS;
+use crate::emit::write_value;
+use crate::example_validation::child;
+use crate::util::unique_name;
+use crate::{Format, LoadError, OpenAPI, RefOr, SerializeOptions};
+
+/// Layout choices for [OpenAPI::split] and [OpenAPI::save_split].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitOptions {
+    /// How each file is written. The format also picks the file extensions.
+    pub serialize: SerializeOptions,
+    /// Write each component to `components/<section>/<name>`.
+    pub components: bool,
+    /// Write each path item to `paths/<path>`, e.g. `paths/pets_petId.yaml` for `/pets/{petId}`.
+    pub paths: bool,
+    /// With `paths`, write one file per tag instead, `paths/<tag>`, holding the path
+    /// items whose first operation has that first tag, by path. Path items without
+    /// tags go to `paths/default`.
+    pub paths_by_tag: bool,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        SplitOptions {
+            serialize: SerializeOptions { format: Format::Yaml, ..SerializeOptions::default() },
+            components: true,
+            paths: true,
+            paths_by_tag: false,
+        }
+    }
+}
+
+/// The documents of a split spec, from [OpenAPI::split], by path relative to the
+/// directory they are meant for. The root document comes first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSet {
+    pub files: IndexMap<PathBuf, Value>,
+    serialize: SerializeOptions,
+}
+
+impl FileSet {
+    /// The root document, `openapi.yaml` or `openapi.json`, that refers to the others.
+    pub fn root(&self) -> (&Path, &Value) {
+        let (path, value) = self.files.first().expect("a file set has a root document");
+        (path, value)
+    }
+
+    /// The files' contents, serialized as the [SplitOptions] said.
+    pub fn to_strings(&self) -> Result<IndexMap<PathBuf, String>, serde_json::Error> {
+        self.files.iter()
+            .map(|(path, value)| Ok((path.clone(), write_value(value.clone(), &self.serialize)?)))
+            .collect()
+    }
+
+    /// Writes the files under `dir`, creating directories as needed. Files already
+    /// in `dir` are overwritten when they collide, and otherwise left alone.
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<(), LoadError> {
+        let dir = dir.as_ref();
+        for (path, content) in self.to_strings()? {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+impl OpenAPI {
+    /// Splits the spec into a root document whose components and path items are
+    /// `$ref`s to separate documents, which in turn refer to each other by relative
+    /// path: the inverse of [OpenAPI::load_dir], in memory.
+    pub fn split(&self, options: &SplitOptions) -> Result<FileSet, serde_json::Error> {
+        let ext = match options.serialize.format {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+        };
+        let root_file = PathBuf::from(format!("openapi.{}", ext));
+        let mut root = serde_json::to_value(self)?;
+        let mut files = IndexMap::new();
+        // Local refs of the split out components, e.g. `#/components/schemas/Pet`, by file.
+        let mut targets = HashMap::new();
+        if let (true, Some(Value::Object(components))) = (options.components, root.get_mut("components")) {
+            for section in SECTIONS {
+                let Some(Value::Object(entries)) = components.get_mut(*section) else { continue };
+                let dir = Path::new("components").join(section);
+                let mut used = HashSet::new();
+                for (name, entry) in entries.iter_mut() {
+                    if entry.get("$ref").is_some() {
+                        continue;
+                    }
+                    let file = unique_file(&mut used, &dir, name, ext);
+                    targets.insert(child(&format!("/components/{}", section), name), file.clone());
+                    let content = std::mem::replace(entry, json!({"$ref": to_slashes(&file)}));
+                    files.insert(file, content);
+                }
+            }
+        }
+        if let (true, Some(Value::Object(paths))) = (options.paths, root.get_mut("paths")) {
+            let mut used = HashSet::new();
+            let mut tags = HashMap::new();
+            for (path, item) in paths.iter_mut() {
+                if item.get("$ref").is_some() {
+                    continue;
+                }
+                if options.paths_by_tag {
+                    let tag = self.paths.paths.get(path)
+                        .and_then(RefOr::as_item)
+                        .and_then(|item| item.iter().next())
+                        .and_then(|(_, op)| op.tags.first())
+                        .map_or("default", String::as_str);
+                    let file = tags.entry(tag.to_string())
+                        .or_insert_with(|| unique_file(&mut used, Path::new("paths"), tag, ext))
+                        .clone();
+                    let content = std::mem::replace(item, json!({"$ref": format!("{}#{}", to_slashes(&file), child("", path))}));
+                    let Value::Object(group) = files.entry(file).or_insert_with(|| json!({})) else { unreachable!() };
+                    group.insert(path.clone(), content);
+                    continue;
+                }
+                let name = path.trim_matches('/').replace('/', "_").replace(['{', '}'], "");
+                let name = if name.is_empty() { "root" } else { &name };
+                let file = unique_file(&mut used, Path::new("paths"), name, ext);
+                let content = std::mem::replace(item, json!({"$ref": to_slashes(&file)}));
+                files.insert(file, content);
+            }
+        }
+        for (file, value) in &mut files {
+            relink(value, file, &root_file, &targets);
+        }
+        files.shift_insert(0, root_file, root);
+        Ok(FileSet { files, serialize: options.serialize.clone() })
+    }
+
+    /// Writes the spec as a tree of files under `dir`, as [OpenAPI::split] lays it
+    /// out, e.g. for [OpenAPI::load_dir] to read back.
+    ///
+    /// Directories are created as needed. Files already in `dir` are overwritten
+    /// when they collide, and otherwise left alone.
+    pub fn save_split(&self, dir: impl AsRef<Path>, options: &SplitOptions) -> Result<(), LoadError> {
+        self.split(options)?.write(dir)
+    }
+}
+
+/// Points the local refs in a split out file at the file now holding their target,
+/// or at the root document.
+fn relink(value: &mut Value, file: &Path, root_file: &Path, targets: &HashMap<String, PathBuf>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                if let Some(pointer) = reference.strip_prefix('#') {
+                    // `/components/<section>/<name>` and whatever follows it.
+                    let split = pointer.match_indices('/').nth(3).map_or(pointer.len(), |(i, _)| i);
+                    let (component, rest) = pointer.split_at(split);
+                    *reference = match targets.get(component) {
+                        Some(target) if rest.is_empty() => relative(file, target),
+                        Some(target) => format!("{}#{}", relative(file, target), rest),
+                        None => format!("{}#{}", relative(file, root_file), pointer),
+                    };
+                }
+                return;
+            }
+            for child in map.values_mut() {
+                relink(child, file, root_file, targets);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                relink(item, file, root_file, targets);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A file name in `dir` derived from `name`, not already in `used`.
+pub(crate) fn unique_file(used: &mut HashSet<String>, dir: &Path, name: &str, ext: &str) -> PathBuf {
+    let stem: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "-_.".contains(c) { c } else { '_' })
+        .collect();
+    let stem = unique_name(&stem, |s| used.contains(&s.to_ascii_lowercase()));
+    used.insert(stem.to_ascii_lowercase());
+    dir.join(format!("{}.{}", stem, ext))
+}
+
+/// The relative ref from `from` to `to`, both relative to the output directory.
+fn relative(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.parent().map(|p| p.components().collect()).unwrap_or_default();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    std::iter::repeat_n("..".to_string(), from.len() - common)
+        .chain(to[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn to_slashes(path: &Path) -> String {
+    relative(Path::new("openapi"), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets/{petId}": {"get": {
+                    "tags": ["pets"],
+                    "parameters": [{"name": "petId", "in": "path", "required": true, "schema": {"$ref": "#/components/schemas/Pet/properties/id"}}],
+                    "responses": {"200": {"$ref": "#/components/responses/Pet"}}
+                }},
+                "/": {"get": {"responses": {"200": {"description": "ok"}}}}
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {"type": "object", "properties": {
+                        "id": {"type": "integer"},
+                        "parent": {"$ref": "#/components/schemas/Pet"},
+                        "status": {"$ref": "#/components/schemas/Status"}
+                    }},
+                    "Status": {"type": "string"},
+                    "Pet Id": {"$ref": "#/components/schemas/Pet/properties/id"}
+                },
+                "responses": {"Pet": {"description": "a pet", "content": {"application/json": {
+                    "schema": {"$ref": "#/components/schemas/Pet"}
+                }}}},
+                "securitySchemes": {"key": {"type": "apiKey", "name": "key", "in": "header"}}
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_split() {
+        let options = SplitOptions { serialize: SerializeOptions::default(), ..SplitOptions::default() };
+        let set = sample().split(&options).unwrap();
+        assert_eq!(set.root().0, Path::new("openapi.json"));
+        let files = set.files;
+        let mut names: Vec<&str> = files.keys().map(|p| p.to_str().unwrap()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec![
+            "components/responses/Pet.json",
+            "components/schemas/Pet.json",
+            "components/schemas/Status.json",
+            "components/securitySchemes/key.json",
+            "openapi.json",
+            "paths/pets_petId.json",
+            "paths/root.json",
+        ]);
+        let root = &files[Path::new("openapi.json")];
+        assert_eq!(root["paths"]["/pets/{petId}"], json!({"$ref": "paths/pets_petId.json"}));
+        assert_eq!(root["components"]["schemas"]["Pet"], json!({"$ref": "components/schemas/Pet.json"}));
+        let pet = &files[Path::new("components/schemas/Pet.json")];
+        assert_eq!(pet["properties"]["parent"], json!({"$ref": "Pet.json"}));
+        assert_eq!(pet["properties"]["status"], json!({"$ref": "Status.json"}));
+        assert_eq!(root["components"]["schemas"]["Pet Id"], json!({"$ref": "#/components/schemas/Pet/properties/id"}));
+        let path = &files[Path::new("paths/pets_petId.json")];
+        assert_eq!(path["get"]["responses"]["200"], json!({"$ref": "../components/responses/Pet.json"}));
+        assert_eq!(path["get"]["parameters"][0]["schema"], json!({"$ref": "../components/schemas/Pet.json#/properties/id"}));
+    }
+
+    #[test]
+    fn test_save_split_round_trip() {
+        let dir = std::env::temp_dir().join(format!("openapiv3-split-{}", std::process::id()));
+        let spec = sample();
+        let options = SplitOptions { serialize: SerializeOptions::default(), ..SplitOptions::default() };
+        spec.save_split(&dir, &options).unwrap();
+        let loaded = OpenAPI::load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.unwrap(), spec);
+    }
+
+    #[test]
+    fn test_split_paths_by_tag() {
+        let dir = std::env::temp_dir().join(format!("openapiv3-split-tags-{}", std::process::id()));
+        let spec = sample();
+        let options = SplitOptions { serialize: SerializeOptions::default(), paths_by_tag: true, ..SplitOptions::default() };
+        let set = spec.split(&options).unwrap();
+        let (_, root) = set.root();
+        assert_eq!(root["paths"]["/pets/{petId}"], json!({"$ref": "paths/pets.json#/~1pets~1{petId}"}));
+        assert_eq!(root["paths"]["/"], json!({"$ref": "paths/default.json#/~1"}));
+        let pets = &set.files[Path::new("paths/pets.json")];
+        assert_eq!(pets["/pets/{petId}"]["get"]["responses"]["200"], json!({"$ref": "../components/responses/Pet.json"}));
+        set.write(&dir).unwrap();
+        let loaded = OpenAPI::load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.unwrap(), spec);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_save_split_yaml_fixture() {
+        let dir = std::env::temp_dir().join(format!("openapiv3-split-yaml-{}", std::process::id()));
+        let spec = OpenAPI::from_yaml_str(include_str!("../fixtures/petstore.yaml")).unwrap();
+        spec.save_split(&dir, &SplitOptions::default()).unwrap();
+        let loaded = OpenAPI::load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.unwrap(), spec);
+    }
+}
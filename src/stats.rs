@@ -0,0 +1,190 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::*;
+
+/// Size and documentation quality figures of a spec, from [OpenAPI::stats], for
+/// dashboards tracking them over time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SpecStats {
+    pub paths: usize,
+    pub operations: usize,
+    /// The number of operations by lowercase method, in the order first seen.
+    pub operations_by_method: IndexMap<String, usize>,
+    /// The component schemas.
+    pub schemas: usize,
+    /// The schemas declared anywhere else, including those nested in component
+    /// schemas, e.g. properties and `items`.
+    pub inline_schemas: usize,
+    /// The most schemas nested in each other as written, `$ref`s not followed;
+    /// 1 for a schema without subschemas, 0 without any schema.
+    pub max_schema_depth: usize,
+    /// The number of components each component refers to directly, by JSON
+    /// pointer, for those referring to any; see [OpenAPI::schema_graph].
+    pub ref_fan_out: IndexMap<String, usize>,
+    /// The parameters declared in `components.parameters` or inline in the paths,
+    /// so that a shared one is counted once however many operations use it.
+    pub parameters: usize,
+    /// Operations with a non-empty `description` or `summary`.
+    pub described_operations: usize,
+    /// Operations declaring an example for a parameter, request or response.
+    pub operations_with_examples: usize,
+    /// `described_operations` as a percentage of `operations`, 100 without any.
+    pub description_coverage: f64,
+    /// `operations_with_examples` as a percentage of `operations`, 100 without any.
+    pub example_coverage: f64,
+    pub deprecated_operations: usize,
+    pub deprecated_parameters: usize,
+    pub deprecated_schemas: usize,
+    /// Tags declared at the top level that no operation uses.
+    pub unused_tags: Vec<String>,
+}
+
+impl OpenAPI {
+    pub fn stats(&self) -> SpecStats {
+        let mut stats = SpecStats {
+            paths: self.paths.paths.len(),
+            schemas: self.schemas.len(),
+            deprecated_schemas: self.schemas.values().filter_map(RefOr::as_item).filter(|schema| schema.deprecated).count(),
+            ..SpecStats::default()
+        };
+
+        let mut schemas = SchemaDepth::default();
+        self.visit_schemas(&mut schemas);
+        stats.inline_schemas = schemas.count - self.schemas.values().filter_map(RefOr::as_item).count();
+        stats.max_schema_depth = schemas.max;
+
+        let graph = self.schema_graph();
+        stats.ref_fan_out = graph.components()
+            .map(|component| (component.to_string(), graph.dependencies_of(component).count()))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+
+        let inline = self.paths.iter()
+            .filter_map(|(_, item)| item.as_item())
+            .flat_map(|item| item.parameters.iter().chain(item.iter().flat_map(|(_, op)| &op.parameters)));
+        for parameter in self.parameters.values().chain(inline).filter_map(RefOr::as_item) {
+            stats.parameters += 1;
+            stats.deprecated_parameters += usize::from(parameter.deprecated == Some(true));
+        }
+
+        let mut used_tags = std::collections::HashSet::new();
+        for op in self.resolved().operations() {
+            stats.operations += 1;
+            *stats.operations_by_method.entry(op.method.to_string()).or_default() += 1;
+            let operation = op.operation;
+            let described = [&operation.description, &operation.summary].iter()
+                .any(|text| text.as_deref().is_some_and(|text| !text.trim().is_empty()));
+            stats.described_operations += usize::from(described);
+            stats.operations_with_examples += usize::from(has_examples(op));
+            stats.deprecated_operations += usize::from(operation.deprecated);
+            used_tags.extend(operation.tags.iter().map(String::as_str));
+        }
+        stats.unused_tags = self.tags.iter()
+            .filter(|tag| !used_tags.contains(tag.name.as_str()))
+            .map(|tag| tag.name.clone())
+            .collect();
+        let total = stats.operations;
+        let percentage = |count: usize| match total {
+            0 => 100.0,
+            total => count as f64 * 100.0 / total as f64,
+        };
+        stats.description_coverage = percentage(stats.described_operations);
+        stats.example_coverage = percentage(stats.operations_with_examples);
+        stats
+    }
+}
+
+/// Counts schemas and how deeply they nest, from the pointers of a pre-order walk.
+#[derive(Default)]
+struct SchemaDepth {
+    count: usize,
+    max: usize,
+    /// The pointers of the schemas enclosing the current one.
+    enclosing: Vec<String>,
+}
+
+impl SchemaVisitor for SchemaDepth {
+    fn visit_schema(&mut self, pointer: &str, _schema: &Schema) {
+        while let Some(last) = self.enclosing.last() {
+            if pointer.strip_prefix(last.as_str()).is_some_and(|rest| rest.starts_with('/')) {
+                break;
+            }
+            self.enclosing.pop();
+        }
+        self.enclosing.push(pointer.to_string());
+        self.count += 1;
+        self.max = self.max.max(self.enclosing.len());
+    }
+}
+
+fn has_examples(op: ResolvedOperation<'_>) -> bool {
+    let media = |content: &Content| content.values().any(|media| media.example.is_some() || !media.examples.is_empty());
+    let parameters = op.parameters().unwrap_or_default().into_iter().any(|parameter| {
+        parameter.example.is_some() || !parameter.examples.is_empty() || match &parameter.format {
+            ParameterSchemaOrContent::Content(content) => media(content),
+            ParameterSchemaOrContent::Schema(_) => false,
+        }
+    });
+    let body = op.request_body().ok().flatten().is_some_and(|body| media(&body.content));
+    let responses = op.responses().unwrap_or_default().into_iter().any(|(_, response)| media(&response.content));
+    parameters || body || responses
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {
+                "/pets": {
+                    "parameters": [{"$ref": "#/components/parameters/Limit"}],
+                    "get": {"summary": "List pets", "tags": ["pets"], "responses": {"200": {"$ref": "#/components/responses/Pets"}}},
+                    "post": {"deprecated": true, "responses": {}}
+                },
+                "/pets/{id}": {"get": {
+                    "description": " ",
+                    "parameters": [{"name": "id", "in": "path", "required": true, "deprecated": true, "schema": {"type": "string"}}],
+                    "responses": {}
+                }}
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {"type": "object", "properties": {
+                        "previous": {"$ref": "#/components/schemas/OldPet"},
+                        "tags": {"type": "array", "items": {"type": "string"}}
+                    }},
+                    "OldPet": {"type": "object", "deprecated": true}},
+                "parameters": {"Limit": {"name": "limit", "in": "query", "schema": {"type": "integer"}}},
+                "responses": {"Pets": {"description": "", "content": {"application/json": {"example": []}}}}
+            },
+            "tags": [{"name": "pets"}, {"name": "legacy"}]
+        })).unwrap();
+        let stats = spec.stats();
+        assert_eq!(stats, SpecStats {
+            paths: 2,
+            operations: 3,
+            operations_by_method: IndexMap::from([("get".to_string(), 2), ("post".to_string(), 1)]),
+            schemas: 2,
+            inline_schemas: 4,
+            max_schema_depth: 3,
+            ref_fan_out: IndexMap::from([("/components/schemas/Pet".to_string(), 1)]),
+            parameters: 2,
+            described_operations: 1,
+            operations_with_examples: 1,
+            description_coverage: 100.0 / 3.0,
+            example_coverage: 100.0 / 3.0,
+            deprecated_operations: 1,
+            deprecated_parameters: 1,
+            deprecated_schemas: 1,
+            unused_tags: vec!["legacy".to_string()],
+        });
+        assert_eq!(OpenAPI::default().stats().description_coverage, 100.0);
+    }
+}
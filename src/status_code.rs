@@ -16,6 +16,37 @@ impl fmt::Display for StatusCode {
     }
 }
 
+impl StatusCode {
+    /// Whether this key covers responses with status `code`: a `Code` only its own,
+    /// a `Range` every code of its hundred, e.g. `4XX` 400 to 499.
+    pub fn matches(&self, code: u16) -> bool {
+        match self {
+            StatusCode::Code(n) => *n == code,
+            StatusCode::Range(n) => code / 100 == *n,
+        }
+    }
+
+    /// Whether this key covers successful, 2xx, responses.
+    pub fn is_success(&self) -> bool {
+        match self {
+            StatusCode::Code(n) => (200..300).contains(n),
+            StatusCode::Range(n) => *n == 2,
+        }
+    }
+}
+
+impl From<u16> for StatusCode {
+    fn from(code: u16) -> Self {
+        StatusCode::Code(code)
+    }
+}
+
+impl From<http::StatusCode> for StatusCode {
+    fn from(status: http::StatusCode) -> Self {
+        StatusCode::Code(status.as_u16())
+    }
+}
+
 impl<'de> Deserialize<'de> for StatusCode {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -97,6 +128,21 @@ impl Serialize for StatusCode {
     }
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for StatusCode {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "StatusCode".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        // Matches what deserialization accepts: a code like `404`, or a range like `4XX`.
+        schemars::json_schema!({
+            "type": ["string", "integer"],
+            "pattern": "^[1-5]([0-9]{2}|[xX]{2})$"
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::StatusCode;
@@ -125,4 +171,14 @@ mod tests {
     fn deserialize_invalid_range() {
         let _: StatusCode = from_str("2XY").unwrap();
     }
+
+    #[test]
+    fn matches() {
+        assert!(StatusCode::Code(404).matches(404));
+        assert!(!StatusCode::Code(404).matches(400));
+        assert!(StatusCode::Range(4).matches(499));
+        assert!(!StatusCode::Range(4).matches(500));
+        assert!(StatusCode::from(http::StatusCode::CREATED).is_success());
+        assert!(!StatusCode::Range(3).is_success());
+    }
 }
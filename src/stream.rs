@@ -0,0 +1,148 @@
+use std::fmt;
+use std::io::{BufReader, Read};
+
+use indexmap::IndexMap;
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+use serde::Deserializer;
+
+use crate::Operation;
+
+const METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// Walks a JSON spec from `reader`, calling `f` with `(path, method, operation)` for
+/// each operation as soon as it is parsed. Nothing else is kept: other top-level
+/// fields are skipped, and memory use is bounded by the largest single operation.
+///
+/// Operations are passed as written, without parameters inherited from their path
+/// item. Path items that are `$ref`s are skipped.
+pub fn stream_operations<F>(reader: impl Read, mut f: F) -> Result<(), serde_json::Error>
+where
+    F: FnMut(&str, &str, Operation),
+{
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    deserializer.deserialize_map(Document(&mut f))?;
+    deserializer.end()
+}
+
+struct Document<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(&str, &str, Operation)> Visitor<'de> for Document<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an OpenAPI document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "paths" {
+                map.next_value_seed(Paths(&mut *self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Paths<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(&str, &str, Operation)> DeserializeSeed<'de> for Paths<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(&str, &str, Operation)> Visitor<'de> for Paths<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Paths Object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(path) = map.next_key::<String>()? {
+            if path.starts_with("x-") {
+                map.next_value::<IgnoredAny>()?;
+            } else {
+                map.next_value_seed(PathItem { path: &path, f: &mut *self.0 })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct PathItem<'a, F> {
+    path: &'a str,
+    f: &'a mut F,
+}
+
+impl<'de, F: FnMut(&str, &str, Operation)> DeserializeSeed<'de> for PathItem<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(&str, &str, Operation)> Visitor<'de> for PathItem<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Path Item Object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match METHODS.iter().find(|m| **m == key) {
+                Some(method) => {
+                    let operation: Operation = map.next_value()?;
+                    (self.f)(self.path, method, operation);
+                }
+                None if key == "additionalOperations" => {
+                    let operations: IndexMap<String, Operation> = map.next_value()?;
+                    for (method, operation) in operations {
+                        (self.f)(self.path, &method, operation);
+                    }
+                }
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpenAPI;
+
+    #[test]
+    fn test_stream_operations() {
+        let input = include_str!("../fixtures/quayio.json");
+        let spec: OpenAPI = serde_json::from_str(input).unwrap();
+        // Methods come in document order, rather than the fixed order of PathItem::iter.
+        let mut streamed = Vec::new();
+        stream_operations(input.as_bytes(), |path, method, op| {
+            streamed.push((path.to_string(), method.to_string(), op));
+        }).unwrap();
+        let mut expected: Vec<_> = spec.operations()
+            .map(|(path, method, op, _)| (path.to_string(), method.to_string(), op.clone()))
+            .collect();
+        streamed.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        expected.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_stream_operations_errors() {
+        let bad = r#"{"paths": {"/a": {"get": {"responses": 1}}}}"#;
+        assert!(stream_operations(bad.as_bytes(), |_, _, _| {}).is_err());
+        let trailing = r#"{"paths": {}} x"#;
+        assert!(stream_operations(trailing.as_bytes(), |_, _, _| {}).is_err());
+    }
+}
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 /// Operation Object. It is not mandatory to have a
 /// Tag Object per tag defined in the Operation Object instances.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Tag {
     /// REQUIRED. The name of the tag.
     pub name: String,
@@ -18,5 +20,115 @@ pub struct Tag {
     pub external_docs: Option<ExternalDocumentation>,
     /// Inline extensions to this object.
     #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
     pub extensions: IndexMap<String, serde_json::Value>,
 }
+
+/// The extension of the spec root that groups its tags, as Redoc displays them.
+pub const TAG_GROUPS: &str = "x-tagGroups";
+
+/// A named group of tags, an entry of the `x-tagGroups` extension.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct TagGroup {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+/// A problem with the `x-tagGroups` of a spec, from [OpenAPI::validate_tag_groups].
+#[derive(Debug)]
+pub enum TagGroupError {
+    /// The extension isn't a list of groups.
+    Invalid(serde_json::Error),
+    /// A group lists a tag that no operation uses and `tags` doesn't declare.
+    UnknownTag { group: String, tag: String },
+}
+
+impl std::fmt::Display for TagGroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagGroupError::Invalid(e) => write!(f, "invalid {}: {}", TAG_GROUPS, e),
+            TagGroupError::UnknownTag { group, tag } => write!(f, "tag group {:?} lists unknown tag {:?}", group, tag),
+        }
+    }
+}
+
+impl std::error::Error for TagGroupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TagGroupError::Invalid(e) => Some(e),
+            TagGroupError::UnknownTag { .. } => None,
+        }
+    }
+}
+
+impl OpenAPI {
+    /// The groups of the `x-tagGroups` extension, empty without one.
+    pub fn tag_groups(&self) -> Result<Vec<TagGroup>, serde_json::Error> {
+        match self.extensions.get(TAG_GROUPS) {
+            Some(groups) => serde_json::from_value(groups.clone()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Replaces the `x-tagGroups` extension by `groups`, removing it if empty.
+    pub fn set_tag_groups(&mut self, groups: Vec<TagGroup>) {
+        if groups.is_empty() {
+            self.extensions.shift_remove(TAG_GROUPS);
+        } else {
+            let groups = serde_json::to_value(groups).expect("tag groups serialize");
+            self.extensions.insert(TAG_GROUPS.to_string(), groups);
+        }
+    }
+
+    /// Checks that the `x-tagGroups` extension parses, and that every tag it lists
+    /// is declared in `tags` or used by an operation.
+    pub fn validate_tag_groups(&self) -> Result<(), Vec<TagGroupError>> {
+        let groups = self.tag_groups().map_err(|e| vec![TagGroupError::Invalid(e)])?;
+        let known: std::collections::HashSet<&str> = self.tags.iter().map(|tag| tag.name.as_str())
+            .chain(self.operations().flat_map(|(_, _, op, _)| op.tags.iter().map(String::as_str)))
+            .collect();
+        let errors: Vec<TagGroupError> = groups.iter()
+            .flat_map(|group| group.tags.iter().map(move |tag| (group, tag)))
+            .filter(|(_, tag)| !known.contains(tag.as_str()))
+            .map(|(group, tag)| TagGroupError::UnknownTag { group: group.name.clone(), tag: tag.clone() })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_tag_groups() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"get": {"tags": ["pets"], "responses": {}}}},
+            "tags": [{"name": "users"}],
+            "x-tagGroups": [{"name": "Core", "tags": ["pets", "users"]}, {"name": "Admin", "tags": ["audit"]}]
+        })).unwrap();
+        let mut groups = spec.tag_groups().unwrap();
+        assert_eq!(groups[0], TagGroup { name: "Core".to_string(), tags: vec!["pets".to_string(), "users".to_string()] });
+        let errors = spec.validate_tag_groups().unwrap_err();
+        assert_eq!(errors.iter().map(ToString::to_string).collect::<Vec<_>>(), [r#"tag group "Admin" lists unknown tag "audit""#]);
+
+        groups.pop();
+        spec.set_tag_groups(groups);
+        assert!(spec.validate_tag_groups().is_ok());
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["x-tagGroups"], json!([{"name": "Core", "tags": ["pets", "users"]}]));
+        spec.set_tag_groups(Vec::new());
+        assert!(!spec.extensions.contains_key(TAG_GROUPS));
+
+        spec.extensions.insert(TAG_GROUPS.to_string(), json!({"name": "Core"}));
+        assert!(matches!(spec.validate_tag_groups().unwrap_err()[..], [TagGroupError::Invalid(_)]));
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+
+use crate::example_validation::{child, unescape};
+use crate::*;
+
+/// Rewrites of a document by [OpenAPI::transform], one hook per kind of object.
+/// Each hook is called on every object of its kind declared in the document, and
+/// does nothing by default. `pointer` is the JSON pointer to the object, after
+/// renames, e.g. `/paths/~1pets/get/parameters/0`.
+pub trait Transform {
+    /// A new name for the component `name` of `section`, e.g. `schemas` or
+    /// `securitySchemes`, or `None` to keep it. The `$ref`s to renamed components
+    /// follow, and so do discriminator mappings and security requirements.
+    fn rename_component(&mut self, _section: &str, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Called on each operation before its contents. Returning `false` removes it.
+    fn operation(&mut self, _pointer: &str, _operation: &mut Operation) -> bool {
+        true
+    }
+
+    fn parameter(&mut self, _pointer: &str, _parameter: &mut Parameter) {}
+
+    fn response(&mut self, _pointer: &str, _response: &mut Response) {}
+
+    /// Called on each schema before the schemas nested in it, which are those
+    /// of the schema as edited.
+    fn schema(&mut self, _pointer: &str, _schema: &mut Schema) {}
+
+    fn security_scheme(&mut self, _pointer: &str, _scheme: &mut SecurityScheme) {}
+}
+
+impl OpenAPI {
+    /// Applies `transform` to the whole document: components, paths, webhooks and
+    /// callbacks, in document order, without following `$ref`s. A path item whose
+    /// operations the transform all removes is removed too, e.g. to strip internal
+    /// operations:
+    ///
+    /// ```
+    /// # use openapiv3::*;
+    /// struct StripInternal;
+    ///
+    /// impl Transform for StripInternal {
+    ///     fn operation(&mut self, _pointer: &str, operation: &mut Operation) -> bool {
+    ///         !operation.extensions.contains_key("x-internal")
+    ///     }
+    /// }
+    /// # let mut spec = OpenAPI::default();
+    /// spec.transform(&mut StripInternal);
+    /// ```
+    pub fn transform(&mut self, transform: &mut impl Transform) {
+        let mut renames = HashMap::new();
+        let components = &mut self.components;
+        rename(transform, &mut renames, "schemas", &mut components.schemas);
+        rename(transform, &mut renames, "responses", &mut components.responses);
+        rename(transform, &mut renames, "parameters", &mut components.parameters);
+        rename(transform, &mut renames, "examples", &mut components.examples);
+        rename(transform, &mut renames, "requestBodies", &mut components.request_bodies);
+        rename(transform, &mut renames, "headers", &mut components.headers);
+        rename(transform, &mut renames, "securitySchemes", &mut components.security_schemes);
+        rename(transform, &mut renames, "links", &mut components.links);
+        rename(transform, &mut renames, "callbacks", &mut components.callbacks);
+        rename(transform, &mut renames, "pathItems", &mut components.path_items);
+
+        let mut walker = Walker { transform, renames };
+        walker.security(&mut self.security);
+        let components = &mut self.components;
+        for (name, schema) in components.schemas.iter_mut() {
+            walker.schema(&child("/components/schemas", name), schema);
+        }
+        for (name, response) in components.responses.iter_mut() {
+            walker.relink_or(response, |w, r| w.response(&child("/components/responses", name), r));
+        }
+        for (name, parameter) in components.parameters.iter_mut() {
+            walker.relink_or(parameter, |w, p| w.parameter(&child("/components/parameters", name), p));
+        }
+        for example in components.examples.values_mut() {
+            walker.relink_or(example, |_, _| {});
+        }
+        for (name, body) in components.request_bodies.iter_mut() {
+            walker.relink_or(body, |w, b| w.content(&child(&child("/components/requestBodies", name), "content"), &mut b.content));
+        }
+        for (name, header) in components.headers.iter_mut() {
+            walker.relink_or(header, |w, h| w.header(&child("/components/headers", name), h));
+        }
+        for (name, scheme) in components.security_schemes.iter_mut() {
+            walker.relink_or(scheme, |w, s| w.transform.security_scheme(&child("/components/securitySchemes", name), s));
+        }
+        for link in components.links.values_mut() {
+            walker.relink_or(link, |_, _| {});
+        }
+        for (name, callback) in components.callbacks.iter_mut() {
+            let pointer = child("/components/callbacks", name);
+            walker.relink_or(callback, |w, c| c.retain(|expression, item| w.path_item(&child(&pointer, expression), item)));
+        }
+        walker.path_items(&child("/components", "pathItems"), &mut components.path_items);
+        walker.path_items("/paths", &mut self.paths.paths);
+        walker.path_items("/webhooks", &mut self.webhooks);
+    }
+}
+
+/// Renames the components of `section` that `transform` renames, keeping their order.
+fn rename<T>(transform: &mut impl Transform, renames: &mut HashMap<(&'static str, String), String>, section: &'static str, map: &mut RefOrMap<T>) {
+    let entries = std::mem::take(&mut **map);
+    **map = entries.into_iter().map(|(name, item)| match transform.rename_component(section, &name) {
+        Some(new) => {
+            renames.insert((section, name), new.clone());
+            (new, item)
+        }
+        None => (name, item),
+    }).collect();
+}
+
+struct Walker<'t, T> {
+    transform: &'t mut T,
+    /// New names by section and old name.
+    renames: HashMap<(&'static str, String), String>,
+}
+
+impl<T: Transform> Walker<'_, T> {
+    fn renamed(&self, section: &'static str, name: &str) -> Option<&String> {
+        self.renames.get(&(section, name.to_string()))
+    }
+
+    /// Points a `$ref` to a renamed component at its new name.
+    fn relink(&self, reference: &mut String) {
+        let Some(pointer) = reference.strip_prefix("#/components/") else { return };
+        let Some((section, rest)) = pointer.split_once('/') else { return };
+        let (name, tail) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+        let name = unescape(name);
+        let Some(section) = ["schemas", "responses", "parameters", "examples", "requestBodies", "headers", "securitySchemes", "links", "callbacks", "pathItems"]
+            .iter().copied().find(|s| *s == section) else { return };
+        if let Some(new) = self.renamed(section, &name) {
+            *reference = format!("{}{}", child(&child("#/components", section), new), tail);
+        }
+    }
+
+    /// Relinks a reference, or calls `f` on an item.
+    fn relink_or<I>(&mut self, item: &mut RefOr<I>, f: impl FnOnce(&mut Self, &mut I)) {
+        match item {
+            RefOr::Reference { reference } => self.relink(reference),
+            RefOr::Item(item) => f(self, item),
+        }
+    }
+
+    fn security(&mut self, requirements: &mut [SecurityRequirement]) {
+        for requirement in requirements {
+            let entries = std::mem::take(requirement);
+            *requirement = entries.into_iter()
+                .map(|(name, scopes)| (self.renamed("securitySchemes", &name).cloned().unwrap_or(name), scopes))
+                .collect();
+        }
+    }
+
+    fn path_items(&mut self, pointer: &str, items: &mut IndexMap<String, RefOr<PathItem>>) {
+        items.retain(|path, item| {
+            let mut keep = true;
+            let pointer = child(pointer, path);
+            self.relink_or(item, |w, item| keep = w.path_item(&pointer, item));
+            keep
+        });
+    }
+
+    /// Whether to keep the path item: it has operations left, or had none to begin with.
+    fn path_item(&mut self, pointer: &str, item: &mut PathItem) -> bool {
+        for (i, parameter) in item.parameters.iter_mut().enumerate() {
+            self.relink_or(parameter, |w, p| w.parameter(&child(&child(pointer, "parameters"), &i.to_string()), p));
+        }
+        let mut removed = Vec::new();
+        for (method, operation) in item.iter_mut() {
+            let pointer = child(pointer, method);
+            if self.transform.operation(&pointer, operation) {
+                self.operation(&pointer, operation);
+            } else {
+                removed.push(method.to_string());
+            }
+        }
+        for method in &removed {
+            item.remove_operation(method);
+        }
+        removed.is_empty() || item.iter().next().is_some()
+    }
+
+    fn operation(&mut self, pointer: &str, operation: &mut Operation) {
+        for (i, parameter) in operation.parameters.iter_mut().enumerate() {
+            self.relink_or(parameter, |w, p| w.parameter(&child(&child(pointer, "parameters"), &i.to_string()), p));
+        }
+        if let Some(body) = &mut operation.request_body {
+            self.relink_or(body, |w, b| w.content(&child(&child(pointer, "requestBody"), "content"), &mut b.content));
+        }
+        let responses = child(pointer, "responses");
+        if let Some(response) = &mut operation.responses.default {
+            self.relink_or(response, |w, r| w.response(&child(&responses, "default"), r));
+        }
+        for (status, response) in operation.responses.responses.iter_mut() {
+            self.relink_or(response, |w, r| w.response(&child(&responses, &status.to_string()), r));
+        }
+        for (name, callback) in operation.callbacks.iter_mut() {
+            let pointer = child(&child(pointer, "callbacks"), name);
+            self.relink_or(callback, |w, c| c.retain(|expression, item| w.path_item(&child(&pointer, expression), item)));
+        }
+        if let Some(security) = &mut operation.security {
+            self.security(security);
+        }
+    }
+
+    fn parameter(&mut self, pointer: &str, parameter: &mut Parameter) {
+        self.transform.parameter(pointer, parameter);
+        self.examples(&mut parameter.data.examples);
+        self.format(pointer, &mut parameter.data.format);
+    }
+
+    fn header(&mut self, pointer: &str, header: &mut Header) {
+        self.examples(&mut header.examples);
+        self.format(pointer, &mut header.format);
+    }
+
+    fn format(&mut self, pointer: &str, format: &mut ParameterSchemaOrContent) {
+        match format {
+            ParameterSchemaOrContent::Schema(schema) => self.schema(&child(pointer, "schema"), schema),
+            ParameterSchemaOrContent::Content(content) => self.content(&child(pointer, "content"), content),
+        }
+    }
+
+    fn examples(&mut self, examples: &mut IndexMap<String, RefOr<Example>>) {
+        for example in examples.values_mut() {
+            self.relink_or(example, |_, _| {});
+        }
+    }
+
+    fn response(&mut self, pointer: &str, response: &mut Response) {
+        self.transform.response(pointer, response);
+        for (name, header) in response.headers.iter_mut() {
+            self.relink_or(header, |w, h| w.header(&child(&child(pointer, "headers"), name), h));
+        }
+        self.content(&child(pointer, "content"), &mut response.content);
+        for link in response.links.values_mut() {
+            self.relink_or(link, |_, _| {});
+        }
+    }
+
+    fn content(&mut self, pointer: &str, content: &mut Content) {
+        for (media_type, media) in content.iter_mut() {
+            let pointer = child(pointer, media_type);
+            if let Some(schema) = &mut media.schema {
+                self.schema(&child(&pointer, "schema"), schema);
+            }
+            self.examples(&mut media.examples);
+            for (property, encoding) in media.encoding.iter_mut() {
+                let encoding_pointer = child(&child(&pointer, "encoding"), property);
+                for (name, header) in encoding.headers.iter_mut() {
+                    self.relink_or(header, |w, h| w.header(&child(&child(&encoding_pointer, "headers"), name), h));
+                }
+            }
+        }
+    }
+
+    fn schema(&mut self, pointer: &str, schema: &mut RefOr<Schema>) {
+        let schema = match schema {
+            RefOr::Item(schema) => schema,
+            RefOr::Reference { reference } => return self.relink(reference),
+        };
+        self.transform.schema(pointer, schema);
+
+        let data = &mut schema.data;
+        if let Some(discriminator) = &mut data.discriminator {
+            for target in discriminator.mapping.values_mut() {
+                match self.renamed("schemas", target) {
+                    Some(new) => *target = new.clone(),
+                    None => self.relink(target),
+                }
+            }
+        }
+        let single = [
+            ("if", &mut data.if_),
+            ("then", &mut data.then),
+            ("else", &mut data.else_),
+            ("contains", &mut data.contains),
+            ("propertyNames", &mut data.property_names),
+            ("contentSchema", &mut data.content_schema),
+        ];
+        for (keyword, nested) in IntoIterator::into_iter(single) {
+            if let Some(nested) = nested {
+                self.schema(&child(pointer, keyword), nested);
+            }
+        }
+        self.additional(&child(pointer, "unevaluatedProperties"), &mut data.unevaluated_properties);
+        self.additional(&child(pointer, "unevaluatedItems"), &mut data.unevaluated_items);
+        self.list(&child(pointer, "prefixItems"), &mut data.prefix_items);
+        for (keyword, map) in [("$defs", &mut data.defs), ("patternProperties", &mut data.pattern_properties), ("dependentSchemas", &mut data.dependent_schemas)] {
+            self.map(&child(pointer, keyword), map);
+        }
+
+        match &mut schema.kind {
+            SchemaKind::Type(Type::Object(o)) => {
+                self.map(&child(pointer, "properties"), &mut o.properties);
+                self.additional(&child(pointer, "additionalProperties"), &mut o.additional_properties);
+            }
+            SchemaKind::Type(Type::Array(a)) => {
+                if let Some(items) = &mut a.items {
+                    self.schema(&child(pointer, "items"), items);
+                }
+            }
+            SchemaKind::Type(_) => {}
+            SchemaKind::OneOf { one_of } => self.list(&child(pointer, "oneOf"), one_of),
+            SchemaKind::AllOf { all_of } => self.list(&child(pointer, "allOf"), all_of),
+            SchemaKind::AnyOf { any_of } => self.list(&child(pointer, "anyOf"), any_of),
+            SchemaKind::Not { not } => self.schema(&child(pointer, "not"), not),
+            SchemaKind::Any(a) => {
+                self.map(&child(pointer, "properties"), &mut a.properties);
+                self.additional(&child(pointer, "additionalProperties"), &mut a.additional_properties);
+                if let Some(items) = &mut a.items {
+                    self.schema(&child(pointer, "items"), items);
+                }
+                self.list(&child(pointer, "allOf"), &mut a.all_of);
+                self.list(&child(pointer, "oneOf"), &mut a.one_of);
+                self.list(&child(pointer, "anyOf"), &mut a.any_of);
+                if let Some(not) = &mut a.not {
+                    self.schema(&child(pointer, "not"), not);
+                }
+            }
+        }
+    }
+
+    fn list(&mut self, pointer: &str, schemas: &mut [RefOr<Schema>]) {
+        for (i, schema) in schemas.iter_mut().enumerate() {
+            self.schema(&child(pointer, &i.to_string()), schema);
+        }
+    }
+
+    fn map(&mut self, pointer: &str, schemas: &mut RefOrMap<Schema>) {
+        for (name, schema) in schemas.iter_mut() {
+            self.schema(&child(pointer, name), schema);
+        }
+    }
+
+    fn additional(&mut self, pointer: &str, additional: &mut Option<AdditionalProperties>) {
+        if let Some(AdditionalProperties::Schema(schema)) = additional {
+            self.schema(pointer, schema);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Renames schemas and security schemes to PascalCase, strips internal
+    /// operations, and records the schemas it sees.
+    #[derive(Default)]
+    struct Rewrite {
+        schemas: Vec<String>,
+    }
+
+    impl Transform for Rewrite {
+        fn rename_component(&mut self, section: &str, name: &str) -> Option<String> {
+            let pascal: String = name.split('_').map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+            }).collect();
+            Some(pascal).filter(|pascal| matches!(section, "schemas" | "securitySchemes") && pascal != name)
+        }
+
+        fn operation(&mut self, _pointer: &str, operation: &mut Operation) -> bool {
+            !operation.extensions.contains_key("x-internal")
+        }
+
+        fn parameter(&mut self, _pointer: &str, parameter: &mut Parameter) {
+            let description = format!("The {}.", parameter.name);
+            parameter.description.get_or_insert(description);
+        }
+
+        fn schema(&mut self, pointer: &str, _schema: &mut Schema) {
+            self.schemas.push(pointer.to_string());
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "security": [{"api_key": []}],
+            "paths": {
+                "/pets": {"get": {
+                    "parameters": [{"name": "limit", "in": "query", "schema": {"type": "integer"}}],
+                    "responses": {"200": {"description": "", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/pet_list"}}}}}
+                }, "delete": {"x-internal": true, "responses": {}}},
+                "/admin": {"post": {"x-internal": true, "responses": {}}}
+            },
+            "components": {
+                "schemas": {
+                    "pet_list": {"type": "array", "items": {"$ref": "#/components/schemas/cat/properties/name"}},
+                    "pet": {
+                        "oneOf": [{"$ref": "#/components/schemas/cat"}],
+                        "discriminator": {"propertyName": "kind", "mapping": {"cat": "cat"}}
+                    },
+                    "cat": {"type": "object", "properties": {"name": {"type": "string"}}}
+                },
+                "securitySchemes": {"api_key": {"type": "apiKey", "in": "header", "name": "X-Key"}}
+            }
+        })).unwrap();
+        let mut rewrite = Rewrite::default();
+        spec.transform(&mut rewrite);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["security"], json!([{"ApiKey": []}]));
+        assert_eq!(json["paths"], json!({"/pets": {"get": {
+            "parameters": [{"name": "limit", "in": "query", "style": "form", "description": "The limit.", "schema": {"type": "integer"}}],
+            "responses": {"200": {"description": "", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/PetList"}}}}}
+        }}}));
+        let schemas = &json["components"]["schemas"];
+        assert_eq!(schemas["PetList"]["items"], json!({"$ref": "#/components/schemas/Cat/properties/name"}));
+        assert_eq!(schemas["Pet"]["oneOf"], json!([{"$ref": "#/components/schemas/Cat"}]));
+        assert_eq!(schemas["Pet"]["discriminator"]["mapping"], json!({"cat": "Cat"}));
+        assert!(json["components"]["securitySchemes"]["ApiKey"].is_object());
+        assert_eq!(rewrite.schemas, [
+            "/components/schemas/PetList",
+            "/components/schemas/Pet",
+            "/components/schemas/Cat",
+            "/components/schemas/Cat/properties/name",
+            "/paths/~1pets/get/parameters/0/schema",
+        ]);
+        assert!(spec.validate().is_ok());
+    }
+}
@@ -0,0 +1,568 @@
+use serde_json::{Map, Value};
+
+use crate::example_validation::child;
+use crate::ParseError;
+
+/// Returns an error for every field of `document` that parsing would silently
+/// drop: keys that are neither an `x-` extension nor a field the model knows for
+/// the object they're in, e.g. `requestBodies` in an operation. Objects with a
+/// `$ref` only know `summary` and `description` besides, and their fields aren't
+/// looked into. Schemas are checked against the keywords the model reads, which
+/// 3.1 documents may legitimately go beyond.
+pub(crate) fn find_unknown_fields(document: &Value) -> Vec<ParseError> {
+    let mut found = Vec::new();
+    check(document, Kind::OpenAPI, "", &mut found);
+    found
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    OpenAPI,
+    Info,
+    Contact,
+    License,
+    Server,
+    ServerVariable,
+    Components,
+    Paths,
+    PathItem,
+    Operation,
+    Parameter,
+    RequestBody,
+    MediaType,
+    Encoding,
+    Responses,
+    Response,
+    Callback,
+    Example,
+    Link,
+    Header,
+    Tag,
+    ExternalDocs,
+    Schema,
+    Discriminator,
+    Xml,
+    SecurityScheme,
+    OAuthFlows,
+    OAuthFlow,
+}
+
+/// What the value of a field is.
+#[derive(Clone, Copy)]
+enum Field {
+    /// A value whose fields, if any, aren't checked.
+    Plain,
+    One(Kind),
+    MapOf(Kind),
+    ListOf(Kind),
+}
+
+use Field::*;
+
+const OPENAPI: &[(&str, Field)] = &[
+    ("openapi", Plain), ("info", One(Kind::Info)), ("jsonSchemaDialect", Plain), ("servers", ListOf(Kind::Server)),
+    ("paths", One(Kind::Paths)), ("webhooks", MapOf(Kind::PathItem)), ("components", One(Kind::Components)),
+    ("security", Plain), ("tags", ListOf(Kind::Tag)), ("externalDocs", One(Kind::ExternalDocs)),
+];
+const INFO: &[(&str, Field)] = &[
+    ("title", Plain), ("summary", Plain), ("description", Plain), ("termsOfService", Plain),
+    ("contact", One(Kind::Contact)), ("license", One(Kind::License)), ("version", Plain),
+];
+const CONTACT: &[(&str, Field)] = &[("name", Plain), ("url", Plain), ("email", Plain)];
+const LICENSE: &[(&str, Field)] = &[("name", Plain), ("identifier", Plain), ("url", Plain)];
+const SERVER: &[(&str, Field)] = &[("url", Plain), ("description", Plain), ("variables", MapOf(Kind::ServerVariable))];
+const SERVER_VARIABLE: &[(&str, Field)] = &[("enum", Plain), ("default", Plain), ("description", Plain)];
+const COMPONENTS: &[(&str, Field)] = &[
+    ("schemas", MapOf(Kind::Schema)), ("responses", MapOf(Kind::Response)), ("parameters", MapOf(Kind::Parameter)),
+    ("examples", MapOf(Kind::Example)), ("requestBodies", MapOf(Kind::RequestBody)), ("headers", MapOf(Kind::Header)),
+    ("securitySchemes", MapOf(Kind::SecurityScheme)), ("links", MapOf(Kind::Link)), ("callbacks", MapOf(Kind::Callback)),
+    ("pathItems", MapOf(Kind::PathItem)),
+];
+const PATH_ITEM: &[(&str, Field)] = &[
+    ("summary", Plain), ("description", Plain), ("get", One(Kind::Operation)), ("put", One(Kind::Operation)),
+    ("post", One(Kind::Operation)), ("delete", One(Kind::Operation)), ("options", One(Kind::Operation)),
+    ("head", One(Kind::Operation)), ("patch", One(Kind::Operation)), ("trace", One(Kind::Operation)),
+    ("additionalOperations", MapOf(Kind::Operation)), ("servers", ListOf(Kind::Server)), ("parameters", ListOf(Kind::Parameter)),
+];
+const OPERATION: &[(&str, Field)] = &[
+    ("tags", Plain), ("summary", Plain), ("description", Plain), ("externalDocs", One(Kind::ExternalDocs)),
+    ("operationId", Plain), ("parameters", ListOf(Kind::Parameter)), ("requestBody", One(Kind::RequestBody)),
+    ("responses", One(Kind::Responses)), ("callbacks", MapOf(Kind::Callback)), ("deprecated", Plain), ("security", Plain), ("servers", ListOf(Kind::Server)),
+];
+const PARAMETER: &[(&str, Field)] = &[
+    ("name", Plain), ("in", Plain), ("description", Plain), ("required", Plain), ("deprecated", Plain),
+    ("style", Plain), ("explode", Plain), ("schema", One(Kind::Schema)), ("content", MapOf(Kind::MediaType)),
+    ("example", Plain), ("examples", MapOf(Kind::Example)),
+];
+const QUERY_PARAMETER: &[(&str, Field)] = &[("allowReserved", Plain), ("allowEmptyValue", Plain)];
+const REQUEST_BODY: &[(&str, Field)] = &[("description", Plain), ("content", MapOf(Kind::MediaType)), ("required", Plain)];
+const MEDIA_TYPE: &[(&str, Field)] = &[
+    ("schema", One(Kind::Schema)), ("example", Plain), ("examples", MapOf(Kind::Example)), ("encoding", MapOf(Kind::Encoding)),
+];
+const ENCODING: &[(&str, Field)] = &[
+    ("contentType", Plain), ("headers", MapOf(Kind::Header)), ("style", Plain), ("explode", Plain), ("allowReserved", Plain),
+];
+const RESPONSE: &[(&str, Field)] = &[
+    ("description", Plain), ("headers", MapOf(Kind::Header)), ("content", MapOf(Kind::MediaType)), ("links", MapOf(Kind::Link)),
+];
+const EXAMPLE: &[(&str, Field)] = &[("summary", Plain), ("description", Plain), ("value", Plain), ("externalValue", Plain)];
+const LINK: &[(&str, Field)] = &[
+    ("operationRef", Plain), ("operationId", Plain), ("parameters", Plain), ("requestBody", Plain),
+    ("description", Plain), ("server", One(Kind::Server)),
+];
+const HEADER: &[(&str, Field)] = &[
+    ("description", Plain), ("required", Plain), ("deprecated", Plain), ("style", Plain),
+    ("schema", One(Kind::Schema)), ("content", MapOf(Kind::MediaType)), ("example", Plain), ("examples", MapOf(Kind::Example)),
+];
+const TAG: &[(&str, Field)] = &[("name", Plain), ("description", Plain), ("externalDocs", One(Kind::ExternalDocs))];
+const EXTERNAL_DOCS: &[(&str, Field)] = &[("description", Plain), ("url", Plain)];
+const SCHEMA: &[(&str, Field)] = &[
+    ("$ref", Plain), ("type", Plain), ("nullable", Plain), ("readOnly", Plain), ("writeOnly", Plain), ("deprecated", Plain),
+    ("externalDocs", One(Kind::ExternalDocs)), ("example", Plain), ("examples", Plain), ("title", Plain), ("description", Plain),
+    ("discriminator", One(Kind::Discriminator)), ("xml", One(Kind::Xml)), ("default", Plain), ("const", Plain), ("enum", Plain),
+    ("$schema", Plain), ("$id", Plain), ("$anchor", Plain), ("$comment", Plain), ("$defs", MapOf(Kind::Schema)),
+    ("if", One(Kind::Schema)), ("then", One(Kind::Schema)), ("else", One(Kind::Schema)), ("not", One(Kind::Schema)),
+    ("allOf", ListOf(Kind::Schema)), ("oneOf", ListOf(Kind::Schema)), ("anyOf", ListOf(Kind::Schema)),
+    ("format", Plain), ("pattern", Plain), ("minLength", Plain), ("maxLength", Plain),
+    ("contentEncoding", Plain), ("contentMediaType", Plain), ("contentSchema", One(Kind::Schema)),
+    ("multipleOf", Plain), ("minimum", Plain), ("maximum", Plain), ("exclusiveMinimum", Plain), ("exclusiveMaximum", Plain),
+    ("properties", MapOf(Kind::Schema)), ("required", Plain), ("additionalProperties", One(Kind::Schema)),
+    ("minProperties", Plain), ("maxProperties", Plain), ("patternProperties", MapOf(Kind::Schema)),
+    ("propertyNames", One(Kind::Schema)), ("dependentSchemas", MapOf(Kind::Schema)), ("dependentRequired", Plain),
+    ("unevaluatedProperties", One(Kind::Schema)), ("items", One(Kind::Schema)), ("prefixItems", ListOf(Kind::Schema)),
+    ("minItems", Plain), ("maxItems", Plain), ("uniqueItems", Plain), ("contains", One(Kind::Schema)),
+    ("minContains", Plain), ("maxContains", Plain), ("unevaluatedItems", One(Kind::Schema)),
+];
+const DISCRIMINATOR: &[(&str, Field)] = &[("propertyName", Plain), ("mapping", Plain)];
+const XML: &[(&str, Field)] = &[("name", Plain), ("namespace", Plain), ("prefix", Plain), ("attribute", Plain), ("wrapped", Plain)];
+const SECURITY_SCHEME: &[(&str, Field)] = &[("type", Plain), ("description", Plain)];
+const OAUTH_FLOWS: &[(&str, Field)] = &[
+    ("implicit", One(Kind::OAuthFlow)), ("password", One(Kind::OAuthFlow)),
+    ("clientCredentials", One(Kind::OAuthFlow)), ("authorizationCode", One(Kind::OAuthFlow)),
+];
+const OAUTH_FLOW: &[(&str, Field)] = &[("authorizationUrl", Plain), ("tokenUrl", Plain), ("refreshUrl", Plain), ("scopes", Plain)];
+
+/// The fields of an object of `kind`, some of which depend on its `in` or `type`.
+fn fields(kind: Kind, map: &Map<String, Value>) -> Vec<(&'static str, Field)> {
+    let tag = |key: &str| map.get(key).and_then(Value::as_str);
+    let (fields, extra): (&[_], &[_]) = match kind {
+        Kind::OpenAPI => (OPENAPI, &[]),
+        Kind::Info => (INFO, &[]),
+        Kind::Contact => (CONTACT, &[]),
+        Kind::License => (LICENSE, &[]),
+        Kind::Server => (SERVER, &[]),
+        Kind::ServerVariable => (SERVER_VARIABLE, &[]),
+        Kind::Components => (COMPONENTS, &[]),
+        Kind::PathItem => (PATH_ITEM, &[]),
+        Kind::Operation => (OPERATION, &[]),
+        Kind::Parameter if tag("in") == Some("query") => (PARAMETER, QUERY_PARAMETER),
+        Kind::Parameter => (PARAMETER, &[]),
+        Kind::RequestBody => (REQUEST_BODY, &[]),
+        Kind::MediaType => (MEDIA_TYPE, &[]),
+        Kind::Encoding => (ENCODING, &[]),
+        Kind::Response => (RESPONSE, &[]),
+        Kind::Example => (EXAMPLE, &[]),
+        Kind::Link => (LINK, &[]),
+        Kind::Header => (HEADER, &[]),
+        Kind::Tag => (TAG, &[]),
+        Kind::ExternalDocs => (EXTERNAL_DOCS, &[]),
+        Kind::Schema => (SCHEMA, &[]),
+        Kind::Discriminator => (DISCRIMINATOR, &[]),
+        Kind::Xml => (XML, &[]),
+        Kind::SecurityScheme => (SECURITY_SCHEME, match tag("type") {
+            Some("apiKey") => &[("in", Plain), ("name", Plain)],
+            Some("http") => &[("scheme", Plain), ("bearerFormat", Plain)],
+            Some("oauth2") => &[("flows", One(Kind::OAuthFlows))],
+            Some("openIdConnect") => &[("openIdConnectUrl", Plain)],
+            _ => &[],
+        }),
+        Kind::OAuthFlows => (OAUTH_FLOWS, &[]),
+        Kind::OAuthFlow => (OAUTH_FLOW, &[]),
+        // Keyed by name rather than by field; see `check`.
+        Kind::Paths | Kind::Responses | Kind::Callback => (&[], &[]),
+    };
+    fields.iter().chain(extra).copied().collect()
+}
+
+fn check(value: &Value, kind: Kind, pointer: &str, found: &mut Vec<ParseError>) {
+    let Some(map) = value.as_object() else { return };
+    if kind != Kind::Schema && map.contains_key("$ref") {
+        for key in map.keys().filter(|key| !matches!(key.as_str(), "$ref" | "summary" | "description")) {
+            found.push(error(child(pointer, key), key));
+        }
+        return;
+    }
+    let fields = fields(kind, map);
+    for (key, value) in map {
+        let pointer = child(pointer, key);
+        if key.starts_with("x-") {
+            continue;
+        }
+        let field = match kind {
+            Kind::Paths if key.starts_with('/') => One(Kind::PathItem),
+            Kind::Responses => One(Kind::Response),
+            Kind::Callback => One(Kind::PathItem),
+            _ => match fields.iter().find(|(name, _)| name == key) {
+                Some((_, field)) => *field,
+                None => {
+                    found.push(error(pointer, key));
+                    continue;
+                }
+            },
+        };
+        match field {
+            Plain => {}
+            One(kind) => check(value, kind, &pointer, found),
+            MapOf(kind) => {
+                for (name, value) in value.as_object().into_iter().flatten() {
+                    check(value, kind, &child(&pointer, name), found);
+                }
+            }
+            ListOf(kind) => {
+                for (i, value) in value.as_array().into_iter().flatten().enumerate() {
+                    check(value, kind, &format!("{}/{}", pointer, i), found);
+                }
+            }
+        }
+    }
+}
+
+fn error(pointer: String, key: &str) -> ParseError {
+    ParseError { pointer, line: None, column: None, message: format!("unknown field `{}`", key) }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use serde_json::json;
+
+    use super::*;
+    use crate::*;
+
+    // Every struct below is written out field by field, without `..Default::default()`,
+    // so a field added to the model doesn't compile here until it's given a value,
+    // and then this test fails until the lists above know its name.
+
+    fn s() -> Option<String> {
+        Some("s".to_string())
+    }
+
+    fn map<T>(value: T) -> IndexMap<String, T> {
+        IndexMap::from([("a".to_string(), value)])
+    }
+
+    fn ref_map<T>(value: T) -> RefOrMap<T> {
+        RefOrMap::from(map(RefOr::Item(value)))
+    }
+
+    fn sub() -> Box<RefOr<Schema>> {
+        Box::new(RefOr::Item(Schema::new_string()))
+    }
+
+    fn schema_data() -> SchemaData {
+        SchemaData {
+            nullable: true,
+            read_only: true,
+            write_only: true,
+            deprecated: true,
+            external_docs: Some(external_docs()),
+            example: Some(json!(1)),
+            title: s(),
+            description: s(),
+            discriminator: Some(Discriminator { property_name: "kind".to_string(), mapping: map("B".to_string()), extensions: IndexMap::new() }),
+            xml: Some(Xml { name: s(), namespace: s(), prefix: s(), attribute: true, wrapped: true, extensions: IndexMap::new() }),
+            default: Some(json!(1)),
+            const_value: Some(json!(1)),
+            if_: Some(sub()),
+            then: Some(sub()),
+            else_: Some(sub()),
+            unevaluated_properties: Some(AdditionalProperties::Any(false)),
+            unevaluated_items: Some(AdditionalProperties::Schema(sub())),
+            dialect: s(),
+            id: s(),
+            anchor: s(),
+            comment: s(),
+            defs: ref_map(Schema::new_string()),
+            examples: vec![json!(1)],
+            prefix_items: vec![*sub()],
+            contains: Some(sub()),
+            min_contains: Some(1),
+            max_contains: Some(1),
+            pattern_properties: ref_map(Schema::new_string()),
+            property_names: Some(sub()),
+            dependent_schemas: ref_map(Schema::new_string()),
+            dependent_required: map(vec!["b".to_string()]),
+            content_schema: Some(sub()),
+            #[cfg(feature = "arbitrary_precision")]
+            numbers: IndexMap::new(),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn schemas() -> RefOrMap<Schema> {
+        let kinds = vec![
+            SchemaKind::Any(AnySchema {
+                typ: s(),
+                types: vec![],
+                pattern: s(),
+                multiple_of: Some(1.0),
+                exclusive_minimum: Some(ExclusiveBound::Value(1.0)),
+                exclusive_maximum: Some(ExclusiveBound::Value(1.0)),
+                minimum: Some(1.0),
+                maximum: Some(1.0),
+                properties: ref_map(Schema::new_string()),
+                required: vec!["a".to_string()],
+                additional_properties: Some(AdditionalProperties::Schema(sub())),
+                min_properties: Some(1),
+                max_properties: Some(1),
+                items: Some(sub()),
+                min_items: Some(1),
+                max_items: Some(1),
+                unique_items: Some(true),
+                enumeration: vec![json!(1)],
+                format: s(),
+                min_length: Some(1),
+                max_length: Some(1),
+                content_encoding: s(),
+                content_media_type: s(),
+                one_of: vec![*sub()],
+                all_of: vec![*sub()],
+                any_of: vec![*sub()],
+                not: Some(sub()),
+            }),
+            SchemaKind::Type(Type::String(StringType {
+                format: VariantOrUnknownOrEmpty::Item(StringFormat::Date),
+                pattern: s(),
+                enumeration: vec!["a".to_string()],
+                min_length: Some(1),
+                max_length: Some(1),
+                content_encoding: s(),
+                content_media_type: s(),
+            })),
+            SchemaKind::Type(Type::Number(NumberType {
+                format: VariantOrUnknownOrEmpty::Item(NumberFormat::Double),
+                multiple_of: Some(1.0),
+                exclusive_minimum: ExclusiveBound::Value(1.0),
+                exclusive_maximum: ExclusiveBound::Value(1.0),
+                minimum: Some(1.0),
+                maximum: Some(1.0),
+                enumeration: vec![Some(1.0)],
+            })),
+            SchemaKind::Type(Type::Integer(IntegerType {
+                format: VariantOrUnknownOrEmpty::Item(IntegerFormat::Int64),
+                multiple_of: Some(1),
+                exclusive_minimum: ExclusiveBound::Value(1),
+                exclusive_maximum: ExclusiveBound::Value(1),
+                minimum: Some(1),
+                maximum: Some(1),
+                enumeration: vec![Some(1)],
+            })),
+            SchemaKind::Type(Type::Object(ObjectType {
+                properties: ref_map(Schema::new_string()),
+                required: vec!["a".to_string()],
+                additional_properties: Some(AdditionalProperties::Any(true)),
+                min_properties: Some(1),
+                max_properties: Some(1),
+            })),
+            SchemaKind::Type(Type::Array(ArrayType { items: Some(sub()), min_items: Some(1), max_items: Some(1), unique_items: true })),
+        ];
+        kinds.into_iter().enumerate()
+            .map(|(i, kind)| (i.to_string(), RefOr::Item(Schema { data: schema_data(), kind })))
+            .collect()
+    }
+
+    fn external_docs() -> ExternalDocumentation {
+        ExternalDocumentation { description: s(), url: "https://example.com".to_string(), extensions: IndexMap::new() }
+    }
+
+    fn server() -> Server {
+        let variable = ServerVariable { enumeration: vec!["a".to_string()], default: "a".to_string(), description: s(), extensions: IndexMap::new() };
+        Server { url: "https://{a}.example.com".to_string(), description: s(), variables: Some(map(variable)), extensions: IndexMap::new() }
+    }
+
+    fn example() -> Example {
+        Example { summary: s(), description: s(), value: Some(json!(1)), external_value: s(), extensions: IndexMap::new() }
+    }
+
+    fn header(format: ParameterSchemaOrContent) -> Header {
+        Header {
+            description: s(),
+            style: HeaderStyle::Simple,
+            required: true,
+            deprecated: Some(true),
+            format,
+            example: Some(json!(1)),
+            examples: map(RefOr::Item(example())),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn media_type() -> MediaType {
+        let encoding = Encoding {
+            content_type: s(),
+            headers: map(RefOr::Item(header(ParameterSchemaOrContent::Schema(*sub())))),
+            style: Some(QueryStyle::Form),
+            explode: Some(true),
+            allow_reserved: true,
+            extensions: IndexMap::new(),
+        };
+        MediaType {
+            schema: Some(*sub()),
+            example: Some(json!(1)),
+            examples: map(RefOr::Item(example())),
+            encoding: map(encoding),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn parameter(format: ParameterSchemaOrContent, kind: ParameterKind) -> Parameter {
+        let data = ParameterData {
+            name: "a".to_string(),
+            description: s(),
+            required: true,
+            deprecated: Some(true),
+            format,
+            example: Some(json!(1)),
+            examples: map(RefOr::Item(example())),
+            explode: Some(true),
+            extensions: IndexMap::new(),
+        };
+        Parameter { data, kind }
+    }
+
+    fn link(operation: LinkOperation) -> Link {
+        Link {
+            description: s(),
+            operation,
+            request_body: Some(json!(1)),
+            parameters: map(json!(1)),
+            server: Some(server()),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn response() -> Response {
+        Response {
+            description: "a".to_string(),
+            headers: map(RefOr::Item(header(ParameterSchemaOrContent::Content(map(media_type()))))),
+            content: map(media_type()),
+            links: IndexMap::from([
+                ("a".to_string(), RefOr::Item(link(LinkOperation::OperationId("a".to_string())))),
+                ("b".to_string(), RefOr::Item(link(LinkOperation::OperationRef("#/paths/~1a/get".to_string())))),
+            ]),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn request_body() -> RequestBody {
+        RequestBody { description: s(), content: map(media_type()), required: true, extensions: IndexMap::new() }
+    }
+
+    fn operation(callbacks: IndexMap<String, RefOr<Callback>>) -> Operation {
+        let query = ParameterKind::Query { allow_reserved: true, style: QueryStyle::Form, allow_empty_value: Some(true) };
+        Operation {
+            tags: vec!["a".to_string()],
+            summary: s(),
+            description: s(),
+            external_docs: Some(external_docs()),
+            operation_id: s(),
+            parameters: vec![
+                RefOr::Item(parameter(ParameterSchemaOrContent::Schema(*sub()), query)),
+                RefOr::Item(parameter(ParameterSchemaOrContent::Content(map(media_type())), ParameterKind::Header { style: HeaderStyle::Simple })),
+            ],
+            request_body: Some(RefOr::Item(request_body())),
+            responses: Responses {
+                default: Some(RefOr::Item(response())),
+                responses: IndexMap::from([(StatusCode::Code(200), RefOr::Item(response()))]),
+                extensions: IndexMap::new(),
+            },
+            callbacks,
+            deprecated: true,
+            security: Some(vec![map(vec!["a".to_string()])]),
+            servers: vec![server()],
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn path_item(callbacks: IndexMap<String, RefOr<Callback>>) -> PathItem {
+        let op = || Some(operation(callbacks.clone()));
+        PathItem {
+            summary: s(),
+            description: s(),
+            get: op(),
+            put: op(),
+            post: op(),
+            delete: op(),
+            options: op(),
+            head: op(),
+            patch: op(),
+            trace: op(),
+            additional_operations: IndexMap::from([("QUERY".to_string(), operation(callbacks.clone()))]),
+            servers: vec![server()],
+            parameters: vec![RefOr::Item(parameter(ParameterSchemaOrContent::Schema(*sub()), ParameterKind::Path { style: PathStyle::Simple }))],
+            extensions: IndexMap::new(),
+        }
+    }
+
+    fn security_schemes() -> RefOrMap<SecurityScheme> {
+        let scopes = map("a".to_string());
+        let flows = OAuth2Flows {
+            implicit: Some(ImplicitOAuth2Flow { authorization_url: "a".to_string(), refresh_url: s(), scopes: scopes.clone() }),
+            password: Some(OAuth2Flow { refresh_url: s(), token_url: "a".to_string(), scopes: scopes.clone() }),
+            client_credentials: Some(OAuth2Flow { refresh_url: s(), token_url: "a".to_string(), scopes: scopes.clone() }),
+            authorization_code: Some(AuthCodeOAuth2Flow { authorization_url: "a".to_string(), token_url: "a".to_string(), refresh_url: s(), scopes }),
+        };
+        vec![
+            SecurityScheme::APIKey { location: APIKeyLocation::Header, name: "a".to_string(), description: s() },
+            SecurityScheme::HTTP { scheme: "bearer".to_string(), bearer_format: s(), description: s() },
+            SecurityScheme::OAuth2 { flows, description: s() },
+            SecurityScheme::OpenIDConnect { open_id_connect_url: "a".to_string(), description: s() },
+            SecurityScheme::MutualTLS { description: s() },
+        ].into_iter().enumerate().map(|(i, scheme)| (i.to_string(), RefOr::Item(scheme))).collect()
+    }
+
+    #[test]
+    fn test_model_fields_known() {
+        let callbacks = map(RefOr::Item(map(path_item(IndexMap::new()))));
+        let spec = OpenAPI {
+            openapi: "3.0.3".to_string(),
+            info: Info {
+                title: "t".to_string(),
+                summary: s(),
+                description: s(),
+                terms_of_service: s(),
+                contact: Some(Contact { name: s(), url: s(), email: s(), extensions: IndexMap::new() }),
+                license: Some(License { name: "a".to_string(), identifier: s(), url: s(), extensions: IndexMap::new() }),
+                version: "1".to_string(),
+                extensions: IndexMap::new(),
+            },
+            json_schema_dialect: s(),
+            servers: vec![server()],
+            paths: Paths {
+                paths: IndexMap::from([("/a".to_string(), RefOr::Item(path_item(callbacks.clone())))]),
+                extensions: IndexMap::new(),
+            },
+            webhooks: map(RefOr::Item(path_item(IndexMap::new()))),
+            components: Components {
+                security_schemes: security_schemes(),
+                responses: ref_map(response()),
+                parameters: ref_map(parameter(ParameterSchemaOrContent::Schema(*sub()), ParameterKind::Cookie { style: CookieStyle::Form })),
+                examples: ref_map(example()),
+                request_bodies: ref_map(request_body()),
+                headers: ref_map(header(ParameterSchemaOrContent::Schema(*sub()))),
+                schemas: schemas(),
+                links: ref_map(link(LinkOperation::OperationId("a".to_string()))),
+                callbacks: ref_map(map(path_item(IndexMap::new()))),
+                path_items: ref_map(path_item(callbacks)),
+                extensions: IndexMap::new(),
+            },
+            security: vec![map(vec![])],
+            tags: vec![Tag { name: "a".to_string(), description: s(), external_docs: Some(external_docs()), extensions: IndexMap::new() }],
+            external_docs: Some(external_docs()),
+            extensions: IndexMap::new(),
+        };
+        let document = serde_json::to_value(&spec).unwrap();
+        let unknown: Vec<String> = find_unknown_fields(&document).into_iter().map(|e| e.to_string()).collect();
+        assert!(unknown.is_empty(), "{:#?}", unknown);
+    }
+}
@@ -18,6 +18,17 @@ pub const fn is_false(v: &bool) -> bool {
     !(*v)
 }
 
+/// Use this as a serde deserialize_with attribute (together with `default`) for
+/// `Option<T>` fields where an explicit `null` must be kept as `Some(null)`
+/// rather than collapsed into `None`.
+pub(crate) fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
 pub(crate) fn deserialize_extensions<'de, D>(
     deserializer: D,
 ) -> Result<IndexMap<String, serde_json::Value>, D::Error>
@@ -67,4 +78,115 @@ where
 
         Ok(ret)
     }
-}
\ No newline at end of file
+}
+
+/// `snake_case` for an identifier written in any case, e.g. `get_pet_by_id` for
+/// `getPetById`, `GetPetByID` or `get-pet-by-id`.
+pub(crate) fn snake_case(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            continue;
+        }
+        if c.is_uppercase() && i > 0 && !out.is_empty() && !out.ends_with('_') {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out.trim_end_matches('_').to_string()
+}
+
+/// `PascalCase` for words separated by anything but letters and digits, e.g.
+/// `FullName` for `full-name` or `full_name`. The rest of each word is kept.
+pub(crate) fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase()).into_iter().chain(chars)
+        })
+        .collect()
+}
+
+/// `base`, or if `taken` says it is, the first of `base2`, `base3`, ... that isn't.
+pub(crate) fn unique_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    unique_name_with(base, |n| format!("{}{}", base, n), taken)
+}
+
+/// `base`, or if `taken` says it is, the first of `numbered(2)`, `numbered(3)`,
+/// ... that isn't.
+pub(crate) fn unique_name_with(base: &str, numbered: impl Fn(usize) -> String, taken: impl Fn(&str) -> bool) -> String {
+    std::iter::once(base.to_string())
+        .chain((2..).map(numbered))
+        .find(|name| !taken(name))
+        .unwrap()
+}
+
+/// A map key as the deserializer hands it out, borrowed from the input when it
+/// can be, for visitors that look at keys before passing them on.
+pub(crate) enum Key<'de> {
+    Borrowed(&'de str),
+    Owned(String),
+}
+
+impl Key<'_> {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Key::Borrowed(key) => key,
+            Key::Owned(key) => key,
+        }
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        match self {
+            Key::Borrowed(key) => key.to_string(),
+            Key::Owned(key) => key,
+        }
+    }
+}
+
+impl<'de> Key<'de> {
+    /// Passes the key on to `seed`, as the deserializer would have.
+    pub(crate) fn deserialize_into<S: serde::de::DeserializeSeed<'de>, E: serde::de::Error>(self, seed: S) -> Result<S::Value, E> {
+        use serde::de::value::{BorrowedStrDeserializer, StringDeserializer};
+        match self {
+            Key::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Key::Owned(key) => seed.deserialize(StringDeserializer::new(key)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a string key")
+            }
+
+            fn visit_borrowed_str<E>(self, key: &'de str) -> Result<Key<'de>, E> {
+                Ok(Key::Borrowed(key))
+            }
+
+            fn visit_str<E>(self, key: &str) -> Result<Key<'de>, E> {
+                Ok(Key::Owned(key.to_string()))
+            }
+
+            fn visit_string<E>(self, key: String) -> Result<Key<'de>, E> {
+                Ok(Key::Owned(key))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
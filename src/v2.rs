@@ -1,4 +1,5 @@
 mod schema;
 mod upgrade;
 
-pub use schema::*;
\ No newline at end of file
+pub use schema::*;
+pub use upgrade::ConvertError;
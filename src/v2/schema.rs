@@ -3,9 +3,10 @@ use indexmap::IndexMap;
 
 // http://json.schemastore.org/swagger-2.0
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Scheme {
+    #[default]
     Http,
     Https,
     Ws,
@@ -23,11 +24,8 @@ impl Scheme {
     }
 }
 
-impl Default for Scheme {
-    fn default() -> Self {
-        Scheme::Http
-    }
-}
+/// top level document
+pub type Swagger = OpenAPI;
 
 /// top level document
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
@@ -185,22 +183,18 @@ pub struct Operation {
 /// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/2.0.md#securityRequirementObject
 pub type SecurityRequirement = IndexMap<String, Vec<String>>;
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ParameterLocation {
+    #[default]
     Query,
     Header,
     Path,
+    #[serde(rename = "formData")]
     FormData,
     Body,
 }
 
-impl Default for ParameterLocation {
-    fn default() -> Self {
-        ParameterLocation::Query
-    }
-}
-
 impl Parameter {
     pub fn valid_v3_location(&self) -> bool {
         use ParameterLocation::*;
@@ -325,6 +319,7 @@ pub struct Schema {
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum ReferenceOrSchema {
     Reference {
         #[serde(rename = "$ref")]
@@ -343,7 +338,7 @@ mod tests {
     fn security_api_deserializes() {
         let json = r#"{"type":"apiKey", "name":"foo", "in": "query"}"#;
         assert_eq!(
-            serde_yaml::from_str::<Security>(&json).unwrap(),
+            serde_yaml::from_str::<Security>(json).unwrap(),
             Security::ApiKey {
                 name: "foo".into(),
                 location: serde_json::from_str("\"query\"").unwrap(),
@@ -370,7 +365,7 @@ mod tests {
     fn security_basic_deserializes() {
         let json = r#"{"type":"basic"}"#;
         assert_eq!(
-            serde_yaml::from_str::<Security>(&json).unwrap(),
+            serde_yaml::from_str::<Security>(json).unwrap(),
             Security::Basic { description: None }
         );
     }
@@ -390,12 +385,12 @@ mod tests {
         let mut scopes = IndexMap::new();
         scopes.insert("foo".into(), "bar".into());
         assert_eq!(
-            serde_yaml::from_str::<Security>(&json).unwrap(),
+            serde_yaml::from_str::<Security>(json).unwrap(),
             Security::Oauth2 {
                 flow: Flow::Implicit,
                 authorization_url: "foo/bar".into(),
                 token_url: None,
-                scopes: scopes,
+                scopes,
                 description: None,
             }
         );
@@ -423,7 +418,7 @@ mod tests {
     fn parameter_or_ref_deserializes_ref() {
         let json = r#"{"$ref":"foo/bar"}"#;
         assert_eq!(
-            serde_yaml::from_str::<ReferenceOrSchema>(&json).unwrap(),
+            serde_yaml::from_str::<ReferenceOrSchema>(json).unwrap(),
             ReferenceOrSchema::Reference {
                 reference: "foo/bar".into()
             }
@@ -1,186 +1,229 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
 use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+
 use crate as v3;
-use crate::{Parameter, StatusCode};
 use super::schema as v2;
 
-trait TryRemove<T> {
-    fn try_remove(&mut self, i: usize) -> Option<T>;
+/// Why a Swagger 2.0 document could not be converted, from [v3::OpenAPI::from_v2].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The document's `swagger` isn't `2.0`.
+    Version(String),
+    /// A schema or parameter `type` Swagger 2.0 doesn't define.
+    UnknownType(String),
+    /// A parameter, named here, that isn't in the body but has no `type`.
+    MissingType(String),
+    /// A `body` or `formData` parameter, named here, where only parameters
+    /// that stay parameters in OpenAPI 3 can go.
+    BodyParameter(String),
+    /// A response key that is neither `default` nor a status code.
+    StatusCode(String),
+    /// An OAuth2 flow other than `implicit` without a `tokenUrl`.
+    MissingTokenUrl,
 }
 
-impl<T> TryRemove<T> for Vec<T> {
-    fn try_remove(&mut self, i: usize) -> Option<T> {
-        self.get(i)?;
-        Some(self.remove(i))
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Version(v) => write!(f, "expected a Swagger 2.0 document, found version {:?}", v),
+            ConvertError::UnknownType(t) => write!(f, "unknown type: {}", t),
+            ConvertError::MissingType(name) => write!(f, "parameter {} has no type", name),
+            ConvertError::BodyParameter(name) => write!(f, "parameter {} is in the body, which only a request body can describe", name),
+            ConvertError::StatusCode(s) => write!(f, "invalid status code: {}", s),
+            ConvertError::MissingTokenUrl => write!(f, "OAuth2 flow has no tokenUrl"),
+        }
     }
 }
 
-impl Into<v3::OpenAPI> for v2::OpenAPI {
-    fn into(self) -> v3::OpenAPI {
-        let v2::OpenAPI {
-            swagger: _,
-            info,
-            host,
-            base_path,
-            schemes,
-            consumes: _,
-            produces: _,
-            paths,
-            definitions,
-            parameters,
-            responses,
-            security_definitions,
-            security,
-            tags,
-            external_docs,
-        } = self;
-        let mut components = v3::Components::default();
-
-        components.schemas = definitions
-            .unwrap_or_default()
-            .into_iter()
-            .map(|(k, v)| (k, v3::RefOr::Item(v.into())))
-            .collect();
-
-        components.parameters = parameters
-            .unwrap_or_default()
-            .into_iter()
-            .filter_map(|(k, v)| {
-                let v: v3::RefOr<v3::Parameter> = v.try_into().ok()?;
-                Some((k, v))
-            })
-            .collect();
-
-        components.responses = responses
-            .unwrap_or_default()
-            .into_iter()
-            .map(|(k, v)| (k, v.into()))
-            .collect();
-
-        components.security_schemes = security_definitions
-            .unwrap_or_default()
-            .into_iter()
-            .map(|(k, v)| (k, v.into()))
-            .collect();
-
-        v3::OpenAPI {
-            openapi: "3.0.3".to_string(),
-            info: info.into(),
-            servers: host
-                .map(|h| {
-                    let scheme = schemes.and_then(|mut s| if s.len() >= 1 {
-                        Some(s.remove(0))
-                    } else {
-                        None
-                    })
-                        .map(|s| s.as_str())
-                        .unwrap_or("http");
-                    let url = format!("{}://{}{}", scheme, h, base_path.unwrap_or_default());
-                    vec![v3::Server {
-                        url,
-                        ..v3::Server::default()
-                    }]
-                }).unwrap_or_default(),
-            paths: paths.into(),
-            components,
-            security: security.unwrap_or_default(),
-            tags: tags.unwrap_or_default()
-                .into_iter()
-                .map(|t| t.into())
-                .collect(),
-            external_docs: external_docs
-                .and_then(|mut e| e.try_remove(0))
-                .map(|e| e.into()),
-            extensions: Default::default(),
+impl std::error::Error for ConvertError {}
+
+impl v3::OpenAPI {
+    /// Converts a Swagger 2.0 document: `definitions` become
+    /// `components.schemas`, `host`, `basePath` and `schemes` a server per
+    /// scheme, `body` and `formData` parameters a `requestBody` of the media types
+    /// the operation `consumes`, responses content of those it `produces`, and
+    /// `securityDefinitions` `components.securitySchemes`. References are
+    /// rewritten to point into `components`.
+    pub fn from_v2(swagger: v2::Swagger) -> Result<v3::OpenAPI, ConvertError> {
+        if swagger.swagger != "2.0" {
+            return Err(ConvertError::Version(swagger.swagger));
         }
+        convert(swagger)
     }
 }
 
-impl Into<v3::Paths> for IndexMap<String, v2::PathItem> {
-    fn into(self) -> v3::Paths {
-        v3::Paths {
-            paths: self.into_iter().map(|(k, v)| (k, v.into())).collect(),
-            extensions: Default::default(),
-        }
+/// Panics where [v3::OpenAPI::from_v2] would fail, but accepts any `swagger`
+/// version.
+impl From<v2::OpenAPI> for v3::OpenAPI {
+    fn from(swagger: v2::OpenAPI) -> Self {
+        convert(swagger).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
-impl Into<v3::RefOr<v3::PathItem>> for v2::PathItem {
-    fn into(self) -> v3::RefOr<v3::PathItem> {
-        let v2::PathItem {
-            get,
-            put,
-            post,
-            delete,
-            options,
-            head,
-            patch,
-            parameters,
-        } = self;
-        v3::RefOr::Item(v3::PathItem {
-            summary: None,
-            description: None,
-            get: get.map(|op| op.into()),
-            put: put.map(|op| op.into()),
-            post: post.map(|op| op.into()),
-            delete: delete.map(|op| op.into()),
-            options: options.map(|op| op.into()),
-            head: head.map(|op| op.into()),
-            patch: patch.map(|op| op.into()),
-            trace: None,
-            servers: vec![],
-            parameters: parameters
-                .unwrap_or_default()
-                .into_iter()
-                .flat_map(|p| p.try_into().ok())
-                .collect(),
+/// The media types of the document, which operations may override.
+struct MediaTypes<'a> {
+    consumes: &'a [String],
+    produces: &'a [String],
+}
+
+fn convert(swagger: v2::OpenAPI) -> Result<v3::OpenAPI, ConvertError> {
+    let v2::OpenAPI {
+        swagger: _,
+        info,
+        host,
+        base_path,
+        schemes,
+        consumes,
+        produces,
+        paths,
+        definitions,
+        parameters,
+        responses,
+        security_definitions,
+        security,
+        tags,
+        external_docs,
+    } = swagger;
+    let consumes = consumes.unwrap_or_default();
+    let produces = produces.unwrap_or_default();
+    let media = MediaTypes { consumes: &consumes, produces: &produces };
+    let mut components = v3::Components::default();
+
+    for (name, schema) in definitions.unwrap_or_default() {
+        components.schemas.insert(name, v3::RefOr::Item(schema.try_into()?));
+    }
+    for (name, parameter) in parameters.unwrap_or_default() {
+        if parameter.valid_v3_location() {
+            components.parameters.insert(name, v3::RefOr::try_from(parameter)?);
+        } else {
+            components.request_bodies.insert(name, v3::RefOr::Item(request_body(vec![parameter], media.consumes)?));
+        }
+    }
+    for (name, response) in responses.unwrap_or_default() {
+        components.responses.insert(name, convert_response(response, media.produces)?);
+    }
+    for (name, scheme) in security_definitions.unwrap_or_default() {
+        components.security_schemes.insert(name, v3::RefOr::Item(scheme.try_into()?));
+    }
+
+    let mut converted = IndexMap::new();
+    for (path, item) in paths {
+        converted.insert(path, v3::RefOr::Item(path_item(item, &media)?));
+    }
+    Ok(v3::OpenAPI {
+        openapi: "3.0.3".to_string(),
+        info: info.into(),
+        json_schema_dialect: None,
+        servers: servers(host, base_path, schemes),
+        paths: v3::Paths {
+            paths: converted,
             extensions: Default::default(),
+        },
+        webhooks: Default::default(),
+        components,
+        security: security.unwrap_or_default(),
+        tags: tags.unwrap_or_default().into_iter().map(Into::into).collect(),
+        external_docs: external_docs.and_then(|e| e.into_iter().next()).map(Into::into),
+        extensions: Default::default(),
+    })
+}
+
+/// A server per scheme, `http` if none are listed, at `host` and `basePath`.
+/// Without a host, the base path alone is the URL, relative to the document.
+fn servers(host: Option<String>, base_path: Option<String>, schemes: Option<Vec<v2::Scheme>>) -> Vec<v3::Server> {
+    let base_path = base_path.unwrap_or_default();
+    let Some(host) = host else {
+        if base_path.is_empty() {
+            return vec![];
+        }
+        return vec![v3::Server { url: base_path, ..v3::Server::default() }];
+    };
+    let schemes = schemes.filter(|s| !s.is_empty()).unwrap_or_else(|| vec![v2::Scheme::Http]);
+    schemes.iter()
+        .map(|scheme| v3::Server {
+            url: format!("{}://{}{}", scheme.as_str(), host, base_path),
+            ..v3::Server::default()
         })
-    }
+        .collect()
+}
+
+fn path_item(item: v2::PathItem, media: &MediaTypes) -> Result<v3::PathItem, ConvertError> {
+    let v2::PathItem {
+        get,
+        put,
+        post,
+        delete,
+        options,
+        head,
+        patch,
+        parameters,
+    } = item;
+    // Body and form parameters of the path go into the body of each operation.
+    let (parameters, body) = split_params_into_params_and_body(parameters);
+    let convert_operation = |op: Option<v2::Operation>| op.map(|op| operation(op, &body, media)).transpose();
+    Ok(v3::PathItem {
+        summary: None,
+        description: None,
+        get: convert_operation(get)?,
+        put: convert_operation(put)?,
+        post: convert_operation(post)?,
+        delete: convert_operation(delete)?,
+        options: convert_operation(options)?,
+        head: convert_operation(head)?,
+        patch: convert_operation(patch)?,
+        trace: None,
+        additional_operations: Default::default(),
+        servers: vec![],
+        parameters: parameters.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?,
+        extensions: Default::default(),
+    })
 }
 
 /// Change something like "#/definitions/User" to "#/components/schemas/User"
 fn rewrite_ref(s: &str) -> String {
     s.replace("#/definitions/", "#/components/schemas/")
+        .replace("#/parameters/", "#/components/parameters/")
+        .replace("#/responses/", "#/components/responses/")
 }
 
-fn build_schema_kind(type_: &str, format: Option<String>) -> v3::SchemaKind {
-    match type_ {
+/// A format of the v3 schema kind, through its JSON form.
+fn format<T: DeserializeOwned + Default>(format: Option<String>) -> T {
+    serde_json::from_value(format.into()).unwrap_or_default()
+}
+
+fn build_schema_kind(type_: &str, format: Option<String>) -> Result<v3::SchemaKind, ConvertError> {
+    Ok(match type_ {
         "string" => v3::SchemaKind::Type(v3::Type::String(v3::StringType {
-            format: {
-                let s = serde_json::to_string(&format).unwrap();
-                serde_json::from_str(&s).unwrap()
-            },
+            format: self::format(format),
+            ..v3::StringType::default()
+        })),
+        // Files are uploaded as binary strings in OpenAPI 3.
+        "file" => v3::SchemaKind::Type(v3::Type::String(v3::StringType {
+            format: v3::VariantOrUnknownOrEmpty::Item(v3::StringFormat::Binary),
             ..v3::StringType::default()
         })),
         "number" => v3::SchemaKind::Type(v3::Type::Number(v3::NumberType {
-            format: {
-                let s = serde_json::to_string(&format).unwrap();
-                serde_json::from_str(&s).unwrap()
-            },
+            format: self::format(format),
             ..v3::NumberType::default()
         })),
         "integer" => v3::SchemaKind::Type(v3::Type::Integer(v3::IntegerType {
-            format: {
-                let s = serde_json::to_string(&format).unwrap();
-                serde_json::from_str(&s).unwrap()
-            },
+            format: self::format(format),
             ..v3::IntegerType::default()
         })),
         "boolean" => v3::SchemaKind::Type(v3::Type::Boolean {}),
-        "array" => v3::SchemaKind::Type(v3::Type::Array(v3::ArrayType {
-            ..v3::ArrayType::default()
-        })),
-        "object" => {
-            let object_type = v3::ObjectType::default();
-            v3::SchemaKind::Type(v3::Type::Object(object_type))
-        }
-        _ => panic!("Unknown schema type: {}", type_),
-    }
+        "array" => v3::SchemaKind::Type(v3::Type::Array(v3::ArrayType::default())),
+        "object" => v3::SchemaKind::Type(v3::Type::Object(v3::ObjectType::default())),
+        _ => return Err(ConvertError::UnknownType(type_.to_string())),
+    })
 }
 
-impl Into<v3::Schema> for v2::Schema {
-    fn into(self) -> v3::Schema {
+impl TryFrom<v2::Schema> for v3::Schema {
+    type Error = ConvertError;
+
+    fn try_from(schema: v2::Schema) -> Result<Self, Self::Error> {
         let v2::Schema {
             description,
             schema_type,
@@ -191,107 +234,97 @@ impl Into<v3::Schema> for v2::Schema {
             properties,
             all_of,
             other,
-        } = self;
+        } = schema;
 
-        let schema_data = v3::SchemaData {
+        let data = v3::SchemaData {
             description,
             extensions: other,
             ..v3::SchemaData::default()
         };
 
         if let Some(all_of) = all_of {
-            return v3::Schema {
-                data: schema_data,
+            return Ok(v3::Schema {
+                data,
                 kind: v3::SchemaKind::AllOf {
-                    all_of: all_of
-                        .into_iter()
-                        .map(|s| s.into())
-                        .collect()
+                    all_of: all_of.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?,
                 },
-            }
+            });
         }
 
         let schema_type = schema_type.unwrap_or_else(|| "object".to_string());
-        let mut schema_kind = build_schema_kind(&schema_type, format);
+        let mut kind = build_schema_kind(&schema_type, format)?;
 
-        match &mut schema_kind {
-            v3::SchemaKind::Type(v3::Type::String(ref mut s)) => {
+        match &mut kind {
+            v3::SchemaKind::Type(v3::Type::String(s)) => {
                 s.enumeration = enum_values.unwrap_or_default();
             }
-            v3::SchemaKind::Type(v3::Type::Object(ref mut o)) => {
-                if let Some(properties) = properties {
-                    o.properties = properties
-                        .into_iter()
-                        .map(|(k, v)| (k, v.into()))
-                        .collect();
+            v3::SchemaKind::Type(v3::Type::Object(o)) => {
+                for (name, property) in properties.unwrap_or_default() {
+                    o.properties.insert(name, v3::RefOr::try_from(property)?);
                 }
                 o.required = required.unwrap_or_default();
             }
-            v3::SchemaKind::Type(v3::Type::Array(ref mut a)) => {
-                a.items = Some({
-                    let item = items.unwrap();
-                    let item = *item;
-                    let item: v3::RefOr<v3::Schema> = item.into();
-                    item.boxed()
-                });
+            v3::SchemaKind::Type(v3::Type::Array(a)) => {
+                a.items = items.map(|item| v3::RefOr::try_from(*item).map(v3::RefOr::boxed)).transpose()?;
             }
             _ => {}
         }
 
-        v3::Schema {
-            data: schema_data,
-            kind: schema_kind,
-        }
+        Ok(v3::Schema { data, kind })
     }
 }
 
-impl TryInto<v3::RefOr<v3::Parameter>> for v2::Parameter {
-    type Error = anyhow::Error;
+impl TryFrom<v2::ReferenceOrSchema> for v3::RefOr<v3::Schema> {
+    type Error = ConvertError;
 
-    fn try_into(self) -> Result<v3::RefOr<v3::Parameter>, Self::Error> {
-        if !self.valid_v3_location() {
-            return Err(anyhow::anyhow!("Invalid location: {}", serde_json::to_string(&self.location).unwrap()));
+    fn try_from(schema: v2::ReferenceOrSchema) -> Result<Self, Self::Error> {
+        Ok(match schema {
+            v2::ReferenceOrSchema::Item(s) => v3::RefOr::Item(s.try_into()?),
+            v2::ReferenceOrSchema::Reference { reference } => v3::RefOr::Reference {
+                reference: rewrite_ref(&reference),
+            },
+        })
+    }
+}
+
+/// The schema a parameter other than a body one describes with its own `type`.
+fn parameter_schema(parameter: &v2::Parameter) -> Result<v3::Schema, ConvertError> {
+    let type_ = parameter.type_.as_deref().ok_or_else(|| ConvertError::MissingType(parameter.name.clone()))?;
+    let mut kind = build_schema_kind(type_, parameter.format.clone())?;
+    if let v3::SchemaKind::Type(v3::Type::Array(a)) = &mut kind {
+        a.items = parameter.items.clone().map(|item| v3::RefOr::try_from(item).map(v3::RefOr::boxed)).transpose()?;
+        a.unique_items = parameter.unique_items.unwrap_or_default();
+    }
+    let data = v3::SchemaData {
+        default: parameter.default.clone(),
+        ..v3::SchemaData::default()
+    };
+    Ok(v3::Schema { data, kind })
+}
+
+impl TryFrom<v2::Parameter> for v3::RefOr<v3::Parameter> {
+    type Error = ConvertError;
+
+    fn try_from(parameter: v2::Parameter) -> Result<Self, Self::Error> {
+        if !parameter.valid_v3_location() {
+            return Err(ConvertError::BodyParameter(parameter.name));
         }
+        let schema = parameter_schema(&parameter)?;
         let v2::Parameter {
             name,
             location,
             description,
             required,
-            schema: _,
-            type_,
-            format,
-            items,
-            default,
-            unique_items,
             collection_format,
-        } = self;
-        let type_ = type_.unwrap();
+            ..
+        } = parameter;
 
-        let mut kind = build_schema_kind(&type_, format);
-        let mut data = v3::SchemaData::default();
-
-        match &mut kind {
-            v3::SchemaKind::Type(v3::Type::Array(ref mut a)) => {
-                a.items = items.map(|item| {
-                    let item: v3::RefOr<v3::Schema> = item.into();
-                    item.boxed()
-                });
-                a.unique_items = unique_items.unwrap_or_default();
-            }
-            _ => {}
-        }
-        data.default = default;
-
-        let mut explode = None;
-        if let Some(collection_format) = collection_format {
-            match collection_format.as_str() {
-                "multi" => explode = Some(true),
-                "csv" => explode = Some(false),
-                _ => {}
-            }
-        }
+        let explode = match collection_format.as_deref() {
+            Some("multi") => Some(true),
+            Some("csv") => Some(false),
+            _ => None,
+        };
 
-        let schema = v3::Schema { data, kind };
         let data = v3::ParameterData {
             name,
             description,
@@ -304,28 +337,20 @@ impl TryInto<v3::RefOr<v3::Parameter>> for v2::Parameter {
             extensions: Default::default(),
         };
         let kind = match location {
-            v2::ParameterLocation::Query => {
-                v3::ParameterKind::Query {
-                    allow_reserved: false,
-                    style: Default::default(),
-                    allow_empty_value: None,
-                }
-            }
-            v2::ParameterLocation::Header => {
-                v3::ParameterKind::Header {
-                    style: Default::default(),
-                }
-            }
-            v2::ParameterLocation::Path => {
-                v3::ParameterKind::Path {
-                    style: Default::default(),
-                }
-            }
-            | v2::ParameterLocation::FormData
-            | v2::ParameterLocation::Body => panic!("Invalid location"),
+            v2::ParameterLocation::Query => v3::ParameterKind::Query {
+                allow_reserved: false,
+                style: Default::default(),
+                allow_empty_value: None,
+            },
+            v2::ParameterLocation::Header => v3::ParameterKind::Header {
+                style: Default::default(),
+            },
+            v2::ParameterLocation::Path => v3::ParameterKind::Path {
+                style: Default::default(),
+            },
+            v2::ParameterLocation::FormData | v2::ParameterLocation::Body => unreachable!("checked above"),
         };
-        let parameter = Parameter { data, kind };
-        Ok(v3::RefOr::Item(parameter))
+        Ok(v3::RefOr::Item(v3::Parameter { data, kind }))
     }
 }
 
@@ -336,120 +361,138 @@ fn split_params_into_params_and_body(params: Option<Vec<v2::Parameter>>) -> (Vec
         .partition(|p| p.valid_v3_location())
 }
 
-impl Into<v3::Operation> for v2::Operation {
-    fn into(self) -> v3::Operation {
-        let v2::Operation {
-            consumes: _,
-            produces: _,
-            schemes: _,
-            tags,
-            summary,
-            description,
-            operation_id,
-            parameters,
-            mut responses,
-            security,
-        } = self;
-        let (parameters, body) = split_params_into_params_and_body(parameters);
-        let body = body.into();
-
-        let responses = {
-            let mut r = v3::Responses::default();
-            r.default = responses.swap_remove("default").map(|r| r.into());
-            r.responses = responses
-                .into_iter()
-                .map(|(k, v)| (
-                    StatusCode::Code(k.parse::<u16>().expect(&format!("Invalid status code: {}", k))),
-                    v.into()
-                ))
-                .collect();
-            r
-        };
-        v3::Operation {
-            tags: tags.unwrap_or_default(),
-            summary,
-            description,
-            external_docs: None,
-            operation_id,
-            parameters: parameters
-                .into_iter()
-                .flat_map(|p| p.try_into().ok())
-                .collect(),
-            request_body: Some(v3::RefOr::Item(body)),
-            responses,
-            deprecated: false,
-            security,
-            servers: vec![],
-            extensions: Default::default(),
+/// `inherited` are the body and form parameters of the path, which those of the
+/// operation override by name.
+fn operation(op: v2::Operation, inherited: &[v2::Parameter], media: &MediaTypes) -> Result<v3::Operation, ConvertError> {
+    let v2::Operation {
+        consumes,
+        produces,
+        schemes: _,
+        tags,
+        summary,
+        description,
+        operation_id,
+        parameters,
+        responses,
+        security,
+    } = op;
+    let (parameters, mut body) = split_params_into_params_and_body(parameters);
+    let inherited: Vec<_> = inherited.iter().filter(|p| !body.iter().any(|b| b.name == p.name)).cloned().collect();
+    body.extend(inherited);
+    let consumes = consumes.as_deref().unwrap_or(media.consumes);
+    let produces = produces.as_deref().unwrap_or(media.produces);
+
+    let mut converted = v3::Responses::default();
+    for (status, response) in responses {
+        let response = convert_response(response, produces)?;
+        if status == "default" {
+            converted.default = Some(response);
+        } else {
+            let code = status.parse().map_err(|_| ConvertError::StatusCode(status.clone()))?;
+            converted.responses.insert(v3::StatusCode::Code(code), response);
         }
     }
+    let request_body = if body.is_empty() {
+        None
+    } else {
+        Some(v3::RefOr::Item(request_body(body, consumes)?))
+    };
+    Ok(v3::Operation {
+        tags: tags.unwrap_or_default(),
+        summary,
+        description,
+        external_docs: None,
+        operation_id,
+        parameters: parameters.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?,
+        request_body,
+        responses: converted,
+        callbacks: Default::default(),
+        deprecated: false,
+        security,
+        servers: vec![],
+        extensions: Default::default(),
+    })
 }
 
-impl Into<v3::RefOr<v3::Schema>> for v2::ReferenceOrSchema {
-    fn into(self) -> v3::RefOr<v3::Schema> {
-        match self {
-            v2::ReferenceOrSchema::Item(s) => v3::RefOr::Item(s.into()),
-            v2::ReferenceOrSchema::Reference { reference } => v3::RefOr::Reference {
-                reference: rewrite_ref(&reference)
-            }
-        }
+/// JSON, unless the document or operation lists media types.
+fn media_types(listed: &[String]) -> Vec<String> {
+    if listed.is_empty() {
+        vec!["application/json".to_string()]
+    } else {
+        listed.to_vec()
     }
 }
 
-impl Into<v3::RequestBody> for Vec<v2::Parameter> {
-    fn into(self) -> v3::RequestBody {
-        let mut object = v3::ObjectType::default();
-        for param in self {
-            let v2::Parameter {
-                name,
-                location,
-                description: _,
-                required,
-                schema,
-                type_: _,
-                format: _,
-                items: _,
-                default: _,
-                unique_items: _,
-                collection_format: _,
-            } = param;
-            assert!(location == v2::ParameterLocation::Body);
-            if required.unwrap_or_default() {
-                object.required.push(name.clone());
-            }
-            let schema = match schema {
-                Some(s) => s.into(),
+/// The request body of a `body` parameter, or of `formData` ones, which become
+/// the properties of an object sent as a form: `multipart/form-data` if one is
+/// a file, unless the operation consumes a form media type.
+fn request_body(parameters: Vec<v2::Parameter>, consumes: &[String]) -> Result<v3::RequestBody, ConvertError> {
+    let mut form = v3::ObjectType::default();
+    let mut file = false;
+    let mut required = false;
+    for parameter in parameters {
+        if parameter.location == v2::ParameterLocation::Body {
+            let schema = match parameter.schema {
+                Some(s) => s.try_into()?,
                 None => v3::RefOr::Item(v3::Schema::new_any()),
             };
-            object.properties.insert(name, schema);
+            let content = media_types(consumes).into_iter()
+                .map(|media_type| (media_type, v3::MediaType {
+                    schema: Some(schema.clone()),
+                    ..v3::MediaType::default()
+                }))
+                .collect();
+            return Ok(v3::RequestBody {
+                description: parameter.description,
+                content,
+                required: parameter.required.unwrap_or_default(),
+                extensions: Default::default(),
+            });
         }
-
-        let mut content = IndexMap::new();
-        content.insert(
-            "application/json".to_string(),
-            v3::MediaType {
-                schema: Some(v3::RefOr::Item(v3::Schema {
-                    data: v3::SchemaData::default(),
-                    kind: v3::SchemaKind::Type(v3::Type::Object(object)),
-                })),
-                ..v3::MediaType::default()
-            },
-        );
-        v3::RequestBody {
-            description: None,
-            content,
-            required: true,
-            extensions: Default::default(),
+        let mut schema = parameter_schema(&parameter)?;
+        schema.data.description = parameter.description;
+        file |= parameter.type_.as_deref() == Some("file");
+        if parameter.required.unwrap_or_default() {
+            required = true;
+            form.required.push(parameter.name.clone());
         }
+        form.properties.insert(parameter.name, v3::RefOr::Item(schema));
     }
+
+    let forms: Vec<String> = consumes.iter()
+        .filter(|m| *m == "application/x-www-form-urlencoded" || *m == "multipart/form-data")
+        .cloned()
+        .collect();
+    let forms = if !forms.is_empty() {
+        forms
+    } else if file {
+        vec!["multipart/form-data".to_string()]
+    } else {
+        vec!["application/x-www-form-urlencoded".to_string()]
+    };
+    let schema = v3::Schema {
+        data: v3::SchemaData::default(),
+        kind: v3::SchemaKind::Type(v3::Type::Object(form)),
+    };
+    Ok(v3::RequestBody {
+        description: None,
+        content: forms.into_iter()
+            .map(|media_type| (media_type, v3::MediaType {
+                schema: Some(v3::RefOr::Item(schema.clone())),
+                ..v3::MediaType::default()
+            }))
+            .collect(),
+        required,
+        extensions: Default::default(),
+    })
 }
 
-impl Into<v3::ExternalDocumentation> for v2::ExternalDoc {
-    fn into(self) -> v3::ExternalDocumentation {
+impl From<v2::ExternalDoc> for v3::ExternalDocumentation {
+    fn from(doc: v2::ExternalDoc) -> Self {
         let v2::ExternalDoc {
             description,
             url,
-        } = self;
+        } = doc;
         v3::ExternalDocumentation {
             description,
             url,
@@ -458,26 +501,26 @@ impl Into<v3::ExternalDocumentation> for v2::ExternalDoc {
     }
 }
 
-impl Into<v3::Tag> for v2::Tag {
-    fn into(self) -> v3::Tag {
+impl From<v2::Tag> for v3::Tag {
+    fn from(tag: v2::Tag) -> Self {
         let v2::Tag {
             name,
             description,
             external_docs,
-        } = self;
+        } = tag;
         v3::Tag {
             name,
             description,
             external_docs: external_docs
-                .and_then(|mut e| e.try_remove(0))
-                .map(|e| e.into()),
+                .and_then(|e| e.into_iter().next())
+                .map(Into::into),
             extensions: Default::default(),
         }
     }
 }
 
-impl Into<v3::Info> for v2::Info {
-    fn into(self) -> v3::Info {
+impl From<v2::Info> for v3::Info {
+    fn from(info: v2::Info) -> Self {
         let v2::Info {
             title,
             description,
@@ -485,26 +528,27 @@ impl Into<v3::Info> for v2::Info {
             contact,
             license,
             version,
-        } = self;
+        } = info;
         v3::Info {
             title: title.unwrap_or_default(),
+            summary: None,
             description,
             terms_of_service,
-            contact: contact.map(|c| c.into()),
-            license: license.map(|l| l.into()),
+            contact: contact.map(Into::into),
+            license: license.map(Into::into),
             version: version.unwrap_or_else(|| "0.1.0".to_string()),
             extensions: Default::default(),
         }
     }
 }
 
-impl Into<v3::Contact> for v2::Contact {
-    fn into(self) -> v3::Contact {
+impl From<v2::Contact> for v3::Contact {
+    fn from(contact: v2::Contact) -> Self {
         let v2::Contact {
             name,
             url,
             email,
-        } = self;
+        } = contact;
         v3::Contact {
             name,
             url,
@@ -514,115 +558,106 @@ impl Into<v3::Contact> for v2::Contact {
     }
 }
 
-impl Into<v3::License> for v2::License {
-    fn into(self) -> v3::License {
+impl From<v2::License> for v3::License {
+    fn from(license: v2::License) -> Self {
         let v2::License {
             name,
             url,
-        } = self;
+        } = license;
         v3::License {
             name: name.unwrap_or_default(),
+            identifier: None,
             url,
             extensions: Default::default(),
         }
     }
 }
 
-impl Into<v3::RefOr<v3::SecurityScheme>> for v2::Security {
-    fn into(self) -> v3::RefOr<v3::SecurityScheme> {
-        match self {
+impl TryFrom<v2::Security> for v3::SecurityScheme {
+    type Error = ConvertError;
+
+    fn try_from(security: v2::Security) -> Result<Self, Self::Error> {
+        Ok(match security {
             v2::Security::ApiKey { name, location, description } => {
                 let location = match location {
                     v2::ApiKeyLocation::Query => v3::APIKeyLocation::Query,
                     v2::ApiKeyLocation::Header => v3::APIKeyLocation::Header,
                 };
-                v3::RefOr::Item(v3::SecurityScheme::APIKey {
+                v3::SecurityScheme::APIKey {
                     location,
                     name,
                     description,
-                })
-            }
-            v2::Security::Basic { description } => {
-                v3::RefOr::Item(v3::SecurityScheme::HTTP {
-                    scheme: "basic".to_string(),
-                    bearer_format: None,
-                    description,
-                })
+                }
             }
+            v2::Security::Basic { description } => v3::SecurityScheme::HTTP {
+                scheme: "basic".to_string(),
+                bearer_format: None,
+                description,
+            },
             v2::Security::Oauth2 { flow, authorization_url, token_url, scopes, description } => {
-                let mut implicit = None;
-                let mut password = None;
-                let mut client_credentials = None;
-                let mut authorization_code = None;
+                let mut flows = v3::OAuth2Flows::default();
+                let token_url = token_url.ok_or(ConvertError::MissingTokenUrl);
                 match flow {
                     v2::Flow::AccessCode => {
-                        authorization_code = Some(v3::AuthCodeOAuth2Flow {
+                        flows.authorization_code = Some(v3::AuthCodeOAuth2Flow {
                             authorization_url,
-                            token_url: token_url.unwrap(),
+                            token_url: token_url?,
                             refresh_url: None,
                             scopes,
                         });
                     }
                     v2::Flow::Application => {
-                        client_credentials = Some(v3::OAuth2Flow {
-                            token_url: token_url.unwrap(),
+                        flows.client_credentials = Some(v3::OAuth2Flow {
+                            token_url: token_url?,
                             refresh_url: None,
                             scopes,
                         });
                     }
                     v2::Flow::Implicit => {
-                        implicit = Some(v3::ImplicitOAuth2Flow {
+                        flows.implicit = Some(v3::ImplicitOAuth2Flow {
                             authorization_url,
                             refresh_url: None,
                             scopes,
                         });
                     }
                     v2::Flow::Password => {
-                        password = Some(v3::OAuth2Flow {
-                            token_url: token_url.unwrap(),
+                        flows.password = Some(v3::OAuth2Flow {
+                            token_url: token_url?,
                             refresh_url: None,
                             scopes,
                         });
                     }
                 }
-                let flows = v3::OAuth2Flows {
-                    implicit,
-                    password,
-                    client_credentials,
-                    authorization_code,
-                };
-                v3::RefOr::Item(v3::SecurityScheme::OAuth2 {
+                v3::SecurityScheme::OAuth2 {
                     flows,
                     description,
-                })
+                }
             }
-        }
+        })
     }
 }
 
-impl Into<v3::RefOr<v3::Response>> for v2::Response {
-    fn into(self) -> v3::RefOr<v3::Response> {
-        let v2::Response {
-            description,
-            schema,
-        } = self;
-        let Some(schema) = schema else {
-            return v3::RefOr::Item(v3::Response {
-                description,
-                ..v3::Response::default()
-            });
-        };
-        v3::RefOr::Item(v3::Response {
+/// The response, with its schema as the content of each media type `produces`.
+fn convert_response(response: v2::Response, produces: &[String]) -> Result<v3::RefOr<v3::Response>, ConvertError> {
+    let v2::Response {
+        description,
+        schema,
+    } = response;
+    let Some(schema) = schema else {
+        return Ok(v3::RefOr::Item(v3::Response {
             description,
-            content: {
-                let mut map = IndexMap::new();
-                map.insert("application/json".to_string(), v3::MediaType {
-                    schema: Some(schema.into()),
-                    ..v3::MediaType::default()
-                });
-                map
-            },
             ..v3::Response::default()
-        })
-    }
-}
\ No newline at end of file
+        }));
+    };
+    let schema: v3::RefOr<v3::Schema> = schema.try_into()?;
+    Ok(v3::RefOr::Item(v3::Response {
+        description,
+        content: media_types(produces).into_iter()
+            .map(|media_type| (media_type, v3::MediaType {
+                schema: Some(schema.clone()),
+                ..v3::MediaType::default()
+            }))
+            .collect(),
+        ..v3::Response::default()
+    }))
+}
@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::dereference::ValueRole;
+use crate::example_validation::child;
+use crate::routes::templates;
+use crate::*;
+
+/// A way a spec breaks the specification that its types can't rule out, from
+/// [OpenAPI::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON pointer to the offending value, e.g. `/paths/~1pets~1{id}/get`.
+    pub pointer: String,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// An `operationId` an earlier operation already has.
+    DuplicateOperationId(String),
+    /// A `{name}` of the path that none of the operation's path parameters declares.
+    UndeclaredPathParameter(String),
+    /// A name in a schema's `required` missing from its `properties`.
+    MissingRequiredProperty(String),
+    /// A local `$ref` that points to nothing.
+    DanglingReference(String),
+    /// A tag name declared earlier in `tags`.
+    DuplicateTag(String),
+    /// A response status code outside of 100 to 599, or range outside of `1XX`
+    /// to `5XX`.
+    InvalidStatusCode(String),
+    /// A security requirement naming a scheme the components don't declare.
+    UnknownSecurityScheme(String),
+    /// A discriminator mapping to a schema that isn't among the members of the
+    /// `oneOf` or `anyOf` alongside it.
+    UnknownDiscriminatorMapping(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.pointer)?;
+        match &self.kind {
+            ValidationErrorKind::DuplicateOperationId(id) => write!(f, "operationId {:?} is not unique", id),
+            ValidationErrorKind::UndeclaredPathParameter(name) => write!(f, "path parameter {:?} is not declared", name),
+            ValidationErrorKind::MissingRequiredProperty(name) => write!(f, "required property {:?} is not in properties", name),
+            ValidationErrorKind::DanglingReference(reference) => write!(f, "reference {:?} points to nothing", reference),
+            ValidationErrorKind::DuplicateTag(name) => write!(f, "tag {:?} is declared twice", name),
+            ValidationErrorKind::InvalidStatusCode(code) => write!(f, "{} is not an HTTP status code", code),
+            ValidationErrorKind::UnknownSecurityScheme(name) => write!(f, "security scheme {:?} is not declared in components", name),
+            ValidationErrorKind::UnknownDiscriminatorMapping(target) => write!(f, "discriminator mapping {:?} is not a member of oneOf or anyOf", target),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl OpenAPI {
+    /// Checks what deserializing can't: that `operationId`s and tag names are
+    /// unique, that each template of a path is declared as a path parameter,
+    /// that the `required` properties of schemas are among their `properties`
+    /// (unless `additionalProperties` allows others), that local `$ref`s point
+    /// somewhere, that response status codes exist, that security requirements
+    /// name declared schemes, and that discriminator mappings of `oneOf` and
+    /// `anyOf` schemas are to their members. Errors are in document order within
+    /// each check.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let mut error = |pointer: String, kind| errors.push(ValidationError { pointer, kind });
+
+        let mut tags = HashSet::new();
+        for (i, tag) in self.tags.iter().enumerate() {
+            if !tags.insert(tag.name.as_str()) {
+                error(child(&child("/tags", &i.to_string()), "name"), ValidationErrorKind::DuplicateTag(tag.name.clone()));
+            }
+        }
+
+        let security = |pointer: &str, requirements: &[SecurityRequirement], error: &mut dyn FnMut(String, ValidationErrorKind)| {
+            for (i, requirement) in requirements.iter().enumerate() {
+                for scheme in requirement.keys().filter(|s| !self.security_schemes.contains_key(*s)) {
+                    error(child(&child(pointer, &i.to_string()), scheme), ValidationErrorKind::UnknownSecurityScheme(scheme.clone()));
+                }
+            }
+        };
+        security("/security", &self.security, &mut error);
+
+        let mut operation_ids = HashSet::new();
+        for (path, method, operation, item) in self.operations() {
+            let pointer = child(&child("/paths", path), method);
+            if let Some(id) = &operation.operation_id {
+                if !operation_ids.insert(id.as_str()) {
+                    error(child(&pointer, "operationId"), ValidationErrorKind::DuplicateOperationId(id.clone()));
+                }
+            }
+            // Unresolved parameters might declare any name; their `$ref`s are reported below.
+            let parameters: Option<Vec<&Parameter>> = item.parameters.iter().chain(&operation.parameters)
+                .map(|p| p.resolve(self).ok())
+                .collect();
+            if let Some(parameters) = parameters {
+                for name in templates(path) {
+                    if !parameters.iter().any(|p| matches!(p.kind, ParameterKind::Path { .. }) && p.name == name) {
+                        error(pointer.clone(), ValidationErrorKind::UndeclaredPathParameter(name.to_string()));
+                    }
+                }
+            }
+            for code in operation.responses.responses.keys() {
+                let valid = match code {
+                    StatusCode::Code(code) => (100..600).contains(code),
+                    StatusCode::Range(range) => (1..6).contains(range),
+                };
+                if !valid {
+                    error(child(&child(&pointer, "responses"), &code.to_string()), ValidationErrorKind::InvalidStatusCode(code.to_string()));
+                }
+            }
+            if let Some(requirements) = &operation.security {
+                security(&child(&pointer, "security"), requirements, &mut error);
+            }
+        }
+
+        if let Ok(document) = serde_json::to_value(self) {
+            check_value(&document, &document, "", ValueRole::Object, &mut error);
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Checks the `$ref`s and `required` properties in `value`, which has the given
+/// role, at `pointer` in `document`.
+fn check_value(document: &Value, value: &Value, pointer: &str, role: ValueRole, error: &mut dyn FnMut(String, ValidationErrorKind)) {
+    match value {
+        Value::Object(map) => {
+            if role.is_keywords() {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    let local = reference.strip_prefix('#');
+                    if local.is_some_and(|p| document.pointer(p).is_none()) {
+                        error(child(pointer, "$ref"), ValidationErrorKind::DanglingReference(reference.clone()));
+                    }
+                }
+                let object = map.contains_key("properties") || map.get("type").and_then(Value::as_str) == Some("object");
+                let closed = map.get("additionalProperties").is_none_or(|a| a == &Value::Bool(false));
+                if let (true, true, Some(Value::Array(required))) = (object, closed, map.get("required")) {
+                    let properties = map.get("properties").and_then(Value::as_object);
+                    for (i, name) in required.iter().enumerate() {
+                        let Some(name) = name.as_str() else { continue };
+                        if properties.is_none_or(|p| !p.contains_key(name)) {
+                            error(child(&child(pointer, "required"), &i.to_string()), ValidationErrorKind::MissingRequiredProperty(name.to_string()));
+                        }
+                    }
+                }
+            }
+            let members = map.get("oneOf").or_else(|| map.get("anyOf")).and_then(Value::as_array);
+            let discriminator = map.get("discriminator").and_then(|d| serde_json::from_value::<Discriminator>(d.clone()).ok());
+            if let (true, Some(members), Some(discriminator)) = (role.is_keywords(), members, discriminator) {
+                let references: Vec<&str> = members.iter().filter_map(|m| m.get("$ref")?.as_str()).collect();
+                for (value, target) in &discriminator.mapping {
+                    if !references.contains(&discriminator.reference_for(value).as_str()) {
+                        let pointer = child(&child(&child(pointer, "discriminator"), "mapping"), value);
+                        error(pointer, ValidationErrorKind::UnknownDiscriminatorMapping(target.clone()));
+                    }
+                }
+            }
+            for (key, child_value) in map {
+                let role = role.child(key);
+                if role != ValueRole::Data {
+                    check_value(document, child_value, &child(pointer, key), role, error);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check_value(document, item, &child(pointer, &i.to_string()), role.item(), error);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "tags": [{"name": "pets"}, {"name": "pets"}],
+            "security": [{"missing": []}],
+            "paths": {
+                "/pets/{id}": {
+                    "get": {
+                        "operationId": "getPet",
+                        "parameters": [{"$ref": "#/components/parameters/Id"}],
+                        "responses": {
+                            "200": {"description": "", "content": {"application/json": {
+                                "schema": {"$ref": "#/components/schemas/Pet"},
+                                "example": {"$ref": "#/not/a/reference"}
+                            }}},
+                            "default": {"$ref": "#/components/responses/Error"}
+                        }
+                    },
+                    "delete": {"operationId": "getPet", "responses": {}}
+                }
+            },
+            "components": {
+                "parameters": {"Id": {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}},
+                "schemas": {
+                    "Pet": {"type": "object", "required": ["name", "tag"], "properties": {
+                        "name": {"$ref": "#/components/schemas/Name"},
+                        "required": {"type": "object", "additionalProperties": true, "required": ["any"]}
+                    }},
+                    "Animal": {
+                        "oneOf": [{"$ref": "#/components/schemas/Pet"}],
+                        "discriminator": {"propertyName": "kind", "mapping": {"pet": "Pet", "fish": "#/components/schemas/Fish"}}
+                    }
+                }
+            }
+        })).unwrap();
+        let op = spec.paths.paths["/pets/{id}"].as_mut().unwrap().get.as_mut().unwrap();
+        op.responses.responses.insert(StatusCode::Code(600), RefOr::Item(Response::default()));
+
+        let errors: Vec<String> = spec.validate().unwrap_err().iter().map(ToString::to_string).collect();
+        assert_eq!(errors, [
+            "/tags/1/name: tag \"pets\" is declared twice",
+            "/security/0/missing: security scheme \"missing\" is not declared in components",
+            "/paths/~1pets~1{id}/get/responses/600: 600 is not an HTTP status code",
+            "/paths/~1pets~1{id}/delete/operationId: operationId \"getPet\" is not unique",
+            "/paths/~1pets~1{id}/delete: path parameter \"id\" is not declared",
+            "/paths/~1pets~1{id}/get/responses/default/$ref: reference \"#/components/responses/Error\" points to nothing",
+            "/components/schemas/Pet/required/1: required property \"tag\" is not in properties",
+            "/components/schemas/Pet/properties/name/$ref: reference \"#/components/schemas/Name\" points to nothing",
+            "/components/schemas/Animal/discriminator/mapping/fish: discriminator mapping \"#/components/schemas/Fish\" is not a member of oneOf or anyOf",
+        ]);
+        assert!(OpenAPI::default().validate().is_ok());
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum VersionedOpenAPI {
     #[cfg(feature = "v2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "v2")))]
@@ -0,0 +1,420 @@
+use crate::example_validation::child;
+use crate::*;
+
+/// What [OpenAPI::visit_schemas] calls for each schema in a document. Closures
+/// taking a pointer and a schema are visitors of schemas, e.g.
+///
+/// ```
+/// # use openapiv3::*;
+/// # let spec = OpenAPI::default();
+/// let mut untitled = Vec::new();
+/// spec.visit_schemas(&mut |pointer: &str, schema: &Schema| {
+///     if schema.title.is_none() {
+///         untitled.push(pointer.to_string());
+///     }
+/// });
+/// ```
+pub trait SchemaVisitor {
+    /// Called on each schema, before the schemas nested in it. `pointer` is the
+    /// JSON pointer to it, e.g. `/components/schemas/Pet/properties/name`.
+    fn visit_schema(&mut self, pointer: &str, schema: &Schema);
+
+    /// Called on each `$ref` to a schema. References aren't followed: the
+    /// component schemas they point to are visited where they're declared.
+    fn visit_reference(&mut self, _pointer: &str, _reference: &str) {}
+}
+
+impl<F: FnMut(&str, &Schema)> SchemaVisitor for F {
+    fn visit_schema(&mut self, pointer: &str, schema: &Schema) {
+        self(pointer, schema)
+    }
+}
+
+/// What [OpenAPI::visit_schemas_mut] calls for each schema in a document, to
+/// edit it. Closures taking a pointer and a mutable schema are visitors too.
+pub trait SchemaVisitorMut {
+    /// Called on each schema, before the schemas nested in it, which are those
+    /// of the schema as edited.
+    fn visit_schema(&mut self, pointer: &str, schema: &mut Schema);
+
+    /// Called on each `$ref` to a schema, which may be pointed elsewhere.
+    fn visit_reference(&mut self, _pointer: &str, _reference: &mut String) {}
+}
+
+impl<F: FnMut(&str, &mut Schema)> SchemaVisitorMut for F {
+    fn visit_schema(&mut self, pointer: &str, schema: &mut Schema) {
+        self(pointer, schema)
+    }
+}
+
+impl OpenAPI {
+    /// Calls `visitor` on every schema in the document, in document order: the
+    /// component schemas, and the schemas of parameters, request bodies,
+    /// responses, headers and encodings, wherever they are declared, including
+    /// in paths, webhooks, callbacks and the other components. Each schema is
+    /// followed by those nested in it: properties, `items`, `allOf`, `oneOf`,
+    /// `anyOf`, `not`, `additionalProperties` and the other keywords taking a
+    /// schema.
+    pub fn visit_schemas(&self, visitor: &mut impl SchemaVisitor) {
+        Walker { visitor }.document(self);
+    }
+
+    /// Like [OpenAPI::visit_schemas], but lets `visitor` edit the schemas.
+    pub fn visit_schemas_mut(&mut self, visitor: &mut impl SchemaVisitorMut) {
+        WalkerMut { visitor }.document(self);
+    }
+}
+
+impl Schema {
+    /// Calls `visitor` on this schema and every schema nested in it, in the
+    /// order of [OpenAPI::visit_schemas]. Pointers are relative to this schema,
+    /// which is at the empty pointer.
+    pub fn visit_schemas(&self, visitor: &mut impl SchemaVisitor) {
+        Walker { visitor }.item("", self);
+    }
+
+    /// Like [Schema::visit_schemas], but lets `visitor` edit the schemas.
+    pub fn visit_schemas_mut(&mut self, visitor: &mut impl SchemaVisitorMut) {
+        WalkerMut { visitor }.item("", self);
+    }
+}
+
+/// Defines the walk over a document for a kind of visitor, once for shared
+/// references and once, with `mut`, for mutable ones.
+macro_rules! walker {
+    ($walker:ident, $visitor:ident, $iter:ident $(, $mut_:tt)?) => {
+        struct $walker<'v, V> {
+            visitor: &'v mut V,
+        }
+
+        impl<V: $visitor> $walker<'_, V> {
+            fn document(&mut self, spec: &$($mut_)? OpenAPI) {
+                let components = &$($mut_)? spec.components;
+                for (name, schema) in components.schemas.$iter() {
+                    self.schema(&child("/components/schemas", name), schema);
+                }
+                for (name, parameter) in components.parameters.$iter() {
+                    if let RefOr::Item(parameter) = parameter {
+                        self.parameter(&child("/components/parameters", name), parameter);
+                    }
+                }
+                for (name, body) in components.request_bodies.$iter() {
+                    if let RefOr::Item(body) = body {
+                        self.content(&child(&child("/components/requestBodies", name), "content"), &$($mut_)? body.content);
+                    }
+                }
+                for (name, response) in components.responses.$iter() {
+                    if let RefOr::Item(response) = response {
+                        self.response(&child("/components/responses", name), response);
+                    }
+                }
+                for (name, header) in components.headers.$iter() {
+                    if let RefOr::Item(header) = header {
+                        self.header(&child("/components/headers", name), header);
+                    }
+                }
+                for (name, callback) in components.callbacks.$iter() {
+                    if let RefOr::Item(callback) = callback {
+                        self.callback(&child("/components/callbacks", name), callback);
+                    }
+                }
+                self.path_items("/components/pathItems", &$($mut_)? components.path_items);
+                self.path_items("/paths", &$($mut_)? spec.paths.paths);
+                self.path_items("/webhooks", &$($mut_)? spec.webhooks);
+            }
+
+            fn path_items(&mut self, pointer: &str, items: &$($mut_)? IndexMap<String, RefOr<PathItem>>) {
+                for (name, item) in items.$iter() {
+                    if let RefOr::Item(item) = item {
+                        self.path_item(&child(pointer, name), item);
+                    }
+                }
+            }
+
+            fn callback(&mut self, pointer: &str, callback: &$($mut_)? Callback) {
+                for (expression, item) in callback.$iter() {
+                    self.path_item(&child(pointer, expression), item);
+                }
+            }
+
+            fn path_item(&mut self, pointer: &str, item: &$($mut_)? PathItem) {
+                for (i, parameter) in item.parameters.$iter().enumerate() {
+                    if let RefOr::Item(parameter) = parameter {
+                        self.parameter(&child(&child(pointer, "parameters"), &i.to_string()), parameter);
+                    }
+                }
+                for (method, operation) in item.$iter() {
+                    let pointer = child(pointer, method);
+                    for (i, parameter) in operation.parameters.$iter().enumerate() {
+                        if let RefOr::Item(parameter) = parameter {
+                            self.parameter(&child(&child(&pointer, "parameters"), &i.to_string()), parameter);
+                        }
+                    }
+                    if let Some(RefOr::Item(body)) = &$($mut_)? operation.request_body {
+                        self.content(&child(&child(&pointer, "requestBody"), "content"), &$($mut_)? body.content);
+                    }
+                    let responses = child(&pointer, "responses");
+                    if let Some(RefOr::Item(response)) = &$($mut_)? operation.responses.default {
+                        self.response(&child(&responses, "default"), response);
+                    }
+                    for (status, response) in operation.responses.responses.$iter() {
+                        if let RefOr::Item(response) = response {
+                            self.response(&child(&responses, &status.to_string()), response);
+                        }
+                    }
+                    for (name, callback) in operation.callbacks.$iter() {
+                        if let RefOr::Item(callback) = callback {
+                            self.callback(&child(&child(&pointer, "callbacks"), name), callback);
+                        }
+                    }
+                }
+            }
+
+            fn parameter(&mut self, pointer: &str, parameter: &$($mut_)? Parameter) {
+                self.format(pointer, &$($mut_)? parameter.data.format);
+            }
+
+            fn header(&mut self, pointer: &str, header: &$($mut_)? Header) {
+                self.format(pointer, &$($mut_)? header.format);
+            }
+
+            fn format(&mut self, pointer: &str, format: &$($mut_)? ParameterSchemaOrContent) {
+                match format {
+                    ParameterSchemaOrContent::Schema(schema) => self.schema(&child(pointer, "schema"), schema),
+                    ParameterSchemaOrContent::Content(content) => self.content(&child(pointer, "content"), content),
+                }
+            }
+
+            fn response(&mut self, pointer: &str, response: &$($mut_)? Response) {
+                for (name, header) in response.headers.$iter() {
+                    if let RefOr::Item(header) = header {
+                        self.header(&child(&child(pointer, "headers"), name), header);
+                    }
+                }
+                self.content(&child(pointer, "content"), &$($mut_)? response.content);
+            }
+
+            fn content(&mut self, pointer: &str, content: &$($mut_)? Content) {
+                for (media_type, media) in content.$iter() {
+                    let pointer = child(pointer, media_type);
+                    if let Some(schema) = &$($mut_)? media.schema {
+                        self.schema(&child(&pointer, "schema"), schema);
+                    }
+                    for (property, encoding) in media.encoding.$iter() {
+                        for (name, header) in encoding.headers.$iter() {
+                            if let RefOr::Item(header) = header {
+                                let encoding = child(&child(&pointer, "encoding"), property);
+                                self.header(&child(&child(&encoding, "headers"), name), header);
+                            }
+                        }
+                    }
+                }
+            }
+
+            fn schema(&mut self, pointer: &str, schema: &$($mut_)? RefOr<Schema>) {
+                match schema {
+                    RefOr::Item(schema) => self.item(pointer, schema),
+                    RefOr::Reference { reference } => self.visitor.visit_reference(pointer, reference),
+                }
+            }
+
+            fn item(&mut self, pointer: &str, schema: &$($mut_)? Schema) {
+                self.visitor.visit_schema(pointer, schema);
+
+                let data = &$($mut_)? schema.data;
+                let single = [
+                    ("if", &$($mut_)? data.if_),
+                    ("then", &$($mut_)? data.then),
+                    ("else", &$($mut_)? data.else_),
+                    ("contains", &$($mut_)? data.contains),
+                    ("propertyNames", &$($mut_)? data.property_names),
+                    ("contentSchema", &$($mut_)? data.content_schema),
+                ];
+                for (keyword, nested) in IntoIterator::into_iter(single) {
+                    if let Some(nested) = nested {
+                        self.schema(&child(pointer, keyword), nested);
+                    }
+                }
+                self.additional(&child(pointer, "unevaluatedProperties"), &$($mut_)? data.unevaluated_properties);
+                self.additional(&child(pointer, "unevaluatedItems"), &$($mut_)? data.unevaluated_items);
+                self.list(&child(pointer, "prefixItems"), &$($mut_)? data.prefix_items);
+                let maps = [
+                    ("$defs", &$($mut_)? data.defs),
+                    ("patternProperties", &$($mut_)? data.pattern_properties),
+                    ("dependentSchemas", &$($mut_)? data.dependent_schemas),
+                ];
+                for (keyword, map) in IntoIterator::into_iter(maps) {
+                    self.map(&child(pointer, keyword), map);
+                }
+
+                match &$($mut_)? schema.kind {
+                    SchemaKind::Type(Type::Object(o)) => {
+                        self.map(&child(pointer, "properties"), &$($mut_)? o.properties);
+                        self.additional(&child(pointer, "additionalProperties"), &$($mut_)? o.additional_properties);
+                    }
+                    SchemaKind::Type(Type::Array(a)) => {
+                        if let Some(items) = &$($mut_)? a.items {
+                            self.schema(&child(pointer, "items"), items);
+                        }
+                    }
+                    SchemaKind::Type(_) => {}
+                    SchemaKind::OneOf { one_of } => self.list(&child(pointer, "oneOf"), one_of),
+                    SchemaKind::AllOf { all_of } => self.list(&child(pointer, "allOf"), all_of),
+                    SchemaKind::AnyOf { any_of } => self.list(&child(pointer, "anyOf"), any_of),
+                    SchemaKind::Not { not } => self.schema(&child(pointer, "not"), not),
+                    SchemaKind::Any(a) => {
+                        self.map(&child(pointer, "properties"), &$($mut_)? a.properties);
+                        self.additional(&child(pointer, "additionalProperties"), &$($mut_)? a.additional_properties);
+                        if let Some(items) = &$($mut_)? a.items {
+                            self.schema(&child(pointer, "items"), items);
+                        }
+                        self.list(&child(pointer, "allOf"), &$($mut_)? a.all_of);
+                        self.list(&child(pointer, "oneOf"), &$($mut_)? a.one_of);
+                        self.list(&child(pointer, "anyOf"), &$($mut_)? a.any_of);
+                        if let Some(not) = &$($mut_)? a.not {
+                            self.schema(&child(pointer, "not"), not);
+                        }
+                    }
+                }
+            }
+
+            fn list(&mut self, pointer: &str, schemas: &$($mut_)? [RefOr<Schema>]) {
+                for (i, schema) in schemas.$iter().enumerate() {
+                    self.schema(&child(pointer, &i.to_string()), schema);
+                }
+            }
+
+            fn map(&mut self, pointer: &str, schemas: &$($mut_)? RefOrMap<Schema>) {
+                for (name, schema) in schemas.$iter() {
+                    self.schema(&child(pointer, name), schema);
+                }
+            }
+
+            fn additional(&mut self, pointer: &str, additional: &$($mut_)? Option<AdditionalProperties>) {
+                if let Some(AdditionalProperties::Schema(schema)) = additional {
+                    self.schema(pointer, schema);
+                }
+            }
+        }
+    };
+}
+
+walker!(Walker, SchemaVisitor, iter);
+walker!(WalkerMut, SchemaVisitorMut, iter_mut, mut);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect {
+        schemas: Vec<String>,
+        references: Vec<String>,
+    }
+
+    impl SchemaVisitor for Collect {
+        fn visit_schema(&mut self, pointer: &str, _schema: &Schema) {
+            self.schemas.push(pointer.to_string());
+        }
+
+        fn visit_reference(&mut self, pointer: &str, reference: &str) {
+            self.references.push(format!("{} -> {}", pointer, reference));
+        }
+    }
+
+    #[test]
+    fn test_visit_schemas() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {"/pets": {"post": {
+                "parameters": [{"name": "q", "in": "query", "schema": {"type": "string"}}],
+                "requestBody": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}},
+                "responses": {"200": {
+                    "description": "",
+                    "headers": {"X-Rate": {"schema": {"type": "integer"}}},
+                    "content": {"application/json": {"schema": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}}}}
+                }}
+            }}},
+            "components": {
+                "schemas": {"Pet": {"allOf": [
+                    {"type": "object", "properties": {"tags": {"type": "object", "additionalProperties": {"type": "string"}}}},
+                    {"oneOf": [{"type": "string"}, {"type": "integer"}]}
+                ]}},
+                "callbacks": {"Hook": {"{$request.body#/url}": {"post": {
+                    "requestBody": {"content": {"application/json": {"schema": {"type": "boolean"}}}},
+                    "responses": {}
+                }}}}
+            }
+        })).unwrap();
+        let mut collect = Collect::default();
+        spec.visit_schemas(&mut collect);
+        assert_eq!(collect.schemas, [
+            "/components/schemas/Pet",
+            "/components/schemas/Pet/allOf/0",
+            "/components/schemas/Pet/allOf/0/properties/tags",
+            "/components/schemas/Pet/allOf/0/properties/tags/additionalProperties",
+            "/components/schemas/Pet/allOf/1",
+            "/components/schemas/Pet/allOf/1/oneOf/0",
+            "/components/schemas/Pet/allOf/1/oneOf/1",
+            "/components/callbacks/Hook/{$request.body#~1url}/post/requestBody/content/application~1json/schema",
+            "/paths/~1pets/post/parameters/0/schema",
+            "/paths/~1pets/post/responses/200/headers/X-Rate/schema",
+            "/paths/~1pets/post/responses/200/content/application~1json/schema",
+        ]);
+        assert_eq!(collect.references, [
+            "/paths/~1pets/post/requestBody/content/application~1json/schema -> #/components/schemas/Pet",
+            "/paths/~1pets/post/responses/200/content/application~1json/schema/items -> #/components/schemas/Pet",
+        ]);
+
+        let mut count = 0;
+        spec.visit_schemas(&mut |_: &str, _: &Schema| count += 1);
+        assert_eq!(count, 11);
+    }
+
+    #[test]
+    fn test_visit_schemas_mut() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.1.0",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "webhooks": {"newPet": {"post": {
+                "requestBody": {"content": {"application/json": {"schema": {"type": "object", "properties": {"name": {"type": "string"}}}}}},
+                "responses": {}
+            }}},
+            "components": {"pathItems": {"Ping": {"get": {
+                "parameters": [{"name": "q", "in": "query", "schema": {"$ref": "#/components/schemas/Q"}}],
+                "responses": {}
+            }}}}
+        })).unwrap();
+        let mut titled = Vec::new();
+        spec.visit_schemas_mut(&mut |pointer: &str, schema: &mut Schema| {
+            schema.title = Some(pointer.to_string());
+            titled.push(pointer.to_string());
+        });
+        assert_eq!(titled, [
+            "/webhooks/newPet/post/requestBody/content/application~1json/schema",
+            "/webhooks/newPet/post/requestBody/content/application~1json/schema/properties/name",
+        ]);
+
+        struct Relink;
+        impl SchemaVisitorMut for Relink {
+            fn visit_schema(&mut self, _pointer: &str, _schema: &mut Schema) {}
+
+            fn visit_reference(&mut self, _pointer: &str, reference: &mut String) {
+                *reference = reference.replace("/Q", "/Query");
+            }
+        }
+        spec.visit_schemas_mut(&mut Relink);
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["components"]["pathItems"]["Ping"]["get"]["parameters"][0]["schema"], json!({"$ref": "#/components/schemas/Query"}));
+
+        let schema = spec.webhooks["newPet"].as_item().unwrap().post.as_ref().unwrap()
+            .request_body.as_ref().unwrap().as_item().unwrap().content["application/json"].schema.clone().unwrap().into_item().unwrap();
+        let mut pointers = Vec::new();
+        schema.visit_schemas(&mut |pointer: &str, _: &Schema| pointers.push(pointer.to_string()));
+        assert_eq!(pointers, ["", "/properties/name"]);
+    }
+}
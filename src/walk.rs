@@ -0,0 +1,132 @@
+use indexmap::IndexMap;
+
+use crate::*;
+
+/// Calls `f` on the media types of every request body and response, wherever
+/// those are declared, including in webhooks. References are skipped.
+pub(crate) fn for_each_body_media_type_mut(spec: &mut OpenAPI, f: &mut dyn FnMut(&mut MediaType)) {
+    let components = &mut spec.components;
+    for response in components.responses.values_mut().filter_map(RefOr::as_mut) {
+        response.content.values_mut().for_each(&mut *f);
+    }
+    for body in components.request_bodies.values_mut().filter_map(RefOr::as_mut) {
+        body.content.values_mut().for_each(&mut *f);
+    }
+    for callback in components.callbacks.values_mut().filter_map(RefOr::as_mut) {
+        for item in callback.values_mut() {
+            path_item_media_types_mut(item, f);
+        }
+    }
+    let items = components.path_items.values_mut().chain(spec.paths.paths.values_mut()).chain(spec.webhooks.values_mut());
+    for item in items.filter_map(RefOr::as_mut) {
+        path_item_media_types_mut(item, f);
+    }
+}
+
+fn path_item_media_types_mut(item: &mut PathItem, f: &mut dyn FnMut(&mut MediaType)) {
+    for (_, operation) in item.iter_mut() {
+        if let Some(RefOr::Item(body)) = operation.request_body.as_mut() {
+            body.content.values_mut().for_each(&mut *f);
+        }
+        let responses = &mut operation.responses;
+        for response in responses.default.iter_mut().chain(responses.responses.values_mut()).filter_map(RefOr::as_mut) {
+            response.content.values_mut().for_each(&mut *f);
+        }
+        for callback in operation.callbacks.values_mut().filter_map(RefOr::as_mut) {
+            for item in callback.values_mut() {
+                path_item_media_types_mut(item, f);
+            }
+        }
+    }
+}
+
+/// What [for_each_example_mut] calls: an `example`, `examples`, and the schema
+/// they illustrate.
+pub(crate) type ExampleVisitor<'f> = dyn FnMut(&mut Option<serde_json::Value>, &mut IndexMap<String, RefOr<Example>>, Option<&RefOr<Schema>>) + 'f;
+
+/// Calls `f` on the `example` and `examples` of every parameter, header and media
+/// type declared in the document, including in webhooks. References are skipped.
+pub(crate) fn for_each_example_mut(spec: &mut OpenAPI, f: &mut ExampleVisitor) {
+    let components = &mut spec.components;
+    for parameter in components.parameters.values_mut().filter_map(RefOr::as_mut) {
+        parameter_examples_mut(parameter, f);
+    }
+    for response in components.responses.values_mut().filter_map(RefOr::as_mut) {
+        response_examples_mut(response, f);
+    }
+    for body in components.request_bodies.values_mut().filter_map(RefOr::as_mut) {
+        content_examples_mut(&mut body.content, f);
+    }
+    for header in components.headers.values_mut().filter_map(RefOr::as_mut) {
+        header_examples_mut(header, f);
+    }
+    for callback in components.callbacks.values_mut().filter_map(RefOr::as_mut) {
+        for item in callback.values_mut() {
+            path_item_examples_mut(item, f);
+        }
+    }
+    let items = components.path_items.values_mut().chain(spec.paths.paths.values_mut()).chain(spec.webhooks.values_mut());
+    for item in items.filter_map(RefOr::as_mut) {
+        path_item_examples_mut(item, f);
+    }
+}
+
+fn path_item_examples_mut(item: &mut PathItem, f: &mut ExampleVisitor) {
+    for parameter in item.parameters.iter_mut().filter_map(RefOr::as_mut) {
+        parameter_examples_mut(parameter, f);
+    }
+    for (_, operation) in item.iter_mut() {
+        for parameter in operation.parameters.iter_mut().filter_map(RefOr::as_mut) {
+            parameter_examples_mut(parameter, f);
+        }
+        if let Some(RefOr::Item(body)) = operation.request_body.as_mut() {
+            content_examples_mut(&mut body.content, f);
+        }
+        let responses = &mut operation.responses;
+        for response in responses.default.iter_mut().chain(responses.responses.values_mut()).filter_map(RefOr::as_mut) {
+            response_examples_mut(response, f);
+        }
+        for callback in operation.callbacks.values_mut().filter_map(RefOr::as_mut) {
+            for item in callback.values_mut() {
+                path_item_examples_mut(item, f);
+            }
+        }
+    }
+}
+
+fn parameter_examples_mut(parameter: &mut Parameter, f: &mut ExampleVisitor) {
+    let data = &mut parameter.data;
+    format_examples_mut(&mut data.example, &mut data.examples, &mut data.format, f);
+}
+
+fn header_examples_mut(header: &mut Header, f: &mut ExampleVisitor) {
+    format_examples_mut(&mut header.example, &mut header.examples, &mut header.format, f);
+}
+
+fn format_examples_mut(example: &mut Option<serde_json::Value>, examples: &mut IndexMap<String, RefOr<Example>>, format: &mut ParameterSchemaOrContent, f: &mut ExampleVisitor) {
+    match format {
+        ParameterSchemaOrContent::Schema(schema) => f(example, examples, Some(schema)),
+        ParameterSchemaOrContent::Content(content) => {
+            f(example, examples, None);
+            content_examples_mut(content, f);
+        }
+    }
+}
+
+fn response_examples_mut(response: &mut Response, f: &mut ExampleVisitor) {
+    for header in response.headers.values_mut().filter_map(RefOr::as_mut) {
+        header_examples_mut(header, f);
+    }
+    content_examples_mut(&mut response.content, f);
+}
+
+fn content_examples_mut(content: &mut Content, f: &mut ExampleVisitor) {
+    for media in content.values_mut() {
+        f(&mut media.example, &mut media.examples, media.schema.as_ref());
+        for encoding in media.encoding.values_mut() {
+            for header in encoding.headers.values_mut().filter_map(RefOr::as_mut) {
+                header_examples_mut(header, f);
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! JavaScript bindings, for web-based spec editors. Specs cross the boundary as
+//! JSON or YAML text, and come back as JSON text for `JSON.parse`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{CyclicRefs, Format, OpenAPI};
+
+fn load(source: &str) -> Result<OpenAPI, JsError> {
+    OpenAPI::from_str_format(source, Format::detect(None, source)).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn to_json(spec: &OpenAPI) -> Result<String, JsError> {
+    serde_json::to_string(spec).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Parses a JSON or YAML spec, throwing on invalid ones, and returns it as JSON.
+#[wasm_bindgen]
+pub fn parse(source: &str) -> Result<String, JsError> {
+    to_json(&load(source)?)
+}
+
+/// The problems of a JSON or YAML spec, as messages prefixed with a JSON pointer
+/// to where they are: the parse error of an invalid one, or else the errors of
+/// [OpenAPI::validate] and its examples that don't match their schemas. Empty
+/// for a valid spec.
+#[wasm_bindgen]
+pub fn validate(source: &str) -> Vec<String> {
+    match OpenAPI::from_str_format(source, Format::detect(None, source)) {
+        Ok(spec) => {
+            let mut errors: Vec<String> = spec.validate().err().unwrap_or_default().iter().map(ToString::to_string).collect();
+            errors.extend(spec.validate_examples().err().unwrap_or_default().iter().map(ToString::to_string));
+            errors
+        }
+        Err(e) => vec![e.to_string()],
+    }
+}
+
+/// `base` with `other` merged in, as by [OpenAPI::merge], as JSON.
+#[wasm_bindgen]
+pub fn merge(base: &str, other: &str) -> Result<String, JsError> {
+    let merged = load(base)?.merge(load(other)?).map_err(|e| JsError::new(&e.to_string()))?;
+    to_json(&merged)
+}
+
+/// The spec with every `$ref` inlined, leaving cyclic ones in place, as by
+/// [OpenAPI::dereference], as JSON.
+#[wasm_bindgen]
+pub fn dereference(source: &str) -> Result<String, JsError> {
+    let dereferenced = load(source)?.dereference(CyclicRefs::Keep).map_err(|e| JsError::new(&e.to_string()))?;
+    to_json(&dereferenced)
+}
+
+/// The changes from `old` to `new`, as by [crate::diff], as JSON.
+#[wasm_bindgen]
+pub fn diff(old: &str, new: &str) -> Result<String, JsError> {
+    let diff = crate::diff(&load(old)?, &load(new)?);
+    serde_json::to_string(&diff).map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,462 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parameter_style::text;
+use crate::*;
+
+/// A metadata object that allows for more fine-tuned XML model definitions.
+///
+/// When using arrays, XML element names are not inferred (for singular/plural
+/// forms) and the name property SHOULD be used to add that information.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Xml {
+    /// Replaces the name of the element/attribute used for the described schema
+    /// property. When defined within items, it will affect the name of the
+    /// individual XML elements within the list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The URI of the namespace definition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// The prefix to be used for the name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Declares whether the property definition translates to an attribute
+    /// instead of an element.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub attribute: bool,
+    /// MAY be used only for an array definition. Signifies whether the array is
+    /// wrapped (for example, `<books><book/><book/></books>`) or unwrapped
+    /// (`<book/><book/>`).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub wrapped: bool,
+    /// Inline extensions to this object.
+    #[serde(flatten, deserialize_with = "crate::util::deserialize_extensions")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::fuzz::extensions))]
+    pub extensions: IndexMap<String, serde_json::Value>,
+}
+
+impl Schema {
+    /// An XML document valid against this schema, for `application/xml` media
+    /// types: a [generated](Schema::generate_example) example laid out by the
+    /// `xml` objects of the schema and its properties, with their names,
+    /// namespaces and prefixes, attributes and wrapped arrays. The root element
+    /// is named by the schema's `xml` name, else its name among the component
+    /// schemas of `spec`, else `root`.
+    pub fn generate_xml_example(&self, spec: &OpenAPI, options: &ExampleOptions) -> String {
+        let value = self.generate_example(spec, options);
+        let name = spec.schemas.iter()
+            .find(|(_, schema)| schema.as_item().is_some_and(|schema| std::ptr::eq(schema, self)))
+            .map_or("root", |(name, _)| name.as_str());
+        to_xml(spec, Some(self), name, &value)
+    }
+}
+
+/// How the values of a schema are written in XML, from [Schema::xml_layout].
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlElement {
+    /// The local name, without the prefix.
+    pub name: String,
+    pub prefix: Option<String>,
+    /// The namespace the element declares.
+    pub namespace: Option<String>,
+    /// Whether the element is written once per item of an array, rather than once.
+    pub repeated: bool,
+    /// The properties written as attributes, in order.
+    pub attributes: Vec<XmlAttribute>,
+    pub content: XmlContent,
+}
+
+/// A property written as an attribute of its object's element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlAttribute {
+    /// The name of the property in the schema.
+    pub property: String,
+    /// The local name, without the prefix.
+    pub name: String,
+    pub prefix: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlContent {
+    /// The value as text, for primitive types.
+    Text,
+    /// The elements of the properties of an object, by property name, in order.
+    /// Those of the `allOf`, `oneOf` and `anyOf` members are included.
+    Elements(IndexMap<String, XmlElement>),
+    /// The element of each item of a wrapped array.
+    Items(Box<XmlElement>),
+    /// A schema by this `$ref`, laid out by an enclosing element already, which
+    /// it would repeat endlessly.
+    Recursive(String),
+}
+
+impl XmlElement {
+    fn new(xml: Option<&Xml>, name: &str, content: XmlContent) -> Self {
+        XmlElement {
+            name: xml.and_then(|xml| xml.name.clone()).unwrap_or_else(|| name.to_string()),
+            prefix: xml.and_then(|xml| xml.prefix.clone()),
+            namespace: xml.and_then(|xml| xml.namespace.clone()),
+            repeated: false,
+            attributes: Vec::new(),
+            content,
+        }
+    }
+
+    /// The name with its prefix, e.g. `ex:Pet`, as in the tags.
+    pub fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Schema {
+    /// The XML layout of this schema and those nested in it, by their `xml`
+    /// objects, as [Schema::generate_xml_example] writes values: element and
+    /// attribute names, namespaces and prefixes, and wrapped and unwrapped arrays.
+    /// The element is named by the schema's `xml` name, else `name`, and wraps
+    /// its items if it is an array, even an unwrapped one, as a document has a
+    /// single root. `$ref`s are followed, but not into a schema being laid out
+    /// already.
+    pub fn xml_layout(&self, spec: &OpenAPI, name: &str) -> XmlElement {
+        let element = XmlLayout { view: spec.resolved(), refs: Vec::new() }.element(self, name);
+        match element.repeated {
+            true => XmlElement::new(self.xml.as_ref(), name, XmlContent::Items(Box::new(element))),
+            false => element,
+        }
+    }
+}
+
+struct XmlLayout<'a> {
+    view: ResolvedSpec<'a>,
+    /// The `$ref`s followed to the schema being laid out.
+    refs: Vec<&'a str>,
+}
+
+impl<'a> XmlLayout<'a> {
+    fn element(&mut self, schema: &'a Schema, name: &str) -> XmlElement {
+        let xml = schema.xml.as_ref();
+        if let Some(items) = array_items(schema) {
+            let local = xml.and_then(|xml| xml.name.as_deref()).unwrap_or(name);
+            let mut item = self.nested(items, local);
+            item.repeated = true;
+            return match xml.is_some_and(|xml| xml.wrapped) {
+                true => XmlElement::new(xml, name, XmlContent::Items(Box::new(item))),
+                false => item,
+            };
+        }
+        let mut properties = IndexMap::new();
+        let object = collect_properties(self.view, schema, &mut properties, 0);
+        let mut element = XmlElement::new(xml, name, XmlContent::Text);
+        if !object {
+            return element;
+        }
+        let mut elements = IndexMap::new();
+        for (key, property) in properties {
+            let resolved = self.view.schema(property).ok();
+            match resolved.and_then(|schema| schema.xml.as_ref()) {
+                Some(xml) if xml.attribute && resolved.is_some_and(|schema| !is_complex(schema)) => {
+                    element.attributes.push(XmlAttribute {
+                        property: key.to_string(),
+                        name: xml.name.clone().unwrap_or_else(|| key.to_string()),
+                        prefix: xml.prefix.clone(),
+                        namespace: xml.namespace.clone(),
+                    });
+                }
+                _ => {
+                    elements.insert(key.to_string(), self.nested(property, key));
+                }
+            }
+        }
+        element.content = XmlContent::Elements(elements);
+        element
+    }
+
+    fn nested(&mut self, schema: &'a RefOr<Schema>, name: &str) -> XmlElement {
+        let Ok(resolved) = self.view.schema(schema) else { return XmlElement::new(None, name, XmlContent::Text) };
+        let Some(reference) = schema.as_ref_str() else { return self.element(resolved, name) };
+        if self.refs.contains(&reference) {
+            return XmlElement::new(resolved.xml.as_ref(), name, XmlContent::Recursive(reference.to_string()));
+        }
+        self.refs.push(reference);
+        let element = self.element(resolved, name);
+        self.refs.pop();
+        element
+    }
+}
+
+/// Collects the properties of `schema` and of its `allOf`, `oneOf` and `anyOf`
+/// members, the first of each name winning, returning whether it is an object.
+fn collect_properties<'a>(view: ResolvedSpec<'a>, schema: &'a Schema, out: &mut IndexMap<&'a str, &'a RefOr<Schema>>, depth: usize) -> bool {
+    if depth > MAX_DEPTH {
+        return false;
+    }
+    let (properties, members, object): (Option<&'a RefOrMap<Schema>>, Vec<&'a RefOr<Schema>>, bool) = match &schema.kind {
+        SchemaKind::Type(Type::Object(object)) => (Some(&object.properties), Vec::new(), true),
+        SchemaKind::AllOf { all_of: members } | SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => {
+            (None, members.iter().collect(), false)
+        }
+        SchemaKind::Any(any) => (
+            Some(&any.properties),
+            any.all_of.iter().chain(&any.one_of).chain(&any.any_of).collect(),
+            any.typ.as_deref() == Some("object") || !any.properties.is_empty(),
+        ),
+        _ => (None, Vec::new(), false),
+    };
+    for (key, property) in properties.into_iter().flatten() {
+        out.entry(key.as_str()).or_insert(property);
+    }
+    let mut object = object;
+    for member in members.into_iter().filter_map(|member| view.schema(member).ok()) {
+        object |= collect_properties(view, member, out, depth + 1);
+    }
+    object
+}
+
+fn array_items(schema: &Schema) -> Option<&RefOr<Schema>> {
+    match &schema.kind {
+        SchemaKind::Type(Type::Array(array)) => array.items.as_deref(),
+        SchemaKind::Any(any) if any.typ.as_deref() == Some("array") || any.items.is_some() => any.items.as_deref(),
+        _ => None,
+    }
+}
+
+/// Whether values of `schema` are objects or arrays, which can't be attributes.
+fn is_complex(schema: &Schema) -> bool {
+    match &schema.kind {
+        SchemaKind::Type(Type::Object(_) | Type::Array(_)) => true,
+        SchemaKind::Type(_) => false,
+        SchemaKind::Any(any) => matches!(any.typ.as_deref(), Some("object" | "array")) || !any.properties.is_empty() || any.items.is_some(),
+        _ => true,
+    }
+}
+
+/// `value` as an XML document laid out by `schema`, its root element named by the
+/// schema's `xml` name, else `name`. A root array is wrapped, even if the schema
+/// says otherwise, as a document has a single root.
+pub(crate) fn to_xml(spec: &OpenAPI, schema: Option<&Schema>, name: &str, value: &Value) -> String {
+    let mut writer = XmlWriter { view: spec.resolved(), out: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n") };
+    writer.element(schema, name, value, 0);
+    writer.out
+}
+
+/// Nested schemas are searched this deep for the properties of an object.
+const MAX_DEPTH: usize = 16;
+
+struct XmlWriter<'a> {
+    view: ResolvedSpec<'a>,
+    out: String,
+}
+
+impl<'a> XmlWriter<'a> {
+    /// Writes `value` as an element named by the `xml` object of `schema`, else
+    /// `name`, on its own line. `indent` is `0` for the root.
+    fn element(&mut self, schema: Option<&'a Schema>, name: &str, value: &Value, indent: usize) {
+        let xml = schema.and_then(|schema| schema.xml.as_ref());
+        let local = xml.and_then(|xml| xml.name.as_deref()).unwrap_or(name);
+        let tag = qualified(xml, local);
+        if let Value::Array(items) = value {
+            let item_schema = schema.and_then(|schema| self.items(schema));
+            if indent > 0 && !xml.is_some_and(|xml| xml.wrapped) {
+                for item in items {
+                    self.element(item_schema, local, item, indent);
+                }
+                return;
+            }
+            self.open(&tag, xml, &[], indent);
+            if items.is_empty() {
+                self.out.push_str("/>\n");
+                return;
+            }
+            self.out.push_str(">\n");
+            for item in items {
+                self.element(item_schema, local, item, indent + 1);
+            }
+            self.close(&tag, indent);
+            return;
+        }
+
+        let mut attributes = Vec::new();
+        let mut children = Vec::new();
+        if let Value::Object(object) = value {
+            for (key, value) in object {
+                let property = schema.and_then(|schema| self.property(schema, key));
+                match property.and_then(|property| property.xml.as_ref()) {
+                    Some(xml) if xml.attribute && !value.is_object() && !value.is_array() => {
+                        let name = qualified(Some(xml), xml.name.as_deref().unwrap_or(key));
+                        attributes.push((name, text(value)));
+                    }
+                    _ => children.push((key, property, value)),
+                }
+            }
+        }
+        self.open(&tag, xml, &attributes, indent);
+        match value {
+            Value::Null => self.out.push_str("/>\n"),
+            Value::Object(_) if children.is_empty() => self.out.push_str("/>\n"),
+            Value::Object(_) => {
+                self.out.push_str(">\n");
+                for (key, property, value) in children {
+                    self.element(property, key, value, indent + 1);
+                }
+                self.close(&tag, indent);
+            }
+            value => {
+                self.out.push('>');
+                self.out.push_str(&escape(&text(value), false));
+                self.out.push_str(&format!("</{}>\n", tag));
+            }
+        }
+    }
+
+    /// Writes the start tag up to its closing `>` or `/>`.
+    fn open(&mut self, tag: &str, xml: Option<&Xml>, attributes: &[(String, String)], indent: usize) {
+        self.out.push_str(&"  ".repeat(indent));
+        self.out.push('<');
+        self.out.push_str(tag);
+        if let Some(namespace) = xml.and_then(|xml| xml.namespace.as_deref()) {
+            match xml.and_then(|xml| xml.prefix.as_deref()) {
+                Some(prefix) => self.out.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape(namespace, true))),
+                None => self.out.push_str(&format!(" xmlns=\"{}\"", escape(namespace, true))),
+            }
+        }
+        for (name, value) in attributes {
+            self.out.push_str(&format!(" {}=\"{}\"", name, escape(value, true)));
+        }
+    }
+
+    fn close(&mut self, tag: &str, indent: usize) {
+        self.out.push_str(&format!("{}</{}>\n", "  ".repeat(indent), tag));
+    }
+
+    fn resolve(&self, schema: &'a RefOr<Schema>) -> Option<&'a Schema> {
+        self.view.schema(schema).ok()
+    }
+
+    fn items(&self, schema: &'a Schema) -> Option<&'a Schema> {
+        match &schema.kind {
+            SchemaKind::Type(Type::Array(array)) => self.resolve(array.items.as_deref()?),
+            SchemaKind::Any(any) => self.resolve(any.items.as_deref()?),
+            _ => None,
+        }
+    }
+
+    /// The schema of the property `key` of objects of `schema`, looked up in the
+    /// members of `allOf`, `oneOf` and `anyOf` too.
+    fn property(&self, schema: &'a Schema, key: &str) -> Option<&'a Schema> {
+        let mut out = IndexMap::new();
+        collect_properties(self.view, schema, &mut out, 0);
+        self.resolve(out.get(key)?)
+    }
+}
+
+fn qualified(xml: Option<&Xml>, name: &str) -> String {
+    match xml.and_then(|xml| xml.prefix.as_deref()) {
+        Some(prefix) => format!("{}:{}", prefix, name),
+        None => name.to_string(),
+    }
+}
+
+/// `s` with the characters XML reserves replaced by entities, quotes only in
+/// attribute values.
+fn escape(s: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_generate_xml_example() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "xml": {"namespace": "https://example.com/schema", "prefix": "ex"},
+                    "properties": {
+                        "id": {"type": "integer", "xml": {"attribute": true}},
+                        "name": {"type": "string", "example": "R&B", "xml": {"name": "fullName"}},
+                        "tags": {"type": "array", "xml": {"wrapped": true}, "items": {"type": "string", "xml": {"name": "tag"}}},
+                        "photos": {"type": "array", "items": {"type": "string"}},
+                        "owner": {"$ref": "#/components/schemas/Owner"}
+                    }
+                },
+                "Owner": {"allOf": [{"type": "object", "properties": {"nick": {"type": "string", "xml": {"attribute": true}}}}]}
+            }}
+        })).unwrap();
+        let pet = spec.schemas["Pet"].as_item().unwrap();
+        let xml = pet.generate_xml_example(&spec, &ExampleOptions::default());
+        assert_eq!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>
+<ex:Pet xmlns:ex="https://example.com/schema" id="0">
+  <fullName>R&amp;B</fullName>
+  <tags>
+    <tag>string</tag>
+  </tags>
+  <photos>string</photos>
+  <owner nick="string"/>
+</ex:Pet>
+"#);
+        let inline: Schema = serde_json::from_value(json!({"type": "string"})).unwrap();
+        assert_eq!(inline.generate_xml_example(&spec, &ExampleOptions::default()), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root>string</root>\n");
+        let list: Schema = serde_json::from_value(json!({"type": "array", "xml": {"name": "pets"}, "items": {"type": "integer"}})).unwrap();
+        assert_eq!(list.generate_xml_example(&spec, &ExampleOptions::default()), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<pets>\n  <pets>0</pets>\n</pets>\n");
+        let XmlContent::Items(item) = list.xml_layout(&spec, "root").content else { panic!() };
+        assert_eq!((item.name.as_str(), item.repeated), ("pets", true));
+    }
+
+    #[test]
+    fn test_xml_layout() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "t", "version": "1"},
+            "paths": {},
+            "components": {"schemas": {
+                "Pet": {
+                    "type": "object",
+                    "xml": {"namespace": "https://example.com/schema", "prefix": "ex"},
+                    "properties": {
+                        "id": {"type": "integer", "xml": {"attribute": true, "name": "key"}},
+                        "tags": {"type": "array", "xml": {"wrapped": true}, "items": {"type": "string", "xml": {"name": "tag"}}},
+                        "photos": {"type": "array", "items": {"type": "string"}},
+                        "parent": {"$ref": "#/components/schemas/Pet"}
+                    }
+                }
+            }}
+        })).unwrap();
+        let layout = spec.schemas["Pet"].as_item().unwrap().xml_layout(&spec, "Pet");
+        assert_eq!(layout.qualified_name(), "ex:Pet");
+        assert_eq!(layout.namespace.as_deref(), Some("https://example.com/schema"));
+        assert_eq!(layout.attributes.iter().map(|a| (a.property.as_str(), a.name.as_str())).collect::<Vec<_>>(), [("id", "key")]);
+        let XmlContent::Elements(elements) = &layout.content else { panic!() };
+        assert_eq!(elements.keys().collect::<Vec<_>>(), ["tags", "photos", "parent"]);
+        let XmlContent::Items(tag) = &elements["tags"].content else { panic!() };
+        assert_eq!((tag.name.as_str(), tag.repeated, &tag.content), ("tag", true, &XmlContent::Text));
+        assert_eq!((elements["photos"].name.as_str(), elements["photos"].repeated), ("photos", true));
+        let parent = &elements["parent"];
+        assert_eq!(parent.qualified_name(), "ex:parent");
+        let XmlContent::Elements(nested) = &parent.content else { panic!() };
+        assert_eq!(nested["parent"].content, XmlContent::Recursive("#/components/schemas/Pet".to_string()));
+    }
+}
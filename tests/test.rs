@@ -107,6 +107,11 @@ static TEST_CASES: &[(FileType, &str, &str)] = &[
         "stripe.yaml",
         include_str!("../fixtures/stripe.yaml"),
     ),
+    (
+        FileType::JSON,
+        "boolean-schemas.json",
+        include_str!("../fixtures/boolean-schemas.json"),
+    ),
 ];
 
 #[test]
@@ -123,6 +128,15 @@ fn run_tests() {
             serde_yaml::to_string(&openapi).expect(&format!("Could not serialize YAML {}", name));
         let _json =
             serde_json::to_string(&openapi).expect(&format!("Could not serialize JSON {}", name));
+        let options = SerializeOptions { format: Format::Yaml, ..SerializeOptions::default() };
+        let yaml = openapi.to_string_with(&options).unwrap();
+        let reparsed: OpenAPI = serde_yaml::from_str(&yaml)
+            .unwrap_or_else(|e| panic!("Could not reparse emitted YAML {}: {}", name, e));
+        assert_eq!(reparsed, openapi, "Emitted YAML for {} does not round-trip", name);
+        let compact = openapi.to_string_with(&SerializeOptions::compact()).unwrap();
+        let reparsed: OpenAPI = serde_json::from_str(&compact)
+            .unwrap_or_else(|e| panic!("Could not reparse compact JSON {}: {}", name, e));
+        assert_eq!(reparsed, openapi, "Compact JSON for {} does not round-trip", name);
     }
 }
 
@@ -349,4 +363,30 @@ fn test_nested_refs_circular_panics() {
     let api: OpenAPI = serde_yaml::from_str(s).expect("Could not deserialize file");
     let s: RefOr<Schema> = RefOr::schema_ref("UserId");
     s.resolve(&api);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_boolean_schemas() {
+    let api = OpenAPI::from_str_format(include_str!("../fixtures/boolean-schemas.json"), Format::Json).unwrap();
+    let schemas = &api.components.schemas;
+    let nothing = Schema {
+        data: SchemaData::default(),
+        kind: SchemaKind::Not { not: Box::new(RefOr::Item(Schema::new_any())) },
+    };
+    let point = schemas["Point"].as_item().unwrap();
+    let SchemaKind::Type(Type::Array(array)) = &point.kind else { panic!("{:?}", point.kind) };
+    assert_eq!(point.prefix_items.len(), 2);
+    assert_eq!(array.items.as_deref(), Some(&RefOr::Item(nothing.clone())));
+    let tagged = schemas["Tagged"].as_item().unwrap();
+    assert_eq!(tagged.properties()["tag"], RefOr::Item(Schema::new_any()));
+    assert_eq!(tagged.properties()["removed"], RefOr::Item(nothing.clone()));
+    assert_eq!(schemas["Anything"], RefOr::Item(Schema::new_any()));
+    let SchemaKind::Not { not } = &schemas["Nothing"].as_item().unwrap().kind else { panic!() };
+    assert_eq!(**not, RefOr::Item(Schema::new_any()));
+    let conditional = schemas["Conditional"].as_item().unwrap();
+    assert_eq!(conditional.if_.as_deref(), Some(&RefOr::Item(Schema::new_any())));
+    assert_eq!(conditional.else_.as_deref(), Some(&RefOr::Item(nothing)));
+
+    let json = serde_json::to_string(&api).unwrap();
+    assert_eq!(OpenAPI::from_str_format(&json, Format::Json).unwrap(), api);
+}
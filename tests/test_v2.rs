@@ -26,4 +26,64 @@ fn load_swagger_20_and_upgrade() {
     assert!(v3.paths.paths.contains_key("/pets"));
     assert!(v3.paths.paths.contains_key("/pets/{id}"));
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn convert_swagger_20() {
+    use openapiv3::v2::{ConvertError, Swagger};
+    use openapiv3::{OpenAPI, RefOr};
+    use serde_json::json;
+
+    let swagger: Swagger = serde_json::from_value(json!({
+        "swagger": "2.0",
+        "info": {"title": "t", "version": "1"},
+        "host": "api.example.com",
+        "basePath": "/v1",
+        "schemes": ["https", "http"],
+        "consumes": ["application/json"],
+        "paths": {"/pets": {
+            "post": {
+                "parameters": [
+                    {"name": "pet", "in": "body", "required": true, "schema": {"$ref": "#/definitions/Pet"}},
+                    {"name": "dry", "in": "query", "type": "boolean"}
+                ],
+                "responses": {"201": {"description": "created", "schema": {"$ref": "#/definitions/Pet"}}}
+            },
+            "put": {
+                "consumes": ["multipart/form-data"],
+                "parameters": [
+                    {"name": "photo", "in": "formData", "type": "file", "required": true},
+                    {"name": "caption", "in": "formData", "type": "string"}
+                ],
+                "responses": {"default": {"description": "error"}}
+            }
+        }},
+        "definitions": {"Pet": {"type": "object", "properties": {"name": {"type": "string"}}}},
+        "securityDefinitions": {"key": {"type": "apiKey", "name": "X-Key", "in": "header"}}
+    })).unwrap();
+    let spec = OpenAPI::from_v2(swagger.clone()).unwrap();
+    let urls: Vec<&str> = spec.servers.iter().map(|s| s.url.as_str()).collect();
+    assert_eq!(urls, ["https://api.example.com/v1", "http://api.example.com/v1"]);
+    assert!(spec.schemas.contains_key("Pet"));
+    assert!(spec.security_schemes.contains_key("key"));
+
+    let json = serde_json::to_value(&spec).unwrap();
+    let post = &json["paths"]["/pets"]["post"];
+    assert_eq!(post["requestBody"], json!({"content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}}, "required": true}));
+    assert_eq!(post["parameters"][0]["name"], "dry");
+    assert_eq!(post["responses"]["201"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/Pet");
+    let form = &json["paths"]["/pets"]["put"]["requestBody"]["content"]["multipart/form-data"]["schema"];
+    assert_eq!(form["properties"]["photo"], json!({"type": "string", "format": "binary"}));
+    assert_eq!(form["required"], json!(["photo"]));
+    let put = spec.paths.paths["/pets"].as_item().unwrap().put.as_ref().unwrap();
+    assert!(matches!(&put.request_body, Some(RefOr::Item(body)) if body.required));
+
+    let mut future = swagger.clone();
+    future.swagger = "3.0".to_string();
+    assert_eq!(OpenAPI::from_v2(future), Err(ConvertError::Version("3.0".to_string())));
+    let mut bad: Swagger = swagger;
+    let pets = bad.paths.get_mut("/pets").unwrap().post.as_mut().unwrap();
+    let created = pets.responses.swap_remove("201").unwrap();
+    pets.responses.insert("created".to_string(), created);
+    assert_eq!(OpenAPI::from_v2(bad), Err(ConvertError::StatusCode("created".to_string())));
+}